@@ -213,6 +213,8 @@ async fn start_node_impl(
 				network_provider: network.clone(),
 				is_validator: parachain_config.role.is_authority(),
 				enable_http_requests: false,
+				http_policy: parachain_config.offchain_worker.http_policy.clone(),
+				prometheus_registry: prometheus_registry.clone(),
 				custom_extensions: move |_| vec![],
 			})
 			.run(client.clone(), task_manager.spawn_handle())
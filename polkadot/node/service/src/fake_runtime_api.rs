@@ -251,6 +251,28 @@ sp_api::impl_runtime_apis! {
 			unimplemented!()
 		}
 
+		fn submit_report_fork_voting_unsigned_extrinsic(
+			_: beefy_primitives::ForkVotingProof<
+				<Block as BlockT>::Header,
+				BeefyId,
+				BeefySignature,
+			>,
+			_: beefy_primitives::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			unimplemented!()
+		}
+
+		fn submit_report_future_block_voting_unsigned_extrinsic(
+			_: beefy_primitives::FutureBlockVotingProof<
+				BlockNumber,
+				BeefyId,
+				BeefySignature,
+			>,
+			_: beefy_primitives::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			unimplemented!()
+		}
+
 		fn generate_key_ownership_proof(
 			_: beefy_primitives::ValidatorSetId,
 			_: BeefyId,
@@ -929,6 +929,8 @@ pub fn new_full<OverseerGenerator: OverseerGen>(
 				network_provider: network.clone(),
 				is_validator: role.is_authority(),
 				enable_http_requests: false,
+				http_policy: config.offchain_worker.http_policy.clone(),
+				prometheus_registry: prometheus_registry.clone(),
 				custom_extensions: move |_| vec![],
 			})
 			.run(client.clone(), task_manager.spawn_handle())
@@ -18,7 +18,7 @@
 
 use frame_support::traits::Get;
 use frame_system::pallet_prelude::BlockNumberFor;
-use parity_scale_codec::Encode;
+use parity_scale_codec::{Decode, Encode};
 use primitives::Id as ParaId;
 use runtime_parachains::{
 	configuration::{self, HostConfiguration},
@@ -27,6 +27,7 @@ use runtime_parachains::{
 use sp_runtime::FixedPointNumber;
 use sp_std::{marker::PhantomData, prelude::*};
 use xcm::prelude::*;
+use xcm_builder::InspectMessageQueues;
 use SendError::*;
 
 /// Simple value-bearing trait for determining/expressing the assets required to be paid for a
@@ -136,6 +137,28 @@ where
 	}
 }
 
+impl<T: dmp::Config, W, P> InspectMessageQueues for ChildParachainRouter<T, W, P> {
+	fn clear_messages() {
+		// Only ever called when dry-running, so the queues are never really cleared.
+		let _ = dmp::DownwardMessageQueues::<T>::clear(u32::MAX, None);
+	}
+
+	fn get_messages() -> Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)> {
+		dmp::DownwardMessageQueues::<T>::iter()
+			.map(|(para_id, messages)| {
+				let decoded_messages: Vec<VersionedXcm<()>> = messages
+					.iter()
+					.filter_map(|downward_message| {
+						VersionedXcm::<()>::decode(&mut &downward_message.msg[..]).ok()
+					})
+					.collect();
+				let destination: MultiLocation = Parachain(para_id.into()).into();
+				(destination.into_versioned(), decoded_messages)
+			})
+			.collect()
+	}
+}
+
 /// Implementation of `pallet_xcm_benchmarks::EnsureDelivery` which helps to ensure delivery to the
 /// `ParaId` parachain (sibling or child). Deposits existential deposit for origin (if needed).
 /// Deposits estimated fee to the origin account (if needed).
@@ -96,10 +96,14 @@ use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 use sp_version::NativeVersion;
 use sp_version::RuntimeVersion;
 use xcm::{
-	latest::{InteriorMultiLocation, Junction, Junction::PalletInstance},
-	VersionedMultiLocation,
+	latest::{AssetId, InteriorMultiLocation, Junction, Junction::PalletInstance},
+	IntoVersion, VersionedAssetId, VersionedMultiAssets, VersionedMultiLocation, VersionedXcm,
 };
 use xcm_builder::PayOverXcm;
+use xcm_runtime_apis::{
+	dry_run::{CallDryRunEffects, Error as XcmDryRunApiError, XcmDryRunEffects},
+	fees::Error as XcmPaymentApiError,
+};
 
 pub use frame_system::Call as SystemCall;
 pub use pallet_balances::Call as BalancesCall;
@@ -1965,6 +1969,38 @@ sp_api::impl_runtime_apis! {
 			)
 		}
 
+		fn submit_report_fork_voting_unsigned_extrinsic(
+			fork_voting_proof: beefy_primitives::ForkVotingProof<
+				<Block as BlockT>::Header,
+				BeefyId,
+				BeefySignature,
+			>,
+			key_owner_proof: beefy_primitives::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			let key_owner_proof = key_owner_proof.decode()?;
+
+			Beefy::submit_unsigned_fork_voting_report(
+				fork_voting_proof,
+				key_owner_proof,
+			)
+		}
+
+		fn submit_report_future_block_voting_unsigned_extrinsic(
+			future_block_voting_proof: beefy_primitives::FutureBlockVotingProof<
+				BlockNumber,
+				BeefyId,
+				BeefySignature,
+			>,
+			key_owner_proof: beefy_primitives::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			let key_owner_proof = key_owner_proof.decode()?;
+
+			Beefy::submit_unsigned_future_block_voting_report(
+				future_block_voting_proof,
+				key_owner_proof,
+			)
+		}
+
 		fn generate_key_ownership_proof(
 			_set_id: beefy_primitives::ValidatorSetId,
 			authority_id: BeefyId,
@@ -2205,6 +2241,49 @@ sp_api::impl_runtime_apis! {
 		}
 	}
 
+	impl xcm_runtime_apis::fees::XcmPaymentApi<Block> for Runtime {
+		fn query_acceptable_payment_assets(xcm_version: xcm::Version) -> Result<Vec<VersionedAssetId>, XcmPaymentApiError> {
+			let acceptable_assets = vec![AssetId::Concrete(xcm_config::TokenLocation::get())];
+			acceptable_assets
+				.into_iter()
+				.map(|asset| VersionedAssetId::from(asset).into_version(xcm_version))
+				.collect::<Result<Vec<_>, _>>()
+				.map_err(|()| XcmPaymentApiError::VersionedConversionFailed)
+		}
+
+		fn query_weight_to_asset_fee(weight: Weight, asset: VersionedAssetId) -> Result<u128, XcmPaymentApiError> {
+			let asset = AssetId::try_from(asset).map_err(|()| XcmPaymentApiError::VersionedConversionFailed)?;
+			match asset {
+				AssetId::Concrete(location) if location == xcm_config::TokenLocation::get() => {
+					// For the native token, the weight is paid the same way extrinsics are.
+					Ok(<WeightToFee as frame_support::weights::WeightToFee>::weight_to_fee(&weight))
+				},
+				_ => {
+					log::trace!(target: "xcm::XcmPaymentApi::query_weight_to_asset_fee", "query_weight_to_asset_fee - unhandled asset: {:?}!", asset);
+					Err(XcmPaymentApiError::AssetNotFound)
+				},
+			}
+		}
+
+		fn query_xcm_weight(message: VersionedXcm<()>) -> Result<Weight, XcmPaymentApiError> {
+			XcmPallet::query_xcm_weight(message)
+		}
+
+		fn query_delivery_fees(destination: VersionedMultiLocation, message: VersionedXcm<()>) -> Result<VersionedMultiAssets, XcmPaymentApiError> {
+			XcmPallet::query_delivery_fees(destination, message)
+		}
+	}
+
+	impl xcm_runtime_apis::dry_run::XcmDryRunApi<Block, RuntimeCall, RuntimeEvent, OriginCaller> for Runtime {
+		fn dry_run_call(origin: OriginCaller, call: RuntimeCall) -> Result<CallDryRunEffects<RuntimeEvent>, XcmDryRunApiError> {
+			XcmPallet::dry_run_call::<xcm_config::XcmRouter, _>(origin, call)
+		}
+
+		fn dry_run_xcm(origin_location: VersionedMultiLocation, xcm: VersionedXcm<RuntimeCall>) -> Result<XcmDryRunEffects<RuntimeEvent>, XcmDryRunApiError> {
+			XcmPallet::dry_run_xcm::<xcm_config::XcmRouter>(origin_location, xcm)
+		}
+	}
+
 	#[cfg(feature = "try-runtime")]
 	impl frame_try_runtime::TryRuntime<Block> for Runtime {
 		fn on_runtime_upgrade(checks: frame_try_runtime::UpgradeCheckSelect) -> (Weight, Weight) {
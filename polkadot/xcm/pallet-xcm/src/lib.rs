@@ -50,8 +50,8 @@ use sp_runtime::{
 use sp_std::{boxed::Box, marker::PhantomData, prelude::*, result::Result, vec};
 use xcm::{latest::QueryResponseInfo, prelude::*};
 use xcm_builder::{
-	ExecuteController, ExecuteControllerWeightInfo, QueryController, QueryControllerWeightInfo,
-	SendController, SendControllerWeightInfo,
+	ExecuteController, ExecuteControllerWeightInfo, InspectMessageQueues, QueryController,
+	QueryControllerWeightInfo, SendController, SendControllerWeightInfo,
 };
 use xcm_executor::{
 	traits::{
@@ -61,6 +61,10 @@ use xcm_executor::{
 	},
 	Assets,
 };
+use xcm_runtime_apis::{
+	dry_run::{CallDryRunEffects, Error as XcmDryRunApiError, XcmDryRunEffects},
+	fees::Error as XcmPaymentApiError,
+};
 
 pub trait WeightInfo {
 	fn send() -> Weight;
@@ -2282,6 +2286,113 @@ impl<T: Config> Pallet<T> {
 		T::XcmRouter::deliver(ticket)
 	}
 
+	/// Dry-run `call` dispatched from `origin`, for implementing the `XcmDryRunApi` runtime API.
+	///
+	/// The messages queued by `Router` while dispatching are reported instead of relying on them
+	/// being delivered; `Router` is usually the runtime's `XcmRouter`. Nothing done here is
+	/// persisted, since runtime APIs are executed on a throw-away overlay of the state.
+	pub fn dry_run_call<Router, OriginCaller>(
+		origin: OriginCaller,
+		call: <T as Config>::RuntimeCall,
+	) -> Result<CallDryRunEffects<<T as frame_system::Config>::RuntimeEvent>, XcmDryRunApiError>
+	where
+		Router: InspectMessageQueues,
+		<T as Config>::RuntimeOrigin: From<OriginCaller>,
+	{
+		// Only report what `call` does, not what happened earlier in the block.
+		frame_system::Pallet::<T>::reset_events();
+		Router::clear_messages();
+		let execution_result = call.dispatch(origin.into());
+		let emitted_events = frame_system::Pallet::<T>::read_events_no_consensus()
+			.map(|record| record.event.clone())
+			.collect();
+		let forwarded_xcms = Router::get_messages();
+		Ok(CallDryRunEffects { execution_result, emitted_events, forwarded_xcms })
+	}
+
+	/// Dry-run executing `xcm` from `origin_location`, for implementing the `XcmDryRunApi`
+	/// runtime API.
+	///
+	/// The program is executed by the configured `XcmExecutor` without any weight limit and
+	/// without any weight credit, so barriers see it exactly as they would see an incoming
+	/// message. See [`Self::dry_run_call`] for the meaning of `Router`.
+	pub fn dry_run_xcm<Router>(
+		origin_location: VersionedMultiLocation,
+		xcm: VersionedXcm<<T as Config>::RuntimeCall>,
+	) -> Result<XcmDryRunEffects<<T as frame_system::Config>::RuntimeEvent>, XcmDryRunApiError>
+	where
+		Router: InspectMessageQueues,
+	{
+		let origin_location: MultiLocation = origin_location.try_into().map_err(|error| {
+			log::error!(
+				target: "xcm::pallet_xcm::dry_run_xcm",
+				"Location version conversion failed with error: {:?}",
+				error,
+			);
+			XcmDryRunApiError::VersionedConversionFailed
+		})?;
+		let xcm: Xcm<<T as Config>::RuntimeCall> = xcm.try_into().map_err(|error| {
+			log::error!(
+				target: "xcm::pallet_xcm::dry_run_xcm",
+				"Xcm version conversion failed with error {:?}",
+				error,
+			);
+			XcmDryRunApiError::VersionedConversionFailed
+		})?;
+		let mut hash = xcm.using_encoded(sp_io::hashing::blake2_256);
+		frame_system::Pallet::<T>::reset_events();
+		Router::clear_messages();
+		let execution_result = T::XcmExecutor::prepare_and_execute(
+			origin_location,
+			xcm,
+			&mut hash,
+			Weight::MAX,
+			Weight::zero(),
+		);
+		let emitted_events = frame_system::Pallet::<T>::read_events_no_consensus()
+			.map(|record| record.event.clone())
+			.collect();
+		let forwarded_xcms = Router::get_messages();
+		Ok(XcmDryRunEffects { execution_result, emitted_events, forwarded_xcms })
+	}
+
+	/// Weight of executing `message` locally, for implementing the `XcmPaymentApi` runtime API.
+	///
+	/// Uses the configured `Weigher`, so it is exactly the weight `execute` would be charged for.
+	pub fn query_xcm_weight(message: VersionedXcm<()>) -> Result<Weight, XcmPaymentApiError> {
+		let message = Xcm::<()>::try_from(message)
+			.map_err(|_| XcmPaymentApiError::VersionedConversionFailed)?;
+
+		T::Weigher::weight(&mut message.into()).map_err(|()| {
+			log::error!(target: "xcm::pallet_xcm::query_xcm_weight", "Error when querying XCM weight");
+			XcmPaymentApiError::WeightNotComputable
+		})
+	}
+
+	/// Fees charged by `XcmRouter` for delivering `message` to `destination`, for implementing
+	/// the `XcmPaymentApi` runtime API.
+	///
+	/// This is the price returned by the router when validating the message, so it is what
+	/// sending it from this chain would cost right now.
+	pub fn query_delivery_fees(
+		destination: VersionedMultiLocation,
+		message: VersionedXcm<()>,
+	) -> Result<VersionedMultiAssets, XcmPaymentApiError> {
+		let destination = destination
+			.try_into()
+			.map_err(|_| XcmPaymentApiError::VersionedConversionFailed)?;
+
+		let message =
+			message.try_into().map_err(|_| XcmPaymentApiError::VersionedConversionFailed)?;
+
+		let (_, fees) = validate_send::<T::XcmRouter>(destination, message).map_err(|error| {
+			log::error!(target: "xcm::pallet_xcm::query_delivery_fees", "Error when querying delivery fees: {:?}", error);
+			XcmPaymentApiError::Unroutable
+		})?;
+
+		Ok(VersionedMultiAssets::from(fees))
+	}
+
 	pub fn check_account() -> T::AccountId {
 		const ID: PalletId = PalletId(*b"py/xcmch");
 		AccountIdConversion::<T::AccountId>::into_account_truncating(&ID)
@@ -36,9 +36,10 @@ use xcm_builder::{
 	AccountId32Aliases, AllowKnownQueryResponses, AllowSubscriptionsFrom,
 	AllowTopLevelPaidExecutionFrom, Case, ChildParachainAsNative, ChildParachainConvertsVia,
 	ChildSystemParachainAsSuperuser, CurrencyAdapter as XcmCurrencyAdapter, DescribeAllTerminal,
-	FixedRateOfFungible, FixedWeightBounds, FungiblesAdapter, HashedDescription, IsConcrete,
-	MatchedConvertedConcreteId, NoChecking, SignedAccountId32AsNative, SignedToAccountId32,
-	SovereignSignedViaLocation, TakeWeightCredit, XcmFeeManagerFromComponents, XcmFeeToAccount,
+	FixedRateOfFungible, FixedWeightBounds, FungiblesAdapter, HashedDescription,
+	InspectMessageQueues, IsConcrete, MatchedConvertedConcreteId, NoChecking,
+	SignedAccountId32AsNative, SignedToAccountId32, SovereignSignedViaLocation, TakeWeightCredit,
+	XcmFeeManagerFromComponents, XcmFeeToAccount,
 };
 use xcm_executor::{
 	traits::{Identity, JustTry},
@@ -242,6 +243,27 @@ impl SendXcm for TestPaidForPara3000SendXcm {
 	}
 }
 
+/// Inspects the messages delivered by any of the test senders, since they all share `SENT_XCM`.
+pub struct TestMessageQueues;
+impl InspectMessageQueues for TestMessageQueues {
+	fn clear_messages() {
+		take_sent_xcm();
+	}
+
+	fn get_messages() -> Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)> {
+		let mut messages: Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)> = Vec::new();
+		for (dest, message) in sent_xcm() {
+			let dest = VersionedMultiLocation::from(dest);
+			let message = VersionedXcm::from(message);
+			match messages.iter_mut().find(|(d, _)| d == &dest) {
+				Some((_, queue)) => queue.push(message),
+				None => messages.push((dest, vec![message])),
+			}
+		}
+		messages
+	}
+}
+
 parameter_types! {
 	pub const BlockHashCount: u64 = 250;
 }
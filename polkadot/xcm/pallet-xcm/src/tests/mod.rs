@@ -995,3 +995,117 @@ fn subscription_side_upgrades_work_with_multistage_notify() {
 		);
 	});
 }
+
+/// Test that dry-running a `send` reports the message that would have been sent, without relying
+/// on it being delivered.
+#[test]
+fn dry_run_call_reports_forwarded_xcms() {
+	let balances = vec![(ALICE, INITIAL_BALANCE)];
+	new_test_ext_with_balances(balances).execute_with(|| {
+		let sender: MultiLocation = AccountId32 { network: None, id: ALICE.into() }.into();
+		let message = Xcm(vec![ClearOrigin]);
+		let call = RuntimeCall::XcmPallet(crate::Call::send {
+			dest: Box::new(RelayLocation::get().into()),
+			message: Box::new(VersionedXcm::from(message.clone())),
+		});
+		// Anything queued before the dry-run must not be reported.
+		assert_ok!(XcmPallet::send_xcm(Here, Parachain(OTHER_PARA_ID), Xcm(vec![])));
+
+		let origin = OriginCaller::system(frame_system::RawOrigin::Signed(ALICE));
+		let effects = XcmPallet::dry_run_call::<TestMessageQueues, _>(origin, call).unwrap();
+
+		assert_ok!(effects.execution_result);
+		let sent_message = Xcm::<()>(vec![DescendOrigin(sender.try_into().unwrap()), ClearOrigin]);
+		assert_eq!(
+			effects.forwarded_xcms,
+			vec![(
+				VersionedMultiLocation::from(RelayLocation::get()),
+				vec![VersionedXcm::from(sent_message.clone())],
+			)],
+		);
+		assert_eq!(
+			effects.emitted_events.last(),
+			Some(&RuntimeEvent::XcmPallet(crate::Event::Sent {
+				origin: sender,
+				destination: RelayLocation::get(),
+				message,
+				message_id: fake_message_hash(&sent_message),
+			})),
+		);
+	});
+}
+
+/// Test that dry-running an XCM program reports its outcome and the messages it forwards.
+#[test]
+fn dry_run_xcm_reports_outcome_and_forwarded_xcms() {
+	let balances = vec![
+		(ALICE, INITIAL_BALANCE),
+		(ParaId::from(OTHER_PARA_ID).into_account_truncating(), INITIAL_BALANCE),
+	];
+	new_test_ext_with_balances(balances).execute_with(|| {
+		let origin_location: MultiLocation = AccountId32 { network: None, id: ALICE.into() }.into();
+		let dest: MultiLocation = Parachain(OTHER_PARA_ID).into();
+		let xcm = Xcm::<RuntimeCall>(vec![
+			WithdrawAsset((Here, SEND_AMOUNT).into()),
+			buy_execution((Here, SEND_AMOUNT)),
+			DepositReserveAsset { assets: AllCounted(1).into(), dest, xcm: Xcm(vec![]) },
+		]);
+
+		let effects = XcmPallet::dry_run_xcm::<TestMessageQueues>(
+			origin_location.into_versioned(),
+			VersionedXcm::from(xcm),
+		)
+		.unwrap();
+
+		assert_eq!(effects.execution_result, Outcome::Complete(BaseXcmWeight::get() * 3));
+		assert_eq!(
+			effects.forwarded_xcms,
+			vec![(
+				dest.into_versioned(),
+				vec![VersionedXcm::from(Xcm::<()>(vec![
+					ReserveAssetDeposited((Parent, SEND_AMOUNT).into()),
+					ClearOrigin,
+				]))],
+			)],
+		);
+		assert_eq!(Balances::total_balance(&ALICE), INITIAL_BALANCE - SEND_AMOUNT);
+	});
+}
+
+/// Test that the XCM weight is reported as computed by the configured `Weigher`.
+#[test]
+fn query_xcm_weight_works() {
+	new_test_ext_with_balances(vec![]).execute_with(|| {
+		let message = Xcm::<()>(vec![
+			WithdrawAsset((Here, SEND_AMOUNT).into()),
+			buy_execution((Here, SEND_AMOUNT)),
+			DepositAsset { assets: AllCounted(1).into(), beneficiary: Here.into() },
+		]);
+		assert_eq!(
+			XcmPallet::query_xcm_weight(VersionedXcm::from(message)),
+			Ok(BaseXcmWeight::get() * 3),
+		);
+	});
+}
+
+/// Test that delivery fees are the price returned by the router for the given destination.
+#[test]
+fn query_delivery_fees_works() {
+	new_test_ext_with_balances(vec![]).execute_with(|| {
+		let message = VersionedXcm::from(Xcm::<()>(vec![ClearOrigin]));
+
+		// `Parachain(3000)` is routed through a sender which charges for delivery.
+		assert_eq!(
+			XcmPallet::query_delivery_fees(
+				Para3000Location::get().into_versioned(),
+				message.clone()
+			),
+			Ok(VersionedMultiAssets::from(Para3000PaymentMultiAssets::get())),
+		);
+		// Other destinations are free.
+		assert_eq!(
+			XcmPallet::query_delivery_fees(RelayLocation::get().into_versioned(), message),
+			Ok(VersionedMultiAssets::from(MultiAssets::new())),
+		);
+	});
+}
@@ -101,7 +101,7 @@ mod filter_asset_location;
 pub use filter_asset_location::{AllAssets, Case, LocationWithAssetFilters, NativeAsset};
 
 mod routing;
-pub use routing::{WithTopicSource, WithUniqueTopic};
+pub use routing::{InspectMessageQueues, WithTopicSource, WithUniqueTopic};
 
 mod universal_exports;
 pub use universal_exports::{
@@ -18,7 +18,7 @@
 
 use frame_system::unique;
 use parity_scale_codec::Encode;
-use sp_std::{marker::PhantomData, result::Result};
+use sp_std::{marker::PhantomData, result::Result, vec::Vec};
 use xcm::prelude::*;
 
 /// Wrapper router which, if the message does not already end with a `SetTopic` instruction,
@@ -59,6 +59,15 @@ impl<Inner: SendXcm> SendXcm for WithUniqueTopic<Inner> {
 		Ok(unique_id)
 	}
 }
+impl<Inner: InspectMessageQueues> InspectMessageQueues for WithUniqueTopic<Inner> {
+	fn clear_messages() {
+		Inner::clear_messages()
+	}
+
+	fn get_messages() -> Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)> {
+		Inner::get_messages()
+	}
+}
 
 pub trait SourceTopic {
 	fn source_topic(entropy: impl Encode) -> XcmHash;
@@ -104,3 +113,40 @@ impl<Inner: SendXcm, TopicSource: SourceTopic> SendXcm for WithTopicSource<Inner
 		Ok(unique_id)
 	}
 }
+impl<Inner: InspectMessageQueues, TopicSource> InspectMessageQueues
+	for WithTopicSource<Inner, TopicSource>
+{
+	fn clear_messages() {
+		Inner::clear_messages()
+	}
+
+	fn get_messages() -> Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)> {
+		Inner::get_messages()
+	}
+}
+
+/// Trait for a type which can inspect the messages which a router has queued for delivery.
+///
+/// This is used when dry-running extrinsics or XCM programs: routers keep the messages they
+/// deliver in some queue (usually in storage) until the transport takes them out, so a dry-run
+/// can look them up instead of actually sending anything.
+pub trait InspectMessageQueues {
+	/// Clear the messages in the queues, so that only messages produced afterwards are reported.
+	fn clear_messages();
+
+	/// Get the messages currently queued, grouped by destination.
+	fn get_messages() -> Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)>;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl InspectMessageQueues for Tuple {
+	fn clear_messages() {
+		for_tuples!( #( Tuple::clear_messages(); )* );
+	}
+
+	fn get_messages() -> Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)> {
+		let mut messages = Vec::new();
+		for_tuples!( #( messages.append(&mut Tuple::get_messages()); )* );
+		messages
+	}
+}
@@ -0,0 +1,84 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API definition for dry-running XCM-related extrinsics.
+//! This API can be used to simulate XCMs and, for example, find the fees
+//! that need to be paid.
+
+use codec::{Decode, Encode};
+use frame_support::pallet_prelude::{DispatchResultWithPostInfo, TypeInfo};
+use sp_std::vec::Vec;
+use xcm::prelude::*;
+
+/// Effects of dry-running an extrinsic.
+#[derive(Encode, Decode, Debug, TypeInfo)]
+pub struct CallDryRunEffects<Event> {
+	/// The result of executing the extrinsic.
+	pub execution_result: DispatchResultWithPostInfo,
+	/// The list of events fired by the extrinsic.
+	pub emitted_events: Vec<Event>,
+	/// The list of XCMs that were queued for sending, grouped by destination.
+	pub forwarded_xcms: Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)>,
+}
+
+/// Effects of dry-running an XCM program.
+#[derive(Encode, Decode, Debug, TypeInfo)]
+pub struct XcmDryRunEffects<Event> {
+	/// The outcome of the XCM program execution.
+	pub execution_result: Outcome,
+	/// List of events fired by the XCM program execution.
+	pub emitted_events: Vec<Event>,
+	/// List of queued messages for sending, grouped by destination.
+	pub forwarded_xcms: Vec<(VersionedMultiLocation, Vec<VersionedXcm<()>>)>,
+}
+
+sp_api::decl_runtime_apis! {
+	/// API for dry-running extrinsics and XCM programs to get the programs that need to be passed
+	/// to the fees API.
+	///
+	/// All calls return a vector of tuples (location, xcm) where each "xcm" is executed in
+	/// "location". If there's local execution, the location will be "Here". This vector can be
+	/// used to calculate both execution and delivery fees.
+	///
+	/// Nothing is committed to state: every call runs on top of the state of the block it is
+	/// invoked at, and all changes are discarded once the call returns.
+	pub trait XcmDryRunApi<Call, Event, OriginCaller>
+	where
+		Call: Encode,
+		Event: Decode,
+		OriginCaller: Encode,
+	{
+		/// Dry run call.
+		fn dry_run_call(origin: OriginCaller, call: Call) -> Result<CallDryRunEffects<Event>, Error>;
+
+		/// Dry run XCM program.
+		fn dry_run_xcm(
+			origin_location: VersionedMultiLocation,
+			xcm: VersionedXcm<Call>,
+		) -> Result<XcmDryRunEffects<Event>, Error>;
+	}
+}
+
+/// Errors that can be returned by [`XcmDryRunApi`].
+#[derive(Copy, Clone, Encode, Decode, Eq, PartialEq, Debug, TypeInfo)]
+pub enum Error {
+	/// An API call is unsupported.
+	#[codec(index = 0)]
+	Unimplemented,
+
+	/// Converting a versioned data structure from one version to another failed.
+	#[codec(index = 1)]
+	VersionedConversionFailed,
+}
@@ -0,0 +1,26 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime APIs that let clients inspect what an XCM-related extrinsic or a raw XCM program
+//! would do, before actually submitting it on-chain.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Dry-run API.
+/// Given an extrinsic or an XCM program, it returns the outcome of its execution.
+pub mod dry_run;
+/// Fee estimation API.
+/// Given an XCM program, it will return the fees needed to execute it properly or send it.
+pub mod fees;
@@ -147,6 +147,8 @@ pub fn new_full(config: Configuration, consensus: Consensus) -> Result<TaskManag
 				)),
 				network_provider: network.clone(),
 				enable_http_requests: true,
+				http_policy: config.offchain_worker.http_policy.clone(),
+				prometheus_registry: config.prometheus_registry().cloned(),
 				custom_extensions: |_| vec![],
 			})
 			.run(client.clone(), task_manager.spawn_handle())
@@ -197,6 +199,7 @@ pub fn new_full(config: Configuration, consensus: Consensus) -> Result<TaskManag
 				env: proposer,
 				client,
 				pool: transaction_pool,
+				commands_stream: None,
 				select_chain,
 				consensus_data_provider: None,
 				create_inherent_data_providers: move |_, ()| async move {
@@ -25,15 +25,30 @@ pub enum Subcommand {
 	/// Export blocks.
 	ExportBlocks(sc_cli::ExportBlocksCmd),
 
+	/// Export archived blocks, in the format read by `import-blocks --binary`.
+	ExportArchivedBlocks(sc_cli::ExportArchivedBlocksCmd),
+
 	/// Export the state of a given block into a chain spec.
 	ExportState(sc_cli::ExportStateCmd),
 
+	/// Export the state of a finalized block into a snapshot.
+	ExportStateSnapshot(sc_cli::ExportStateSnapshotCmd),
+
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
+	/// Bootstrap a fresh database from a state snapshot.
+	ImportStateSnapshot(sc_cli::ImportStateSnapshotCmd),
+
+	/// Copy the database into a new database using the other backend.
+	MigrateDb(sc_cli::MigrateDbCmd),
+
 	/// Remove the whole chain.
 	PurgeChain(sc_cli::PurgeChainCmd),
 
+	/// Run the offchain workers of a block in isolation and report what they did.
+	ReplayOffchainWorker(sc_cli::ReplayOffchainWorkerCmd),
+
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
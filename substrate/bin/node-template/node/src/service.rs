@@ -182,6 +182,8 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 				)),
 				network_provider: network.clone(),
 				enable_http_requests: true,
+				http_policy: config.offchain_worker.http_policy.clone(),
+				prometheus_registry: config.prometheus_registry().cloned(),
 				custom_extensions: |_| vec![],
 			})
 			.run(client.clone(), task_manager.spawn_handle())
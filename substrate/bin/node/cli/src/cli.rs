@@ -88,18 +88,74 @@ pub enum Subcommand {
 	/// Export blocks.
 	ExportBlocks(sc_cli::ExportBlocksCmd),
 
+	/// Export archived blocks, in the format read by `import-blocks --binary`.
+	ExportArchivedBlocks(sc_cli::ExportArchivedBlocksCmd),
+
 	/// Export the state of a given block into a chain spec.
 	ExportState(sc_cli::ExportStateCmd),
 
+	/// Export the state of a finalized block into a snapshot.
+	ExportStateSnapshot(sc_cli::ExportStateSnapshotCmd),
+
+	/// Look for GRANDPA equivocations in the votes and justifications known to the node.
+	GrandpaFisherman(GrandpaFishermanCmd),
+
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
+	/// Bootstrap a fresh database from a state snapshot.
+	ImportStateSnapshot(sc_cli::ImportStateSnapshotCmd),
+
+	/// Copy the database into a new database using the other backend.
+	MigrateDb(sc_cli::MigrateDbCmd),
+
 	/// Remove the whole chain.
 	PurgeChain(sc_cli::PurgeChainCmd),
 
+	/// Run the offchain workers of a block in isolation and report what they did.
+	ReplayOffchainWorker(sc_cli::ReplayOffchainWorkerCmd),
+
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
 }
+
+/// The `grandpa-fisherman` command used to look for GRANDPA equivocations in the votes and
+/// justifications known to the node.
+///
+/// The equivocations are written to a JSON report, along with the key ownership proofs of the
+/// offenders, ready to be submitted to the runtime. Nothing is submitted by the command itself.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct GrandpaFishermanCmd {
+	/// First block of the range scanned for justifications.
+	#[arg(long, value_name = "NUMBER", default_value = "0")]
+	pub from: sc_cli::GenericNumber,
+
+	/// Last block of the range scanned for justifications.
+	/// Default is the last finalized block.
+	#[arg(long, value_name = "NUMBER")]
+	pub to: Option<sc_cli::GenericNumber>,
+
+	/// Also scan the evidence in the given files, SCALE encoded lists of votes and
+	/// justifications, e.g. collected by other nodes.
+	#[arg(long, value_name = "PATH")]
+	pub evidence: Vec<std::path::PathBuf>,
+
+	/// Output file name of the report or stdout if unspecified.
+	#[arg(long, short, value_name = "PATH")]
+	pub output: Option<std::path::PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: sc_cli::PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: sc_cli::DatabaseParams,
+}
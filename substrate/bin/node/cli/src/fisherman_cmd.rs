@@ -0,0 +1,164 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `grandpa-fisherman` command, running a [`Fisherman`] over the database of the node.
+
+use crate::{service::FullClient, GrandpaFishermanCmd};
+use codec::{Decode, Encode};
+use grandpa::{Evidence, Fisherman, JustificationTarget, SharedAuthoritySet};
+use log::{info, warn};
+use node_primitives::{Block, BlockNumber, Hash};
+use sc_cli::{CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::HeaderBackend;
+use serde_json::{json, Value};
+use sp_core::{bytes::to_hex, crypto::ByteArray};
+use std::{
+	fs,
+	io::{self, BufWriter},
+	sync::Arc,
+};
+
+const LOG_TARGET: &str = "grandpa";
+
+impl GrandpaFishermanCmd {
+	/// Run the grandpa-fisherman command
+	pub async fn run(
+		&self,
+		client: Arc<FullClient>,
+		authority_set: SharedAuthoritySet<Hash, BlockNumber>,
+	) -> sc_cli::Result<()> {
+		let from: BlockNumber = self.from.parse()?;
+		let to = match &self.to {
+			Some(to) => to.parse()?,
+			None => client.info().finalized_number,
+		};
+
+		let application_error = |e: grandpa::Error| sc_cli::Error::Application(Box::new(e));
+		let mut fisherman = Fisherman::new(client, authority_set);
+
+		info!(target: LOG_TARGET, "Scanning the justifications of blocks #{} to #{}", from, to);
+		fisherman.scan_justifications(from, to).map_err(application_error)?;
+		fisherman.scan_voter_rounds().map_err(application_error)?;
+
+		for path in &self.evidence {
+			let evidence =
+				Vec::<Evidence<Block>>::decode(&mut &fs::read(path)?[..]).map_err(|e| {
+					sc_cli::Error::Input(format!("Invalid evidence {}: {}", path.display(), e))
+				})?;
+			info!(
+				target: LOG_TARGET,
+				"Scanning {} pieces of evidence from {}",
+				evidence.len(),
+				path.display()
+			);
+			for evidence in evidence {
+				fisherman.import_evidence(evidence);
+			}
+		}
+
+		let equivocations = fisherman
+			.equivocations()
+			.iter()
+			.map(|proof| {
+				let offender = to_hex(proof.offender().as_slice(), false);
+				// A missing proof only prevents reporting this equivocation, so the error is
+				// part of the report instead of aborting the scan.
+				let (key_owner_proof, key_owner_proof_error) =
+					match fisherman.generate_key_ownership_proof(proof) {
+						Ok(key_owner_proof) =>
+							(key_owner_proof.map(|proof| to_hex(&proof.encode(), false)), None),
+						Err(e) => {
+							warn!(
+								target: LOG_TARGET,
+								"Failed to generate the key ownership proof of {} in round {} of \
+								 set {}: {}",
+								offender,
+								proof.round(),
+								proof.set_id(),
+								e,
+							);
+							(None, Some(e.to_string()))
+						},
+					};
+				json!({
+					"setId": proof.set_id(),
+					"round": proof.round(),
+					"offender": offender,
+					"equivocationProof": to_hex(&proof.encode(), false),
+					"keyOwnerProof": key_owner_proof,
+					"keyOwnerProofError": key_owner_proof_error,
+				})
+			})
+			.collect::<Vec<_>>();
+		let conflicting_justifications = fisherman
+			.conflicting_justifications()
+			.iter()
+			.map(|conflict| {
+				json!({
+					"setId": conflict.set_id,
+					"first": justification_target(&conflict.first),
+					"second": justification_target(&conflict.second),
+				})
+			})
+			.collect::<Vec<_>>();
+
+		info!(
+			target: LOG_TARGET,
+			"Found {} equivocations and {} conflicting justifications",
+			equivocations.len(),
+			conflicting_justifications.len()
+		);
+
+		let report = json!({
+			"from": from.to_string(),
+			"to": to.to_string(),
+			"invalidVotes": fisherman.invalid_votes(),
+			"invalidJustifications": fisherman.invalid_justifications(),
+			"equivocations": equivocations,
+			"conflictingJustifications": conflicting_justifications,
+		});
+		let output: Box<dyn io::Write> = match &self.output {
+			Some(filename) => Box::new(BufWriter::new(fs::File::create(filename)?)),
+			None => Box::new(BufWriter::new(io::stdout())),
+		};
+		serde_json::to_writer_pretty(output, &report).map_err(io::Error::from)?;
+		Ok(())
+	}
+}
+
+fn justification_target(target: &JustificationTarget<Block>) -> Value {
+	json!({
+		"round": target.round,
+		"hash": format!("{:?}", target.hash),
+		"number": target.number.to_string(),
+	})
+}
+
+impl CliConfiguration for GrandpaFishermanCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
@@ -257,6 +257,34 @@ impl pallet_tx_pause::Config for Runtime {
 	type WeightInfo = pallet_tx_pause::weights::SubstrateWeight<Runtime>;
 }
 
+/// Calls that can still be dispatched while multi-block migrations are ongoing.
+pub struct MbmWhitelistedCalls;
+impl Contains<RuntimeCall> for MbmWhitelistedCalls {
+	fn contains(call: &RuntimeCall) -> bool {
+		match call {
+			RuntimeCall::System(_) | RuntimeCall::MultiBlockMigrations(_) => true,
+			_ => false,
+		}
+	}
+}
+
+parameter_types! {
+	pub MbmServiceWeight: Weight = Perbill::from_percent(80) * RuntimeBlockWeights::get().max_block;
+}
+
+impl pallet_migrations::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Migrations = ();
+	type CursorMaxLen = ConstU32<65_536>;
+	type IdentifierMaxLen = ConstU32<256>;
+	type MigrationStatusHandler = ();
+	type FailedMigrationHandler = frame_support::migrations::FreezeChainOnFailedMigration;
+	type MaxServiceWeight = MbmServiceWeight;
+	type WhitelistedCalls = MbmWhitelistedCalls;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type WeightInfo = pallet_migrations::weights::SubstrateWeight<Runtime>;
+}
+
 parameter_types! {
 	pub const EnterDuration: BlockNumber = 4 * HOURS;
 	pub const EnterDepositAmount: Balance = 2_000_000 * DOLLARS;
@@ -2137,6 +2165,7 @@ construct_runtime!(
 		Broker: pallet_broker,
 		Mixnet: pallet_mixnet,
 		SkipFeelessPayment: pallet_skip_feeless_payment,
+		MultiBlockMigrations: pallet_migrations,
 	}
 );
 
@@ -2184,6 +2213,7 @@ pub type Executive = frame_executive::Executive<
 	Runtime,
 	AllPalletsWithSystem,
 	Migrations,
+	MultiBlockMigrations,
 >;
 
 // All migrations executed on runtime upgrade as a nested tuple of types implementing
@@ -2241,6 +2271,7 @@ mod benches {
 		[pallet_lottery, Lottery]
 		[pallet_membership, TechnicalMembership]
 		[pallet_message_queue, MessageQueue]
+		[pallet_migrations, MultiBlockMigrations]
 		[pallet_mmr, Mmr]
 		[pallet_multisig, Multisig]
 		[pallet_nomination_pools, NominationPoolsBench::<Runtime>]
@@ -2305,6 +2336,15 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl frame_support::view_functions::runtime_api::RuntimeViewFunction<Block> for Runtime {
+		fn execute_view_function(
+			id: frame_support::view_functions::ViewFunctionId,
+			input: Vec<u8>,
+		) -> Result<Vec<u8>, frame_support::view_functions::ViewFunctionDispatchError> {
+			Runtime::execute_view_function(id, input)
+		}
+	}
+
 	impl sp_block_builder::BlockBuilder<Block> for Runtime {
 		fn apply_extrinsic(extrinsic: <Block as BlockT>::Extrinsic) -> ApplyExtrinsicResult {
 			Executive::apply_extrinsic(extrinsic)
@@ -2837,8 +2877,9 @@ impl_runtime_apis! {
 mod tests {
 	use super::*;
 	use frame_election_provider_support::NposSolution;
+	use frame_support::migrations::MultiStepMigrator;
 	use frame_system::offchain::CreateSignedTransaction;
-	use sp_runtime::UpperOf;
+	use sp_runtime::{transaction_validity::InvalidTransaction, UpperOf};
 
 	#[test]
 	fn validate_transaction_submitter_bounds() {
@@ -2862,6 +2903,47 @@ mod tests {
 			maximum_chain_accuracy.iter().fold(0, |acc, x| acc.checked_add(*x).unwrap());
 	}
 
+	#[test]
+	fn inherents_are_applied_while_mbms_are_ongoing() {
+		let mut ext: sp_io::TestExternalities =
+			frame_system::GenesisConfig::<Runtime>::default().build_storage().unwrap().into();
+
+		ext.execute_with(|| {
+			frame_system::LastRuntimeUpgrade::<Runtime>::put(
+				frame_system::LastRuntimeUpgradeInfo::from(VERSION),
+			);
+			pallet_migrations::Cursor::<Runtime>::put(pallet_migrations::MigrationCursor::Stuck);
+
+			Executive::initialize_block(&Header::new(
+				1,
+				Default::default(),
+				Default::default(),
+				Default::default(),
+				Default::default(),
+			));
+			assert!(MultiBlockMigrations::ongoing());
+
+			// `Timestamp::set` is mandatory, filtering it would make every block invalid.
+			let now = SLOT_DURATION / 2;
+			let inherent = UncheckedExtrinsic::new_unsigned(RuntimeCall::Timestamp(
+				pallet_timestamp::Call::set { now },
+			));
+			Executive::apply_extrinsic(inherent).unwrap().unwrap();
+			assert_eq!(Timestamp::now(), now);
+
+			// Everything else is still suspended until the migrations completed.
+			let remark = UncheckedExtrinsic::new_unsigned(RuntimeCall::System(
+				frame_system::Call::remark { remark: vec![] },
+			));
+			assert_eq!(
+				Executive::apply_extrinsic(remark),
+				Err(InvalidTransaction::ExhaustsResources.into())
+			);
+
+			Executive::finalize_block();
+		});
+	}
+
 	#[test]
 	fn call_size() {
 		let size = core::mem::size_of::<RuntimeCall>();
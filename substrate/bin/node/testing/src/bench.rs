@@ -384,6 +384,7 @@ impl BenchDb {
 			state_pruning: Some(PruningMode::ArchiveAll),
 			source: database_type.into_settings(dir.into()),
 			blocks_pruning: sc_client_db::BlocksPruning::KeepAll,
+			blocks_archive: None,
 		};
 		let task_executor = TaskExecutor::new();
 
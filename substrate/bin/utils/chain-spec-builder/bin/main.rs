@@ -17,11 +17,13 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use chain_spec_builder::{
-	generate_chain_spec_for_runtime, ChainSpecBuilder, ChainSpecBuilderCmd, ConvertToRawCmd,
-	UpdateCodeCmd, VerifyCmd,
+	diff_chain_specs, generate_chain_spec_for_runtime, verify_chain_spec, ChainSpecBuilder,
+	ChainSpecBuilderCmd, ConvertToRawCmd, DisplayPresetCmd, ListPresetsCmd, UpdateCodeCmd,
 };
 use clap::Parser;
-use sc_chain_spec::{update_code_in_json_chain_spec, GenericChainSpec};
+use sc_chain_spec::{
+	update_code_in_json_chain_spec, GenericChainSpec, GenesisConfigBuilderRuntimeCaller,
+};
 use staging_chain_spec_builder as chain_spec_builder;
 use std::fs;
 
@@ -66,10 +68,43 @@ fn main() -> Result<(), String> {
 				.map_err(|e| format!("Conversion to pretty failed: {e}"))?;
 			fs::write(chain_spec_path, chain_spec_json).map_err(|err| err.to_string())?;
 		},
-		ChainSpecBuilderCmd::Verify(VerifyCmd { ref input_chain_spec }) => {
-			let chain_spec = GenericChainSpec::<()>::from_json_file(input_chain_spec.clone())?;
-			let _ = serde_json::from_str::<serde_json::Value>(&chain_spec.as_json(true)?)
-				.map_err(|e| format!("Conversion to json failed: {e}"))?;
+		ChainSpecBuilderCmd::Verify(ref cmd) => {
+			let problems = verify_chain_spec(cmd)?;
+			if !problems.is_empty() {
+				problems.iter().for_each(|problem| println!("{problem}"));
+				return Err(format!("Chain spec verification failed: {} problems", problems.len()))
+			}
+			println!("Chain spec verification: OK");
+		},
+		ChainSpecBuilderCmd::Diff(ref cmd) => {
+			let diffs = diff_chain_specs(cmd)?;
+			diffs.iter().for_each(|diff| println!("{diff}"));
+			println!("{} differences found", diffs.len());
+		},
+		ChainSpecBuilderCmd::ListPresets(ListPresetsCmd { ref runtime_wasm_path }) => {
+			let code = fs::read(runtime_wasm_path.as_path())
+				.map_err(|e| format!("wasm blob shall be readable {e}"))?;
+			let caller: GenesisConfigBuilderRuntimeCaller =
+				GenesisConfigBuilderRuntimeCaller::new(&code[..]);
+			let presets = caller
+				.preset_names()
+				.map_err(|e| format!("getting presets from runtime should work: {e}"))?;
+			println!("{}", serde_json::json!({ "presets": presets }));
+		},
+		ChainSpecBuilderCmd::DisplayPreset(DisplayPresetCmd {
+			ref runtime_wasm_path,
+			ref preset_name,
+		}) => {
+			let code = fs::read(runtime_wasm_path.as_path())
+				.map_err(|e| format!("wasm blob shall be readable {e}"))?;
+			let caller: GenesisConfigBuilderRuntimeCaller =
+				GenesisConfigBuilderRuntimeCaller::new(&code[..]);
+			let preset = caller
+				.get_named_preset(preset_name.as_ref())
+				.map_err(|e| format!("getting preset from runtime should work: {e}"))?;
+			let preset = serde_json::to_string_pretty(&preset)
+				.map_err(|e| format!("Conversion to pretty failed: {e}"))?;
+			println!("{preset}");
 		},
 	};
 	Ok(())
@@ -58,6 +58,27 @@
 //! 
 //! _Note_: [`GenesisBuilder::build_config`][sp-genesis-builder-build] runtime function is called.
 //!
+//! ##### Generate raw storage chain spec using runtime's named preset.
+//!
+//! Build the chain spec using the genesis config preset (e.g. `development`) built into the
+//! runtime:
+//! ```text
+//! chain-spec-builder create -s -r runtime.wasm named-preset development
+//! ```
+//! 
+//! _Note_: [`GenesisBuilder::get_preset`][sp-genesis-builder-get-preset] runtime function is called.
+//!
+//! ##### List and display the runtime's presets.
+//!
+//! List the names of the genesis config presets provided by the runtime, and display the patch of
+//! one of them (or the default config if no name is given):
+//! ```text
+//! chain-spec-builder list-presets -r runtime.wasm
+//! chain-spec-builder display-preset -r runtime.wasm -p development
+//! ```
+//! 
+//! _Note_: [`GenesisBuilder::preset_names`][sp-genesis-builder-list] runtime function is called.
+//!
 //! ##### Generate human readable chain spec using provided genesis config patch.
 //! ```text
 //! chain-spec-builder create -r runtime.wasm patch patch.json
@@ -68,20 +89,46 @@
 //! chain-spec-builder create -r runtime.wasm full full-genesis-config.json
 //! ```
 //! 
+//! ##### Verify the raw storage of a chain spec.
+//!
+//! Rebuild the raw storage from the runtime embedded in `raw.json` and the `patch.json`, and
+//! compare it against the raw genesis of `raw.json`. Keys of the patch unknown to the runtime's
+//! default genesis config are reported too:
+//! ```text
+//! chain-spec-builder verify raw.json --patch patch.json
+//! ```
+//! 
+//! ##### Compare the genesis of two chain specs.
+//!
+//! Print the differences between the genesis storage of two chain specs. Storage items are named
+//! and their values decoded using the metadata of the runtimes embedded in the chain specs:
+//! ```text
+//! chain-spec-builder diff old.json new.json
+//! ```
+//! 
 //! ##### Extra tools.
-//! The `chain-spec-builder` provides also some extra utilities: [`VerifyCmd`], [`ConvertToRawCmd`], [`UpdateCodeCmd`].
+//! The `chain-spec-builder` provides also some extra utilities: [`VerifyCmd`], [`DiffCmd`],
+//! [`ConvertToRawCmd`], [`UpdateCodeCmd`], [`ListPresetsCmd`], [`DisplayPresetCmd`].
 //!
 //! [`sc-chain-spec`]: ../sc_chain_spec/index.html
 //! [`node-cli`]: ../node_cli/index.html
 //! [`sp-genesis-builder`]: ../sp_genesis_builder/index.html
 //! [sp-genesis-builder-create]: ../sp_genesis_builder/trait.GenesisBuilder.html#method.create_default_config
 //! [sp-genesis-builder-build]: ../sp_genesis_builder/trait.GenesisBuilder.html#method.build_config
+//! [sp-genesis-builder-get-preset]: ../sp_genesis_builder/trait.GenesisBuilder.html#method.get_preset
+//! [sp-genesis-builder-list]: ../sp_genesis_builder/trait.GenesisBuilder.html#method.preset_names
 
-use std::{fs, path::PathBuf};
+mod metadata;
+
+use std::{fmt, fs, path::PathBuf};
 
 use clap::{Parser, Subcommand};
 use sc_chain_spec::{GenericChainSpec, GenesisConfigBuilderRuntimeCaller};
 use serde_json::Value;
+use sp_core::storage::well_known_keys;
+use sp_runtime::{BuildStorage, Storage};
+
+use metadata::{diff_storage, unknown_keys, StorageLayout};
 
 /// A utility to easily create a chain spec definition.
 #[derive(Debug, Parser)]
@@ -99,8 +146,11 @@ pub struct ChainSpecBuilder {
 pub enum ChainSpecBuilderCmd {
 	Create(CreateCmd),
 	Verify(VerifyCmd),
+	Diff(DiffCmd),
 	UpdateCode(UpdateCodeCmd),
 	ConvertToRaw(ConvertToRawCmd),
+	ListPresets(ListPresetsCmd),
+	DisplayPreset(DisplayPresetCmd),
 }
 
 /// Create a new chain spec by interacting with the provided runtime wasm blob.
@@ -131,6 +181,7 @@ enum GenesisBuildAction {
 	Patch(PatchCmd),
 	Full(FullCmd),
 	Default(DefaultCmd),
+	NamedPreset(NamedPresetCmd),
 }
 
 /// Patches the runtime's default genesis config with provided patch.
@@ -157,6 +208,13 @@ struct DefaultCmd {
 	default_config_path: Option<PathBuf>,
 }
 
+/// Uses the runtime's named genesis config preset in ChainSpec.
+#[derive(Parser, Debug, Clone)]
+struct NamedPresetCmd {
+	/// The name of the preset, as returned by the `list-presets` command.
+	preset_name: String,
+}
+
 /// Updates the code in the provided input chain spec.
 ///
 /// The code field of the chain spec will be updated with the runtime provided in the
@@ -176,15 +234,86 @@ pub struct ConvertToRawCmd {
 	pub input_chain_spec: PathBuf,
 }
 
+/// Lists the names of the genesis config presets provided by the runtime.
+#[derive(Parser, Debug, Clone)]
+pub struct ListPresetsCmd {
+	/// The path to runtime wasm blob.
+	#[arg(long, short)]
+	pub runtime_wasm_path: PathBuf,
+}
+
+/// Displays the JSON patch of the given genesis config preset provided by the runtime.
+///
+/// If no preset name is given, the default genesis config is displayed.
+#[derive(Parser, Debug, Clone)]
+pub struct DisplayPresetCmd {
+	/// The path to runtime wasm blob.
+	#[arg(long, short)]
+	pub runtime_wasm_path: PathBuf,
+	/// The name of the preset to be displayed.
+	#[arg(long, short)]
+	pub preset_name: Option<String>,
+}
+
 /// Verifies the provided input chain spec.
 ///
-/// Silently checks if given input chain spec can be converted to raw. It allows to check if all
+/// Checks if given input chain spec can be converted to raw. It allows to check if all
 /// RuntimeGenesisConfig fiels are properly initialized and if the json does not contain invalid
-/// fields.
+/// fields. Keys of the genesis config patch which are not present in the runtime's default genesis
+/// config are reported, since they are silently ignored or removed when the patch is applied.
+///
+/// If a patch is provided, the raw storage is rebuilt from the runtime embedded in the chain spec
+/// and the patch, and compared against the genesis storage of the chain spec.
 #[derive(Parser, Debug, Clone)]
 pub struct VerifyCmd {
 	/// Chain spec to be verified.
 	pub input_chain_spec: PathBuf,
+	/// The path to the runtime genesis config patch the chain spec was built from.
+	#[arg(long)]
+	pub patch: Option<PathBuf>,
+}
+
+/// Compares the genesis storage of two chain specs.
+///
+/// The storage items are named and their values decoded using the metadata of the runtimes
+/// embedded in the chain specs. Keys which can't be decoded are reported in hex.
+#[derive(Parser, Debug, Clone)]
+pub struct DiffCmd {
+	/// The chain spec to compare against.
+	pub left_chain_spec: PathBuf,
+	/// The compared chain spec.
+	pub right_chain_spec: PathBuf,
+}
+
+/// A difference between the genesis storage of two chain specs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenesisDiff {
+	/// The name of the storage item, or `None` if it is unknown.
+	pub item: Option<String>,
+	/// The hex-encoded storage key.
+	pub key: String,
+	/// The value in the left chain spec, `None` if the key is missing.
+	pub left: Option<Value>,
+	/// The value in the right chain spec, `None` if the key is missing.
+	pub right: Option<Value>,
+}
+
+impl fmt::Display for GenesisDiff {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let marker = match (&self.left, &self.right) {
+			(None, Some(_)) => '+',
+			(Some(_), None) => '-',
+			_ => '~',
+		};
+		write!(f, "{marker} {} {}", self.item.as_deref().unwrap_or("<unknown>"), self.key)?;
+		if let Some(ref left) = self.left {
+			write!(f, "\n    - {left}")?;
+		}
+		if let Some(ref right) = self.right {
+			write!(f, "\n    + {right}")?;
+		}
+		Ok(())
+	}
 }
 
 /// Processes `CreateCmd` and returns JSON version of `ChainSpec`.
@@ -224,6 +353,8 @@ pub fn generate_chain_spec_for_runtime(cmd: &CreateCmd) -> Result<String, String
 			});
 			builder.with_genesis_config(default_config)
 		},
+		GenesisBuildAction::NamedPreset(NamedPresetCmd { ref preset_name }) =>
+			builder.with_genesis_config_preset_name(preset_name),
 	};
 
 	let chain_spec = builder.build();
@@ -238,3 +369,111 @@ pub fn generate_chain_spec_for_runtime(cmd: &CreateCmd) -> Result<String, String
 		(false, false) => chain_spec.as_json(false),
 	}
 }
+
+/// Processes `VerifyCmd` and returns the list of problems found in the chain spec.
+pub fn verify_chain_spec(cmd: &VerifyCmd) -> Result<Vec<String>, String> {
+	let chain_spec = GenericChainSpec::<()>::from_json_file(cmd.input_chain_spec.clone())?;
+	let storage = chain_spec.build_storage()?;
+
+	let chain_spec_json = serde_json::from_str::<Value>(&chain_spec.as_json(false)?)
+		.map_err(|e| format!("Conversion to json failed: {e}"))?;
+	let runtime_genesis = &chain_spec_json["genesis"]["runtimeGenesis"];
+	let json_blobs = [&runtime_genesis["patch"], &runtime_genesis["config"]]
+		.into_iter()
+		.filter(|json_blob| !json_blob.is_null())
+		.collect::<Vec<_>>();
+
+	// Raw chain specs are verified by building their storage, the runtime's `GenesisBuilder` is
+	// only needed to check the genesis config of plain chain specs or to rebuild the storage
+	// from a patch.
+	if json_blobs.is_empty() && cmd.patch.is_none() {
+		return Ok(vec![])
+	}
+
+	let code = storage
+		.top
+		.get(well_known_keys::CODE)
+		.ok_or("the chain spec does not contain the runtime code")?;
+	let caller: GenesisConfigBuilderRuntimeCaller = GenesisConfigBuilderRuntimeCaller::new(code);
+	let default_config = caller
+		.get_default_config()
+		.map_err(|e| format!("getting default config from runtime should work: {e}"))?;
+
+	let mut problems = vec![];
+	for json_blob in json_blobs {
+		problems.extend(
+			unknown_keys(&default_config, json_blob)
+				.into_iter()
+				.map(|key| format!("unknown key in genesis config: `{key}`")),
+		);
+	}
+
+	if let Some(ref patch_path) = cmd.patch {
+		let patch = fs::read(patch_path.as_path())
+			.map_err(|e| format!("patch file {patch_path:?} shall be readable: {e}"))?;
+		let patch = serde_json::from_slice::<Value>(&patch[..])
+			.map_err(|e| format!("patch file {patch_path:?} shall contain a valid json: {e}"))?;
+		problems.extend(
+			unknown_keys(&default_config, &patch)
+				.into_iter()
+				.map(|key| format!("unknown key in patch: `{key}`")),
+		);
+
+		let mut rebuilt = caller.get_storage_for_patch(patch)?;
+		rebuilt.top.insert(well_known_keys::CODE.to_vec(), code.clone());
+		problems.extend(
+			diff_storage(&rebuilt, &storage, None, None)
+				.into_iter()
+				.map(|diff| format!("raw genesis does not match the patch:\n{diff}")),
+		);
+	}
+
+	Ok(problems)
+}
+
+/// Processes `DiffCmd` and returns the differences between the genesis of the chain specs.
+pub fn diff_chain_specs(cmd: &DiffCmd) -> Result<Vec<GenesisDiff>, String> {
+	let left =
+		GenericChainSpec::<()>::from_json_file(cmd.left_chain_spec.clone())?.build_storage()?;
+	let right =
+		GenericChainSpec::<()>::from_json_file(cmd.right_chain_spec.clone())?.build_storage()?;
+
+	let layout = |storage: &Storage| {
+		storage
+			.top
+			.get(well_known_keys::CODE)
+			.map(|code| StorageLayout::from_code(code))
+	};
+	let left_layout = layout(&left).transpose().map_err(|e| format!("left chain spec: {e}"))?;
+	let right_layout = layout(&right).transpose().map_err(|e| format!("right chain spec: {e}"))?;
+
+	Ok(diff_storage(&left, &right, left_layout.as_ref(), right_layout.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn raw_chain_spec_is_verified_without_genesis_builder() {
+		// the code is not a valid runtime, so calling the `GenesisBuilder` API would fail
+		let chain_spec = serde_json::json!({
+			"name": "TestName",
+			"id": "test_id",
+			"chainType": "Local",
+			"bootNodes": [],
+			"telemetryEndpoints": null,
+			"protocolId": null,
+			"properties": null,
+			"codeSubstitutes": {},
+			"genesis": { "raw": { "top": { "0x3a636f6465": "0x010101" }, "childrenDefault": {} } }
+		});
+		let dir = tempfile::tempdir().unwrap();
+		let input_chain_spec = dir.path().join("raw.json");
+		fs::write(&input_chain_spec, chain_spec.to_string()).unwrap();
+
+		let problems = verify_chain_spec(&VerifyCmd { input_chain_spec, patch: None }).unwrap();
+
+		assert!(problems.is_empty());
+	}
+}
@@ -0,0 +1,529 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Decoding and comparison of the genesis storage using the runtime metadata.
+
+use codec::{Compact, Decode};
+use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed};
+use sc_executor::WasmExecutor;
+use scale_info::{form::PortableForm, Field, PortableRegistry, TypeDef, TypeDefPrimitive};
+use serde_json::{json, Map, Value};
+use sp_core::{
+	bytes::to_hex,
+	storage::well_known_keys,
+	traits::{CallContext, CodeExecutor, RuntimeCode, WrappedRuntimeCode},
+	twox_128,
+};
+use sp_runtime::Storage;
+use sp_state_machine::BasicExternalities;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::GenesisDiff;
+
+/// A storage item described by the runtime metadata.
+struct StorageItem {
+	/// The name of the item, prefixed with the pallet's storage prefix.
+	name: String,
+	/// The type of the values stored in the item.
+	value_ty: u32,
+}
+
+/// A storage value decoded using the runtime metadata.
+pub struct DecodedValue {
+	/// The name of the storage item (e.g. `System.Account`).
+	pub item: String,
+	/// The decoded value.
+	pub value: Value,
+}
+
+/// The storage items of the runtime, indexed by their 32-byte key prefix.
+pub struct StorageLayout {
+	registry: PortableRegistry,
+	items: HashMap<[u8; 32], StorageItem>,
+}
+
+macro_rules! storage_items {
+	($metadata:expr) => {
+		$metadata
+			.pallets
+			.iter()
+			.filter_map(|pallet| pallet.storage.as_ref())
+			.flat_map(|storage| {
+				storage.entries.iter().map(|entry| {
+					let value_ty = match entry.ty {
+						frame_metadata::v14::StorageEntryType::Plain(ref ty) => ty.id,
+						frame_metadata::v14::StorageEntryType::Map { ref value, .. } => value.id,
+					};
+					let mut prefix = [0u8; 32];
+					prefix[..16].copy_from_slice(&twox_128(storage.prefix.as_bytes()));
+					prefix[16..].copy_from_slice(&twox_128(entry.name.as_bytes()));
+					(
+						prefix,
+						StorageItem {
+							name: format!("{}.{}", storage.prefix, entry.name),
+							value_ty,
+						},
+					)
+				})
+			})
+			.collect()
+	};
+}
+
+impl StorageLayout {
+	/// Fetches the metadata from the runtime `code` and builds the storage layout.
+	pub fn from_code(code: &[u8]) -> Result<Self, String> {
+		let executor = WasmExecutor::<sp_io::SubstrateHostFunctions>::builder()
+			.with_allow_missing_host_functions(true)
+			.build();
+		let code_fetcher = WrappedRuntimeCode(code.into());
+		let runtime_code = RuntimeCode {
+			code_fetcher: &code_fetcher,
+			heap_pages: None,
+			hash: sp_core::blake2_256(code).to_vec(),
+		};
+
+		let mut ext = BasicExternalities::new_empty();
+		let call_result = executor
+			.call(&mut ext, &runtime_code, "Metadata_metadata", &[], CallContext::Offchain)
+			.0
+			.map_err(|e| format!("wasm call error {e}"))?;
+		let metadata = Vec::<u8>::decode(&mut &call_result[..])
+			.map_err(|e| format!("scale codec error: {e}"))?;
+		let metadata = RuntimeMetadataPrefixed::decode(&mut &metadata[..])
+			.map_err(|e| format!("metadata can't be decoded: {e}"))?;
+
+		match metadata.1 {
+			RuntimeMetadata::V14(metadata) =>
+				Ok(Self { items: storage_items!(metadata), registry: metadata.types }),
+			RuntimeMetadata::V15(metadata) =>
+				Ok(Self { items: storage_items!(metadata), registry: metadata.types }),
+			_ => Err("unsupported metadata version".into()),
+		}
+	}
+
+	/// Decodes the storage value stored under `key`.
+	///
+	/// Returns `None` if the key doesn't belong to any storage item known to the metadata or if
+	/// the value can't be decoded.
+	pub fn decode(&self, key: &[u8], value: &[u8]) -> Option<DecodedValue> {
+		let prefix: [u8; 32] = key.get(..32)?.try_into().ok()?;
+		let item = self.items.get(&prefix)?;
+
+		let mut input = value;
+		let value = self.decode_value(item.value_ty, &mut input).ok()?;
+		input.is_empty().then(|| DecodedValue { item: item.name.clone(), value })
+	}
+
+	fn decode_value(&self, ty: u32, input: &mut &[u8]) -> Result<Value, String> {
+		let ty = self.registry.resolve(ty).ok_or_else(|| format!("unknown type {ty}"))?;
+		match &ty.type_def {
+			TypeDef::Composite(composite) => self.decode_fields(&composite.fields, input),
+			TypeDef::Variant(variant) => {
+				let index = u8::decode(input).map_err(|e| e.to_string())?;
+				let variant = variant
+					.variants
+					.iter()
+					.find(|variant| variant.index == index)
+					.ok_or_else(|| format!("unknown variant {index}"))?;
+				if variant.fields.is_empty() {
+					Ok(Value::String(variant.name.clone()))
+				} else {
+					Ok(json!({ variant.name.clone(): self.decode_fields(&variant.fields, input)? }))
+				}
+			},
+			TypeDef::Sequence(sequence) => {
+				let len = Compact::<u32>::decode(input).map_err(|e| e.to_string())?.0;
+				self.decode_items(sequence.type_param.id, len as usize, input)
+			},
+			TypeDef::Array(array) =>
+				self.decode_items(array.type_param.id, array.len as usize, input),
+			TypeDef::Tuple(tuple) => tuple
+				.fields
+				.iter()
+				.map(|field| self.decode_value(field.id, input))
+				.collect::<Result<_, _>>()
+				.map(Value::Array),
+			TypeDef::Primitive(primitive) => decode_primitive(primitive, input),
+			TypeDef::Compact(_) => {
+				let value = Compact::<u128>::decode(input).map_err(|e| e.to_string())?.0;
+				Ok(u64::try_from(value).map_or_else(|_| json!(value.to_string()), |v| json!(v)))
+			},
+			TypeDef::BitSequence(_) => Err("bit sequences are not supported".into()),
+		}
+	}
+
+	fn decode_fields(
+		&self,
+		fields: &[Field<PortableForm>],
+		input: &mut &[u8],
+	) -> Result<Value, String> {
+		if fields.len() == 1 && fields[0].name.is_none() {
+			return self.decode_value(fields[0].ty.id, input)
+		}
+
+		if fields.iter().all(|field| field.name.is_some()) {
+			let mut object = Map::new();
+			for field in fields {
+				let name = field.name.clone().unwrap_or_default();
+				object.insert(name, self.decode_value(field.ty.id, input)?);
+			}
+			Ok(Value::Object(object))
+		} else {
+			fields
+				.iter()
+				.map(|field| self.decode_value(field.ty.id, input))
+				.collect::<Result<_, _>>()
+				.map(Value::Array)
+		}
+	}
+
+	fn decode_items(&self, ty: u32, len: usize, input: &mut &[u8]) -> Result<Value, String> {
+		let is_byte = matches!(
+			self.registry.resolve(ty).map(|ty| &ty.type_def),
+			Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+		);
+		if is_byte {
+			let bytes = input.get(..len).ok_or("not enough data")?;
+			*input = &input[len..];
+			return Ok(Value::String(to_hex(bytes, false)))
+		}
+
+		(0..len)
+			.map(|_| self.decode_value(ty, input))
+			.collect::<Result<_, _>>()
+			.map(Value::Array)
+	}
+}
+
+fn decode_primitive(primitive: &TypeDefPrimitive, input: &mut &[u8]) -> Result<Value, String> {
+	fn decode<T: Decode>(input: &mut &[u8]) -> Result<T, String> {
+		T::decode(input).map_err(|e| e.to_string())
+	}
+
+	Ok(match primitive {
+		TypeDefPrimitive::Bool => json!(decode::<bool>(input)?),
+		TypeDefPrimitive::Char => json!(char::from_u32(decode::<u32>(input)?)),
+		TypeDefPrimitive::Str => json!(decode::<String>(input)?),
+		TypeDefPrimitive::U8 => json!(decode::<u8>(input)?),
+		TypeDefPrimitive::U16 => json!(decode::<u16>(input)?),
+		TypeDefPrimitive::U32 => json!(decode::<u32>(input)?),
+		TypeDefPrimitive::U64 => json!(decode::<u64>(input)?),
+		TypeDefPrimitive::U128 => json!(decode::<u128>(input)?.to_string()),
+		TypeDefPrimitive::I8 => json!(decode::<i8>(input)?),
+		TypeDefPrimitive::I16 => json!(decode::<i16>(input)?),
+		TypeDefPrimitive::I32 => json!(decode::<i32>(input)?),
+		TypeDefPrimitive::I64 => json!(decode::<i64>(input)?),
+		TypeDefPrimitive::I128 => json!(decode::<i128>(input)?.to_string()),
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 =>
+			json!(to_hex(&decode::<[u8; 32]>(input)?, false)),
+	})
+}
+
+/// Returns the keys of `json_blob` which are not present in the `default_config`.
+///
+/// Objects which are empty in the default config are skipped, since they usually represent maps
+/// accepting arbitrary keys.
+pub(crate) fn unknown_keys(default_config: &Value, json_blob: &Value) -> Vec<String> {
+	fn collect(default: &Value, json_blob: &Value, path: &str, unknown: &mut Vec<String>) {
+		let (Value::Object(default), Value::Object(json_blob)) = (default, json_blob) else {
+			return
+		};
+		if default.is_empty() {
+			return
+		}
+
+		for (key, value) in json_blob {
+			let key_path = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+			match default.get(key) {
+				Some(default) => collect(default, value, &key_path, unknown),
+				None => unknown.push(key_path),
+			}
+		}
+	}
+
+	let mut unknown = vec![];
+	collect(default_config, json_blob, "", &mut unknown);
+	unknown
+}
+
+/// Compares two genesis storages, decoding the values with the given storage layouts.
+pub(crate) fn diff_storage(
+	left: &Storage,
+	right: &Storage,
+	left_layout: Option<&StorageLayout>,
+	right_layout: Option<&StorageLayout>,
+) -> Vec<GenesisDiff> {
+	let describe = |layout: Option<&StorageLayout>, key: &[u8], value: &[u8]| {
+		if key == well_known_keys::CODE {
+			let hash = to_hex(&sp_core::blake2_256(value), false);
+			return (Some("runtime code".to_string()), Value::String(format!("blake2 {hash}")))
+		}
+		match layout.and_then(|layout| layout.decode(key, value)) {
+			Some(decoded) => (Some(decoded.item), decoded.value),
+			None => (None, Value::String(to_hex(value, false))),
+		}
+	};
+
+	let mut diffs = diff_maps(&left.top, &right.top, |side, key, value| {
+		describe(if side { right_layout } else { left_layout }, key, value)
+	});
+
+	let child_keys = left
+		.children_default
+		.keys()
+		.chain(right.children_default.keys())
+		.collect::<BTreeSet<_>>();
+	let empty = BTreeMap::new();
+	for child_key in child_keys {
+		let child_name = format!("child {}", to_hex(child_key, false));
+		let left = left.children_default.get(child_key).map_or(&empty, |child| &child.data);
+		let right = right.children_default.get(child_key).map_or(&empty, |child| &child.data);
+		diffs.extend(diff_maps(left, right, |_, _, value| {
+			(Some(child_name.clone()), Value::String(to_hex(value, false)))
+		}));
+	}
+
+	diffs
+}
+
+/// Compares two key-value maps, using `describe` to name and decode the values.
+///
+/// The first argument of `describe` is `false` for values of the left map and `true` for values
+/// of the right one.
+fn diff_maps(
+	left: &BTreeMap<Vec<u8>, Vec<u8>>,
+	right: &BTreeMap<Vec<u8>, Vec<u8>>,
+	describe: impl Fn(bool, &[u8], &[u8]) -> (Option<String>, Value),
+) -> Vec<GenesisDiff> {
+	let keys = left.keys().chain(right.keys()).collect::<BTreeSet<_>>();
+	keys.into_iter()
+		.filter_map(|key| {
+			let (left, right) = (left.get(key), right.get(key));
+			if left == right {
+				return None
+			}
+			let left = left.map(|value| describe(false, key, value));
+			let right = right.map(|value| describe(true, key, value));
+			let item = right.as_ref().or(left.as_ref()).and_then(|(item, _)| item.clone());
+			Some(GenesisDiff {
+				item,
+				key: to_hex(key, false),
+				left: left.map(|(_, value)| value),
+				right: right.map(|(_, value)| value),
+			})
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use sc_chain_spec::{ChainType, GenericChainSpec};
+	use scale_info::{MetaType, Registry, TypeInfo};
+	use sp_core::crypto::Ss58Codec;
+	use sp_keyring::AccountKeyring;
+	use sp_runtime::BuildStorage;
+
+	#[derive(Encode, TypeInfo)]
+	struct Balance {
+		#[codec(compact)]
+		free: u128,
+		reserved: u128,
+	}
+
+	#[derive(Encode, TypeInfo)]
+	enum Status {
+		Active,
+		Frozen { until: u32 },
+	}
+
+	#[derive(Encode, TypeInfo)]
+	struct Account {
+		nonce: u32,
+		data: Balance,
+		status: Status,
+		flags: Vec<(u8, Option<bool>)>,
+		name: Vec<u8>,
+	}
+
+	fn prefix(pallet: &str, item: &str) -> [u8; 32] {
+		let mut prefix = [0u8; 32];
+		prefix[..16].copy_from_slice(&twox_128(pallet.as_bytes()));
+		prefix[16..].copy_from_slice(&twox_128(item.as_bytes()));
+		prefix
+	}
+
+	fn layout<T: TypeInfo + 'static>(pallet: &str, item: &str) -> StorageLayout {
+		let mut registry = Registry::new();
+		let value_ty = registry.register_type(&MetaType::new::<T>()).id;
+		let name = format!("{pallet}.{item}");
+		let items = [(prefix(pallet, item), StorageItem { name, value_ty })].into();
+		StorageLayout { registry: registry.into(), items }
+	}
+
+	fn plain_chain_spec_storage(balance: u64) -> Storage {
+		GenericChainSpec::<()>::builder(substrate_test_runtime::wasm_binary_unwrap(), ())
+			.with_name("TestName")
+			.with_id("test_id")
+			.with_chain_type(ChainType::Local)
+			.with_genesis_config_patch(json!({
+				"balances": {
+					"balances": [[AccountKeyring::Alice.public().to_ss58check(), balance]],
+				}
+			}))
+			.build()
+			.build_storage()
+			.unwrap()
+	}
+
+	#[test]
+	fn decodes_map_values_with_nested_types() {
+		let layout = layout::<Account>("System", "Account");
+		let key = [&prefix("System", "Account")[..], &[1u8; 48][..]].concat();
+		let value = Account {
+			nonce: 5,
+			data: Balance { free: 1000, reserved: 7 },
+			status: Status::Frozen { until: 9 },
+			flags: vec![(1, Some(true)), (2, None)],
+			name: b"alice".to_vec(),
+		};
+
+		let decoded = layout.decode(&key, &value.encode()).unwrap();
+
+		assert_eq!(decoded.item, "System.Account");
+		assert_eq!(
+			decoded.value,
+			json!({
+				"nonce": 5,
+				"data": { "free": 1000, "reserved": "7" },
+				"status": { "Frozen": { "until": 9 } },
+				"flags": [[1, { "Some": true }], [2, "None"]],
+				"name": "0x616c696365",
+			})
+		);
+	}
+
+	#[test]
+	fn decodes_compacts() {
+		let layout = layout::<Vec<Compact<u128>>>("Balances", "TotalIssuance");
+		let key = prefix("Balances", "TotalIssuance");
+		let value = vec![Compact(1u128), Compact(u128::MAX)];
+
+		let decoded = layout.decode(&key, &value.encode()).unwrap();
+
+		assert_eq!(decoded.value, json!([1, u128::MAX.to_string()]));
+	}
+
+	#[test]
+	fn does_not_decode_unknown_keys_or_partial_values() {
+		let layout = layout::<u32>("Balances", "TotalIssuance");
+		let key = prefix("Balances", "TotalIssuance");
+
+		assert!(layout.decode(&prefix("Balances", "Locks"), &1u32.encode()).is_none());
+		assert!(layout.decode(&key[..16], &1u32.encode()).is_none());
+		assert!(layout.decode(&key, &1u64.encode()).is_none());
+		assert!(layout.decode(&key, &1u16.encode()).is_none());
+		assert_eq!(layout.decode(&key, &1u32.encode()).unwrap().value, json!(1));
+	}
+
+	#[test]
+	fn unknown_keys_are_detected() {
+		let default_config = json!({
+			"balances": { "balances": [] },
+			"system": {},
+			"session": { "keys": [], "nonAuthorityKeys": [] },
+		});
+		let patch = json!({
+			"balances": { "balancess": [] },
+			"sytem": {},
+			"system": { "anything": 1 },
+			"session": { "keys": [["a", "b"]] },
+		});
+
+		let mut unknown = unknown_keys(&default_config, &patch);
+		unknown.sort();
+
+		assert_eq!(unknown, vec!["balances.balancess", "sytem"]);
+		assert!(unknown_keys(&default_config, &default_config).is_empty());
+	}
+
+	#[test]
+	fn raw_storage_diff_works() {
+		let storage = |entries: &[(&[u8], &[u8])]| Storage {
+			top: entries.iter().map(|(key, value)| (key.to_vec(), value.to_vec())).collect(),
+			children_default: Default::default(),
+		};
+		let left = storage(&[(b"removed", b"1"), (b"changed", b"2"), (b"same", b"3")]);
+		let right = storage(&[(b"added", b"4"), (b"changed", b"5"), (b"same", b"3")]);
+
+		let diffs = diff_storage(&left, &right, None, None);
+
+		let hex = |value: &[u8]| Value::String(to_hex(value, false));
+		assert_eq!(
+			diffs,
+			vec![
+				GenesisDiff {
+					item: None,
+					key: to_hex(b"added", false),
+					left: None,
+					right: Some(hex(b"4")),
+				},
+				GenesisDiff {
+					item: None,
+					key: to_hex(b"changed", false),
+					left: Some(hex(b"2")),
+					right: Some(hex(b"5")),
+				},
+				GenesisDiff {
+					item: None,
+					key: to_hex(b"removed", false),
+					left: Some(hex(b"1")),
+					right: None,
+				},
+			]
+		);
+		assert!(diff_storage(&left, &left, None, None).is_empty());
+	}
+
+	#[test]
+	fn plain_chain_spec_diff_is_decoded() {
+		let layout =
+			StorageLayout::from_code(substrate_test_runtime::wasm_binary_unwrap()).unwrap();
+		let left = plain_chain_spec_storage(1000);
+		let right = plain_chain_spec_storage(2000);
+
+		let diffs = diff_storage(&left, &right, Some(&layout), Some(&layout));
+
+		assert!(!diffs.is_empty());
+		assert!(diffs.iter().all(|diff| diff.item.is_some()));
+		let total_issuance = diffs
+			.iter()
+			.find(|diff| diff.item.as_deref() == Some("Balances.TotalIssuance"))
+			.unwrap();
+		assert_eq!(total_issuance.left, Some(json!(1000)));
+		assert_eq!(total_issuance.right, Some(json!(2000)));
+		let account = diffs
+			.iter()
+			.find(|diff| diff.item.as_deref() == Some("System.Account"))
+			.unwrap();
+		assert_eq!(account.left.as_ref().unwrap()["data"]["free"], json!(1000));
+		assert_eq!(account.right.as_ref().unwrap()["data"]["free"], json!(2000));
+	}
+}
@@ -45,6 +45,7 @@ use std::{
 enum GenesisBuildAction {
 	Patch(json::Value),
 	Full(json::Value),
+	NamedPreset(String),
 }
 
 #[allow(deprecated)]
@@ -72,7 +73,7 @@ impl<G> Clone for GenesisSource<G> {
 }
 
 impl<G: RuntimeGenesis> GenesisSource<G> {
-	fn resolve(&self) -> Result<Genesis<G>, String> {
+	fn resolve<EHF: HostFunctions>(&self) -> Result<Genesis<G>, String> {
 		/// helper container for deserializing genesis from the JSON file (ChainSpec JSON file is
 		/// also supported here)
 		#[derive(Serialize, Deserialize)]
@@ -118,6 +119,13 @@ impl<G: RuntimeGenesis> GenesisSource<G> {
 					json_blob: RuntimeGenesisConfigJson::Patch(patch.clone()),
 					code: code.clone(),
 				})),
+			Self::GenesisBuilderApi(GenesisBuildAction::NamedPreset(name), code) => {
+				let patch = RuntimeCaller::<EHF>::new(&code[..]).get_named_preset(Some(name))?;
+				Ok(Genesis::RuntimeGenesis(RuntimeGenesisInner {
+					json_blob: RuntimeGenesisConfigJson::Patch(patch),
+					code: code.clone(),
+				}))
+			},
 		}
 	}
 }
@@ -127,7 +135,7 @@ where
 	EHF: HostFunctions,
 {
 	fn assimilate_storage(&self, storage: &mut Storage) -> Result<(), String> {
-		match self.genesis.resolve()? {
+		match self.genesis.resolve::<EHF>()? {
 			#[allow(deprecated)]
 			Genesis::Runtime(runtime_genesis_config) => {
 				runtime_genesis_config.assimilate_storage(storage)?;
@@ -431,6 +439,14 @@ impl<G, E, EHF> ChainSpecBuilder<G, E, EHF> {
 		self
 	}
 
+	/// Sets the name of the runtime's built-in GenesisConfig preset.
+	///
+	/// The preset's JSON patch is fetched from the runtime when the chain spec is built.
+	pub fn with_genesis_config_preset_name(mut self, name: &str) -> Self {
+		self.genesis_build_action = GenesisBuildAction::NamedPreset(name.to_string());
+		self
+	}
+
 	/// Builds a [`ChainSpec`] instance using the provided settings.
 	pub fn build(self) -> ChainSpec<G, E, EHF> {
 		let client_spec = ClientSpec {
@@ -635,7 +651,7 @@ where
 	EHF: HostFunctions,
 {
 	fn json_container(&self, raw: bool) -> Result<ChainSpecJsonContainer<G, E>, String> {
-		let raw_genesis = match (raw, self.genesis.resolve()?) {
+		let raw_genesis = match (raw, self.genesis.resolve::<EHF>()?) {
 			(
 				true,
 				Genesis::RuntimeGenesis(RuntimeGenesisInner {
@@ -1089,6 +1105,41 @@ mod tests {
 		assert_eq!(actual_raw, expected_raw);
 	}
 
+	#[test]
+	fn generate_chain_spec_with_named_preset_works() {
+		let output = ChainSpec::<()>::builder(
+			substrate_test_runtime::wasm_binary_unwrap().into(),
+			Default::default(),
+		)
+		.with_name("TestName")
+		.with_id("test_id")
+		.with_chain_type(ChainType::Local)
+		.with_genesis_config_preset_name("staging")
+		.build();
+
+		let actual = from_str::<Value>(&output.as_json(false).unwrap()).unwrap();
+		assert_eq!(
+			actual["genesis"]["runtimeGenesis"]["patch"]["substrateTest"]["authorities"],
+			json!([AccountKeyring::Alice.public().to_ss58check()])
+		);
+		assert!(output.as_json(true).is_ok());
+	}
+
+	#[test]
+	fn chain_spec_as_json_fails_with_invalid_preset() {
+		let output = ChainSpec::<()>::builder(
+			substrate_test_runtime::wasm_binary_unwrap().into(),
+			Default::default(),
+		)
+		.with_name("TestName")
+		.with_id("test_id")
+		.with_chain_type(ChainType::Local)
+		.with_genesis_config_preset_name("unknown")
+		.build();
+
+		assert!(output.as_json(false).is_err());
+	}
+
 	#[test]
 	fn chain_spec_as_json_fails_with_invalid_config() {
 		let j =
@@ -26,7 +26,7 @@ use sp_core::{
 	storage::Storage,
 	traits::{CallContext, CodeExecutor, Externalities, FetchRuntimeCode, RuntimeCode},
 };
-use sp_genesis_builder::Result as BuildResult;
+use sp_genesis_builder::{PresetId, Result as BuildResult};
 use sp_state_machine::BasicExternalities;
 use std::borrow::Cow;
 
@@ -111,6 +111,41 @@ where
 		Ok(ext.into_storages())
 	}
 
+	/// Returns a JSON blob representation of the built-in `GenesisConfig` preset identified by
+	/// `id`, or the default `GenesisConfig` if `id` is `None`.
+	///
+	/// Calls [`GenesisBuilder::get_preset`](sp_genesis_builder::GenesisBuilder::get_preset) in the
+	/// `runtime`.
+	pub fn get_named_preset(&self, id: Option<&String>) -> core::result::Result<Value, String> {
+		let mut t = BasicExternalities::new_empty();
+		let call_result = self
+			.call(&mut t, "GenesisBuilder_get_preset", &id.map(String::as_str).encode())
+			.map_err(|e| format!("wasm call error {e}"))?;
+		let named_preset = Option::<Vec<u8>>::decode(&mut &call_result[..])
+			.map_err(|e| format!("scale codec error: {e}"))?;
+
+		match named_preset {
+			Some(preset) =>
+				from_slice(&preset[..]).map_err(|e| format!("preset is not a valid json: {e}")),
+			None => Err(format!("The preset with name {id:?} is not available.")),
+		}
+	}
+
+	/// Returns the identifiers of all the built-in `GenesisConfig` presets.
+	///
+	/// Calls [`GenesisBuilder::preset_names`](sp_genesis_builder::GenesisBuilder::preset_names) in
+	/// the `runtime`.
+	pub fn preset_names(&self) -> core::result::Result<Vec<String>, String> {
+		let mut t = BasicExternalities::new_empty();
+		let call_result = self
+			.call(&mut t, "GenesisBuilder_preset_names", &[])
+			.map_err(|e| format!("wasm call error {e}"))?;
+		let preset_names = Vec::<PresetId>::decode(&mut &call_result[..])
+			.map_err(|e| format!("scale codec error: {e}"))?;
+
+		Ok(preset_names.into_iter().map(Into::into).collect())
+	}
+
 	/// Creates the genesis state by patching the default `GenesisConfig` and applying it.
 	///
 	/// This function generates the `GenesisConfig` for the runtime by applying a provided JSON
@@ -153,6 +188,30 @@ mod tests {
 		assert_eq!(from_str::<Value>(expected).unwrap(), config);
 	}
 
+	#[test]
+	fn preset_names_works() {
+		let names =
+			<GenesisConfigBuilderRuntimeCaller>::new(substrate_test_runtime::wasm_binary_unwrap())
+				.preset_names()
+				.unwrap();
+		assert_eq!(names, vec!["development".to_string(), "staging".to_string()]);
+	}
+
+	#[test]
+	fn get_named_preset_works() {
+		let caller =
+			<GenesisConfigBuilderRuntimeCaller>::new(substrate_test_runtime::wasm_binary_unwrap());
+
+		let preset = caller.get_named_preset(Some(&"staging".to_string())).unwrap();
+		assert_eq!(
+			preset["substrateTest"]["authorities"],
+			json!(["5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"])
+		);
+
+		assert_eq!(caller.get_named_preset(None).unwrap(), caller.get_default_config().unwrap());
+		assert!(caller.get_named_preset(Some(&"unknown".to_string())).is_err());
+	}
+
 	#[test]
 	fn get_storage_for_patch_works() {
 		let patch = json!({
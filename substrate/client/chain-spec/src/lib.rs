@@ -189,6 +189,11 @@
 //! Consequently, the runtime must support the [`sp_genesis_builder::GenesisBuilder`] API to
 //! utilize either `patch` or `full` formats.
 //!
+//! Runtimes implementing version 2 of the [`sp_genesis_builder::GenesisBuilder`] API may also
+//! provide named genesis config presets (e.g. `development` or `local_testnet`). The
+//! [`ChainSpecBuilder::with_genesis_config_preset_name`] method fetches the preset's patch from the
+//! runtime and uses it as the `patch` genesis field.
+//!
 //! This entire process is encapsulated within the implementation of the [`BuildStorage`] trait,
 //! which can be accessed through the [`ChainSpec::as_storage_builder`] method. There is an
 //! intermediate internal helper that facilitates this interaction,
@@ -109,6 +109,16 @@ impl Into<sc_tracing::TracingReceiver> for TracingReceiver {
 	}
 }
 
+/// The format of the log output.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum LogFormat {
+	/// Human readable lines.
+	Text,
+	/// One JSON object per line, including the fields of the enclosing spans.
+	Json,
+}
+
 /// The type of the node key.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
 #[value(rename_all = "kebab-case")]
@@ -251,3 +261,23 @@ impl Into<sc_network::config::SyncMode> for SyncMode {
 		}
 	}
 }
+
+/// Type of the transaction pool.
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq)]
+#[value(rename_all = "kebab-case")]
+pub enum TransactionPoolType {
+	/// Keep a single validated state following the best block.
+	SingleState,
+	/// Keep a validated view for every fork.
+	ForkAware,
+}
+
+impl Into<sc_service::config::TransactionPoolType> for TransactionPoolType {
+	fn into(self) -> sc_service::config::TransactionPoolType {
+		match self {
+			TransactionPoolType::SingleState =>
+				sc_service::config::TransactionPoolType::SingleState,
+			TransactionPoolType::ForkAware => sc_service::config::TransactionPoolType::ForkAware,
+		}
+	}
+}
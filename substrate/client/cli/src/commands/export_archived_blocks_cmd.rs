@@ -0,0 +1,96 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{GenericNumber, PruningParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use log::info;
+use sc_client_db::BlocksArchive;
+use std::{
+	fmt::Debug,
+	fs,
+	io::{self, BufWriter},
+	path::PathBuf,
+};
+
+/// The `export-archived-blocks` command used to export blocks from a blocks archive.
+///
+/// The output uses the binary format read by `import-blocks --binary`.
+#[derive(Debug, Clone, Parser)]
+pub struct ExportArchivedBlocksCmd {
+	/// Output file name or stdout if unspecified.
+	#[arg()]
+	pub output: Option<PathBuf>,
+
+	/// Specify starting block number.
+	/// Default is 1.
+	#[arg(long, value_name = "BLOCK")]
+	pub from: Option<GenericNumber>,
+
+	/// Specify last block number.
+	/// Default is the last archived block.
+	#[arg(long, value_name = "BLOCK")]
+	pub to: Option<GenericNumber>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+}
+
+impl ExportArchivedBlocksCmd {
+	/// Run the export-archived-blocks command
+	pub fn run(&self, blocks_archive: Option<PathBuf>) -> error::Result<()> {
+		let path = blocks_archive.ok_or_else(|| {
+			error::Error::Input(
+				"The blocks archive must be specified with `--blocks-archive`".into(),
+			)
+		})?;
+		let archive = BlocksArchive::open(&path)?;
+
+		let from = self.from.as_ref().map(|f| f.parse()).transpose()?.unwrap_or(1u64);
+		let to = self.to.as_ref().map(|t| t.parse()).transpose()?.unwrap_or(u64::MAX);
+		if to < from {
+			return Err(error::Error::Input("Invalid block range specified".into()))
+		}
+
+		let output: Box<dyn io::Write> = match &self.output {
+			Some(filename) => Box::new(BufWriter::new(fs::File::create(filename)?)),
+			None => Box::new(BufWriter::new(io::stdout())),
+		};
+		let count = archive.export(from, to, output)?;
+		info!("Exported {} archived blocks from {}", count, path.display());
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportArchivedBlocksCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+}
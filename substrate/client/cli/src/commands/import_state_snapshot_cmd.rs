@@ -0,0 +1,135 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{ImportParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use sc_client_api::HeaderBackend;
+use sc_service::chain_ops::{import_state_snapshot, StateSnapshot};
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	fmt::Debug,
+	fs,
+	io::{self, BufReader, Read},
+	path::PathBuf,
+	str::FromStr,
+	sync::Arc,
+};
+
+/// The `import-state-snapshot` command used to bootstrap a fresh database from a state
+/// snapshot created by `export-state-snapshot`.
+#[derive(Debug, Parser)]
+pub struct ImportStateSnapshotCmd {
+	/// Input file or stdin if unspecified.
+	#[arg()]
+	pub input: Option<PathBuf>,
+
+	/// Hash of a trusted block the header chain of the snapshot must contain.
+	///
+	/// Required unless `--unsafe-trust-snapshot` is passed.
+	#[arg(long, value_name = "HASH", required_unless_present = "unsafe_trust_snapshot")]
+	pub trusted_hash: Option<String>,
+
+	/// Import the snapshot without a trusted block hash.
+	///
+	/// The snapshot is then only checked for consistency and trusted as is, so only use it
+	/// for snapshots of a trusted origin.
+	#[arg(long, conflicts_with = "trusted_hash")]
+	pub unsafe_trust_snapshot: bool,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub import_params: ImportParams,
+}
+
+impl ImportStateSnapshotCmd {
+	/// Run the `import-state-snapshot` command
+	pub async fn run<B, C, IQ>(&self, client: Arc<C>, import_queue: IQ) -> error::Result<()>
+	where
+		C: HeaderBackend<B> + Send + Sync + 'static,
+		B: BlockT,
+		IQ: sc_service::ImportQueue<B> + 'static,
+		<B::Hash as FromStr>::Err: Debug,
+	{
+		let trusted_hash = self
+			.trusted_hash
+			.as_ref()
+			.map(|hash| {
+				hash.trim_start_matches("0x")
+					.parse::<B::Hash>()
+					.map_err(|e| error::Error::Input(format!("Invalid trusted hash: {e:?}")))
+			})
+			.transpose()?;
+
+		let input: Box<dyn Read> = match &self.input {
+			Some(filename) => Box::new(BufReader::new(fs::File::open(filename)?)),
+			None => Box::new(io::stdin()),
+		};
+		let snapshot = StateSnapshot::<B>::read_from(input)?;
+
+		import_state_snapshot(client, import_queue, snapshot, trusted_hash)
+			.await
+			.map_err(Into::into)
+	}
+}
+
+impl CliConfiguration for ImportStateSnapshotCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn trusted_hash_is_required_unless_unsafe() {
+		let hash = format!("0x{}", "00".repeat(32));
+
+		assert!(ImportStateSnapshotCmd::try_parse_from(["import-state-snapshot"]).is_err());
+		assert!(ImportStateSnapshotCmd::try_parse_from([
+			"import-state-snapshot",
+			"--trusted-hash",
+			&hash,
+			"--unsafe-trust-snapshot",
+		])
+		.is_err());
+
+		let cmd =
+			ImportStateSnapshotCmd::parse_from(["import-state-snapshot", "--trusted-hash", &hash]);
+		assert_eq!(cmd.trusted_hash, Some(hash));
+		let cmd = ImportStateSnapshotCmd::parse_from([
+			"import-state-snapshot",
+			"--unsafe-trust-snapshot",
+		]);
+		assert!(cmd.unsafe_trust_snapshot);
+		assert!(cmd.trusted_hash.is_none());
+	}
+}
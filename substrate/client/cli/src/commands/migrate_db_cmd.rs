@@ -0,0 +1,148 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{DatabaseParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use sc_service::DatabaseSource;
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	fmt::Debug,
+	path::{Path, PathBuf},
+};
+
+/// The `migrate-db` command used to copy a database into a new database using the other backend,
+/// RocksDB or ParityDB.
+///
+/// The migration runs while the node is stopped. All the columns of a RocksDB database are
+/// copied to ParityDB. ParityDB can not list the keys of its columns, so a ParityDB database is
+/// copied to RocksDB by following its finalized chain: unfinalized blocks and offchain storage are
+/// left behind, and only the auxiliary data whose keys are known is copied.
+#[derive(Debug, Clone, Parser)]
+pub struct MigrateDbCmd {
+	/// Path of the database to create.
+	///
+	/// An interrupted migration is resumed by running the command again with the same path.
+	#[arg(long, value_name = "PATH")]
+	pub target_path: PathBuf,
+
+	/// Number of finalized blocks whose state root is compared between both databases once the
+	/// migration is complete.
+	#[arg(long, value_name = "COUNT", default_value_t = 16)]
+	pub verify_samples: u32,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl MigrateDbCmd {
+	/// Run the `migrate-db` command.
+	///
+	/// `aux_keys` returns the keys of the auxiliary data to copy from a ParityDB database, given
+	/// the hash of its finalized block, such as the keys used by the consensus engines.
+	pub fn run<B: BlockT>(
+		&self,
+		database_config: &DatabaseSource,
+		aux_keys: impl Fn(&B::Hash) -> Vec<Vec<u8>>,
+	) -> error::Result<()> {
+		let (source, to_paritydb) = match database_config {
+			#[cfg(feature = "rocksdb")]
+			DatabaseSource::RocksDb { path, .. } => (path, true),
+			DatabaseSource::ParityDb { path } => (path, false),
+			DatabaseSource::Auto { paritydb_path, rocksdb_path, .. } =>
+				match (rocksdb_path.exists(), paritydb_path.exists()) {
+					(true, false) => (rocksdb_path, true),
+					(false, true) => (paritydb_path, false),
+					_ =>
+						return Err(error::Error::Input(
+							"Select the database to migrate with `--database`".into(),
+						)),
+				},
+			DatabaseSource::Custom { .. } =>
+				return Err(error::Error::Input(
+					"Cannot migrate custom database implementation".into(),
+				)),
+		};
+
+		if source == &self.target_path {
+			return Err(error::Error::Input("Target path must differ from the database path".into()))
+		}
+		self.migrate::<B>(source, to_paritydb, aux_keys)
+	}
+
+	#[cfg(feature = "rocksdb")]
+	fn migrate<B: BlockT>(
+		&self,
+		source: &Path,
+		to_paritydb: bool,
+		aux_keys: impl Fn(&B::Hash) -> Vec<Vec<u8>>,
+	) -> error::Result<()> {
+		use sc_client_db::MigrationDirection;
+
+		let direction = if to_paritydb {
+			sc_client_db::migrate_rocksdb_to_paritydb::<B>(source, &self.target_path)?;
+			MigrationDirection::RocksDbToParityDb
+		} else {
+			sc_client_db::migrate_paritydb_to_rocksdb::<B>(source, &self.target_path, aux_keys)?;
+			MigrationDirection::ParityDbToRocksDb
+		};
+		let verified = sc_client_db::verify_migration::<B>(
+			direction,
+			source,
+			&self.target_path,
+			self.verify_samples,
+		)?;
+		println!(
+			"Migrated {:?} to {:?}: best and finalized blocks match, {} state roots verified.",
+			source, self.target_path, verified,
+		);
+		println!(
+			"Start the node with `--database {}` and the migrated database in place.",
+			if to_paritydb { "paritydb" } else { "rocksdb" },
+		);
+		Ok(())
+	}
+
+	#[cfg(not(feature = "rocksdb"))]
+	fn migrate<B: BlockT>(
+		&self,
+		_source: &Path,
+		_to_paritydb: bool,
+		_aux_keys: impl Fn(&B::Hash) -> Vec<Vec<u8>>,
+	) -> error::Result<()> {
+		Err(error::Error::Input("`rocksdb` feature not enabled, database can not be opened".into()))
+	}
+}
+
+impl CliConfiguration for MigrateDbCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
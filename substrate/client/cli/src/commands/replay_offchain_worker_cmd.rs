@@ -0,0 +1,160 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	error,
+	params::{BlockNumberOrHash, DatabaseParams, PruningParams, SharedParams},
+	CliConfiguration,
+};
+use clap::Parser;
+use log::{info, warn};
+use sc_client_api::{HeaderBackend, UsageProvider};
+use sc_offchain::{
+	replay::{replay, HttpSource, ReplayOptions},
+	OffchainWorkerApi,
+};
+use sp_api::ProvideRuntimeApi;
+use sp_keystore::KeystorePtr;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use std::{
+	fmt::Debug,
+	fs,
+	io::{self, BufReader, BufWriter},
+	path::PathBuf,
+	str::FromStr,
+	sync::Arc,
+};
+
+/// The `replay-offchain-worker` command used to run the offchain worker of a block in isolation.
+///
+/// Everything the offchain worker does is written to a JSON report: offchain storage writes,
+/// HTTP requests and submitted transactions. Nothing is written to the database and no
+/// transaction is actually submitted.
+#[derive(Debug, Clone, Parser)]
+pub struct ReplayOffchainWorkerCmd {
+	/// Block hash or number.
+	/// Default is the best block.
+	#[arg(value_name = "HASH or NUMBER")]
+	pub input: Option<BlockNumberOrHash>,
+
+	/// Serve HTTP responses from the given fixture file.
+	///
+	/// Requests without a response in the fixture fail. If neither this nor `--record-http` is
+	/// given, all HTTP requests fail.
+	#[arg(long, value_name = "PATH", conflicts_with = "record_http")]
+	pub http_fixture: Option<PathBuf>,
+
+	/// Perform real HTTP requests and record them into the given fixture file.
+	#[arg(long, value_name = "PATH")]
+	pub record_http: Option<PathBuf>,
+
+	/// Output file name of the report or stdout if unspecified.
+	#[arg(long, short, value_name = "PATH")]
+	pub output: Option<PathBuf>,
+
+	/// Run the offchain worker as if the node was a validator.
+	#[arg(long)]
+	pub as_validator: bool,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl ReplayOffchainWorkerCmd {
+	/// Run the replay-offchain-worker command
+	pub async fn run<B, BA, C>(
+		&self,
+		client: Arc<C>,
+		backend: Arc<BA>,
+		keystore: Option<KeystorePtr>,
+	) -> error::Result<()>
+	where
+		B: BlockT,
+		BA: sc_client_api::backend::Backend<B>,
+		BA::OffchainStorage: 'static,
+		C: ProvideRuntimeApi<B> + HeaderBackend<B> + UsageProvider<B> + Send + Sync + 'static,
+		C::Api: OffchainWorkerApi<B>,
+		<B::Hash as FromStr>::Err: Debug,
+		<<B::Header as HeaderT>::Number as FromStr>::Err: Debug,
+	{
+		let block_id = self.input.as_ref().map(|b| b.parse()).transpose()?;
+		let hash = match block_id {
+			Some(id) => client.expect_block_hash_from_id(&id)?,
+			None => client.usage_info().chain.best_hash,
+		};
+
+		let http = match (&self.http_fixture, &self.record_http) {
+			(Some(path), _) => {
+				let file = BufReader::new(fs::File::open(path)?);
+				HttpSource::Fixture(serde_json::from_reader(file).map_err(|e| {
+					error::Error::Input(format!("Invalid HTTP fixture {}: {}", path.display(), e))
+				})?)
+			},
+			(None, Some(_)) => HttpSource::Network,
+			(None, None) => HttpSource::Fixture(Default::default()),
+		};
+		let options = ReplayOptions {
+			offchain_db: backend.offchain_storage(),
+			keystore,
+			is_validator: self.as_validator,
+			http,
+		};
+
+		info!("Replaying offchain workers at {:?}", hash);
+		let report = replay(client, hash, options).await?;
+		if let Some(error) = &report.error {
+			warn!("Offchain workers failed: {}", error);
+		}
+
+		if let Some(path) = &self.record_http {
+			let file = BufWriter::new(fs::File::create(path)?);
+			serde_json::to_writer_pretty(file, &report.fixture()).map_err(io::Error::from)?;
+			info!("Recorded {} HTTP requests to {}", report.http_requests.len(), path.display());
+		}
+
+		let output: Box<dyn io::Write> = match &self.output {
+			Some(filename) => Box::new(BufWriter::new(fs::File::create(filename)?)),
+			None => Box::new(BufWriter::new(io::stdout())),
+		};
+		serde_json::to_writer_pretty(output, &report).map_err(io::Error::from)?;
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ReplayOffchainWorkerCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
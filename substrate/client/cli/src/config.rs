@@ -19,8 +19,10 @@
 //! Configuration trait for a CLI based on substrate
 
 use crate::{
-	arg_enums::Database, error::Result, DatabaseParams, ImportParams, KeystoreParams,
-	NetworkParams, NodeKeyParams, OffchainWorkerParams, PruningParams, SharedParams, SubstrateCli,
+	arg_enums::{Database, LogFormat},
+	error::Result,
+	DatabaseParams, ImportParams, KeystoreParams, NetworkParams, NodeKeyParams,
+	OffchainWorkerParams, PruningParams, SharedParams, SubstrateCli,
 };
 use log::warn;
 use names::{Generator, Name};
@@ -261,6 +263,14 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			.unwrap_or_else(|| Ok(BlocksPruning::KeepFinalized))
 	}
 
+	/// Get the directory where blocks are archived before they are pruned.
+	///
+	/// By default this is retrieved from `PruningParams` if it is available. Otherwise its
+	/// `None`.
+	fn blocks_archive(&self) -> Result<Option<PathBuf>> {
+		Ok(self.pruning_params().and_then(|x| x.blocks_archive.clone()))
+	}
+
 	/// Get the chain ID (string).
 	///
 	/// By default this is retrieved from `SharedParams`.
@@ -404,6 +414,15 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(self.shared_params().tracing_receiver())
 	}
 
+	/// Returns the interval of the blocks measured for per-pallet runtime metrics, if they should
+	/// be exported.
+	///
+	/// By default this is retrieved from [`SharedParams`] if it is available. Otherwise its
+	/// `None`.
+	fn runtime_metrics(&self) -> Result<Option<u32>> {
+		Ok(self.shared_params().runtime_metrics())
+	}
+
 	/// Get the node key from the current object
 	///
 	/// By default this is retrieved from `NodeKeyParams` if it is available. Otherwise its
@@ -468,12 +487,18 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		let keystore = self.keystore_config(&config_dir)?;
 		let telemetry_endpoints = self.telemetry_endpoints(&chain_spec)?;
 		let runtime_cache_size = self.runtime_cache_size()?;
+		let mut transaction_pool = self.transaction_pool(is_dev)?;
+		if let Some(journal) = transaction_pool.journal.as_mut() {
+			if journal.path.is_relative() {
+				journal.path = config_dir.join(&journal.path);
+			}
+		}
 
 		Ok(Configuration {
 			impl_name: C::impl_name(),
 			impl_version: C::impl_version(),
 			tokio_handle,
-			transaction_pool: self.transaction_pool(is_dev)?,
+			transaction_pool,
 			network: self.network_config(
 				&chain_spec,
 				is_dev,
@@ -490,6 +515,7 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			trie_cache_maximum_size: self.trie_cache_maximum_size()?,
 			state_pruning: self.state_pruning()?,
 			blocks_pruning: self.blocks_pruning()?,
+			blocks_archive: self.blocks_archive()?,
 			wasm_method: self.wasm_method()?,
 			wasm_runtime_overrides: self.wasm_runtime_overrides(),
 			rpc_addr: self.rpc_addr(DCV::rpc_listen_port())?,
@@ -536,6 +562,13 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		Ok(self.shared_params().detailed_log_output())
 	}
 
+	/// Format of the log output.
+	///
+	/// By default this is retrieved from `SharedParams`.
+	fn log_format(&self) -> Result<LogFormat> {
+		Ok(self.shared_params().log_format())
+	}
+
 	/// Is log reloading enabled?
 	fn enable_log_reloading(&self) -> Result<bool> {
 		Ok(self.shared_params().enable_log_reloading())
@@ -589,7 +622,8 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 		let mut logger = LoggerBuilder::new(self.log_filters()?);
 		logger
 			.with_log_reloading(self.enable_log_reloading()?)
-			.with_detailed_output(self.detailed_log_output()?);
+			.with_detailed_output(self.detailed_log_output()?)
+			.with_runtime_metrics(self.runtime_metrics()?);
 
 		if let Some(tracing_targets) = self.tracing_targets()? {
 			let tracing_receiver = self.tracing_receiver()?;
@@ -600,6 +634,10 @@ pub trait CliConfiguration<DCV: DefaultConfigurationValues = ()>: Sized {
 			logger.with_colors(false);
 		}
 
+		if self.log_format()? == LogFormat::Json {
+			logger.with_json_output(true);
+		}
+
 		// Call hook for custom profiling setup.
 		logger_hook(&mut logger, config);
 
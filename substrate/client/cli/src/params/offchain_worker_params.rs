@@ -25,7 +25,7 @@
 
 use clap::{ArgAction, Args};
 use sc_network::config::Role;
-use sc_service::config::OffchainWorkerConfig;
+use sc_service::config::{OffchainHttpPolicy, OffchainWorkerConfig};
 
 use crate::{error, OffchainWorkerEnabled};
 
@@ -47,6 +47,25 @@ pub struct OffchainWorkerParams {
 	/// Allows the runtime to write directly to offchain workers DB during block import.
 	#[arg(long = "enable-offchain-indexing", value_name = "ENABLE_OFFCHAIN_INDEXING", default_value_t = false, action = ArgAction::Set)]
 	pub indexing_enabled: bool,
+
+	/// Host allowed to be contacted by offchain worker HTTP requests.
+	///
+	/// Either an exact host name or a wildcard matching all subdomains, e.g. `*.example.com`.
+	/// Can be passed multiple times. If not given, all hosts are allowed.
+	#[arg(long = "offchain-http-allow", value_name = "HOST_PATTERN")]
+	pub http_allowed_hosts: Vec<String>,
+
+	/// Maximum number of HTTP requests an offchain worker may have open at the same time.
+	#[arg(long = "offchain-http-max-concurrent", value_name = "COUNT")]
+	pub http_max_concurrent_requests: Option<usize>,
+
+	/// Maximum size of offchain worker HTTP request and response bodies.
+	#[arg(long = "offchain-http-max-body-size", value_name = "BYTES")]
+	pub http_max_body_size: Option<usize>,
+
+	/// Maximum number of HTTP requests an offchain worker may start per block.
+	#[arg(long = "offchain-http-max-requests-per-block", value_name = "COUNT")]
+	pub http_max_requests_per_block: Option<usize>,
 }
 
 impl OffchainWorkerParams {
@@ -60,6 +79,12 @@ impl OffchainWorkerParams {
 		};
 
 		let indexing_enabled = self.indexing_enabled;
-		Ok(OffchainWorkerConfig { enabled, indexing_enabled })
+		let http_policy = OffchainHttpPolicy {
+			allowed_hosts: self.http_allowed_hosts.clone(),
+			max_concurrent_requests: self.http_max_concurrent_requests,
+			max_body_size: self.http_max_body_size,
+			max_requests_per_block: self.http_max_requests_per_block,
+		};
+		Ok(OffchainWorkerConfig { enabled, indexing_enabled, http_policy })
 	}
 }
@@ -19,6 +19,7 @@
 use crate::error;
 use clap::Args;
 use sc_service::{BlocksPruning, PruningMode};
+use std::{path::PathBuf, time::Duration};
 
 /// Parameters to define the pruning mode
 #[derive(Debug, Clone, Args)]
@@ -34,9 +35,29 @@ pub struct PruningParams {
 	///  - archive: Keep the state of all blocks.
 	///  - 'archive-canonical' Keep only the state of finalized blocks.
 	///  - number Keep the state of the last number of finalized blocks.
+	///  - duration, e.g. '30d' or '12h': Keep the state of the blocks finalized within the given
+	///    number of days or hours.
 	/// [default: 256]
 	#[arg(alias = "pruning", long, value_name = "PRUNING_MODE")]
-	pub state_pruning: Option<DatabasePruningMode>,
+	pub state_pruning: Option<StatePruningMode>,
+
+	/// Prune the state of the oldest finalized blocks once the trie nodes they inserted exceed the
+	/// given size, e.g. '500GiB' or '800MiB'.
+	///
+	/// This is the amount of state written by the blocks kept, not the size of the database:
+	/// nodes shared with older states, database overhead and compression are not accounted for.
+	/// Combined with a number or duration `--state-pruning` mode, the state is pruned as soon as
+	/// either limit is reached.
+	#[arg(long, value_name = "SIZE", value_parser = parse_size)]
+	pub state_pruning_max_inserted: Option<u64>,
+
+	/// Keep the state of the given finalized blocks while pruning the rest.
+	///
+	/// Accepts a comma separated list of block numbers, e.g. era boundaries. Can't be used
+	/// with an archive state pruning mode and requires a database with reference counting
+	/// support, such as ParityDb.
+	#[arg(long, value_name = "BLOCK", value_delimiter = ',')]
+	pub state_pruning_keep: Vec<u64>,
 
 	/// Specify the blocks pruning mode.
 	///
@@ -54,12 +75,42 @@ pub struct PruningParams {
 		default_value = "archive-canonical"
 	)]
 	pub blocks_pruning: DatabasePruningMode,
+
+	/// Archive the bodies and justifications of finalized blocks before they are pruned.
+	///
+	/// Blocks are appended to segment files in the given directory. The archived blocks can be
+	/// exported with the `export-archived-blocks` subcommand, in the format read by
+	/// `import-blocks --binary`.
+	#[arg(long, value_name = "PATH")]
+	pub blocks_archive: Option<PathBuf>,
 }
 
 impl PruningParams {
 	/// Get the pruning value from the parameters
 	pub fn state_pruning(&self) -> error::Result<Option<PruningMode>> {
-		Ok(self.state_pruning.map(|v| v.into()))
+		let mode = self.state_pruning.map(|v| v.into());
+		if self.state_pruning_max_inserted.is_none() && self.state_pruning_keep.is_empty() {
+			return Ok(mode)
+		}
+
+		let mode = match (mode, self.state_pruning_max_inserted) {
+			(None, Some(max_bytes)) => PruningMode::inserted_bytes_pruning(max_bytes),
+			(mode, _) => mode.unwrap_or_default(),
+		};
+		match mode {
+			PruningMode::Constrained(mut constraints) => {
+				if let Some(max_bytes) = self.state_pruning_max_inserted {
+					constraints.max_inserted_bytes = Some(max_bytes);
+				}
+				constraints.keep_blocks = self.state_pruning_keep.clone();
+				Ok(Some(PruningMode::Constrained(constraints)))
+			},
+			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => Err(error::Error::Input(
+				"`--state-pruning-max-inserted` and `--state-pruning-keep` can't be used with an \
+				 archive state pruning mode"
+					.into(),
+			)),
+		}
 	}
 
 	/// Get the block pruning value from the parameters
@@ -98,6 +149,77 @@ impl std::str::FromStr for DatabasePruningMode {
 	}
 }
 
+/// Specifies the state pruning mode of the database.
+///
+/// In addition to the modes of [`DatabasePruningMode`], the state may be pruned by age.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatePruningMode {
+	/// Keep the state of all blocks.
+	Archive,
+	/// Keep only the state of finalized blocks.
+	ArchiveCanonical,
+	/// Keep the state of the last number of finalized blocks.
+	Custom(u32),
+	/// Keep the state of the blocks finalized within the given duration.
+	Age(Duration),
+}
+
+impl std::str::FromStr for StatePruningMode {
+	type Err = String;
+
+	fn from_str(input: &str) -> Result<Self, Self::Err> {
+		const UNITS: [(&str, u64); 2] = [("d", 24 * 60 * 60), ("h", 60 * 60)];
+
+		match parse_with_units(input, &UNITS) {
+			Some(secs) => secs
+				.map(|secs| Self::Age(Duration::from_secs(secs)))
+				.ok_or_else(|| "Invalid pruning mode specified".to_string()),
+			None => input.parse::<DatabasePruningMode>().map(Into::into),
+		}
+	}
+}
+
+/// Parse a size with a `GiB`, `MiB` or `KiB` suffix into a number of bytes.
+fn parse_size(input: &str) -> Result<u64, String> {
+	const UNITS: [(&str, u64); 3] =
+		[("GiB", 1024 * 1024 * 1024), ("MiB", 1024 * 1024), ("KiB", 1024)];
+
+	parse_with_units(input, &UNITS)
+		.flatten()
+		.ok_or_else(|| format!("Invalid size {input:?}, expected e.g. '500GiB' or '800MiB'"))
+}
+
+/// Parse a number followed by one of the `units` suffixes, multiplied by the unit.
+///
+/// Returns `None` if `input` has none of the suffixes, and `Some(None)` if the number is invalid.
+fn parse_with_units(input: &str, units: &[(&str, u64)]) -> Option<Option<u64>> {
+	units.iter().find_map(|(suffix, multiplier)| {
+		let value = input.strip_suffix(suffix)?;
+		Some(value.parse::<u64>().ok().and_then(|value| value.checked_mul(*multiplier)))
+	})
+}
+
+impl From<DatabasePruningMode> for StatePruningMode {
+	fn from(mode: DatabasePruningMode) -> Self {
+		match mode {
+			DatabasePruningMode::Archive => Self::Archive,
+			DatabasePruningMode::ArchiveCanonical => Self::ArchiveCanonical,
+			DatabasePruningMode::Custom(n) => Self::Custom(n),
+		}
+	}
+}
+
+impl Into<PruningMode> for StatePruningMode {
+	fn into(self) -> PruningMode {
+		match self {
+			StatePruningMode::Archive => PruningMode::ArchiveAll,
+			StatePruningMode::ArchiveCanonical => PruningMode::ArchiveCanonical,
+			StatePruningMode::Custom(n) => PruningMode::blocks_pruning(n),
+			StatePruningMode::Age(max_age) => PruningMode::age_pruning(max_age),
+		}
+	}
+}
+
 impl Into<PruningMode> for DatabasePruningMode {
 	fn into(self) -> PruningMode {
 		match self {
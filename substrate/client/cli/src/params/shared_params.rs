@@ -16,7 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::arg_enums::TracingReceiver;
+use crate::arg_enums::{LogFormat, TracingReceiver};
 use clap::Args;
 use sc_service::config::BasePath;
 use std::path::PathBuf;
@@ -68,6 +68,13 @@ pub struct SharedParams {
 	#[arg(long)]
 	pub disable_log_color: bool,
 
+	/// Format of the log output.
+	///
+	/// `json` prints every log line as a JSON object with the timestamp, level, target, the fields
+	/// of the event and of the spans it was emitted in, and the block being imported if any.
+	#[arg(long, value_name = "FORMAT", value_enum, ignore_case = true, default_value_t = LogFormat::Text)]
+	pub log_format: LogFormat,
+
 	/// Enable feature to dynamically update and reload the log filter.
 	///
 	/// Be aware that enabling this feature can lead to a performance decrease up to factor six or
@@ -84,6 +91,24 @@ pub struct SharedParams {
 	#[arg(long, value_name = "TARGETS")]
 	pub tracing_targets: Option<String>,
 
+	/// Export the time spent in each pallet call and hook while importing blocks, together with
+	/// the declared and the actual weight of the calls, as Prometheus metrics.
+	///
+	/// The runtime needs to be built with the `with-tracing` feature. Tracing the runtime slows
+	/// down the import of the measured blocks, see `--runtime-metrics-interval`.
+	#[arg(long)]
+	pub runtime_metrics: bool,
+
+	/// Only measure the import of every n-th block for `--runtime-metrics`.
+	#[arg(
+		long,
+		value_name = "BLOCKS",
+		default_value_t = 10,
+		requires = "runtime_metrics",
+		value_parser = clap::value_parser!(u32).range(1..),
+	)]
+	pub runtime_metrics_interval: u32,
+
 	/// Receiver to process tracing messages.
 	#[arg(long, value_name = "RECEIVER", value_enum, ignore_case = true, default_value_t = TracingReceiver::Log)]
 	pub tracing_receiver: TracingReceiver,
@@ -133,6 +158,11 @@ impl SharedParams {
 		self.disable_log_color
 	}
 
+	/// Format of the log output.
+	pub fn log_format(&self) -> LogFormat {
+		self.log_format
+	}
+
 	/// Is log reloading enabled
 	pub fn enable_log_reloading(&self) -> bool {
 		self.enable_log_reloading
@@ -147,4 +177,9 @@ impl SharedParams {
 	pub fn tracing_targets(&self) -> Option<String> {
 		self.tracing_targets.clone()
 	}
+
+	/// Interval of the blocks measured for the per-pallet runtime metrics, if they are exported.
+	pub fn runtime_metrics(&self) -> Option<u32> {
+		self.runtime_metrics.then_some(self.runtime_metrics_interval)
+	}
 }
@@ -16,12 +16,26 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use crate::arg_enums::TransactionPoolType;
 use clap::Args;
-use sc_service::config::TransactionPoolOptions;
+use sc_service::config::{
+	TransactionPoolJournalOptions, TransactionPoolOptions, TransactionPoolSenderLimits,
+};
+use sp_runtime::Percent;
 
 /// Parameters used to create the pool configuration.
 #[derive(Debug, Clone, Args)]
 pub struct TransactionPoolParams {
+	/// The type of the transaction pool.
+	#[arg(
+		long,
+		value_name = "TYPE",
+		value_enum,
+		ignore_case = true,
+		default_value_t = TransactionPoolType::SingleState,
+	)]
+	pub pool_type: TransactionPoolType,
+
 	/// Maximum number of transactions in the transaction pool.
 	#[arg(long, value_name = "COUNT", default_value_t = 8192)]
 	pub pool_limit: usize,
@@ -35,6 +49,66 @@ pub struct TransactionPoolParams {
 	/// If it is considered invalid. Defaults to 1800s.
 	#[arg(long, value_name = "SECONDS")]
 	pub tx_ban_seconds: Option<u64>,
+
+	/// Keep a journal of pending transactions on disk and re-import them after a restart.
+	///
+	/// The journal is stored in the chain's data directory.
+	#[arg(long)]
+	pub pool_journal: bool,
+
+	/// Maximum age of the transactions restored from the journal.
+	///
+	/// Transactions that entered the pool longer ago are dropped from the journal.
+	#[arg(long, value_name = "SECONDS", default_value_t = 10800)]
+	pub pool_journal_max_age: u64,
+
+	/// Maximum number of kilobytes of transactions stored in the journal.
+	#[arg(long, value_name = "KB", default_value_t = 10240)]
+	pub pool_journal_kbytes: usize,
+
+	/// Minimal priority increase (in percent) required to replace a transaction in the pool.
+	///
+	/// With the default of 0 any higher priority is enough.
+	#[arg(long, value_name = "PERCENT", default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=100))]
+	pub pool_replacement_bump: u8,
+
+	/// Maximum number of ready transactions of a single sender.
+	///
+	/// Enables per-sender limits. The sender is identified by the nonce tag the transaction
+	/// provides, see `--pool-sender-account-id-bytes` and `--pool-sender-nonce-bytes`.
+	#[arg(long, value_name = "COUNT")]
+	pub pool_sender_ready_limit: Option<usize>,
+
+	/// Maximum number of future transactions of a single sender.
+	///
+	/// Only used together with `--pool-sender-ready-limit`. Defaults to a quarter of it.
+	#[arg(long, value_name = "COUNT")]
+	pub pool_sender_future_limit: Option<usize>,
+
+	/// Length of the encoded account id of the runtime, used to find the sender of a transaction.
+	///
+	/// The sender limits only apply to transactions providing a tag of exactly
+	/// `--pool-sender-account-id-bytes` + `--pool-sender-nonce-bytes` bytes, as added by FRAME's
+	/// `CheckNonce` extension, and the sender is the account id the tag starts with.
+	#[arg(long, value_name = "BYTES", default_value_t = 32)]
+	pub pool_sender_account_id_bytes: usize,
+
+	/// Length of the encoded nonce of the runtime, used to find the sender of a transaction.
+	#[arg(long, value_name = "BYTES", default_value_t = 4)]
+	pub pool_sender_nonce_bytes: usize,
+
+	/// Number of ready transactions reserved for transactions submitted to this node.
+	///
+	/// Transactions received from the network can't use the reserved part of the pool, while
+	/// the ones submitted via RPC or by offchain workers can.
+	#[arg(long, value_name = "COUNT", default_value_t = 0)]
+	pub pool_local_reserved: usize,
+
+	/// Number of kilobytes of ready transactions reserved for transactions submitted to this node.
+	///
+	/// Defaults to the share of `--pool-kbytes` proportional to `--pool-local-reserved`.
+	#[arg(long, value_name = "KB")]
+	pub pool_local_reserved_kbytes: Option<usize>,
 }
 
 impl TransactionPoolParams {
@@ -42,6 +116,8 @@ impl TransactionPoolParams {
 	pub fn transaction_pool(&self, is_dev: bool) -> TransactionPoolOptions {
 		let mut opts = TransactionPoolOptions::default();
 
+		opts.pool_type = self.pool_type.into();
+
 		// ready queue
 		opts.ready.count = self.pool_limit;
 		opts.ready.total_bytes = self.pool_kbytes * 1024;
@@ -59,6 +135,33 @@ impl TransactionPoolParams {
 			std::time::Duration::from_secs(30 * 60)
 		};
 
+		if self.pool_journal {
+			opts.journal = Some(TransactionPoolJournalOptions {
+				max_age: std::time::Duration::from_secs(self.pool_journal_max_age),
+				max_bytes: self.pool_journal_kbytes * 1024,
+				..Default::default()
+			});
+		}
+
+		opts.replacement_bump = Percent::from_percent(self.pool_replacement_bump);
+
+		opts.sender_limits =
+			self.pool_sender_ready_limit.map(|ready| TransactionPoolSenderLimits {
+				ready,
+				future: self.pool_sender_future_limit.unwrap_or(ready / 4),
+				account_id_len: self.pool_sender_account_id_bytes,
+				nonce_len: self.pool_sender_nonce_bytes,
+			});
+
+		opts.local_reserved.count = self.pool_local_reserved.min(self.pool_limit);
+		// unless given, reserve the same share of the ready queue size as of its count
+		let local_reserved_kbytes = self.pool_local_reserved_kbytes.unwrap_or_else(|| {
+			(self.pool_kbytes * opts.local_reserved.count)
+				.checked_div(self.pool_limit)
+				.unwrap_or(0)
+		});
+		opts.local_reserved.total_bytes = local_reserved_kbytes.min(self.pool_kbytes) * 1024;
+
 		opts
 	}
 }
@@ -251,6 +251,7 @@ mod tests {
 				trie_cache_maximum_size: None,
 				state_pruning: None,
 				blocks_pruning: sc_client_db::BlocksPruning::KeepAll,
+				blocks_archive: None,
 				chain_spec: Box::new(
 					GenericChainSpec::<()>::builder(Default::default(), NoExtension::None)
 						.with_name("test")
@@ -135,6 +135,16 @@ pub fn load_block_weight<H: Encode, B: AuxStore>(
 	load_decode(backend, block_weight_key(block_hash).as_slice())
 }
 
+/// The keys of the data BABE stores in the aux storage that is needed to build on `best_hash`:
+/// the epoch changes and the weight of `best_hash`.
+pub fn aux_keys<H: Encode>(best_hash: H) -> Vec<Vec<u8>> {
+	vec![
+		BABE_EPOCH_CHANGES_VERSION.to_vec(),
+		BABE_EPOCH_CHANGES_KEY.to_vec(),
+		block_weight_key(best_hash),
+	]
+}
+
 #[cfg(test)]
 mod test {
 	use super::*;
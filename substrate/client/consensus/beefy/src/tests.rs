@@ -55,9 +55,9 @@ use sp_consensus_beefy::{
 	ecdsa_crypto::{AuthorityId, Signature},
 	known_payloads,
 	mmr::{find_mmr_root_digest, MmrRootProvider},
-	BeefyApi, Commitment, ConsensusLog, EquivocationProof, Keyring as BeefyKeyring, MmrRootHash,
-	OpaqueKeyOwnershipProof, Payload, SignedCommitment, ValidatorSet, ValidatorSetId,
-	VersionedFinalityProof, VoteMessage, BEEFY_ENGINE_ID,
+	BeefyApi, Commitment, ConsensusLog, EquivocationProof, ForkVotingProof, FutureBlockVotingProof,
+	Keyring as BeefyKeyring, MmrRootHash, OpaqueKeyOwnershipProof, Payload, SignedCommitment,
+	ValidatorSet, ValidatorSetId, VersionedFinalityProof, VoteMessage, BEEFY_ENGINE_ID,
 };
 use sp_core::H256;
 use sp_keystore::{testing::MemoryKeystore, Keystore, KeystorePtr};
@@ -68,7 +68,7 @@ use sp_runtime::{
 	BuildStorage, DigestItem, EncodedJustification, Justifications, Storage,
 };
 use std::{marker::PhantomData, sync::Arc, task::Poll};
-use substrate_test_runtime_client::{BlockBuilderExt, ClientExt};
+use substrate_test_runtime_client::{runtime::Header, BlockBuilderExt, ClientExt};
 use tokio::time::Duration;
 
 const GENESIS_HASH: H256 = H256::zero();
@@ -252,6 +252,10 @@ pub(crate) struct TestApi {
 	pub mmr_root_hash: MmrRootHash,
 	pub reported_equivocations:
 		Option<Arc<Mutex<Vec<EquivocationProof<NumberFor<Block>, AuthorityId, Signature>>>>>,
+	pub reported_fork_votings:
+		Option<Arc<Mutex<Vec<ForkVotingProof<Header, AuthorityId, Signature>>>>>,
+	pub reported_future_block_votings:
+		Option<Arc<Mutex<Vec<FutureBlockVotingProof<NumberFor<Block>, AuthorityId, Signature>>>>>,
 }
 
 impl TestApi {
@@ -265,6 +269,8 @@ impl TestApi {
 			validator_set: Some(validator_set.clone()),
 			mmr_root_hash,
 			reported_equivocations: None,
+			reported_fork_votings: None,
+			reported_future_block_votings: None,
 		}
 	}
 
@@ -274,11 +280,15 @@ impl TestApi {
 			validator_set: Some(validator_set.clone()),
 			mmr_root_hash: GOOD_MMR_ROOT,
 			reported_equivocations: None,
+			reported_fork_votings: None,
+			reported_future_block_votings: None,
 		}
 	}
 
 	pub fn allow_equivocations(&mut self) {
 		self.reported_equivocations = Some(Arc::new(Mutex::new(vec![])));
+		self.reported_fork_votings = Some(Arc::new(Mutex::new(vec![])));
+		self.reported_future_block_votings = Some(Arc::new(Mutex::new(vec![])));
 	}
 }
 
@@ -316,6 +326,35 @@ sp_api::mock_impl_runtime_apis! {
 			}
 		}
 
+		fn submit_report_fork_voting_unsigned_extrinsic(
+			proof: ForkVotingProof<Header, AuthorityId, Signature>,
+			_dummy: OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			if let Some(fork_votings_buf) = self.inner.reported_fork_votings.as_ref() {
+				fork_votings_buf.lock().push(proof);
+				None
+			} else {
+				panic!("Fork votings not expected, but following proof was reported: {:?}", proof);
+			}
+		}
+
+		fn submit_report_future_block_voting_unsigned_extrinsic(
+			proof: FutureBlockVotingProof<NumberFor<Block>, AuthorityId, Signature>,
+			_dummy: OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			if let Some(future_block_votings_buf) =
+				self.inner.reported_future_block_votings.as_ref()
+			{
+				future_block_votings_buf.lock().push(proof);
+				None
+			} else {
+				panic!(
+					"Future block votings not expected, but following proof was reported: {:?}",
+					proof
+				);
+			}
+		}
+
 		fn generate_key_ownership_proof(
 			_dummy1: ValidatorSetId,
 			_dummy2: AuthorityId,
@@ -329,7 +368,7 @@ sp_api::mock_impl_runtime_apis! {
 	}
 }
 
-fn add_mmr_digest(builder: &mut impl BlockBuilderExt, mmr_hash: MmrRootHash) {
+pub(crate) fn add_mmr_digest(builder: &mut impl BlockBuilderExt, mmr_hash: MmrRootHash) {
 	builder
 		.push_deposit_log_digest_item(DigestItem::Consensus(
 			BEEFY_ENGINE_ID,
@@ -1451,7 +1490,14 @@ async fn gossipped_finality_proofs() {
 
 	let (best_blocks, versioned_finality_proof) = get_beefy_streams(&mut net.lock(), peers.clone());
 	// Charlie gossips finality proof for #1 -> Alice and Bob also finalize.
-	let proof = crate::communication::gossip::tests::dummy_proof(1, &validator_set);
+	// The proof has to commit to the canonical MMR root, otherwise it would be
+	// reported as fork voting and dropped.
+	let header = net.lock().peer(2).client().as_client().expect_header(hashes[1]).unwrap();
+	let mmr_root = find_mmr_root_digest::<Block>(&header).unwrap();
+	let payload = Payload::from_single_entry(known_payloads::MMR_ROOT_ID, mmr_root.encode());
+	let commitment = Commitment { payload, block_number: 1, validator_set_id: validator_set.id() };
+	let signatures = validators.iter().map(|key| Some(sign_commitment(key, &commitment))).collect();
+	let proof = VersionedFinalityProof::V1(SignedCommitment { commitment, signatures });
 	let gossip_proof = GossipMessage::<Block>::FinalityProof(proof);
 	let encoded_proof = gossip_proof.encode();
 	charlie_gossip_engine.gossip_message(proofs_topic::<Block>(), encoded_proof, true);
@@ -36,14 +36,15 @@ use log::{debug, error, info, log_enabled, trace, warn};
 use sc_client_api::{Backend, FinalityNotification, FinalityNotifications, HeaderBackend};
 use sc_network_gossip::GossipEngine;
 use sc_utils::{mpsc::TracingUnboundedReceiver, notification::NotificationReceiver};
-use sp_api::ProvideRuntimeApi;
+use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_arithmetic::traits::{AtLeast32Bit, Saturating};
 use sp_consensus::SyncOracle;
 use sp_consensus_beefy::{
-	check_equivocation_proof,
+	check_equivocation_proof, check_fork_voting_proof, check_future_block_voting_proof,
 	ecdsa_crypto::{AuthorityId, Signature},
-	BeefyApi, Commitment, ConsensusLog, EquivocationProof, PayloadProvider, ValidatorSet,
-	VersionedFinalityProof, VoteMessage, BEEFY_ENGINE_ID,
+	BeefyApi, Commitment, ConsensusLog, EquivocationProof, ForkVotingProof, FutureBlockVotingProof,
+	OpaqueKeyOwnershipProof, PayloadProvider, ValidatorSet, ValidatorSetId, VersionedFinalityProof,
+	VoteMessage, BEEFY_ENGINE_ID,
 };
 use sp_runtime::{
 	generic::{BlockId, OpaqueDigestItemId},
@@ -505,7 +506,10 @@ where
 	) -> Result<(), Error> {
 		let block_num = vote.commitment.block_number;
 		match self.voting_oracle().triage_round(block_num)? {
-			RoundAction::Process =>
+			RoundAction::Process => {
+				if self.check_vote_against_canonical_chain(&vote)? {
+					return Ok(())
+				}
 				if let Some(finality_proof) = self.handle_vote(vote)? {
 					let gossip_proof = GossipMessage::<B>::FinalityProof(finality_proof);
 					let encoded_proof = gossip_proof.encode();
@@ -514,9 +518,13 @@ where
 						encoded_proof,
 						true,
 					);
-				},
+				}
+			},
 			RoundAction::Drop => metric_inc!(self, beefy_stale_votes),
-			RoundAction::Enqueue => error!(target: LOG_TARGET, "🥩 unexpected vote: {:?}.", vote),
+			RoundAction::Enqueue =>
+				if !self.check_vote_for_future_block(&vote)? {
+					error!(target: LOG_TARGET, "🥩 unexpected vote: {:?}.", vote)
+				},
 		};
 		Ok(())
 	}
@@ -534,11 +542,30 @@ where
 		let block_num = signed_commitment.commitment.block_number;
 		match self.voting_oracle().triage_round(block_num)? {
 			RoundAction::Process => {
+				if self.check_justification_votes(
+					&justification,
+					Self::check_vote_against_canonical_chain,
+				)? {
+					debug!(
+						target: LOG_TARGET,
+						"🥩 Drop misbehaving justification for round: {:?}.", block_num
+					);
+					return Ok(())
+				}
 				debug!(target: LOG_TARGET, "🥩 Process justification for round: {:?}.", block_num);
 				metric_inc!(self, beefy_imported_justifications);
 				self.finalize(justification)?
 			},
 			RoundAction::Enqueue => {
+				if self
+					.check_justification_votes(&justification, Self::check_vote_for_future_block)?
+				{
+					debug!(
+						target: LOG_TARGET,
+						"🥩 Drop misbehaving justification for round: {:?}.", block_num
+					);
+					return Ok(())
+				}
 				debug!(target: LOG_TARGET, "🥩 Buffer justification for round: {:?}.", block_num);
 				if self.pending_justifications.len() < MAX_BUFFERED_JUSTIFICATIONS {
 					self.pending_justifications.entry(block_num).or_insert(justification);
@@ -946,6 +973,129 @@ where
 		(error, self.comms)
 	}
 
+	/// Check `vote` against the canonical chain and report it if it targets a finalized block
+	/// with a payload that doesn't match the MMR root of the canonical block at that height.
+	///
+	/// Only meant for votes triaged for processing. Returns `true` if the vote is such
+	/// misbehavior, in which case it shouldn't be processed any further.
+	fn check_vote_against_canonical_chain(
+		&self,
+		vote: &VoteMessage<NumberFor<B>, AuthorityId, Signature>,
+	) -> Result<bool, Error> {
+		let number = vote.commitment.block_number;
+		if number > self.backend.blockchain().info().finalized_number {
+			return Ok(false)
+		}
+
+		let header = self
+			.backend
+			.blockchain()
+			.hash(number)
+			.map_err(|e| Error::Backend(e.to_string()))?
+			.map(|hash| self.backend.blockchain().expect_header(hash))
+			.transpose()
+			.map_err(|e| Error::Backend(e.to_string()))?;
+		let Some(header) = header else { return Ok(false) };
+
+		let proof = ForkVotingProof { vote: vote.clone(), header };
+		if !check_fork_voting_proof::<_, _, BeefySignatureHasher>(&proof) {
+			return Ok(false)
+		}
+		metric_inc!(self, beefy_equivocation_votes);
+		self.report_fork_voting(proof)?;
+		Ok(true)
+	}
+
+	/// Check `vote` and report it if it targets a block that hasn't been produced yet.
+	///
+	/// Only meant for votes triaged for later: the block might just not be imported or finalized
+	/// yet, so a vote is only considered a future block vote once the node is synced and has
+	/// finalized its whole best chain. Returns `true` if the vote is such misbehavior, in which
+	/// case it shouldn't be kept for later.
+	fn check_vote_for_future_block(
+		&self,
+		vote: &VoteMessage<NumberFor<B>, AuthorityId, Signature>,
+	) -> Result<bool, Error> {
+		let info = self.backend.blockchain().info();
+		if vote.commitment.block_number <= info.best_number ||
+			info.finalized_number < info.best_number ||
+			self.sync.is_major_syncing()
+		{
+			return Ok(false)
+		}
+
+		let proof = FutureBlockVotingProof { vote: vote.clone() };
+		if !check_future_block_voting_proof::<_, _, BeefySignatureHasher>(&proof) {
+			return Ok(false)
+		}
+		metric_inc!(self, beefy_equivocation_votes);
+		self.report_future_block_voting(proof)?;
+		Ok(true)
+	}
+
+	/// Check every vote aggregated in `justification` with `check_vote`.
+	///
+	/// Only justifications for the active validator set are checked, as otherwise signatures
+	/// can't be attributed to authorities. Returns `true` if any of the votes is misbehavior.
+	fn check_justification_votes(
+		&self,
+		justification: &BeefyVersionedFinalityProof<B>,
+		check_vote: fn(
+			&Self,
+			&VoteMessage<NumberFor<B>, AuthorityId, Signature>,
+		) -> Result<bool, Error>,
+	) -> Result<bool, Error> {
+		let signed_commitment = match justification {
+			VersionedFinalityProof::V1(sc) => sc,
+		};
+		let rounds = self.persisted_state.voting_oracle.active_rounds()?;
+		if signed_commitment.commitment.validator_set_id != rounds.validator_set_id() {
+			return Ok(false)
+		}
+
+		let mut misbehavior = false;
+		for (id, signature) in rounds.validators().iter().zip(signed_commitment.signatures.iter()) {
+			if let Some(signature) = signature {
+				let vote = VoteMessage {
+					commitment: signed_commitment.commitment.clone(),
+					id: id.clone(),
+					signature: signature.clone(),
+				};
+				misbehavior |= check_vote(self, &vote)?;
+			}
+		}
+		Ok(misbehavior)
+	}
+
+	/// Generate a session membership proof of `offender_id` at block `at`.
+	///
+	/// Returns `None` if the offender is the local authority or isn't part of the authority set,
+	/// in which case no report should be submitted.
+	fn generate_key_owner_proof(
+		&self,
+		at: B::Hash,
+		validator_set_id: ValidatorSetId,
+		offender_id: &AuthorityId,
+	) -> Result<Option<OpaqueKeyOwnershipProof>, Error> {
+		let rounds = self.persisted_state.voting_oracle.active_rounds()?;
+		if let Some(local_id) = self.key_store.authority_id(rounds.validators()) {
+			if *offender_id == local_id {
+				debug!(target: LOG_TARGET, "🥩 Skip equivocation report for own equivocation");
+				return Ok(None)
+			}
+		}
+
+		let key_owner_proof = self
+			.runtime
+			.runtime_api()
+			.generate_key_ownership_proof(at, validator_set_id, offender_id.clone())
+			.map_err(Error::RuntimeApi)?;
+		if key_owner_proof.is_none() {
+			debug!(target: LOG_TARGET, "🥩 Equivocation offender not part of the authority set.");
+		}
+		Ok(key_owner_proof)
+	}
+
 	/// Report the given equivocation to the BEEFY runtime module. This method
 	/// generates a session membership proof of the offender and then submits an
 	/// extrinsic to report the equivocation. In particular, the session membership
@@ -955,18 +1105,12 @@ where
 		&self,
 		proof: EquivocationProof<NumberFor<B>, AuthorityId, Signature>,
 	) -> Result<(), Error> {
-		let rounds = self.persisted_state.voting_oracle.active_rounds()?;
-		let (validators, validator_set_id) = (rounds.validators(), rounds.validator_set_id());
-		let offender_id = proof.offender_id().clone();
+		let validator_set_id =
+			self.persisted_state.voting_oracle.active_rounds()?.validator_set_id();
 
 		if !check_equivocation_proof::<_, _, BeefySignatureHasher>(&proof) {
 			debug!(target: LOG_TARGET, "🥩 Skip report for bad equivocation {:?}", proof);
 			return Ok(())
-		} else if let Some(local_id) = self.key_store.authority_id(validators) {
-			if offender_id == local_id {
-				debug!(target: LOG_TARGET, "🥩 Skip equivocation report for own equivocation");
-				return Ok(())
-			}
 		}
 
 		let number = *proof.round_number();
@@ -981,30 +1125,99 @@ where
 				);
 				Error::Backend(err_msg)
 			})?;
-		let runtime_api = self.runtime.runtime_api();
 		// generate key ownership proof at that block
-		let key_owner_proof = match runtime_api
-			.generate_key_ownership_proof(hash, validator_set_id, offender_id)
-			.map_err(Error::RuntimeApi)?
-		{
-			Some(proof) => proof,
-			None => {
-				debug!(
-					target: LOG_TARGET,
-					"🥩 Equivocation offender not part of the authority set."
-				);
-				return Ok(())
-			},
+		let Some(key_owner_proof) =
+			self.generate_key_owner_proof(hash, validator_set_id, proof.offender_id())?
+		else {
+			return Ok(())
 		};
 
 		// submit equivocation report at **best** block
 		let best_block_hash = self.backend.blockchain().info().best_hash;
-		runtime_api
+		self.runtime
+			.runtime_api()
 			.submit_report_equivocation_unsigned_extrinsic(best_block_hash, proof, key_owner_proof)
 			.map_err(Error::RuntimeApi)?;
 
 		Ok(())
 	}
+
+	/// Report the given fork voting to the BEEFY runtime module. The session
+	/// membership proof of the offender is generated at the canonical block the
+	/// offender voted on.
+	///
+	/// Expects `proof` to be valid, see [`check_fork_voting_proof`].
+	pub(crate) fn report_fork_voting(
+		&self,
+		proof: ForkVotingProof<B::Header, AuthorityId, Signature>,
+	) -> Result<(), Error> {
+		let best_block_hash = self.backend.blockchain().info().best_hash;
+		if !self.runtime_supports_voting_reports(best_block_hash)? {
+			debug!(target: LOG_TARGET, "🥩 Skip fork voting report, unsupported by runtime");
+			return Ok(())
+		}
+
+		// generate key ownership proof at the voted block
+		let Some(key_owner_proof) = self.generate_key_owner_proof(
+			proof.header.hash(),
+			proof.set_id(),
+			proof.offender_id(),
+		)?
+		else {
+			return Ok(())
+		};
+
+		// submit fork voting report at **best** block
+		self.runtime
+			.runtime_api()
+			.submit_report_fork_voting_unsigned_extrinsic(best_block_hash, proof, key_owner_proof)
+			.map_err(Error::RuntimeApi)?;
+
+		Ok(())
+	}
+
+	/// Report the given future block voting to the BEEFY runtime module. As the
+	/// voted block doesn't exist, the session membership proof of the offender is
+	/// generated at the best block.
+	///
+	/// Expects `proof` to be valid, see [`check_future_block_voting_proof`].
+	pub(crate) fn report_future_block_voting(
+		&self,
+		proof: FutureBlockVotingProof<NumberFor<B>, AuthorityId, Signature>,
+	) -> Result<(), Error> {
+		let best_block_hash = self.backend.blockchain().info().best_hash;
+		if !self.runtime_supports_voting_reports(best_block_hash)? {
+			debug!(target: LOG_TARGET, "🥩 Skip future block voting report, unsupported by runtime");
+			return Ok(())
+		}
+
+		let Some(key_owner_proof) =
+			self.generate_key_owner_proof(best_block_hash, proof.set_id(), proof.offender_id())?
+		else {
+			return Ok(())
+		};
+
+		self.runtime
+			.runtime_api()
+			.submit_report_future_block_voting_unsigned_extrinsic(
+				best_block_hash,
+				proof,
+				key_owner_proof,
+			)
+			.map_err(Error::RuntimeApi)?;
+
+		Ok(())
+	}
+
+	/// Whether the runtime at block `at` supports reporting fork and future block voting.
+	fn runtime_supports_voting_reports(&self, at: B::Hash) -> Result<bool, Error> {
+		let version = self
+			.runtime
+			.runtime_api()
+			.api_version::<dyn BeefyApi<B, AuthorityId>>(at)
+			.map_err(Error::RuntimeApi)?;
+		Ok(version.map_or(false, |version| version >= 4))
+	}
 }
 
 /// Scan the `header` digest log for a BEEFY validator set change. Return either the new
@@ -1064,8 +1277,8 @@ pub(crate) mod tests {
 	use crate::{
 		communication::notification::{BeefyBestBlockStream, BeefyVersionedFinalityProofStream},
 		tests::{
-			create_beefy_keystore, get_beefy_streams, make_beefy_ids, BeefyPeer, BeefyTestNet,
-			TestApi,
+			add_mmr_digest, create_beefy_keystore, get_beefy_streams, make_beefy_ids, BeefyPeer,
+			BeefyTestNet, TestApi,
 		},
 		BeefyRPCLinks, KnownPeers,
 	};
@@ -1075,9 +1288,10 @@ pub(crate) mod tests {
 	use sc_network_sync::SyncingService;
 	use sc_network_test::TestNetFactory;
 	use sp_blockchain::Backend as BlockchainBackendT;
+	use sp_consensus::BlockOrigin;
 	use sp_consensus_beefy::{
 		generate_equivocation_proof, known_payloads, known_payloads::MMR_ROOT_ID,
-		mmr::MmrRootProvider, Keyring, Payload, SignedCommitment,
+		mmr::MmrRootProvider, signed_vote, Keyring, MmrRootHash, Payload, SignedCommitment,
 	};
 	use sp_runtime::traits::{Header as HeaderT, One};
 	use substrate_test_runtime_client::{
@@ -1684,4 +1898,150 @@ pub(crate) mod tests {
 		// verify nothing reported to runtime
 		assert!(api_alice.reported_equivocations.as_ref().unwrap().lock().is_empty());
 	}
+
+	#[tokio::test]
+	async fn should_report_fork_and_future_block_votes() {
+		let set_id = 1;
+		let keys = [Keyring::Alice, Keyring::Bob];
+		let validator_set = ValidatorSet::new(make_beefy_ids(&keys), set_id).unwrap();
+		// Alice votes on good MMR roots, misbehavior reports are allowed/expected
+		let mut api_alice = TestApi::with_validator_set(&validator_set);
+		api_alice.allow_equivocations();
+		let api_alice = Arc::new(api_alice);
+
+		let mut net = BeefyTestNet::new(1);
+		let mut worker = create_beefy_worker(net.peer(0), &keys[0], 1, validator_set.clone());
+		worker.runtime = api_alice.clone();
+
+		// let there be a finalized block with num = 2, committing to a good MMR root:
+		let good_root = MmrRootHash::repeat_byte(0xbf);
+		let bad_root = MmrRootHash::repeat_byte(0x42);
+		let hashes = net.peer(0).generate_blocks(1, BlockOrigin::File, |mut builder| {
+			add_mmr_digest(&mut builder, good_root);
+			builder.build().unwrap().block
+		});
+		net.peer(0).client().as_backend().finalize_block(hashes[0], None).unwrap();
+		let block_num = 2;
+		let payload = |root: MmrRootHash| Payload::from_single_entry(MMR_ROOT_ID, root.encode());
+
+		// votes on the canonical payload are fine
+		let vote = signed_vote(block_num, payload(good_root), set_id, &Keyring::Bob);
+		assert_eq!(worker.check_vote_against_canonical_chain(&vote), Ok(false));
+
+		// votes on a different payload are reported as fork voting
+		let vote = signed_vote(block_num, payload(bad_root), set_id, &Keyring::Bob);
+		assert_eq!(worker.check_vote_against_canonical_chain(&vote), Ok(true));
+		{
+			let reported = api_alice.reported_fork_votings.as_ref().unwrap().lock();
+			assert_eq!(reported.len(), 1);
+			assert_eq!(reported[0].vote, vote);
+			assert_eq!(reported[0].header.hash(), hashes[0]);
+		}
+
+		// votes on blocks that haven't been produced yet are enqueued by the voter, and reported
+		// as future block voting
+		let vote = signed_vote(block_num + 10, payload(good_root), set_id, &Keyring::Bob);
+		assert!(matches!(
+			worker.voting_oracle().triage_round(vote.commitment.block_number),
+			Ok(RoundAction::Enqueue)
+		));
+		worker.triage_incoming_vote(vote.clone()).unwrap();
+		{
+			let reported = api_alice.reported_future_block_votings.as_ref().unwrap().lock();
+			assert_eq!(reported.len(), 1);
+			assert_eq!(reported[0].vote, vote);
+		}
+
+		// enqueued votes on imported blocks are not
+		let vote = signed_vote(block_num, payload(good_root), set_id, &Keyring::Bob);
+		worker.triage_incoming_vote(vote).unwrap();
+		assert_eq!(api_alice.reported_future_block_votings.as_ref().unwrap().lock().len(), 1);
+
+		// neither are votes while the best chain isn't finalized yet
+		net.peer(0).generate_blocks(1, BlockOrigin::File, |builder| builder.build().unwrap().block);
+		let vote = signed_vote(block_num + 10, payload(good_root), set_id, &Keyring::Bob);
+		worker.triage_incoming_vote(vote).unwrap();
+		assert_eq!(api_alice.reported_future_block_votings.as_ref().unwrap().lock().len(), 1);
+
+		// misbehavior done by 'self' is detected, but not reported
+		let vote = signed_vote(block_num, payload(bad_root), set_id, &Keyring::Alice);
+		assert_eq!(worker.check_vote_against_canonical_chain(&vote), Ok(true));
+		assert_eq!(api_alice.reported_fork_votings.as_ref().unwrap().lock().len(), 1);
+
+		// justifications are checked signature by signature
+		let commitment = Commitment {
+			payload: payload(bad_root),
+			block_number: block_num,
+			validator_set_id: set_id,
+		};
+		let signatures = vec![None, Some(Keyring::Bob.sign(&commitment.encode()))];
+		let justification = VersionedFinalityProof::V1(SignedCommitment { commitment, signatures });
+		assert_eq!(
+			worker.check_justification_votes(
+				&justification,
+				BeefyWorker::check_vote_against_canonical_chain
+			),
+			Ok(true)
+		);
+		assert_eq!(api_alice.reported_fork_votings.as_ref().unwrap().lock().len(), 2);
+	}
+
+	#[tokio::test]
+	async fn should_not_report_justifications_received_before_their_block() {
+		let set_id = 1;
+		let keys = [Keyring::Alice, Keyring::Bob];
+		let validator_set = ValidatorSet::new(make_beefy_ids(&keys), set_id).unwrap();
+		let mut api_alice = TestApi::with_validator_set(&validator_set);
+		api_alice.allow_equivocations();
+		let api_alice = Arc::new(api_alice);
+
+		let mut net = BeefyTestNet::new(1);
+		let backend = net.peer(0).client().as_backend();
+		let mut worker = create_beefy_worker(net.peer(0), &keys[0], 1, validator_set.clone());
+		worker.runtime = api_alice.clone();
+
+		let good_root = MmrRootHash::repeat_byte(0xbf);
+		let create_justification = |block_number: NumberFor<Block>| {
+			let commitment = Commitment {
+				payload: Payload::from_single_entry(MMR_ROOT_ID, good_root.encode()),
+				block_number,
+				validator_set_id: set_id,
+			};
+			let signatures = keys.iter().map(|key| Some(key.sign(&commitment.encode()))).collect();
+			VersionedFinalityProof::V1(SignedCommitment { commitment, signatures })
+		};
+
+		// finalize the mandatory block #1 of the session
+		worker.triage_incoming_justif(create_justification(1)).unwrap();
+		assert_eq!(worker.persisted_state.best_beefy_block(), 1);
+
+		// justification for block #2 arrives before GRANDPA finalized the block, it is only
+		// buffered
+		let hashes = net.peer(0).generate_blocks(1, BlockOrigin::File, |mut builder| {
+			add_mmr_digest(&mut builder, good_root);
+			builder.build().unwrap().block
+		});
+		worker.triage_incoming_justif(create_justification(2)).unwrap();
+		assert!(worker.pending_justifications.contains_key(&2));
+		assert_eq!(worker.persisted_state.best_beefy_block(), 1);
+
+		// block #2 gets finalized by GRANDPA
+		backend.finalize_block(hashes[0], None).unwrap();
+		let header = backend.blockchain().expect_header(hashes[0]).unwrap();
+		worker.persisted_state.set_best_grandpa(header);
+
+		// buffered justification is now processed
+		worker.try_pending_justifications().unwrap();
+		assert!(worker.pending_justifications.is_empty());
+		assert_eq!(worker.persisted_state.best_beefy_block(), 2);
+
+		// and no misbehavior was reported along the way
+		assert!(api_alice.reported_fork_votings.as_ref().unwrap().lock().is_empty());
+		assert!(api_alice.reported_future_block_votings.as_ref().unwrap().lock().is_empty());
+
+		// a justification for a block that hasn't been produced yet is reported, not buffered
+		worker.triage_incoming_justif(create_justification(12)).unwrap();
+		assert!(worker.pending_justifications.is_empty());
+		assert_eq!(api_alice.reported_future_block_votings.as_ref().unwrap().lock().len(), 1);
+	}
 }
@@ -471,6 +471,14 @@ where
 	load_decode::<_, GrandpaJustification<Block>>(backend, BEST_JUSTIFICATION)
 }
 
+/// The keys of all the data GRANDPA stores in the aux storage.
+pub fn aux_keys() -> Vec<Vec<u8>> {
+	[VERSION_KEY, SET_STATE_KEY, CONCLUDED_ROUNDS, AUTHORITY_SET_KEY, BEST_JUSTIFICATION]
+		.iter()
+		.map(|key| key.to_vec())
+		.collect()
+}
+
 /// Write voter set state.
 pub(crate) fn write_voter_set_state<Block: BlockT, B: AuxStore>(
 	backend: &B,
@@ -491,6 +499,26 @@ pub(crate) fn write_concluded_round<Block: BlockT, B: AuxStore>(
 	backend.insert_aux(&[(&key[..], round_data.encode().as_slice())], &[])
 }
 
+/// Load the voter set state, which includes the votes of the last completed rounds.
+pub(crate) fn load_voter_set_state<Block: BlockT, B: AuxStore>(
+	backend: &B,
+) -> ClientResult<Option<VoterSetState<Block>>> {
+	load_decode(backend, SET_STATE_KEY)
+}
+
+/// Load a concluded round, written by [`write_concluded_round`].
+///
+/// Only the latest round with the given number is kept, regardless of the authority set.
+pub(crate) fn load_concluded_round<Block: BlockT, B: AuxStore>(
+	backend: &B,
+	round_number: RoundNumber,
+) -> ClientResult<Option<CompletedRound<Block>>> {
+	let mut key = CONCLUDED_ROUNDS.to_vec();
+	round_number.using_encoded(|n| key.extend(n));
+
+	load_decode(backend, &key)
+}
+
 #[cfg(test)]
 pub(crate) fn load_authorities<B: AuxStore, H: Decode, N: Decode + Clone + Ord>(
 	backend: &B,
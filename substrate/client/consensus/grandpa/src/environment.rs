@@ -42,7 +42,7 @@ use sc_client_api::{
 use sc_telemetry::{telemetry, TelemetryHandle, CONSENSUS_DEBUG, CONSENSUS_INFO};
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
 use sp_api::ApiExt;
-use sp_blockchain::HeaderMetadata;
+use sp_blockchain::{Error as ClientError, HeaderBackend, HeaderMetadata};
 use sp_consensus::SelectChain as SelectChainT;
 use sp_consensus_grandpa::{
 	AuthorityId, AuthoritySignature, Equivocation, EquivocationProof, GrandpaApi, RoundNumber,
@@ -509,45 +509,9 @@ where
 			}
 		}
 
-		let is_descendent_of = is_descendent_of(&*self.client, None);
-
-		let (best_block_hash, best_block_number) = {
-			// TODO [#9158]: Use SelectChain::best_chain() to get a potentially
-			// more accurate best block
-			let info = self.client.info();
-			(info.best_hash, info.best_number)
-		};
-
+		let best_block_hash = self.client.info().best_hash;
 		let authority_set = self.authority_set.inner();
-
-		// block hash and number of the next pending authority set change in the
-		// given best chain.
-		let next_change = authority_set
-			.next_change(&best_block_hash, &is_descendent_of)
-			.map_err(|e| Error::Safety(e.to_string()))?;
-
-		// find the hash of the latest block in the current set
-		let current_set_latest_hash = match next_change {
-			Some((_, n)) if n.is_zero() =>
-				return Err(Error::Safety("Authority set change signalled at genesis.".to_string())),
-			// the next set starts at `n` so the current one lasts until `n - 1`. if
-			// `n` is later than the best block, then the current set is still live
-			// at best block.
-			Some((_, n)) if n > best_block_number => best_block_hash,
-			Some((h, _)) => {
-				// this is the header at which the new set will start
-				let header = self.client.header(h)?.expect(
-					"got block hash from registered pending change; \
-					 pending changes are only registered on block import; qed.",
-				);
-
-				// its parent block is the last block in the current set
-				*header.parent_hash()
-			},
-			// there is no pending change, the latest block for the current set is
-			// the best block.
-			None => best_block_hash,
-		};
+		let current_set_latest_hash = current_set_latest_hash(&*self.client, &authority_set)?;
 
 		// generate key ownership proof at that block
 		let key_owner_proof = match self
@@ -591,6 +555,55 @@ where
 	}
 }
 
+/// Returns the hash of the latest block of the current authority set on the best chain, which
+/// isn't necessarily the best block if there are pending authority set changes.
+pub(crate) fn current_set_latest_hash<Block, Client>(
+	client: &Client,
+	authority_set: &AuthoritySet<Block::Hash, NumberFor<Block>>,
+) -> Result<Block::Hash, Error>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + HeaderMetadata<Block, Error = ClientError>,
+{
+	let is_descendent_of = is_descendent_of(client, None);
+
+	let (best_block_hash, best_block_number) = {
+		// TODO [#9158]: Use SelectChain::best_chain() to get a potentially
+		// more accurate best block
+		let info = client.info();
+		(info.best_hash, info.best_number)
+	};
+
+	// block hash and number of the next pending authority set change in the
+	// given best chain.
+	let next_change = authority_set
+		.next_change(&best_block_hash, &is_descendent_of)
+		.map_err(|e| Error::Safety(e.to_string()))?;
+
+	// find the hash of the latest block in the current set
+	match next_change {
+		Some((_, n)) if n.is_zero() =>
+			Err(Error::Safety("Authority set change signalled at genesis.".to_string())),
+		// the next set starts at `n` so the current one lasts until `n - 1`. if
+		// `n` is later than the best block, then the current set is still live
+		// at best block.
+		Some((_, n)) if n > best_block_number => Ok(best_block_hash),
+		Some((h, _)) => {
+			// this is the header at which the new set will start
+			let header = client.header(h)?.expect(
+				"got block hash from registered pending change; \
+				 pending changes are only registered on block import; qed.",
+			);
+
+			// its parent block is the last block in the current set
+			Ok(*header.parent_hash())
+		},
+		// there is no pending change, the latest block for the current set is
+		// the best block.
+		None => Ok(best_block_hash),
+	}
+}
+
 impl<BE, Block, C, N, S, SC, VR> finality_grandpa::Chain<Block::Hash, NumberFor<Block>>
 	for Environment<BE, Block, C, N, S, SC, VR>
 where
@@ -0,0 +1,782 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Offline detection of GRANDPA equivocations.
+//!
+//! The [`Fisherman`] looks for authorities that signed two different prevotes or precommits in
+//! the same round. It can scan the precommits of the justifications stored in the database, the
+//! votes of the rounds concluded by the local voter and any evidence collected elsewhere, e.g.
+//! justifications of blocks on other forks.
+//!
+//! Votes are only scanned if they are signed by a member of the authority set they were cast in,
+//! and justifications are only recorded if they are valid for that set. Two justifications for
+//! blocks on different forks in the same authority set are reported as conflicting, and the
+//! equivocations they contain are detected like any other vote. Every equivocation is turned into
+//! an [`EquivocationProof`] that can be reported to the runtime, including for authority sets that
+//! are no longer active.
+
+use std::{collections::HashMap, sync::Arc};
+
+use finality_grandpa::{voter_set::VoterSet, BlockNumberOps};
+use log::debug;
+use parity_scale_codec::{Decode, Encode};
+use sc_client_api::{backend::AuxStore, utils::is_descendent_of, BlockBackend};
+use sc_transaction_pool_api::OffchainTransactionPoolFactory;
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::{Error as ClientError, HeaderBackend, HeaderMetadata};
+use sp_consensus_grandpa::{
+	AuthorityId, AuthorityList, AuthoritySignature, Equivocation, EquivocationProof, GrandpaApi,
+	OpaqueKeyOwnershipProof, RoundNumber, SetId, GRANDPA_ENGINE_ID,
+};
+use sp_runtime::traits::{Block as BlockT, NumberFor, One, Saturating, Zero};
+
+use crate::{
+	authorities::{AuthoritySetChangeId, SharedAuthoritySet},
+	aux_schema,
+	environment::{current_set_latest_hash, CompletedRound},
+	Error, GrandpaJustification, Message, SignedMessage, LOG_TARGET,
+};
+
+/// A vote along with the authority set and round it was cast in.
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq)]
+pub struct CollectedVote<Block: BlockT> {
+	/// The authority set id.
+	pub set_id: SetId,
+	/// The round number.
+	pub round: RoundNumber,
+	/// The signed vote.
+	pub message: SignedMessage<Block::Header>,
+}
+
+/// Evidence collected outside of the local database, e.g. by another node.
+#[derive(Clone, Debug, Encode, Decode)]
+pub enum Evidence<Block: BlockT> {
+	/// A single vote.
+	Vote(CollectedVote<Block>),
+	/// A justification, possibly for a block that isn't known locally.
+	Justification(GrandpaJustification<Block>),
+}
+
+/// The block finalized by a justification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JustificationTarget<Block: BlockT> {
+	/// The round the block was finalized in.
+	pub round: RoundNumber,
+	/// The hash of the block.
+	pub hash: Block::Hash,
+	/// The number of the block.
+	pub number: NumberFor<Block>,
+}
+
+/// Two justifications of the same authority set finalizing blocks on different forks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConflictingJustifications<Block: BlockT> {
+	/// The authority set id.
+	pub set_id: SetId,
+	/// The target of the justification that was imported first.
+	pub first: JustificationTarget<Block>,
+	/// The target of the conflicting justification.
+	pub second: JustificationTarget<Block>,
+}
+
+/// The first vote of some kind cast by an authority in a round.
+struct FirstVote<V> {
+	vote: Option<(V, AuthoritySignature)>,
+	reported: bool,
+}
+
+impl<V> Default for FirstVote<V> {
+	fn default() -> Self {
+		FirstVote { vote: None, reported: false }
+	}
+}
+
+impl<V: Clone + PartialEq> FirstVote<V> {
+	/// Record the given vote, returning an equivocation the first time it differs from the first
+	/// vote.
+	fn record(
+		&mut self,
+		round_number: RoundNumber,
+		identity: &AuthorityId,
+		vote: V,
+		signature: AuthoritySignature,
+	) -> Option<finality_grandpa::Equivocation<AuthorityId, V, AuthoritySignature>> {
+		match &self.vote {
+			None => {
+				self.vote = Some((vote, signature));
+				None
+			},
+			Some((first, _)) if *first == vote || self.reported => None,
+			Some(first) => {
+				self.reported = true;
+				Some(finality_grandpa::Equivocation {
+					round_number,
+					identity: identity.clone(),
+					first: first.clone(),
+					second: (vote, signature),
+				})
+			},
+		}
+	}
+}
+
+/// The first votes cast by an authority in a round.
+struct RoundVotes<Block: BlockT> {
+	prevote: FirstVote<crate::Prevote<Block::Header>>,
+	precommit: FirstVote<crate::Precommit<Block::Header>>,
+}
+
+impl<Block: BlockT> Default for RoundVotes<Block> {
+	fn default() -> Self {
+		RoundVotes { prevote: Default::default(), precommit: Default::default() }
+	}
+}
+
+/// Detects equivocations in the votes and justifications known to the node.
+pub struct Fisherman<Block: BlockT, Client> {
+	client: Arc<Client>,
+	authority_set: SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
+	/// The voters of the authority sets seen so far, `None` if they are unknown.
+	voters: HashMap<SetId, Option<Arc<VoterSet<AuthorityId>>>>,
+	votes: HashMap<(SetId, RoundNumber, AuthorityId), RoundVotes<Block>>,
+	justifications: Vec<(SetId, JustificationTarget<Block>)>,
+	equivocations: Vec<EquivocationProof<Block::Hash, NumberFor<Block>>>,
+	conflicting_justifications: Vec<ConflictingJustifications<Block>>,
+	invalid_votes: usize,
+	invalid_justifications: usize,
+}
+
+impl<Block, Client> Fisherman<Block, Client>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block>
+		+ HeaderMetadata<Block, Error = ClientError>
+		+ BlockBackend<Block>
+		+ AuxStore
+		+ ProvideRuntimeApi<Block>,
+	Client::Api: GrandpaApi<Block>,
+	NumberFor<Block>: BlockNumberOps,
+{
+	/// Create a new fisherman, using the given authority set to find out the set id of the
+	/// scanned blocks.
+	pub fn new(
+		client: Arc<Client>,
+		authority_set: SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
+	) -> Self {
+		Fisherman {
+			client,
+			authority_set,
+			voters: HashMap::new(),
+			votes: HashMap::new(),
+			justifications: Vec::new(),
+			equivocations: Vec::new(),
+			conflicting_justifications: Vec::new(),
+			invalid_votes: 0,
+			invalid_justifications: 0,
+		}
+	}
+
+	/// The equivocations found so far.
+	pub fn equivocations(&self) -> &[EquivocationProof<Block::Hash, NumberFor<Block>>] {
+		&self.equivocations
+	}
+
+	/// The conflicting justifications found so far.
+	pub fn conflicting_justifications(&self) -> &[ConflictingJustifications<Block>] {
+		&self.conflicting_justifications
+	}
+
+	/// The number of votes that were ignored because of an invalid signature, or because they
+	/// weren't cast by a known member of the authority set.
+	pub fn invalid_votes(&self) -> usize {
+		self.invalid_votes
+	}
+
+	/// The number of justifications that weren't recorded because they aren't valid for their
+	/// authority set.
+	pub fn invalid_justifications(&self) -> usize {
+		self.invalid_justifications
+	}
+
+	/// Import a vote, recording an equivocation if the authority already cast a different vote
+	/// of the same kind in the same round.
+	pub fn import_vote(&mut self, vote: CollectedVote<Block>) {
+		let CollectedVote { set_id, round, message: SignedMessage { message, signature, id } } =
+			vote;
+
+		if !self.voters(set_id).map_or(false, |voters| voters.contains(&id)) {
+			debug!(
+				target: LOG_TARGET,
+				"Ignoring vote of {:?}, not an authority of set {}", id, set_id
+			);
+			self.invalid_votes += 1;
+			return
+		}
+		if !sp_consensus_grandpa::check_message_signature(&message, &id, &signature, round, set_id)
+		{
+			debug!(target: LOG_TARGET, "Ignoring vote of {:?} with a bad signature", id);
+			self.invalid_votes += 1;
+			return
+		}
+
+		let votes = self.votes.entry((set_id, round, id.clone())).or_default();
+		let equivocation = match message {
+			Message::Prevote(prevote) =>
+				votes.prevote.record(round, &id, prevote, signature).map(Equivocation::Prevote),
+			Message::Precommit(precommit) => votes
+				.precommit
+				.record(round, &id, precommit, signature)
+				.map(Equivocation::Precommit),
+			// equivocating primary proposals aren't slashable.
+			Message::PrimaryPropose(_) => None,
+		};
+
+		if let Some(equivocation) = equivocation {
+			debug!(target: LOG_TARGET, "Found equivocation of {:?} in round {}", id, round);
+			self.equivocations.push(EquivocationProof::new(set_id, equivocation));
+		}
+	}
+
+	/// Import the precommits of a justification and, if it is valid for its authority set, record
+	/// it as conflicting with any previously recorded justification of the same set finalizing a
+	/// block on another fork.
+	pub fn import_justification(&mut self, justification: GrandpaJustification<Block>) {
+		let (number, hash) = justification.target();
+		let Some(set_id) = self.set_id_at(number) else {
+			debug!(target: LOG_TARGET, "Unknown authority set of justification for {:?}", hash);
+			return
+		};
+
+		// the precommits of an invalid justification may still be equivocations of members of
+		// the set, `import_vote` ignores the others.
+		let is_valid = match self.voters(set_id) {
+			Some(voters) => justification.verify_with_voter_set(set_id, &voters).is_ok(),
+			None => false,
+		};
+
+		let justification = justification.justification;
+		let round = justification.round;
+		for signed in justification.commit.precommits {
+			self.import_vote(CollectedVote {
+				set_id,
+				round,
+				message: SignedMessage {
+					message: Message::Precommit(signed.precommit),
+					signature: signed.signature,
+					id: signed.id,
+				},
+			});
+		}
+
+		if !is_valid {
+			debug!(target: LOG_TARGET, "Ignoring invalid justification for {:?}", hash);
+			self.invalid_justifications += 1;
+			return
+		}
+
+		let target = JustificationTarget { round, hash, number };
+		if self.justifications.contains(&(set_id, target.clone())) {
+			return
+		}
+
+		let conflicting_justifications = self
+			.justifications
+			.iter()
+			.filter(|(other_set_id, other)| {
+				*other_set_id == set_id && !self.on_same_chain(other, &target)
+			})
+			.map(|(_, other)| ConflictingJustifications {
+				set_id,
+				first: other.clone(),
+				second: target.clone(),
+			})
+			.collect::<Vec<_>>();
+
+		self.conflicting_justifications.extend(conflicting_justifications);
+		self.justifications.push((set_id, target));
+	}
+
+	/// Import some evidence collected outside of the local database.
+	pub fn import_evidence(&mut self, evidence: Evidence<Block>) {
+		match evidence {
+			Evidence::Vote(vote) => self.import_vote(vote),
+			Evidence::Justification(justification) => self.import_justification(justification),
+		}
+	}
+
+	/// Scan the justifications stored for the blocks of the canonical chain between `from` and
+	/// `to`, inclusive.
+	pub fn scan_justifications(
+		&mut self,
+		from: NumberFor<Block>,
+		to: NumberFor<Block>,
+	) -> Result<(), Error> {
+		let mut number = from;
+		while number <= to {
+			let justification = match self.client.hash(number)? {
+				Some(hash) => self.client.justifications(hash)?.and_then(|justifications| {
+					justifications.into_justification(GRANDPA_ENGINE_ID)
+				}),
+				None => None,
+			};
+
+			if let Some(justification) = justification {
+				match GrandpaJustification::<Block>::decode(&mut &justification[..]) {
+					Ok(justification) => self.import_justification(justification),
+					Err(err) => debug!(
+						target: LOG_TARGET,
+						"Ignoring undecodable justification of block #{}: {}", number, err
+					),
+				}
+			}
+
+			number += One::one();
+		}
+
+		Ok(())
+	}
+
+	/// Scan the votes of the rounds concluded by the local voter.
+	///
+	/// Only the latest round with a given number is kept in the database, so the rounds of
+	/// previous authority sets are only available if they lasted longer than the current one.
+	pub fn scan_voter_rounds(&mut self) -> Result<(), Error> {
+		let Some(voter_set_state) = aux_schema::load_voter_set_state::<Block, _>(&*self.client)?
+		else {
+			return Ok(())
+		};
+
+		let completed_rounds = voter_set_state.completed_rounds();
+		let (set_id, _) = completed_rounds.set_info();
+		for round in completed_rounds.iter() {
+			self.import_round(set_id, round);
+		}
+
+		let last_round = completed_rounds.last().number;
+		let mut number = 1;
+		loop {
+			match aux_schema::load_concluded_round::<Block, _>(&*self.client, number)? {
+				// the base of a round is finalized by the previous set when the set changes, the
+				// blocks built on top of it belong to the set of the round.
+				Some(round) => match self.set_id_at(round.base.1 + One::one()) {
+					Some(set_id) => self.import_round(set_id, &round),
+					None => debug!(target: LOG_TARGET, "Unknown authority set of round {}", number),
+				},
+				None if number > last_round => break,
+				None => {},
+			}
+			number += 1;
+		}
+
+		Ok(())
+	}
+
+	/// Generate a proof that the offender of the given equivocation was part of the authority
+	/// set, at the latest block of that set.
+	///
+	/// Returns `None` if the runtime doesn't know the offender.
+	pub fn generate_key_ownership_proof(
+		&self,
+		equivocation_proof: &EquivocationProof<Block::Hash, NumberFor<Block>>,
+	) -> Result<Option<OpaqueKeyOwnershipProof>, Error> {
+		let set_id = equivocation_proof.set_id();
+		let at = self.set_latest_hash(set_id)?;
+
+		self.client
+			.runtime_api()
+			.generate_key_ownership_proof(at, set_id, equivocation_proof.offender().clone())
+			.map_err(Error::RuntimeApi)
+	}
+
+	/// Report the given equivocation to the runtime, submitting an unsigned extrinsic to the
+	/// transaction pool of the local node.
+	///
+	/// Returns `false` if the offender isn't known to the runtime.
+	pub fn report_equivocation(
+		&self,
+		equivocation_proof: EquivocationProof<Block::Hash, NumberFor<Block>>,
+		offchain_tx_pool_factory: &OffchainTransactionPoolFactory<Block>,
+	) -> Result<bool, Error> {
+		let Some(key_owner_proof) = self.generate_key_ownership_proof(&equivocation_proof)? else {
+			return Ok(false)
+		};
+
+		let best_block_hash = self.client.info().best_hash;
+		let mut runtime_api = self.client.runtime_api();
+		runtime_api.register_extension(
+			offchain_tx_pool_factory.offchain_transaction_pool(best_block_hash),
+		);
+		runtime_api
+			.submit_report_equivocation_unsigned_extrinsic(
+				best_block_hash,
+				equivocation_proof,
+				key_owner_proof,
+			)
+			.map_err(Error::RuntimeApi)?;
+
+		Ok(true)
+	}
+
+	fn import_round(&mut self, set_id: SetId, round: &CompletedRound<Block>) {
+		for message in &round.votes {
+			self.import_vote(CollectedVote {
+				set_id,
+				round: round.number,
+				message: message.clone(),
+			});
+		}
+	}
+
+	/// The voters of the given authority set, if they are known.
+	fn voters(&mut self, set_id: SetId) -> Option<Arc<VoterSet<AuthorityId>>> {
+		if let Some(voters) = self.voters.get(&set_id) {
+			return voters.clone()
+		}
+
+		let voters = match self.set_authorities(set_id) {
+			Ok(authorities) => VoterSet::new(authorities).map(Arc::new),
+			Err(err) => {
+				debug!(target: LOG_TARGET, "Unknown authorities of set {}: {}", set_id, err);
+				None
+			},
+		};
+		self.voters.insert(set_id, voters.clone());
+		voters
+	}
+
+	/// The authorities of the given set, read from the runtime at the latest block of the set if
+	/// it isn't the current one.
+	fn set_authorities(&self, set_id: SetId) -> Result<AuthorityList, Error> {
+		{
+			let authority_set = self.authority_set.inner();
+			if set_id == authority_set.set_id {
+				return Ok(authority_set.current_authorities.clone())
+			}
+		}
+
+		let at = self.set_latest_hash(set_id)?;
+		self.client.runtime_api().grandpa_authorities(at).map_err(Error::RuntimeApi)
+	}
+
+	/// The id of the authority set which finalized the given block.
+	fn set_id_at(&self, number: NumberFor<Block>) -> Option<SetId> {
+		let authority_set = self.authority_set.inner();
+		match authority_set.authority_set_changes.get_set_id(number) {
+			AuthoritySetChangeId::Latest => Some(authority_set.set_id),
+			AuthoritySetChangeId::Set(set_id, _) => Some(set_id),
+			// no change was ever applied, all the blocks belong to the genesis set.
+			AuthoritySetChangeId::Unknown if authority_set.set_id == 0 => Some(0),
+			AuthoritySetChangeId::Unknown => None,
+		}
+	}
+
+	/// The hash of the latest block of the given authority set.
+	fn set_latest_hash(&self, set_id: SetId) -> Result<Block::Hash, Error> {
+		let authority_set = self.authority_set.inner();
+		if set_id == authority_set.set_id {
+			return current_set_latest_hash(&*self.client, &authority_set)
+		}
+
+		// the recorded block enacts the next set, its parent is the latest block of the set.
+		let number = authority_set
+			.authority_set_changes
+			.iter_from(Zero::zero())
+			.into_iter()
+			.flatten()
+			.find(|(id, _)| *id == set_id)
+			.map(|(_, number)| number.saturating_sub(One::one()))
+			.ok_or_else(|| Error::Blockchain(format!("Unknown authority set {}", set_id)))?;
+
+		self.client.hash(number)?.ok_or_else(|| {
+			Error::Blockchain(format!("Missing latest block of authority set {}", set_id))
+		})
+	}
+
+	/// Whether one of the given blocks is an ancestor of the other.
+	///
+	/// Blocks that aren't known locally are assumed to be on the same chain, unless they have
+	/// the same number.
+	fn on_same_chain(
+		&self,
+		a: &JustificationTarget<Block>,
+		b: &JustificationTarget<Block>,
+	) -> bool {
+		if a.hash == b.hash {
+			return true
+		}
+		if a.number == b.number {
+			return false
+		}
+
+		let (ancestor, descendent) = if a.number < b.number { (a, b) } else { (b, a) };
+		is_descendent_of::<Block, _>(&*self.client, None)(&ancestor.hash, &descendent.hash)
+			.unwrap_or(true)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::AuthoritySet;
+	use sc_block_builder::BlockBuilderBuilder;
+	use sp_consensus::BlockOrigin;
+	use sp_consensus_grandpa::check_equivocation_proof;
+	use sp_core::H256;
+	use sp_keyring::Ed25519Keyring;
+	use sp_runtime::{traits::Header as HeaderT, Justifications};
+	use substrate_test_runtime_client::{
+		runtime::{Block, Header},
+		ClientBlockImportExt, ClientExt, TestClient,
+	};
+
+	fn fisherman(client: Arc<TestClient>) -> Fisherman<Block, TestClient> {
+		let authorities = [Ed25519Keyring::Alice, Ed25519Keyring::Bob]
+			.iter()
+			.map(|keyring| (keyring.public().into(), 1))
+			.collect();
+		let authority_set = AuthoritySet::genesis(authorities).unwrap();
+		Fisherman::new(client, authority_set.into())
+	}
+
+	fn signed_vote(
+		keyring: Ed25519Keyring,
+		set_id: SetId,
+		round: RoundNumber,
+		message: Message<Header>,
+	) -> CollectedVote<Block> {
+		let encoded = sp_consensus_grandpa::localized_payload(round, set_id, &message);
+		CollectedVote {
+			set_id,
+			round,
+			message: SignedMessage {
+				message,
+				signature: keyring.sign(&encoded).into(),
+				id: keyring.public().into(),
+			},
+		}
+	}
+
+	fn prevote(hash: H256, number: u64) -> Message<Header> {
+		Message::Prevote(finality_grandpa::Prevote::new(hash, number))
+	}
+
+	fn precommit(hash: H256, number: u64) -> Message<Header> {
+		Message::Precommit(finality_grandpa::Precommit::new(hash, number))
+	}
+
+	fn justification(
+		round: RoundNumber,
+		hash: H256,
+		number: u64,
+		signers: &[Ed25519Keyring],
+	) -> GrandpaJustification<Block> {
+		let precommits = signers
+			.iter()
+			.map(|keyring| {
+				let precommit = finality_grandpa::Precommit::new(hash, number);
+				let message = Message::<Header>::Precommit(precommit.clone());
+				let encoded = sp_consensus_grandpa::localized_payload(round, 0, &message);
+				finality_grandpa::SignedPrecommit {
+					precommit,
+					signature: keyring.sign(&encoded).into(),
+					id: keyring.public().into(),
+				}
+			})
+			.collect();
+
+		sp_consensus_grandpa::GrandpaJustification::<Header> {
+			round,
+			commit: finality_grandpa::Commit {
+				target_hash: hash,
+				target_number: number,
+				precommits,
+			},
+			votes_ancestries: Vec::new(),
+		}
+		.into()
+	}
+
+	#[test]
+	fn detects_vote_equivocations() {
+		let mut fisherman = fisherman(Arc::new(substrate_test_runtime_client::new()));
+		let alice = Ed25519Keyring::Alice;
+
+		// the same vote seen twice isn't an equivocation.
+		fisherman.import_vote(signed_vote(alice, 0, 1, prevote(H256::repeat_byte(1), 1)));
+		fisherman.import_vote(signed_vote(alice, 0, 1, prevote(H256::repeat_byte(1), 1)));
+		// neither are votes of a different kind or round.
+		fisherman.import_vote(signed_vote(alice, 0, 1, precommit(H256::repeat_byte(2), 1)));
+		fisherman.import_vote(signed_vote(alice, 0, 2, prevote(H256::repeat_byte(2), 1)));
+		assert!(fisherman.equivocations().is_empty());
+
+		fisherman.import_vote(signed_vote(alice, 0, 1, prevote(H256::repeat_byte(2), 1)));
+		// the equivocation is only reported once.
+		fisherman.import_vote(signed_vote(alice, 0, 1, prevote(H256::repeat_byte(3), 1)));
+		fisherman.import_vote(signed_vote(alice, 0, 1, precommit(H256::repeat_byte(3), 1)));
+
+		let equivocations = fisherman.equivocations();
+		assert_eq!(equivocations.len(), 2);
+		assert!(equivocations.iter().all(|proof| {
+			proof.set_id() == 0 &&
+				proof.round() == 1 &&
+				*proof.offender() == AuthorityId::from(alice.public()) &&
+				check_equivocation_proof(proof.clone())
+		}));
+		assert_eq!(fisherman.invalid_votes(), 0);
+	}
+
+	#[test]
+	fn ignores_votes_of_non_authorities() {
+		let mut fisherman = fisherman(Arc::new(substrate_test_runtime_client::new()));
+		let charlie = Ed25519Keyring::Charlie;
+
+		fisherman.import_vote(signed_vote(charlie, 0, 1, prevote(H256::repeat_byte(1), 1)));
+		fisherman.import_vote(signed_vote(charlie, 0, 1, prevote(H256::repeat_byte(2), 1)));
+		// the authorities of unknown sets can't be checked.
+		let alice = Ed25519Keyring::Alice;
+		fisherman.import_vote(signed_vote(alice, 1, 1, prevote(H256::repeat_byte(1), 1)));
+		fisherman.import_vote(signed_vote(alice, 1, 1, prevote(H256::repeat_byte(2), 1)));
+
+		assert!(fisherman.equivocations().is_empty());
+		assert_eq!(fisherman.invalid_votes(), 4);
+	}
+
+	#[test]
+	fn ignores_votes_with_bad_signatures() {
+		let mut fisherman = fisherman(Arc::new(substrate_test_runtime_client::new()));
+		let alice = Ed25519Keyring::Alice;
+
+		fisherman.import_vote(signed_vote(alice, 0, 1, prevote(H256::repeat_byte(1), 1)));
+		let mut vote = signed_vote(alice, 1, 1, prevote(H256::repeat_byte(2), 1));
+		vote.set_id = 0;
+		fisherman.import_vote(vote);
+
+		assert!(fisherman.equivocations().is_empty());
+		assert_eq!(fisherman.invalid_votes(), 1);
+	}
+
+	#[test]
+	fn detects_conflicting_justifications() {
+		let mut fisherman = fisherman(Arc::new(substrate_test_runtime_client::new()));
+		let signers = [Ed25519Keyring::Alice, Ed25519Keyring::Bob];
+
+		let first = justification(1, H256::repeat_byte(1), 1, &signers);
+		fisherman.import_evidence(Evidence::Justification(first.clone()));
+		fisherman.import_evidence(Evidence::Justification(first));
+		fisherman.import_evidence(Evidence::Justification(justification(
+			1,
+			H256::repeat_byte(2),
+			1,
+			&signers,
+		)));
+
+		assert_eq!(
+			fisherman.conflicting_justifications(),
+			&[ConflictingJustifications {
+				set_id: 0,
+				first: JustificationTarget { round: 1, hash: H256::repeat_byte(1), number: 1 },
+				second: JustificationTarget { round: 1, hash: H256::repeat_byte(2), number: 1 },
+			}]
+		);
+		// both signers precommitted to both blocks in the same round.
+		assert_eq!(fisherman.equivocations().len(), 2);
+
+		// blocks with different numbers can only conflict if we know they are on different forks.
+		fisherman.import_evidence(Evidence::Justification(justification(
+			2,
+			H256::repeat_byte(3),
+			2,
+			&signers,
+		)));
+		assert_eq!(fisherman.conflicting_justifications().len(), 1);
+		assert_eq!(fisherman.invalid_justifications(), 0);
+	}
+
+	#[test]
+	fn ignores_invalid_justifications() {
+		let mut fisherman = fisherman(Arc::new(substrate_test_runtime_client::new()));
+		let signers = [Ed25519Keyring::Alice, Ed25519Keyring::Bob];
+
+		fisherman.import_evidence(Evidence::Justification(justification(
+			1,
+			H256::repeat_byte(1),
+			1,
+			&signers,
+		)));
+		// a single authority is below the threshold of the set.
+		fisherman.import_evidence(Evidence::Justification(justification(
+			1,
+			H256::repeat_byte(2),
+			1,
+			&signers[..1],
+		)));
+		// neither signer is an authority of the set.
+		fisherman.import_evidence(Evidence::Justification(justification(
+			1,
+			H256::repeat_byte(3),
+			1,
+			&[Ed25519Keyring::Charlie, Ed25519Keyring::Dave],
+		)));
+
+		assert!(fisherman.conflicting_justifications().is_empty());
+		assert_eq!(fisherman.invalid_justifications(), 2);
+		// the precommits of authorities are still scanned, the others are invalid.
+		assert_eq!(fisherman.equivocations().len(), 1);
+		assert_eq!(
+			*fisherman.equivocations()[0].offender(),
+			AuthorityId::from(Ed25519Keyring::Alice.public())
+		);
+		assert_eq!(fisherman.invalid_votes(), 2);
+	}
+
+	#[test]
+	fn scans_stored_justifications() {
+		let mut client = Arc::new(substrate_test_runtime_client::new());
+		let block = BlockBuilderBuilder::new(&*client)
+			.on_parent_block(client.genesis_hash())
+			.with_parent_block_number(0)
+			.build()
+			.unwrap()
+			.build()
+			.unwrap()
+			.block;
+		let hash = block.header.hash();
+
+		let signers = [Ed25519Keyring::Alice, Ed25519Keyring::Bob];
+		let stored = justification(1, hash, 1, &signers);
+		futures::executor::block_on(client.import_justified(
+			BlockOrigin::Own,
+			block,
+			Justifications::from((GRANDPA_ENGINE_ID, stored.encode())),
+		))
+		.unwrap();
+
+		let mut fisherman = fisherman(client);
+		fisherman.scan_justifications(0, 1).unwrap();
+		fisherman.import_evidence(Evidence::Justification(justification(
+			1,
+			H256::repeat_byte(1),
+			1,
+			&signers,
+		)));
+
+		assert_eq!(fisherman.conflicting_justifications().len(), 1);
+		assert_eq!(fisherman.conflicting_justifications()[0].first.hash, hash);
+		assert_eq!(fisherman.equivocations().len(), 2);
+	}
+}
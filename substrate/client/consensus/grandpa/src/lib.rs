@@ -118,6 +118,7 @@ mod aux_schema;
 mod communication;
 mod environment;
 mod finality_proof;
+mod fisherman;
 mod import;
 mod justification;
 mod notification;
@@ -127,10 +128,13 @@ mod voting_rule;
 pub mod warp_proof;
 
 pub use authorities::{AuthoritySet, AuthoritySetChanges, SharedAuthoritySet};
-pub use aux_schema::best_justification;
+pub use aux_schema::{aux_keys, best_justification};
 pub use communication::grandpa_protocol_name::standard_name as protocol_standard_name;
 pub use finality_grandpa::voter::report;
 pub use finality_proof::{FinalityProof, FinalityProofError, FinalityProofProvider};
+pub use fisherman::{
+	CollectedVote, ConflictingJustifications, Evidence, Fisherman, JustificationTarget,
+};
 pub use import::{find_forced_change, find_scheduled_change, GrandpaBlockImport};
 pub use justification::GrandpaJustification;
 pub use notification::{GrandpaJustificationSender, GrandpaJustificationStream};
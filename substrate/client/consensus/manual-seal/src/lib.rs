@@ -19,7 +19,7 @@
 //! A manual sealing engine: the engine listens for rpc calls to seal blocks and create forks.
 //! This is suitable for a testing environment.
 
-use futures::prelude::*;
+use futures::{channel::mpsc, prelude::*};
 use futures_timer::Delay;
 use prometheus_endpoint::Registry;
 use sc_client_api::{
@@ -40,6 +40,8 @@ use std::{marker::PhantomData, sync::Arc, time::Duration};
 mod error;
 mod finalize_block;
 mod seal_block;
+mod storage_override;
+mod time_travel;
 
 pub mod consensus;
 pub mod rpc;
@@ -48,11 +50,13 @@ pub use self::{
 	consensus::ConsensusDataProvider,
 	error::Error,
 	finalize_block::{finalize_block, FinalizeBlockParams},
-	rpc::{CreatedBlock, EngineCommand},
+	rpc::{CreatedBlock, EngineCommand, StorageOverrideKey, TimeTravel},
 	seal_block::{seal_block, SealBlockParams, MAX_PROPOSAL_DURATION},
+	storage_override::{resolve_storage_key, StorageOverrides},
+	time_travel::TimeOffsets,
 };
 use sc_transaction_pool_api::TransactionPool;
-use sp_api::ProvideRuntimeApi;
+use sp_api::{CallApiAt, Metadata, ProvideRuntimeApi};
 
 const LOG_TARGET: &str = "manual-seal";
 
@@ -128,6 +132,10 @@ pub struct InstantSealParams<B: BlockT, BI, E, C: ProvideRuntimeApi<B>, TP, SC,
 	/// Shared reference to the transaction pool.
 	pub pool: Arc<TP>,
 
+	/// Receiving end of a channel for sending additional commands to the authorship task, e.g.
+	/// from the manual seal RPC. A block is still sealed for every imported transaction.
+	pub commands_stream: Option<mpsc::Receiver<EngineCommand<<B as BlockT>::Hash>>>,
+
 	/// SelectChain strategy.
 	pub select_chain: SC,
 
@@ -165,7 +173,8 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 ) where
 	B: BlockT + 'static,
 	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
-	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + CallApiAt<B> + 'static,
+	C::Api: Metadata<B>,
 	CB: ClientBackend<B> + 'static,
 	E: Environment<B> + 'static,
 	E::Proposer: Proposer<B, Proof = P>,
@@ -175,6 +184,9 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 	CIDP: CreateInherentDataProviders<B, ()>,
 	P: codec::Encode + Send + Sync + 'static,
 {
+	let mut time_offsets = TimeOffsets::default();
+	let mut storage_overrides = StorageOverrides::default();
+
 	while let Some(command) = commands_stream.next().await {
 		match command {
 			EngineCommand::SealNewBlock { create_empty, finalize, parent_hash, sender } => {
@@ -190,9 +202,64 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 					pool: pool.clone(),
 					client: client.clone(),
 					create_inherent_data_providers: &create_inherent_data_providers,
+					time_travel: None,
+					time_offsets: &mut time_offsets,
+					storage_overrides: &mut storage_overrides,
 				})
 				.await;
 			},
+			EngineCommand::SealNewBlocks {
+				count,
+				create_empty,
+				finalize,
+				mut parent_hash,
+				mut time_travel,
+				mut sender,
+			} => {
+				let mut created_blocks = Vec::new();
+				let mut result = Ok(());
+				for _ in 0..count {
+					let (block_sender, block_receiver) = futures::channel::oneshot::channel();
+					seal_block(SealBlockParams {
+						sender: Some(block_sender),
+						parent_hash,
+						finalize,
+						create_empty,
+						env: &mut env,
+						select_chain: &select_chain,
+						block_import: &mut block_import,
+						consensus_data_provider: consensus_data_provider.as_deref(),
+						pool: pool.clone(),
+						client: client.clone(),
+						create_inherent_data_providers: &create_inherent_data_providers,
+						time_travel: time_travel.take(),
+						time_offsets: &mut time_offsets,
+						storage_overrides: &mut storage_overrides,
+					})
+					.await;
+
+					match block_receiver.await.map_err(Error::from).and_then(|r| r) {
+						Ok(created_block) => {
+							// build the next block on top of this one, even if it's on a fork.
+							parent_hash = Some(created_block.hash);
+							created_blocks.push(created_block);
+						},
+						Err(err) => {
+							result = Err(err);
+							break
+						},
+					}
+				}
+				rpc::send_result(&mut sender, result.map(|_| created_blocks));
+			},
+			EngineCommand::SetStorage { key, value, mut sender } => {
+				let result =
+					resolve_storage_key(&*client, client.info().best_hash, key).map(|key| {
+						storage_overrides.set(key.clone(), value.map(|value| value.0));
+						key.into()
+					});
+				rpc::send_result(&mut sender, result);
+			},
 			EngineCommand::FinalizeBlock { hash, sender, justification } => {
 				let justification = justification.map(|j| (MANUAL_SEAL_ENGINE_ID, j));
 				finalize_block(FinalizeBlockParams {
@@ -217,6 +284,7 @@ pub async fn run_instant_seal<B, BI, CB, E, C, TP, SC, CIDP, P>(
 		env,
 		client,
 		pool,
+		commands_stream: rpc_commands_stream,
 		select_chain,
 		consensus_data_provider,
 		create_inherent_data_providers,
@@ -224,7 +292,8 @@ pub async fn run_instant_seal<B, BI, CB, E, C, TP, SC, CIDP, P>(
 ) where
 	B: BlockT + 'static,
 	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
-	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + CallApiAt<B> + 'static,
+	C::Api: Metadata<B>,
 	CB: ClientBackend<B> + 'static,
 	E: Environment<B> + 'static,
 	E::Proposer: Proposer<B, Proof = P>,
@@ -241,6 +310,8 @@ pub async fn run_instant_seal<B, BI, CB, E, C, TP, SC, CIDP, P>(
 		parent_hash: None,
 		sender: None,
 	});
+	let commands_stream =
+		stream::select(commands_stream, stream::iter(rpc_commands_stream).flatten());
 
 	run_manual_seal(ManualSealParams {
 		block_import,
@@ -267,6 +338,7 @@ pub async fn run_instant_seal_and_finalize<B, BI, CB, E, C, TP, SC, CIDP, P>(
 		env,
 		client,
 		pool,
+		commands_stream: rpc_commands_stream,
 		select_chain,
 		consensus_data_provider,
 		create_inherent_data_providers,
@@ -274,7 +346,8 @@ pub async fn run_instant_seal_and_finalize<B, BI, CB, E, C, TP, SC, CIDP, P>(
 ) where
 	B: BlockT + 'static,
 	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
-	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + CallApiAt<B> + 'static,
+	C::Api: Metadata<B>,
 	CB: ClientBackend<B> + 'static,
 	E: Environment<B> + 'static,
 	E::Proposer: Proposer<B, Proof = P>,
@@ -291,6 +364,8 @@ pub async fn run_instant_seal_and_finalize<B, BI, CB, E, C, TP, SC, CIDP, P>(
 		parent_hash: None,
 		sender: None,
 	});
+	let commands_stream =
+		stream::select(commands_stream, stream::iter(rpc_commands_stream).flatten());
 
 	run_manual_seal(ManualSealParams {
 		block_import,
@@ -346,12 +421,17 @@ pub async fn run_delayed_finalize<B, CB, C, S>(
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use codec::{Decode, Encode};
 	use sc_basic_authorship::ProposerFactory;
+	use sc_client_api::StorageProvider;
 	use sc_consensus::ImportedAux;
 	use sc_transaction_pool::{BasicPool, FullChainApi, Options, RevalidationType};
 	use sc_transaction_pool_api::{MaintainedTransactionPool, TransactionPool, TransactionSource};
+	use sp_core::storage::StorageKey;
 	use sp_inherents::InherentData;
 	use sp_runtime::generic::{Digest, DigestItem};
+	use sp_timestamp::TimestampInherentData;
+	use std::sync::atomic::{AtomicU64, Ordering};
 	use substrate_test_runtime_client::{
 		AccountKeyring::*, DefaultTestClientBuilderExt, TestClientBuilder, TestClientBuilderExt,
 	};
@@ -469,6 +549,67 @@ mod tests {
 		assert_eq!(client.header(created_block.hash).unwrap().unwrap().number, 1)
 	}
 
+	#[tokio::test]
+	async fn instant_seal_applies_commands() {
+		let builder = TestClientBuilder::new();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool_api = Arc::new(FullChainApi::new(client.clone(), None, &spawner.clone()));
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			pool_api,
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+		let (mut sink, commands_stream) = futures::channel::mpsc::channel(1024);
+		let future = run_instant_seal(InstantSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			pool: pool.clone(),
+			commands_stream: Some(commands_stream),
+			select_chain,
+			create_inherent_data_providers: |_, _| async { Ok(()) },
+			consensus_data_provider: None,
+		});
+		std::thread::spawn(|| {
+			let rt = tokio::runtime::Runtime::new().unwrap();
+			// spawn the background authorship task
+			rt.block_on(future);
+		});
+
+		// storage overrides sent through the commands stream are applied to the next block.
+		let key = StorageKey(b"instant_seal_key".to_vec());
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SetStorage {
+			key: StorageOverrideKey::Raw(key.0.clone().into()),
+			value: Some(b"value".to_vec().into()),
+			sender: Some(tx),
+		})
+		.await
+		.unwrap();
+		rx.await.unwrap().unwrap();
+
+		// which is still sealed as soon as a transaction is imported into the pool.
+		let mut imported_blocks = client.import_notification_stream();
+		let result = pool.submit_one(genesis_hash, SOURCE, uxt(Alice, 0)).await;
+		assert!(result.is_ok());
+		let block = imported_blocks.next().await.unwrap();
+		assert_eq!(block.header.number, 1);
+		assert_eq!(
+			client.storage(block.hash, &key).unwrap().map(|value| value.0),
+			Some(b"value".to_vec()),
+		);
+	}
+
 	#[tokio::test]
 	async fn instant_seal_delayed_finalize() {
 		let builder = TestClientBuilder::new();
@@ -760,6 +901,135 @@ mod tests {
 		assert!(client.header(imported.hash).unwrap().is_some())
 	}
 
+	// Records the timestamp inherent data of each block in its digest.
+	struct TimestampDigestProvider;
+
+	impl<B: BlockT> ConsensusDataProvider<B> for TimestampDigestProvider {
+		type Proof = ();
+
+		fn create_digest(
+			&self,
+			_parent: &B::Header,
+			inherents: &InherentData,
+		) -> Result<Digest, Error> {
+			let timestamp = inherents
+				.timestamp_inherent_data()?
+				.ok_or_else(|| Error::StringError("No timestamp inherent data".into()))?;
+			Ok(Digest { logs: vec![DigestItem::Other(timestamp.encode())] })
+		}
+
+		fn append_block_import(
+			&self,
+			_parent: &B::Header,
+			_params: &mut BlockImportParams<B>,
+			_inherents: &InherentData,
+			_proof: Self::Proof,
+		) -> Result<(), Error> {
+			Ok(())
+		}
+	}
+
+	#[tokio::test]
+	async fn manual_seal_create_blocks_with_time_travel() {
+		let builder = TestClientBuilder::new();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			api(),
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		// every block is one second after the previous one.
+		let now = Arc::new(AtomicU64::new(1_000));
+		let (mut sink, commands_stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			pool: pool.clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: Some(Box::new(TimestampDigestProvider)),
+			create_inherent_data_providers: move |_, _| {
+				let timestamp = now.fetch_add(1_000, Ordering::SeqCst);
+				async move { Ok(sp_timestamp::InherentDataProvider::new(timestamp.into())) }
+			},
+		});
+		std::thread::spawn(|| {
+			let rt = tokio::runtime::Runtime::new().unwrap();
+			rt.block_on(future);
+		});
+
+		let timestamp_of = |hash| {
+			let header = client.header(hash).unwrap().unwrap();
+			let timestamp = header.digest.logs.iter().find_map(|log| match log {
+				DigestItem::Other(data) => Some(u64::decode(&mut &data[..]).unwrap()),
+				_ => None,
+			});
+			(header.number, timestamp.unwrap())
+		};
+
+		// jump forward three blocks, starting at the given timestamp.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealNewBlocks {
+			count: 3,
+			create_empty: true,
+			finalize: false,
+			parent_hash: None,
+			time_travel: Some(TimeTravel { timestamp: Some(100_000), slot: None }),
+			sender: Some(tx),
+		})
+		.await
+		.unwrap();
+		let created_blocks = rx.await.unwrap().unwrap();
+		let blocks: Vec<_> = created_blocks.iter().map(|b| timestamp_of(b.hash)).collect();
+		assert_eq!(blocks, vec![(1, 100_000), (2, 101_000), (3, 102_000)]);
+		assert_eq!(client.info().best_hash, created_blocks[2].hash);
+
+		// time keeps moving forward from there.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealNewBlock {
+			parent_hash: None,
+			sender: Some(tx),
+			create_empty: true,
+			finalize: false,
+		})
+		.await
+		.unwrap();
+		let created_block = rx.await.unwrap().unwrap();
+		assert_eq!(timestamp_of(created_block.hash), (4, 103_000));
+
+		// create a fork on top of block #1.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealNewBlocks {
+			count: 2,
+			create_empty: true,
+			finalize: false,
+			parent_hash: Some(created_blocks[0].hash),
+			time_travel: None,
+			sender: Some(tx),
+		})
+		.await
+		.unwrap();
+		let fork_blocks = rx.await.unwrap().unwrap();
+		let blocks: Vec<_> = fork_blocks.iter().map(|b| timestamp_of(b.hash)).collect();
+		assert_eq!(blocks, vec![(2, 104_000), (3, 105_000)]);
+		assert!(fork_blocks.iter().all(|b| !b.aux.is_new_best));
+		let fork_header = client.header(fork_blocks[1].hash).unwrap().unwrap();
+		assert_eq!(fork_header.parent_hash, fork_blocks[0].hash);
+		assert_eq!(client.info().best_hash, created_block.hash);
+	}
+
 	#[tokio::test]
 	async fn manual_seal_post_hash() {
 		let builder = TestClientBuilder::new();
@@ -811,4 +1081,103 @@ mod tests {
 		let header = client.header(created_block.hash).unwrap().unwrap();
 		assert_eq!(header.number, 1);
 	}
+
+	#[tokio::test]
+	async fn manual_seal_storage_overrides() {
+		let builder = TestClientBuilder::new();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			api(),
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		let (sink, commands_stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			pool: pool.clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: None,
+			create_inherent_data_providers: |_, _| async { Ok(()) },
+		});
+		std::thread::spawn(|| {
+			let rt = tokio::runtime::Runtime::new().unwrap();
+			rt.block_on(future);
+		});
+
+		let key = StorageKey(b"manual_seal_key".to_vec());
+		let storage_at = |hash| client.storage(hash, &key).unwrap().map(|value| value.0);
+
+		let set_storage = |value: Option<&[u8]>| {
+			let (tx, rx) = futures::channel::oneshot::channel();
+			let command = EngineCommand::SetStorage {
+				key: StorageOverrideKey::Raw(key.0.clone().into()),
+				value: value.map(|value| value.to_vec().into()),
+				sender: Some(tx),
+			};
+			let mut sink = sink.clone();
+			async move {
+				sink.send(command).await.unwrap();
+				rx.await.unwrap().unwrap()
+			}
+		};
+		let seal_block = |mut sink: futures::channel::mpsc::Sender<_>| async move {
+			let (tx, rx) = futures::channel::oneshot::channel();
+			sink.send(EngineCommand::SealNewBlock {
+				parent_hash: None,
+				sender: Some(tx),
+				create_empty: true,
+				finalize: false,
+			})
+			.await
+			.unwrap();
+			rx.await.unwrap().unwrap().hash
+		};
+
+		assert_eq!(set_storage(Some(b"value")).await.0, key.0);
+		assert_eq!(storage_at(genesis_hash), None);
+
+		// the override is written in the next block and the state root is updated accordingly,
+		// otherwise the import would fail.
+		let block1 = seal_block(sink.clone()).await;
+		assert_eq!(storage_at(block1), Some(b"value".to_vec()));
+
+		// overrides are only applied once.
+		let block2 = seal_block(sink.clone()).await;
+		assert_eq!(storage_at(block2), Some(b"value".to_vec()));
+
+		// and can remove keys.
+		set_storage(None).await;
+		let block3 = seal_block(sink.clone()).await;
+		assert_eq!(storage_at(block3), None);
+		assert_eq!(client.header(block3).unwrap().unwrap().number, 3);
+	}
+
+	#[tokio::test]
+	async fn create_blocks_rejects_too_many_blocks() {
+		use rpc::{ManualSeal, ManualSealApiServer, MAX_CREATED_BLOCKS};
+
+		let (sink, mut commands_stream) = futures::channel::mpsc::channel(1);
+		let manual_seal = ManualSeal::<sp_core::H256>::new(sink);
+		let result =
+			manual_seal.create_blocks(MAX_CREATED_BLOCKS + 1, true, false, None, None).await;
+		assert!(result.is_err());
+
+		// the request never reached the authorship task.
+		drop(manual_seal);
+		assert!(commands_stream.next().await.is_none());
+	}
 }
@@ -29,6 +29,7 @@ use jsonrpsee::{
 };
 use sc_consensus::ImportedAux;
 use serde::{Deserialize, Serialize};
+use sp_core::Bytes;
 use sp_runtime::EncodedJustification;
 
 /// Sender passed to the authorship task to report errors or successes.
@@ -53,6 +54,37 @@ pub enum EngineCommand<Hash> {
 		/// sender to report errors/success to the rpc.
 		sender: Sender<CreatedBlock<Hash>>,
 	},
+	/// Tells the engine to propose `count` new blocks, each one built on top of the previous.
+	///
+	/// if time_travel is set, the first block is authored at the given timestamp and/or slot,
+	/// and the following blocks, including the ones sealed by later commands, keep moving
+	/// forward from there.
+	SealNewBlocks {
+		/// number of blocks to create.
+		count: u32,
+		/// if true, empty blocks(without extrinsics) will be created.
+		/// otherwise, will return Error::EmptyTransactionPool.
+		create_empty: bool,
+		/// instantly finalize the blocks?
+		finalize: bool,
+		/// specify the parent hash of the first block, which may not be the best block.
+		parent_hash: Option<Hash>,
+		/// timestamp and slot to author the first block at.
+		time_travel: Option<TimeTravel>,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<Vec<CreatedBlock<Hash>>>,
+	},
+	/// Tells the engine to write a storage value at the end of the next sealed block.
+	///
+	/// if value is None, the key is removed from the storage.
+	SetStorage {
+		/// key of the storage value to override.
+		key: StorageOverrideKey,
+		/// SCALE encoded value to write.
+		value: Option<Bytes>,
+		/// sender to report the raw storage key or errors to the rpc.
+		sender: Sender<Bytes>,
+	},
 	/// Tells the engine to finalize the block with the supplied hash
 	FinalizeBlock {
 		/// hash of the block
@@ -76,6 +108,42 @@ pub trait ManualSealApi<Hash> {
 		parent_hash: Option<Hash>,
 	) -> RpcResult<CreatedBlock<Hash>>;
 
+	/// Instructs the manual-seal authorship task to create a chain of new blocks, optionally
+	/// moving the time to the given timestamp and/or slot.
+	///
+	/// The first block is created on top of `parent_hash`, or the best block if not given,
+	/// which allows to deterministically create forks. Creation stops at the first failure.
+	/// At most [`MAX_CREATED_BLOCKS`] blocks are created per call.
+	#[method(name = "engine_createBlocks")]
+	async fn create_blocks(
+		&self,
+		count: u32,
+		create_empty: bool,
+		finalize: bool,
+		parent_hash: Option<Hash>,
+		time_travel: Option<TimeTravel>,
+	) -> RpcResult<Vec<CreatedBlock<Hash>>>;
+
+	/// Instructs the manual-seal authorship task to write `value` under the raw storage `key`
+	/// at the end of the next sealed block, or to remove the key if `value` is `None`.
+	///
+	/// The value overrides any change made by the block itself.
+	#[method(name = "engine_setStorage")]
+	async fn set_storage(&self, key: Bytes, value: Option<Bytes>) -> RpcResult<Bytes>;
+
+	/// Same as `engine_setStorage`, but the key is computed from the runtime metadata, given the
+	/// name of the pallet and of the storage item, and the SCALE encoded keys of a map.
+	///
+	/// Returns the raw storage key.
+	#[method(name = "engine_setStorageItem")]
+	async fn set_storage_item(
+		&self,
+		pallet: String,
+		item: String,
+		keys: Vec<Bytes>,
+		value: Option<Bytes>,
+	) -> RpcResult<Bytes>;
+
 	/// Instructs the manual-seal authorship task to finalize a block
 	#[method(name = "engine_finalizeBlock")]
 	async fn finalize_block(
@@ -101,11 +169,65 @@ pub struct CreatedBlock<Hash> {
 	pub proof_size: usize,
 }
 
+/// Maximum number of blocks created by a single `engine_createBlocks` call.
+pub const MAX_CREATED_BLOCKS: u32 = 1_000;
+
+/// Timestamp and slot to author a block at.
+///
+/// Overrides the values provided by the `sp-timestamp` and the BABE or Aura slot inherent
+/// data providers.
+///
+/// On BABE and Aura chains the runtime requires the slot to match the timestamp, i.e. the slot
+/// to be the timestamp divided by the slot duration, so both have to be moved together. Moving
+/// only one of them makes the block fail with `Timestamp slot must match CurrentSlot`.
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeTravel {
+	/// unix timestamp, in milliseconds.
+	pub timestamp: Option<u64>,
+	/// slot number.
+	pub slot: Option<u64>,
+}
+
+/// Key of a storage value to override.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageOverrideKey {
+	/// A raw storage key.
+	Raw(Bytes),
+	/// A storage item described by the runtime metadata.
+	Item {
+		/// name of the pallet.
+		pallet: String,
+		/// name of the storage item.
+		item: String,
+		/// SCALE encoded keys of the map, empty for a plain storage value.
+		keys: Vec<Bytes>,
+	},
+}
+
 impl<Hash> ManualSeal<Hash> {
 	/// Create new `ManualSeal` with the given reference to the client.
 	pub fn new(import_block_channel: mpsc::Sender<EngineCommand<Hash>>) -> Self {
 		Self { import_block_channel }
 	}
+
+	async fn send_set_storage(
+		&self,
+		key: StorageOverrideKey,
+		value: Option<Bytes>,
+	) -> RpcResult<Bytes> {
+		let mut sink = self.import_block_channel.clone();
+		let (sender, receiver) = oneshot::channel();
+		let command = EngineCommand::SetStorage { key, value, sender: Some(sender) };
+
+		sink.send(command).await?;
+
+		match receiver.await {
+			Ok(Ok(rx)) => Ok(rx),
+			Ok(Err(e)) => Err(e.into()),
+			Err(e) => Err(JsonRpseeError::to_call_error(e)),
+		}
+	}
 }
 
 #[async_trait]
@@ -135,6 +257,57 @@ impl<Hash: Send + 'static> ManualSealApiServer<Hash> for ManualSeal<Hash> {
 		}
 	}
 
+	async fn create_blocks(
+		&self,
+		count: u32,
+		create_empty: bool,
+		finalize: bool,
+		parent_hash: Option<Hash>,
+		time_travel: Option<TimeTravel>,
+	) -> RpcResult<Vec<CreatedBlock<Hash>>> {
+		if count > MAX_CREATED_BLOCKS {
+			return Err(Error::StringError(format!(
+				"Can not create more than {} blocks at once, requested {}",
+				MAX_CREATED_BLOCKS, count
+			))
+			.into())
+		}
+
+		let mut sink = self.import_block_channel.clone();
+		let (sender, receiver) = oneshot::channel();
+		let command = EngineCommand::SealNewBlocks {
+			count,
+			create_empty,
+			finalize,
+			parent_hash,
+			time_travel,
+			sender: Some(sender),
+		};
+
+		sink.send(command).await?;
+
+		match receiver.await {
+			Ok(Ok(rx)) => Ok(rx),
+			Ok(Err(e)) => Err(e.into()),
+			Err(e) => Err(JsonRpseeError::to_call_error(e)),
+		}
+	}
+
+	async fn set_storage(&self, key: Bytes, value: Option<Bytes>) -> RpcResult<Bytes> {
+		self.send_set_storage(StorageOverrideKey::Raw(key), value).await
+	}
+
+	async fn set_storage_item(
+		&self,
+		pallet: String,
+		item: String,
+		keys: Vec<Bytes>,
+		value: Option<Bytes>,
+	) -> RpcResult<Bytes> {
+		self.send_set_storage(StorageOverrideKey::Item { pallet, item, keys }, value)
+			.await
+	}
+
 	async fn finalize_block(
 		&self,
 		hash: Hash,
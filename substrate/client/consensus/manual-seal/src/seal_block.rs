@@ -18,11 +18,13 @@
 
 //! Block sealing utilities
 
-use crate::{rpc, ConsensusDataProvider, CreatedBlock, Error};
+use crate::{
+	rpc, ConsensusDataProvider, CreatedBlock, Error, StorageOverrides, TimeOffsets, TimeTravel,
+};
 use futures::prelude::*;
 use sc_consensus::{BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult, StateAction};
 use sc_transaction_pool_api::TransactionPool;
-use sp_api::ProvideRuntimeApi;
+use sp_api::{CallApiAt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
 use sp_consensus::{self, BlockOrigin, Environment, Proposer, SelectChain};
 use sp_inherents::{CreateInherentDataProviders, InherentDataProvider};
@@ -57,6 +59,12 @@ pub struct SealBlockParams<'a, B: BlockT, BI, SC, C: ProvideRuntimeApi<B>, E, TP
 	pub block_import: &'a mut BI,
 	/// Something that can create the inherent data providers.
 	pub create_inherent_data_providers: &'a CIDP,
+	/// author the block at the given timestamp and/or slot.
+	pub time_travel: Option<TimeTravel>,
+	/// offsets applied to the inherent data, updated when `time_travel` is set.
+	pub time_offsets: &'a mut TimeOffsets,
+	/// storage values to write at the end of the block, cleared once the block is imported.
+	pub storage_overrides: &'a mut StorageOverrides,
 }
 
 /// seals a new block with the given params
@@ -72,12 +80,15 @@ pub async fn seal_block<B, BI, SC, C, E, TP, CIDP, P>(
 		env,
 		create_inherent_data_providers,
 		consensus_data_provider: digest_provider,
+		time_travel,
+		time_offsets,
+		storage_overrides,
 		mut sender,
 	}: SealBlockParams<'_, B, BI, SC, C, E, TP, CIDP, P>,
 ) where
 	B: BlockT,
 	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
-	C: HeaderBackend<B> + ProvideRuntimeApi<B>,
+	C: HeaderBackend<B> + ProvideRuntimeApi<B> + CallApiAt<B>,
 	E: Environment<B>,
 	E::Proposer: Proposer<B, Proof = P>,
 	TP: TransactionPool<Block = B>,
@@ -104,7 +115,12 @@ pub async fn seal_block<B, BI, SC, C, E, TP, CIDP, P>(
 			.await
 			.map_err(|e| Error::Other(e))?;
 
-		let inherent_data = inherent_data_providers.create_inherent_data().await?;
+		let mut inherent_data = inherent_data_providers.create_inherent_data().await?;
+
+		if let Some(time_travel) = time_travel {
+			time_offsets.travel(&inherent_data, time_travel)?;
+		}
+		time_offsets.apply(&mut inherent_data)?;
 
 		let proposer = env.init(&parent).map_err(|err| Error::StringError(err.to_string())).await?;
 		let inherents_len = inherent_data.len();
@@ -129,16 +145,21 @@ pub async fn seal_block<B, BI, SC, C, E, TP, CIDP, P>(
 			return Err(Error::EmptyTransactionPool)
 		}
 
-		let (header, body) = proposal.block.deconstruct();
+		let (mut header, body) = proposal.block.deconstruct();
+		let mut storage_changes = proposal.storage_changes;
+		if !storage_overrides.is_empty() {
+			storage_changes = storage_overrides.apply(&*client, parent.hash(), storage_changes)?;
+			header.set_state_root(storage_changes.transaction_storage_root);
+		}
+
 		let proof = proposal.proof;
 		let proof_size = proof.encoded_size();
 		let mut params = BlockImportParams::new(BlockOrigin::Own, header.clone());
 		params.body = Some(body);
 		params.finalized = finalize;
 		params.fork_choice = Some(ForkChoiceStrategy::LongestChain);
-		params.state_action = StateAction::ApplyChanges(sc_consensus::StorageChanges::Changes(
-			proposal.storage_changes,
-		));
+		params.state_action =
+			StateAction::ApplyChanges(sc_consensus::StorageChanges::Changes(storage_changes));
 
 		if let Some(digest_provider) = digest_provider {
 			digest_provider.append_block_import(&parent, &mut params, &inherent_data, proof)?;
@@ -150,11 +171,14 @@ pub async fn seal_block<B, BI, SC, C, E, TP, CIDP, P>(
 		post_header.digest_mut().logs.extend(params.post_digests.iter().cloned());
 
 		match block_import.import_block(params).await? {
-			ImportResult::Imported(aux) => Ok(CreatedBlock {
-				hash: <B as BlockT>::Header::hash(&post_header),
-				aux,
-				proof_size,
-			}),
+			ImportResult::Imported(aux) => {
+				storage_overrides.clear();
+				Ok(CreatedBlock {
+					hash: <B as BlockT>::Header::hash(&post_header),
+					aux,
+					proof_size,
+				})
+			},
 			other => Err(other.into()),
 		}
 	};
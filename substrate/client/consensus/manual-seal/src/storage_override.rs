@@ -0,0 +1,253 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Storage overrides, allow to set arbitrary storage values in the next sealed block.
+
+use crate::{rpc::StorageOverrideKey, Error};
+use codec::Decode;
+use frame_metadata::{
+	v14::{StorageEntryType, StorageHasher},
+	RuntimeMetadata, RuntimeMetadataPrefixed,
+};
+use sp_api::{CallApiAt, Metadata, ProvideRuntimeApi, StateBackend, StorageChanges};
+use sp_core::{
+	hashing::{blake2_128, blake2_256, twox_128, twox_256, twox_64},
+	storage::ChildInfo,
+};
+use sp_runtime::traits::Block as BlockT;
+use std::collections::BTreeMap;
+
+/// Storage values to write at the end of the next sealed block.
+///
+/// The overrides are applied on top of the changes made by the block, so they win over any
+/// value written by its extrinsics. Only the main trie can be overridden.
+///
+/// Note that the resulting state can't be reproduced by executing the block, so other nodes
+/// would fail to import it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StorageOverrides {
+	/// The values to write, `None` removes the key.
+	changes: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl StorageOverrides {
+	/// Schedule `value` to be written under `key`, or the key to be removed if `value` is `None`.
+	pub fn set(&mut self, key: Vec<u8>, value: Option<Vec<u8>>) {
+		self.changes.insert(key, value);
+	}
+
+	/// Returns `true` if there is nothing to override.
+	pub fn is_empty(&self) -> bool {
+		self.changes.is_empty()
+	}
+
+	/// Discard all the scheduled overrides, usually once they have been sealed.
+	pub fn clear(&mut self) {
+		self.changes.clear();
+	}
+
+	/// Apply the overrides to the `changes` of a block built on top of `parent_hash`.
+	///
+	/// Returns the updated changes, including the new state root.
+	pub fn apply<B, C>(
+		&self,
+		client: &C,
+		parent_hash: B::Hash,
+		changes: StorageChanges<B>,
+	) -> Result<StorageChanges<B>, Error>
+	where
+		B: BlockT,
+		C: CallApiAt<B>,
+	{
+		let state = client.state_at(parent_hash).map_err(|e| Error::Other(Box::new(e)))?;
+		let state_version = client
+			.runtime_version_at(parent_hash)
+			.map_err(|e| Error::Other(Box::new(e)))?
+			.state_version();
+
+		let mut main_storage_changes =
+			changes.main_storage_changes.into_iter().collect::<BTreeMap<_, _>>();
+		main_storage_changes.extend(self.changes.clone());
+		let main_storage_changes = main_storage_changes.into_iter().collect::<Vec<_>>();
+
+		let child_infos = changes
+			.child_storage_changes
+			.iter()
+			.map(|(storage_key, _)| ChildInfo::new_default(storage_key))
+			.collect::<Vec<_>>();
+		let (transaction_storage_root, transaction) = state.full_storage_root(
+			main_storage_changes.iter().map(|(k, v)| (&k[..], v.as_deref())),
+			child_infos.iter().zip(changes.child_storage_changes.iter()).map(
+				|(child_info, (_, changes))| {
+					(child_info, changes.iter().map(|(k, v)| (&k[..], v.as_deref())))
+				},
+			),
+			state_version,
+		);
+
+		Ok(StorageChanges::<B> {
+			main_storage_changes,
+			child_storage_changes: changes.child_storage_changes,
+			offchain_storage_changes: changes.offchain_storage_changes,
+			transaction,
+			transaction_storage_root,
+			transaction_index_changes: changes.transaction_index_changes,
+		})
+	}
+}
+
+/// Find the storage prefix and the key hashers of the `$pallet.$item` storage item.
+macro_rules! storage_entry {
+	($metadata:expr, $pallet:expr, $item:expr) => {
+		$metadata
+			.pallets
+			.into_iter()
+			.find(|pallet| pallet.name == $pallet)
+			.and_then(|pallet| pallet.storage)
+			.and_then(|storage| {
+				let entry = storage.entries.into_iter().find(|entry| entry.name == $item)?;
+				let hashers = match entry.ty {
+					StorageEntryType::Plain(_) => Vec::new(),
+					StorageEntryType::Map { hashers, .. } => hashers,
+				};
+				Some((storage.prefix, hashers))
+			})
+	};
+}
+
+/// Resolve `key` to a raw storage key, using the metadata of the runtime at `at` if needed.
+pub fn resolve_storage_key<B, C>(
+	client: &C,
+	at: B::Hash,
+	key: StorageOverrideKey,
+) -> Result<Vec<u8>, Error>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: Metadata<B>,
+{
+	let (pallet, item, keys) = match key {
+		StorageOverrideKey::Raw(key) => return Ok(key.0),
+		StorageOverrideKey::Item { pallet, item, keys } => (pallet, item, keys),
+	};
+
+	let metadata = client.runtime_api().metadata(at).map_err(|e| Error::Other(Box::new(e)))?;
+	let metadata = RuntimeMetadataPrefixed::decode(&mut &metadata[..])
+		.map_err(|e| Error::StringError(format!("Runtime metadata can't be decoded: {e}")))?;
+
+	let entry = match metadata.1 {
+		RuntimeMetadata::V14(metadata) => storage_entry!(metadata, pallet, item),
+		RuntimeMetadata::V15(metadata) => storage_entry!(metadata, pallet, item),
+		_ => return Err(Error::StringError("Unsupported runtime metadata version".into())),
+	};
+	let (prefix, hashers) = entry.ok_or_else(|| {
+		Error::StringError(format!("Storage item {pallet}.{item} not found in the metadata"))
+	})?;
+
+	let keys = keys.into_iter().map(|key| key.0).collect::<Vec<_>>();
+	storage_item_key(&prefix, &item, &hashers, &keys)
+}
+
+/// Compute the key of the value of a storage item, given the SCALE encoded `keys` of the map.
+fn storage_item_key(
+	prefix: &str,
+	item: &str,
+	hashers: &[StorageHasher],
+	keys: &[Vec<u8>],
+) -> Result<Vec<u8>, Error> {
+	if hashers.len() != keys.len() {
+		return Err(Error::StringError(format!(
+			"Storage item {prefix}.{item} expects {} keys, got {}",
+			hashers.len(),
+			keys.len()
+		)))
+	}
+
+	let mut storage_key = twox_128(prefix.as_bytes()).to_vec();
+	storage_key.extend(twox_128(item.as_bytes()));
+	for (hasher, key) in hashers.iter().zip(keys) {
+		match hasher {
+			StorageHasher::Blake2_128 => storage_key.extend(blake2_128(key)),
+			StorageHasher::Blake2_256 => storage_key.extend(blake2_256(key)),
+			StorageHasher::Blake2_128Concat => {
+				storage_key.extend(blake2_128(key));
+				storage_key.extend(key);
+			},
+			StorageHasher::Twox128 => storage_key.extend(twox_128(key)),
+			StorageHasher::Twox256 => storage_key.extend(twox_256(key)),
+			StorageHasher::Twox64Concat => {
+				storage_key.extend(twox_64(key));
+				storage_key.extend(key);
+			},
+			StorageHasher::Identity => storage_key.extend(key),
+		}
+	}
+
+	Ok(storage_key)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn plain_storage_item_key() {
+		let key = storage_item_key("Sudo", "Key", &[], &[]).unwrap();
+		assert_eq!(key, [twox_128(b"Sudo"), twox_128(b"Key")].concat());
+
+		assert!(storage_item_key("Sudo", "Key", &[], &[vec![1]]).is_err());
+	}
+
+	#[test]
+	fn map_storage_item_key() {
+		let account = vec![1u8; 32];
+		let key = storage_item_key(
+			"System",
+			"Account",
+			&[StorageHasher::Blake2_128Concat],
+			&[account.clone()],
+		)
+		.unwrap();
+		assert_eq!(
+			key,
+			[&twox_128(b"System")[..], &twox_128(b"Account"), &blake2_128(&account), &account]
+				.concat()
+		);
+
+		let key = storage_item_key(
+			"Staking",
+			"ErasStakers",
+			&[StorageHasher::Twox64Concat, StorageHasher::Identity],
+			&[vec![7, 0, 0, 0], account.clone()],
+		)
+		.unwrap();
+		assert_eq!(
+			key,
+			[
+				&twox_128(b"Staking")[..],
+				&twox_128(b"ErasStakers"),
+				&twox_64(&[7, 0, 0, 0]),
+				&[7, 0, 0, 0],
+				&account
+			]
+			.concat()
+		);
+
+		assert!(storage_item_key("System", "Account", &[StorageHasher::Identity], &[]).is_err());
+	}
+}
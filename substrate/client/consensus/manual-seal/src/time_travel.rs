@@ -0,0 +1,167 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Time travel support, allows to seal blocks at an arbitrary timestamp and slot.
+
+use crate::{rpc::TimeTravel, Error};
+use sp_consensus_slots::Slot;
+use sp_inherents::{InherentData, InherentIdentifier};
+use sp_timestamp::{Timestamp, TimestampInherentData};
+
+/// Inherent identifiers of the slot inherent data used by the supported consensus engines.
+const SLOT_INHERENT_IDENTIFIERS: [InherentIdentifier; 2] = [
+	sp_consensus_babe::inherents::INHERENT_IDENTIFIER,
+	sp_consensus_aura::inherents::INHERENT_IDENTIFIER,
+];
+
+/// Offsets applied to the timestamp and slot reported by the inherent data providers.
+///
+/// Once the time has been moved by a [`TimeTravel`] request, all the following blocks keep
+/// the same offsets, so that the time never goes back to the one of the inherent data
+/// providers and the runtime keeps accepting the produced timestamps.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TimeOffsets {
+	/// Offset of the timestamp, in milliseconds.
+	timestamp: i64,
+	/// Offset of the slot.
+	slot: i64,
+}
+
+impl TimeOffsets {
+	/// Update the offsets so that the given inherent data, as produced by the inherent data
+	/// providers, reports the timestamp and slot requested by `time_travel`.
+	pub fn travel(
+		&mut self,
+		inherent_data: &InherentData,
+		time_travel: TimeTravel,
+	) -> Result<(), Error> {
+		if let Some(timestamp) = time_travel.timestamp {
+			let current = inherent_data
+				.timestamp_inherent_data()?
+				.ok_or_else(|| Error::StringError("No timestamp inherent data".into()))?;
+			self.timestamp = offset(*current, timestamp);
+		}
+
+		if let Some(slot) = time_travel.slot {
+			let current = slot_inherent_data(inherent_data)?
+				.ok_or_else(|| Error::StringError("No slot inherent data".into()))?;
+			self.slot = offset(*current, slot);
+		}
+
+		Ok(())
+	}
+
+	/// Apply the offsets to the given inherent data.
+	pub fn apply(&self, inherent_data: &mut InherentData) -> Result<(), Error> {
+		if self.timestamp != 0 {
+			if let Some(timestamp) = inherent_data.timestamp_inherent_data()? {
+				let timestamp = Timestamp::new(shift(*timestamp, self.timestamp));
+				inherent_data.replace_data(sp_timestamp::INHERENT_IDENTIFIER, &timestamp);
+			}
+		}
+
+		if self.slot != 0 {
+			for identifier in SLOT_INHERENT_IDENTIFIERS {
+				if let Some(slot) = inherent_data.get_data::<Slot>(&identifier)? {
+					let slot = Slot::from(shift(*slot, self.slot));
+					inherent_data.replace_data(identifier, &slot);
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+fn slot_inherent_data(inherent_data: &InherentData) -> Result<Option<Slot>, Error> {
+	for identifier in SLOT_INHERENT_IDENTIFIERS {
+		if let Some(slot) = inherent_data.get_data::<Slot>(&identifier)? {
+			return Ok(Some(slot))
+		}
+	}
+	Ok(None)
+}
+
+fn offset(current: u64, target: u64) -> i64 {
+	(target as i128 - current as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+fn shift(value: u64, offset: i64) -> u64 {
+	value.saturating_add_signed(offset)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn inherent_data(timestamp: u64, slot: u64) -> InherentData {
+		let mut inherent_data = InherentData::new();
+		inherent_data
+			.put_data(sp_timestamp::INHERENT_IDENTIFIER, &Timestamp::new(timestamp))
+			.unwrap();
+		inherent_data
+			.put_data(sp_consensus_babe::inherents::INHERENT_IDENTIFIER, &Slot::from(slot))
+			.unwrap();
+		inherent_data
+	}
+
+	fn timestamp_and_slot(inherent_data: &InherentData) -> (u64, u64) {
+		let timestamp = inherent_data.timestamp_inherent_data().unwrap().unwrap();
+		let slot = slot_inherent_data(inherent_data).unwrap().unwrap();
+		(*timestamp, *slot)
+	}
+
+	#[test]
+	fn offsets_are_kept_for_following_blocks() {
+		let mut offsets = TimeOffsets::default();
+
+		let mut data = inherent_data(6_000, 1);
+		offsets
+			.travel(&data, TimeTravel { timestamp: Some(600_000), slot: Some(100) })
+			.unwrap();
+		offsets.apply(&mut data).unwrap();
+		assert_eq!(timestamp_and_slot(&data), (600_000, 100));
+
+		let mut data = inherent_data(12_000, 2);
+		offsets.apply(&mut data).unwrap();
+		assert_eq!(timestamp_and_slot(&data), (606_000, 101));
+
+		// Moving back in time.
+		let mut data = inherent_data(18_000, 3);
+		offsets
+			.travel(&data, TimeTravel { timestamp: Some(6_000), slot: None })
+			.unwrap();
+		offsets.apply(&mut data).unwrap();
+		assert_eq!(timestamp_and_slot(&data), (6_000, 102));
+
+		let mut data = inherent_data(24_000, 4);
+		offsets.apply(&mut data).unwrap();
+		assert_eq!(timestamp_and_slot(&data), (12_000, 103));
+	}
+
+	#[test]
+	fn travel_requires_inherent_data() {
+		let mut offsets = TimeOffsets::default();
+		let data = InherentData::new();
+
+		assert!(offsets.travel(&data, TimeTravel { timestamp: Some(1), slot: None }).is_err());
+		assert!(offsets.travel(&data, TimeTravel { timestamp: None, slot: Some(1) }).is_err());
+		assert!(offsets.travel(&data, TimeTravel::default()).is_ok());
+		assert_eq!(offsets, TimeOffsets::default());
+	}
+}
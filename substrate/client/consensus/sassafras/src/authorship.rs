@@ -0,0 +1,654 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Sassafras tickets generation, slot claiming and block authoring worker.
+
+use super::*;
+
+use std::{
+	collections::BTreeSet,
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration,
+};
+
+use futures::prelude::*;
+use log::{info, warn};
+
+use sc_consensus::block_import::{BlockImport, BlockImportParams, StateAction};
+use sc_consensus_epochs::EpochIdentifier;
+use sc_consensus_slots::{
+	BackoffAuthoringBlocksStrategy, InherentDataProviderExt, SlotInfo, StorageChanges,
+};
+use sc_telemetry::TelemetryHandle;
+use sc_transaction_pool_api::OffchainTransactionPoolFactory;
+use sp_api::ApiExt;
+use sp_consensus::{BlockOrigin, Environment, Proposer, SelectChain};
+use sp_consensus_sassafras::{
+	ticket_id_threshold,
+	vrf::{RingContext, RingProver},
+	EphemeralPublic, KEY_TYPE,
+};
+use sp_core::{
+	blake2_64,
+	crypto::{ByteArray, Wraps},
+	ed25519::Pair as EphemeralPair,
+	Pair,
+};
+use sp_inherents::CreateInherentDataProviders;
+use sp_keystore::KeystorePtr;
+use sp_runtime::DigestItem;
+
+/// Get the secondary author index for the given slot.
+///
+/// Used to assign the slots without an associated ticket. The caller must ensure that
+/// the epoch authorities list is not empty.
+pub(crate) fn secondary_authority_index(slot: Slot, epoch: &Epoch) -> AuthorityIndex {
+	let rand = u64::from_le_bytes((epoch.randomness, slot).using_encoded(blake2_64));
+	(rand % epoch.authorities.len() as u64) as AuthorityIndex
+}
+
+/// Try to claim an epoch slot.
+///
+/// If a ticket is assigned to the slot the claim is possible only if the ticket was generated
+/// by this node, otherwise the slot falls back to the secondary authority.
+pub(crate) fn claim_slot(
+	slot: Slot,
+	epoch: &Epoch,
+	maybe_ticket: Option<(TicketId, TicketBody)>,
+	keystore: &KeystorePtr,
+) -> Option<(SlotClaim, AuthorityId)> {
+	if epoch.authorities.is_empty() {
+		return None
+	}
+
+	let mut vrf_sign_data = vrf::slot_claim_sign_data(&epoch.randomness, slot, epoch.index);
+
+	let (authority_idx, ticket_claim) = match maybe_ticket {
+		Some((ticket_id, ticket_body)) => {
+			debug!(target: LOG_TARGET, "Trying primary claim of slot {} ({:032x})", slot, ticket_id);
+			let (authority_idx, erased_seed) = epoch.tickets_aux.get(&ticket_id)?;
+
+			// Bind the claim to the ticket and reveal the key committed in its body.
+			vrf_sign_data.push_transcript_data(&ticket_body.encode());
+			let revealed_input =
+				vrf::revealed_key_input(&epoch.randomness, ticket_body.attempt_idx, epoch.index);
+			// Sign data has room for up to three inputs and only the claim one is there.
+			vrf_sign_data.push_vrf_input(revealed_input).ok()?;
+
+			// Prove ownership of the ticket using the erased key.
+			let challenge = vrf_sign_data.challenge::<32>();
+			let erased_signature = EphemeralPair::from_seed(erased_seed).sign(&challenge);
+
+			(*authority_idx, Some(TicketClaim { erased_signature }))
+		},
+		None => {
+			debug!(target: LOG_TARGET, "Trying secondary claim of slot {}", slot);
+			(secondary_authority_index(slot, epoch), None)
+		},
+	};
+
+	let authority_id = epoch.authorities.get(authority_idx as usize)?;
+
+	let vrf_signature = keystore
+		.bandersnatch_vrf_sign(KEY_TYPE, authority_id.as_ref(), &vrf_sign_data)
+		.ok()
+		.flatten()?;
+
+	let claim = SlotClaim { authority_idx, slot, vrf_signature, ticket_claim };
+
+	Some((claim, authority_id.clone()))
+}
+
+/// Generate the tickets of the local authorities for the given epoch.
+///
+/// The secrets required to claim the slots assigned to the generated tickets are saved
+/// in the epoch `tickets_aux`. Returns the tickets to be submitted on-chain.
+pub(crate) fn generate_epoch_tickets(
+	epoch: &mut Epoch,
+	keystore: &KeystorePtr,
+	ring_ctx: &RingContext,
+) -> Vec<TicketEnvelope> {
+	let mut tickets = Vec::new();
+
+	let threshold = ticket_id_threshold(
+		epoch.config.redundancy_factor,
+		epoch.length,
+		epoch.config.attempts_number,
+		epoch.authorities.len() as u32,
+	);
+	debug!(
+		target: LOG_TARGET,
+		"Generating tickets for epoch {} (threshold: {:032x})", epoch.index, threshold
+	);
+
+	let public_keys: Vec<sp_core::bandersnatch::Public> =
+		epoch.authorities.iter().map(|id| id.as_inner_ref().clone()).collect();
+
+	for (authority_idx, authority_id) in epoch.authorities.clone().into_iter().enumerate() {
+		if !keystore.has_keys(&[(authority_id.to_raw_vec(), KEY_TYPE)]) {
+			continue
+		}
+
+		let Some(prover) = ring_ctx.prover(&public_keys, authority_idx) else {
+			warn!(target: LOG_TARGET, "Unable to build the ring prover for {:?}", authority_id);
+			continue
+		};
+
+		for attempt_idx in 0..epoch.config.attempts_number {
+			let Some((ticket_id, envelope, erased_seed)) =
+				make_ticket(epoch, &authority_id, attempt_idx, threshold, &prover, keystore)
+			else {
+				continue
+			};
+			tickets.push(envelope);
+			epoch
+				.tickets_aux
+				.insert(ticket_id, (authority_idx as AuthorityIndex, erased_seed));
+		}
+	}
+
+	tickets
+}
+
+/// Make the ticket for the given attempt, if its identifier is below the threshold.
+fn make_ticket(
+	epoch: &Epoch,
+	authority_id: &AuthorityId,
+	attempt_idx: u32,
+	threshold: TicketId,
+	prover: &RingProver,
+	keystore: &KeystorePtr,
+) -> Option<(TicketId, TicketEnvelope, EphemeralSeed)> {
+	let ticket_id_input = vrf::ticket_id_input(&epoch.randomness, attempt_idx, epoch.index);
+	let ticket_id_pre_output = keystore
+		.bandersnatch_vrf_pre_output(KEY_TYPE, authority_id.as_ref(), &ticket_id_input)
+		.ok()
+		.flatten()?;
+	let ticket_id = vrf::make_ticket_id(&ticket_id_input, &ticket_id_pre_output);
+	if ticket_id >= threshold {
+		return None
+	}
+
+	let (erased_pair, erased_seed) = EphemeralPair::generate();
+
+	let revealed_input = vrf::revealed_key_input(&epoch.randomness, attempt_idx, epoch.index);
+	let revealed_pre_output = keystore
+		.bandersnatch_vrf_pre_output(KEY_TYPE, authority_id.as_ref(), &revealed_input)
+		.ok()
+		.flatten()?;
+	let revealed_seed = vrf::make_revealed_key_seed(&revealed_input, &revealed_pre_output);
+	let revealed_public: EphemeralPublic = EphemeralPair::from_seed(&revealed_seed).public();
+
+	let body = TicketBody { attempt_idx, erased_public: erased_pair.public(), revealed_public };
+
+	let sign_data = vrf::ticket_body_sign_data(&body, ticket_id_input);
+	let signature = keystore
+		.bandersnatch_ring_vrf_sign(KEY_TYPE, authority_id.as_ref(), &sign_data, prover)
+		.ok()
+		.flatten()?;
+
+	debug!(target: LOG_TARGET, "Generated ticket {:032x} (attempt {})", ticket_id, attempt_idx);
+
+	Some((ticket_id, TicketEnvelope { body, signature }, erased_seed))
+}
+
+/// Parameters for Sassafras.
+pub struct SassafrasParams<B: BlockT, C, SC, E, I, SO, L, CIDP, BS> {
+	/// The keystore that manages the keys of the node.
+	pub keystore: KeystorePtr,
+
+	/// The client to use
+	pub client: Arc<C>,
+
+	/// The SelectChain Strategy
+	pub select_chain: SC,
+
+	/// The environment we are producing blocks for.
+	pub env: E,
+
+	/// The underlying block-import object to supply our produced blocks to.
+	/// This must be a `SassafrasBlockImport` or a wrapper of it, otherwise
+	/// critical consensus logic will be omitted.
+	pub block_import: I,
+
+	/// A sync oracle
+	pub sync_oracle: SO,
+
+	/// Hook into the sync module to control the justification sync process.
+	pub justification_sync_link: L,
+
+	/// Something that can create the inherent data providers.
+	pub create_inherent_data_providers: CIDP,
+
+	/// Force authoring of blocks even if we are offline
+	pub force_authoring: bool,
+
+	/// Strategy and parameters for backing off block production.
+	pub backoff_authoring_blocks: Option<BS>,
+
+	/// The source of timestamps for relative slots
+	pub sassafras_link: SassafrasLink<B>,
+
+	/// The proportion of the slot dedicated to proposing.
+	///
+	/// The block proposing will be limited to this proportion of the slot from the starting of the
+	/// slot. However, the proposing can still take longer when there is some lenience factor
+	/// applied, because there were no blocks produced for some slots.
+	pub block_proposal_slot_portion: SlotProportion,
+
+	/// The maximum proportion of the slot dedicated to proposing with any lenience factor applied
+	/// due to no blocks being produced.
+	pub max_block_proposal_slot_portion: Option<SlotProportion>,
+
+	/// Handle use to report telemetries.
+	pub telemetry: Option<TelemetryHandle>,
+
+	/// The offchain transaction pool factory.
+	///
+	/// Will be used when submitting the tickets.
+	pub offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+}
+
+/// Start the Sassafras worker.
+pub fn start_sassafras<B, C, SC, E, I, SO, CIDP, BS, L, Error>(
+	SassafrasParams {
+		keystore,
+		client,
+		select_chain,
+		env,
+		block_import,
+		sync_oracle,
+		justification_sync_link,
+		create_inherent_data_providers,
+		force_authoring,
+		backoff_authoring_blocks,
+		sassafras_link,
+		block_proposal_slot_portion,
+		max_block_proposal_slot_portion,
+		telemetry,
+		offchain_tx_pool_factory,
+	}: SassafrasParams<B, C, SC, E, I, SO, L, CIDP, BS>,
+) -> Result<SassafrasWorker, sp_consensus::Error>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>
+		+ HeaderBackend<B>
+		+ HeaderMetadata<B, Error = ClientError>
+		+ AuxStore
+		+ Send
+		+ Sync
+		+ 'static,
+	C::Api: SassafrasApi<B>,
+	SC: SelectChain<B> + 'static,
+	E: Environment<B, Error = Error> + Send + Sync + 'static,
+	E::Proposer: Proposer<B, Error = Error>,
+	I: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
+	SO: SyncOracle + Send + Sync + Clone + 'static,
+	L: sc_consensus::JustificationSyncLink<B> + 'static,
+	CIDP: CreateInherentDataProviders<B, ()> + Send + Sync + 'static,
+	CIDP::InherentDataProviders: InherentDataProviderExt + Send,
+	BS: BackoffAuthoringBlocksStrategy<NumberFor<B>> + Send + Sync + 'static,
+	Error: std::error::Error + Send + From<sp_consensus::Error> + From<I::Error> + 'static,
+{
+	let worker = SassafrasSlotWorker {
+		client: client.clone(),
+		block_import,
+		env,
+		sync_oracle: sync_oracle.clone(),
+		justification_sync_link,
+		force_authoring,
+		backoff_authoring_blocks,
+		keystore,
+		epoch_changes: sassafras_link.epoch_changes.clone(),
+		config: sassafras_link.config.clone(),
+		block_proposal_slot_portion,
+		max_block_proposal_slot_portion,
+		telemetry,
+		offchain_tx_pool_factory,
+		tickets_epochs: BTreeSet::new(),
+	};
+
+	info!(target: LOG_TARGET, "🌳 Starting Sassafras Authorship worker");
+
+	let slot_worker = sc_consensus_slots::start_slot_worker(
+		sassafras_link.config.slot_duration(),
+		select_chain,
+		sc_consensus_slots::SimpleSlotWorkerToSlotWorker(worker),
+		sync_oracle,
+		create_inherent_data_providers,
+	);
+
+	Ok(SassafrasWorker { inner: Box::pin(slot_worker) })
+}
+
+/// Worker for Sassafras which implements `Future<Output=()>`. This must be polled.
+#[must_use]
+pub struct SassafrasWorker {
+	inner: Pin<Box<dyn Future<Output = ()> + Send + 'static>>,
+}
+
+impl Future for SassafrasWorker {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+		self.inner.as_mut().poll(cx)
+	}
+}
+
+struct SassafrasSlotWorker<B: BlockT, C, E, I, SO, L, BS> {
+	client: Arc<C>,
+	block_import: I,
+	env: E,
+	sync_oracle: SO,
+	justification_sync_link: L,
+	force_authoring: bool,
+	backoff_authoring_blocks: Option<BS>,
+	keystore: KeystorePtr,
+	epoch_changes: SharedEpochChanges<B, Epoch>,
+	config: SassafrasConfiguration,
+	block_proposal_slot_portion: SlotProportion,
+	max_block_proposal_slot_portion: Option<SlotProportion>,
+	telemetry: Option<TelemetryHandle>,
+	offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+	/// Epochs for which the tickets have already been handled.
+	tickets_epochs: BTreeSet<EpochIdentifier<B::Hash, NumberFor<B>>>,
+}
+
+impl<B, C, E, I, SO, L, BS> SassafrasSlotWorker<B, C, E, I, SO, L, BS>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + HeaderMetadata<B, Error = ClientError> + AuxStore,
+	C::Api: SassafrasApi<B>,
+{
+	/// Generate and submit the tickets for the epoch following the one of `slot`.
+	///
+	/// Tickets are accepted by the runtime only during the first half of the epoch
+	/// which precedes the one they are generated for, and are handled once per epoch.
+	fn submit_next_epoch_tickets(
+		&mut self,
+		parent_header: &B::Header,
+		slot: Slot,
+		epoch_descriptor: &ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>,
+	) {
+		// The next epoch is announced by the first block of the current one.
+		let ViableEpochDescriptor::Signaled(_, epoch_header) = epoch_descriptor else { return };
+		let epoch_length = *epoch_header.end_slot - *epoch_header.start_slot;
+		if slot >= epoch_header.start_slot + epoch_length / 2 {
+			return
+		}
+
+		let parent_hash = parent_header.hash();
+		let (next_epoch_id, mut next_epoch) = {
+			let epoch_changes = self.epoch_changes.shared_data();
+			let next_epoch_id = match epoch_changes.epoch_descriptor_for_child_of(
+				descendent_query(&*self.client),
+				&parent_hash,
+				*parent_header.number(),
+				epoch_header.end_slot,
+			) {
+				Ok(Some(ViableEpochDescriptor::Signaled(id, header)))
+					if header.start_slot == epoch_header.end_slot =>
+					id,
+				_ => return,
+			};
+			if self.tickets_epochs.contains(&next_epoch_id) {
+				return
+			}
+			let Some(next_epoch) = epoch_changes.epoch(&next_epoch_id).cloned() else { return };
+			(next_epoch_id, next_epoch)
+		};
+
+		if !next_epoch.tickets_aux.is_empty() {
+			// Tickets were generated before a restart.
+			self.tickets_epochs.insert(next_epoch_id);
+			return
+		}
+
+		let ring_ctx = match self.client.runtime_api().ring_context(parent_hash) {
+			Ok(Some(ring_ctx)) => ring_ctx,
+			Ok(None) => {
+				debug!(target: LOG_TARGET, "Ring context not available, skipping tickets");
+				return
+			},
+			Err(err) => {
+				warn!(target: LOG_TARGET, "Unable to fetch the ring context: {}", err);
+				return
+			},
+		};
+
+		self.tickets_epochs.insert(next_epoch_id.clone());
+
+		let tickets = generate_epoch_tickets(&mut next_epoch, &self.keystore, &ring_ctx);
+		if tickets.is_empty() {
+			return
+		}
+
+		{
+			let mut epoch_changes = self.epoch_changes.shared_data();
+			let Some(epoch) = epoch_changes.epoch_mut(&next_epoch_id) else { return };
+			epoch.tickets_aux = next_epoch.tickets_aux;
+			if let Err(err) = aux_schema::write_epoch_changes::<B, _, _>(&epoch_changes, |insert| {
+				self.client.insert_aux(insert, [])
+			}) {
+				warn!(target: LOG_TARGET, "Unable to persist tickets: {}", err);
+			}
+		}
+
+		let tickets_count = tickets.len();
+		let mut runtime_api = self.client.runtime_api();
+
+		// Register the offchain tx pool to be able to use it from the runtime.
+		runtime_api.register_extension(
+			self.offchain_tx_pool_factory.offchain_transaction_pool(parent_hash),
+		);
+
+		match runtime_api.submit_tickets_unsigned_extrinsic(parent_hash, tickets) {
+			Ok(true) => info!(
+				target: LOG_TARGET,
+				"🎫 Submitted {} tickets for epoch {}", tickets_count, next_epoch.index
+			),
+			Ok(false) => warn!(target: LOG_TARGET, "Unable to submit tickets"),
+			Err(err) => warn!(target: LOG_TARGET, "Unable to submit tickets: {}", err),
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl<B, C, E, I, Error, SO, L, BS> sc_consensus_slots::SimpleSlotWorker<B>
+	for SassafrasSlotWorker<B, C, E, I, SO, L, BS>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>
+		+ HeaderBackend<B>
+		+ HeaderMetadata<B, Error = ClientError>
+		+ AuxStore
+		+ Send
+		+ Sync,
+	C::Api: SassafrasApi<B>,
+	E: Environment<B, Error = Error> + Send + Sync,
+	E::Proposer: Proposer<B, Error = Error>,
+	I: BlockImport<B> + Send + Sync + 'static,
+	SO: SyncOracle + Send + Clone + Sync,
+	L: sc_consensus::JustificationSyncLink<B>,
+	BS: BackoffAuthoringBlocksStrategy<NumberFor<B>> + Send + Sync,
+	Error: std::error::Error + Send + From<sp_consensus::Error> + From<I::Error> + 'static,
+{
+	type Claim = (SlotClaim, AuthorityId);
+	type SyncOracle = SO;
+	type JustificationSyncLink = L;
+	type CreateProposer =
+		Pin<Box<dyn Future<Output = Result<E::Proposer, sp_consensus::Error>> + Send + 'static>>;
+	type Proposer = E::Proposer;
+	type BlockImport = I;
+	type AuxData = ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>;
+
+	fn logging_target(&self) -> &'static str {
+		LOG_TARGET
+	}
+
+	fn block_import(&mut self) -> &mut Self::BlockImport {
+		&mut self.block_import
+	}
+
+	fn aux_data(
+		&self,
+		parent: &B::Header,
+		slot: Slot,
+	) -> Result<Self::AuxData, sp_consensus::Error> {
+		self.epoch_changes
+			.shared_data()
+			.epoch_descriptor_for_child_of(
+				descendent_query(&*self.client),
+				&parent.hash(),
+				*parent.number(),
+				slot,
+			)
+			.map_err(|e| sp_consensus::Error::ChainLookup(e.to_string()))?
+			.ok_or(sp_consensus::Error::InvalidAuthoritiesSet)
+	}
+
+	fn authorities_len(&self, epoch_descriptor: &Self::AuxData) -> Option<usize> {
+		self.epoch_changes
+			.shared_data()
+			.viable_epoch(epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))
+			.map(|epoch| epoch.as_ref().authorities.len())
+	}
+
+	async fn claim_slot(
+		&mut self,
+		parent_header: &B::Header,
+		slot: Slot,
+		epoch_descriptor: &ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>,
+	) -> Option<Self::Claim> {
+		self.submit_next_epoch_tickets(parent_header, slot, epoch_descriptor);
+
+		debug!(target: LOG_TARGET, "Attempting to claim slot {}", slot);
+
+		let maybe_ticket = self
+			.client
+			.runtime_api()
+			.slot_ticket(parent_header.hash(), slot)
+			.map_err(|err| warn!(target: LOG_TARGET, "Unable to fetch slot ticket: {}", err))
+			.ok()?;
+
+		let claim = claim_slot(
+			slot,
+			self.epoch_changes
+				.shared_data()
+				.viable_epoch(epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))?
+				.as_ref(),
+			maybe_ticket,
+			&self.keystore,
+		);
+
+		if claim.is_some() {
+			debug!(target: LOG_TARGET, "Claimed slot {}", slot);
+		}
+
+		claim
+	}
+
+	fn pre_digest_data(&self, _slot: Slot, claim: &Self::Claim) -> Vec<DigestItem> {
+		vec![DigestItem::from(&claim.0)]
+	}
+
+	async fn block_import_params(
+		&self,
+		header: B::Header,
+		header_hash: &B::Hash,
+		body: Vec<B::Extrinsic>,
+		storage_changes: StorageChanges<B>,
+		(_, public): Self::Claim,
+		epoch_descriptor: Self::AuxData,
+	) -> Result<BlockImportParams<B>, sp_consensus::Error> {
+		let signature: AuthoritySignature = self
+			.keystore
+			.bandersnatch_sign(KEY_TYPE, public.as_ref(), header_hash.as_ref())
+			.map_err(|e| sp_consensus::Error::CannotSign(format!("{}. Key: {:?}", e, public)))?
+			.ok_or_else(|| {
+				sp_consensus::Error::CannotSign(format!(
+					"Could not find key in keystore. Key: {:?}",
+					public
+				))
+			})?
+			.into();
+
+		let mut import_block = BlockImportParams::new(BlockOrigin::Own, header);
+		import_block.post_digests.push(DigestItem::from(&signature));
+		import_block.body = Some(body);
+		import_block.state_action =
+			StateAction::ApplyChanges(sc_consensus::StorageChanges::Changes(storage_changes));
+		import_block
+			.insert_intermediate(INTERMEDIATE_KEY, SassafrasIntermediate::<B> { epoch_descriptor });
+
+		Ok(import_block)
+	}
+
+	fn force_authoring(&self) -> bool {
+		self.force_authoring
+	}
+
+	fn should_backoff(&self, slot: Slot, chain_head: &B::Header) -> bool {
+		if let Some(ref strategy) = self.backoff_authoring_blocks {
+			if let Ok(chain_head_slot) = find_slot::<B>(chain_head) {
+				return strategy.should_backoff(
+					*chain_head.number(),
+					chain_head_slot,
+					self.client.info().finalized_number,
+					slot,
+					self.logging_target(),
+				)
+			}
+		}
+		false
+	}
+
+	fn sync_oracle(&mut self) -> &mut Self::SyncOracle {
+		&mut self.sync_oracle
+	}
+
+	fn justification_sync_link(&mut self) -> &mut Self::JustificationSyncLink {
+		&mut self.justification_sync_link
+	}
+
+	fn proposer(&mut self, block: &B::Header) -> Self::CreateProposer {
+		Box::pin(
+			self.env
+				.init(block)
+				.map_err(|e| sp_consensus::Error::ClientImport(e.to_string())),
+		)
+	}
+
+	fn telemetry(&self) -> Option<TelemetryHandle> {
+		self.telemetry.clone()
+	}
+
+	fn proposing_remaining_duration(&self, slot_info: &SlotInfo<B>) -> Duration {
+		let parent_slot = find_slot::<B>(&slot_info.chain_head).ok();
+
+		sc_consensus_slots::proposing_remaining_duration(
+			parent_slot,
+			slot_info,
+			&self.block_proposal_slot_portion,
+			self.max_block_proposal_slot_portion.as_ref(),
+			sc_consensus_slots::SlotLenienceType::Exponential,
+			self.logging_target(),
+		)
+	}
+}
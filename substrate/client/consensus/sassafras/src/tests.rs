@@ -0,0 +1,1056 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Sassafras testsuite
+
+use super::*;
+use authorship::{claim_slot, generate_epoch_tickets, secondary_authority_index};
+use futures::{future, StreamExt};
+use parking_lot::Mutex;
+use sc_block_builder::{BlockBuilder, BlockBuilderBuilder};
+use sc_client_api::{BlockchainEvents, Finalizer};
+use sc_consensus::{
+	BlockCheckParams, BlockImport, BlockImportParams, BoxBlockImport, BoxJustificationImport,
+	ForkChoiceStrategy, ImportResult, Verifier,
+};
+use sc_consensus_slots::{BackoffAuthoringOnFinalizedHeadLagging, CheckedHeader};
+use sc_network_test::{Block as TestBlock, *};
+use sc_transaction_pool_api::{OffchainTransactionPoolFactory, RejectAllTxPool};
+use sp_consensus::{
+	BlockOrigin, DisableProofRecording, Environment, NoNetwork as DummyOracle, Proposal, Proposer,
+};
+use sp_consensus_sassafras::{vrf::RingContext, KEY_TYPE};
+use sp_core::crypto::Pair;
+use sp_inherents::{CreateInherentDataProviders, InherentData, InherentIdentifier};
+use sp_keyring::BandersnatchKeyring;
+use sp_keystore::{testing::MemoryKeystore, Keystore, KeystorePtr};
+use sp_runtime::generic::{Digest, DigestItem};
+use sp_timestamp::Timestamp;
+use std::{cell::RefCell, task::Poll, time::Duration};
+use verification::{check_header, VerificationParams};
+
+type Item = DigestItem;
+
+type Error = sp_blockchain::Error;
+
+type TestClient = substrate_test_runtime_client::client::Client<
+	substrate_test_runtime_client::Backend,
+	substrate_test_runtime_client::ExecutorDispatch,
+	TestBlock,
+	substrate_test_runtime_client::runtime::RuntimeApi,
+>;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Stage {
+	PreSeal,
+	PostSeal,
+}
+
+type Mutator = Arc<dyn Fn(&mut TestHeader, Stage) + Send + Sync>;
+
+type SassafrasBlockImport =
+	PanickingBlockImport<crate::SassafrasBlockImport<TestBlock, TestClient, Arc<TestClient>>>;
+
+const SLOT_DURATION_MS: u64 = 1000;
+
+const EPOCH_LENGTH: u32 = 6;
+
+const AUTHORITIES: [BandersnatchKeyring; 3] =
+	[BandersnatchKeyring::Alice, BandersnatchKeyring::Bob, BandersnatchKeyring::Charlie];
+
+// The test runtime doesn't provide the Sassafras epochs, thus the configuration is built here.
+fn test_config() -> SassafrasConfiguration {
+	SassafrasConfiguration {
+		slot_duration: SlotDuration::from_millis(SLOT_DURATION_MS),
+		genesis_epoch: sp_consensus_sassafras::Epoch {
+			index: 0,
+			start: 0.into(),
+			length: EPOCH_LENGTH,
+			randomness: [0; 32],
+			authorities: AUTHORITIES.iter().map(|authority| authority.public().into()).collect(),
+			config: EpochConfiguration { redundancy_factor: 1, attempts_number: 2 },
+		},
+	}
+}
+
+/// Inherent data provider only exposing the slot.
+///
+/// The test runtime doesn't check any consensus related inherent.
+struct SlotInherentDataProvider(Slot);
+
+impl std::ops::Deref for SlotInherentDataProvider {
+	type Target = Slot;
+
+	fn deref(&self) -> &Slot {
+		&self.0
+	}
+}
+
+#[async_trait::async_trait]
+impl sp_inherents::InherentDataProvider for SlotInherentDataProvider {
+	async fn provide_inherent_data(&self, _: &mut InherentData) -> Result<(), sp_inherents::Error> {
+		Ok(())
+	}
+
+	async fn try_handle_error(
+		&self,
+		_: &InherentIdentifier,
+		_: &[u8],
+	) -> Option<Result<(), sp_inherents::Error>> {
+		None
+	}
+}
+
+#[derive(Clone)]
+struct DummyFactory {
+	client: Arc<TestClient>,
+	epoch_changes: SharedEpochChanges<TestBlock, Epoch>,
+	config: SassafrasConfiguration,
+	mutator: Mutator,
+}
+
+struct DummyProposer {
+	factory: DummyFactory,
+	parent_hash: Hash,
+}
+
+impl Environment<TestBlock> for DummyFactory {
+	type CreateProposer = future::Ready<Result<DummyProposer, Error>>;
+	type Proposer = DummyProposer;
+	type Error = Error;
+
+	fn init(&mut self, parent_header: &<TestBlock as BlockT>::Header) -> Self::CreateProposer {
+		future::ready(Ok(DummyProposer {
+			factory: self.clone(),
+			parent_hash: parent_header.hash(),
+		}))
+	}
+}
+
+impl DummyProposer {
+	fn propose_with(
+		&mut self,
+		mut pre_digests: Digest,
+	) -> future::Ready<Result<Proposal<TestBlock, ()>, Error>> {
+		// The test runtime doesn't announce the epochs, thus the epoch change digest
+		// is added here for the first block of each epoch.
+		let maybe_slot = pre_digests
+			.logs
+			.iter()
+			.find_map(|log| SlotClaim::try_from(log).ok())
+			.map(|c| c.slot);
+		if let Some(slot) = maybe_slot {
+			let client = &*self.factory.client;
+			let parent_header = client.header(self.parent_hash).unwrap().unwrap();
+			let parent_slot = find_slot::<TestBlock>(&parent_header).unwrap();
+			let epoch_descriptor = self
+				.factory
+				.epoch_changes
+				.shared_data()
+				.epoch_descriptor_for_child_of(
+					descendent_query(client),
+					&self.parent_hash,
+					*parent_header.number(),
+					slot,
+				)
+				.unwrap()
+				.unwrap();
+			if parent_slot < epoch_descriptor.start_slot() {
+				let genesis_epoch = &self.factory.config.genesis_epoch;
+				let next_epoch = NextEpochDescriptor {
+					randomness: genesis_epoch.randomness,
+					authorities: genesis_epoch.authorities.clone(),
+					config: None,
+				};
+				pre_digests.push(DigestItem::Consensus(
+					SASSAFRAS_ENGINE_ID,
+					ConsensusLog::NextEpochData(next_epoch).encode(),
+				));
+			}
+		}
+
+		let block_builder = BlockBuilderBuilder::new(&*self.factory.client)
+			.on_parent_block(self.parent_hash)
+			.fetch_parent_block_number(&*self.factory.client)
+			.unwrap()
+			.with_inherent_digests(pre_digests)
+			.build()
+			.unwrap();
+
+		let mut block = match block_builder.build().map_err(|e| e.into()) {
+			Ok(b) => b.block,
+			Err(e) => return future::ready(Err(e)),
+		};
+
+		// mutate the block header according to the mutator.
+		(self.factory.mutator)(&mut block.header, Stage::PreSeal);
+
+		future::ready(Ok(Proposal { block, proof: (), storage_changes: Default::default() }))
+	}
+}
+
+impl Proposer<TestBlock> for DummyProposer {
+	type Error = Error;
+	type Proposal = future::Ready<Result<Proposal<TestBlock, ()>, Error>>;
+	type ProofRecording = DisableProofRecording;
+	type Proof = ();
+
+	fn propose(
+		mut self,
+		_: InherentData,
+		pre_digests: Digest,
+		_: Duration,
+		_: Option<usize>,
+	) -> Self::Proposal {
+		self.propose_with(pre_digests)
+	}
+}
+
+thread_local! {
+	static MUTATOR: RefCell<Mutator> = RefCell::new(Arc::new(|_, _|()));
+}
+
+#[derive(Clone)]
+pub struct PanickingBlockImport<B>(B);
+
+#[async_trait::async_trait]
+impl<B: BlockImport<TestBlock>> BlockImport<TestBlock> for PanickingBlockImport<B>
+where
+	B: Send,
+{
+	type Error = B::Error;
+
+	async fn import_block(
+		&mut self,
+		block: BlockImportParams<TestBlock>,
+	) -> Result<ImportResult, Self::Error> {
+		Ok(self.0.import_block(block).await.expect("importing block failed"))
+	}
+
+	async fn check_block(
+		&mut self,
+		block: BlockCheckParams<TestBlock>,
+	) -> Result<ImportResult, Self::Error> {
+		Ok(self.0.check_block(block).await.expect("checking block failed"))
+	}
+}
+
+type SassafrasPeer = Peer<Option<PeerData>, SassafrasBlockImport>;
+
+#[derive(Default)]
+pub struct SassafrasTestNet {
+	peers: Vec<SassafrasPeer>,
+}
+
+type TestHeader = <TestBlock as BlockT>::Header;
+
+type TestSelectChain =
+	substrate_test_runtime_client::LongestChain<substrate_test_runtime_client::Backend, TestBlock>;
+
+pub struct TestVerifier {
+	inner: SassafrasVerifier<
+		TestBlock,
+		PeersFullClient,
+		TestSelectChain,
+		Box<
+			dyn CreateInherentDataProviders<
+				TestBlock,
+				(),
+				InherentDataProviders = (SlotInherentDataProvider,),
+			>,
+		>,
+	>,
+	mutator: Mutator,
+}
+
+#[async_trait::async_trait]
+impl Verifier<TestBlock> for TestVerifier {
+	/// Verify the given data and return the BlockImportParams and an optional
+	/// new set of validators to import. If not, err with an Error-Message
+	/// presented to the User in the logs.
+	async fn verify(
+		&mut self,
+		mut block: BlockImportParams<TestBlock>,
+	) -> Result<BlockImportParams<TestBlock>, String> {
+		// apply post-sealing mutations (i.e. stripping seal, if desired).
+		(self.mutator)(&mut block.header, Stage::PostSeal);
+		self.inner.verify(block).await
+	}
+}
+
+pub struct PeerData {
+	link: SassafrasLink<TestBlock>,
+	block_import: Mutex<Option<BoxBlockImport<TestBlock>>>,
+}
+
+impl TestNetFactory for SassafrasTestNet {
+	type Verifier = TestVerifier;
+	type PeerData = Option<PeerData>;
+	type BlockImport = SassafrasBlockImport;
+
+	fn make_block_import(
+		&self,
+		client: PeersClient,
+	) -> (
+		BlockImportAdapter<Self::BlockImport>,
+		Option<BoxJustificationImport<Block>>,
+		Option<PeerData>,
+	) {
+		let client = client.as_client();
+
+		let (block_import, link) =
+			crate::block_import(test_config(), client.clone(), client.clone())
+				.expect("can initialize block-import");
+
+		let block_import = PanickingBlockImport(block_import);
+
+		let data_block_import =
+			Mutex::new(Some(Box::new(block_import.clone()) as BoxBlockImport<_>));
+		(
+			BlockImportAdapter::new(block_import),
+			None,
+			Some(PeerData { link, block_import: data_block_import }),
+		)
+	}
+
+	fn make_verifier(&self, client: PeersClient, maybe_link: &Option<PeerData>) -> Self::Verifier {
+		use substrate_test_runtime_client::DefaultTestClientBuilderExt;
+
+		let client = client.as_client();
+		trace!(target: LOG_TARGET, "Creating a verifier");
+
+		// ensure block import and verifier are linked correctly.
+		let data = maybe_link
+			.as_ref()
+			.expect("sassafras link always provided to verifier instantiation");
+
+		let (_, longest_chain) = TestClientBuilder::new().build_with_longest_chain();
+
+		TestVerifier {
+			inner: SassafrasVerifier::new(
+				client.clone(),
+				longest_chain,
+				Box::new(|_, _| async {
+					let slot = Slot::from_timestamp(
+						Timestamp::current(),
+						SlotDuration::from_millis(SLOT_DURATION_MS),
+					);
+					Ok((SlotInherentDataProvider(slot),))
+				}),
+				data.link.config.clone(),
+				data.link.epoch_changes.clone(),
+				None,
+				OffchainTransactionPoolFactory::new(RejectAllTxPool::default()),
+			),
+			mutator: MUTATOR.with(|m| m.borrow().clone()),
+		}
+	}
+
+	fn peer(&mut self, i: usize) -> &mut SassafrasPeer {
+		trace!(target: LOG_TARGET, "Retrieving a peer");
+		&mut self.peers[i]
+	}
+
+	fn peers(&self) -> &Vec<SassafrasPeer> {
+		trace!(target: LOG_TARGET, "Retrieving peers");
+		&self.peers
+	}
+
+	fn peers_mut(&mut self) -> &mut Vec<SassafrasPeer> {
+		trace!(target: LOG_TARGET, "Retrieving peers, mutable");
+		&mut self.peers
+	}
+
+	fn mut_peers<F: FnOnce(&mut Vec<SassafrasPeer>)>(&mut self, closure: F) {
+		closure(&mut self.peers);
+	}
+}
+
+#[tokio::test]
+#[should_panic(expected = "No Sassafras pre-runtime digest found")]
+async fn rejects_empty_block() {
+	sp_tracing::try_init_simple();
+	let mut net = SassafrasTestNet::new(3);
+	let block_builder = |builder: BlockBuilder<_, _>| builder.build().unwrap().block;
+	net.mut_peers(|peer| {
+		peer[0].generate_blocks(1, BlockOrigin::NetworkInitialSync, block_builder);
+	})
+}
+
+fn create_keystore(authority: BandersnatchKeyring) -> KeystorePtr {
+	let keystore = MemoryKeystore::new();
+	keystore
+		.bandersnatch_generate_new(KEY_TYPE, Some(&authority.to_seed()))
+		.expect("Generates authority key");
+	keystore.into()
+}
+
+async fn run_one_test(mutator: impl Fn(&mut TestHeader, Stage) + Send + Sync + 'static) {
+	sp_tracing::try_init_simple();
+	let mutator = Arc::new(mutator) as Mutator;
+
+	MUTATOR.with(|m| *m.borrow_mut() = mutator.clone());
+
+	let net = SassafrasTestNet::new(3);
+
+	let net = Arc::new(Mutex::new(net));
+	let mut import_notifications = Vec::new();
+	let mut sassafras_futures = Vec::new();
+
+	for (peer_id, auth_id) in AUTHORITIES.iter().enumerate() {
+		let mut net = net.lock();
+		let peer = net.peer(peer_id);
+		let client = peer.client().as_client();
+		let select_chain = peer.select_chain().expect("Full client has select_chain");
+
+		let keystore = create_keystore(*auth_id);
+
+		let mut got_own = false;
+		let mut got_other = false;
+
+		let data = peer.data.as_ref().expect("sassafras link set up during initialization");
+
+		let environ = DummyFactory {
+			client: client.clone(),
+			epoch_changes: data.link.epoch_changes.clone(),
+			config: data.link.config.clone(),
+			mutator: mutator.clone(),
+		};
+
+		import_notifications.push(
+			// run each future until we get one of our own blocks with number higher than 5
+			// that was produced locally.
+			client
+				.import_notification_stream()
+				.take_while(move |n| {
+					future::ready(
+						n.header.number() < &5 || {
+							if n.origin == BlockOrigin::Own {
+								got_own = true;
+							} else {
+								got_other = true;
+							}
+
+							// continue until we have at least one block of our own
+							// and one of another peer.
+							!(got_own && got_other)
+						},
+					)
+				})
+				.for_each(|_| future::ready(())),
+		);
+
+		let client_clone = client.clone();
+		sassafras_futures.push(
+			start_sassafras(SassafrasParams {
+				block_import: data.block_import.lock().take().expect("import set up during init"),
+				select_chain,
+				client,
+				env: environ,
+				sync_oracle: DummyOracle,
+				create_inherent_data_providers: Box::new(move |parent, _| {
+					// Get the slot of the parent header and just increase this slot.
+					//
+					// Below we will running everything in one big future. If we would use
+					// time based slot, it can happen that on sassafras instance imports a block
+					// from another sassafras instance and then tries to build a block in the same
+					// slot making this test fail.
+					let parent_header = client_clone.header(parent).ok().flatten().unwrap();
+					let slot = find_slot::<TestBlock>(&parent_header).unwrap() + 1;
+
+					async move { Ok((SlotInherentDataProvider(slot),)) }
+				}),
+				force_authoring: false,
+				backoff_authoring_blocks: Some(BackoffAuthoringOnFinalizedHeadLagging::default()),
+				sassafras_link: data.link.clone(),
+				keystore,
+				justification_sync_link: (),
+				block_proposal_slot_portion: SlotProportion::new(0.5),
+				max_block_proposal_slot_portion: None,
+				telemetry: None,
+				offchain_tx_pool_factory: OffchainTransactionPoolFactory::new(
+					RejectAllTxPool::default(),
+				),
+			})
+			.expect("Starts sassafras"),
+		);
+	}
+	future::select(
+		futures::future::poll_fn(move |cx| {
+			let mut net = net.lock();
+			net.poll(cx);
+			for p in net.peers() {
+				for (h, e) in p.failed_verifications() {
+					panic!("Verification failed for {:?}: {}", h, e);
+				}
+			}
+
+			Poll::<()>::Pending
+		}),
+		future::select(future::join_all(import_notifications), future::join_all(sassafras_futures)),
+	)
+	.await;
+}
+
+#[tokio::test]
+async fn authoring_blocks() {
+	run_one_test(|_, _| ()).await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "valid sassafras headers must contain a slot claim")]
+async fn rejects_missing_slot_claim() {
+	run_one_test(|header: &mut TestHeader, stage| {
+		let v = std::mem::take(&mut header.digest_mut().logs);
+		header.digest_mut().logs = v
+			.into_iter()
+			.filter(|v| stage == Stage::PostSeal || SlotClaim::try_from(v).is_err())
+			.collect()
+	})
+	.await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "has a bad seal")]
+async fn rejects_missing_seals() {
+	run_one_test(|header: &mut TestHeader, stage| {
+		let v = std::mem::take(&mut header.digest_mut().logs);
+		header.digest_mut().logs = v
+			.into_iter()
+			.filter(|v| stage == Stage::PreSeal || AuthoritySignature::try_from(v).is_err())
+			.collect()
+	})
+	.await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "Expected epoch change to happen")]
+async fn rejects_missing_consensus_digests() {
+	run_one_test(|header: &mut TestHeader, stage| {
+		let v = std::mem::take(&mut header.digest_mut().logs);
+		header.digest_mut().logs = v
+			.into_iter()
+			.filter(|v| {
+				stage == Stage::PostSeal ||
+					v.try_to::<ConsensusLog>(OpaqueDigestItemId::Consensus(&SASSAFRAS_ENGINE_ID))
+						.is_none()
+			})
+			.collect()
+	})
+	.await;
+}
+
+fn test_epoch(authorities: &[BandersnatchKeyring]) -> Epoch {
+	sp_consensus_sassafras::Epoch {
+		index: 1,
+		start: (EPOCH_LENGTH as u64).into(),
+		length: EPOCH_LENGTH,
+		randomness: [0; 32],
+		authorities: authorities.iter().map(|authority| authority.public().into()).collect(),
+		config: EpochConfiguration { redundancy_factor: 1, attempts_number: 2 },
+	}
+	.into()
+}
+
+// Build a block header carrying the given claim and sealed by the given authority.
+fn make_sealed_header(claim: &SlotClaim, authority: BandersnatchKeyring) -> TestHeader {
+	let mut header = TestHeader::new(
+		1,
+		Default::default(),
+		Default::default(),
+		Default::default(),
+		Digest { logs: vec![Item::from(claim)] },
+	);
+	let signature: AuthoritySignature = authority.pair().sign(header.hash().as_ref()).into();
+	header.digest_mut().push(Item::from(&signature));
+	header
+}
+
+fn verify_header(
+	header: TestHeader,
+	claim: &SlotClaim,
+	epoch: &Epoch,
+	maybe_ticket: Option<(TicketId, TicketBody)>,
+) -> Result<CheckedHeader<TestHeader, verification::VerifiedHeaderInfo>, crate::Error<TestBlock>> {
+	check_header::<TestBlock>(VerificationParams {
+		header,
+		claim,
+		slot_now: claim.slot + 1,
+		epoch,
+		maybe_ticket,
+	})
+}
+
+#[test]
+fn claim_secondary_slots() {
+	let epoch = test_epoch(&AUTHORITIES);
+
+	// Without tickets every slot is assigned to exactly one authority.
+	for (authority_idx, authority) in AUTHORITIES.iter().enumerate() {
+		let keystore = create_keystore(*authority);
+		for slot in *epoch.start..*epoch.start + EPOCH_LENGTH as u64 {
+			let slot = Slot::from(slot);
+			let expected = secondary_authority_index(slot, &epoch) == authority_idx as u32;
+			match claim_slot(slot, &epoch, None, &keystore) {
+				Some((claim, authority_id)) => {
+					assert!(expected);
+					assert_eq!(claim.authority_idx, authority_idx as u32);
+					assert_eq!(authority_id, AuthorityId::from(authority.public()));
+					assert!(claim.ticket_claim.is_none());
+				},
+				None => assert!(!expected),
+			}
+		}
+	}
+}
+
+#[test]
+fn secondary_claim_verification() {
+	let epoch = test_epoch(&AUTHORITIES);
+	let slot = epoch.start;
+	let authority_idx = secondary_authority_index(slot, &epoch);
+	let authority = AUTHORITIES[authority_idx as usize];
+	let keystore = create_keystore(authority);
+
+	let (claim, _) = claim_slot(slot, &epoch, None, &keystore).unwrap();
+	let header = make_sealed_header(&claim, authority);
+
+	assert!(matches!(
+		verify_header(header.clone(), &claim, &epoch, None),
+		Ok(CheckedHeader::Checked(..))
+	));
+
+	// A header from the future is deferred.
+	let params = VerificationParams {
+		header: header.clone(),
+		claim: &claim,
+		slot_now: slot - 1,
+		epoch: &epoch,
+		maybe_ticket: None,
+	};
+	assert!(
+		matches!(check_header::<TestBlock>(params), Ok(CheckedHeader::Deferred(_, s)) if s == slot)
+	);
+
+	// Sealed by another authority.
+	let other = AUTHORITIES[(authority_idx as usize + 1) % AUTHORITIES.len()];
+	let bad_header = make_sealed_header(&claim, other);
+	assert!(matches!(
+		verify_header(bad_header, &claim, &epoch, None),
+		Err(crate::Error::BadSignature(_))
+	));
+
+	// Claimed by an authority which is not the secondary author.
+	let mut bad_claim = claim.clone();
+	bad_claim.authority_idx = (authority_idx + 1) % AUTHORITIES.len() as u32;
+	let bad_header = make_sealed_header(&bad_claim, other);
+	assert!(matches!(
+		verify_header(bad_header, &bad_claim, &epoch, None),
+		Err(crate::Error::InvalidAuthor(..))
+	));
+
+	// Missing seal.
+	let mut unsealed_header = header;
+	unsealed_header.digest_mut().pop();
+	assert!(matches!(
+		verify_header(unsealed_header, &claim, &epoch, None),
+		Err(crate::Error::HeaderBadSeal(_))
+	));
+}
+
+#[test]
+fn generate_and_claim_tickets() {
+	let authority = BandersnatchKeyring::Alice;
+	let keystore = create_keystore(authority);
+	let mut epoch = test_epoch(&[authority]);
+	let ring_ctx = RingContext::new_testing();
+
+	// With a single authority the threshold saturates and each attempt produces a ticket.
+	let tickets = generate_epoch_tickets(&mut epoch, &keystore, &ring_ctx);
+	assert_eq!(tickets.len(), epoch.config.attempts_number as usize);
+	assert_eq!(epoch.tickets_aux.len(), tickets.len());
+
+	let verifier = ring_ctx.verifier(&[authority.public()]).unwrap();
+	let tickets: Vec<_> = tickets
+		.into_iter()
+		.map(|ticket| {
+			let ticket_id_input =
+				vrf::ticket_id_input(&epoch.randomness, ticket.body.attempt_idx, epoch.index);
+			let sign_data = vrf::ticket_body_sign_data(&ticket.body, ticket_id_input.clone());
+			assert!(ticket.signature.ring_vrf_verify(&sign_data, &verifier));
+
+			let ticket_id = vrf::make_ticket_id(&ticket_id_input, &ticket.signature.pre_outputs[0]);
+			assert!(epoch.tickets_aux.contains_key(&ticket_id));
+			(ticket_id, ticket.body)
+		})
+		.collect();
+
+	let slot = epoch.start;
+	let ticket = tickets[0].clone();
+
+	let (claim, _) = claim_slot(slot, &epoch, Some(ticket.clone()), &keystore).unwrap();
+	assert!(claim.ticket_claim.is_some());
+	let header = make_sealed_header(&claim, authority);
+
+	assert!(matches!(
+		verify_header(header.clone(), &claim, &epoch, Some(ticket.clone())),
+		Ok(CheckedHeader::Checked(..))
+	));
+
+	// The claim doesn't refer to the ticket assigned to the slot.
+	assert!(matches!(
+		verify_header(header.clone(), &claim, &epoch, Some(tickets[1].clone())),
+		Err(crate::Error::BadTicketOwnershipProof)
+	));
+
+	// No ticket is assigned to the slot.
+	assert!(matches!(
+		verify_header(header, &claim, &epoch, None),
+		Err(crate::Error::UnexpectedTicketClaim)
+	));
+
+	// A ticket is assigned to the slot but a secondary claim is produced.
+	let mut secondary_claim = claim;
+	secondary_claim.ticket_claim = None;
+	let header = make_sealed_header(&secondary_claim, authority);
+	assert!(matches!(
+		verify_header(header, &secondary_claim, &epoch, Some(ticket.clone())),
+		Err(crate::Error::MissingTicketClaim)
+	));
+
+	// Tickets generated by other nodes can't be claimed.
+	epoch.tickets_aux.clear();
+	assert!(claim_slot(slot, &epoch, Some(ticket), &keystore).is_none());
+}
+
+#[test]
+fn tickets_are_dropped_for_skipped_epochs() {
+	let mut epoch = test_epoch(&AUTHORITIES);
+	epoch.tickets_aux.insert(42, (0, [1; 32]));
+
+	let same_epoch = epoch.clone_for_slot(epoch.start + EPOCH_LENGTH as u64 - 1);
+	assert_eq!(same_epoch, epoch);
+
+	let skipped = epoch.clone_for_slot(epoch.start + 2 * EPOCH_LENGTH as u64);
+	assert_eq!(skipped.index, epoch.index + 2);
+	assert_eq!(skipped.start, epoch.start + 2 * EPOCH_LENGTH as u64);
+	assert!(skipped.tickets_aux.is_empty());
+}
+
+// Propose and import a new Sassafras block on top of the given parent.
+async fn propose_and_import_block(
+	parent: &TestHeader,
+	slot: Option<Slot>,
+	proposer_factory: &mut DummyFactory,
+	block_import: &mut BoxBlockImport<TestBlock>,
+) -> Hash {
+	let mut proposer = proposer_factory.init(parent).await.unwrap();
+
+	let slot = slot.unwrap_or_else(|| find_slot::<TestBlock>(parent).unwrap() + 1);
+
+	let parent_hash = parent.hash();
+
+	let epoch_descriptor = proposer_factory
+		.epoch_changes
+		.shared_data()
+		.epoch_descriptor_for_child_of(
+			descendent_query(&*proposer_factory.client),
+			&parent_hash,
+			*parent.number(),
+			slot,
+		)
+		.unwrap()
+		.unwrap();
+
+	let epoch = proposer_factory
+		.epoch_changes
+		.shared_data()
+		.viable_epoch(&epoch_descriptor, |slot| Epoch::genesis(&proposer_factory.config, slot))
+		.unwrap()
+		.into_cloned();
+
+	let authority_idx = secondary_authority_index(slot, epoch.as_ref());
+	let authority = AUTHORITIES[authority_idx as usize];
+	let keystore = create_keystore(authority);
+	let (claim, _) = claim_slot(slot, epoch.as_ref(), None, &keystore).unwrap();
+
+	let pre_digest = Digest { logs: vec![Item::from(&claim)] };
+
+	let mut block = proposer.propose_with(pre_digest).await.unwrap().block;
+
+	let seal = {
+		// sign the pre-sealed hash of the block and then
+		// add it to a digest item.
+		let pre_hash = block.header.hash();
+		let signature: AuthoritySignature = authority.pair().sign(pre_hash.as_ref()).into();
+		Item::from(&signature)
+	};
+
+	let post_hash = {
+		block.header.digest_mut().push(seal.clone());
+		let h = block.header.hash();
+		block.header.digest_mut().pop();
+		h
+	};
+
+	let mut import = BlockImportParams::new(BlockOrigin::Own, block.header);
+	import.post_digests.push(seal);
+	import.body = Some(block.extrinsics);
+	import.insert_intermediate(
+		INTERMEDIATE_KEY,
+		SassafrasIntermediate::<TestBlock> { epoch_descriptor },
+	);
+	import.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+	let import_result = block_import.import_block(import).await.unwrap();
+
+	match import_result {
+		ImportResult::Imported(_) => {},
+		_ => panic!("expected block to be imported"),
+	}
+
+	post_hash
+}
+
+// Propose and import n valid Sassafras blocks that are built on top of the given parent.
+// The proposer takes care of producing epoch change digests according to the epoch
+// length (which is set to 6 slots in the test configuration).
+async fn propose_and_import_blocks(
+	client: &PeersFullClient,
+	proposer_factory: &mut DummyFactory,
+	block_import: &mut BoxBlockImport<TestBlock>,
+	parent_hash: Hash,
+	n: usize,
+) -> Vec<Hash> {
+	let mut hashes = Vec::with_capacity(n);
+	let mut parent_header = client.header(parent_hash).unwrap().unwrap();
+
+	for _ in 0..n {
+		let block_hash =
+			propose_and_import_block(&parent_header, None, proposer_factory, block_import).await;
+		hashes.push(block_hash);
+		parent_header = client.header(block_hash).unwrap().unwrap();
+	}
+
+	hashes
+}
+
+#[tokio::test]
+async fn importing_block_one_sets_genesis_epoch() {
+	let mut net = SassafrasTestNet::new(1);
+
+	let peer = net.peer(0);
+	let data = peer.data.as_ref().expect("sassafras link set up during initialization");
+	let client = peer.client().as_client();
+
+	let mut proposer_factory = DummyFactory {
+		client: client.clone(),
+		epoch_changes: data.link.epoch_changes.clone(),
+		config: data.link.config.clone(),
+		mutator: Arc::new(|_, _| ()),
+	};
+
+	let mut block_import = data.block_import.lock().take().expect("import set up during init");
+
+	let genesis_header = client.header(client.chain_info().genesis_hash).unwrap().unwrap();
+
+	let block_hash = propose_and_import_block(
+		&genesis_header,
+		Some(999.into()),
+		&mut proposer_factory,
+		&mut block_import,
+	)
+	.await;
+
+	let genesis_epoch = Epoch::genesis(&data.link.config, 999.into());
+
+	let epoch_changes = data.link.epoch_changes.shared_data();
+	let epoch_for_second_block = epoch_changes
+		.epoch_data_for_child_of(descendent_query(&*client), &block_hash, 1, 1000.into(), |slot| {
+			Epoch::genesis(&data.link.config, slot)
+		})
+		.unwrap()
+		.unwrap();
+
+	assert_eq!(epoch_for_second_block, genesis_epoch);
+}
+
+#[tokio::test]
+#[should_panic(expected = "Slot number must increase: parent slot: 999, this slot: 999")]
+async fn verify_slots_are_strictly_increasing() {
+	let mut net = SassafrasTestNet::new(1);
+
+	let peer = net.peer(0);
+	let data = peer.data.as_ref().expect("sassafras link set up during initialization");
+
+	let client = peer.client().as_client();
+	let mut block_import = data.block_import.lock().take().expect("import set up during init");
+
+	let mut proposer_factory = DummyFactory {
+		client: client.clone(),
+		epoch_changes: data.link.epoch_changes.clone(),
+		config: data.link.config.clone(),
+		mutator: Arc::new(|_, _| ()),
+	};
+
+	let genesis_header = client.header(client.chain_info().genesis_hash).unwrap().unwrap();
+
+	// we should have no issue importing this block
+	let b1 = propose_and_import_block(
+		&genesis_header,
+		Some(999.into()),
+		&mut proposer_factory,
+		&mut block_import,
+	)
+	.await;
+
+	let b1 = client.header(b1).unwrap().unwrap();
+
+	// we should fail to import this block since the slot number didn't increase.
+	// we will panic due to the `PanickingBlockImport` defined above.
+	propose_and_import_block(&b1, Some(999.into()), &mut proposer_factory, &mut block_import).await;
+}
+
+#[tokio::test]
+async fn importing_epoch_change_block_prunes_tree() {
+	let mut net = SassafrasTestNet::new(1);
+
+	let peer = net.peer(0);
+	let data = peer.data.as_ref().expect("sassafras link set up during initialization");
+
+	let client = peer.client().as_client();
+	let mut block_import = data.block_import.lock().take().expect("import set up during init");
+	let epoch_changes = data.link.epoch_changes.clone();
+
+	let mut proposer_factory = DummyFactory {
+		client: client.clone(),
+		epoch_changes: data.link.epoch_changes.clone(),
+		config: data.link.config.clone(),
+		mutator: Arc::new(|_, _| ()),
+	};
+
+	// This is the block tree that we're going to use in this test. Each node
+	// represents an epoch change block, the epoch length is 6 slots.
+	//
+	//    *---- F (#7)
+	//   /
+	//  /
+	// A (#1) - B (#7) - C (#13)
+
+	// Create and import the canon chain and keep track of the fork block (A).
+	let canon = propose_and_import_blocks(
+		&client,
+		&mut proposer_factory,
+		&mut block_import,
+		client.chain_info().genesis_hash,
+		12,
+	)
+	.await;
+
+	// Create the fork
+	let fork =
+		propose_and_import_blocks(&client, &mut proposer_factory, &mut block_import, canon[0], 7)
+			.await;
+
+	// We should be tracking a total of 3 epoch changes in the fork tree
+	assert_eq!(epoch_changes.shared_data().tree().iter().count(), 3);
+
+	// We finalize block #8 from the canon chain, so on the next epoch
+	// change the tree should be pruned, to not contain F (#7).
+	client.finalize_block(canon[7], None, false).unwrap();
+	propose_and_import_blocks(
+		&client,
+		&mut proposer_factory,
+		&mut block_import,
+		client.chain_info().best_hash,
+		7,
+	)
+	.await;
+
+	let nodes: Vec<_> = epoch_changes.shared_data().tree().iter().map(|(h, _, _)| *h).collect();
+
+	// no hashes from the fork must exist on the tree
+	assert!(!nodes.iter().any(|h| fork.contains(h)));
+
+	// Check that we contain the nodes that we care about
+	assert!(nodes.iter().any(|h| *h == canon[6]));
+}
+
+#[tokio::test]
+async fn epoch_changes_are_persisted_with_tickets() {
+	let mut net = SassafrasTestNet::new(1);
+
+	let peer = net.peer(0);
+	let data = peer.data.as_ref().expect("sassafras link set up during initialization");
+
+	let client = peer.client().as_client();
+	let mut block_import = data.block_import.lock().take().expect("import set up during init");
+
+	let mut proposer_factory = DummyFactory {
+		client: client.clone(),
+		epoch_changes: data.link.epoch_changes.clone(),
+		config: data.link.config.clone(),
+		mutator: Arc::new(|_, _| ()),
+	};
+
+	let genesis_header = client.header(client.chain_info().genesis_hash).unwrap().unwrap();
+
+	let b1 = propose_and_import_block(
+		&genesis_header,
+		Some(1.into()),
+		&mut proposer_factory,
+		&mut block_import,
+	)
+	.await;
+
+	// Attach the tickets to the next epoch, announced by block 1.
+	let next_epoch_id = {
+		let mut epoch_changes = data.link.epoch_changes.shared_data();
+		let descriptor = epoch_changes
+			.epoch_descriptor_for_child_of(
+				descendent_query(&*client),
+				&b1,
+				1,
+				(1 + EPOCH_LENGTH as u64).into(),
+			)
+			.unwrap()
+			.unwrap();
+		let ViableEpochDescriptor::Signaled(next_epoch_id, _) = descriptor else {
+			panic!("next epoch is signaled by block 1")
+		};
+		epoch_changes
+			.epoch_mut(&next_epoch_id)
+			.unwrap()
+			.tickets_aux
+			.insert(42, (1, [7; 32]));
+		aux_schema::write_epoch_changes::<TestBlock, _, _>(&epoch_changes, |values| {
+			client.insert_aux(values, &[])
+		})
+		.unwrap();
+		next_epoch_id
+	};
+
+	// Restart: epoch changes are loaded from aux storage.
+	let epoch_changes = aux_schema::load_epoch_changes::<TestBlock, _>(&*client).unwrap();
+	let epoch_changes = epoch_changes.shared_data();
+	let next_epoch = epoch_changes.epoch(&next_epoch_id).unwrap();
+	assert_eq!(next_epoch.index, 1);
+	assert_eq!(next_epoch.tickets_aux.get(&42), Some(&(1, [7; 32])));
+
+	// Weight of secondary blocks doesn't increase.
+	assert_eq!(block_weight(&*client, b1).unwrap(), Some(0));
+}
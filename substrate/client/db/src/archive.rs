@@ -0,0 +1,334 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Archive of pruned blocks.
+//!
+//! With blocks pruning enabled, the bodies and justifications of finalized blocks are removed from
+//! the database once they leave the pruning window. When an archive directory is configured, each
+//! canonical block is appended to the archive before it is pruned, so the complete history can be
+//! kept on cold storage.
+//!
+//! The archive is a directory of append-only segment files, each holding the blocks of a range of
+//! `SEGMENT_BLOCKS` numbers. A segment is a sequence of records made of the block number (`u64`,
+//! little endian), the length of the block (`u32`, little endian) and the SCALE encoded
+//! `SignedBlock`. Blocks of displaced forks are not archived.
+
+use std::{
+	fs,
+	io::{self, BufReader, Read, Write},
+	path::{Path, PathBuf},
+};
+
+/// Number of block numbers covered by a segment file.
+pub const SEGMENT_BLOCKS: u64 = 100_000;
+
+const SEGMENT_EXTENSION: &str = "blocks";
+const RECORD_HEADER_LEN: u64 = 12;
+
+fn segment_path(dir: &Path, segment: u64) -> PathBuf {
+	dir.join(format!("{:020}.{}", segment, SEGMENT_EXTENSION))
+}
+
+/// Return the first block numbers of the segments found in `dir`, in ascending order.
+fn segments(dir: &Path) -> io::Result<Vec<u64>> {
+	let mut segments = Vec::new();
+	for entry in fs::read_dir(dir)? {
+		let path = entry?.path();
+		if path.extension().map_or(true, |extension| extension != SEGMENT_EXTENSION) {
+			continue
+		}
+		if let Some(segment) = path.file_stem().and_then(|s| s.to_str()?.parse().ok()) {
+			segments.push(segment);
+		}
+	}
+	segments.sort_unstable();
+	Ok(segments)
+}
+
+/// Sequential reader of the complete records of a segment.
+///
+/// A record partially written by an interrupted append is ignored.
+struct SegmentReader {
+	reader: BufReader<fs::File>,
+	len: u64,
+	/// Offset of the end of the last record returned by `next_record`.
+	end: u64,
+}
+
+impl SegmentReader {
+	fn open(path: &Path) -> io::Result<Self> {
+		let file = fs::File::open(path)?;
+		let len = file.metadata()?.len();
+		Ok(SegmentReader { reader: BufReader::new(file), len, end: 0 })
+	}
+
+	/// Read the number and the length of the next block. The block must then be read with
+	/// `read_block` or skipped with `skip_block`.
+	fn next_record(&mut self) -> io::Result<Option<(u64, u32)>> {
+		if self.end + RECORD_HEADER_LEN > self.len {
+			return Ok(None)
+		}
+		let mut header = [0u8; RECORD_HEADER_LEN as usize];
+		self.reader.read_exact(&mut header)?;
+		let number = u64::from_le_bytes(header[..8].try_into().expect("8 bytes; qed"));
+		let len = u32::from_le_bytes(header[8..].try_into().expect("4 bytes; qed"));
+		let end = self.end + RECORD_HEADER_LEN + len as u64;
+		if end > self.len {
+			return Ok(None)
+		}
+		self.end = end;
+		Ok(Some((number, len)))
+	}
+
+	fn read_block(&mut self, len: u32, block: &mut Vec<u8>) -> io::Result<()> {
+		block.resize(len as usize, 0);
+		self.reader.read_exact(block)
+	}
+
+	fn skip_block(&mut self, len: u32) -> io::Result<()> {
+		self.reader.seek_relative(len as i64)
+	}
+}
+
+/// Appends the blocks to prune to an archive directory.
+pub(crate) struct ArchiveWriter {
+	dir: PathBuf,
+	/// First block number of the segment open for writing, with its file.
+	segment: Option<(u64, fs::File)>,
+	/// Number of the last archived block.
+	last_archived: Option<u64>,
+}
+
+impl ArchiveWriter {
+	/// Open the archive in `dir`, creating it if needed.
+	///
+	/// A record partially written by an interrupted append is discarded.
+	pub(crate) fn open(dir: &Path) -> io::Result<Self> {
+		fs::create_dir_all(dir)?;
+		let mut writer = ArchiveWriter { dir: dir.into(), segment: None, last_archived: None };
+		let Some(&segment) = segments(dir)?.last() else { return Ok(writer) };
+
+		let path = segment_path(dir, segment);
+		let mut reader = SegmentReader::open(&path)?;
+		while let Some((number, len)) = reader.next_record()? {
+			reader.skip_block(len)?;
+			writer.last_archived = Some(number);
+		}
+		let file = fs::OpenOptions::new().append(true).open(&path)?;
+		if reader.end < reader.len {
+			log::warn!(target: "db", "Discarding a partially archived block in {}", path.display());
+			file.set_len(reader.end)?;
+		}
+		writer.segment = Some((segment, file));
+		Ok(writer)
+	}
+
+	/// Append the encoded `SignedBlock` of block `number`.
+	///
+	/// Blocks are expected in ascending order: a block that is not above the last archived block
+	/// has been archived already, by an append whose database transaction was not committed.
+	pub(crate) fn append(&mut self, number: u64, block: &[u8]) -> io::Result<()> {
+		if self.last_archived.map_or(false, |last| number <= last) {
+			return Ok(())
+		}
+		let len = u32::try_from(block.len())
+			.map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Block is too large"))?;
+
+		let segment = number - number % SEGMENT_BLOCKS;
+		let file = match &mut self.segment {
+			Some((current, file)) if *current == segment => file,
+			_ => {
+				let file = fs::OpenOptions::new()
+					.create(true)
+					.append(true)
+					.open(segment_path(&self.dir, segment))?;
+				&mut self.segment.insert((segment, file)).1
+			},
+		};
+
+		let mut record = Vec::with_capacity(RECORD_HEADER_LEN as usize + block.len());
+		record.extend_from_slice(&number.to_le_bytes());
+		record.extend_from_slice(&len.to_le_bytes());
+		record.extend_from_slice(block);
+		file.write_all(&record)?;
+		// The block is removed from the database once the transaction is committed.
+		file.sync_data()?;
+		self.last_archived = Some(number);
+		Ok(())
+	}
+}
+
+/// Read access to an archive of pruned blocks.
+pub struct BlocksArchive {
+	dir: PathBuf,
+}
+
+impl BlocksArchive {
+	/// Open the archive in `dir`.
+	pub fn open(dir: &Path) -> io::Result<Self> {
+		if !dir.is_dir() {
+			return Err(io::Error::new(
+				io::ErrorKind::NotFound,
+				format!("No blocks archive at {}", dir.display()),
+			))
+		}
+		Ok(BlocksArchive { dir: dir.into() })
+	}
+
+	/// Call `f` with the number and the encoded `SignedBlock` of each archived block in the
+	/// `from..=to` range, in ascending order. Blocks are only read when `read_blocks` is set.
+	fn for_each(
+		&self,
+		from: u64,
+		to: u64,
+		read_blocks: bool,
+		mut f: impl FnMut(u64, &[u8]) -> io::Result<()>,
+	) -> io::Result<()> {
+		let mut block = Vec::new();
+		for segment in segments(&self.dir)? {
+			if segment > to {
+				break
+			}
+			if segment.saturating_add(SEGMENT_BLOCKS) <= from {
+				continue
+			}
+
+			let mut reader = SegmentReader::open(&segment_path(&self.dir, segment))?;
+			while let Some((number, len)) = reader.next_record()? {
+				if number < from || number > to {
+					reader.skip_block(len)?;
+				} else if read_blocks {
+					reader.read_block(len, &mut block)?;
+					f(number, &block)?;
+				} else {
+					reader.skip_block(len)?;
+					f(number, &[])?;
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Return the number of archived blocks in the `from..=to` range.
+	pub fn count(&self, from: u64, to: u64) -> io::Result<u64> {
+		let mut count = 0;
+		self.for_each(from, to, false, |_, _| {
+			count += 1;
+			Ok(())
+		})?;
+		Ok(count)
+	}
+
+	/// Return the encoded `SignedBlock` of each archived block in the `from..=to` range, with its
+	/// number.
+	pub fn blocks(&self, from: u64, to: u64) -> io::Result<Vec<(u64, Vec<u8>)>> {
+		let mut blocks = Vec::new();
+		self.for_each(from, to, true, |number, block| {
+			blocks.push((number, block.to_vec()));
+			Ok(())
+		})?;
+		Ok(blocks)
+	}
+
+	/// Write the archived blocks in the `from..=to` range to `output`, in the binary format read
+	/// by the `import-blocks` command. Returns the number of exported blocks.
+	pub fn export(&self, from: u64, to: u64, mut output: impl Write) -> io::Result<u64> {
+		let count = self.count(from, to)?;
+		// The block count is SCALE encoded as a `u64`.
+		output.write_all(&count.to_le_bytes())?;
+		// Blocks archived since they were counted are left out.
+		let mut remaining = count;
+		self.for_each(from, to, true, |_, block| {
+			if remaining == 0 {
+				return Ok(())
+			}
+			remaining -= 1;
+			output.write_all(block)
+		})?;
+		output.flush()?;
+		Ok(count)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn appends_and_exports_ranges() {
+		let dir = tempfile::tempdir().unwrap();
+		let mut writer = ArchiveWriter::open(dir.path()).unwrap();
+		for number in [1, 2, 3, SEGMENT_BLOCKS, SEGMENT_BLOCKS + 1] {
+			writer.append(number, &number.to_le_bytes()).unwrap();
+		}
+		// Already archived.
+		writer.append(2, b"duplicate").unwrap();
+		drop(writer);
+
+		let archive = BlocksArchive::open(dir.path()).unwrap();
+		assert_eq!(archive.count(0, u64::MAX).unwrap(), 5);
+		let numbers = |from, to| {
+			archive
+				.blocks(from, to)
+				.unwrap()
+				.into_iter()
+				.map(|(n, _)| n)
+				.collect::<Vec<_>>()
+		};
+		assert_eq!(numbers(2, SEGMENT_BLOCKS), vec![2, 3, SEGMENT_BLOCKS]);
+		assert_eq!(numbers(SEGMENT_BLOCKS + 1, u64::MAX), vec![SEGMENT_BLOCKS + 1]);
+		assert_eq!(archive.blocks(3, 3).unwrap(), vec![(3, 3u64.to_le_bytes().to_vec())]);
+
+		let mut output = Vec::new();
+		assert_eq!(archive.export(1, 3, &mut output).unwrap(), 3);
+		let mut expected = 3u64.to_le_bytes().to_vec();
+		for number in 1u64..=3 {
+			expected.extend_from_slice(&number.to_le_bytes());
+		}
+		assert_eq!(output, expected);
+	}
+
+	#[test]
+	fn discards_partial_record() {
+		let dir = tempfile::tempdir().unwrap();
+		let mut writer = ArchiveWriter::open(dir.path()).unwrap();
+		writer.append(1, b"first").unwrap();
+		writer.append(2, b"second").unwrap();
+		drop(writer);
+
+		let path = segment_path(dir.path(), 0);
+		let len = fs::metadata(&path).unwrap().len();
+		fs::OpenOptions::new()
+			.write(true)
+			.open(&path)
+			.unwrap()
+			.set_len(len - 2)
+			.unwrap();
+
+		let mut writer = ArchiveWriter::open(dir.path()).unwrap();
+		assert_eq!(writer.last_archived, Some(1));
+		writer.append(2, b"second").unwrap();
+		drop(writer);
+
+		let archive = BlocksArchive::open(dir.path()).unwrap();
+		assert_eq!(
+			archive.blocks(0, 10).unwrap(),
+			vec![(1, b"first".to_vec()), (2, b"second".to_vec())]
+		);
+	}
+}
@@ -32,7 +32,10 @@ pub mod offchain;
 
 pub mod bench;
 
+mod archive;
 mod children;
+#[cfg(any(feature = "rocksdb", test))]
+mod migration;
 mod parity_db;
 mod pinned_blocks_cache;
 mod record_stats_state;
@@ -77,7 +80,7 @@ use sp_core::{
 };
 use sp_database::Transaction;
 use sp_runtime::{
-	generic::BlockId,
+	generic::{BlockId, SignedBlock},
 	traits::{
 		Block as BlockT, Hash, HashingFor, Header as HeaderT, NumberFor, One, SaturatedConversion,
 		Zero,
@@ -96,7 +99,12 @@ use sp_trie::{cache::SharedTrieCache, prefixed_key, MemoryDB, MerkleValue, Prefi
 pub use sc_state_db::PruningMode;
 pub use sp_database::Database;
 
+pub use archive::{BlocksArchive, SEGMENT_BLOCKS};
 pub use bench::BenchmarkingState;
+#[cfg(any(feature = "rocksdb", test))]
+pub use migration::{
+	migrate_paritydb_to_rocksdb, migrate_rocksdb_to_paritydb, verify_migration, MigrationDirection,
+};
 
 const CACHE_HEADERS: usize = 8;
 
@@ -307,6 +315,10 @@ pub struct DatabaseSettings {
 	///
 	/// NOTE: only finalized blocks are subject for removal!
 	pub blocks_pruning: BlocksPruning,
+	/// Directory where finalized blocks are archived before they are pruned.
+	///
+	/// See [`BlocksArchive`] to read the archive.
+	pub blocks_archive: Option<PathBuf>,
 }
 
 /// Block pruning settings.
@@ -1113,6 +1125,7 @@ pub struct Backend<Block: BlockT> {
 	import_lock: Arc<RwLock<()>>,
 	is_archive: bool,
 	blocks_pruning: BlocksPruning,
+	blocks_archive: Option<Mutex<archive::ArchiveWriter>>,
 	io_stats: FrozenForDuration<(kvdb::IoStats, StateUsageInfo)>,
 	state_usage: Arc<StateUsageStats>,
 	genesis_state: RwLock<Option<Arc<DbGenesisStorage<Block>>>>,
@@ -1173,6 +1186,7 @@ impl<Block: BlockT> Backend<Block> {
 			state_pruning: Some(state_pruning),
 			source: DatabaseSource::Custom { db, require_create_flag: true },
 			blocks_pruning,
+			blocks_archive: None,
 		};
 
 		Self::new(db_setting, canonicalization_delay).expect("failed to create test-db")
@@ -1226,6 +1240,17 @@ impl<Block: BlockT> Backend<Block> {
 
 		let offchain_storage = offchain::LocalStorage::new(db.clone());
 
+		let blocks_archive = match &config.blocks_archive {
+			Some(path) => Some(Mutex::new(archive::ArchiveWriter::open(path).map_err(|e| {
+				sp_blockchain::Error::Backend(format!(
+					"Failed to open blocks archive at {}: {}",
+					path.display(),
+					e
+				))
+			})?)),
+			None => None,
+		};
+
 		let backend = Backend {
 			storage: Arc::new(storage_db),
 			offchain_storage,
@@ -1236,6 +1261,7 @@ impl<Block: BlockT> Backend<Block> {
 			io_stats: FrozenForDuration::new(std::time::Duration::from_secs(1)),
 			state_usage: Arc::new(StateUsageStats::new()),
 			blocks_pruning: config.blocks_pruning,
+			blocks_archive,
 			genesis_state: RwLock::new(None),
 			shared_trie_cache: config.trie_cache_maximum_size.map(|maximum_size| {
 				SharedTrieCache::new(sp_trie::cache::CacheSize::new(maximum_size))
@@ -1834,9 +1860,10 @@ impl<Block: BlockT> Backend<Block> {
 
 						// If the block was finalized in this transaction, it will not be in the db
 						// yet.
-						if let Some(justification) =
-							current_transaction_justifications.remove(&hash)
-						{
+						let current_justification =
+							current_transaction_justifications.remove(&hash);
+						self.archive_block(hash, number, current_justification.clone())?;
+						if let Some(justification) = current_justification {
 							self.blockchain.insert_justifications_if_pinned(hash, justification);
 						} else {
 							self.blockchain.insert_persisted_justifications_if_pinned(hash)?;
@@ -1854,6 +1881,36 @@ impl<Block: BlockT> Backend<Block> {
 		Ok(())
 	}
 
+	/// Append a block to the blocks archive, if any, before it is pruned.
+	fn archive_block(
+		&self,
+		hash: Block::Hash,
+		number: NumberFor<Block>,
+		current_justification: Option<Justification>,
+	) -> ClientResult<()> {
+		let Some(archive) = &self.blocks_archive else { return Ok(()) };
+		let (Some(header), Some(body)) =
+			(self.blockchain.header(hash)?, self.blockchain.body(hash)?)
+		else {
+			return Ok(())
+		};
+
+		let mut justifications = self.blockchain.justifications(hash)?;
+		if let Some(justification) = current_justification {
+			match &mut justifications {
+				Some(justifications) => {
+					justifications.append(justification);
+				},
+				None => justifications = Some(Justifications::from(justification)),
+			}
+		}
+
+		let block = SignedBlock { block: Block::new(header, body), justifications };
+		archive.lock().append(number.saturated_into(), &block.encode()).map_err(|e| {
+			sp_blockchain::Error::Backend(format!("Failed to archive block #{}: {}", number, e))
+		})
+	}
+
 	fn prune_displaced_branches(
 		&self,
 		transaction: &mut Transaction<DbHash>,
@@ -2691,6 +2748,7 @@ pub(crate) mod tests {
 				state_pruning: Some(PruningMode::blocks_pruning(1)),
 				source: DatabaseSource::Custom { db: backing, require_create_flag: false },
 				blocks_pruning: BlocksPruning::KeepFinalized,
+				blocks_archive: None,
 			},
 			0,
 		)
@@ -3429,6 +3487,58 @@ pub(crate) mod tests {
 		}
 	}
 
+	#[test]
+	fn archive_blocks_before_pruning() {
+		let archive_dir = tempfile::tempdir().unwrap();
+		let db = sp_database::as_database(kvdb_memorydb::create(crate::utils::NUM_COLUMNS));
+		let backend = Backend::<Block>::new(
+			DatabaseSettings {
+				trie_cache_maximum_size: None,
+				state_pruning: Some(PruningMode::blocks_pruning(2)),
+				source: DatabaseSource::Custom { db, require_create_flag: false },
+				blocks_pruning: BlocksPruning::Some(2),
+				blocks_archive: Some(archive_dir.path().into()),
+			},
+			0,
+		)
+		.unwrap();
+
+		let mut blocks = Vec::new();
+		let mut prev_hash = Default::default();
+		for i in 0..5 {
+			let hash = insert_block(
+				&backend,
+				i,
+				prev_hash,
+				None,
+				Default::default(),
+				vec![i.into()],
+				None,
+			)
+			.unwrap();
+			blocks.push(hash);
+			prev_hash = hash;
+		}
+
+		{
+			let mut op = backend.begin_operation().unwrap();
+			backend.begin_state_operation(&mut op, blocks[4]).unwrap();
+			for i in 1..5 {
+				op.mark_finalized(blocks[i], Some((CONS0_ENGINE_ID, vec![i as u8]))).unwrap();
+			}
+			backend.commit_operation(op).unwrap();
+		}
+		assert_eq!(None, backend.blockchain().body(blocks[1]).unwrap());
+
+		let archived =
+			BlocksArchive::open(archive_dir.path()).unwrap().blocks(0, u64::MAX).unwrap();
+		assert_eq!(archived.iter().map(|(number, _)| *number).collect::<Vec<_>>(), vec![0, 1, 2]);
+		let block = SignedBlock::<Block>::decode(&mut &archived[1].1[..]).unwrap();
+		assert_eq!(block.block.header().hash(), blocks[1]);
+		assert_eq!(block.block.extrinsics(), &[1.into()]);
+		assert_eq!(block.justifications, Some(Justifications::from((CONS0_ENGINE_ID, vec![1]))));
+	}
+
 	#[test]
 	fn prune_blocks_on_finalize_with_fork() {
 		sp_tracing::try_init_simple();
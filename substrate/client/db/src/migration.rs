@@ -0,0 +1,944 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Migration of a database between RocksDB and ParityDB.
+//!
+//! Data is streamed from the source into the target database in batches. The progress is
+//! checkpointed in the target database atomically with each batch, so an interrupted migration is
+//! resumed by running it again. The meta column is written last: the target is only recognized as
+//! a database once the migration is complete.
+//!
+//! RocksDB does not count references, so trie node keys are prefixed and the transaction column
+//! emulates reference counting. When migrating to ParityDB, every column is enumerated and
+//! translated to the reference counted columns of ParityDB, along with the journals of the state
+//! database.
+//!
+//! ParityDB can enumerate the values of its columns, but not their keys. When migrating to
+//! RocksDB, the data is therefore found by following the finalized chain and the tries of its
+//! states instead, see [`migrate_paritydb_to_rocksdb`].
+
+use crate::{
+	children, columns, parity_db, upgrade,
+	utils::{self, meta_keys, DatabaseType, NUM_COLUMNS},
+	Database, DbExtrinsic, DbHash, StateMetaDb, Transaction, DB_HASH_LEN,
+};
+use codec::{Decode, Encode};
+use kvdb::KeyValueDB;
+use log::info;
+use sc_client_api::leaves::LeafSet;
+use sc_state_db::PruningMode;
+use sp_blockchain::{Error, Result};
+use sp_core::storage::{well_known_keys, ChildInfo};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{
+		Block as BlockT, HashingFor, Header as HeaderT, NumberFor, UniqueSaturatedFrom,
+		UniqueSaturatedInto,
+	},
+};
+use sp_trie::{empty_trie_root, prefixed_key, LayoutV1, NodeCodec};
+use std::{
+	collections::{HashMap, HashSet},
+	path::Path,
+	sync::Arc,
+};
+use trie_db::{
+	node::{Node, NodeHandle, Value},
+	NibbleVec, NodeCodec as _,
+};
+
+const LOG_TARGET: &str = "db::migration";
+
+/// Key of the migration checkpoint in the meta column of the target database.
+const MIGRATION_CHECKPOINT: &[u8] = b"migration_checkpoint";
+
+/// Number of bytes copied before a batch is committed to the target database.
+const BATCH_SIZE: usize = 64 * 1024 * 1024;
+
+/// Direction of a database migration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationDirection {
+	/// From RocksDB to ParityDB, see [`migrate_rocksdb_to_paritydb`].
+	RocksDbToParityDb,
+	/// From ParityDB to RocksDB, see [`migrate_paritydb_to_rocksdb`].
+	ParityDbToRocksDb,
+}
+
+/// Progress of a migration.
+#[derive(Encode, Decode)]
+struct Checkpoint {
+	/// All columns before this one are copied.
+	///
+	/// When migrating to RocksDB, this is the index of the stage in progress in
+	/// [`PARITYDB_STAGES`] instead.
+	column: u32,
+	/// Last key of `column` that is copied, if any.
+	///
+	/// When migrating to RocksDB, this is the encoded number of the block to resume from instead.
+	last_key: Option<Vec<u8>>,
+}
+
+/// Stages of a migration to RocksDB, in order.
+const PARITYDB_STAGES: [&str; 4] = ["blocks", "states", "aux", "meta"];
+
+fn column_name(column: u32) -> &'static str {
+	match column {
+		columns::META => "meta",
+		columns::STATE => "state",
+		columns::STATE_META => "state meta",
+		columns::KEY_LOOKUP => "key lookup",
+		columns::HEADER => "headers",
+		columns::JUSTIFICATIONS => "justifications",
+		columns::BODY => "bodies",
+		columns::AUX => "aux",
+		columns::OFFCHAIN => "offchain",
+		columns::TRANSACTION => "transactions",
+		columns::BODY_INDEX => "body index",
+		_ => "unused",
+	}
+}
+
+/// Strip the prefix RocksDB adds to trie node keys.
+fn unprefixed_key(key: &[u8]) -> &[u8] {
+	&key[key.len().saturating_sub(DB_HASH_LEN)..]
+}
+
+fn open_rocksdb<Block: BlockT>(path: &Path, create: bool) -> Result<kvdb_rocksdb::Database> {
+	let open_err = |e: &dyn std::fmt::Display| {
+		Error::Backend(format!("Failed to open RocksDB database at {}: {}", path.display(), e))
+	};
+	if !create {
+		upgrade::upgrade_db::<Block>(path, DatabaseType::Full).map_err(|e| open_err(&e))?;
+	}
+	let mut config = kvdb_rocksdb::DatabaseConfig::with_columns(NUM_COLUMNS);
+	config.create_if_missing = create;
+	let db = kvdb_rocksdb::Database::open(&config, path).map_err(|e| open_err(&e))?;
+	if create {
+		upgrade::update_version(path).map_err(|e| open_err(&e))?;
+	}
+	Ok(db)
+}
+
+fn open_paritydb(path: &Path, create: bool) -> Result<Arc<dyn Database<DbHash>>> {
+	parity_db::open(path, DatabaseType::Full, create, false).map_err(|e| {
+		Error::Backend(format!("Failed to open ParityDB database at {}: {}", path.display(), e))
+	})
+}
+
+/// Open both databases of a migration, in the given `direction`.
+fn open_databases<Block: BlockT>(
+	direction: MigrationDirection,
+	source: &Path,
+	target: &Path,
+	create: bool,
+) -> Result<(Arc<dyn Database<DbHash>>, Arc<dyn Database<DbHash>>)> {
+	let rocksdb = |path, create| {
+		open_rocksdb::<Block>(path, create).map(sp_database::as_database::<_, DbHash>)
+	};
+	Ok(match direction {
+		MigrationDirection::RocksDbToParityDb =>
+			(rocksdb(source, false)?, open_paritydb(target, create)?),
+		MigrationDirection::ParityDbToRocksDb =>
+			(open_paritydb(source, false)?, rocksdb(target, create)?),
+	})
+}
+
+/// Read the checkpoint of an interrupted migration, or start a new one if `target` is empty.
+fn start_migration(target: &dyn Database<DbHash>) -> Result<Checkpoint> {
+	match read_checkpoint(target)? {
+		Some(checkpoint) => Ok(checkpoint),
+		None if target.get(columns::META, meta_keys::TYPE).is_some() =>
+			Err(Error::Backend("Target database already exists".into())),
+		None => Ok(Checkpoint { column: 0, last_key: None }),
+	}
+}
+
+fn read_checkpoint(target: &dyn Database<DbHash>) -> Result<Option<Checkpoint>> {
+	target
+		.get(columns::META, MIGRATION_CHECKPOINT)
+		.map(|checkpoint| Checkpoint::decode(&mut checkpoint.as_slice()))
+		.transpose()
+		.map_err(|e| Error::Backend(format!("Invalid migration checkpoint: {}", e)))
+}
+
+fn commit(
+	target: &dyn Database<DbHash>,
+	mut transaction: Transaction<DbHash>,
+	checkpoint: Checkpoint,
+) -> Result<()> {
+	transaction.set_from_vec(columns::META, MIGRATION_CHECKPOINT, checkpoint.encode());
+	target
+		.commit(transaction)
+		.map_err(|e| Error::Backend(format!("Failed to write to target database: {}", e)))
+}
+
+/// Copy the RocksDB database at `source` into a new ParityDB database at `target`.
+///
+/// An interrupted migration is resumed by calling this function again with the same paths. The
+/// source database must not be in use while it is migrated.
+pub fn migrate_rocksdb_to_paritydb<Block: BlockT>(source: &Path, target: &Path) -> Result<()> {
+	let source = open_rocksdb::<Block>(source, false)?;
+	let target = open_paritydb(target, true)?;
+	let checkpoint = start_migration(&*target)?;
+	if checkpoint.column > 0 || checkpoint.last_key.is_some() {
+		info!(
+			target: LOG_TARGET,
+			"Resuming the migration from the {} column",
+			column_name(checkpoint.column),
+		);
+	}
+
+	for column in checkpoint.column..NUM_COLUMNS {
+		// Both meta columns are written once all the data is copied.
+		if column == columns::META || column == columns::STATE_META {
+			continue
+		}
+		let resume_after = checkpoint.last_key.as_ref().filter(|_| column == checkpoint.column);
+		copy_column(&source, &*target, column, resume_after)?;
+	}
+	copy_meta::<Block>(&source, &*target)?;
+
+	info!(target: LOG_TARGET, "Database migration complete");
+	Ok(())
+}
+
+fn copy_column(
+	source: &kvdb_rocksdb::Database,
+	target: &dyn Database<DbHash>,
+	column: u32,
+	resume_after: Option<&Vec<u8>>,
+) -> Result<()> {
+	let read_err =
+		|e: std::io::Error| Error::Backend(format!("Failed to read from source database: {}", e));
+
+	let mut transaction = Transaction::new();
+	let mut batch_size = 0;
+	let mut keys = 0u64;
+	let mut bytes = 0u64;
+	for entry in source.iter(column) {
+		let (key, value) = entry.map_err(read_err)?;
+		if resume_after.map_or(false, |last_key| key.as_ref() <= last_key.as_slice()) {
+			continue
+		}
+
+		let size = key.len() + value.len();
+		match column {
+			columns::STATE => {
+				if key.len() < DB_HASH_LEN {
+					return Err(Error::Backend(format!("Invalid trie node key: {:?}", key)))
+				}
+				transaction.store(columns::STATE, DbHash::from_slice(unprefixed_key(&key)), value);
+			},
+			// Reference counters are stored next to the values, with a zero byte appended to the
+			// key.
+			columns::TRANSACTION if key.len() == DB_HASH_LEN + 1 && key.ends_with(&[0]) => continue,
+			columns::TRANSACTION => {
+				let mut counter_key = key.to_vec();
+				counter_key.push(0);
+				let references = match source.get(column, &counter_key).map_err(read_err)? {
+					Some(counter) => u32::decode(&mut counter.as_slice())
+						.map_err(|e| Error::Backend(format!("Invalid reference counter: {}", e)))?,
+					None => 1,
+				};
+				let hash = DbHash::from_slice(&key);
+				for _ in 0..references {
+					transaction.store(columns::TRANSACTION, hash, value.clone());
+				}
+			},
+			_ => transaction.set_from_vec(column, &key, value),
+		}
+		batch_size += size;
+		keys += 1;
+		bytes += size as u64;
+
+		if batch_size >= BATCH_SIZE {
+			let checkpoint = Checkpoint { column, last_key: Some(key.to_vec()) };
+			commit(target, std::mem::take(&mut transaction), checkpoint)?;
+			batch_size = 0;
+			info!(
+				target: LOG_TARGET,
+				"Migrating {}: {} keys, {} MiB",
+				column_name(column),
+				keys,
+				bytes / (1024 * 1024),
+			);
+		}
+	}
+
+	commit(target, transaction, Checkpoint { column: column + 1, last_key: None })?;
+	if keys > 0 {
+		info!(
+			target: LOG_TARGET,
+			"Migrated {}: {} keys, {} MiB",
+			column_name(column),
+			keys,
+			bytes / (1024 * 1024),
+		);
+	}
+	Ok(())
+}
+
+/// Copy the state meta column, translated for reference counting, and the meta column, then drop
+/// the checkpoint.
+fn copy_meta<Block: BlockT>(
+	source: &kvdb_rocksdb::Database,
+	target: &dyn Database<DbHash>,
+) -> Result<()> {
+	let read_column = |column| {
+		source
+			.iter(column)
+			.map(|entry| entry.map(|(key, value)| (key.to_vec(), value)))
+			.collect::<std::io::Result<Vec<_>>>()
+			.map_err(|e| Error::Backend(format!("Failed to read from source database: {}", e)))
+	};
+
+	let mut state_meta = read_column(columns::STATE_META)?;
+	sc_state_db::translate_meta_for_ref_counting::<Block::Hash>(&mut state_meta, |key| {
+		unprefixed_key(key).to_vec()
+	})
+	.map_err(Error::from_state_db)?;
+
+	let mut transaction = Transaction::new();
+	for (key, value) in state_meta {
+		transaction.set_from_vec(columns::STATE_META, &key, value);
+	}
+	for (key, value) in read_column(columns::META)? {
+		transaction.set_from_vec(columns::META, &key, value);
+	}
+	transaction.remove(columns::META, MIGRATION_CHECKPOINT);
+	target
+		.commit(transaction)
+		.map_err(|e| Error::Backend(format!("Failed to write to target database: {}", e)))
+}
+
+/// Copy the finalized chain of the ParityDB database at `source` into a new RocksDB database at
+/// `target`.
+///
+/// ParityDB does not store the keys of its hash indexed columns, so they are derived from the
+/// chain: blocks are read by number up to the finalized block, trie nodes are found by walking the
+/// states of these blocks and indexed transactions are found from the block bodies. The states of
+/// all the copied blocks are migrated from an archive database, only the finalized state
+/// otherwise. The keys of the auxiliary data to copy are given by `aux_keys` from the hash of the
+/// finalized block. Unfinalized blocks and offchain storage are not migrated.
+///
+/// An interrupted migration is resumed by calling this function again with the same paths. The
+/// source database must not be in use while it is migrated.
+pub fn migrate_paritydb_to_rocksdb<Block: BlockT>(
+	source: &Path,
+	target: &Path,
+	aux_keys: impl Fn(&Block::Hash) -> Vec<Vec<u8>>,
+) -> Result<()> {
+	let (source, target) =
+		open_databases::<Block>(MigrationDirection::ParityDbToRocksDb, source, target, true)?;
+	let checkpoint = start_migration(&*target)?;
+	let resume_from = checkpoint
+		.last_key
+		.map(|key| u64::decode(&mut key.as_slice()))
+		.transpose()
+		.map_err(|e| Error::Backend(format!("Invalid migration checkpoint: {}", e)))?;
+	if checkpoint.column > 0 || resume_from.is_some() {
+		info!(
+			target: LOG_TARGET,
+			"Resuming the migration from the {} stage",
+			PARITYDB_STAGES.get(checkpoint.column as usize).unwrap_or(&"unknown"),
+		);
+	}
+
+	let meta = utils::read_meta::<Block>(&*source, columns::HEADER)?;
+	if meta.genesis_hash == Default::default() {
+		return Err(Error::Backend("Source database is empty".into()))
+	}
+	let mode = sc_state_db::stored_pruning_mode(&StateMetaDb(source.clone()))
+		.map_err(Error::from_state_db)?
+		.ok_or_else(|| Error::Backend("Source database has no state pruning mode".into()))?;
+	let finalized: u64 = meta.finalized_number.unique_saturated_into();
+
+	for stage in checkpoint.column..PARITYDB_STAGES.len() as u32 {
+		let resume_from = resume_from.filter(|_| stage == checkpoint.column);
+		match stage {
+			0 => copy_blocks::<Block>(&*source, &*target, resume_from, finalized)?,
+			1 => {
+				// Only archive databases keep the state of every finalized block.
+				let first = if mode.is_archive() { resume_from.unwrap_or(0) } else { finalized };
+				copy_states::<Block>(&*source, &*target, first, finalized)?
+			},
+			2 => copy_aux(&*source, &*target, aux_keys(&meta.finalized_hash))?,
+			_ => write_meta::<Block>(&*source, &*target, &meta, &mode)?,
+		}
+	}
+
+	info!(target: LOG_TARGET, "Database migration complete");
+	Ok(())
+}
+
+fn read_block_header<Block: BlockT>(
+	db: &dyn Database<DbHash>,
+	number: u64,
+) -> Result<Option<Block::Header>> {
+	let id = BlockId::<Block>::Number(UniqueSaturatedFrom::unique_saturated_from(number));
+	utils::read_header::<Block>(db, columns::KEY_LOOKUP, columns::HEADER, id)
+}
+
+/// Copy the blocks of the canonical chain from `first`, or genesis, to `last`, along with the
+/// transactions they index.
+fn copy_blocks<Block: BlockT>(
+	source: &dyn Database<DbHash>,
+	target: &dyn Database<DbHash>,
+	first: Option<u64>,
+	last: u64,
+) -> Result<()> {
+	let mut transaction = Transaction::new();
+	let mut references = HashMap::new();
+	let mut batch_size = 0;
+	for number in first.unwrap_or(0)..=last {
+		// Blocks of a gap left by warp sync are missing.
+		let Some(header) = read_block_header::<Block>(source, number)? else { continue };
+		let hash = header.hash();
+		let lookup_key = utils::number_and_hash_to_lookup_key(number, hash)?;
+
+		utils::insert_number_to_key_mapping(&mut transaction, columns::KEY_LOOKUP, number, hash)?;
+		utils::insert_hash_to_key_mapping(&mut transaction, columns::KEY_LOOKUP, number, hash)?;
+		let encoded = header.encode();
+		batch_size += encoded.len();
+		transaction.set_from_vec(columns::HEADER, &lookup_key, encoded);
+		for column in [columns::BODY, columns::JUSTIFICATIONS, columns::BODY_INDEX] {
+			let Some(value) = source.get(column, &lookup_key) else { continue };
+			if column == columns::BODY_INDEX {
+				let index = Vec::<DbExtrinsic<Block>>::decode(&mut value.as_slice())
+					.map_err(|e| Error::Backend(format!("Error decoding body list: {}", e)))?;
+				for extrinsic in index {
+					if let DbExtrinsic::Indexed { hash, .. } = extrinsic {
+						*references.entry(hash).or_insert(0u32) += 1;
+					}
+				}
+			}
+			batch_size += value.len();
+			transaction.set_from_vec(column, &lookup_key, value);
+		}
+		children::write_children(
+			&mut transaction,
+			columns::META,
+			meta_keys::CHILDREN_PREFIX,
+			*header.parent_hash(),
+			vec![hash],
+		);
+
+		if batch_size >= BATCH_SIZE {
+			copy_transactions(source, target, &mut transaction, std::mem::take(&mut references))?;
+			let checkpoint = Checkpoint { column: 0, last_key: Some((number + 1).encode()) };
+			commit(target, std::mem::take(&mut transaction), checkpoint)?;
+			batch_size = 0;
+			info!(target: LOG_TARGET, "Migrating blocks: #{} of #{}", number, last);
+		}
+	}
+
+	copy_transactions(source, target, &mut transaction, references)?;
+	commit(target, transaction, Checkpoint { column: 1, last_key: None })?;
+	info!(target: LOG_TARGET, "Migrated blocks up to #{}", last);
+	Ok(())
+}
+
+/// Add the indexed transactions with the given number of new `references` to `transaction`.
+///
+/// RocksDB counts references next to the values, with a zero byte appended to the key. Several
+/// references added in a single transaction only count once, so the counters are written
+/// directly.
+fn copy_transactions(
+	source: &dyn Database<DbHash>,
+	target: &dyn Database<DbHash>,
+	transaction: &mut Transaction<DbHash>,
+	references: HashMap<DbHash, u32>,
+) -> Result<()> {
+	for (hash, count) in references {
+		let value = source.get(columns::TRANSACTION, hash.as_ref()).ok_or_else(|| {
+			Error::Backend(format!("Indexed transaction {:?} is missing from the source", hash))
+		})?;
+		let mut counter_key = hash.as_ref().to_vec();
+		counter_key.push(0);
+		let existing = match target.get(columns::TRANSACTION, &counter_key) {
+			Some(counter) => u32::decode(&mut counter.as_slice())
+				.map_err(|e| Error::Backend(format!("Invalid reference counter: {}", e)))?,
+			None => 0,
+		};
+		transaction.set_from_vec(columns::TRANSACTION, hash.as_ref(), value);
+		transaction.set_from_vec(columns::TRANSACTION, &counter_key, (existing + count).encode());
+	}
+	Ok(())
+}
+
+/// Copy the states of the canonical blocks from `first` to `last`. The state of `last` must be in
+/// the source, the others are skipped when they are missing.
+fn copy_states<Block: BlockT>(
+	source: &dyn Database<DbHash>,
+	target: &dyn Database<DbHash>,
+	first: u64,
+	last: u64,
+) -> Result<()> {
+	let mut copy = StateCopy::<Block> {
+		source,
+		target,
+		transaction: Transaction::new(),
+		pending: HashSet::new(),
+		batch_size: 0,
+		block: first,
+		nodes: 0,
+		bytes: 0,
+	};
+	for number in first..=last {
+		let Some(header) = read_block_header::<Block>(source, number)? else { continue };
+		let root = *header.state_root();
+		if root != empty_trie_root::<LayoutV1<HashingFor<Block>>>() &&
+			source.get(columns::STATE, root.as_ref()).is_none()
+		{
+			if number == last {
+				return Err(Error::Backend(format!(
+					"State of the finalized block #{} is missing from the source",
+					number
+				)))
+			}
+			continue
+		}
+		copy.block = number;
+		copy.copy_trie(&[], root)?;
+	}
+	copy.commit(Checkpoint { column: 2, last_key: None })?;
+	info!(
+		target: LOG_TARGET,
+		"Migrated states: {} nodes, {} MiB",
+		copy.nodes,
+		copy.bytes / (1024 * 1024),
+	);
+	Ok(())
+}
+
+/// Copies the nodes of state tries from ParityDB, where they are keyed by hash, to RocksDB, where
+/// they are keyed by prefixed hash.
+///
+/// A node is only written once all its descendants are, so the descendants of a node that is in
+/// the target already are not visited again.
+struct StateCopy<'a, Block: BlockT> {
+	source: &'a dyn Database<DbHash>,
+	target: &'a dyn Database<DbHash>,
+	transaction: Transaction<DbHash>,
+	/// Keys of the nodes written to `transaction`.
+	pending: HashSet<Vec<u8>>,
+	batch_size: usize,
+	/// Number of the block whose state is copied.
+	block: u64,
+	nodes: u64,
+	bytes: u64,
+}
+
+impl<'a, Block: BlockT> StateCopy<'a, Block> {
+	/// Copy the trie with the given `root`, in the `keyspace` of a child trie or in the main trie.
+	fn copy_trie(&mut self, keyspace: &[u8], root: Block::Hash) -> Result<()> {
+		if root == empty_trie_root::<LayoutV1<HashingFor<Block>>>() {
+			return Ok(())
+		}
+		self.copy_node(keyspace, root, &mut NibbleVec::new())
+	}
+
+	/// Copy the node with the given `hash` at `prefix`, with its descendants.
+	fn copy_node(
+		&mut self,
+		keyspace: &[u8],
+		hash: Block::Hash,
+		prefix: &mut NibbleVec,
+	) -> Result<()> {
+		let key = node_key::<Block>(keyspace, &hash, prefix);
+		if self.is_copied(&key) {
+			return Ok(())
+		}
+		let data = self.read(&hash)?;
+		self.copy_descendants(keyspace, &data, prefix)?;
+		self.write(key, data)
+	}
+
+	/// Copy the descendants of the encoded node `data` at `prefix`.
+	fn copy_descendants(
+		&mut self,
+		keyspace: &[u8],
+		data: &[u8],
+		prefix: &mut NibbleVec,
+	) -> Result<()> {
+		let node = NodeCodec::<HashingFor<Block>>::decode(data)
+			.map_err(|e| Error::Backend(format!("Invalid trie node: {}", e)))?;
+		let depth = prefix.len();
+		match node {
+			Node::Empty => {},
+			Node::Leaf(partial, value) => {
+				prefix.append_partial(partial.right());
+				self.copy_value(keyspace, value, prefix)?;
+			},
+			Node::Extension(partial, child) => {
+				prefix.append_partial(partial.right());
+				self.copy_handle(keyspace, child, prefix)?;
+			},
+			Node::Branch(children, value) => self.copy_branch(keyspace, children, value, prefix)?,
+			Node::NibbledBranch(partial, children, value) => {
+				prefix.append_partial(partial.right());
+				self.copy_branch(keyspace, children, value, prefix)?;
+			},
+		}
+		prefix.drop_lasts(prefix.len() - depth);
+		Ok(())
+	}
+
+	fn copy_branch(
+		&mut self,
+		keyspace: &[u8],
+		children: [Option<NodeHandle>; 16],
+		value: Option<Value>,
+		prefix: &mut NibbleVec,
+	) -> Result<()> {
+		if let Some(value) = value {
+			self.copy_value(keyspace, value, prefix)?;
+		}
+		for (index, child) in children.into_iter().enumerate() {
+			if let Some(child) = child {
+				prefix.push(index as u8);
+				self.copy_handle(keyspace, child, prefix)?;
+				prefix.pop();
+			}
+		}
+		Ok(())
+	}
+
+	fn copy_handle(
+		&mut self,
+		keyspace: &[u8],
+		handle: NodeHandle,
+		prefix: &mut NibbleVec,
+	) -> Result<()> {
+		match handle {
+			NodeHandle::Hash(hash) => self.copy_node(keyspace, decode_hash::<Block>(hash)?, prefix),
+			NodeHandle::Inline(data) => self.copy_descendants(keyspace, data, prefix),
+		}
+	}
+
+	/// Copy the value stored at `key`, and the child trie whose root it is, if any.
+	fn copy_value(&mut self, keyspace: &[u8], value: Value, key: &mut NibbleVec) -> Result<()> {
+		let value = match value {
+			Value::Inline(value) => value.to_vec(),
+			Value::Node(hash) => {
+				let hash = decode_hash::<Block>(hash)?;
+				let node_key = node_key::<Block>(keyspace, &hash, key);
+				let value = self.read(&hash)?;
+				if !self.is_copied(&node_key) {
+					self.write(node_key, value.clone())?;
+				}
+				value
+			},
+		};
+
+		let storage_key = key.inner();
+		if keyspace.is_empty() &&
+			storage_key.starts_with(well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX)
+		{
+			let child_info = ChildInfo::new_default(
+				&storage_key[well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX.len()..],
+			);
+			self.copy_trie(child_info.keyspace(), decode_hash::<Block>(&value)?)?;
+		}
+		Ok(())
+	}
+
+	fn is_copied(&self, key: &[u8]) -> bool {
+		self.pending.contains(key) || self.target.contains(columns::STATE, key)
+	}
+
+	fn read(&self, hash: &Block::Hash) -> Result<Vec<u8>> {
+		self.source.get(columns::STATE, hash.as_ref()).ok_or_else(|| {
+			Error::Backend(format!(
+				"Trie node {:?} of the state of block #{} is missing from the source",
+				hash, self.block
+			))
+		})
+	}
+
+	fn write(&mut self, key: Vec<u8>, data: Vec<u8>) -> Result<()> {
+		let size = key.len() + data.len();
+		self.transaction.set_from_vec(columns::STATE, &key, data);
+		self.pending.insert(key);
+		self.batch_size += size;
+		self.nodes += 1;
+		self.bytes += size as u64;
+
+		if self.batch_size >= BATCH_SIZE {
+			// The state of the current block is walked again on resume, skipping the nodes that
+			// are copied.
+			self.commit(Checkpoint { column: 1, last_key: Some(self.block.encode()) })?;
+			info!(
+				target: LOG_TARGET,
+				"Migrating states: block #{}, {} nodes, {} MiB",
+				self.block,
+				self.nodes,
+				self.bytes / (1024 * 1024),
+			);
+		}
+		Ok(())
+	}
+
+	fn commit(&mut self, checkpoint: Checkpoint) -> Result<()> {
+		commit(self.target, std::mem::take(&mut self.transaction), checkpoint)?;
+		self.pending.clear();
+		self.batch_size = 0;
+		Ok(())
+	}
+}
+
+/// Key of a trie node in a database that does not count references.
+fn node_key<Block: BlockT>(keyspace: &[u8], hash: &Block::Hash, prefix: &NibbleVec) -> Vec<u8> {
+	let (prefix, last) = prefix.as_prefix();
+	let prefix = [keyspace, prefix].concat();
+	prefixed_key::<HashingFor<Block>>(hash, (&prefix, last))
+}
+
+fn decode_hash<Block: BlockT>(hash: &[u8]) -> Result<Block::Hash> {
+	Block::Hash::decode(&mut &hash[..])
+		.map_err(|e| Error::Backend(format!("Invalid trie node hash {:?}: {}", hash, e)))
+}
+
+fn copy_aux(
+	source: &dyn Database<DbHash>,
+	target: &dyn Database<DbHash>,
+	keys: Vec<Vec<u8>>,
+) -> Result<()> {
+	let mut transaction = Transaction::new();
+	for key in keys {
+		if let Some(value) = source.get(columns::AUX, &key) {
+			transaction.set_from_vec(columns::AUX, &key, value);
+		}
+	}
+	commit(target, transaction, Checkpoint { column: 3, last_key: None })
+}
+
+/// Write the meta columns, with the finalized block as the best block and the only leaf, then
+/// drop the checkpoint.
+fn write_meta<Block: BlockT>(
+	source: &dyn Database<DbHash>,
+	target: &dyn Database<DbHash>,
+	meta: &utils::Meta<NumberFor<Block>, Block::Hash>,
+	mode: &PruningMode,
+) -> Result<()> {
+	let finalized =
+		utils::number_and_hash_to_lookup_key(meta.finalized_number, meta.finalized_hash)?;
+	let header = source
+		.get(columns::HEADER, &finalized)
+		.map(|header| Block::Header::decode(&mut header.as_slice()))
+		.transpose()
+		.map_err(|e| Error::Backend(format!("Error decoding header: {}", e)))?
+		.ok_or_else(|| Error::Backend("Header of the finalized block is missing".into()))?;
+
+	let mut transaction = Transaction::new();
+	for key in [meta_keys::TYPE, meta_keys::GENESIS_HASH, meta_keys::BLOCK_GAP] {
+		if let Some(value) = source.get(columns::META, key) {
+			transaction.set_from_vec(columns::META, key, value);
+		}
+	}
+	for key in [meta_keys::BEST_BLOCK, meta_keys::FINALIZED_BLOCK, meta_keys::FINALIZED_STATE] {
+		transaction.set_from_vec(columns::META, key, finalized.clone());
+	}
+	let mut leaves = LeafSet::new();
+	leaves.import(meta.finalized_hash, meta.finalized_number, *header.parent_hash());
+	leaves.prepare_transaction(&mut transaction, columns::META, meta_keys::LEAF_PREFIX);
+
+	let state_meta = sc_state_db::meta_for_migrated_state(
+		mode,
+		&meta.finalized_hash,
+		meta.finalized_number.unique_saturated_into(),
+	);
+	for (key, value) in state_meta {
+		transaction.set_from_vec(columns::STATE_META, &key, value);
+	}
+	transaction.remove(columns::META, MIGRATION_CHECKPOINT);
+	target
+		.commit(transaction)
+		.map_err(|e| Error::Backend(format!("Failed to write to target database: {}", e)))
+}
+
+/// Compare a migrated database at `target` with the database at `source` it was migrated from.
+///
+/// The genesis and finalized blocks must match, as well as the headers and state root nodes of up
+/// to `samples` finalized blocks spread over the chain. The best block must match too when
+/// migrating to ParityDB, and be the finalized block when migrating to RocksDB. Blocks whose state
+/// is not migrated are skipped. Returns the number of state roots that were compared.
+pub fn verify_migration<Block: BlockT>(
+	direction: MigrationDirection,
+	source: &Path,
+	target: &Path,
+	samples: u32,
+) -> Result<u32> {
+	let (source, target) = open_databases::<Block>(direction, source, target, false)?;
+	if read_checkpoint(&*target)?.is_some() {
+		return Err(Error::Backend("The migration is not complete".into()))
+	}
+
+	let source_meta = utils::read_meta::<Block>(&*source, columns::HEADER)?;
+	let target_meta = utils::read_meta::<Block>(&*target, columns::HEADER)?;
+	let mismatch = |what: &str, source: &dyn std::fmt::Debug, target: &dyn std::fmt::Debug| {
+		Error::Backend(format!(
+			"{} mismatch: {:?} in the source, {:?} in the target",
+			what, source, target
+		))
+	};
+	if source_meta.genesis_hash != target_meta.genesis_hash {
+		return Err(mismatch("Genesis block", &source_meta.genesis_hash, &target_meta.genesis_hash))
+	}
+	let best = |meta: &utils::Meta<_, _>| (meta.best_number, meta.best_hash);
+	let finalized = |meta: &utils::Meta<_, _>| (meta.finalized_number, meta.finalized_hash);
+	let expected_best = match direction {
+		MigrationDirection::RocksDbToParityDb => best(&source_meta),
+		MigrationDirection::ParityDbToRocksDb => finalized(&source_meta),
+	};
+	if expected_best != best(&target_meta) {
+		return Err(mismatch("Best block", &expected_best, &best(&target_meta)))
+	}
+	if finalized(&source_meta) != finalized(&target_meta) {
+		return Err(mismatch("Finalized block", &finalized(&source_meta), &finalized(&target_meta)))
+	}
+
+	// Only the finalized state is migrated to RocksDB from a pruned database.
+	let all_states = match direction {
+		MigrationDirection::RocksDbToParityDb => true,
+		MigrationDirection::ParityDbToRocksDb =>
+			sc_state_db::stored_pruning_mode(&StateMetaDb(source.clone()))
+				.map_err(Error::from_state_db)?
+				.map_or(false, |mode| mode.is_archive()),
+	};
+	let finalized_number: u64 = source_meta.finalized_number.unique_saturated_into();
+	let mut verified = 0;
+	for sample in 0..samples as u64 {
+		let number = finalized_number - finalized_number * sample / samples as u64;
+		let Some(header) = read_block_header::<Block>(&*source, number)? else { continue };
+		let target_header = read_block_header::<Block>(&*target, number)?;
+		if target_header.as_ref() != Some(&header) {
+			return Err(mismatch(&format!("Header of block #{}", number), &header, &target_header))
+		}
+
+		if !all_states && number != finalized_number {
+			continue
+		}
+		let state_root = header.state_root().as_ref();
+		let Some(root_node) = source.get(columns::STATE, state_root) else { continue };
+		if target.get(columns::STATE, state_root).as_ref() != Some(&root_node) {
+			return Err(Error::Backend(format!(
+				"State root of block #{} is missing from the target",
+				number
+			)))
+		}
+		verified += 1;
+	}
+	Ok(verified)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		tests::{insert_header, Block},
+		Backend, BlocksPruning, DatabaseSettings, DatabaseSource,
+	};
+	use sc_client_api::{
+		backend::{AuxStore, Backend as _},
+		blockchain::HeaderBackend,
+	};
+	use sp_blockchain::Info;
+	use sp_core::H256;
+	use sp_state_machine::Backend as _;
+
+	fn backend(db: Arc<dyn Database<DbHash>>) -> Backend<Block> {
+		let settings = DatabaseSettings {
+			trie_cache_maximum_size: Some(16 * 1024 * 1024),
+			state_pruning: Some(PruningMode::ArchiveCanonical),
+			source: DatabaseSource::Custom { db, require_create_flag: false },
+			blocks_pruning: BlocksPruning::KeepFinalized,
+			blocks_archive: None,
+		};
+		Backend::new(settings, 2).unwrap()
+	}
+
+	/// Create a RocksDB database with 12 blocks, 9 of them finalized, and an aux record.
+	fn rocksdb_source(path: &Path) -> Info<Block> {
+		let db = open_rocksdb::<Block>(path, true).unwrap();
+		let backend = backend(sp_database::as_database(db));
+		let mut hash = H256::default();
+		for number in 0..12 {
+			hash = insert_header(&backend, number, hash, None, H256::default());
+			if number == 8 {
+				backend.finalize_block(hash, None).unwrap();
+			}
+		}
+		backend.insert_aux(&[(&b"aux"[..], &b"value"[..])], &[]).unwrap();
+		backend.blockchain().info()
+	}
+
+	#[test]
+	fn migrates_rocksdb_to_paritydb() {
+		let dir = tempfile::tempdir().unwrap();
+		let source = dir.path().join("rocksdb");
+		let target = dir.path().join("paritydb");
+		let info = rocksdb_source(&source);
+
+		migrate_rocksdb_to_paritydb::<Block>(&source, &target).unwrap();
+		let direction = MigrationDirection::RocksDbToParityDb;
+		assert_eq!(verify_migration::<Block>(direction, &source, &target, 8).unwrap(), 8);
+		assert!(migrate_rocksdb_to_paritydb::<Block>(&source, &target).is_err());
+
+		let backend = backend(open_paritydb(&target, false).unwrap());
+		let migrated = backend.blockchain().info();
+		assert_eq!(migrated.best_hash, info.best_hash);
+		assert_eq!(migrated.finalized_hash, info.finalized_hash);
+		assert!(backend.state_at(info.best_hash).is_ok());
+		assert!(backend.state_at(info.finalized_hash).is_ok());
+	}
+
+	#[test]
+	fn migrates_paritydb_to_rocksdb() {
+		let dir = tempfile::tempdir().unwrap();
+		let rocksdb = dir.path().join("rocksdb");
+		let paritydb = dir.path().join("paritydb");
+		let target = dir.path().join("target");
+		let info = rocksdb_source(&rocksdb);
+		migrate_rocksdb_to_paritydb::<Block>(&rocksdb, &paritydb).unwrap();
+
+		migrate_paritydb_to_rocksdb::<Block>(&paritydb, &target, |_| vec![b"aux".to_vec()])
+			.unwrap();
+		let direction = MigrationDirection::ParityDbToRocksDb;
+		assert_eq!(verify_migration::<Block>(direction, &paritydb, &target, 8).unwrap(), 8);
+		assert!(migrate_paritydb_to_rocksdb::<Block>(&paritydb, &target, |_| vec![]).is_err());
+
+		let backend =
+			backend(sp_database::as_database(open_rocksdb::<Block>(&target, false).unwrap()));
+		let migrated = backend.blockchain().info();
+		assert_eq!(migrated.best_hash, info.finalized_hash);
+		assert_eq!(migrated.finalized_hash, info.finalized_hash);
+		assert_eq!(migrated.genesis_hash, info.genesis_hash);
+		assert_eq!(backend.get_aux(b"aux").unwrap(), Some(b"value".to_vec()));
+
+		// Every block stores the hash of its parent in its state.
+		for number in 1..=8 {
+			let hash = backend.blockchain().hash(number).unwrap().unwrap();
+			let header = backend.blockchain().header(hash).unwrap().unwrap();
+			let parent_hash = header.parent_hash.as_ref();
+			let state = backend.state_at(hash).unwrap();
+			assert_eq!(state.storage(parent_hash).unwrap(), Some(parent_hash.to_vec()));
+		}
+		assert!(backend.blockchain().header(info.best_hash).unwrap().is_none());
+	}
+}
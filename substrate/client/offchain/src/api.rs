@@ -18,7 +18,7 @@
 
 use std::{collections::HashSet, str::FromStr, sync::Arc, thread::sleep};
 
-use crate::NetworkProvider;
+use crate::{metrics::MetricsLink, HttpPolicy, NetworkProvider};
 use codec::{Decode, Encode};
 use futures::Future;
 pub use http::SharedClient;
@@ -32,9 +32,9 @@ use sp_core::{
 };
 pub use sp_offchain::STORAGE_PREFIX;
 
-mod http;
+pub(crate) mod http;
 
-mod timestamp;
+pub(crate) mod timestamp;
 
 /// Asynchronous offchain API.
 ///
@@ -201,8 +201,10 @@ impl AsyncApi {
 		network_provider: Arc<dyn NetworkProvider + Send + Sync>,
 		is_validator: bool,
 		shared_http_client: SharedClient,
+		http_policy: Arc<HttpPolicy>,
+		metrics: MetricsLink,
 	) -> (Api, Self) {
-		let (http_api, http_worker) = http::http(shared_http_client);
+		let (http_api, http_worker) = http::http(shared_http_client, http_policy, metrics);
 
 		let api = Api { network_provider, is_validator, http: http_api };
 
@@ -319,7 +321,7 @@ mod tests {
 		let mock = Arc::new(TestNetwork());
 		let shared_client = SharedClient::new();
 
-		AsyncApi::new(mock, false, shared_client)
+		AsyncApi::new(mock, false, shared_client, Default::default(), Default::default())
 	}
 
 	fn offchain_db() -> OffchainDb<LocalStorage> {
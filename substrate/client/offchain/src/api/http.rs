@@ -27,7 +27,7 @@
 //! (i.e.: the socket should continue being processed) in the background even if the runtime isn't
 //! actively calling any function.
 
-use crate::api::timestamp;
+use crate::{api::timestamp, metrics::MetricsLink, policy::Violation, HttpPolicy};
 use bytes::buf::{Buf, Reader};
 use fnv::FnvHashMap;
 use futures::{channel::mpsc, future, prelude::*};
@@ -65,7 +65,13 @@ impl SharedClient {
 }
 
 /// Creates a pair of [`HttpApi`] and [`HttpWorker`].
-pub fn http(shared_client: SharedClient) -> (HttpApi, HttpWorker) {
+///
+/// The requests started through the [`HttpApi`] are restricted by the given [`HttpPolicy`].
+pub fn http(
+	shared_client: SharedClient,
+	policy: Arc<HttpPolicy>,
+	metrics: MetricsLink,
+) -> (HttpApi, HttpWorker) {
 	let (to_worker, from_api) = tracing_unbounded("mpsc_ocw_to_worker", 100_000);
 	let (to_api, from_worker) = tracing_unbounded("mpsc_ocw_to_api", 100_000);
 
@@ -76,6 +82,10 @@ pub fn http(shared_client: SharedClient) -> (HttpApi, HttpWorker) {
 		// writing runtime code with hardcoded IDs.
 		next_id: HttpRequestId(rand::random::<u16>() % 2000),
 		requests: FnvHashMap::default(),
+		policy,
+		metrics,
+		requests_started: 0,
+		body_sizes: FnvHashMap::default(),
 	};
 
 	let engine =
@@ -98,6 +108,15 @@ pub struct HttpApi {
 	next_id: HttpRequestId,
 	/// List of HTTP requests in preparation or in progress.
 	requests: FnvHashMap<HttpRequestId, HttpApiRequest>,
+	/// Restrictions applied to the requests.
+	policy: Arc<HttpPolicy>,
+	/// Prometheus metrics.
+	metrics: MetricsLink,
+	/// Number of requests started so far, checked against the per-block budget.
+	requests_started: usize,
+	/// Number of bytes of the request body written and of the response body read, per request.
+	/// Only tracked if the policy limits the body size.
+	body_sizes: FnvHashMap<HttpRequestId, (usize, usize)>,
 }
 
 /// One active request within `HttpApi`.
@@ -147,6 +166,18 @@ impl HttpApi {
 		*request.method_mut() = hyper::Method::from_bytes(method.as_bytes()).map_err(|_| ())?;
 		*request.uri_mut() = hyper::Uri::from_maybe_shared(uri.to_owned()).map_err(|_| ())?;
 
+		if let Err(violation) = self.check_new_request(request.uri()) {
+			tracing::debug!(
+				target: LOG_TARGET,
+				%method,
+				%uri,
+				?violation,
+				"Request rejected by the HTTP policy",
+			);
+			self.metrics.report_violation(violation);
+			return Err(())
+		}
+
 		let new_id = self.next_id;
 		debug_assert!(!self.requests.contains_key(&new_id));
 		match self.next_id.0.checked_add(1) {
@@ -161,6 +192,8 @@ impl HttpApi {
 		};
 		self.requests
 			.insert(new_id, HttpApiRequest::NotDispatched(request, body_sender));
+		self.requests_started += 1;
+		self.metrics.report(|metrics| metrics.http_requests_started.inc());
 
 		tracing::trace!(
 			target: LOG_TARGET,
@@ -207,6 +240,18 @@ impl HttpApi {
 		// Don't forget to add it back if necessary when returning.
 		let mut request = self.requests.remove(&request_id).ok_or(HttpError::Invalid)?;
 
+		// Requests whose body grows past the limit are aborted rather than sent out truncated.
+		if let Err(error) = self.account_body_bytes(request_id, chunk.len(), false) {
+			match request {
+				HttpApiRequest::Dispatched(Some(sender)) |
+				HttpApiRequest::Response(HttpApiRequestRp {
+					sending_body: Some(sender), ..
+				}) => sender.abort(),
+				_ => {},
+			}
+			return Err(error)
+		}
+
 		let mut deadline = timestamp::deadline_to_future(deadline);
 		// Closure that writes data to a sender, taking the deadline into account. Can return `Ok`
 		// (if the body has been written), or `DeadlineReached`, or `IoError`.
@@ -517,6 +562,8 @@ impl HttpApi {
 				match current_read_chunk.read(buffer) {
 					Ok(0) => {},
 					Ok(n) => {
+						// Dropping the response stops the worker from reading the rest of it.
+						self.account_body_bytes(request_id, n, true)?;
 						self.requests.insert(
 							request_id,
 							HttpApiRequest::Response(HttpApiRequestRp {
@@ -556,6 +603,62 @@ impl HttpApi {
 	}
 }
 
+impl HttpApi {
+	/// Checks whether a new request to `uri` is permitted by the policy.
+	fn check_new_request(&self, uri: &hyper::Uri) -> Result<(), Violation> {
+		if !self.policy.is_host_allowed(uri.host().unwrap_or_default()) {
+			return Err(Violation::HostNotAllowed)
+		}
+
+		if self
+			.policy
+			.max_requests_per_block
+			.map_or(false, |max| self.requests_started >= max)
+		{
+			return Err(Violation::BudgetExhausted)
+		}
+
+		if self
+			.policy
+			.max_concurrent_requests
+			.map_or(false, |max| self.requests.len() >= max)
+		{
+			return Err(Violation::TooManyConcurrentRequests)
+		}
+
+		Ok(())
+	}
+
+	/// Adds `len` bytes to the request (or response) body size of the given request and checks
+	/// the total against the policy.
+	fn account_body_bytes(
+		&mut self,
+		request_id: HttpRequestId,
+		len: usize,
+		response: bool,
+	) -> Result<(), HttpError> {
+		let Some(max_body_size) = self.policy.max_body_size else { return Ok(()) };
+
+		let sizes = self.body_sizes.entry(request_id).or_default();
+		let size = if response { &mut sizes.1 } else { &mut sizes.0 };
+		*size = size.saturating_add(len);
+
+		if *size > max_body_size {
+			tracing::debug!(
+				target: LOG_TARGET,
+				id = %request_id.0,
+				response,
+				"Body size exceeds the HTTP policy limit",
+			);
+			self.body_sizes.remove(&request_id);
+			self.metrics.report_violation(Violation::BodyTooLarge);
+			return Err(HttpError::IoError)
+		}
+
+		Ok(())
+	}
+}
+
 impl fmt::Debug for HttpApi {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		f.debug_list().entries(self.requests.iter()).finish()
@@ -776,9 +879,12 @@ mod tests {
 		() => {
 			build_api_server!(hyper::Response::new(hyper::Body::from("Hello World!")))
 		};
-		( $response:expr ) => {{
+		( $response:expr ) => {
+			build_api_server!(HttpPolicy::default(), $response)
+		};
+		( $policy:expr, $response:expr ) => {{
 			let hyper_client = SHARED_CLIENT.clone();
-			let (api, worker) = http(hyper_client.clone());
+			let (api, worker) = http(hyper_client.clone(), Arc::new($policy), Default::default());
 
 			let (addr_tx, addr_rx) = std::sync::mpsc::channel();
 			std::thread::spawn(move || {
@@ -1057,6 +1163,68 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn policy_rejects_disallowed_hosts() {
+		let policy =
+			HttpPolicy { allowed_hosts: vec!["*.example.com".into()], ..Default::default() };
+		let (mut api, addr) = build_api_server!(policy, hyper::Response::new(hyper::Body::empty()));
+
+		assert!(api.request_start("GET", &format!("http://{}", addr)).is_err());
+		assert!(api.request_start("GET", "http://example.com").is_err());
+		assert!(api.request_start("GET", "http://api.example.com").is_ok());
+	}
+
+	#[test]
+	fn policy_enforces_request_quotas() {
+		let deadline = timestamp::now().add(Duration::from_millis(10_000));
+		let policy = HttpPolicy {
+			max_concurrent_requests: Some(1),
+			max_requests_per_block: Some(2),
+			..Default::default()
+		};
+		let (mut api, addr) =
+			build_api_server!(policy, hyper::Response::new("Hello World!".into()));
+		let url = format!("http://{}", addr);
+
+		let id = api.request_start("GET", &url).unwrap();
+		assert!(api.request_start("GET", &url).is_err());
+
+		// Once the first request is completely read, another one may be started.
+		let mut buf = vec![0; 2048];
+		while api.response_read_body(id, &mut buf, Some(deadline)).unwrap() != 0 {}
+		let id = api.request_start("GET", &url).unwrap();
+		while api.response_read_body(id, &mut buf, Some(deadline)).unwrap() != 0 {}
+
+		// No request is open anymore, but the per-block budget is exhausted.
+		assert!(api.request_start("GET", &url).is_err());
+	}
+
+	#[test]
+	fn policy_limits_body_size() {
+		let deadline = timestamp::now().add(Duration::from_millis(10_000));
+		let policy = HttpPolicy { max_body_size: Some(8), ..Default::default() };
+		let (mut api, addr) =
+			build_api_server!(policy, hyper::Response::new("Hello World!".into()));
+		let url = format!("http://{}", addr);
+
+		let id = api.request_start("POST", &url).unwrap();
+		api.request_write_body(id, &[1, 2, 3, 4], Some(deadline)).unwrap();
+		api.request_write_body(id, &[5, 6, 7, 8], Some(deadline)).unwrap();
+		assert_eq!(api.request_write_body(id, &[9], Some(deadline)), Err(HttpError::IoError));
+		assert_eq!(api.request_write_body(id, &[], Some(deadline)), Err(HttpError::Invalid));
+
+		let id = api.request_start("GET", &url).unwrap();
+		let mut buf = vec![0; 2048];
+		let result = loop {
+			match api.response_read_body(id, &mut buf, Some(deadline)) {
+				Ok(n) if n > 0 => continue,
+				result => break result,
+			}
+		};
+		assert_eq!(result, Err(HttpError::IoError));
+		assert_eq!(api.response_read_body(id, &mut buf, Some(deadline)), Err(HttpError::Invalid));
+	}
+
 	#[test]
 	fn fuzzing() {
 		// Uses the API in random ways to try to trigger panics.
@@ -1101,7 +1269,13 @@ mod tests {
 
 		{
 			let mock = Arc::new(TestNetwork());
-			let (mut api, async_api) = AsyncApi::new(mock, false, shared_client.clone());
+			let (mut api, async_api) = AsyncApi::new(
+				mock,
+				false,
+				shared_client.clone(),
+				Default::default(),
+				Default::default(),
+			);
 			api.timestamp();
 
 			futures::executor::block_on(async move {
@@ -1116,7 +1290,13 @@ mod tests {
 
 		{
 			let mock = Arc::new(TestNetwork());
-			let (mut api, async_api) = AsyncApi::new(mock, false, shared_client.clone());
+			let (mut api, async_api) = AsyncApi::new(
+				mock,
+				false,
+				shared_client.clone(),
+				Default::default(),
+				Default::default(),
+			);
 			let id = api.http_request_start("lol", "nope", &[]).unwrap();
 			api.http_request_write_body(id, &[], None).unwrap();
 			futures::executor::block_on(async move {
@@ -42,6 +42,7 @@ use futures::{
 	prelude::*,
 };
 use parking_lot::Mutex;
+use prometheus_endpoint::Registry;
 use sc_client_api::BlockchainEvents;
 use sc_network::{NetworkPeers, NetworkStateInfo};
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
@@ -53,7 +54,11 @@ use sp_runtime::traits::{self, Header};
 use threadpool::ThreadPool;
 
 mod api;
+mod metrics;
+mod policy;
+pub mod replay;
 
+pub use policy::HttpPolicy;
 pub use sp_core::offchain::storage::OffchainDb;
 pub use sp_offchain::{OffchainWorkerApi, STORAGE_PREFIX};
 
@@ -110,6 +115,10 @@ pub struct OffchainWorkerOptions<RA, Block: traits::Block, Storage, CE> {
 	///
 	/// If not enabled, any http request will panic.
 	pub enable_http_requests: bool,
+	/// Restrictions applied to the http requests of offchain workers.
+	pub http_policy: HttpPolicy,
+	/// Prometheus registry to report metrics to.
+	pub prometheus_registry: Option<Registry>,
 	/// Callback to create custom [`Extension`]s that should be registered for the
 	/// `offchain_worker` runtime call.
 	///
@@ -132,6 +141,8 @@ pub struct OffchainWorkers<RA, Block: traits::Block, Storage> {
 	thread_pool: Mutex<ThreadPool>,
 	shared_http_client: api::SharedClient,
 	enable_http_requests: bool,
+	http_policy: Arc<HttpPolicy>,
+	metrics: metrics::MetricsLink,
 	keystore: Option<KeystorePtr>,
 	offchain_db: Option<OffchainDb<Storage>>,
 	transaction_pool: Option<OffchainTransactionPoolFactory<Block>>,
@@ -151,6 +162,8 @@ impl<RA, Block: traits::Block, Storage> OffchainWorkers<RA, Block, Storage> {
 			network_provider,
 			is_validator,
 			enable_http_requests,
+			http_policy,
+			prometheus_registry,
 			custom_extensions,
 		}: OffchainWorkerOptions<RA, Block, Storage, CE>,
 	) -> Self {
@@ -162,6 +175,8 @@ impl<RA, Block: traits::Block, Storage> OffchainWorkers<RA, Block, Storage> {
 			)),
 			shared_http_client: api::SharedClient::new(),
 			enable_http_requests,
+			http_policy: Arc::new(http_policy),
+			metrics: metrics::MetricsLink::new(prometheus_registry.as_ref()),
 			keystore,
 			offchain_db: offchain_db.map(OffchainDb::new),
 			transaction_pool,
@@ -247,6 +262,8 @@ where
 				self.network_provider.clone(),
 				self.is_validator,
 				self.shared_http_client.clone(),
+				self.http_policy.clone(),
+				self.metrics.clone(),
 			);
 			tracing::debug!(target: LOG_TARGET, "Spawning offchain workers at {hash:?}");
 			let header = header.clone();
@@ -451,6 +468,8 @@ mod tests {
 			network_provider: network,
 			is_validator: false,
 			enable_http_requests: false,
+			http_policy: Default::default(),
+			prometheus_registry: None,
 			custom_extensions: |_| Vec::new(),
 		});
 		futures::executor::block_on(offchain.on_block_imported(&header));
@@ -0,0 +1,83 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Offchain workers Prometheus metrics.
+
+use std::sync::Arc;
+
+use prometheus_endpoint::{register, Counter, CounterVec, Opts, PrometheusError, Registry, U64};
+
+use crate::policy::Violation;
+
+#[derive(Clone, Default)]
+pub(crate) struct MetricsLink(Arc<Option<Metrics>>);
+
+impl MetricsLink {
+	pub fn new(registry: Option<&Registry>) -> Self {
+		Self(Arc::new(registry.and_then(|registry| {
+			Metrics::register(registry)
+				.map_err(|err| {
+					log::warn!("Failed to register prometheus metrics: {}", err);
+				})
+				.ok()
+		})))
+	}
+
+	pub fn report(&self, do_this: impl FnOnce(&Metrics)) {
+		if let Some(metrics) = self.0.as_ref() {
+			do_this(metrics);
+		}
+	}
+
+	/// Reports an HTTP request rejected by the [`HttpPolicy`](crate::HttpPolicy).
+	pub fn report_violation(&self, violation: Violation) {
+		self.report(|metrics| {
+			metrics.http_policy_violations.with_label_values(&[violation.as_str()]).inc()
+		});
+	}
+}
+
+/// Offchain workers Prometheus metrics.
+pub(crate) struct Metrics {
+	pub http_requests_started: Counter<U64>,
+	pub http_policy_violations: CounterVec<U64>,
+}
+
+impl Metrics {
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			http_requests_started: register(
+				Counter::new(
+					"substrate_offchain_http_requests_started_total",
+					"Total number of HTTP requests started by offchain workers",
+				)?,
+				registry,
+			)?,
+			http_policy_violations: register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_offchain_http_policy_violations_total",
+						"Total number of offchain worker HTTP requests rejected by the HTTP policy",
+					),
+					&["reason"],
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
@@ -0,0 +1,118 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Operator-configured restrictions on HTTP requests made by offchain workers.
+
+/// Restrictions applied to the HTTP requests issued by offchain workers.
+///
+/// Every offchain worker run (i.e. every imported block) gets its own request budget. The default
+/// policy doesn't restrict anything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpPolicy {
+	/// Host patterns requests are allowed to be sent to.
+	///
+	/// A pattern is either an exact host name (`example.com`), a wildcard matching all of its
+	/// subdomains (`*.example.com`) or `*`, matching any host. An empty list allows all hosts.
+	pub allowed_hosts: Vec<String>,
+	/// Maximum number of requests a single offchain worker may have open at the same time.
+	pub max_concurrent_requests: Option<usize>,
+	/// Maximum size in bytes of a request body and of a response body.
+	pub max_body_size: Option<usize>,
+	/// Maximum number of requests a single offchain worker may start per block.
+	pub max_requests_per_block: Option<usize>,
+}
+
+impl HttpPolicy {
+	/// Returns `true` if requests to `host` are permitted.
+	pub fn is_host_allowed(&self, host: &str) -> bool {
+		if self.allowed_hosts.is_empty() {
+			return true
+		}
+
+		let host = host.trim_end_matches('.').to_ascii_lowercase();
+		self.allowed_hosts.iter().any(|pattern| {
+			let pattern = pattern.trim_end_matches('.').to_ascii_lowercase();
+			if pattern == "*" {
+				return true
+			}
+
+			match pattern.strip_prefix("*.") {
+				Some(domain) => host
+					.strip_suffix(domain)
+					.map_or(false, |subdomain| subdomain.len() > 1 && subdomain.ends_with('.')),
+				None => host == pattern,
+			}
+		})
+	}
+}
+
+/// Reason for which a request was rejected by the [`HttpPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Violation {
+	/// The host isn't part of the allow-list.
+	HostNotAllowed,
+	/// Too many requests are open at the same time.
+	TooManyConcurrentRequests,
+	/// The per-block request budget is exhausted.
+	BudgetExhausted,
+	/// A request or response body exceeded the maximum size.
+	BodyTooLarge,
+}
+
+impl Violation {
+	/// Label used when reporting the violation to Prometheus.
+	pub(crate) fn as_str(&self) -> &'static str {
+		match self {
+			Self::HostNotAllowed => "host_not_allowed",
+			Self::TooManyConcurrentRequests => "too_many_concurrent_requests",
+			Self::BudgetExhausted => "budget_exhausted",
+			Self::BodyTooLarge => "body_too_large",
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::HttpPolicy;
+
+	fn policy(hosts: &[&str]) -> HttpPolicy {
+		HttpPolicy {
+			allowed_hosts: hosts.iter().map(|h| h.to_string()).collect(),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn empty_allow_list_allows_everything() {
+		assert!(policy(&[]).is_host_allowed("example.com"));
+		assert!(policy(&["*"]).is_host_allowed("127.0.0.1"));
+	}
+
+	#[test]
+	fn host_patterns_are_matched() {
+		let policy = policy(&["localhost", "*.Example.com"]);
+
+		assert!(policy.is_host_allowed("localhost"));
+		assert!(policy.is_host_allowed("api.example.com"));
+		assert!(policy.is_host_allowed("a.b.example.com."));
+		assert!(!policy.is_host_allowed("example.com"));
+		assert!(!policy.is_host_allowed("badexample.com"));
+		assert!(!policy.is_host_allowed("example.com.evil.org"));
+		assert!(!policy.is_host_allowed("127.0.0.1"));
+	}
+}
@@ -0,0 +1,706 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Deterministic execution of offchain workers, for debugging.
+//!
+//! [`replay`] runs the offchain worker of a single block outside of the regular block import
+//! pipeline and captures everything it does into a [`Report`]: offchain storage writes, HTTP
+//! requests and submitted transactions. Storage writes are kept in memory and never reach the
+//! node's database, and submitted transactions are not added to any pool.
+//!
+//! HTTP responses are either served from a [`Fixture`], which makes the execution deterministic,
+//! or obtained by performing real requests. A fixture also provides the start time of a virtual
+//! clock, while real requests are performed against the system clock. The [`Report`] of the latter
+//! can be turned into a fixture with [`Report::fixture`] in order to reproduce the run later on.
+
+use std::{collections::HashMap, sync::Arc, thread::sleep};
+
+use futures::future::{self, OptionFuture};
+use parking_lot::Mutex;
+use sc_client_api::HeaderBackend;
+use serde::{Deserialize, Serialize};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_core::{
+	offchain::{
+		self, HttpError, HttpRequestId, HttpRequestStatus, OffchainStorage, OpaqueNetworkState,
+		OpaquePeerId, StorageKind, Timestamp, STORAGE_PREFIX,
+	},
+	Bytes,
+};
+use sp_keystore::{KeystoreExt, KeystorePtr};
+use sp_runtime::traits::{self, Header};
+
+use crate::{
+	api::{http, timestamp, SharedClient},
+	OffchainWorkerApi, LOG_TARGET,
+};
+
+/// HTTP exchanges and non-deterministic inputs of an offchain worker run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fixture {
+	/// Unix timestamp in milliseconds at the start of the run.
+	pub timestamp: u64,
+	/// Value returned by the `random_seed` host function. Must be 32 bytes long, or empty for
+	/// all zeros.
+	#[serde(with = "sp_core::bytes")]
+	pub random_seed: Vec<u8>,
+	/// HTTP requests and the responses they received, in the order they were started.
+	pub http: Vec<HttpExchange>,
+}
+
+/// An HTTP request made by an offchain worker together with its response.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpExchange {
+	/// Method of the request.
+	pub method: String,
+	/// URI of the request.
+	pub uri: String,
+	/// Headers added to the request.
+	pub request_headers: Vec<(String, String)>,
+	/// Body of the request.
+	#[serde(with = "sp_core::bytes")]
+	pub request_body: Vec<u8>,
+	/// Status code of the response, `None` if the request failed or didn't complete.
+	pub status: Option<u16>,
+	/// Headers of the response. Invalid UTF-8 is replaced.
+	pub response_headers: Vec<(String, String)>,
+	/// Part of the response body read by the offchain worker.
+	#[serde(with = "sp_core::bytes")]
+	pub response_body: Vec<u8>,
+}
+
+/// A write to the offchain storage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageWrite {
+	/// Storage that was written to.
+	pub kind: StorageKind,
+	/// Key that was written.
+	pub key: Bytes,
+	/// New value, `None` if the key was cleared.
+	pub value: Option<Bytes>,
+}
+
+/// Everything an offchain worker did during a [`replay`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+	/// Hash of the block the offchain worker was run for.
+	pub block_hash: String,
+	/// Unix timestamp in milliseconds the run started at.
+	pub timestamp: u64,
+	/// Random seed provided to the offchain worker.
+	#[serde(with = "sp_core::bytes")]
+	pub random_seed: Vec<u8>,
+	/// Writes to the offchain storage, in order.
+	pub storage_writes: Vec<StorageWrite>,
+	/// HTTP requests started by the offchain worker, in order.
+	pub http_requests: Vec<HttpExchange>,
+	/// `<method> <uri>` of the HTTP requests for which the fixture had no response.
+	pub unmatched_http_requests: Vec<String>,
+	/// Transactions submitted by the offchain worker.
+	pub transactions: Vec<Bytes>,
+	/// Error returned by the runtime, if any.
+	pub error: Option<String>,
+}
+
+impl Report {
+	/// Returns a [`Fixture`] that reproduces the inputs of this run.
+	pub fn fixture(&self) -> Fixture {
+		Fixture {
+			timestamp: self.timestamp,
+			random_seed: self.random_seed.clone(),
+			http: self.http_requests.clone(),
+		}
+	}
+}
+
+/// Where the HTTP responses of a replayed offchain worker come from.
+#[derive(Debug, Clone)]
+pub enum HttpSource {
+	/// Serve the responses from a fixture. Requests without a matching exchange fail.
+	Fixture(Fixture),
+	/// Perform real requests.
+	Network,
+}
+
+/// Options for [`replay`].
+pub struct ReplayOptions<Storage> {
+	/// Offchain database to read the persistent storage from. Writes are never applied to it.
+	pub offchain_db: Option<Storage>,
+	/// Provides access to the keystore.
+	pub keystore: Option<KeystorePtr>,
+	/// Whether the offchain worker should believe to run on a validator.
+	pub is_validator: bool,
+	/// Source of the HTTP responses.
+	pub http: HttpSource,
+}
+
+/// Runs the offchain worker of the block `hash` and reports what it did.
+///
+/// Errors returned by the runtime are part of the [`Report`]; an `Err` is only returned if the
+/// offchain worker couldn't be run at all.
+pub async fn replay<Client, Block, Storage>(
+	client: Arc<Client>,
+	hash: Block::Hash,
+	options: ReplayOptions<Storage>,
+) -> Result<Report, String>
+where
+	Block: traits::Block,
+	Client: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	Client::Api: OffchainWorkerApi<Block>,
+	Storage: OffchainStorage + 'static,
+{
+	let ReplayOptions { offchain_db, keystore, is_validator, http } = options;
+
+	let header = client
+		.header(hash)
+		.map_err(|e| e.to_string())?
+		.ok_or_else(|| format!("Unknown block {hash:?}"))?;
+	let version = client
+		.runtime_api()
+		.api_version::<dyn OffchainWorkerApi<Block>>(hash)
+		.map_err(|e| e.to_string())?
+		.ok_or_else(|| format!("The runtime at {hash:?} has no offchain workers"))?;
+
+	let (fixture, clock, http, worker) = match http {
+		HttpSource::Fixture(fixture) => {
+			let exchanges = fixture.http.iter().cloned().map(Some).collect();
+			let http = ReplayHttp::Fixture { exchanges, requests: HashMap::new(), next_id: 0 };
+			let clock = Clock::Virtual(fixture.timestamp);
+			(fixture, clock, http, None)
+		},
+		HttpSource::Network => {
+			let fixture = Fixture {
+				timestamp: timestamp::now().unix_millis(),
+				random_seed: rand::random::<[u8; 32]>().to_vec(),
+				http: Vec::new(),
+			};
+			let (api, worker) =
+				http::http(SharedClient::new(), Default::default(), Default::default());
+			(fixture, Clock::System, ReplayHttp::Network(api), Some(worker))
+		},
+	};
+	let random_seed = match &fixture.random_seed[..] {
+		[] => [0; 32],
+		seed => <[u8; 32]>::try_from(seed)
+			.map_err(|_| "The random seed must be 32 bytes long".to_string())?,
+	};
+
+	let recording = Arc::new(Mutex::new(Recording {
+		report: Report {
+			block_hash: format!("{hash:?}"),
+			timestamp: fixture.timestamp,
+			random_seed: fixture.random_seed,
+			..Default::default()
+		},
+		..Default::default()
+	}));
+	let api = ReplayApi { is_validator, clock, random_seed, http, recording: recording.clone() };
+	let db = ReplayDb { persistent: offchain_db, recording: recording.clone() };
+	let pool = ReplayTransactionPool(recording.clone());
+
+	let (result_tx, result_rx) = futures::channel::oneshot::channel();
+	std::thread::spawn(move || {
+		let capabilities = offchain::Capabilities::all();
+		let mut runtime = client.runtime_api();
+
+		if let Some(keystore) = keystore {
+			runtime.register_extension(KeystoreExt(keystore));
+		}
+		runtime.register_extension(offchain::TransactionPoolExt::new(pool));
+		runtime.register_extension(offchain::OffchainDbExt::new(
+			offchain::LimitedExternalities::new(capabilities, db),
+		));
+		runtime.register_extension(offchain::OffchainWorkerExt::new(
+			offchain::LimitedExternalities::new(capabilities, api),
+		));
+
+		tracing::debug!(target: LOG_TARGET, "Replaying offchain workers at {hash:?}");
+		let run = if version >= 2 {
+			runtime.offchain_worker(hash, &header)
+		} else {
+			#[allow(deprecated)]
+			runtime.offchain_worker_before_version_2(hash, *header.number())
+		};
+		let _ = result_tx.send(run.map_err(|e| e.to_string()));
+	});
+
+	// The HTTP worker stops once the runtime is done and drops its side of the HTTP API.
+	let (result, _) = future::join(result_rx, OptionFuture::from(worker)).await;
+	let result = result.map_err(|_| "The offchain worker panicked".to_string())?;
+
+	let mut report = std::mem::take(&mut recording.lock().report);
+	report.error = result.err();
+	Ok(report)
+}
+
+/// State shared by the extensions of a replayed offchain worker.
+#[derive(Default)]
+struct Recording {
+	/// Report being built.
+	report: Report,
+	/// Index in `report.http_requests` of every started HTTP request.
+	requests: HashMap<HttpRequestId, usize>,
+	/// Writes to the persistent storage, which aren't applied to the database.
+	persistent: HashMap<Vec<u8>, Option<Vec<u8>>>,
+	/// Writes to the local storage, which only lives for the duration of the run.
+	local: HashMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl Recording {
+	/// Returns the writes to the given storage.
+	fn overlay(&mut self, kind: StorageKind) -> &mut HashMap<Vec<u8>, Option<Vec<u8>>> {
+		match kind {
+			StorageKind::PERSISTENT => &mut self.persistent,
+			StorageKind::LOCAL => &mut self.local,
+		}
+	}
+
+	/// Returns the recorded exchange of the given HTTP request.
+	fn exchange(&mut self, request_id: HttpRequestId) -> Option<&mut HttpExchange> {
+		let index = *self.requests.get(&request_id)?;
+		self.report.http_requests.get_mut(index)
+	}
+}
+
+/// Offchain storage recording writes in memory on top of an optional database.
+struct ReplayDb<Storage> {
+	persistent: Option<Storage>,
+	recording: Arc<Mutex<Recording>>,
+}
+
+impl<Storage> ReplayDb<Storage> {
+	fn write(&self, kind: StorageKind, key: &[u8], value: Option<&[u8]>) {
+		let mut recording = self.recording.lock();
+		recording.report.storage_writes.push(StorageWrite {
+			kind,
+			key: key.to_vec().into(),
+			value: value.map(|v| v.to_vec().into()),
+		});
+		recording.overlay(kind).insert(key.to_vec(), value.map(|v| v.to_vec()));
+	}
+}
+
+impl<Storage: OffchainStorage> offchain::DbExternalities for ReplayDb<Storage> {
+	fn local_storage_set(&mut self, kind: StorageKind, key: &[u8], value: &[u8]) {
+		self.write(kind, key, Some(value))
+	}
+
+	fn local_storage_clear(&mut self, kind: StorageKind, key: &[u8]) {
+		self.write(kind, key, None)
+	}
+
+	fn local_storage_compare_and_set(
+		&mut self,
+		kind: StorageKind,
+		key: &[u8],
+		old_value: Option<&[u8]>,
+		new_value: &[u8],
+	) -> bool {
+		if self.local_storage_get(kind, key).as_deref() != old_value {
+			return false
+		}
+		self.write(kind, key, Some(new_value));
+		true
+	}
+
+	fn local_storage_get(&mut self, kind: StorageKind, key: &[u8]) -> Option<Vec<u8>> {
+		if let Some(value) = self.recording.lock().overlay(kind).get(key) {
+			return value.clone()
+		}
+		match kind {
+			StorageKind::PERSISTENT =>
+				self.persistent.as_ref().and_then(|db| db.get(STORAGE_PREFIX, key)),
+			StorageKind::LOCAL => None,
+		}
+	}
+}
+
+/// Transaction pool recording the submitted transactions.
+struct ReplayTransactionPool(Arc<Mutex<Recording>>);
+
+impl offchain::TransactionPool for ReplayTransactionPool {
+	fn submit_transaction(&mut self, extrinsic: Vec<u8>) -> Result<(), ()> {
+		self.0.lock().report.transactions.push(extrinsic.into());
+		Ok(())
+	}
+}
+
+/// HTTP layer of a replayed offchain worker.
+enum ReplayHttp {
+	/// Responses are served from a fixture.
+	Fixture {
+		/// Exchanges of the fixture, `None` once matched by a request.
+		exchanges: Vec<Option<HttpExchange>>,
+		/// Matched exchange and number of response body bytes read so far, per request.
+		requests: HashMap<HttpRequestId, (HttpExchange, usize)>,
+		/// Id to assign to the next request.
+		next_id: u16,
+	},
+	/// Requests are performed for real.
+	Network(http::HttpApi),
+}
+
+/// Time seen by a replayed offchain worker.
+enum Clock {
+	/// Virtual time in milliseconds of a fixture replay, only advanced by `sleep_until`.
+	Virtual(u64),
+	/// The system clock, used when performing real requests.
+	System,
+}
+
+/// Offchain worker externalities with a replaceable clock and HTTP layer.
+struct ReplayApi {
+	is_validator: bool,
+	clock: Clock,
+	random_seed: [u8; 32],
+	http: ReplayHttp,
+	recording: Arc<Mutex<Recording>>,
+}
+
+impl offchain::Externalities for ReplayApi {
+	fn is_validator(&self) -> bool {
+		self.is_validator
+	}
+
+	fn network_state(&self) -> Result<OpaqueNetworkState, ()> {
+		Err(())
+	}
+
+	fn timestamp(&mut self) -> Timestamp {
+		match self.clock {
+			Clock::Virtual(now) => Timestamp::from_unix_millis(now),
+			Clock::System => timestamp::now(),
+		}
+	}
+
+	fn sleep_until(&mut self, deadline: Timestamp) {
+		match &mut self.clock {
+			Clock::Virtual(now) => *now = (*now).max(deadline.unix_millis()),
+			Clock::System => sleep(timestamp::timestamp_from_now(deadline)),
+		}
+	}
+
+	fn random_seed(&mut self) -> [u8; 32] {
+		self.random_seed
+	}
+
+	fn http_request_start(
+		&mut self,
+		method: &str,
+		uri: &str,
+		_meta: &[u8],
+	) -> Result<HttpRequestId, ()> {
+		let request_id = match &mut self.http {
+			ReplayHttp::Fixture { exchanges, requests, next_id } => {
+				let exchange = exchanges
+					.iter_mut()
+					.find(|e| e.as_ref().map_or(false, |e| e.method == method && e.uri == uri))
+					.and_then(Option::take);
+				let Some(exchange) = exchange else {
+					tracing::debug!(target: LOG_TARGET, %method, %uri, "No response in fixture");
+					self.recording
+						.lock()
+						.report
+						.unmatched_http_requests
+						.push(format!("{method} {uri}"));
+					return Err(())
+				};
+				let request_id = HttpRequestId(*next_id);
+				*next_id = next_id.checked_add(1).ok_or(())?;
+				requests.insert(request_id, (exchange, 0));
+				request_id
+			},
+			ReplayHttp::Network(api) => api.request_start(method, uri)?,
+		};
+
+		let mut recording = self.recording.lock();
+		let index = recording.report.http_requests.len();
+		recording.report.http_requests.push(HttpExchange {
+			method: method.into(),
+			uri: uri.into(),
+			..Default::default()
+		});
+		recording.requests.insert(request_id, index);
+		Ok(request_id)
+	}
+
+	fn http_request_add_header(
+		&mut self,
+		request_id: HttpRequestId,
+		name: &str,
+		value: &str,
+	) -> Result<(), ()> {
+		match &mut self.http {
+			ReplayHttp::Fixture { requests, .. } =>
+				if !requests.contains_key(&request_id) {
+					return Err(())
+				},
+			ReplayHttp::Network(api) => api.request_add_header(request_id, name, value)?,
+		}
+
+		if let Some(exchange) = self.recording.lock().exchange(request_id) {
+			exchange.request_headers.push((name.into(), value.into()));
+		}
+		Ok(())
+	}
+
+	fn http_request_write_body(
+		&mut self,
+		request_id: HttpRequestId,
+		chunk: &[u8],
+		deadline: Option<Timestamp>,
+	) -> Result<(), HttpError> {
+		match &mut self.http {
+			ReplayHttp::Fixture { requests, .. } =>
+				if !requests.contains_key(&request_id) {
+					return Err(HttpError::Invalid)
+				},
+			ReplayHttp::Network(api) => api.request_write_body(request_id, chunk, deadline)?,
+		}
+
+		if let Some(exchange) = self.recording.lock().exchange(request_id) {
+			exchange.request_body.extend_from_slice(chunk);
+		}
+		Ok(())
+	}
+
+	fn http_response_wait(
+		&mut self,
+		ids: &[HttpRequestId],
+		deadline: Option<Timestamp>,
+	) -> Vec<HttpRequestStatus> {
+		let statuses = match &mut self.http {
+			ReplayHttp::Fixture { requests, .. } => ids
+				.iter()
+				.map(|id| match requests.get(id) {
+					Some((HttpExchange { status: Some(status), .. }, _)) =>
+						HttpRequestStatus::Finished(*status),
+					Some(_) => HttpRequestStatus::IoError,
+					None => HttpRequestStatus::Invalid,
+				})
+				.collect(),
+			ReplayHttp::Network(api) => api.response_wait(ids, deadline),
+		};
+
+		let mut recording = self.recording.lock();
+		for (id, status) in ids.iter().zip(&statuses) {
+			if let (Some(exchange), HttpRequestStatus::Finished(status)) =
+				(recording.exchange(*id), status)
+			{
+				exchange.status = Some(*status);
+			}
+		}
+		statuses
+	}
+
+	fn http_response_headers(&mut self, request_id: HttpRequestId) -> Vec<(Vec<u8>, Vec<u8>)> {
+		let headers = match &mut self.http {
+			ReplayHttp::Fixture { requests, .. } => requests
+				.get(&request_id)
+				.map(|(exchange, _)| {
+					exchange
+						.response_headers
+						.iter()
+						.map(|(name, value)| {
+							(name.clone().into_bytes(), value.clone().into_bytes())
+						})
+						.collect()
+				})
+				.unwrap_or_default(),
+			ReplayHttp::Network(api) => api.response_headers(request_id),
+		};
+
+		if let Some(exchange) = self.recording.lock().exchange(request_id) {
+			exchange.response_headers = headers
+				.iter()
+				.map(|(name, value)| {
+					(
+						String::from_utf8_lossy(name).into_owned(),
+						String::from_utf8_lossy(value).into_owned(),
+					)
+				})
+				.collect();
+		}
+		headers
+	}
+
+	fn http_response_read_body(
+		&mut self,
+		request_id: HttpRequestId,
+		buffer: &mut [u8],
+		deadline: Option<Timestamp>,
+	) -> Result<usize, HttpError> {
+		let read = match &mut self.http {
+			ReplayHttp::Fixture { requests, .. } => {
+				let (exchange, offset) = requests.get_mut(&request_id).ok_or(HttpError::Invalid)?;
+				if exchange.status.is_none() {
+					return Err(HttpError::IoError)
+				}
+				let remaining = &exchange.response_body[*offset..];
+				let read = remaining.len().min(buffer.len());
+				buffer[..read].copy_from_slice(&remaining[..read]);
+				*offset += read;
+				read
+			},
+			ReplayHttp::Network(api) => api.response_read_body(request_id, buffer, deadline)?,
+		};
+
+		if let Some(exchange) = self.recording.lock().exchange(request_id) {
+			exchange.response_body.extend_from_slice(&buffer[..read]);
+		}
+		Ok(read)
+	}
+
+	fn set_authorized_nodes(&mut self, _nodes: Vec<OpaquePeerId>, _authorized_only: bool) {}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::offchain::{DbExternalities, Externalities};
+
+	fn replay_api(fixture: Fixture) -> ReplayApi {
+		let exchanges = fixture.http.into_iter().map(Some).collect();
+		ReplayApi {
+			is_validator: false,
+			clock: Clock::Virtual(fixture.timestamp),
+			random_seed: [0; 32],
+			http: ReplayHttp::Fixture { exchanges, requests: HashMap::new(), next_id: 0 },
+			recording: Default::default(),
+		}
+	}
+
+	#[test]
+	fn http_is_served_from_fixture() {
+		let exchange = HttpExchange {
+			method: "GET".into(),
+			uri: "https://example.com/price".into(),
+			status: Some(200),
+			response_headers: vec![("Content-Type".to_string(), "text/plain".to_string())],
+			response_body: b"42.5".to_vec(),
+			..Default::default()
+		};
+		let mut api =
+			replay_api(Fixture { timestamp: 1_000, http: vec![exchange], ..Default::default() });
+
+		assert!(api.http_request_start("GET", "https://example.com/other", &[]).is_err());
+		let id = api.http_request_start("GET", "https://example.com/price", &[]).unwrap();
+		api.http_request_add_header(id, "Accept", "text/plain").unwrap();
+		assert_eq!(api.http_response_wait(&[id], None), vec![HttpRequestStatus::Finished(200)]);
+		assert_eq!(
+			api.http_response_headers(id),
+			vec![(b"Content-Type".to_vec(), b"text/plain".to_vec())],
+		);
+
+		let mut buffer = [0; 3];
+		assert_eq!(api.http_response_read_body(id, &mut buffer, None), Ok(3));
+		assert_eq!(api.http_response_read_body(id, &mut buffer, None), Ok(1));
+		assert_eq!(&buffer[..1], b"5");
+		assert_eq!(api.http_response_read_body(id, &mut buffer, None), Ok(0));
+
+		// Every exchange of the fixture is only served once.
+		assert!(api.http_request_start("GET", "https://example.com/price", &[]).is_err());
+
+		// Sleeping only advances the virtual clock.
+		api.sleep_until(Timestamp::from_unix_millis(5_000));
+		assert_eq!(api.timestamp(), Timestamp::from_unix_millis(5_000));
+
+		let report = std::mem::take(&mut api.recording.lock().report);
+		assert_eq!(
+			report.unmatched_http_requests,
+			vec!["GET https://example.com/other", "GET https://example.com/price"],
+		);
+		assert_eq!(report.http_requests.len(), 1);
+		assert_eq!(
+			report.http_requests[0].request_headers,
+			vec![("Accept".to_string(), "text/plain".to_string())],
+		);
+		assert_eq!(report.http_requests[0].response_body, b"42.5".to_vec());
+		assert_eq!(report.fixture().http[0].status, Some(200));
+	}
+
+	#[test]
+	fn storage_writes_record_their_kind() {
+		let mut persistent = sp_core::offchain::storage::InMemOffchainStorage::default();
+		persistent.set(STORAGE_PREFIX, b"stored", b"db");
+		let recording = Arc::<Mutex<Recording>>::default();
+		let mut db = ReplayDb { persistent: Some(persistent), recording: recording.clone() };
+
+		assert_eq!(db.local_storage_get(StorageKind::PERSISTENT, b"stored"), Some(b"db".to_vec()));
+		assert_eq!(db.local_storage_get(StorageKind::LOCAL, b"stored"), None);
+		db.local_storage_set(StorageKind::LOCAL, b"key", b"local");
+		assert!(db.local_storage_compare_and_set(StorageKind::PERSISTENT, b"key", None, b"new"));
+		db.local_storage_clear(StorageKind::PERSISTENT, b"stored");
+
+		assert_eq!(db.local_storage_get(StorageKind::LOCAL, b"key"), Some(b"local".to_vec()));
+		assert_eq!(db.local_storage_get(StorageKind::PERSISTENT, b"key"), Some(b"new".to_vec()));
+		assert_eq!(db.local_storage_get(StorageKind::PERSISTENT, b"stored"), None);
+		// Writes never reach the database.
+		let persistent = db.persistent.as_ref().unwrap();
+		assert_eq!(persistent.get(STORAGE_PREFIX, b"stored"), Some(b"db".to_vec()));
+
+		let write = |kind, key: &[u8], value: Option<&[u8]>| StorageWrite {
+			kind,
+			key: key.to_vec().into(),
+			value: value.map(|v| v.to_vec().into()),
+		};
+		assert_eq!(
+			recording.lock().report.storage_writes,
+			vec![
+				write(StorageKind::LOCAL, b"key", Some(b"local")),
+				write(StorageKind::PERSISTENT, b"key", Some(b"new")),
+				write(StorageKind::PERSISTENT, b"stored", None),
+			],
+		);
+	}
+
+	#[test]
+	fn network_replay_uses_the_system_clock() {
+		let mut api = replay_api(Default::default());
+		api.clock = Clock::System;
+
+		let before = timestamp::now();
+		let now = api.timestamp();
+		assert!(now >= before && now <= timestamp::now());
+	}
+
+	#[test]
+	fn offchain_worker_is_replayed() {
+		sp_tracing::try_init_simple();
+
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let hash = client.chain_info().genesis_hash;
+		let options = ReplayOptions {
+			offchain_db: None::<crate::NoOffchainStorage>,
+			keystore: None,
+			is_validator: false,
+			http: HttpSource::Fixture(Default::default()),
+		};
+
+		let report = futures::executor::block_on(replay(client, hash, options)).unwrap();
+
+		assert_eq!(report.block_hash, format!("{hash:?}"));
+		assert_eq!(report.error, None);
+		assert_eq!(report.transactions.len(), 1);
+		assert!(report.http_requests.is_empty());
+	}
+}
@@ -86,6 +86,8 @@ const POOL_NO_TAGS: i32 = POOL_INVALID_TX + 9;
 const POOL_INVALID_BLOCK_ID: i32 = POOL_INVALID_TX + 10;
 /// The pool is not accepting future transactions.
 const POOL_FUTURE_TX: i32 = POOL_INVALID_TX + 11;
+/// The sender has reached its limit of transactions in the pool.
+const POOL_SENDER_LIMIT: i32 = POOL_INVALID_TX + 12;
 
 impl From<Error> for JsonRpseeError {
 	fn from(e: Error) -> Self {
@@ -176,6 +178,11 @@ impl From<Error> for JsonRpseeError {
 					None::<()>,
 				))
 			},
+			Error::Pool(PoolError::SenderLimitReached) => CallError::Custom(ErrorObject::owned(
+				POOL_SENDER_LIMIT,
+				"Sender limit reached",
+				Some("The sender has too many transactions in the pool"),
+			)),
 			Error::UnsafeRpcCalled(e) => e.into(),
 			e => CallError::Failed(e.into()),
 		}.into()
@@ -71,6 +71,17 @@ pub struct PeerInfo<Hash, Number> {
 	pub best_number: Number,
 }
 
+/// A directive of the node's log filter
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFilter {
+	/// The directive, e.g. `sync=debug`
+	pub directive: String,
+	/// Seconds until a temporary directive is removed again
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub expires_in: Option<u64>,
+}
+
 /// The role the node is running as
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub enum NodeRole {
@@ -23,7 +23,7 @@ use jsonrpsee::{
 	proc_macros::rpc,
 };
 
-pub use self::helpers::{Health, NodeRole, PeerInfo, SyncState, SystemInfo};
+pub use self::helpers::{Health, LogFilter, NodeRole, PeerInfo, SyncState, SystemInfo};
 
 pub mod error;
 pub mod helpers;
@@ -117,6 +117,17 @@ pub trait SystemApi<Hash, Number> {
 	#[method(name = "system_addLogFilter")]
 	fn system_add_log_filter(&self, directives: String) -> RpcResult<()>;
 
+	/// Adds the supplied directives to the current log filter for `duration` seconds
+	///
+	/// The directives are removed again once the duration elapsed. The syntax is identical to
+	/// `system_addLogFilter`.
+	#[method(name = "system_addTemporaryLogFilter")]
+	fn system_add_temporary_log_filter(&self, directives: String, duration: u64) -> RpcResult<()>;
+
+	/// Returns the directives of the current log filter
+	#[method(name = "system_logFilters")]
+	fn system_log_filters(&self) -> RpcResult<Vec<LogFilter>>;
+
 	/// Resets the log filter to Substrate defaults
 	#[method(name = "system_resetLogFilter")]
 	fn system_reset_log_filter(&self) -> RpcResult<()>;
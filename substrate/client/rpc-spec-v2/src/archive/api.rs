@@ -18,7 +18,13 @@
 
 //! API trait of the archive methods.
 
-use crate::MethodResult;
+use crate::{
+	common::events::{
+		ArchiveStorageDiffItem, ArchiveStorageDiffResult, ArchiveStorageResult,
+		PaginatedStorageQuery,
+	},
+	MethodResult,
+};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 
 #[rpc(client, server)]
@@ -88,4 +94,41 @@ pub trait ArchiveApi<Hash> {
 		function: String,
 		call_parameters: String,
 	) -> RpcResult<MethodResult>;
+
+	/// Returns storage entries at a specific block's state.
+	///
+	/// Descendant queries report at most a configured number of entries; the last
+	/// reported key can be provided as `paginationStartKey` to resume the iteration.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "archive_unstable_storage", blocking)]
+	fn archive_unstable_storage(
+		&self,
+		hash: Hash,
+		items: Vec<PaginatedStorageQuery<String>>,
+		child_trie: Option<String>,
+	) -> RpcResult<ArchiveStorageResult>;
+
+	/// Returns the storage differences between two blocks.
+	///
+	/// Reports the keys that were added, modified or deleted in the block `hash`
+	/// compared to the block `previousHash`. When `previousHash` is not provided,
+	/// the parent of `hash` is used.
+	///
+	/// The number of reported differences and compared keys is limited. Items that were
+	/// not fully compared are returned as `remainingItems`, with a `paginationStartKey`
+	/// to resume the comparison in a subsequent call.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "archive_unstable_storageDiff", blocking)]
+	fn archive_unstable_storage_diff(
+		&self,
+		hash: Hash,
+		items: Vec<ArchiveStorageDiffItem<String>>,
+		previous_hash: Option<Hash>,
+	) -> RpcResult<ArchiveStorageDiffResult>;
 }
@@ -19,15 +19,24 @@
 //! API implementation for `archive`.
 
 use crate::{
-	archive::{error::Error as ArchiveError, ArchiveApiServer},
+	archive::{
+		archive_storage::{ArchiveStorage, ArchiveStorageDiff},
+		error::Error as ArchiveError,
+		ArchiveApiServer,
+	},
 	chain_head::hex_string,
+	common::events::{
+		ArchiveStorageDiffItem, ArchiveStorageDiffResult, ArchiveStorageResult,
+		PaginatedStorageQuery,
+	},
 	MethodResult,
 };
 
 use codec::Encode;
 use jsonrpsee::core::{async_trait, RpcResult};
 use sc_client_api::{
-	Backend, BlockBackend, BlockchainEvents, CallExecutor, ExecutorProvider, StorageProvider,
+	Backend, BlockBackend, BlockchainEvents, CallExecutor, ChildInfo, ExecutorProvider, StorageKey,
+	StorageProvider,
 };
 use sp_api::{CallApiAt, CallContext};
 use sp_blockchain::{
@@ -40,6 +49,60 @@ use sp_runtime::{
 };
 use std::{collections::HashSet, marker::PhantomData, sync::Arc};
 
+/// The configuration of [`Archive`].
+pub struct ArchiveConfig {
+	/// The maximum number of items the `archive_storage` can return for a descendant query before
+	/// pagination is required.
+	pub max_descendant_responses: usize,
+	/// The maximum number of queried items allowed for the `archive_storage` and
+	/// `archive_storageDiff` at a time.
+	pub max_queried_items: usize,
+	/// The maximum number of differences the `archive_storageDiff` can report before pagination
+	/// is required.
+	pub max_diff_reported_items: usize,
+	/// The maximum number of bytes the `archive_storageDiff` can report before pagination is
+	/// required.
+	pub max_diff_reported_bytes: usize,
+	/// The maximum number of keys the `archive_storageDiff` can compare before pagination is
+	/// required.
+	pub max_diff_compared_keys: usize,
+}
+
+/// The maximum number of items the `archive_storage` can return for a descendant query before
+/// pagination is required.
+///
+/// Note: this is identical to the `chainHead` value.
+const MAX_DESCENDANT_RESPONSES: usize = 5;
+
+/// The maximum number of queried items allowed for the `archive_storage` at a time.
+///
+/// Note: A queried item can also be a descendant query which can return up to
+/// `MAX_DESCENDANT_RESPONSES`.
+const MAX_QUERIED_ITEMS: usize = 8;
+
+/// The maximum number of differences the `archive_storageDiff` can report at a time.
+const MAX_DIFF_REPORTED_ITEMS: usize = 1024;
+
+/// The maximum number of bytes the `archive_storageDiff` can report at a time.
+const MAX_DIFF_REPORTED_BYTES: usize = 4 * 1024 * 1024;
+
+/// The maximum number of keys the `archive_storageDiff` can compare at a time.
+///
+/// Note: this bounds the time spent on large tries with few differences.
+const MAX_DIFF_COMPARED_KEYS: usize = 64 * 1024;
+
+impl Default for ArchiveConfig {
+	fn default() -> Self {
+		ArchiveConfig {
+			max_descendant_responses: MAX_DESCENDANT_RESPONSES,
+			max_queried_items: MAX_QUERIED_ITEMS,
+			max_diff_reported_items: MAX_DIFF_REPORTED_ITEMS,
+			max_diff_reported_bytes: MAX_DIFF_REPORTED_BYTES,
+			max_diff_compared_keys: MAX_DIFF_COMPARED_KEYS,
+		}
+	}
+}
+
 /// An API for archive RPC calls.
 pub struct Archive<BE: Backend<Block>, Block: BlockT, Client> {
 	/// Substrate client.
@@ -48,6 +111,17 @@ pub struct Archive<BE: Backend<Block>, Block: BlockT, Client> {
 	backend: Arc<BE>,
 	/// The hexadecimal encoded hash of the genesis block.
 	genesis_hash: String,
+	/// The maximum number of items the `archive_storage` can return for a descendant query before
+	/// pagination is required.
+	storage_max_descendant_responses: usize,
+	/// The maximum number of queried items allowed for the `archive_storage` at a time.
+	storage_max_queried_items: usize,
+	/// The maximum number of differences the `archive_storageDiff` can report at a time.
+	storage_diff_max_reported_items: usize,
+	/// The maximum number of bytes the `archive_storageDiff` can report at a time.
+	storage_diff_max_reported_bytes: usize,
+	/// The maximum number of keys the `archive_storageDiff` can compare at a time.
+	storage_diff_max_compared_keys: usize,
 	/// Phantom member to pin the block type.
 	_phantom: PhantomData<(Block, BE)>,
 }
@@ -58,9 +132,20 @@ impl<BE: Backend<Block>, Block: BlockT, Client> Archive<BE, Block, Client> {
 		client: Arc<Client>,
 		backend: Arc<BE>,
 		genesis_hash: GenesisHash,
+		config: ArchiveConfig,
 	) -> Self {
 		let genesis_hash = hex_string(&genesis_hash.as_ref());
-		Self { client, backend, genesis_hash, _phantom: PhantomData }
+		Self {
+			client,
+			backend,
+			genesis_hash,
+			storage_max_descendant_responses: config.max_descendant_responses,
+			storage_max_queried_items: config.max_queried_items,
+			storage_diff_max_reported_items: config.max_diff_reported_items,
+			storage_diff_max_reported_bytes: config.max_diff_reported_bytes,
+			storage_diff_max_compared_keys: config.max_diff_compared_keys,
+			_phantom: PhantomData,
+		}
 	}
 }
 
@@ -185,4 +270,94 @@ where
 			Err(error) => MethodResult::err(error.to_string()),
 		})
 	}
+
+	fn archive_unstable_storage(
+		&self,
+		hash: Block::Hash,
+		items: Vec<PaginatedStorageQuery<String>>,
+		child_trie: Option<String>,
+	) -> RpcResult<ArchiveStorageResult> {
+		let items = items
+			.into_iter()
+			.map(|query| {
+				let key = StorageKey(parse_hex_param(query.key)?);
+				let pagination_start_key = query
+					.pagination_start_key
+					.map(|key| parse_hex_param(key).map(StorageKey))
+					.transpose()?;
+
+				// The pagination start key is only meaningful for descendant queries.
+				if pagination_start_key.is_some() && !query.query_type.is_descendant_query() {
+					return Err(ArchiveError::InvalidParam(
+						"Pagination start key is only supported for descendants queries"
+							.to_string(),
+					))
+				}
+
+				Ok(PaginatedStorageQuery {
+					key,
+					query_type: query.query_type,
+					pagination_start_key,
+				})
+			})
+			.collect::<Result<Vec<_>, ArchiveError>>()?;
+
+		let child_trie = child_trie
+			.map(parse_hex_param)
+			.transpose()?
+			.map(ChildInfo::new_default_from_vec);
+
+		let storage_client = ArchiveStorage::new(
+			self.client.clone(),
+			self.storage_max_descendant_responses,
+			self.storage_max_queried_items,
+		);
+		Ok(storage_client.handle_query(hash, items, child_trie))
+	}
+
+	fn archive_unstable_storage_diff(
+		&self,
+		hash: Block::Hash,
+		items: Vec<ArchiveStorageDiffItem<String>>,
+		previous_hash: Option<Block::Hash>,
+	) -> RpcResult<ArchiveStorageDiffResult> {
+		let items = items
+			.into_iter()
+			.map(|item| {
+				Ok(ArchiveStorageDiffItem {
+					key: StorageKey(parse_hex_param(item.key)?),
+					return_type: item.return_type,
+					child_trie_key: item
+						.child_trie_key
+						.map(|key| parse_hex_param(key).map(StorageKey))
+						.transpose()?,
+					pagination_start_key: item
+						.pagination_start_key
+						.map(|key| parse_hex_param(key).map(StorageKey))
+						.transpose()?,
+				})
+			})
+			.collect::<Result<Vec<_>, ArchiveError>>()?;
+
+		let previous_hash = match previous_hash {
+			Some(previous_hash) => previous_hash,
+			None => match self.client.header(hash) {
+				Ok(Some(header)) => *header.parent_hash(),
+				Ok(None) =>
+					return Ok(ArchiveStorageDiffResult::err(format!(
+						"Block header is not present: {hash:?}"
+					))),
+				Err(error) => return Ok(ArchiveStorageDiffResult::err(error.to_string())),
+			},
+		};
+
+		let storage_client = ArchiveStorageDiff::new(
+			self.client.clone(),
+			self.storage_max_queried_items,
+			self.storage_diff_max_reported_items,
+			self.storage_diff_max_reported_bytes,
+			self.storage_diff_max_compared_keys,
+		);
+		Ok(storage_client.handle_diff(hash, previous_hash, items))
+	}
 }
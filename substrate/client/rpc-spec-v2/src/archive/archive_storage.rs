@@ -0,0 +1,472 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Implementation of the `archive_storage` and `archive_storageDiff` methods.
+
+use std::{cmp::Ordering, sync::Arc};
+
+use sc_client_api::{Backend, ChildInfo, StorageKey, StorageProvider};
+use sp_runtime::traits::Block as BlockT;
+
+use crate::{
+	chain_head::hex_string,
+	common::{
+		events::{
+			ArchiveStorageDiffItem, ArchiveStorageDiffOperationType, ArchiveStorageDiffResult,
+			ArchiveStorageDiffResultItem, ArchiveStorageDiffType, ArchiveStorageResult,
+			PaginatedStorageQuery, StorageQueryType, StorageResult, StorageResultType,
+		},
+		storage::{is_key_queryable, IterQueryType, QueryIter, QueryResult, Storage},
+	},
+};
+
+/// Generates the results of the `archive_storage` method.
+pub struct ArchiveStorage<Client, Block, BE> {
+	/// Storage client.
+	client: Storage<Client, Block, BE>,
+	/// The maximum number of responses the API can return for a descendant query at a time.
+	max_descendant_responses: usize,
+	/// The maximum number of queried items allowed for the `archive_storage` at a time.
+	max_queried_items: usize,
+}
+
+impl<Client, Block, BE> ArchiveStorage<Client, Block, BE> {
+	/// Constructs a new [`ArchiveStorage`].
+	pub fn new(
+		client: Arc<Client>,
+		max_descendant_responses: usize,
+		max_queried_items: usize,
+	) -> Self {
+		Self { client: Storage::new(client), max_descendant_responses, max_queried_items }
+	}
+}
+
+impl<Client, Block, BE> ArchiveStorage<Client, Block, BE>
+where
+	Block: BlockT + 'static,
+	BE: Backend<Block> + 'static,
+	Client: StorageProvider<Block, BE> + 'static,
+{
+	/// Generate the response of the `archive_storage` method.
+	pub fn handle_query(
+		&self,
+		hash: Block::Hash,
+		mut items: Vec<PaginatedStorageQuery<StorageKey>>,
+		child_key: Option<ChildInfo>,
+	) -> ArchiveStorageResult {
+		let discarded_items = items.len().saturating_sub(self.max_queried_items);
+		items.truncate(self.max_queried_items);
+
+		if let Some(child_key) = child_key.as_ref() {
+			if !is_key_queryable(child_key.storage_key()) {
+				return ArchiveStorageResult::ok(Vec::new(), discarded_items)
+			}
+		}
+
+		let mut storage_results = Vec::with_capacity(items.len());
+		for item in items {
+			if !is_key_queryable(&item.key.0) {
+				continue
+			}
+
+			let iter_query = |ty| QueryIter {
+				query_key: item.key.clone(),
+				ty,
+				pagination_start_key: item.pagination_start_key.clone(),
+			};
+
+			let result = match item.query_type {
+				StorageQueryType::Value =>
+					self.client.query_value(hash, &item.key, child_key.as_ref()),
+				StorageQueryType::Hash =>
+					self.client.query_hash(hash, &item.key, child_key.as_ref()),
+				StorageQueryType::ClosestDescendantMerkleValue =>
+					self.client.query_merkle_value(hash, &item.key, child_key.as_ref()),
+				StorageQueryType::DescendantsValues => {
+					match self.client.query_iter_pagination(
+						iter_query(IterQueryType::Value),
+						hash,
+						child_key.as_ref(),
+						self.max_descendant_responses,
+					) {
+						Ok((results, _)) => storage_results.extend(results),
+						Err(error) => return ArchiveStorageResult::err(error),
+					}
+					continue
+				},
+				StorageQueryType::DescendantsHashes => {
+					match self.client.query_iter_pagination(
+						iter_query(IterQueryType::Hash),
+						hash,
+						child_key.as_ref(),
+						self.max_descendant_responses,
+					) {
+						Ok((results, _)) => storage_results.extend(results),
+						Err(error) => return ArchiveStorageResult::err(error),
+					}
+					continue
+				},
+			};
+
+			match result {
+				Ok(Some(value)) => storage_results.push(value),
+				Ok(None) => continue,
+				Err(error) => return ArchiveStorageResult::err(error),
+			}
+		}
+
+		ArchiveStorageResult::ok(storage_results, discarded_items)
+	}
+}
+
+/// Generates the results of the `archive_storageDiff` method.
+pub struct ArchiveStorageDiff<Client, Block, BE> {
+	/// Substrate client, used to iterate over the keys of a trie.
+	client: Arc<Client>,
+	/// Storage client, used to fetch the values or hashes of keys.
+	storage: Storage<Client, Block, BE>,
+	/// The maximum number of queried items allowed for the `archive_storageDiff` at a time.
+	max_queried_items: usize,
+	/// The maximum number of differences reported by a single call.
+	max_reported_items: usize,
+	/// The maximum number of bytes of keys and results reported by a single call.
+	max_reported_bytes: usize,
+	/// The maximum number of keys compared by a single call.
+	max_compared_keys: usize,
+}
+
+impl<Client, Block, BE> ArchiveStorageDiff<Client, Block, BE> {
+	/// Constructs a new [`ArchiveStorageDiff`].
+	pub fn new(
+		client: Arc<Client>,
+		max_queried_items: usize,
+		max_reported_items: usize,
+		max_reported_bytes: usize,
+		max_compared_keys: usize,
+	) -> Self {
+		Self {
+			storage: Storage::new(client.clone()),
+			client,
+			max_queried_items,
+			max_reported_items,
+			max_reported_bytes,
+			max_compared_keys,
+		}
+	}
+}
+
+/// Tracks the work done by a single `archive_storageDiff` call.
+#[derive(Default)]
+struct DiffBudget {
+	/// The number of reported differences.
+	reported_items: usize,
+	/// The number of bytes of the reported differences.
+	reported_bytes: usize,
+	/// The number of compared keys.
+	compared_keys: usize,
+}
+
+impl<Client, Block, BE> ArchiveStorageDiff<Client, Block, BE>
+where
+	Block: BlockT + 'static,
+	BE: Backend<Block> + 'static,
+	Client: StorageProvider<Block, BE> + 'static,
+{
+	/// Generate the response of the `archive_storageDiff` method.
+	///
+	/// Reports the keys that were added, modified or deleted in the block `hash` compared
+	/// to the block `previous_hash`. An empty list of items reports the differences of the
+	/// entire main trie.
+	///
+	/// The comparison stops once one of the limits is reached. The items that were not fully
+	/// compared are returned, with the key to resume from, in the `remaining_items` of the result.
+	pub fn handle_diff(
+		&self,
+		hash: Block::Hash,
+		previous_hash: Block::Hash,
+		items: Vec<ArchiveStorageDiffItem<StorageKey>>,
+	) -> ArchiveStorageDiffResult {
+		let mut items = if items.is_empty() {
+			vec![ArchiveStorageDiffItem {
+				key: StorageKey(Vec::new()),
+				return_type: ArchiveStorageDiffType::Value,
+				child_trie_key: None,
+				pagination_start_key: None,
+			}]
+		} else {
+			deduplicate_items(items)
+		};
+		let discarded_items = items.split_off(items.len().min(self.max_queried_items));
+
+		let mut results = Vec::new();
+		let mut remaining_items = Vec::new();
+		let mut budget = DiffBudget::default();
+
+		let mut items = items.into_iter();
+		while let Some(item) = items.next() {
+			match self.diff_item(hash, previous_hash, item, &mut results, &mut budget) {
+				Ok(None) => continue,
+				Ok(Some(remaining)) => {
+					remaining_items.push(remaining);
+					remaining_items.extend(items.by_ref());
+				},
+				Err(error) => return ArchiveStorageDiffResult::err(error),
+			}
+		}
+		remaining_items.extend(discarded_items);
+
+		ArchiveStorageDiffResult::ok(
+			results,
+			remaining_items.into_iter().map(hex_encode_item).collect(),
+		)
+	}
+
+	/// Returns `true` if the call did as much work as it is allowed to.
+	fn is_exhausted(&self, budget: &DiffBudget) -> bool {
+		budget.reported_items >= self.max_reported_items ||
+			budget.reported_bytes >= self.max_reported_bytes ||
+			budget.compared_keys >= self.max_compared_keys
+	}
+
+	/// Fetch the value or the hash of the given key, depending on the return type.
+	fn fetch(
+		&self,
+		hash: Block::Hash,
+		key: &StorageKey,
+		child_key: Option<&ChildInfo>,
+		return_type: &ArchiveStorageDiffType,
+	) -> QueryResult {
+		match return_type {
+			ArchiveStorageDiffType::Value => self.storage.query_value(hash, key, child_key),
+			ArchiveStorageDiffType::Hash => self.storage.query_hash(hash, key, child_key),
+		}
+	}
+
+	/// Compare the descendants of the item's key between the two blocks.
+	///
+	/// Both key iterators are sorted lexicographically, which allows walking them in
+	/// lockstep and visiting every key at most once.
+	///
+	/// Returns the item to resume from if the limits were reached before all keys were
+	/// compared.
+	fn diff_item(
+		&self,
+		hash: Block::Hash,
+		previous_hash: Block::Hash,
+		item: ArchiveStorageDiffItem<StorageKey>,
+		results: &mut Vec<ArchiveStorageDiffResultItem>,
+		budget: &mut DiffBudget,
+	) -> Result<Option<ArchiveStorageDiffItem<StorageKey>>, String> {
+		let child_key = item.child_trie_key.as_ref().map(|key| ChildInfo::new_default(&key.0));
+		let child_trie_key = item.child_trie_key.as_ref().map(|key| hex_string(&key.0));
+
+		let keys_at = |at: Block::Hash| {
+			if let Some(child_key) = child_key.as_ref() {
+				self.client.child_storage_keys(
+					at,
+					child_key.to_owned(),
+					Some(&item.key),
+					item.pagination_start_key.as_ref(),
+				)
+			} else {
+				self.client
+					.storage_keys(at, Some(&item.key), item.pagination_start_key.as_ref())
+			}
+			.map_err(|error| error.to_string())
+		};
+
+		let mut keys = keys_at(hash)?.peekable();
+		let mut previous_keys = keys_at(previous_hash)?.peekable();
+		let mut last_key = None;
+
+		let mut report = |result: StorageResult, operation_type, budget: &mut DiffBudget| {
+			let result_len = match &result.result {
+				StorageResultType::Value(value) |
+				StorageResultType::Hash(value) |
+				StorageResultType::ClosestDescendantMerkleValue(value) => value.len(),
+			};
+			budget.reported_items += 1;
+			budget.reported_bytes += result.key.len() + result_len;
+
+			results.push(ArchiveStorageDiffResultItem {
+				key: result.key,
+				result: result.result,
+				operation_type,
+				child_trie_key: child_trie_key.clone(),
+			})
+		};
+
+		loop {
+			let ordering = match (keys.peek(), previous_keys.peek()) {
+				(None, None) => break,
+				(Some(_), None) => Ordering::Less,
+				(None, Some(_)) => Ordering::Greater,
+				(Some(key), Some(previous_key)) => key.0.cmp(&previous_key.0),
+			};
+
+			if self.is_exhausted(budget) {
+				return Ok(Some(ArchiveStorageDiffItem {
+					pagination_start_key: last_key.or(item.pagination_start_key),
+					..item
+				}))
+			}
+			budget.compared_keys += 1;
+
+			match ordering {
+				// The key is present only in the current block.
+				Ordering::Less => {
+					let Some(key) = keys.next() else { break };
+					if let Some(result) =
+						self.fetch(hash, &key, child_key.as_ref(), &item.return_type)?
+					{
+						report(result, ArchiveStorageDiffOperationType::Added, budget);
+					}
+					last_key = Some(key);
+				},
+				// The key is present only in the previous block.
+				Ordering::Greater => {
+					let Some(key) = previous_keys.next() else { break };
+					if let Some(result) =
+						self.fetch(previous_hash, &key, child_key.as_ref(), &item.return_type)?
+					{
+						report(result, ArchiveStorageDiffOperationType::Deleted, budget);
+					}
+					last_key = Some(key);
+				},
+				// The key is present in both blocks.
+				Ordering::Equal => {
+					let (Some(key), Some(_)) = (keys.next(), previous_keys.next()) else { break };
+					let current = self.fetch(hash, &key, child_key.as_ref(), &item.return_type)?;
+					let previous =
+						self.fetch(previous_hash, &key, child_key.as_ref(), &item.return_type)?;
+
+					if let (Some(current), Some(previous)) = (current, previous) {
+						if current.result != previous.result {
+							report(current, ArchiveStorageDiffOperationType::Modified, budget);
+						}
+					}
+					last_key = Some(key);
+				},
+			}
+		}
+
+		Ok(None)
+	}
+}
+
+/// Hex-encode the keys of the item, as expected by the RPC clients.
+fn hex_encode_item(item: ArchiveStorageDiffItem<StorageKey>) -> ArchiveStorageDiffItem<String> {
+	ArchiveStorageDiffItem {
+		key: hex_string(&item.key.0),
+		return_type: item.return_type,
+		child_trie_key: item.child_trie_key.map(|key| hex_string(&key.0)),
+		pagination_start_key: item.pagination_start_key.map(|key| hex_string(&key.0)),
+	}
+}
+
+/// Remove the items that are already covered by other items.
+///
+/// An item is covered if another item of the same trie and return type
+/// queries a prefix of its key.
+fn deduplicate_items(
+	mut items: Vec<ArchiveStorageDiffItem<StorageKey>>,
+) -> Vec<ArchiveStorageDiffItem<StorageKey>> {
+	items.sort_by(|a, b| {
+		let a_child = a.child_trie_key.as_ref().map(|key| &key.0);
+		let b_child = b.child_trie_key.as_ref().map(|key| &key.0);
+		a_child
+			.cmp(&b_child)
+			.then_with(|| a.return_type.cmp(&b.return_type))
+			.then_with(|| a.key.0.cmp(&b.key.0))
+	});
+
+	// Keys are sorted lexicographically, therefore any key that has the last kept key
+	// as prefix is placed right after it.
+	let mut deduplicated: Vec<ArchiveStorageDiffItem<StorageKey>> = Vec::with_capacity(items.len());
+	for item in items {
+		if let Some(last) = deduplicated.last() {
+			let same_trie = last.child_trie_key.as_ref().map(|key| &key.0) ==
+				item.child_trie_key.as_ref().map(|key| &key.0);
+			if same_trie &&
+				last.return_type == item.return_type &&
+				item.key.0.starts_with(&last.key.0)
+			{
+				continue
+			}
+		}
+
+		deduplicated.push(item);
+	}
+
+	deduplicated
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn item(
+		key: &[u8],
+		return_type: ArchiveStorageDiffType,
+		child_trie_key: Option<&[u8]>,
+	) -> ArchiveStorageDiffItem<StorageKey> {
+		ArchiveStorageDiffItem {
+			key: StorageKey(key.to_vec()),
+			return_type,
+			child_trie_key: child_trie_key.map(|key| StorageKey(key.to_vec())),
+			pagination_start_key: None,
+		}
+	}
+
+	#[test]
+	fn deduplicate_prefixed_items() {
+		let items = vec![
+			item(b":AB", ArchiveStorageDiffType::Value, None),
+			item(b":A", ArchiveStorageDiffType::Value, None),
+			item(b":ABC", ArchiveStorageDiffType::Value, None),
+			item(b":B", ArchiveStorageDiffType::Value, None),
+		];
+
+		assert_eq!(
+			deduplicate_items(items),
+			vec![
+				item(b":A", ArchiveStorageDiffType::Value, None),
+				item(b":B", ArchiveStorageDiffType::Value, None),
+			]
+		);
+	}
+
+	#[test]
+	fn deduplicate_keeps_distinct_return_types_and_tries() {
+		let items = vec![
+			item(b":A", ArchiveStorageDiffType::Value, None),
+			item(b":AB", ArchiveStorageDiffType::Hash, None),
+			item(b":AB", ArchiveStorageDiffType::Value, Some(b"child")),
+			item(b":ABC", ArchiveStorageDiffType::Value, Some(b"child")),
+		];
+
+		assert_eq!(
+			deduplicate_items(items),
+			vec![
+				item(b":A", ArchiveStorageDiffType::Value, None),
+				item(b":AB", ArchiveStorageDiffType::Hash, None),
+				item(b":AB", ArchiveStorageDiffType::Value, Some(b"child")),
+			]
+		);
+	}
+}
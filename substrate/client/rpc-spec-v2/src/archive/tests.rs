@@ -16,20 +16,34 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{chain_head::hex_string, MethodResult};
+use crate::{
+	chain_head::hex_string,
+	common::events::{
+		ArchiveStorageDiffItem, ArchiveStorageDiffOperationType, ArchiveStorageDiffResult,
+		ArchiveStorageDiffResultItem, ArchiveStorageDiffType, ArchiveStorageMethodOk,
+		ArchiveStorageResult, PaginatedStorageQuery, StorageQueryType, StorageResult,
+		StorageResultType,
+	},
+	MethodResult,
+};
 
-use super::{archive::Archive, *};
+use super::{
+	archive::{Archive, ArchiveConfig},
+	*,
+};
 
 use assert_matches::assert_matches;
 use codec::{Decode, Encode};
 use jsonrpsee::{
 	core::error::Error,
+	rpc_params,
 	types::{error::CallError, EmptyServerParams as EmptyParams},
 	RpcModule,
 };
 use sc_block_builder::BlockBuilderBuilder;
 use sp_blockchain::HeaderBackend;
 use sp_consensus::BlockOrigin;
+use sp_core::{storage::ChildInfo, Blake2Hasher, Hasher};
 use sp_runtime::{
 	traits::{Block as BlockT, Header as HeaderT},
 	SaturatedConversion,
@@ -42,23 +56,47 @@ use substrate_test_runtime_client::{
 
 const CHAIN_GENESIS: [u8; 32] = [0; 32];
 const INVALID_HASH: [u8; 32] = [1; 32];
+const MAX_PAGINATION_LIMIT: usize = 5;
+const MAX_QUERIED_LIMIT: usize = 5;
+const KEY: &[u8] = b":mock";
+const VALUE: &[u8] = b"hello world";
+const CHILD_STORAGE_KEY: &[u8] = b"child";
+const CHILD_VALUE: &[u8] = b"child value";
 
 type Header = substrate_test_runtime_client::runtime::Header;
 type Block = substrate_test_runtime_client::runtime::Block;
 
-fn setup_api() -> (Arc<Client<Backend>>, RpcModule<Archive<Backend, Block, Client<Backend>>>) {
-	let builder = TestClientBuilder::new();
+fn setup_api(
+	max_descendant_responses: usize,
+	max_queried_items: usize,
+) -> (Arc<Client<Backend>>, RpcModule<Archive<Backend, Block, Client<Backend>>>) {
+	setup_api_with_config(ArchiveConfig {
+		max_descendant_responses,
+		max_queried_items,
+		..Default::default()
+	})
+}
+
+fn setup_api_with_config(
+	config: ArchiveConfig,
+) -> (Arc<Client<Backend>>, RpcModule<Archive<Backend, Block, Client<Backend>>>) {
+	let child_info = ChildInfo::new_default(CHILD_STORAGE_KEY);
+	let builder = TestClientBuilder::new().add_extra_child_storage(
+		&child_info,
+		KEY.to_vec(),
+		CHILD_VALUE.to_vec(),
+	);
 	let backend = builder.backend();
 	let client = Arc::new(builder.build());
 
-	let api = Archive::new(client.clone(), backend, CHAIN_GENESIS).into_rpc();
+	let api = Archive::new(client.clone(), backend, CHAIN_GENESIS, config).into_rpc();
 
 	(client, api)
 }
 
 #[tokio::test]
 async fn archive_genesis() {
-	let (_client, api) = setup_api();
+	let (_client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
 
 	let genesis: String =
 		api.call("archive_unstable_genesisHash", EmptyParams::new()).await.unwrap();
@@ -67,7 +105,7 @@ async fn archive_genesis() {
 
 #[tokio::test]
 async fn archive_body() {
-	let (mut client, api) = setup_api();
+	let (mut client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
 
 	// Invalid block hash.
 	let invalid_hash = hex_string(&INVALID_HASH);
@@ -101,7 +139,7 @@ async fn archive_body() {
 
 #[tokio::test]
 async fn archive_header() {
-	let (mut client, api) = setup_api();
+	let (mut client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
 
 	// Invalid block hash.
 	let invalid_hash = hex_string(&INVALID_HASH);
@@ -135,7 +173,7 @@ async fn archive_header() {
 
 #[tokio::test]
 async fn archive_finalized_height() {
-	let (client, api) = setup_api();
+	let (client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
 
 	let client_height: u32 = client.info().finalized_number.saturated_into();
 
@@ -147,7 +185,7 @@ async fn archive_finalized_height() {
 
 #[tokio::test]
 async fn archive_hash_by_height() {
-	let (mut client, api) = setup_api();
+	let (mut client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
 
 	// Genesis height.
 	let hashes: Vec<String> = api.call("archive_unstable_hashByHeight", [0]).await.unwrap();
@@ -253,7 +291,7 @@ async fn archive_hash_by_height() {
 
 #[tokio::test]
 async fn archive_call() {
-	let (mut client, api) = setup_api();
+	let (mut client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
 	let invalid_hash = hex_string(&INVALID_HASH);
 
 	// Invalid parameter (non-hex).
@@ -309,3 +347,467 @@ async fn archive_call() {
 	let expected = MethodResult::ok("0x0000000000000000");
 	assert_eq!(result, expected);
 }
+
+#[tokio::test]
+async fn archive_storage_hashes_values() {
+	let (mut client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
+
+	let block = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(client.chain_info().genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap()
+		.build()
+		.unwrap()
+		.block;
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(block.hash())
+		.with_parent_block_number(1)
+		.build()
+		.unwrap();
+	builder.push_storage_change(b":A".to_vec(), Some(b"B".to_vec())).unwrap();
+	builder.push_storage_change(b":AA".to_vec(), Some(b"BB".to_vec())).unwrap();
+	let block = builder.build().unwrap().block;
+	let block_hash = format!("{:?}", block.header.hash());
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	let key = hex_string(b":A");
+	let items: Vec<PaginatedStorageQuery<String>> = vec![
+		PaginatedStorageQuery {
+			key: key.clone(),
+			query_type: StorageQueryType::DescendantsHashes,
+			pagination_start_key: None,
+		},
+		PaginatedStorageQuery {
+			key: key.clone(),
+			query_type: StorageQueryType::DescendantsValues,
+			pagination_start_key: None,
+		},
+		PaginatedStorageQuery {
+			key: key.clone(),
+			query_type: StorageQueryType::Hash,
+			pagination_start_key: None,
+		},
+		PaginatedStorageQuery {
+			key: key.clone(),
+			query_type: StorageQueryType::Value,
+			pagination_start_key: None,
+		},
+	];
+
+	let result: ArchiveStorageResult = api
+		.call("archive_unstable_storage", rpc_params![&block_hash, items.clone()])
+		.await
+		.unwrap();
+
+	let value = hex_string(b"B");
+	// Hash of the value.
+	let hash = format!("{:?}", Blake2Hasher::hash(b"B"));
+	let expected_key = hex_string(b":AA");
+	let expected_value = hex_string(b"BB");
+	let expected_hash = format!("{:?}", Blake2Hasher::hash(b"BB"));
+
+	match result {
+		ArchiveStorageResult::Ok(ArchiveStorageMethodOk { result, discarded_items }) => {
+			assert_eq!(result.len(), 6);
+			assert_eq!(discarded_items, 0);
+
+			assert_eq!(result[0].key, key);
+			assert_eq!(result[0].result, StorageResultType::Hash(hash.clone()));
+			assert_eq!(result[1].key, expected_key);
+			assert_eq!(result[1].result, StorageResultType::Hash(expected_hash));
+			assert_eq!(result[2].key, key);
+			assert_eq!(result[2].result, StorageResultType::Value(value.clone()));
+			assert_eq!(result[3].key, expected_key);
+			assert_eq!(result[3].result, StorageResultType::Value(expected_value));
+			assert_eq!(result[4].key, key);
+			assert_eq!(result[4].result, StorageResultType::Hash(hash));
+			assert_eq!(result[5].key, key);
+			assert_eq!(result[5].result, StorageResultType::Value(value));
+		},
+		_ => panic!("Unexpected result"),
+	};
+
+	// Child trie queries report the child storage.
+	let child_info = hex_string(&CHILD_STORAGE_KEY);
+	let items = vec![PaginatedStorageQuery {
+		key: hex_string(&KEY),
+		query_type: StorageQueryType::Value,
+		pagination_start_key: None,
+	}];
+	let result: ArchiveStorageResult = api
+		.call("archive_unstable_storage", rpc_params![&block_hash, items, &child_info])
+		.await
+		.unwrap();
+	assert_eq!(
+		result,
+		ArchiveStorageResult::ok(
+			vec![StorageResult {
+				key: hex_string(&KEY),
+				result: StorageResultType::Value(hex_string(&CHILD_VALUE)),
+			}],
+			0
+		)
+	);
+}
+
+#[tokio::test]
+async fn archive_storage_paginate_iterations() {
+	// 1 iteration allowed before pagination kicks in.
+	let (mut client, api) = setup_api(1, MAX_QUERIED_LIMIT);
+
+	// Import a new block with storage changes.
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(client.chain_info().genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap();
+	builder.push_storage_change(b":m".to_vec(), Some(b"a".to_vec())).unwrap();
+	builder.push_storage_change(b":mo".to_vec(), Some(b"ab".to_vec())).unwrap();
+	builder.push_storage_change(b":moc".to_vec(), Some(b"abc".to_vec())).unwrap();
+	let block = builder.build().unwrap().block;
+	let block_hash = format!("{:?}", block.header.hash());
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	// Invalid pagination start key for a non-descendant query.
+	let err = api
+		.call::<_, serde_json::Value>(
+			"archive_unstable_storage",
+			rpc_params![
+				&block_hash,
+				vec![PaginatedStorageQuery {
+					key: hex_string(b":m"),
+					query_type: StorageQueryType::Value,
+					pagination_start_key: Some(hex_string(b":m")),
+				}]
+			],
+		)
+		.await
+		.unwrap_err();
+	assert_matches!(err, Error::Call(CallError::Custom(ref err)) if err.code() == 3001 && err.message().contains("Invalid parameter"));
+
+	// Iterate over the descendants one key at a time.
+	let mut pagination_start_key = None;
+	let expected: [(&[u8], &[u8]); 3] = [(b":m", b"a"), (b":mo", b"ab"), (b":moc", b"abc")];
+	for (key, value) in expected {
+		let result: ArchiveStorageResult = api
+			.call(
+				"archive_unstable_storage",
+				rpc_params![
+					&block_hash,
+					vec![PaginatedStorageQuery {
+						key: hex_string(b":m"),
+						query_type: StorageQueryType::DescendantsValues,
+						pagination_start_key: pagination_start_key.clone(),
+					}]
+				],
+			)
+			.await
+			.unwrap();
+
+		let expected_key = hex_string(&key);
+		assert_eq!(
+			result,
+			ArchiveStorageResult::ok(
+				vec![StorageResult {
+					key: expected_key.clone(),
+					result: StorageResultType::Value(hex_string(&value)),
+				}],
+				0
+			)
+		);
+		pagination_start_key = Some(expected_key);
+	}
+
+	// No more keys to iterate.
+	let result: ArchiveStorageResult = api
+		.call(
+			"archive_unstable_storage",
+			rpc_params![
+				&block_hash,
+				vec![PaginatedStorageQuery {
+					key: hex_string(b":m"),
+					query_type: StorageQueryType::DescendantsValues,
+					pagination_start_key,
+				}]
+			],
+		)
+		.await
+		.unwrap();
+	assert_eq!(result, ArchiveStorageResult::ok(vec![], 0));
+}
+
+#[tokio::test]
+async fn archive_storage_discarded_items() {
+	// One query at a time.
+	let (mut client, api) = setup_api(MAX_PAGINATION_LIMIT, 1);
+
+	// Import a new block with storage changes.
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(client.chain_info().genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap();
+	builder.push_storage_change(b":m".to_vec(), Some(b"a".to_vec())).unwrap();
+	let block = builder.build().unwrap().block;
+	let block_hash = format!("{:?}", block.header.hash());
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	// Valid call with storage at the key.
+	let result: ArchiveStorageResult = api
+		.call(
+			"archive_unstable_storage",
+			rpc_params![
+				&block_hash,
+				vec![
+					PaginatedStorageQuery {
+						key: hex_string(b":m"),
+						query_type: StorageQueryType::Value,
+						pagination_start_key: None,
+					},
+					PaginatedStorageQuery {
+						key: hex_string(b":m"),
+						query_type: StorageQueryType::Hash,
+						pagination_start_key: None,
+					},
+				]
+			],
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		result,
+		ArchiveStorageResult::ok(
+			vec![StorageResult {
+				key: hex_string(b":m"),
+				result: StorageResultType::Value(hex_string(b"a")),
+			}],
+			1
+		)
+	);
+}
+
+#[tokio::test]
+async fn archive_storage_diff_main_trie() {
+	let (mut client, api) = setup_api(MAX_PAGINATION_LIMIT, MAX_QUERIED_LIMIT);
+
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(client.chain_info().genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap();
+	builder.push_storage_change(b":A".to_vec(), Some(b"B".to_vec())).unwrap();
+	builder.push_storage_change(b":AA".to_vec(), Some(b"BB".to_vec())).unwrap();
+	builder.push_storage_change(b":AAA".to_vec(), Some(b"BBB".to_vec())).unwrap();
+	let prev_block = builder.build().unwrap().block;
+	let prev_hash = format!("{:?}", prev_block.header.hash());
+	client.import(BlockOrigin::Own, prev_block.clone()).await.unwrap();
+
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(prev_block.hash())
+		.with_parent_block_number(1)
+		.build()
+		.unwrap();
+	// Modified.
+	builder.push_storage_change(b":A".to_vec(), Some(b"11".to_vec())).unwrap();
+	// Unchanged value, not reported.
+	builder.push_storage_change(b":AA".to_vec(), Some(b"BB".to_vec())).unwrap();
+	// Deleted.
+	builder.push_storage_change(b":AAA".to_vec(), None).unwrap();
+	// Added.
+	builder.push_storage_change(b":AAB".to_vec(), Some(b"22".to_vec())).unwrap();
+	let block = builder.build().unwrap().block;
+	let block_hash = format!("{:?}", block.header.hash());
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	let expected = ArchiveStorageDiffResult::ok(
+		vec![
+			ArchiveStorageDiffResultItem {
+				key: hex_string(b":A"),
+				result: StorageResultType::Value(hex_string(b"11")),
+				operation_type: ArchiveStorageDiffOperationType::Modified,
+				child_trie_key: None,
+			},
+			ArchiveStorageDiffResultItem {
+				key: hex_string(b":AAA"),
+				result: StorageResultType::Value(hex_string(b"BBB")),
+				operation_type: ArchiveStorageDiffOperationType::Deleted,
+				child_trie_key: None,
+			},
+			ArchiveStorageDiffResultItem {
+				key: hex_string(b":AAB"),
+				result: StorageResultType::Value(hex_string(b"22")),
+				operation_type: ArchiveStorageDiffOperationType::Added,
+				child_trie_key: None,
+			},
+		],
+		Vec::new(),
+	);
+
+	// The overlapping `:AA` item is covered by the `:A` item.
+	let items = vec![
+		ArchiveStorageDiffItem {
+			key: hex_string(b":A"),
+			return_type: ArchiveStorageDiffType::Value,
+			child_trie_key: None,
+			pagination_start_key: None,
+		},
+		ArchiveStorageDiffItem {
+			key: hex_string(b":AA"),
+			return_type: ArchiveStorageDiffType::Value,
+			child_trie_key: None,
+			pagination_start_key: None,
+		},
+	];
+
+	// The previous hash defaults to the parent block.
+	let result: ArchiveStorageDiffResult = api
+		.call("archive_unstable_storageDiff", rpc_params![&block_hash, items.clone()])
+		.await
+		.unwrap();
+	assert_eq!(result, expected);
+
+	let result: ArchiveStorageDiffResult = api
+		.call("archive_unstable_storageDiff", rpc_params![&block_hash, items, &prev_hash])
+		.await
+		.unwrap();
+	assert_eq!(result, expected);
+
+	// Hashes are reported when requested.
+	let items = vec![ArchiveStorageDiffItem {
+		key: hex_string(b":AAB"),
+		return_type: ArchiveStorageDiffType::Hash,
+		child_trie_key: None,
+		pagination_start_key: None,
+	}];
+	let result: ArchiveStorageDiffResult = api
+		.call("archive_unstable_storageDiff", rpc_params![&block_hash, items])
+		.await
+		.unwrap();
+	assert_eq!(
+		result,
+		ArchiveStorageDiffResult::ok(
+			vec![ArchiveStorageDiffResultItem {
+				key: hex_string(b":AAB"),
+				result: StorageResultType::Hash(format!("{:?}", Blake2Hasher::hash(b"22"))),
+				operation_type: ArchiveStorageDiffOperationType::Added,
+				child_trie_key: None,
+			}],
+			Vec::new()
+		)
+	);
+
+	// Unknown blocks produce an error result.
+	let invalid_hash = hex_string(&INVALID_HASH);
+	let result: ArchiveStorageDiffResult = api
+		.call(
+			"archive_unstable_storageDiff",
+			rpc_params![&invalid_hash, Vec::<ArchiveStorageDiffItem<String>>::new()],
+		)
+		.await
+		.unwrap();
+	assert_matches!(result, ArchiveStorageDiffResult::Err(_));
+}
+
+#[tokio::test]
+async fn archive_storage_diff_pagination() {
+	let (mut client, api) = setup_api_with_config(ArchiveConfig {
+		max_diff_reported_items: 2,
+		max_diff_compared_keys: 4,
+		..Default::default()
+	});
+
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(client.chain_info().genesis_hash)
+		.with_parent_block_number(0)
+		.build()
+		.unwrap();
+	builder.push_storage_change(b":A0".to_vec(), Some(b"0".to_vec())).unwrap();
+	builder.push_storage_change(b":A1".to_vec(), Some(b"1".to_vec())).unwrap();
+	builder.push_storage_change(b":A2".to_vec(), Some(b"2".to_vec())).unwrap();
+	builder.push_storage_change(b":A3".to_vec(), Some(b"3".to_vec())).unwrap();
+	builder.push_storage_change(b":A4".to_vec(), Some(b"4".to_vec())).unwrap();
+	let block = builder.build().unwrap().block;
+	let block_hash = format!("{:?}", block.header.hash());
+	client.import(BlockOrigin::Own, block.clone()).await.unwrap();
+
+	let added = |key: &[u8], value: &[u8]| ArchiveStorageDiffResultItem {
+		key: hex_string(&key),
+		result: StorageResultType::Value(hex_string(&value)),
+		operation_type: ArchiveStorageDiffOperationType::Added,
+		child_trie_key: None,
+	};
+	let item = |pagination_start_key: Option<&[u8]>| ArchiveStorageDiffItem {
+		key: hex_string(b":A"),
+		return_type: ArchiveStorageDiffType::Value,
+		child_trie_key: None,
+		pagination_start_key: pagination_start_key.map(|key| hex_string(&key)),
+	};
+	let unrelated_item = ArchiveStorageDiffItem {
+		key: hex_string(b":B"),
+		return_type: ArchiveStorageDiffType::Value,
+		child_trie_key: None,
+		pagination_start_key: None,
+	};
+
+	// The number of reported differences is limited, the items that were not fully compared are
+	// returned with the key to resume from.
+	let result: ArchiveStorageDiffResult = api
+		.call(
+			"archive_unstable_storageDiff",
+			rpc_params![&block_hash, vec![item(None), unrelated_item.clone()]],
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		result,
+		ArchiveStorageDiffResult::ok(
+			vec![added(b":A0", b"0"), added(b":A1", b"1")],
+			vec![item(Some(b":A1")), unrelated_item.clone()],
+		)
+	);
+
+	// Resuming the comparison reports the remaining differences.
+	let result: ArchiveStorageDiffResult = api
+		.call(
+			"archive_unstable_storageDiff",
+			rpc_params![&block_hash, vec![item(Some(b":A1")), unrelated_item.clone()]],
+		)
+		.await
+		.unwrap();
+	assert_eq!(
+		result,
+		ArchiveStorageDiffResult::ok(
+			vec![added(b":A2", b"2"), added(b":A3", b"3")],
+			vec![item(Some(b":A3")), unrelated_item.clone()],
+		)
+	);
+
+	let result: ArchiveStorageDiffResult = api
+		.call(
+			"archive_unstable_storageDiff",
+			rpc_params![&block_hash, vec![item(Some(b":A3")), unrelated_item.clone()]],
+		)
+		.await
+		.unwrap();
+	assert_eq!(result, ArchiveStorageDiffResult::ok(vec![added(b":A4", b"4")], Vec::new()));
+
+	// Keys without differences count towards the compared keys.
+	let mut builder = BlockBuilderBuilder::new(&*client)
+		.on_parent_block(block.hash())
+		.with_parent_block_number(1)
+		.build()
+		.unwrap();
+	builder.push_storage_change(b":A4".to_vec(), Some(b"44".to_vec())).unwrap();
+	let next_block = builder.build().unwrap().block;
+	let next_block_hash = format!("{:?}", next_block.header.hash());
+	client.import(BlockOrigin::Own, next_block.clone()).await.unwrap();
+
+	let result: ArchiveStorageDiffResult = api
+		.call("archive_unstable_storageDiff", rpc_params![&next_block_hash, vec![item(None)]])
+		.await
+		.unwrap();
+	assert_eq!(result, ArchiveStorageDiffResult::ok(Vec::new(), vec![item(Some(b":A3"))]));
+}
@@ -28,6 +28,7 @@ use serde::{Deserialize, Serialize};
 pub mod archive;
 pub mod chain_head;
 pub mod chain_spec;
+pub mod common;
 pub mod transaction;
 
 /// Task executor that is being used by RPC subscriptions.
@@ -19,7 +19,7 @@
 //! API trait for transactions.
 
 use crate::transaction::event::TransactionEvent;
-use jsonrpsee::proc_macros::rpc;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use sp_core::Bytes;
 
 #[rpc(client, server)]
@@ -35,3 +35,33 @@ pub trait TransactionApi<Hash: Clone> {
 	)]
 	fn submit_and_watch(&self, bytes: Bytes);
 }
+
+#[rpc(client, server)]
+pub trait TransactionBroadcastApi {
+	/// Broadcast an extrinsic to the chain.
+	///
+	/// The extrinsic is submitted to the transaction pool and re-submitted whenever the
+	/// pool drops it, such that it keeps being propagated to peers until it is finalized
+	/// or the operation is stopped.
+	///
+	/// Returns an operation ID that can be provided to `transaction_unstable_stop`, or
+	/// `null` if the server cannot handle more broadcasts at the moment.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "transaction_unstable_broadcast")]
+	fn broadcast(&self, bytes: Bytes) -> RpcResult<Option<String>>;
+
+	/// Stop broadcasting an extrinsic previously provided to `transaction_unstable_broadcast`.
+	///
+	/// Returns `null` once the operation is stopped, or an invalid params error if the
+	/// operation ID is unknown, which includes operations that were already stopped or
+	/// that finished on their own.
+	///
+	/// # Unstable
+	///
+	/// This method is unstable and subject to change in the future.
+	#[method(name = "transaction_unstable_stop")]
+	fn stop_broadcast(&self, operation_id: String) -> RpcResult<()>;
+}
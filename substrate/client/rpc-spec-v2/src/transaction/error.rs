@@ -20,7 +20,14 @@
 //!
 //! Errors are interpreted as transaction events for subscriptions.
 
-use crate::transaction::event::{TransactionError, TransactionEvent};
+use crate::{
+	chain_head::error::json_rpc_spec,
+	transaction::event::{TransactionError, TransactionEvent},
+};
+use jsonrpsee::{
+	core::Error as RpcError,
+	types::error::{CallError, ErrorObject},
+};
 use sc_transaction_pool_api::error::Error as PoolError;
 use sp_runtime::transaction_validity::InvalidTransaction;
 
@@ -95,6 +102,35 @@ impl<Hash> From<Error> for TransactionEvent<Hash> {
 				TransactionEvent::Invalid(TransactionError {
 					error: "The pool is not accepting future transactions".into(),
 				}),
+			Error::Pool(PoolError::SenderLimitReached) =>
+				TransactionEvent::Invalid(TransactionError {
+					error: "The sender has too many transactions in the pool".into(),
+				}),
+		}
+	}
+}
+
+/// TransactionBroadcast error.
+#[derive(Debug, thiserror::Error)]
+pub enum ErrorBroadcast {
+	/// The provided operation ID is invalid.
+	#[error("Invalid operation id")]
+	InvalidOperationID,
+}
+
+impl From<ErrorBroadcast> for ErrorObject<'static> {
+	fn from(e: ErrorBroadcast) -> Self {
+		let msg = e.to_string();
+
+		match e {
+			ErrorBroadcast::InvalidOperationID =>
+				ErrorObject::owned(json_rpc_spec::INVALID_PARAM_ERROR, msg, None::<()>),
 		}
 	}
 }
+
+impl From<ErrorBroadcast> for RpcError {
+	fn from(e: ErrorBroadcast) -> Self {
+		CallError::Custom(e.into()).into()
+	}
+}
@@ -19,20 +19,26 @@
 //! Substrate transaction API.
 //!
 //! The transaction methods allow submitting a transaction and subscribing to
-//! its status updates generated by the chain.
+//! its status updates generated by the chain, or broadcasting a transaction to
+//! the peers of the node until it is finalized.
 //!
 //! # Note
 //!
 //! Methods are prefixed by `transaction`.
 
+#[cfg(test)]
+mod tests;
+
 pub mod api;
 pub mod error;
 pub mod event;
 pub mod transaction;
+pub mod transaction_broadcast;
 
-pub use api::TransactionApiServer;
+pub use api::{TransactionApiServer, TransactionBroadcastApiServer};
 pub use event::{
 	TransactionBlock, TransactionBroadcasted, TransactionDropped, TransactionError,
 	TransactionEvent,
 };
 pub use transaction::Transaction;
+pub use transaction_broadcast::TransactionBroadcast;
@@ -0,0 +1,130 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::chain_head::{error::json_rpc_spec, hex_string};
+use assert_matches::assert_matches;
+use codec::Encode;
+use futures::StreamExt;
+use jsonrpsee::{core::error::Error, rpc_params, types::error::CallError, RpcModule};
+use sc_transaction_pool::{BasicPool, FullChainApi};
+use sc_transaction_pool_api::TransactionPool;
+use sp_core::testing::TaskExecutor;
+use std::sync::Arc;
+use substrate_test_runtime_client::{
+	prelude::*,
+	runtime::{Block, Extrinsic, ExtrinsicBuilder, Transfer},
+	Backend, Client,
+};
+
+type FullTransactionPool = BasicPool<FullChainApi<Client<Backend>, Block>, Block>;
+
+fn setup_api(
+) -> (Arc<FullTransactionPool>, RpcModule<TransactionBroadcast<FullTransactionPool, Client<Backend>>>)
+{
+	let client = Arc::new(TestClientBuilder::new().build());
+	let spawner = TaskExecutor::new();
+	let pool =
+		BasicPool::new_full(Default::default(), true.into(), None, spawner.clone(), client.clone());
+
+	let api = TransactionBroadcast::new(client, pool.clone(), Arc::new(spawner)).into_rpc();
+
+	(pool, api)
+}
+
+fn uxt(sender: AccountKeyring, nonce: u64) -> Extrinsic {
+	let tx = Transfer {
+		amount: Default::default(),
+		nonce,
+		from: sender.into(),
+		to: AccountKeyring::Bob.into(),
+	};
+	ExtrinsicBuilder::new_transfer(tx).build()
+}
+
+#[tokio::test]
+async fn tx_broadcast_enters_pool() {
+	let (pool, api) = setup_api();
+	let mut import_stream = pool.import_notification_stream();
+
+	let xt = hex_string(&uxt(AccountKeyring::Alice, 0).encode());
+	let operation_id: Option<String> =
+		api.call("transaction_unstable_broadcast", rpc_params![&xt]).await.unwrap();
+	let operation_id = operation_id.expect("Server is not overloaded; qed");
+
+	// The transaction is submitted to the pool, which propagates it to peers.
+	let tx_hash = import_stream.next().await.unwrap();
+	assert_eq!(pool.status().ready, 1);
+	assert!(pool.ready_transaction(&tx_hash).is_some());
+
+	// Stop the broadcast operation.
+	let _: () = api.call("transaction_unstable_stop", rpc_params![&operation_id]).await.unwrap();
+}
+
+#[tokio::test]
+async fn tx_broadcast_stop_twice() {
+	let (pool, api) = setup_api();
+	let mut import_stream = pool.import_notification_stream();
+
+	let xt = hex_string(&uxt(AccountKeyring::Alice, 0).encode());
+	let operation_id: Option<String> =
+		api.call("transaction_unstable_broadcast", rpc_params![&xt]).await.unwrap();
+	let operation_id = operation_id.expect("Server is not overloaded; qed");
+	import_stream.next().await.unwrap();
+
+	// The first call stops the operation and returns `null`.
+	let result: serde_json::Value =
+		api.call("transaction_unstable_stop", rpc_params![&operation_id]).await.unwrap();
+	assert_eq!(result, serde_json::Value::Null);
+
+	// The operation ID is no longer valid.
+	let err = api
+		.call::<_, serde_json::Value>("transaction_unstable_stop", rpc_params![&operation_id])
+		.await
+		.unwrap_err();
+	assert_matches!(err,
+		Error::Call(CallError::Custom(ref err)) if err.code() == json_rpc_spec::INVALID_PARAM_ERROR && err.message() == "Invalid operation id"
+	);
+}
+
+#[tokio::test]
+async fn tx_broadcast_invalid_tx() {
+	let (pool, api) = setup_api();
+
+	// Invalid parameters.
+	let operation_id: Option<String> =
+		api.call("transaction_unstable_broadcast", rpc_params![&"0x00"]).await.unwrap();
+	assert!(operation_id.is_none());
+	assert_eq!(pool.status().ready, 0);
+}
+
+#[tokio::test]
+async fn tx_stop_with_invalid_operation_id() {
+	let (_pool, api) = setup_api();
+
+	let err = api
+		.call::<_, serde_json::Value>(
+			"transaction_unstable_stop",
+			rpc_params![&"invalid_operation_id"],
+		)
+		.await
+		.unwrap_err();
+	assert_matches!(err,
+		Error::Call(CallError::Custom(ref err)) if err.code() == json_rpc_spec::INVALID_PARAM_ERROR && err.message() == "Invalid operation id"
+	);
+}
@@ -0,0 +1,221 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! API implementation for broadcasting transactions.
+
+use crate::{
+	transaction::{api::TransactionBroadcastApiServer, error::ErrorBroadcast},
+	SubscriptionTaskExecutor,
+};
+use codec::Decode;
+use futures::{
+	future::{abortable, AbortHandle},
+	FutureExt, Stream, StreamExt,
+};
+use jsonrpsee::core::{async_trait, RpcResult};
+use parking_lot::RwLock;
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use sc_client_api::BlockchainEvents;
+use sc_transaction_pool_api::{
+	error::IntoPoolError, TransactionFor, TransactionPool, TransactionSource,
+};
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::traits::Block as BlockT;
+use std::{collections::HashMap, sync::Arc};
+
+/// The maximum number of broadcast operations tracked at the same time.
+///
+/// Past this limit `transaction_unstable_broadcast` returns `null`.
+const MAX_ONGOING_BROADCASTS: usize = 512;
+
+/// The length of the randomly generated operation IDs.
+const OPERATION_ID_LEN: usize = 16;
+
+/// An API for transaction RPC calls.
+///
+/// Broadcast operations are tracked in a table owned by this instance, keyed by their
+/// randomly generated operation IDs. The RPC server does not expose the connection of a
+/// method call, so the table cannot be split per connection: the operation IDs are
+/// unguessable instead, such that only the caller that started an operation can stop it.
+/// An operation lives until its transaction is finalized, it becomes permanently invalid,
+/// or it is explicitly stopped.
+pub struct TransactionBroadcast<Pool, Client> {
+	/// Substrate client.
+	client: Arc<Client>,
+	/// Transactions pool.
+	pool: Arc<Pool>,
+	/// Executor to spawn subscriptions.
+	executor: SubscriptionTaskExecutor,
+	/// The broadcast operation IDs.
+	broadcast_ids: Arc<RwLock<HashMap<String, BroadcastState>>>,
+}
+
+/// The state of a broadcast operation.
+struct BroadcastState {
+	/// Handle to abort the running future that broadcasts the transaction.
+	handle: AbortHandle,
+}
+
+impl<Pool, Client> TransactionBroadcast<Pool, Client> {
+	/// Creates a new [`TransactionBroadcast`].
+	pub fn new(client: Arc<Client>, pool: Arc<Pool>, executor: SubscriptionTaskExecutor) -> Self {
+		TransactionBroadcast { client, pool, executor, broadcast_ids: Default::default() }
+	}
+
+	/// Generate an unique operation ID for the `transaction_broadcast` RPC method.
+	///
+	/// Operation IDs are random to prevent other RPC users from guessing them.
+	fn generate_unique_id(broadcast_ids: &HashMap<String, BroadcastState>) -> String {
+		loop {
+			let id: String = thread_rng()
+				.sample_iter(&Alphanumeric)
+				.take(OPERATION_ID_LEN)
+				.map(char::from)
+				.collect();
+
+			if !broadcast_ids.contains_key(&id) {
+				return id
+			}
+		}
+	}
+}
+
+/// Currently we treat all RPC transactions as externals.
+///
+/// Possibly in the future we could allow opt-in for special treatment
+/// of such transactions, so that the block authors can inject
+/// some unique transactions via RPC and have them included in the pool.
+const TX_SOURCE: TransactionSource = TransactionSource::External;
+
+#[async_trait]
+impl<Pool, Client> TransactionBroadcastApiServer for TransactionBroadcast<Pool, Client>
+where
+	Pool: TransactionPool + Sync + Send + 'static,
+	<Pool::Block as BlockT>::Hash: Unpin,
+	Client: HeaderBackend<Pool::Block> + BlockchainEvents<Pool::Block> + Send + Sync + 'static,
+{
+	fn broadcast(&self, bytes: Bytes) -> RpcResult<Option<String>> {
+		// There is nothing we could do with an extrinsic of invalid format.
+		let Ok(decoded_extrinsic) = TransactionFor::<Pool>::decode(&mut &bytes[..]) else {
+			return Ok(None)
+		};
+
+		let pool = self.pool.clone();
+		let mut best_block_hash = self.client.info().best_hash;
+		// Subscribe before the first submission to not miss any new best block.
+		let mut best_block_import_stream =
+			Box::pin(self.client.import_notification_stream().filter_map(
+				|notification| async move { notification.is_new_best.then_some(notification.hash) },
+			));
+
+		let broadcast_transaction_fut = async move {
+			loop {
+				match pool
+					.submit_and_watch(best_block_hash, TX_SOURCE, decoded_extrinsic.clone())
+					.await
+				{
+					Ok(mut stream) =>
+						while let Some(event) = stream.next().await {
+							// The transaction could be submitted again at a later time.
+							if event.is_retriable() {
+								break
+							}
+
+							// Stop if this is the final event of the transaction stream.
+							if event.is_final() {
+								return
+							}
+						},
+					Err(error) => match error.into_pool_error() {
+						// Allow the transaction to be resubmitted in the future.
+						Ok(pool_error) if pool_error.is_retriable() => {},
+						// The transaction is invalid.
+						_ => return,
+					},
+				}
+
+				// Wait for the latest best block before submitting the transaction again.
+				let Some(hash) = last_stream_element(&mut best_block_import_stream).await else {
+					return
+				};
+				best_block_hash = hash;
+			}
+		};
+
+		// Convert the future into an abortable future, for easily terminating it from the
+		// `transaction_stop` method.
+		let (fut, handle) = abortable(broadcast_transaction_fut);
+
+		let id = {
+			let mut broadcast_ids = self.broadcast_ids.write();
+			if broadcast_ids.len() >= MAX_ONGOING_BROADCASTS {
+				return Ok(None)
+			}
+
+			let id = Self::generate_unique_id(&broadcast_ids);
+			broadcast_ids.insert(id.clone(), BroadcastState { handle });
+			id
+		};
+
+		let broadcast_ids = self.broadcast_ids.clone();
+		let drop_id = id.clone();
+		// The future expected by the executor must be `Future<Output = ()>` instead of
+		// `Future<Output = Result<(), Aborted>>`.
+		let fut = fut.map(move |_| {
+			// Remove the entry from the broadcast IDs map.
+			broadcast_ids.write().remove(&drop_id);
+		});
+
+		self.executor
+			.spawn("substrate-rpc-transaction-broadcast", Some("rpc"), fut.boxed());
+
+		Ok(Some(id))
+	}
+
+	fn stop_broadcast(&self, operation_id: String) -> RpcResult<()> {
+		let Some(broadcast_state) = self.broadcast_ids.write().remove(&operation_id) else {
+			return Err(ErrorBroadcast::InvalidOperationID.into())
+		};
+
+		broadcast_state.handle.abort();
+
+		Ok(())
+	}
+}
+
+/// Returns the last element of the provided stream, or `None` if the stream is closed.
+///
+/// Waits for at least one element to become available.
+async fn last_stream_element<S>(stream: &mut S) -> Option<S::Item>
+where
+	S: Stream + Unpin,
+{
+	let mut element = stream.next().await?;
+
+	// Drain the elements that are already available, the `now_or_never` returns
+	// `None` if the stream is `Pending`.
+	//
+	// If the stream contains `Hash0x1 Hash0x2 Hash0x3 Hash0x4`, we want only `Hash0x4`.
+	while let Some(next) = stream.next().now_or_never() {
+		// Nothing to do if the stream terminated.
+		element = next?;
+	}
+
+	Some(element)
+}
@@ -30,10 +30,11 @@ use sc_rpc_api::DenyUnsafe;
 use sc_tracing::logging;
 use sc_utils::mpsc::TracingUnboundedSender;
 use sp_runtime::traits::{self, Header as HeaderT};
+use std::time::Duration;
 
 use self::error::Result;
 
-pub use self::helpers::{Health, NodeRole, PeerInfo, SyncState, SystemInfo};
+pub use self::helpers::{Health, LogFilter, NodeRole, PeerInfo, SyncState, SystemInfo};
 pub use sc_rpc_api::system::*;
 
 /// System API implementation
@@ -191,6 +192,30 @@ impl<B: traits::Block> SystemApiServer<B::Hash, <B::Header as HeaderT>::Number>
 		})
 	}
 
+	fn system_add_temporary_log_filter(&self, directives: String, duration: u64) -> RpcResult<()> {
+		self.deny_unsafe.check_if_safe()?;
+
+		logging::add_temporary_directives(&directives, Duration::from_secs(duration)).map_err(|e| {
+			JsonRpseeError::Call(CallError::Custom(ErrorObject::owned(
+				ErrorCode::InternalError.code(),
+				e,
+				None::<()>,
+			)))
+		})
+	}
+
+	fn system_log_filters(&self) -> RpcResult<Vec<LogFilter>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		Ok(logging::current_directives()
+			.into_iter()
+			.map(|d| LogFilter {
+				directive: d.directive,
+				expires_in: d.expires_in.map(|expires_in| expires_in.as_secs()),
+			})
+			.collect())
+	}
+
 	fn system_reset_log_filter(&self) -> RpcResult<()> {
 		self.deny_unsafe.check_if_safe()?;
 		logging::reset_log_filter().map_err(|e| {
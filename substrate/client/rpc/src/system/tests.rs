@@ -362,6 +362,12 @@ fn test_add_reset_log_filter() {
 				let fut =
 					async move { api(None).call::<_, ()>("system_addLogFilter", [filter]).await };
 				futures::executor::block_on(fut).expect("`system_addLogFilter (trace)` failed");
+			} else if line.contains("add_temporary") {
+				let params = ("test_after_add", 1u64);
+				let fut = async move {
+					api(None).call::<_, ()>("system_addTemporaryLogFilter", params).await
+				};
+				futures::executor::block_on(fut).expect("`system_addTemporaryLogFilter` failed");
 			} else if line.contains("reset") {
 				let fut = async move {
 					api(None).call::<_, ()>("system_resetLogFilter", EmptyParams::new()).await
@@ -415,6 +421,15 @@ fn test_add_reset_log_filter() {
 	child_in.write_all(b"reset\n").unwrap();
 	assert!(read_line().contains(EXPECTED_BEFORE_ADD));
 
+	// Add a directive that is removed again after a second
+	child_in.write_all(b"add_temporary\n").unwrap();
+	assert!(read_line().contains(EXPECTED_BEFORE_ADD));
+	assert!(read_line().contains(EXPECTED_AFTER_ADD));
+
+	thread::sleep(std::time::Duration::from_secs(2));
+	child_in.write_all(b"\n").unwrap();
+	assert!(read_line().contains(EXPECTED_BEFORE_ADD));
+
 	// Return from child process
 	child_in.write_all(b"exit\n").unwrap();
 	assert!(child_process.wait().expect("Error waiting for child process").success());
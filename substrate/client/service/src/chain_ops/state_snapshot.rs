@@ -0,0 +1,398 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! State snapshots, allowing to bootstrap a node from a trusted state without syncing it.
+//!
+//! A snapshot contains the full state at a finalized block and the header of this block together
+//! with some of its ancestors. It is stored as [`STATE_SNAPSHOT_MAGIC`], followed by the SCALE
+//! encoded [`STATE_SNAPSHOT_VERSION`] and the SCALE encoded [`StateSnapshot`].
+//!
+//! Justifications are not part of a snapshot: the snapshot block is trusted through the hash of
+//! a trusted block its header chain must contain, and its state through the state root.
+
+use crate::{chain_ops::export_raw_state, error::Error};
+use codec::{Compact, Decode, Encode, Input, IoReader as CodecIoReader, Output};
+use futures::{future, prelude::*};
+use log::{info, warn};
+use sc_client_api::{HeaderBackend, StorageProvider, UsageProvider};
+use sc_consensus::{
+	import_queue::{BlockImportError, BlockImportStatus, ImportQueue, IncomingBlock, Link},
+	ImportedState,
+};
+use sp_consensus::BlockOrigin;
+use sp_core::storage::StorageKey;
+use sp_runtime::traits::{Block as BlockT, Header, NumberFor, Zero};
+use sp_state_machine::{KeyValueStates, KeyValueStorageLevel};
+use std::{
+	io::{self, Read, Write},
+	pin::Pin,
+	sync::Arc,
+};
+
+/// Magic bytes every state snapshot starts with.
+pub const STATE_SNAPSHOT_MAGIC: [u8; 8] = *b"substate";
+
+/// The current version of the state snapshot format.
+pub const STATE_SNAPSHOT_VERSION: u32 = 2;
+
+/// A default child trie stored in a [`StateSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct SnapshotChildTrie {
+	/// The unprefixed storage key of the child trie.
+	pub storage_key: Vec<u8>,
+	/// The root of the child trie, as stored in the top trie.
+	pub root: Vec<u8>,
+	/// The key value pairs of the child trie.
+	pub key_values: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+/// The state of a finalized block, together with the headers required to trust it.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct StateSnapshot<B: BlockT> {
+	/// The header of the snapshot block, preceded by its ancestors, ordered from the oldest.
+	pub headers: Vec<B::Header>,
+	/// The key value pairs of the top trie, without the default child trie roots.
+	pub top: Vec<(Vec<u8>, Vec<u8>)>,
+	/// The default child tries.
+	pub children: Vec<SnapshotChildTrie>,
+}
+
+impl<B: BlockT> StateSnapshot<B> {
+	/// The header of the snapshot block.
+	pub fn header(&self) -> Option<&B::Header> {
+		self.headers.last()
+	}
+
+	/// Writes the snapshot, prefixed by the magic bytes and the format version.
+	///
+	/// The snapshot is encoded straight into `output`, without buffering it.
+	pub fn write_to(&self, output: &mut impl Write) -> Result<(), Error> {
+		let mut output = IoOutput { inner: output, error: None };
+		STATE_SNAPSHOT_MAGIC.encode_to(&mut output);
+		STATE_SNAPSHOT_VERSION.encode_to(&mut output);
+		self.encode_to(&mut output);
+		match output.error {
+			Some(e) => Err(e.into()),
+			None => output.inner.flush().map_err(Into::into),
+		}
+	}
+
+	/// Reads a snapshot written by [`Self::write_to`].
+	///
+	/// The key value pairs are decoded one by one, so the announced lengths are never trusted
+	/// for allocations.
+	pub fn read_from(input: impl Read) -> Result<Self, Error> {
+		let mut input = CodecIoReader(input);
+		let decode_err = |e: codec::Error| Error::Other(format!("Invalid state snapshot: {e}"));
+
+		let magic = <[u8; 8]>::decode(&mut input).map_err(decode_err)?;
+		if magic != STATE_SNAPSHOT_MAGIC {
+			return Err(Error::Other("Input is not a state snapshot".into()))
+		}
+		let version = u32::decode(&mut input).map_err(decode_err)?;
+		if version != STATE_SNAPSHOT_VERSION {
+			return Err(Error::Other(format!(
+				"Unsupported state snapshot version {version}, expected {STATE_SNAPSHOT_VERSION}"
+			)))
+		}
+
+		let headers = Vec::<B::Header>::decode(&mut input).map_err(decode_err)?;
+		let top = decode_key_values(&mut input).map_err(decode_err)?;
+		let mut children = Vec::new();
+		for _ in 0..Compact::<u32>::decode(&mut input).map_err(decode_err)?.0 {
+			children.push(SnapshotChildTrie {
+				storage_key: Vec::decode(&mut input).map_err(decode_err)?,
+				root: Vec::decode(&mut input).map_err(decode_err)?,
+				key_values: decode_key_values(&mut input).map_err(decode_err)?,
+			});
+		}
+		Ok(Self { headers, top, children })
+	}
+
+	/// Checks that the headers form a chain and that the chain contains `trusted_hash`, if any.
+	///
+	/// The state itself is checked against the state root of the snapshot block on import.
+	pub fn verify_headers(&self, trusted_hash: Option<B::Hash>) -> Result<(), Error> {
+		if self.headers.is_empty() {
+			return Err(Error::Other("State snapshot contains no header".into()))
+		}
+
+		for pair in self.headers.windows(2) {
+			if *pair[1].parent_hash() != pair[0].hash() {
+				return Err(Error::Other(format!(
+					"State snapshot header #{} is not the parent of header #{}",
+					pair[0].number(),
+					pair[1].number(),
+				)))
+			}
+		}
+
+		match trusted_hash {
+			Some(trusted) if !self.headers.iter().any(|header| header.hash() == trusted) => Err(
+				Error::Other(format!("State snapshot does not contain trusted block {trusted}")),
+			),
+			_ => Ok(()),
+		}
+	}
+
+	fn into_incoming_block(self) -> Option<IncomingBlock<B>> {
+		let header = self.headers.into_iter().last()?;
+		let hash = header.hash();
+
+		let mut levels = vec![KeyValueStorageLevel {
+			state_root: Vec::new(),
+			parent_storage_keys: Vec::new(),
+			key_values: self.top,
+		}];
+		levels.extend(self.children.into_iter().map(|child| {
+			let child_info = sp_core::storage::ChildInfo::new_default(&child.storage_key);
+			KeyValueStorageLevel {
+				state_root: child.root,
+				parent_storage_keys: vec![child_info.prefixed_storage_key().into_inner()],
+				key_values: child.key_values,
+			}
+		}));
+
+		Some(IncomingBlock {
+			hash,
+			header: Some(header),
+			body: None,
+			indexed_body: None,
+			justifications: None,
+			origin: None,
+			allow_missing_state: true,
+			import_existing: true,
+			skip_execution: true,
+			state: Some(ImportedState { block: hash, state: KeyValueStates(levels) }),
+		})
+	}
+}
+
+/// Adapts a [`Write`] to an [`Output`], keeping the first error instead of panicking on it.
+struct IoOutput<W> {
+	inner: W,
+	error: Option<io::Error>,
+}
+
+impl<W: Write> Output for IoOutput<W> {
+	fn write(&mut self, bytes: &[u8]) {
+		if self.error.is_none() {
+			self.error = self.inner.write_all(bytes).err();
+		}
+	}
+}
+
+fn decode_key_values(input: &mut impl Input) -> Result<Vec<(Vec<u8>, Vec<u8>)>, codec::Error> {
+	let len = Compact::<u32>::decode(input)?.0;
+	let mut key_values = Vec::new();
+	for _ in 0..len {
+		key_values.push(<(Vec<u8>, Vec<u8>)>::decode(input)?);
+	}
+	Ok(key_values)
+}
+
+/// Exports the state snapshot of the finalized block `hash`, including `ancestry` ancestors of
+/// its header.
+pub fn export_state_snapshot<B, BA, C>(
+	client: Arc<C>,
+	hash: B::Hash,
+	ancestry: u32,
+) -> Result<StateSnapshot<B>, Error>
+where
+	C: UsageProvider<B> + StorageProvider<B, BA> + HeaderBackend<B>,
+	B: BlockT,
+	BA: sc_client_api::backend::Backend<B>,
+{
+	let header = client.expect_header(hash)?;
+	let number = *header.number();
+	if number > client.info().finalized_number || client.hash(number)? != Some(hash) {
+		return Err(Error::Other(format!("Block {hash} is not finalized")))
+	}
+
+	let mut headers = vec![header];
+	for _ in 0..ancestry {
+		let oldest = headers.last().expect("headers is never empty; qed");
+		if oldest.number().is_zero() {
+			break
+		}
+		headers.push(client.expect_header(*oldest.parent_hash())?);
+	}
+	headers.reverse();
+
+	let storage = export_raw_state(client.clone(), hash)?;
+	let mut children = Vec::with_capacity(storage.children_default.len());
+	for (storage_key, child) in storage.children_default {
+		let root_key = StorageKey(child.child_info.prefixed_storage_key().into_inner());
+		let root = client
+			.storage(hash, &root_key)?
+			.ok_or_else(|| Error::Other(format!("Missing root of child trie {root_key:?}")))?;
+		children.push(SnapshotChildTrie {
+			storage_key,
+			root: root.0,
+			key_values: child.data.into_iter().collect(),
+		});
+	}
+	children.sort_by(|a, b| a.storage_key.cmp(&b.storage_key));
+
+	Ok(StateSnapshot { headers, top: storage.top.into_iter().collect(), children })
+}
+
+/// Imports a state snapshot into a fresh database.
+///
+/// The snapshot block is imported like a warp synced block, with its state checked against the
+/// state root of its header. If `trusted_hash` is given, the header chain of the snapshot must
+/// contain it. Otherwise the snapshot is trusted as is, which callers should only allow on
+/// explicit request.
+pub fn import_state_snapshot<B, IQ, C>(
+	client: Arc<C>,
+	mut import_queue: IQ,
+	snapshot: StateSnapshot<B>,
+	trusted_hash: Option<B::Hash>,
+) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>
+where
+	C: HeaderBackend<B> + Send + Sync + 'static,
+	B: BlockT,
+	IQ: ImportQueue<B> + 'static,
+{
+	struct SnapshotLink {
+		result: Option<Result<(), String>>,
+	}
+
+	impl<B: BlockT> Link<B> for SnapshotLink {
+		fn blocks_processed(
+			&mut self,
+			_imported: usize,
+			_num_expected_blocks: usize,
+			results: Vec<(Result<BlockImportStatus<NumberFor<B>>, BlockImportError>, B::Hash)>,
+		) {
+			for (result, hash) in results {
+				self.result = Some(result.map(|_| ()).map_err(|e| {
+					format!("There was an error importing state snapshot block {hash:?}: {e}")
+				}));
+			}
+		}
+	}
+
+	if let Err(e) = snapshot.verify_headers(trusted_hash) {
+		return future::ready(Err(e)).boxed()
+	}
+	if trusted_hash.is_none() {
+		warn!("No trusted block hash given, the state snapshot is trusted as is");
+	}
+	if !client.info().best_number.is_zero() {
+		return future::ready(Err(Error::Other(
+			"State snapshots can only be imported into a fresh database".into(),
+		)))
+		.boxed()
+	}
+
+	let Some(block) = snapshot.into_incoming_block() else {
+		return future::ready(Err(Error::Other("State snapshot contains no header".into()))).boxed()
+	};
+	let number = block.header.as_ref().map(|header| *header.number());
+	info!("Importing state snapshot of block #{:?} ({})", number, block.hash);
+	import_queue.service_ref().import_blocks(BlockOrigin::File, vec![block]);
+
+	let mut link = SnapshotLink { result: None };
+	future::poll_fn(move |cx| {
+		import_queue.poll_actions(cx, &mut link);
+		match link.result.take() {
+			Some(Ok(())) => {
+				let info = client.info();
+				info!("🎉 Imported state snapshot. Finalized: #{}", info.finalized_number);
+				std::task::Poll::Ready(Ok(()))
+			},
+			Some(Err(e)) => std::task::Poll::Ready(Err(Error::Other(e))),
+			None => std::task::Poll::Pending,
+		}
+	})
+	.boxed()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::traits::Header as _;
+	use substrate_test_runtime::{Block, Header, H256};
+
+	fn headers(count: u64) -> Vec<Header> {
+		let mut headers: Vec<Header> = Vec::new();
+		for number in 0..count {
+			let parent_hash = headers.last().map(|header| header.hash()).unwrap_or_default();
+			headers.push(Header::new(
+				number,
+				Default::default(),
+				Default::default(),
+				parent_hash,
+				Default::default(),
+			));
+		}
+		headers
+	}
+
+	fn snapshot(headers: Vec<Header>) -> StateSnapshot<Block> {
+		StateSnapshot {
+			headers,
+			top: vec![(b"key".to_vec(), b"value".to_vec())],
+			children: vec![SnapshotChildTrie {
+				storage_key: b"child".to_vec(),
+				root: vec![1; 32],
+				key_values: vec![(b"child_key".to_vec(), b"child_value".to_vec())],
+			}],
+		}
+	}
+
+	#[test]
+	fn snapshot_roundtrip_works() {
+		let snapshot = snapshot(headers(3));
+		let mut encoded = Vec::new();
+		snapshot.write_to(&mut encoded).unwrap();
+
+		assert_eq!(&encoded[..8], &STATE_SNAPSHOT_MAGIC);
+		assert_eq!(StateSnapshot::<Block>::read_from(&encoded[..]).unwrap(), snapshot);
+	}
+
+	#[test]
+	fn snapshot_with_wrong_magic_or_version_is_rejected() {
+		let mut encoded = Vec::new();
+		snapshot(headers(1)).write_to(&mut encoded).unwrap();
+
+		let mut wrong_magic = encoded.clone();
+		wrong_magic[0] = 0;
+		assert!(StateSnapshot::<Block>::read_from(&wrong_magic[..]).is_err());
+
+		let mut wrong_version = encoded;
+		wrong_version[8] = STATE_SNAPSHOT_VERSION as u8 + 1;
+		assert!(StateSnapshot::<Block>::read_from(&wrong_version[..]).is_err());
+	}
+
+	#[test]
+	fn verify_headers_checks_chain_and_trusted_hash() {
+		let headers = headers(3);
+		let trusted = headers[0].hash();
+		let snapshot = snapshot(headers.clone());
+
+		assert!(snapshot.verify_headers(None).is_ok());
+		assert!(snapshot.verify_headers(Some(trusted)).is_ok());
+		assert!(snapshot.verify_headers(Some(H256::repeat_byte(1))).is_err());
+
+		let broken = self::snapshot(vec![headers[0].clone(), headers[2].clone()]);
+		assert!(broken.verify_headers(None).is_err());
+		assert!(self::snapshot(Vec::new()).verify_headers(None).is_err());
+	}
+}
@@ -1747,7 +1747,13 @@ where
 		&mut self,
 		mut import_block: BlockImportParams<Block>,
 	) -> Result<ImportResult, Self::Error> {
-		let span = tracing::span!(tracing::Level::DEBUG, "import_block");
+		// Enabled by default, to attach the block to the lines logged while importing it.
+		let span = tracing::span!(
+			tracing::Level::INFO,
+			"import_block",
+			block_hash = ?import_block.post_hash(),
+			block_number = import_block.header.number().saturated_into::<u64>(),
+		);
 		let _enter = span.enter();
 
 		let storage_changes =
@@ -33,8 +33,12 @@ pub use sc_network::{
 
 use prometheus_endpoint::Registry;
 use sc_chain_spec::ChainSpec;
+pub use sc_offchain::HttpPolicy as OffchainHttpPolicy;
 pub use sc_telemetry::TelemetryEndpoints;
-pub use sc_transaction_pool::Options as TransactionPoolOptions;
+pub use sc_transaction_pool::{
+	JournalOptions as TransactionPoolJournalOptions, Options as TransactionPoolOptions,
+	PoolSenderLimits as TransactionPoolSenderLimits, TransactionPoolType,
+};
 use sp_core::crypto::SecretString;
 use std::{
 	io, iter,
@@ -72,6 +76,8 @@ pub struct Configuration {
 	///
 	/// NOTE: only finalized blocks are subject for removal!
 	pub blocks_pruning: BlocksPruning,
+	/// Directory where finalized blocks are archived before they are pruned.
+	pub blocks_archive: Option<PathBuf>,
 	/// Chain configuration.
 	pub chain_spec: Box<dyn ChainSpec>,
 	/// Wasm execution method.
@@ -178,6 +184,8 @@ pub struct OffchainWorkerConfig {
 	pub enabled: bool,
 	/// allow writes from the runtime to the offchain worker database.
 	pub indexing_enabled: bool,
+	/// Restrictions applied to the http requests of offchain workers.
+	pub http_policy: OffchainHttpPolicy,
 }
 
 /// Configuration of the Prometheus endpoint.
@@ -241,6 +249,7 @@ impl Configuration {
 			state_pruning: self.state_pruning.clone(),
 			source: self.database.clone(),
 			blocks_pruning: self.blocks_pruning,
+			blocks_archive: self.blocks_archive.clone(),
 		}
 	}
 }
@@ -479,11 +479,7 @@ where
 			},
 		};
 
-		let import_future = self.pool.submit_one(
-			self.client.info().best_hash,
-			sc_transaction_pool_api::TransactionSource::External,
-			uxt,
-		);
+		let import_future = self.pool.submit_from_network(self.client.info().best_hash, uxt);
 		Box::pin(async move {
 			match import_future.await {
 				Ok(_) => TransactionImport::NewGood,
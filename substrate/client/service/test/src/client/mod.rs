@@ -26,9 +26,13 @@ use sc_client_api::{
 };
 use sc_client_db::{Backend, BlocksPruning, DatabaseSettings, DatabaseSource, PruningMode};
 use sc_consensus::{
-	BlockCheckParams, BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult,
+	BasicQueue, BlockCheckParams, BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult,
+	Verifier,
+};
+use sc_service::{
+	chain_ops::{export_state_snapshot, import_state_snapshot, StateSnapshot},
+	client::{new_in_mem, Client, LocalCallExecutor},
 };
-use sc_service::client::{new_in_mem, Client, LocalCallExecutor};
 use sp_api::ProvideRuntimeApi;
 use sp_consensus::{BlockOrigin, Error as ConsensusError, SelectChain};
 use sp_core::{testing::TaskExecutor, traits::CallContext, H256};
@@ -1482,6 +1486,7 @@ fn doesnt_import_blocks_that_revert_finality() {
 				trie_cache_maximum_size: Some(1 << 20),
 				state_pruning: Some(PruningMode::ArchiveAll),
 				blocks_pruning: BlocksPruning::KeepAll,
+				blocks_archive: None,
 				source: DatabaseSource::RocksDb { path: tmp.path().into(), cache_size: 1024 },
 			},
 			u64::MAX,
@@ -1761,6 +1766,7 @@ fn returns_status_for_pruned_blocks() {
 				trie_cache_maximum_size: Some(1 << 20),
 				state_pruning: Some(PruningMode::blocks_pruning(1)),
 				blocks_pruning: BlocksPruning::KeepFinalized,
+				blocks_archive: None,
 				source: DatabaseSource::RocksDb { path: tmp.path().into(), cache_size: 1024 },
 			},
 			u64::MAX,
@@ -2310,3 +2316,74 @@ fn finalize_after_best_block_updates_best() {
 	assert_eq!(client.chain_info().finalized_hash, a3.hash());
 	assert_eq!(client.chain_info().best_hash, a3.hash());
 }
+
+#[test]
+fn state_snapshot_export_import_roundtrip_works() {
+	struct FinalizingVerifier;
+
+	#[async_trait::async_trait]
+	impl Verifier<Block> for FinalizingVerifier {
+		async fn verify(
+			&mut self,
+			mut block: BlockImportParams<Block>,
+		) -> Result<BlockImportParams<Block>, String> {
+			block.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+			block.finalized = true;
+			Ok(block)
+		}
+	}
+
+	let mut client = substrate_test_runtime_client::new();
+	let mut hash = client.chain_info().genesis_hash;
+	for nonce in 0..3 {
+		let mut builder = BlockBuilderBuilder::new(&client)
+			.on_parent_block(hash)
+			.with_parent_block_number(nonce)
+			.build()
+			.unwrap();
+		builder
+			.push_transfer(Transfer {
+				from: AccountKeyring::Alice.into(),
+				to: AccountKeyring::Ferdie.into(),
+				amount: 42 * DOLLARS,
+				nonce,
+			})
+			.unwrap();
+		let block = builder.build().unwrap().block;
+		hash = block.hash();
+		block_on(client.import(BlockOrigin::Own, block)).unwrap();
+	}
+	ClientExt::finalize_block(&client, hash, None).unwrap();
+	let client = Arc::new(client);
+
+	let mut encoded = Vec::new();
+	export_state_snapshot(client.clone(), hash, 2)
+		.unwrap()
+		.write_to(&mut encoded)
+		.unwrap();
+	let snapshot = StateSnapshot::<Block>::read_from(&encoded[..]).unwrap();
+	assert_eq!(snapshot.headers.len(), 3);
+	let trusted = snapshot.headers[0].hash();
+
+	let fresh = Arc::new(substrate_test_runtime_client::new());
+	let import_queue = BasicQueue::new(
+		FinalizingVerifier,
+		Box::new(fresh.clone()),
+		None,
+		&TaskExecutor::new(),
+		None,
+	);
+	block_on(import_state_snapshot(fresh.clone(), import_queue, snapshot, Some(trusted))).unwrap();
+
+	let info = fresh.chain_info();
+	assert_eq!(info.best_hash, hash);
+	assert_eq!(info.finalized_hash, hash);
+	assert_eq!(fresh.header(hash).unwrap(), client.header(hash).unwrap());
+	for key in client.storage_keys(hash, None, None).unwrap() {
+		assert_eq!(fresh.storage(hash, &key).unwrap(), client.storage(hash, &key).unwrap());
+	}
+	assert_eq!(
+		fresh.storage_keys(hash, None, None).unwrap().count(),
+		client.storage_keys(hash, None, None).unwrap().count(),
+	);
+}
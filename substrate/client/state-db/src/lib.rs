@@ -39,7 +39,9 @@
 //!
 //! # Pruning.
 //! See `RefWindow` for pruning algorithm details. `StateDb` prunes on each canonicalization until
-//! pruning constraints are satisfied.
+//! pruning constraints are satisfied. The pruning window may be constrained by its number of
+//! blocks, by the age of its blocks and by the number of bytes inserted by its blocks. The state of
+//! selected blocks may be kept permanently while the rest is pruned.
 
 mod noncanonical;
 mod pruning;
@@ -54,6 +56,7 @@ use pruning::{HaveBlock, RefWindow};
 use std::{
 	collections::{hash_map::Entry, HashMap},
 	fmt,
+	time::Duration,
 };
 
 const LOG_TARGET: &str = "state-db";
@@ -145,6 +148,8 @@ pub enum StateDbError {
 	BlockUnavailable,
 	/// Block record is missing from the pruning window
 	BlockMissing,
+	/// Keeping the state of blocks requires a database with reference counting
+	KeptBlocksUnsupported,
 }
 
 impl<E> From<StateDbError> for Error<E> {
@@ -196,6 +201,10 @@ impl fmt::Debug for StateDbError {
 				write!(f, "Trying to get a block record from db while it is not commit to db yet")
 			},
 			Self::BlockMissing => write!(f, "Block record is missing from the pruning window"),
+			Self::KeptBlocksUnsupported => write!(
+				f,
+				"Keeping the state of blocks requires a database with reference counting support"
+			),
 		}
 	}
 }
@@ -221,9 +230,51 @@ pub struct CommitSet<H: Hash> {
 /// Pruning constraints. If none are specified pruning is
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Constraints {
-	/// Maximum blocks. Defaults to 0 when unspecified, effectively keeping only non-canonical
-	/// states.
+	/// Maximum blocks. Defaults to 0 when unspecified and no other constraint is set, effectively
+	/// keeping only non-canonical states.
 	pub max_blocks: Option<u32>,
+	/// Maximum age of the kept states, measured from the canonicalization of their block.
+	pub max_age: Option<Duration>,
+	/// Maximum number of bytes of the trie nodes inserted by the blocks of the pruning window.
+	///
+	/// This is not the size of the database: nodes shared with older states, database overhead
+	/// and compression are not accounted for.
+	pub max_inserted_bytes: Option<u64>,
+	/// Numbers of the blocks whose state is kept when the pruning window moves past them.
+	/// Requires a database with reference counting support.
+	pub keep_blocks: Vec<u64>,
+}
+
+impl Constraints {
+	/// Whether the pruning window exceeds the constraints, given its number of blocks, the age of
+	/// its oldest block and the number of bytes inserted by its blocks.
+	fn is_exceeded(
+		&self,
+		window_size: u64,
+		oldest_age: Option<Duration>,
+		window_bytes: u64,
+	) -> bool {
+		if window_size == 0 {
+			return false
+		}
+		if self.max_age.is_none() && self.max_inserted_bytes.is_none() {
+			return window_size > self.max_blocks.unwrap_or(0) as u64
+		}
+
+		self.max_blocks.map_or(false, |max_blocks| window_size > max_blocks as u64) ||
+			self.max_age.zip(oldest_age).map_or(false, |(max_age, age)| age >= max_age) ||
+			self.max_inserted_bytes.map_or(false, |max_bytes| window_bytes > max_bytes)
+	}
+
+	/// Expected number of blocks in the pruning window.
+	fn window_size_hint(&self) -> u32 {
+		match self.max_blocks {
+			Some(max_blocks) => max_blocks,
+			None if self.max_age.is_some() || self.max_inserted_bytes.is_some() =>
+				DEFAULT_MAX_BLOCK_CONSTRAINT,
+			None => 0,
+		}
+	}
 }
 
 /// Pruning mode.
@@ -240,7 +291,26 @@ pub enum PruningMode {
 impl PruningMode {
 	/// Create a mode that keeps given number of blocks.
 	pub fn blocks_pruning(n: u32) -> PruningMode {
-		PruningMode::Constrained(Constraints { max_blocks: Some(n) })
+		PruningMode::Constrained(Constraints { max_blocks: Some(n), ..Default::default() })
+	}
+
+	/// Create a mode that keeps the state of blocks canonicalized within the given duration.
+	pub fn age_pruning(max_age: Duration) -> PruningMode {
+		PruningMode::Constrained(Constraints {
+			max_blocks: None,
+			max_age: Some(max_age),
+			..Default::default()
+		})
+	}
+
+	/// Create a mode that keeps the state of the most recent blocks, as long as the trie nodes
+	/// they inserted stay below `max_bytes`.
+	pub fn inserted_bytes_pruning(max_bytes: u64) -> PruningMode {
+		PruningMode::Constrained(Constraints {
+			max_blocks: None,
+			max_inserted_bytes: Some(max_bytes),
+			..Default::default()
+		})
 	}
 
 	/// Is this an archive (either ArchiveAll or ArchiveCanonical) pruning mode?
@@ -278,7 +348,12 @@ impl Default for PruningMode {
 
 impl Default for Constraints {
 	fn default() -> Self {
-		Self { max_blocks: Some(DEFAULT_MAX_BLOCK_CONSTRAINT) }
+		Self {
+			max_blocks: Some(DEFAULT_MAX_BLOCK_CONSTRAINT),
+			max_age: None,
+			max_inserted_bytes: None,
+			keep_blocks: Vec::new(),
+		}
 	}
 }
 
@@ -317,8 +392,10 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 
 		let non_canonical: NonCanonicalOverlay<BlockHash, Key> = NonCanonicalOverlay::new(&db)?;
 		let pruning: Option<RefWindow<BlockHash, Key, D>> = match mode {
-			PruningMode::Constrained(Constraints { max_blocks }) =>
-				Some(RefWindow::new(db, max_blocks.unwrap_or(0), ref_counting)?),
+			PruningMode::Constrained(ref constraints) => Some(
+				RefWindow::new(db, constraints.window_size_hint(), ref_counting)?
+					.with_kept_blocks(&constraints.keep_blocks)?,
+			),
 			PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => None,
 		};
 
@@ -412,7 +489,11 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDbSync<BlockHash, Key, D> {
 			(&mut self.pruning, &self.mode)
 		{
 			loop {
-				if pruning.window_size() <= constraints.max_blocks.unwrap_or(0) as u64 {
+				if !constraints.is_exceeded(
+					pruning.window_size(),
+					pruning.oldest_age(),
+					pruning.window_bytes(),
+				) {
 					break
 				}
 
@@ -660,6 +741,42 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> StateDb<BlockHash, Key, D> {
 	}
 }
 
+/// Rewrite the state meta records of a database that relies on `StateDb` to count references, so
+/// they can be used with a database that supports reference counting itself.
+///
+/// `meta` holds all the records of the state meta column and `map_key` maps the node keys found in
+/// the journals to the keys used by the target database.
+pub fn translate_meta_for_ref_counting<BlockHash: Hash>(
+	meta: &mut [(Vec<u8>, DBValue)],
+	map_key: impl Fn(&[u8]) -> Vec<u8>,
+) -> Result<(), StateDbError> {
+	noncanonical::translate_journals::<BlockHash>(meta, &map_key)?;
+	pruning::translate_journals::<BlockHash>(meta, &map_key)
+}
+
+/// The state meta records of a database whose state is migrated from another database up to the
+/// canonical block `hash` with number `number`.
+///
+/// The migrated block is the last canonicalized one. With [`PruningMode::Constrained`], it is the
+/// only block of the pruning window: it does not delete any node when it is pruned.
+pub fn meta_for_migrated_state<BlockHash: Hash>(
+	mode: &PruningMode,
+	hash: &BlockHash,
+	number: u64,
+) -> Vec<(Vec<u8>, DBValue)> {
+	let mut meta = vec![
+		(to_meta_key(PRUNING_MODE, &()), mode.id().to_vec()),
+		(to_meta_key(noncanonical::LAST_CANONICAL, &()), codec::Encode::encode(&(hash, number))),
+	];
+	meta.extend(pruning::migrated_window(hash, number));
+	meta
+}
+
+/// Read the pruning mode stored in the meta records of `db`, if any.
+pub fn stored_pruning_mode<D: MetaDb>(db: &D) -> Result<Option<PruningMode>, Error<D::Error>> {
+	fetch_stored_pruning_mode(db)
+}
+
 /// The result return by `StateDb::is_pruned`
 #[derive(Debug, PartialEq, Eq)]
 pub enum IsPruned {
@@ -705,10 +822,12 @@ fn choose_pruning_mode(
 #[cfg(test)]
 mod tests {
 	use crate::{
+		meta_for_migrated_state,
 		test::{make_changeset, make_db, TestDb},
-		Constraints, Error, IsPruned, PruningMode, StateDb, StateDbError,
+		ChangeSet, CommitSet, Constraints, Error, IsPruned, PruningMode, StateDb, StateDbError,
 	};
 	use sp_core::H256;
+	use std::time::Duration;
 
 	fn make_test_db(settings: PruningMode) -> (TestDb, StateDb<H256, H256, TestDb>) {
 		let mut db = make_db(&[91, 921, 922, 93, 94]);
@@ -788,8 +907,10 @@ mod tests {
 
 	#[test]
 	fn block_record_unavailable() {
-		let (mut db, state_db) =
-			make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(1) }));
+		let (mut db, state_db) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(1),
+			..Default::default()
+		}));
 		// import 2 blocks
 		for i in &[5, 6] {
 			db.commit(
@@ -823,13 +944,19 @@ mod tests {
 
 	#[test]
 	fn prune_window_0() {
-		let (db, _) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(0) }));
+		let (db, _) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(0),
+			..Default::default()
+		}));
 		assert!(db.data_eq(&make_db(&[21, 3, 922, 94])));
 	}
 
 	#[test]
 	fn prune_window_1() {
-		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(1) }));
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(1),
+			..Default::default()
+		}));
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(0), 0), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::Pruned);
@@ -839,7 +966,10 @@ mod tests {
 
 	#[test]
 	fn prune_window_2() {
-		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints { max_blocks: Some(2) }));
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(2),
+			..Default::default()
+		}));
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(0), 0), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
 		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::NotPruned);
@@ -847,6 +977,70 @@ mod tests {
 		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
 	}
 
+	#[test]
+	fn prune_window_by_age() {
+		let (db, _) = make_test_db(PruningMode::age_pruning(Duration::ZERO));
+		assert!(db.data_eq(&make_db(&[21, 3, 922, 94])));
+
+		let (db, sdb) = make_test_db(PruningMode::age_pruning(Duration::from_secs(3600)));
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::NotPruned);
+		assert!(db.data_eq(&make_db(&[1, 21, 3, 91, 921, 922, 93, 94])));
+	}
+
+	#[test]
+	fn prune_window_by_inserted_bytes() {
+		// every canonicalized block inserts a single 32 bytes value
+		let (db, sdb) = make_test_db(PruningMode::inserted_bytes_pruning(64));
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::Pruned);
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::NotPruned);
+		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
+	}
+
+	#[test]
+	fn kept_block_state_is_not_pruned() {
+		let (mut db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(0),
+			keep_blocks: vec![1],
+			..Default::default()
+		}));
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(1), 1), IsPruned::NotPruned);
+		assert_eq!(sdb.is_pruned(&H256::from_low_u64_be(21), 2), IsPruned::Pruned);
+		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
+
+		// nodes inserted after the kept block are still pruned, also after a restart
+		db.commit(&sdb.canonicalize_block(&H256::from_low_u64_be(4)).unwrap());
+		let mode = PruningMode::Constrained(Constraints {
+			max_blocks: Some(0),
+			keep_blocks: vec![1],
+			..Default::default()
+		});
+		let (_, sdb) =
+			StateDb::<H256, H256, TestDb>::open(db.clone(), Some(mode), false, false).unwrap();
+		db.commit(
+			&sdb.insert_block(
+				&H256::from_low_u64_be(5),
+				5,
+				&H256::from_low_u64_be(4),
+				make_changeset(&[5], &[21, 4]),
+			)
+			.unwrap(),
+		);
+		db.commit(&sdb.canonicalize_block(&H256::from_low_u64_be(5)).unwrap());
+		assert!(sdb.pin(&H256::from_low_u64_be(1), 1, || false).is_ok());
+		assert!(db.data_eq(&make_db(&[1, 3, 5, 921, 922, 93, 94])));
+	}
+
+	#[test]
+	fn kept_blocks_require_ref_counting() {
+		let db = make_db(&[]);
+		let mode =
+			PruningMode::Constrained(Constraints { keep_blocks: vec![1], ..Default::default() });
+		assert!(matches!(
+			StateDb::<H256, H256, TestDb>::open(db, Some(mode), true, true),
+			Err(Error::StateDb(StateDbError::KeptBlocksUnsupported))
+		));
+	}
+
 	#[test]
 	fn detects_incompatible_mode() {
 		let mut db = make_db(&[]);
@@ -863,7 +1057,8 @@ mod tests {
 				)
 				.unwrap(),
 		);
-		let new_mode = PruningMode::Constrained(Constraints { max_blocks: Some(2) });
+		let new_mode =
+			PruningMode::Constrained(Constraints { max_blocks: Some(2), ..Default::default() });
 		let state_db_open_result: Result<(_, StateDb<H256, H256, TestDb>), _> =
 			StateDb::open(db.clone(), Some(new_mode), false, false);
 		assert!(state_db_open_result.is_err());
@@ -946,4 +1141,21 @@ mod tests {
 			check_stored_and_requested_mode_compatibility(created, reopened, expected);
 		}
 	}
+
+	#[test]
+	fn migrated_state_is_not_pruned() {
+		let hash = H256::from_low_u64_be(5);
+		let mut db = make_db(&[]);
+		db.commit(&CommitSet {
+			data: Default::default(),
+			meta: ChangeSet {
+				inserted: meta_for_migrated_state(&PruningMode::blocks_pruning(256), &hash, 5),
+				deleted: Vec::new(),
+			},
+		});
+
+		let (_, state_db) = StateDb::<H256, H256, TestDb>::open(db, None, false, false).unwrap();
+		assert_eq!(state_db.is_pruned(&hash, 5), IsPruned::NotPruned);
+		assert_eq!(state_db.is_pruned(&H256::from_low_u64_be(4), 4), IsPruned::Pruned);
+	}
 }
@@ -81,6 +81,28 @@ fn to_journal_key(block: u64, index: u64) -> Vec<u8> {
 	to_meta_key(NON_CANONICAL_JOURNAL, &(block, index))
 }
 
+/// Map the node keys of the non-canonical journals found in `meta` with `map_key`.
+pub(crate) fn translate_journals<BlockHash: Hash>(
+	meta: &mut [(Vec<u8>, DBValue)],
+	map_key: &impl Fn(&[u8]) -> Vec<u8>,
+) -> Result<(), StateDbError> {
+	for (key, value) in meta.iter_mut() {
+		if key.len() != 16 + NON_CANONICAL_JOURNAL.len() || !key.ends_with(NON_CANONICAL_JOURNAL) {
+			continue
+		}
+		let record = JournalRecord::<BlockHash, Vec<u8>>::decode(&mut value.as_slice())
+			.map_err(StateDbError::Decoding)?;
+		let record = JournalRecord {
+			hash: record.hash,
+			parent_hash: record.parent_hash,
+			inserted: record.inserted.into_iter().map(|(k, v)| (map_key(&k), v)).collect(),
+			deleted: record.deleted.iter().map(|k| map_key(k)).collect::<Vec<_>>(),
+		};
+		*value = record.encode();
+	}
+	Ok(())
+}
+
 #[cfg_attr(test, derive(PartialEq, Debug))]
 struct BlockOverlay<BlockHash: Hash, Key: Hash> {
 	hash: BlockHash,
@@ -23,17 +23,39 @@
 //! If a node is re-inserted into the window it gets removed from
 //! the death list.
 //! The changes are journaled in the DB.
+//!
+//! The canonicalization time and the number of bytes inserted by each block of the window are
+//! journaled as well, allowing to constrain the window by age and by size.
+//!
+//! The state of some blocks may be kept when the window moves past them. Nodes of a kept state
+//! are never deleted: for the blocks following the last kept block, the insertions of each node
+//! are counted in memory, and deletions are only journaled while they are balanced by those
+//! insertions. This relies on the database counting references, so that skipping a deletion keeps
+//! the node alive. The counters only track the nodes inserted since the last kept block that are
+//! still part of the state. They are snapshotted every `KEPT_SNAPSHOT_INTERVAL` blocks, and the
+//! journal records of the pruned blocks following the last snapshot record their insertions and
+//! are retained, so that the counters can be rebuilt on startup.
 
 use crate::{
-	noncanonical::LAST_CANONICAL, to_meta_key, CommitSet, Error, Hash, MetaDb, StateDbError,
-	DEFAULT_MAX_BLOCK_CONSTRAINT, LOG_TARGET,
+	noncanonical::LAST_CANONICAL, to_meta_key, CommitSet, DBValue, Error, Hash, MetaDb,
+	StateDbError, DEFAULT_MAX_BLOCK_CONSTRAINT, LOG_TARGET,
 };
 use codec::{Decode, Encode};
-use log::trace;
-use std::collections::{HashMap, HashSet, VecDeque};
+use log::{trace, warn};
+use std::{
+	collections::{BTreeMap, HashMap, HashSet, VecDeque},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 pub(crate) const LAST_PRUNED: &[u8] = b"last_pruned";
 const PRUNING_JOURNAL: &[u8] = b"pruning_journal";
+const PRUNING_INFO_JOURNAL: &[u8] = b"pruning_info_journal";
+const KEPT_BLOCK: &[u8] = b"kept_block";
+const KEPT_SNAPSHOT: &[u8] = b"kept_snapshot";
+/// Number of canonicalized blocks between two snapshots of the insertion counters.
+const KEPT_SNAPSHOT_INTERVAL: u64 = 1024;
+/// Maximum number of insertion counters stored in a single meta value of a snapshot.
+const KEPT_SNAPSHOT_CHUNK: usize = 1 << 16;
 
 /// See module documentation.
 pub struct RefWindow<BlockHash: Hash, Key: Hash, D: MetaDb> {
@@ -42,6 +64,12 @@ pub struct RefWindow<BlockHash: Hash, Key: Hash, D: MetaDb> {
 	queue: DeathRowQueue<BlockHash, Key, D>,
 	/// Block number that is next to be pruned.
 	base: u64,
+	/// Canonicalization time and inserted bytes of each block in the pruning window.
+	infos: VecDeque<BlockInfo>,
+	/// Sum of the bytes inserted by the blocks in the pruning window.
+	window_bytes: u64,
+	/// Blocks whose state is kept when the pruning window moves past them.
+	kept: KeptBlocks<BlockHash, Key>,
 }
 
 /// `DeathRowQueue` used to keep track of blocks in the pruning window, there are two flavors:
@@ -279,6 +307,205 @@ fn to_journal_key(block: u64) -> Vec<u8> {
 	to_meta_key(PRUNING_JOURNAL, &block)
 }
 
+/// Rewrite the pruning journals found in `meta` for a database that counts references itself.
+///
+/// Node keys are mapped with `map_key`. A deletion that is cancelled by a later block of the window
+/// inserting the same key again is dropped, like the in-memory death row queue does on load, and
+/// the insertions are not journaled anymore.
+pub(crate) fn translate_journals<BlockHash: Hash>(
+	meta: &mut [(Vec<u8>, DBValue)],
+	map_key: &impl Fn(&[u8]) -> Vec<u8>,
+) -> Result<(), StateDbError> {
+	let mut journals = Vec::new();
+	for (index, (key, value)) in meta.iter().enumerate() {
+		if key.len() != 8 + PRUNING_JOURNAL.len() || !key.ends_with(PRUNING_JOURNAL) {
+			continue
+		}
+		let block = u64::decode(&mut &key[..8]).map_err(StateDbError::Decoding)?;
+		let record = JournalRecord::<BlockHash, Vec<u8>>::decode(&mut value.as_slice())
+			.map_err(StateDbError::Decoding)?;
+		journals.push((block, index, record));
+	}
+
+	// Walk the window backwards so the insertions of all later blocks are known.
+	journals.sort_by_key(|(block, _, _)| std::cmp::Reverse(*block));
+	let mut reinserted = HashSet::new();
+	for (_, index, record) in journals {
+		let deleted = record
+			.deleted
+			.iter()
+			.filter(|key| !reinserted.contains(*key))
+			.map(|key| map_key(key))
+			.collect();
+		reinserted.extend(record.inserted);
+		let record = JournalRecord { hash: record.hash, inserted: Vec::<Vec<u8>>::new(), deleted };
+		meta[index].1 = record.encode();
+	}
+	Ok(())
+}
+
+/// The records of a pruning window holding the single block `hash` with number `number`, which
+/// does not delete any node.
+pub(crate) fn migrated_window<BlockHash: Hash>(
+	hash: &BlockHash,
+	number: u64,
+) -> Vec<(Vec<u8>, DBValue)> {
+	let record = JournalRecord::<BlockHash, Vec<u8>> {
+		hash: hash.clone(),
+		inserted: Vec::new(),
+		deleted: Vec::new(),
+	};
+	let mut meta = vec![(to_journal_key(number), record.encode())];
+	if let Some(last_pruned) = number.checked_sub(1) {
+		meta.push((to_meta_key(LAST_PRUNED, &()), last_pruned.encode()));
+	}
+	meta
+}
+
+/// Information about a block of the pruning window, journaled separately from the
+/// `JournalRecord` to keep the existing journals readable.
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+struct BlockInfo {
+	/// Seconds since the unix epoch at which the block was canonicalized.
+	canonicalized_at: u64,
+	/// Number of bytes inserted by the block.
+	inserted_bytes: u64,
+}
+
+fn to_info_key(block: u64) -> Vec<u8> {
+	to_meta_key(PRUNING_INFO_JOURNAL, &block)
+}
+
+fn unix_time() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs())
+}
+
+/// Load the `BlockInfo` of `len` blocks starting from `base`.
+///
+/// Blocks journaled before the `BlockInfo` were introduced are considered canonicalized at the
+/// unix epoch, so that they don't hold back the age-based pruning.
+fn load_block_infos<D: MetaDb>(
+	db: &D,
+	base: u64,
+	len: u64,
+) -> Result<VecDeque<BlockInfo>, Error<D::Error>> {
+	let mut infos = VecDeque::with_capacity(len as usize);
+	for block in base..base + len {
+		let info = match db.get_meta(&to_info_key(block)).map_err(Error::Db)? {
+			Some(info) => Decode::decode(&mut info.as_slice())?,
+			None => BlockInfo { canonicalized_at: 0, inserted_bytes: 0 },
+		};
+		infos.push_back(info);
+	}
+	Ok(infos)
+}
+
+/// Blocks whose state is kept, see the module documentation.
+struct KeptBlocks<BlockHash: Hash, Key: Hash> {
+	/// Numbers of the kept blocks, with their hash once canonicalized.
+	blocks: BTreeMap<u64, Option<BlockHash>>,
+	/// Insertions of each node since the last canonicalized kept block which are not yet
+	/// balanced by a deletion.
+	insertions: HashMap<Key, u32>,
+	/// Number of the block up to which the insertions are stored in the snapshot. The journal
+	/// records of the pruned blocks following it are retained.
+	snapshot: Option<u64>,
+	/// Number of meta values holding the insertions of the stored snapshot.
+	snapshot_chunks: u32,
+}
+
+/// Header of the snapshot of the insertion counters.
+#[derive(Encode, Decode)]
+struct KeptSnapshot {
+	/// The last kept block when the snapshot was taken.
+	kept: u64,
+	/// The block up to which the insertions are counted.
+	at: u64,
+	/// Number of meta values holding the insertions.
+	chunks: u32,
+}
+
+fn to_snapshot_chunk_key(chunk: u32) -> Vec<u8> {
+	to_meta_key(KEPT_SNAPSHOT, &chunk)
+}
+
+impl<BlockHash: Hash, Key: Hash> KeptBlocks<BlockHash, Key> {
+	fn new() -> Self {
+		KeptBlocks {
+			blocks: BTreeMap::new(),
+			insertions: HashMap::new(),
+			snapshot: None,
+			snapshot_chunks: 0,
+		}
+	}
+
+	/// The last kept block before `number` whose state must be protected from the changes of
+	/// the block `number`.
+	fn protecting(&self, number: u64) -> Option<u64> {
+		self.blocks
+			.range(..number)
+			.rev()
+			.find_map(|(kept, hash)| hash.as_ref().map(|_| *kept))
+	}
+
+	/// The last canonicalized kept block.
+	fn last(&self) -> Option<u64> {
+		self.protecting(u64::MAX)
+	}
+
+	/// Count the insertions of a block following the last kept block, and filter out its
+	/// deletions that are not balanced by an insertion.
+	fn apply(&mut self, inserted: &[Key], deleted: Vec<Key>) -> Vec<Key> {
+		for key in inserted {
+			*self.insertions.entry(key.clone()).or_default() += 1;
+		}
+		deleted
+			.into_iter()
+			.filter(|key| match self.insertions.get_mut(key) {
+				Some(count) => {
+					*count -= 1;
+					if *count == 0 {
+						self.insertions.remove(key);
+					}
+					true
+				},
+				None => false,
+			})
+			.collect()
+	}
+
+	/// Store the insertions counted up to the block `number`, following the kept block `kept`,
+	/// and drop the journal records of the pruned blocks retained since the previous snapshot.
+	fn snapshot(&mut self, kept: u64, number: u64, base: u64, commit: &mut CommitSet<Key>) {
+		trace!(
+			target: LOG_TARGET,
+			"Storing {} kept state insertions at #{}",
+			self.insertions.len(),
+			number,
+		);
+		if let Some(previous) = self.snapshot {
+			for block in previous + 1..base {
+				commit.meta.deleted.push(to_journal_key(block));
+			}
+		}
+
+		let insertions = self.insertions.iter().collect::<Vec<_>>();
+		let mut chunks = 0;
+		for chunk in insertions.chunks(KEPT_SNAPSHOT_CHUNK) {
+			commit.meta.inserted.push((to_snapshot_chunk_key(chunks), chunk.encode()));
+			chunks += 1;
+		}
+		for chunk in chunks..self.snapshot_chunks {
+			commit.meta.deleted.push(to_snapshot_chunk_key(chunk));
+		}
+		let header = KeptSnapshot { kept, at: number, chunks };
+		commit.meta.inserted.push((to_meta_key(KEPT_SNAPSHOT, &()), header.encode()));
+
+		self.snapshot = Some(number);
+		self.snapshot_chunks = chunks;
+	}
+}
+
 /// The result return by `RefWindow::have_block`
 #[derive(Debug, PartialEq, Eq)]
 pub enum HaveBlock {
@@ -319,7 +546,7 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 				None => None,
 			};
 
-		let queue = if count_insertions {
+		let (queue, infos) = if count_insertions {
 			// Highly scientific crafted number for deciding when to print the warning!
 			//
 			// Rocksdb doesn't support refcounting and requires that we load the entire pruning
@@ -332,7 +559,9 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 				);
 			}
 
-			DeathRowQueue::new_mem(&db, base)?
+			let queue = DeathRowQueue::new_mem(&db, base)?;
+			let infos = load_block_infos(&db, base, queue.len(base))?;
+			(queue, infos)
 		} else {
 			let last = match last_canonicalized_number {
 				Some(last_canonicalized_number) => {
@@ -344,16 +573,96 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 				// ever been committed to the db, thus set `unload` to zero
 				None => None,
 			};
-			DeathRowQueue::new_db_backed(db, base, last, window_size)?
+			let infos = load_block_infos(&db, base, last.map_or(0, |last| last + 1 - base))?;
+			(DeathRowQueue::new_db_backed(db, base, last, window_size)?, infos)
 		};
 
-		Ok(RefWindow { queue, base })
+		let window_bytes = infos.iter().map(|info| info.inserted_bytes).sum();
+		Ok(RefWindow { queue, base, infos, window_bytes, kept: KeptBlocks::new() })
+	}
+
+	/// Keep the state of the given blocks when the pruning window moves past them.
+	///
+	/// Only supported when the database counts references.
+	pub fn with_kept_blocks(mut self, blocks: &[u64]) -> Result<Self, Error<D::Error>> {
+		if blocks.is_empty() {
+			return Ok(self)
+		}
+		let DeathRowQueue::DbBacked { ref db, .. } = self.queue else {
+			return Err(Error::StateDb(StateDbError::KeptBlocksUnsupported))
+		};
+
+		for number in blocks {
+			let hash = match db.get_meta(&to_meta_key(KEPT_BLOCK, number)).map_err(Error::Db)? {
+				Some(hash) => Some(Decode::decode(&mut hash.as_slice())?),
+				None if *number < self.base => {
+					warn!(
+						target: LOG_TARGET,
+						"The state of block #{number} can't be kept, it is already pruned",
+					);
+					None
+				},
+				None => None,
+			};
+			self.kept.blocks.insert(*number, hash);
+		}
+
+		// Rebuild the insertion counters from the last snapshot and the journal records following
+		// it, whose deletions are already filtered.
+		let snapshot = match db.get_meta(&to_meta_key(KEPT_SNAPSHOT, &())).map_err(Error::Db)? {
+			Some(header) => Some(KeptSnapshot::decode(&mut header.as_slice())?),
+			None => None,
+		};
+		if let Some(ref snapshot) = snapshot {
+			self.kept.snapshot_chunks = snapshot.chunks;
+		}
+		if let Some(kept) = self.kept.last() {
+			let mut block = match snapshot {
+				Some(snapshot) if snapshot.kept == kept => {
+					for chunk in 0..snapshot.chunks {
+						let Some(insertions) =
+							db.get_meta(&to_snapshot_chunk_key(chunk)).map_err(Error::Db)?
+						else {
+							return Err(Error::StateDb(StateDbError::Metadata(format!(
+								"Missing chunk {chunk} of the kept state insertions"
+							))))
+						};
+						self.kept
+							.insertions
+							.extend(Vec::<(Key, u32)>::decode(&mut insertions.as_slice())?);
+					}
+					snapshot.at + 1
+				},
+				// Written before the snapshots were introduced, the journal records following the
+				// kept block are all retained.
+				_ => kept + 1,
+			};
+			self.kept.snapshot = Some(block - 1);
+			while let Some(record) = db.get_meta(&to_journal_key(block)).map_err(Error::Db)? {
+				let record: JournalRecord<BlockHash, Key> = Decode::decode(&mut record.as_slice())?;
+				self.kept.apply(&record.inserted, record.deleted);
+				block += 1;
+			}
+		}
+		Ok(self)
 	}
 
 	pub fn window_size(&self) -> u64 {
 		self.queue.len(self.base) as u64
 	}
 
+	/// Sum of the bytes inserted by the blocks in the pruning window.
+	pub fn window_bytes(&self) -> u64 {
+		self.window_bytes
+	}
+
+	/// Time elapsed since the canonicalization of the oldest block in the pruning window.
+	pub fn oldest_age(&self) -> Option<Duration> {
+		self.infos
+			.front()
+			.map(|info| Duration::from_secs(unix_time().saturating_sub(info.canonicalized_at)))
+	}
+
 	/// Get the hash of the next pruning block
 	pub fn next_hash(&mut self) -> Result<Option<BlockHash>, Error<D::Error>> {
 		let res = match &mut self.queue {
@@ -374,6 +683,9 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 
 	// Check if a block is in the pruning window and not be pruned yet
 	pub fn have_block(&self, hash: &BlockHash, number: u64) -> HaveBlock {
+		if matches!(self.kept.blocks.get(&number), Some(Some(kept)) if kept == hash) {
+			return HaveBlock::Yes
+		}
 		// if the queue is empty or the block number exceed the pruning window, we definitely
 		// do not have this block
 		if self.is_empty() || number < self.base || number >= self.base + self.window_size() {
@@ -387,9 +699,15 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 		if let Some(pruned) = self.queue.pop_front(self.base)? {
 			trace!(target: LOG_TARGET, "Pruning {:?} ({} deleted)", pruned.hash, pruned.deleted.len());
 			let index = self.base;
+			if let Some(info) = self.infos.pop_front() {
+				self.window_bytes = self.window_bytes.saturating_sub(info.inserted_bytes);
+			}
 			commit.data.deleted.extend(pruned.deleted.into_iter());
 			commit.meta.inserted.push((to_meta_key(LAST_PRUNED, &()), index.encode()));
-			commit.meta.deleted.push(to_journal_key(self.base));
+			if self.kept.snapshot.map_or(true, |snapshot| index <= snapshot) {
+				commit.meta.deleted.push(to_journal_key(self.base));
+			}
+			commit.meta.deleted.push(to_info_key(self.base));
 			self.base += 1;
 			Ok(())
 		} else {
@@ -424,29 +742,74 @@ impl<BlockHash: Hash, Key: Hash, D: MetaDb> RefWindow<BlockHash, Key, D> {
 			commit.data.inserted.len(),
 			commit.data.deleted.len(),
 		);
-		let inserted = if matches!(self.queue, DeathRowQueue::Mem { .. }) {
+		let inserted: Vec<Key> = if matches!(self.queue, DeathRowQueue::Mem { .. }) ||
+			self.kept.protecting(number).is_some()
+		{
 			commit.data.inserted.iter().map(|(k, _)| k.clone()).collect()
 		} else {
 			Default::default()
 		};
+		let inserted_bytes = commit.data.inserted.iter().map(|(_, value)| value.len() as u64).sum();
+		let info = BlockInfo { canonicalized_at: unix_time(), inserted_bytes };
+		commit.meta.inserted.push((to_info_key(number), info.encode()));
+		self.window_bytes += inserted_bytes;
+		self.infos.push_back(info);
+
 		let deleted = std::mem::take(&mut commit.data.deleted);
+		let deleted = self.note_kept(hash, number, &inserted, deleted, commit);
 		let journal_record = JournalRecord { hash: hash.clone(), inserted, deleted };
 		commit.meta.inserted.push((to_journal_key(number), journal_record.encode()));
 		self.queue.import(self.base, number, journal_record);
+
+		if let (Some(kept), Some(snapshot)) = (self.kept.last(), self.kept.snapshot) {
+			if number >= snapshot + KEPT_SNAPSHOT_INTERVAL {
+				self.kept.snapshot(kept, number, self.base, commit);
+			}
+		}
 		Ok(())
 	}
+
+	/// Filter out the deletions of a block following a kept block that would remove nodes of the
+	/// kept state, and record the canonicalization of a kept block.
+	fn note_kept(
+		&mut self,
+		hash: &BlockHash,
+		number: u64,
+		inserted: &[Key],
+		deleted: Vec<Key>,
+		commit: &mut CommitSet<Key>,
+	) -> Vec<Key> {
+		let deleted = match self.kept.protecting(number) {
+			Some(_) => self.kept.apply(inserted, deleted),
+			None => deleted,
+		};
+
+		if let Some(kept) = self.kept.blocks.get_mut(&number) {
+			trace!(target: LOG_TARGET, "Keeping the state of {:?} (#{})", hash, number);
+			*kept = Some(hash.clone());
+			commit.meta.inserted.push((to_meta_key(KEPT_BLOCK, &number), hash.encode()));
+			self.kept.insertions.clear();
+			self.kept.snapshot(number, number, self.base, commit);
+		}
+		deleted
+	}
 }
 
 #[cfg(test)]
 mod tests {
-	use super::{to_journal_key, DeathRowQueue, HaveBlock, JournalRecord, RefWindow, LAST_PRUNED};
+	use super::{
+		to_journal_key, translate_journals, DeathRowQueue, HaveBlock, JournalRecord, RefWindow,
+		KEPT_SNAPSHOT_INTERVAL, LAST_PRUNED,
+	};
 	use crate::{
 		noncanonical::LAST_CANONICAL,
 		test::{make_commit, make_db, TestDb},
-		to_meta_key, CommitSet, Error, Hash, StateDbError, DEFAULT_MAX_BLOCK_CONSTRAINT,
+		to_meta_key, CommitSet, DBValue, Error, Hash, MetaDb, StateDbError,
+		DEFAULT_MAX_BLOCK_CONSTRAINT,
 	};
-	use codec::Encode;
+	use codec::{Decode, Encode};
 	use sp_core::H256;
+	use std::time::Duration;
 
 	fn check_journal(pruning: &RefWindow<H256, H256, TestDb>, db: &TestDb) {
 		let count_insertions = matches!(pruning.queue, DeathRowQueue::Mem { .. });
@@ -907,4 +1270,111 @@ mod tests {
 			assert_eq!(HaveBlock::Yes, pruning.have_block(&block, block));
 		}
 	}
+
+	#[test]
+	fn block_infos_are_journaled() {
+		let mut db = make_db(&[1, 2, 3]);
+		let mut pruning: RefWindow<H256, H256, TestDb> =
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+		let mut commit = make_commit(&[4, 5], &[1]);
+		pruning.note_canonical(&H256::random(), 0, &mut commit).unwrap();
+		db.commit(&commit);
+		let mut commit = make_commit(&[6], &[2]);
+		pruning.note_canonical(&H256::random(), 1, &mut commit).unwrap();
+		db.commit(&commit);
+		assert_eq!(pruning.window_bytes(), 96);
+		assert!(pruning.oldest_age().is_some());
+
+		let restored: RefWindow<H256, H256, TestDb> =
+			RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+		assert_eq!(restored.infos, pruning.infos);
+		assert_eq!(restored.window_bytes(), 96);
+
+		let mut commit = CommitSet::default();
+		pruning.prune_one(&mut commit).unwrap();
+		db.commit(&commit);
+		assert_eq!(pruning.window_bytes(), 32);
+	}
+
+	#[test]
+	fn kept_block_bookkeeping_is_bounded() {
+		let mut db = make_db(&[]);
+		let mut pruning: RefWindow<H256, H256, TestDb> =
+			RefWindow::new(db.clone(), 1, false).unwrap().with_kept_blocks(&[1]).unwrap();
+		let blocks = 4 * KEPT_SNAPSHOT_INTERVAL;
+		for number in 0..blocks {
+			// every block replaces the node inserted by its parent
+			let hash = H256::from_low_u64_be(number);
+			let mut commit = make_commit(&[number + 1], &[number]);
+			pruning.note_canonical(&hash, number, &mut commit).unwrap();
+			commit
+				.meta
+				.inserted
+				.push((to_meta_key(LAST_CANONICAL, &()), (hash, number).encode()));
+			if pruning.window_size() > 1 {
+				pruning.prune_one(&mut commit).unwrap();
+			}
+			db.commit(&commit);
+			assert!(pruning.kept.insertions.len() <= 1);
+		}
+
+		let journals = (0..blocks)
+			.filter(|block| db.get_meta(&to_journal_key(*block)).unwrap().is_some())
+			.count();
+		assert!(journals as u64 <= KEPT_SNAPSHOT_INTERVAL + 1);
+		// only the node of the kept state and the ones of the last block and its parent are left
+		assert!(db.data_eq(&make_db(&[2, blocks - 1, blocks])));
+
+		let restored: RefWindow<H256, H256, TestDb> =
+			RefWindow::new(db.clone(), 1, false).unwrap().with_kept_blocks(&[1]).unwrap();
+		assert_eq!(restored.kept.insertions, pruning.kept.insertions);
+		assert_eq!(restored.kept.snapshot, pruning.kept.snapshot);
+	}
+
+	#[test]
+	fn blocks_without_info_are_expired() {
+		// journaled before the block infos were introduced
+		let mut db = make_db(&[1]);
+		let mut commit = CommitSet::default();
+		let record = JournalRecord::<H256, H256> {
+			hash: H256::random(),
+			inserted: vec![],
+			deleted: vec![H256::from_low_u64_be(1)],
+		};
+		commit.meta.inserted.push((to_journal_key(0), record.encode()));
+		db.commit(&commit);
+
+		// the age doesn't restart with the node
+		for _ in 0..2 {
+			let pruning: RefWindow<H256, H256, TestDb> =
+				RefWindow::new(db.clone(), DEFAULT_MAX_BLOCK_CONSTRAINT, true).unwrap();
+			assert_eq!(pruning.window_size(), 1);
+			assert!(pruning.oldest_age().unwrap() > Duration::from_secs(3600));
+		}
+	}
+
+	#[test]
+	fn translate_journals_drops_cancelled_deletions() {
+		let key = |n: u8| vec![n; 4];
+		let record = |inserted: Vec<Vec<u8>>, deleted: Vec<Vec<u8>>| {
+			JournalRecord { hash: H256::default(), inserted, deleted }.encode()
+		};
+		let mut meta = vec![
+			(to_journal_key(0), record(vec![key(3)], vec![key(1), key(2)])),
+			(to_journal_key(1), record(vec![key(1)], vec![key(3)])),
+			(to_meta_key(LAST_PRUNED, &()), 5u64.encode()),
+		];
+		translate_journals::<H256>(&mut meta, &|key| key[1..].to_vec()).unwrap();
+
+		let decode = |value: &DBValue| {
+			JournalRecord::<H256, Vec<u8>>::decode(&mut value.as_slice()).unwrap()
+		};
+		let first = decode(&meta[0].1);
+		assert!(first.inserted.is_empty());
+		assert_eq!(first.deleted, vec![vec![2; 3]]);
+		let second = decode(&meta[1].1);
+		assert!(second.inserted.is_empty());
+		assert_eq!(second.deleted, vec![vec![3; 3]]);
+		assert_eq!(meta[2].1, 5u64.encode());
+	}
 }
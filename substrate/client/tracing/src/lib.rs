@@ -30,6 +30,7 @@
 
 pub mod block;
 pub mod logging;
+pub mod runtime_metrics;
 
 use rustc_hash::FxHashMap;
 use serde::ser::{Serialize, SerializeMap, Serializer};
@@ -14,8 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-use parking_lot::Mutex;
-use std::sync::OnceLock;
+use parking_lot::{Condvar, Mutex, MutexGuard};
+use std::{
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		OnceLock,
+	},
+	time::{Duration, Instant},
+};
 use tracing_subscriber::{
 	filter::Directive, fmt as tracing_fmt, layer, reload::Handle, EnvFilter, Registry,
 };
@@ -26,6 +32,33 @@ static FILTER_RELOAD_HANDLE: OnceLock<Handle<EnvFilter, SCSubscriber>> = OnceLoc
 static DEFAULT_DIRECTIVES: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
 // Current state of log filter
 static CURRENT_DIRECTIVES: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
+// Directives that are only part of the log filter until they expired
+static TEMPORARY_DIRECTIVES: OnceLock<Mutex<Vec<TemporaryDirectives>>> = OnceLock::new();
+// Identifier of the next temporary directives
+static NEXT_TEMPORARY_ID: AtomicU64 = AtomicU64::new(0);
+// Wakes the thread removing the temporary directives once they expired
+static TEMPORARY_DIRECTIVES_CHANGED: Condvar = Condvar::new();
+// Result of spawning the thread removing the temporary directives, spawned on first use
+static EXPIRY_THREAD: OnceLock<Result<(), String>> = OnceLock::new();
+
+/// A directive of the current log filter, as returned by [`current_directives`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogDirective {
+	/// The directive, e.g. `sync=debug`.
+	pub directive: String,
+	/// Time left until the directive is removed again, if it was added temporarily.
+	pub expires_in: Option<Duration>,
+}
+
+/// Directives added by a single [`add_temporary_directives`] call.
+struct TemporaryDirectives {
+	/// Unique identifier, as several directives may expire at the same instant.
+	id: u64,
+	/// The directives, e.g. `sync=debug,state=trace`.
+	directives: String,
+	/// The instant at which the directives are removed again.
+	expires_at: Instant,
+}
 
 /// Add log filter directive(s) to the defaults
 ///
@@ -48,6 +81,102 @@ pub fn add_directives(directives: &str) {
 		.push(directives.to_owned());
 }
 
+/// Add directives to current directives until `duration` elapsed and reload the log filter.
+///
+/// The log filter is reloaded again once the directives expired. A single thread, spawned on
+/// first use, takes care of removing all expired directives.
+pub fn add_temporary_directives(directives: &str, duration: Duration) -> Result<(), String> {
+	let expires_at = Instant::now()
+		.checked_add(duration)
+		.ok_or_else(|| format!("Log filter duration of {}s is too long", duration.as_secs()))?;
+	add_temporary_directives_until(directives, expires_at)
+}
+
+/// Add directives to current directives until `expires_at` and reload the log filter.
+fn add_temporary_directives_until(directives: &str, expires_at: Instant) -> Result<(), String> {
+	let temporary = TEMPORARY_DIRECTIVES.get_or_init(|| Mutex::new(Vec::new()));
+	EXPIRY_THREAD
+		.get_or_init(|| {
+			std::thread::Builder::new()
+				.name("log-filter-expiry".into())
+				.spawn(move || remove_expired_directives(temporary))
+				.map(drop)
+				.map_err(|e| format!("Unable to schedule the log filter expiry: {}", e))
+		})
+		.clone()?;
+
+	let id = NEXT_TEMPORARY_ID.fetch_add(1, Ordering::Relaxed);
+	temporary.lock().push(TemporaryDirectives {
+		id,
+		directives: directives.to_owned(),
+		expires_at,
+	});
+	if let Err(e) = reload_filter() {
+		temporary.lock().retain(|temporary| temporary.id != id);
+		return Err(e)
+	}
+	TEMPORARY_DIRECTIVES_CHANGED.notify_one();
+	Ok(())
+}
+
+/// Remove the temporary directives once they expired and reload the log filter, waiting for the
+/// next directive to expire in between.
+fn remove_expired_directives(temporary: &Mutex<Vec<TemporaryDirectives>>) -> ! {
+	let mut directives = temporary.lock();
+	loop {
+		let now = Instant::now();
+		let count = directives.len();
+		directives.retain(|temporary| temporary.expires_at > now);
+		if directives.len() != count {
+			// `reload_filter` reads the temporary directives itself.
+			MutexGuard::unlocked(&mut directives, || {
+				if let Err(e) = reload_filter() {
+					log::warn!(target: "tracing", "Unable to remove expired log filter: {}", e);
+				}
+			});
+			continue
+		}
+
+		match directives.iter().map(|temporary| temporary.expires_at).min() {
+			Some(next_expiry) => {
+				TEMPORARY_DIRECTIVES_CHANGED.wait_until(&mut directives, next_expiry);
+			},
+			None => TEMPORARY_DIRECTIVES_CHANGED.wait(&mut directives),
+		}
+	}
+}
+
+/// Returns the directives of the current log filter.
+pub fn current_directives() -> Vec<LogDirective> {
+	let mut directives = Vec::new();
+	if let Some(current_directives) = CURRENT_DIRECTIVES.get() {
+		directives.extend(
+			current_directives
+				.lock()
+				.join(",")
+				.split(',')
+				.filter(|directive| !directive.is_empty())
+				.map(|directive| LogDirective {
+					directive: directive.to_owned(),
+					expires_in: None,
+				}),
+		);
+	}
+	if let Some(temporary_directives) = TEMPORARY_DIRECTIVES.get() {
+		let now = Instant::now();
+		for temporary in temporary_directives.lock().iter() {
+			let expires_in = Some(temporary.expires_at.saturating_duration_since(now));
+			directives.extend(
+				temporary
+					.directives
+					.split(',')
+					.map(|directive| LogDirective { directive: directive.to_owned(), expires_in }),
+			);
+		}
+	}
+	directives
+}
+
 /// Parse `Directive` and add to default directives if successful.
 ///
 /// Ensures the supplied directive will be restored when resetting the log filter.
@@ -60,9 +189,20 @@ pub(crate) fn parse_default_directive(directive: &str) -> super::Result<Directiv
 /// Reload the logging filter with the supplied directives added to the existing directives
 pub fn reload_filter() -> Result<(), String> {
 	let mut env_filter = EnvFilter::default();
-	if let Some(current_directives) = CURRENT_DIRECTIVES.get() {
+	let mut directives = CURRENT_DIRECTIVES.get().map(|d| d.lock().clone()).unwrap_or_default();
+	if let Some(temporary_directives) = TEMPORARY_DIRECTIVES.get() {
+		let now = Instant::now();
+		directives.extend(
+			temporary_directives
+				.lock()
+				.iter()
+				.filter(|temporary| temporary.expires_at > now)
+				.map(|temporary| temporary.directives.clone()),
+		);
+	}
+	if !directives.is_empty() {
 		// Use join and then split in case any directives added together
-		for directive in current_directives.lock().join(",").split(',').map(|d| d.parse()) {
+		for directive in directives.join(",").split(',').map(|d| d.parse()) {
 			match directive {
 				Ok(dir) => env_filter = env_filter.add_directive(dir),
 				Err(invalid_directive) => {
@@ -96,6 +236,9 @@ pub fn reset_log_filter() -> Result<(), String> {
 	let directive = DEFAULT_DIRECTIVES.get_or_init(|| Mutex::new(Vec::new())).lock().clone();
 
 	*CURRENT_DIRECTIVES.get_or_init(|| Mutex::new(Vec::new())).lock() = directive;
+	if let Some(temporary_directives) = TEMPORARY_DIRECTIVES.get() {
+		temporary_directives.lock().clear();
+	}
 	reload_filter()
 }
 
@@ -111,3 +254,30 @@ type SCSubscriber<
 	E = crate::logging::EventFormat,
 	W = crate::logging::DefaultLogger,
 > = layer::Layered<tracing_fmt::Layer<Registry, N, E, W>, Registry>;
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn failed_temporary_directives_only_remove_themselves() {
+		// No logger is initialized in this process, so reloading the log filter fails.
+		assert!(FILTER_RELOAD_HANDLE.get().is_none());
+		let expires_at = Instant::now() + Duration::from_secs(3600);
+		let temporary = TEMPORARY_DIRECTIVES.get_or_init(|| Mutex::new(Vec::new()));
+		temporary.lock().push(TemporaryDirectives {
+			id: NEXT_TEMPORARY_ID.fetch_add(1, Ordering::Relaxed),
+			directives: "first=debug".into(),
+			expires_at,
+		});
+
+		assert!(add_temporary_directives_until("second=trace", expires_at).is_err());
+
+		let directives = current_directives()
+			.into_iter()
+			.filter(|directive| directive.expires_in.is_some())
+			.map(|directive| directive.directive)
+			.collect::<Vec<_>>();
+		assert_eq!(directives, vec!["first=debug"]);
+	}
+}
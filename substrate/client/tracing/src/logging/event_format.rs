@@ -16,9 +16,13 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::logging::fast_local_time::FastLocalTime;
+use crate::logging::{
+	fast_local_time::FastLocalTime,
+	layers::{JsonVisitor, SpanFields, PREFIX_LOG_SPAN},
+};
 use ansi_term::Colour;
 use regex::Regex;
+use serde_json::{Map, Value};
 use std::fmt::{self, Write};
 use tracing::{Event, Level, Subscriber};
 use tracing_log::NormalizeEvent;
@@ -43,6 +47,8 @@ pub struct EventFormat<T = FastLocalTime> {
 	pub enable_color: bool,
 	/// Duplicate INFO, WARN and ERROR messages to stdout.
 	pub dup_to_stdout: bool,
+	/// Output every event as a single line JSON object instead of human readable text.
+	pub json: bool,
 }
 
 impl<T> EventFormat<T>
@@ -62,6 +68,10 @@ where
 		S: Subscriber + for<'a> LookupSpan<'a>,
 		N: for<'a> FormatFields<'a> + 'static,
 	{
+		if self.json {
+			return self.format_event_json(ctx, writer, event)
+		}
+
 		let writer = &mut ControlCodeSanitizer::new(!self.enable_color, writer);
 		let normalized_meta = event.normalized_metadata();
 		let meta = normalized_meta.as_ref().unwrap_or_else(|| event.metadata());
@@ -113,6 +123,78 @@ where
 
 		writer.flush()
 	}
+
+	/// Format the event as a JSON object.
+	///
+	/// Next to the fields of the event, the object contains the fields of all the spans the event
+	/// was emitted in. The `block_hash` and `block_number` fields of the event or of the closest
+	/// span recording them are also copied to the top level.
+	fn format_event_json<'b, S, N>(
+		&self,
+		ctx: CustomFmtContext<'b, S, N>,
+		writer: &mut dyn fmt::Write,
+		event: &Event,
+	) -> fmt::Result
+	where
+		S: Subscriber + for<'a> LookupSpan<'a>,
+		N: for<'a> FormatFields<'a> + 'static,
+	{
+		let normalized_meta = event.normalized_metadata();
+		let meta = normalized_meta.as_ref().unwrap_or_else(|| event.metadata());
+
+		let mut fields = Map::new();
+		event.record(&mut JsonVisitor(&mut fields));
+
+		let mut json = Map::new();
+		json.insert("timestamp".into(), chrono::Utc::now().to_rfc3339().into());
+		json.insert("level".into(), meta.level().to_string().into());
+		json.insert("target".into(), meta.target().into());
+		if let Some(name) = std::thread::current().name() {
+			json.insert("thread".into(), name.into());
+		}
+		for key in ["block_hash", "block_number"] {
+			if let Some(value) = fields.get(key) {
+				json.insert(key.into(), value.clone());
+			}
+		}
+
+		let mut spans = Vec::new();
+		if let Some(span) = ctx.lookup_current() {
+			for span in span.scope() {
+				let exts = span.extensions();
+				let span_fields = exts.get::<SpanFields>().map(|f| &f.0);
+				if span.name() == PREFIX_LOG_SPAN {
+					if let Some(name) = span_fields.and_then(|f| f.get("name")) {
+						json.entry("node").or_insert_with(|| name.clone());
+					}
+					continue
+				}
+
+				let mut span_json = Map::new();
+				span_json.insert("name".into(), span.name().into());
+				if let Some(span_fields) = span_fields {
+					for key in ["block_hash", "block_number"] {
+						if let Some(value) = span_fields.get(key) {
+							json.entry(key).or_insert_with(|| value.clone());
+						}
+					}
+					span_json.extend(span_fields.clone());
+				}
+				spans.push(Value::Object(span_json));
+			}
+		}
+		// Outermost span first, like in the human readable output of `tracing-subscriber`.
+		spans.reverse();
+
+		if let Some(Value::String(message)) = fields.remove("message") {
+			json.insert("message".into(), strip_control_codes(&message).into());
+		}
+		json.insert("fields".into(), fields.into());
+		json.insert("spans".into(), spans.into());
+
+		let line = serde_json::to_string(&json).map_err(|_| fmt::Error)?;
+		writeln!(writer, "{}", line)
+	}
 }
 
 // NOTE: the following code took inspiration from tracing-subscriber
@@ -0,0 +1,89 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+use serde_json::{Map, Value};
+use std::fmt;
+use tracing::{
+	field::{Field, Visit},
+	span::{Attributes, Record},
+	Id, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// The fields recorded on a span, as captured by [`SpanFieldsLayer`].
+#[derive(Debug, Default)]
+pub(crate) struct SpanFields(pub(crate) Map<String, Value>);
+
+/// Visitor collecting fields into a JSON object.
+///
+/// Fields added by `tracing-log` to describe the origin of a `log` record are skipped.
+pub(crate) struct JsonVisitor<'a>(pub(crate) &'a mut Map<String, Value>);
+
+impl<'a> JsonVisitor<'a> {
+	fn insert(&mut self, field: &Field, value: Value) {
+		if !field.name().starts_with("log.") {
+			self.0.insert(field.name().to_owned(), value);
+		}
+	}
+}
+
+impl<'a> Visit for JsonVisitor<'a> {
+	fn record_i64(&mut self, field: &Field, value: i64) {
+		self.insert(field, value.into());
+	}
+
+	fn record_u64(&mut self, field: &Field, value: u64) {
+		self.insert(field, value.into());
+	}
+
+	fn record_bool(&mut self, field: &Field, value: bool) {
+		self.insert(field, value.into());
+	}
+
+	fn record_str(&mut self, field: &Field, value: &str) {
+		self.insert(field, value.into());
+	}
+
+	fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+		self.insert(field, format!("{:?}", value).into());
+	}
+}
+
+/// A `Layer` that captures the fields of every span, which are then added to the log lines by
+/// [`crate::logging::EventFormat`] when it outputs JSON.
+pub struct SpanFieldsLayer;
+
+impl<S> Layer<S> for SpanFieldsLayer
+where
+	S: Subscriber + for<'a> LookupSpan<'a>,
+{
+	fn new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+		let Some(span) = ctx.span(id) else { return };
+
+		let mut fields = SpanFields::default();
+		attrs.record(&mut JsonVisitor(&mut fields.0));
+		span.extensions_mut().insert(fields);
+	}
+
+	fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+		let Some(span) = ctx.span(id) else { return };
+
+		if let Some(fields) = span.extensions_mut().get_mut::<SpanFields>() {
+			values.record(&mut JsonVisitor(&mut fields.0));
+		}
+	}
+}
@@ -65,6 +65,7 @@ pub enum Error {
 	SetGlobalDefaultError(#[from] tracing::subscriber::SetGlobalDefaultError),
 	DirectiveParseError(#[from] tracing_subscriber::filter::ParseError),
 	SetLoggerError(#[from] tracing_log::log_tracer::SetLoggerError),
+	PrometheusError(#[from] prometheus_endpoint::PrometheusError),
 }
 
 macro_rules! enable_log_reloading {
@@ -94,6 +95,8 @@ fn to_log_level_filter(level_filter: Option<LevelFilter>) -> log::LevelFilter {
 fn prepare_subscriber<N, E, F, W>(
 	directives: &str,
 	profiling_targets: Option<&str>,
+	runtime_metrics: Option<u32>,
+	json_output: bool,
 	force_colors: Option<bool>,
 	detailed_output: bool,
 	builder_hook: impl Fn(
@@ -159,6 +162,13 @@ where
 		);
 	}
 
+	// Spans of the runtime are all reported with the `wasm_tracing` target.
+	if runtime_metrics.is_some() {
+		env_filter = env_filter.add_directive(
+			parse_default_directive("wasm_tracing=trace").expect("provided directive is valid"),
+		);
+	}
+
 	let max_level_hint = Layer::<FmtSubscriber>::max_level_hint(&env_filter);
 	let max_level = to_log_level_filter(max_level_hint);
 
@@ -170,7 +180,8 @@ where
 		_ => true,
 	} || detailed_output;
 
-	let enable_color = force_colors.unwrap_or_else(|| atty::is(atty::Stream::Stderr));
+	let enable_color =
+		!json_output && force_colors.unwrap_or_else(|| atty::is(atty::Stream::Stderr));
 	let timer = fast_local_time::FastLocalTime { with_fractional: detailed_output };
 
 	let event_format = EventFormat {
@@ -180,6 +191,7 @@ where
 		display_thread_name: detailed_output,
 		enable_color,
 		dup_to_stdout: !atty::is(atty::Stream::Stderr) && atty::is(atty::Stream::Stdout),
+		json: json_output,
 	};
 	let builder = FmtSubscriber::builder().with_env_filter(env_filter);
 
@@ -191,7 +203,18 @@ where
 
 	let builder = builder_hook(builder);
 
-	let subscriber = builder.finish().with(PrefixLayer);
+	// Other consumers of the runtime spans need them for every block.
+	let runtime_metrics = runtime_metrics
+		.map(|interval| {
+			crate::runtime_metrics::RuntimeMetricsLayer::new(interval, profiling_targets.is_none())
+		})
+		.transpose()?;
+
+	let subscriber = builder
+		.finish()
+		.with(PrefixLayer)
+		.with(json_output.then_some(SpanFieldsLayer))
+		.with(runtime_metrics);
 
 	Ok(subscriber)
 }
@@ -201,6 +224,8 @@ pub struct LoggerBuilder {
 	directives: String,
 	profiling: Option<(crate::TracingReceiver, String)>,
 	custom_profiler: Option<Box<dyn crate::TraceHandler>>,
+	runtime_metrics: Option<u32>,
+	json_output: bool,
 	log_reloading: bool,
 	force_colors: Option<bool>,
 	detailed_output: bool,
@@ -213,6 +238,8 @@ impl LoggerBuilder {
 			directives: directives.into(),
 			profiling: None,
 			custom_profiler: None,
+			runtime_metrics: None,
+			json_output: false,
 			log_reloading: false,
 			force_colors: None,
 			detailed_output: false,
@@ -238,6 +265,22 @@ impl LoggerBuilder {
 		self
 	}
 
+	/// Export per-pallet runtime execution metrics, measuring every `sample_interval`-th block.
+	///
+	/// See [`crate::runtime_metrics`] for details.
+	pub fn with_runtime_metrics(&mut self, sample_interval: Option<u32>) -> &mut Self {
+		self.runtime_metrics = sample_interval;
+		self
+	}
+
+	/// Whether to output every log line as a JSON object.
+	///
+	/// The object includes the fields of the event and of the spans it was emitted in.
+	pub fn with_json_output(&mut self, enabled: bool) -> &mut Self {
+		self.json_output = enabled;
+		self
+	}
+
 	/// Wether or not to disable log reloading.
 	pub fn with_log_reloading(&mut self, enabled: bool) -> &mut Self {
 		self.log_reloading = enabled;
@@ -270,6 +313,8 @@ impl LoggerBuilder {
 				let subscriber = prepare_subscriber(
 					&self.directives,
 					Some(&profiling_targets),
+					self.runtime_metrics,
+					self.json_output,
 					self.force_colors,
 					self.detailed_output,
 					|builder| enable_log_reloading!(builder),
@@ -288,6 +333,8 @@ impl LoggerBuilder {
 				let subscriber = prepare_subscriber(
 					&self.directives,
 					Some(&profiling_targets),
+					self.runtime_metrics,
+					self.json_output,
 					self.force_colors,
 					self.detailed_output,
 					|builder| builder,
@@ -307,6 +354,8 @@ impl LoggerBuilder {
 			let subscriber = prepare_subscriber(
 				&self.directives,
 				None,
+				self.runtime_metrics,
+				self.json_output,
 				self.force_colors,
 				self.detailed_output,
 				|builder| enable_log_reloading!(builder),
@@ -319,6 +368,8 @@ impl LoggerBuilder {
 			let subscriber = prepare_subscriber(
 				&self.directives,
 				None,
+				self.runtime_metrics,
+				self.json_output,
 				self.force_colors,
 				self.detailed_output,
 				|builder| builder,
@@ -344,6 +395,7 @@ mod tests {
 			atomic::{AtomicBool, AtomicUsize, Ordering},
 			Arc,
 		},
+		time::Duration,
 	};
 	use tracing::{metadata::Kind, subscriber::Interest, Callsite, Level, Metadata};
 
@@ -478,6 +530,47 @@ mod tests {
 		log::info!("{}", EXPECTED_LOG_MESSAGE);
 	}
 
+	#[test]
+	fn json_log_lines() {
+		let executable = env::current_exe().unwrap();
+		let output = Command::new(executable)
+			.env("ENABLE_LOGGING", "1")
+			.args(&["--nocapture", "json_log_lines_entrypoint"])
+			.output()
+			.unwrap();
+
+		let output = String::from_utf8(output.stderr).unwrap();
+		let json: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+		assert_eq!(json["level"], "INFO");
+		assert_eq!(json["target"], "test");
+		assert_eq!(json["message"], EXPECTED_LOG_MESSAGE);
+		assert_eq!(json["node"], EXPECTED_NODE_NAME);
+		assert_eq!(json["block_number"], 42);
+		assert_eq!(json["fields"]["peers"], 3);
+		assert_eq!(json["spans"][0]["name"], "import_block");
+		assert_eq!(json["spans"][0]["block_hash"], "0x01");
+	}
+
+	/// This is not an actual test, it is used by the `json_log_lines` test.
+	/// The given test will call the test executable and only execute this one test that
+	/// only prints a log line as JSON from within a span.
+	#[test]
+	fn json_log_lines_entrypoint() {
+		if env::var("ENABLE_LOGGING").is_ok() {
+			let mut builder = LoggerBuilder::new("");
+			builder.with_json_output(true);
+			builder.init().unwrap();
+			json_log_lines_process();
+		}
+	}
+
+	#[crate::logging::prefix_logs_with(EXPECTED_NODE_NAME)]
+	fn json_log_lines_process() {
+		let span = tracing::info_span!("import_block", block_hash = "0x01", block_number = 42u64);
+		let _enter = span.enter();
+		tracing::info!(target: "test", peers = 3u64, "{}", EXPECTED_LOG_MESSAGE);
+	}
+
 	/// This is not an actual test, it is used by the `do_not_write_with_colors_on_tty` test.
 	/// The given test will call the test executable and only execute this one test that
 	/// only prints a log line with some colors in it.
@@ -644,6 +737,29 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn temporary_directives_expire() {
+		run_test_in_another_process("temporary_directives_expire", || {
+			LoggerBuilder::new("").with_log_reloading(true).init().unwrap();
+
+			assert!(add_temporary_directives("test=trace", Duration::MAX).is_err());
+			add_temporary_directives("test=debug", Duration::from_secs(3600)).unwrap();
+			add_temporary_directives("test=trace", Duration::from_millis(100)).unwrap();
+			let temporary_directives = || {
+				current_directives()
+					.into_iter()
+					.filter(|directive| directive.expires_in.is_some())
+					.map(|directive| directive.directive)
+					.collect::<Vec<_>>()
+			};
+			assert_eq!(temporary_directives(), vec!["test=debug", "test=trace"]);
+
+			// only the expired directive is removed.
+			std::thread::sleep(Duration::from_millis(500));
+			assert_eq!(temporary_directives(), vec!["test=debug"]);
+		});
+	}
+
 	#[test]
 	fn control_characters_are_always_stripped_out_from_the_log_messages() {
 		const RAW_LINE: &str = "$$START$$\x1B[1;32mIn\u{202a}\u{202e}\u{2066}\u{2069}ner\n\r\x7ftext!\u{80}\u{9f}\x1B[0m$$END$$";
@@ -0,0 +1,613 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Per-pallet runtime execution metrics.
+//!
+//! [`RuntimeMetricsLayer`] follows the spans emitted by `frame-executive` and by the pallet call
+//! and hook wrappers while a block is imported. It exports how long each pallet call and hook
+//! took, together with the weight a call declared and the weight it reported after dispatch.
+//!
+//! Only every n-th imported block is measured: the runtime spans of all other blocks are disabled,
+//! so the runtime doesn't have to emit them. These spans are only emitted by runtimes built with
+//! the `with-tracing` feature, a warning is logged if none show up.
+
+use prometheus_endpoint::{
+	exponential_buckets, register, CounterVec, HistogramOpts, HistogramVec, Opts, PrometheusError,
+	Registry, U64,
+};
+use sp_tracing::{WasmValue, WasmValuesSet, WASM_NAME_KEY, WASM_TARGET_KEY, WASM_TRACE_IDENTIFIER};
+use std::{
+	sync::{
+		atomic::{AtomicBool, AtomicU32, Ordering},
+		OnceLock,
+	},
+	time::{Duration, Instant},
+};
+use tracing::{
+	field::{Field, Visit},
+	span::{Attributes, Id},
+	subscriber::Interest,
+	Metadata, Subscriber,
+};
+use tracing_subscriber::{
+	layer::{Context, Layer},
+	registry::LookupSpan,
+};
+
+/// Span of the client wrapping the import of a block.
+const IMPORT_BLOCK: &str = "import_block";
+/// Target of the spans emitted by `frame-executive`.
+const EXECUTIVE_TARGET: &str = "frame_executive";
+/// Span wrapping the execution of an imported block.
+const EXECUTE_BLOCK: &str = "execute_block";
+/// Span wrapping a single extrinsic.
+const APPLY_EXTRINSIC: &str = "apply_extrinsic";
+/// Span carrying the declared and the actual weight of the extrinsic being applied.
+const DISPATCH_WEIGHT: &str = "dispatch_weight";
+/// Number of sampled block imports after which we warn if the runtime never emitted a span.
+const SAMPLED_IMPORTS_BEFORE_WARNING: u32 = 16;
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+/// Register the runtime metrics in the given registry.
+///
+/// Does nothing if no [`RuntimeMetricsLayer`] was created.
+pub fn register_metrics(registry: &Registry) -> Result<(), PrometheusError> {
+	match METRICS.get() {
+		Some(metrics) => metrics.register(registry),
+		None => Ok(()),
+	}
+}
+
+#[derive(Clone)]
+struct Metrics {
+	execution_time: HistogramVec,
+	declared_weight: CounterVec<U64>,
+	consumed_weight: CounterVec<U64>,
+	time_to_weight: HistogramVec,
+}
+
+impl Metrics {
+	fn new() -> Result<Self, PrometheusError> {
+		Ok(Self {
+			execution_time: HistogramVec::new(
+				HistogramOpts::new(
+					"substrate_runtime_dispatch_time_seconds",
+					"Time spent in pallet calls and hooks while importing sampled blocks",
+				)
+				.buckets(exponential_buckets(0.000_001, 4.0, 12)?),
+				&["pallet", "call"],
+			)?,
+			declared_weight: CounterVec::new(
+				Opts::new(
+					"substrate_runtime_dispatch_declared_weight_total",
+					"Sum of the ref time weight declared by dispatched calls, in picoseconds",
+				),
+				&["pallet", "call"],
+			)?,
+			consumed_weight: CounterVec::new(
+				Opts::new(
+					"substrate_runtime_dispatch_consumed_weight_total",
+					"Sum of the ref time weight reported by dispatched calls, in picoseconds",
+				),
+				&["pallet", "call"],
+			)?,
+			time_to_weight: HistogramVec::new(
+				HistogramOpts::new(
+					"substrate_runtime_dispatch_time_to_weight_ratio",
+					"Measured execution time of dispatched calls divided by the weight they reported",
+				)
+				.buckets(exponential_buckets(0.125, 2.0, 8)?),
+				&["pallet", "call"],
+			)?,
+		})
+	}
+
+	fn register(&self, registry: &Registry) -> Result<(), PrometheusError> {
+		register(self.execution_time.clone(), registry)?;
+		register(self.declared_weight.clone(), registry)?;
+		register(self.consumed_weight.clone(), registry)?;
+		register(self.time_to_weight.clone(), registry)?;
+		Ok(())
+	}
+
+	fn observe_weight(&self, dispatched: &Dispatched, declared: u64, actual: u64) {
+		let labels = [dispatched.pallet.as_str(), dispatched.call.as_str()];
+		self.declared_weight.with_label_values(&labels).inc_by(declared);
+		self.consumed_weight.with_label_values(&labels).inc_by(actual);
+		if actual > 0 {
+			let time = dispatched.time.as_nanos() as f64 * 1_000.0;
+			self.time_to_weight.with_label_values(&labels).observe(time / actual as f64);
+		}
+	}
+}
+
+/// Pallet call executed by an `apply_extrinsic` span.
+struct Dispatched {
+	pallet: String,
+	call: String,
+	time: Duration,
+}
+
+/// Data tracked for the import of a sampled block and every span opened within it.
+struct RuntimeSpan {
+	name: String,
+	target: String,
+	/// The span this one was opened in.
+	///
+	/// Spans coming from wasm are always registered as root spans, so their parent is taken from
+	/// the span that was entered when they were created.
+	parent: Option<Id>,
+	/// Whether this span is part of `execute_block`.
+	importing: bool,
+	entered: Option<Instant>,
+	dispatched: Option<Dispatched>,
+	/// Declared and actual weight recorded on a `dispatch_weight` span, or reported to the
+	/// `apply_extrinsic` span containing it.
+	weight: Option<(u64, u64)>,
+}
+
+/// Fields of interest recorded on a span.
+#[derive(Default)]
+struct SpanFields {
+	name: Option<String>,
+	target: Option<String>,
+	block_number: Option<u64>,
+	declared: Option<u64>,
+	actual: Option<u64>,
+}
+
+impl Visit for SpanFields {
+	fn record_u64(&mut self, field: &Field, value: u64) {
+		match field.name() {
+			"block_number" => self.block_number = Some(value),
+			"declared" => self.declared = Some(value),
+			"actual" => self.actual = Some(value),
+			_ => {},
+		}
+	}
+
+	fn record_str(&mut self, field: &Field, value: &str) {
+		if field.name() == WASM_NAME_KEY {
+			self.name = Some(value.to_owned());
+		} else if field.name() == WASM_TARGET_KEY {
+			self.target = Some(value.to_owned());
+		}
+	}
+
+	fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+}
+
+/// Declared and actual weight carried by the values of a wasm `dispatch_weight` span.
+fn wasm_weight(values: &WasmValuesSet) -> Option<(u64, u64)> {
+	let value = |name| match values.get(name) {
+		Some(WasmValue::U64(value)) => Some(*value),
+		_ => None,
+	};
+	Some((value("declared")?, value("actual")?))
+}
+
+/// A [`Layer`] exporting the time spent in every pallet call and hook while importing blocks.
+///
+/// Only the outermost pallet span below a `frame-executive` span is measured, so the time of
+/// nested dispatches, e.g. in `utility::batch`, is attributed to the call containing them.
+pub struct RuntimeMetricsLayer {
+	metrics: Metrics,
+	sample_interval: u64,
+	filter_runtime_spans: bool,
+	runtime_spans_seen: AtomicBool,
+	sampled_imports: AtomicU32,
+}
+
+impl RuntimeMetricsLayer {
+	/// Create a new layer measuring the import of every `sample_interval`-th block.
+	///
+	/// If `filter_runtime_spans` is set, the spans of the runtime are disabled outside of the
+	/// measured blocks. It should not be set if other layers consume these spans.
+	///
+	/// The metrics are shared by all layers and need to be registered with [`register_metrics`].
+	pub fn new(sample_interval: u32, filter_runtime_spans: bool) -> Result<Self, PrometheusError> {
+		let metrics = match METRICS.get() {
+			Some(metrics) => metrics.clone(),
+			None => {
+				let metrics = Metrics::new()?;
+				METRICS.get_or_init(|| metrics).clone()
+			},
+		};
+		Ok(Self {
+			metrics,
+			sample_interval: sample_interval.max(1).into(),
+			filter_runtime_spans,
+			runtime_spans_seen: AtomicBool::new(false),
+			sampled_imports: AtomicU32::new(0),
+		})
+	}
+
+	fn note_sampled_import(&self) {
+		let imports = self.sampled_imports.fetch_add(1, Ordering::Relaxed) + 1;
+		if imports == SAMPLED_IMPORTS_BEFORE_WARNING &&
+			!self.runtime_spans_seen.load(Ordering::Relaxed)
+		{
+			log::warn!(
+				"No runtime spans were seen while importing {} blocks, runtime metrics require a \
+				 runtime built with the `with-tracing` feature.",
+				imports,
+			);
+		}
+	}
+}
+
+impl<S> Layer<S> for RuntimeMetricsLayer
+where
+	S: Subscriber + for<'span> LookupSpan<'span>,
+{
+	fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+		if metadata.name() == WASM_TRACE_IDENTIFIER {
+			Interest::sometimes()
+		} else {
+			Interest::always()
+		}
+	}
+
+	fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<S>) -> bool {
+		if metadata.name() != WASM_TRACE_IDENTIFIER {
+			return true
+		}
+
+		self.runtime_spans_seen.store(true, Ordering::Relaxed);
+		!self.filter_runtime_spans ||
+			ctx.lookup_current()
+				.map_or(false, |span| span.extensions().get::<RuntimeSpan>().is_some())
+	}
+
+	fn new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<S>) {
+		let Some(span) = ctx.span(id) else { return };
+
+		let mut fields = SpanFields::default();
+		attrs.record(&mut fields);
+		let metadata = attrs.metadata();
+		let (name, target, parent) = if metadata.name() == WASM_TRACE_IDENTIFIER {
+			let parent =
+				attrs.parent().cloned().or_else(|| ctx.lookup_current().map(|span| span.id()));
+			(fields.name.unwrap_or_default(), fields.target.unwrap_or_default(), parent)
+		} else {
+			let parent = span.parent().map(|span| span.id());
+			(metadata.name().to_owned(), metadata.target().to_owned(), parent)
+		};
+
+		let parent_importing = if name == IMPORT_BLOCK && metadata.name() != WASM_TRACE_IDENTIFIER {
+			match fields.block_number {
+				Some(number) if number % self.sample_interval == 0 => false,
+				_ => return,
+			}
+		} else {
+			// Only spans opened within the import of a sampled block are tracked.
+			let Some(parent) = parent.as_ref().and_then(|parent| ctx.span(parent)) else { return };
+			let Some(importing) = parent.extensions().get::<RuntimeSpan>().map(|p| p.importing)
+			else {
+				return
+			};
+			importing
+		};
+
+		let importing = parent_importing || (target == EXECUTIVE_TARGET && name == EXECUTE_BLOCK);
+		let mut weight = fields.declared.zip(fields.actual);
+		if weight.is_none() && metadata.name() == WASM_TRACE_IDENTIFIER && name == DISPATCH_WEIGHT {
+			weight = sp_tracing::new_wasm_span_values().as_ref().and_then(wasm_weight);
+		}
+		span.extensions_mut().insert(RuntimeSpan {
+			name,
+			target,
+			parent,
+			importing,
+			entered: None,
+			dispatched: None,
+			weight,
+		});
+	}
+
+	fn on_enter(&self, id: &Id, ctx: Context<S>) {
+		if let Some(span) = ctx.span(id) {
+			if let Some(span) = span.extensions_mut().get_mut::<RuntimeSpan>() {
+				span.entered = Some(Instant::now());
+			}
+		}
+	}
+
+	// Spans coming from wasm are entered and exited once, but never closed, so everything is
+	// recorded when a span is exited.
+	fn on_exit(&self, id: &Id, ctx: Context<S>) {
+		let Some(span) = ctx.span(id) else { return };
+		let mut extensions = span.extensions_mut();
+		let Some(exited) = extensions.get_mut::<RuntimeSpan>() else { return };
+		let Some(entered) = exited.entered.take() else { return };
+		let busy = entered.elapsed();
+
+		if !exited.importing {
+			if exited.name == IMPORT_BLOCK {
+				drop(extensions);
+				self.note_sampled_import();
+			}
+			return
+		}
+
+		let Some(parent) = exited.parent.as_ref().and_then(|parent| ctx.span(parent)) else {
+			return
+		};
+
+		if exited.target == EXECUTIVE_TARGET {
+			if exited.name == DISPATCH_WEIGHT {
+				if let Some(parent) = parent.extensions_mut().get_mut::<RuntimeSpan>() {
+					parent.weight = exited.weight;
+				}
+			} else if let (Some(dispatched), Some((declared, actual))) =
+				(exited.dispatched.take(), exited.weight)
+			{
+				self.metrics.observe_weight(&dispatched, declared, actual);
+			}
+			return
+		}
+
+		let mut parent_extensions = parent.extensions_mut();
+		let Some(parent) = parent_extensions.get_mut::<RuntimeSpan>() else { return };
+		if parent.target != EXECUTIVE_TARGET {
+			return
+		}
+
+		let pallet = exited.target.split("::").next().unwrap_or_default().to_owned();
+		self.metrics
+			.execution_time
+			.with_label_values(&[pallet.as_str(), exited.name.as_str()])
+			.observe(busy.as_secs_f64());
+		if parent.name == APPLY_EXTRINSIC {
+			parent.dispatched = Some(Dispatched { pallet, call: exited.name.clone(), time: busy });
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_tracing::{WasmEntryAttributes, WasmFieldName, WasmLevel, WasmMetadata};
+	use tracing_subscriber::layer::SubscriberExt;
+
+	fn setup_subscriber() -> (impl Subscriber + Send + Sync, Metrics) {
+		let mut layer = RuntimeMetricsLayer::new(10, true).unwrap();
+		let metrics = Metrics::new().unwrap();
+		layer.metrics = metrics.clone();
+		let subscriber = tracing_subscriber::fmt()
+			.with_max_level(tracing::Level::TRACE)
+			.with_writer(std::io::sink)
+			.finish()
+			.with(layer);
+		(subscriber, metrics)
+	}
+
+	fn wasm_metadata(name: &str, target: &str) -> WasmMetadata {
+		WasmMetadata {
+			name: name.as_bytes().to_vec(),
+			target: target.as_bytes().to_vec(),
+			level: WasmLevel::TRACE,
+			..Default::default()
+		}
+	}
+
+	/// Open and enter a span the way `sp-io` does for spans coming from wasm.
+	fn enter_wasm_span(name: &str, target: &str, params: Vec<(&str, u64)>) -> u64 {
+		let span: tracing::Span = WasmEntryAttributes {
+			parent_id: None,
+			metadata: wasm_metadata(name, target),
+			fields: params
+				.into_iter()
+				.map(|(k, v)| (WasmFieldName::from(k), Some(WasmValue::from(v))))
+				.collect::<Vec<_>>()
+				.into(),
+		}
+		.into();
+		let id = span.id().unwrap();
+		tracing::dispatcher::get_default(|d| {
+			let id = d.clone_span(&id);
+			d.enter(&id);
+			id.into_u64()
+		})
+	}
+
+	fn exit_wasm_span(id: u64) {
+		tracing::dispatcher::get_default(|d| d.exit(&Id::from_u64(id)));
+	}
+
+	fn wasm_span_enabled() -> bool {
+		let metadata = wasm_metadata(EXECUTE_BLOCK, EXECUTIVE_TARGET);
+		let metadata: &Metadata<'static> = (&metadata).into();
+		tracing::dispatcher::get_default(|d| d.enabled(metadata))
+	}
+
+	fn apply_transfer() {
+		let apply = enter_wasm_span(APPLY_EXTRINSIC, EXECUTIVE_TARGET, vec![]);
+		let call = enter_wasm_span("transfer", "pallet_balances::pallet", vec![]);
+		exit_wasm_span(call);
+		let weight = enter_wasm_span(
+			DISPATCH_WEIGHT,
+			EXECUTIVE_TARGET,
+			vec![("declared", 10_000), ("actual", 8_000)],
+		);
+		exit_wasm_span(weight);
+		exit_wasm_span(apply);
+	}
+
+	fn import_block(number: u64) {
+		tracing::info_span!("import_block", block_number = number).in_scope(|| {
+			if !wasm_span_enabled() {
+				return
+			}
+			let block = enter_wasm_span(EXECUTE_BLOCK, EXECUTIVE_TARGET, vec![]);
+			let init = enter_wasm_span("init_block", EXECUTIVE_TARGET, vec![]);
+			let hook = enter_wasm_span("on_initialize", "pallet_timestamp::pallet", vec![]);
+			exit_wasm_span(hook);
+			exit_wasm_span(init);
+			apply_transfer();
+			exit_wasm_span(block);
+		});
+	}
+
+	#[test]
+	fn records_wasm_spans_of_sampled_blocks() {
+		let (subscriber, metrics) = setup_subscriber();
+		let _guard = tracing::subscriber::set_default(subscriber);
+
+		import_block(20);
+
+		let labels = ["pallet_balances", "transfer"];
+		assert_eq!(metrics.execution_time.with_label_values(&labels).get_sample_count(), 1);
+		assert_eq!(metrics.declared_weight.with_label_values(&labels).get(), 10_000);
+		assert_eq!(metrics.consumed_weight.with_label_values(&labels).get(), 8_000);
+		assert_eq!(metrics.time_to_weight.with_label_values(&labels).get_sample_count(), 1);
+		assert_eq!(
+			metrics
+				.execution_time
+				.with_label_values(&["pallet_timestamp", "on_initialize"])
+				.get_sample_count(),
+			1,
+		);
+	}
+
+	/// A subscriber that can't be downcast to the registry it wraps.
+	struct Wrapped<S>(S);
+
+	impl<S: Subscriber> Subscriber for Wrapped<S> {
+		fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+			self.0.register_callsite(metadata)
+		}
+
+		fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+			self.0.enabled(metadata)
+		}
+
+		fn new_span(&self, span: &Attributes<'_>) -> Id {
+			self.0.new_span(span)
+		}
+
+		fn record(&self, span: &Id, values: &tracing::span::Record<'_>) {
+			self.0.record(span, values)
+		}
+
+		fn record_follows_from(&self, span: &Id, follows: &Id) {
+			self.0.record_follows_from(span, follows)
+		}
+
+		fn event(&self, event: &tracing::Event<'_>) {
+			self.0.event(event)
+		}
+
+		fn enter(&self, span: &Id) {
+			self.0.enter(span)
+		}
+
+		fn exit(&self, span: &Id) {
+			self.0.exit(span)
+		}
+
+		fn clone_span(&self, id: &Id) -> Id {
+			self.0.clone_span(id)
+		}
+
+		fn try_close(&self, id: Id) -> bool {
+			self.0.try_close(id)
+		}
+
+		fn current_span(&self) -> tracing::span::Current {
+			self.0.current_span()
+		}
+	}
+
+	#[test]
+	fn records_wasm_weights_with_any_subscriber() {
+		let (subscriber, metrics) = setup_subscriber();
+		let _guard = tracing::subscriber::set_default(Wrapped(subscriber));
+
+		import_block(20);
+
+		let labels = ["pallet_balances", "transfer"];
+		assert_eq!(metrics.declared_weight.with_label_values(&labels).get(), 10_000);
+		assert_eq!(metrics.consumed_weight.with_label_values(&labels).get(), 8_000);
+	}
+
+	#[test]
+	fn disables_runtime_spans_of_other_blocks() {
+		let (subscriber, metrics) = setup_subscriber();
+		let _guard = tracing::subscriber::set_default(subscriber);
+
+		import_block(21);
+		assert!(!wasm_span_enabled());
+		tracing::info_span!("import_block", block_number = 30u64)
+			.in_scope(|| assert!(wasm_span_enabled()));
+
+		let labels = ["pallet_balances", "transfer"];
+		assert_eq!(metrics.execution_time.with_label_values(&labels).get_sample_count(), 0);
+		assert_eq!(metrics.declared_weight.with_label_values(&labels).get(), 0);
+	}
+
+	#[test]
+	fn ignores_extrinsics_outside_of_block_execution() {
+		let (subscriber, metrics) = setup_subscriber();
+		let _guard = tracing::subscriber::set_default(subscriber);
+
+		tracing::info_span!("import_block", block_number = 10u64).in_scope(apply_transfer);
+
+		let labels = ["pallet_balances", "transfer"];
+		assert_eq!(metrics.execution_time.with_label_values(&labels).get_sample_count(), 0);
+		assert_eq!(metrics.declared_weight.with_label_values(&labels).get(), 0);
+	}
+
+	#[test]
+	fn nested_dispatches_are_attributed_to_the_outer_call() {
+		let (subscriber, metrics) = setup_subscriber();
+		let _guard = tracing::subscriber::set_default(subscriber);
+
+		tracing::info_span!("import_block", block_number = 0u64).in_scope(|| {
+			tracing::info_span!(target: "frame_executive", "execute_block").in_scope(|| {
+				tracing::info_span!(target: "frame_executive", "apply_extrinsic").in_scope(|| {
+					tracing::trace_span!(target: "pallet_utility::pallet", "batch").in_scope(
+						|| {
+							tracing::trace_span!(target: "pallet_balances::pallet", "transfer")
+								.in_scope(|| {});
+						},
+					);
+					tracing::trace_span!(
+						target: "frame_executive",
+						"dispatch_weight",
+						declared = 5u64,
+						actual = 3u64
+					)
+					.in_scope(|| {});
+				});
+			});
+		});
+
+		let labels = ["pallet_utility", "batch"];
+		assert_eq!(metrics.execution_time.with_label_values(&labels).get_sample_count(), 1);
+		assert_eq!(metrics.consumed_weight.with_label_values(&labels).get(), 3);
+		assert_eq!(
+			metrics
+				.execution_time
+				.with_label_values(&["pallet_balances", "transfer"])
+				.get_sample_count(),
+			0,
+		);
+	}
+}
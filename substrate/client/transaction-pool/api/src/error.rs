@@ -69,6 +69,33 @@ pub enum Error {
 
 	#[error("The pool is not accepting future transactions")]
 	RejectedFutureTransaction,
+
+	#[error("The sender has too many transactions in the pool")]
+	SenderLimitReached,
+}
+
+impl Error {
+	/// Returns true if the transaction could be re-submitted to the pool in the future.
+	///
+	/// For example, `Error::ImmediatelyDropped` is retriable, because the transaction
+	/// may enter the pool if there is space for it in the future.
+	pub fn is_retriable(&self) -> bool {
+		matches!(
+			self,
+			// An invalid transaction is temporarily banned, however it can
+			// become valid at a later time.
+			Error::TemporarilyBanned |
+				// The pool is full at the moment.
+				Error::ImmediatelyDropped |
+				// The block id is not known to the pool.
+				// The node might be lagging behind, or during a warp sync.
+				Error::InvalidBlockId(_) |
+				// The pool is configured to not accept future transactions.
+				Error::RejectedFutureTransaction |
+				// The sender may submit again once its transactions are included.
+				Error::SenderLimitReached
+		)
+	}
 }
 
 /// Transaction pool error conversion.
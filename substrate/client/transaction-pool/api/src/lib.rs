@@ -131,6 +131,37 @@ pub enum TransactionStatus<Hash, BlockHash> {
 	Invalid,
 }
 
+impl<Hash, BlockHash> TransactionStatus<Hash, BlockHash> {
+	/// Returns true if this is the last event emitted by [`TransactionStatusStream`].
+	pub fn is_final(&self) -> bool {
+		matches!(
+			self,
+			Self::Usurped(_) |
+				Self::Finalized(_) |
+				Self::FinalityTimeout(_) |
+				Self::Invalid |
+				Self::Dropped
+		)
+	}
+
+	/// Returns true if the transaction could be re-submitted to the pool in the future.
+	///
+	/// For example, `TransactionStatus::Dropped` is retriable, because the transaction
+	/// may enter the pool if there is space for it in the future.
+	pub fn is_retriable(&self) -> bool {
+		matches!(
+			self,
+			// The number of finality watchers has been reached.
+			Self::FinalityTimeout(_) |
+				// An invalid transaction might be valid at a later time.
+				Self::Invalid |
+				// The transaction was dropped because of the limits of the pool.
+				// It can reenter the pool when other transactions are removed / finalized.
+				Self::Dropped
+		)
+	}
+}
+
 /// The stream of transaction events.
 pub type TransactionStatusStream<Hash, BlockHash> =
 	dyn Stream<Item = TransactionStatus<Hash, BlockHash>> + Send;
@@ -221,6 +252,18 @@ pub trait TransactionPool: Send + Sync {
 		xt: TransactionFor<Self>,
 	) -> PoolFuture<Pin<Box<TransactionStatusStreamFor<Self>>>, Self::Error>;
 
+	/// Returns a future that imports one unverified transaction received from the network.
+	///
+	/// Unlike the transactions submitted to this node, such transactions can't use the part of
+	/// the pool capacity reserved for them.
+	fn submit_from_network(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		xt: TransactionFor<Self>,
+	) -> PoolFuture<TxHash<Self>, Self::Error> {
+		self.submit_one(at, TransactionSource::External, xt)
+	}
+
 	// *** Block production / Networking
 	/// Get an iterator for ready transactions ordered by priority.
 	///
@@ -237,6 +280,26 @@ pub trait TransactionPool: Send + Sync {
 		>,
 	>;
 
+	/// Get an iterator for ready transactions ordered by priority, for building on top of the
+	/// given block.
+	///
+	/// Pools that keep a separate state for every fork return the transactions that are ready
+	/// on top of `hash`. The default implementation ignores the hash and falls back to
+	/// [`TransactionPool::ready_at`].
+	fn ready_at_block(
+		&self,
+		_hash: <Self::Block as BlockT>::Hash,
+		number: NumberFor<Self::Block>,
+	) -> Pin<
+		Box<
+			dyn Future<
+					Output = Box<dyn ReadyTransactions<Item = Arc<Self::InPoolTransaction>> + Send>,
+				> + Send,
+		>,
+	> {
+		self.ready_at(number)
+	}
+
 	/// Get an iterator for ready transactions ordered by priority.
 	fn ready(&self) -> Box<dyn ReadyTransactions<Item = Arc<Self::InPoolTransaction>> + Send>;
 
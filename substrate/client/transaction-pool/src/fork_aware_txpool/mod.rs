@@ -0,0 +1,63 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Fork-aware transaction pool.
+//!
+//! Unlike [`BasicPool`](crate::BasicPool), which keeps a single validated state that follows
+//! the best block, the fork-aware pool keeps a separate *view* for every leaf of the tree of
+//! blocks it was asked to build on or which became the best block. Each view is an independent
+//! `graph::Pool` holding the transactions validated against the state of its block.
+//!
+//! ## Views
+//!
+//! A new view is created by cloning the view of the nearest ancestor block and pruning the
+//! transactions included in the blocks enacted in between. Transactions from the mempool that
+//! are not yet known to the view are then validated against its block. If there is no ancestor
+//! view, the view is populated from the mempool only. Once created, the view replaces the views
+//! of its ancestors, as they are no longer leaves.
+//!
+//! Block authors obtain the ready set for the exact parent they build on via
+//! [`TransactionPool::ready_at_block`](sc_transaction_pool_api::TransactionPool::ready_at_block),
+//! so switching between forks never requires re-validating the whole pool.
+//!
+//! Views that do not descend from the most recently finalized block are dropped on finality.
+//!
+//! ## Mempool
+//!
+//! All submitted transactions are kept in a single mempool which is the source of truth for
+//! new views. Transactions leave the mempool when they are finalized, when they are not valid
+//! in any of the remaining views after finalization, or when they are removed as invalid.
+//!
+//! ## Transaction status
+//!
+//! Watched transactions receive a single aggregated stream of events:
+//! - `Ready`/`Future` reflect the state of the transaction in the best view,
+//! - `InBlock` is reported once for every block including the transaction on the chain of any view,
+//!   so a transaction included on several forks is reported for each of them,
+//! - `Retracted` is reported when a block previously reported in `InBlock` is retracted from the
+//!   best chain,
+//! - `Finalized` carries the index of the transaction within the finalized block,
+//! - `Invalid` is reported when the transaction is not valid in any of the views.
+
+mod multi_view_listener;
+mod tx_mem_pool;
+mod txpool;
+mod view;
+mod view_store;
+
+pub use txpool::{ForkAwareTxPool, FullForkAwarePool};
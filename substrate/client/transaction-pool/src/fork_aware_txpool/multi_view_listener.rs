@@ -0,0 +1,172 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Aggregated status streams of watched transactions.
+//!
+//! Every view of the fork-aware pool has its own idea about the state of a transaction. The
+//! listener merges them into a single stream per transaction, avoiding repeated `Ready` and
+//! `Future` events, reporting every including block only once and closing the stream once a
+//! final event was sent.
+
+use std::{
+	collections::{HashMap, HashSet},
+	pin::Pin,
+};
+
+use parking_lot::Mutex;
+use sc_transaction_pool_api::{TransactionStatus, TransactionStatusStream, TxIndex};
+use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedSender};
+
+use super::view::TxState;
+use crate::{
+	graph::{BlockHash, ChainApi, ExtrinsicHash},
+	LOG_TARGET,
+};
+
+/// Stream of status events of a single watched transaction.
+pub(super) type TxStatusStream<Api> = TransactionStatusStream<ExtrinsicHash<Api>, BlockHash<Api>>;
+
+struct Controller<Api: ChainApi> {
+	sender: TracingUnboundedSender<TransactionStatus<ExtrinsicHash<Api>, BlockHash<Api>>>,
+	/// The last `Ready`/`Future` state reported, cleared by block related events.
+	last_state: Option<TxState>,
+	/// The blocks reported in `InBlock` events that were not retracted since.
+	in_blocks: HashSet<BlockHash<Api>>,
+}
+
+/// Keeps the status senders of all watched transactions.
+pub(super) struct MultiViewListener<Api: ChainApi> {
+	controllers: Mutex<HashMap<ExtrinsicHash<Api>, Controller<Api>>>,
+}
+
+impl<Api: ChainApi> Default for MultiViewListener<Api> {
+	fn default() -> Self {
+		Self { controllers: Default::default() }
+	}
+}
+
+impl<Api: ChainApi> MultiViewListener<Api> {
+	/// Creates a new status stream for the given transaction.
+	pub(super) fn create_watcher(&self, hash: ExtrinsicHash<Api>) -> Pin<Box<TxStatusStream<Api>>> {
+		let (sender, receiver) = tracing_unbounded("mpsc_txpool_watcher", 100_000);
+		self.controllers
+			.lock()
+			.insert(hash, Controller { sender, last_state: None, in_blocks: Default::default() });
+		Box::pin(receiver)
+	}
+
+	/// Stops watching the transaction without sending any further event.
+	pub(super) fn remove_watcher(&self, hash: &ExtrinsicHash<Api>) {
+		self.controllers.lock().remove(hash);
+	}
+
+	fn send(
+		&self,
+		hash: &ExtrinsicHash<Api>,
+		status: TransactionStatus<ExtrinsicHash<Api>, BlockHash<Api>>,
+	) {
+		let mut controllers = self.controllers.lock();
+		let Some(controller) = controllers.get_mut(hash) else { return };
+
+		let state = match status {
+			TransactionStatus::Ready => Some(TxState::Ready),
+			TransactionStatus::Future => Some(TxState::Future),
+			_ => None,
+		};
+		if state.is_some() {
+			if controller.last_state == state {
+				return
+			}
+			controller.last_state = state;
+		} else if !matches!(status, TransactionStatus::Broadcast(_)) {
+			controller.last_state = None;
+		}
+
+		log::trace!(target: LOG_TARGET, "[{:?}] Sending status {:?}", hash, status);
+		let is_final = status.is_final();
+		if controller.sender.unbounded_send(status).is_err() || is_final {
+			controllers.remove(hash);
+		}
+	}
+
+	/// Reports the state of the transaction in the best view.
+	pub(super) fn state_changed(&self, hash: &ExtrinsicHash<Api>, state: TxState) {
+		match state {
+			TxState::Ready => self.send(hash, TransactionStatus::Ready),
+			TxState::Future => self.send(hash, TransactionStatus::Future),
+		}
+	}
+
+	/// The transaction was broadcast to the given peers.
+	pub(super) fn broadcasted(&self, hash: &ExtrinsicHash<Api>, peers: Vec<String>) {
+		self.send(hash, TransactionStatus::Broadcast(peers));
+	}
+
+	/// The transaction was included in a block of one of the forks.
+	///
+	/// Does nothing if the block was already reported.
+	pub(super) fn in_block(
+		&self,
+		hash: &ExtrinsicHash<Api>,
+		block: BlockHash<Api>,
+		index: TxIndex,
+	) {
+		let newly_reported = self
+			.controllers
+			.lock()
+			.get_mut(hash)
+			.map_or(false, |controller| controller.in_blocks.insert(block));
+		if newly_reported {
+			self.send(hash, TransactionStatus::InBlock((block, index)));
+		}
+	}
+
+	/// The block including the transaction is no longer part of the best chain.
+	///
+	/// Does nothing if the block was not reported in an `InBlock` event.
+	pub(super) fn retracted(&self, hash: &ExtrinsicHash<Api>, block: BlockHash<Api>) {
+		let was_reported = self
+			.controllers
+			.lock()
+			.get_mut(hash)
+			.map_or(false, |controller| controller.in_blocks.remove(&block));
+		if was_reported {
+			self.send(hash, TransactionStatus::Retracted(block));
+		}
+	}
+
+	/// The block including the transaction was finalized.
+	pub(super) fn finalized(
+		&self,
+		hash: &ExtrinsicHash<Api>,
+		block: BlockHash<Api>,
+		index: TxIndex,
+	) {
+		self.send(hash, TransactionStatus::Finalized((block, index)));
+	}
+
+	/// The transaction is not valid in any view.
+	pub(super) fn invalid(&self, hash: &ExtrinsicHash<Api>) {
+		self.send(hash, TransactionStatus::Invalid);
+	}
+
+	/// Removes the senders whose receivers were dropped.
+	pub(super) fn remove_closed(&self) {
+		self.controllers.lock().retain(|_, controller| !controller.sender.is_closed());
+	}
+}
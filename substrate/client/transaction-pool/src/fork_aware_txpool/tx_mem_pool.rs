@@ -0,0 +1,210 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Mempool of the fork-aware transaction pool.
+//!
+//! Holds every transaction submitted to the pool, regardless of its validity in the particular
+//! views. New views are populated from here.
+
+use std::{collections::HashMap, sync::Arc};
+
+use parking_lot::RwLock;
+use sc_transaction_pool_api::{error::Error as TxPoolError, TransactionSource};
+
+use codec::Encode;
+
+use crate::{
+	graph::{ChainApi, ExtrinsicFor, ExtrinsicHash},
+	journal::Journaled,
+};
+
+/// A transaction stored in the mempool.
+pub(super) struct TxInMemPool<Api: ChainApi> {
+	/// The transaction itself.
+	pub(super) tx: ExtrinsicFor<Api>,
+	/// Size of the encoded transaction.
+	pub(super) bytes: usize,
+	/// Source of the transaction.
+	pub(super) source: TransactionSource,
+	/// Whether the transaction was received from the network.
+	pub(super) from_network: bool,
+	/// Whether the transaction is watched by a `submit_and_watch` subscriber.
+	pub(super) watched: bool,
+}
+
+struct Inner<Api: ChainApi> {
+	transactions: HashMap<ExtrinsicHash<Api>, TxInMemPool<Api>>,
+	total_bytes: usize,
+}
+
+/// Bounded set of all transactions known to the fork-aware pool.
+pub(super) struct TxMemPool<Api: ChainApi> {
+	api: Arc<Api>,
+	inner: RwLock<Inner<Api>>,
+	max_count: usize,
+	max_bytes: usize,
+}
+
+impl<Api: ChainApi> TxMemPool<Api> {
+	/// Creates a new mempool holding at most `max_count` transactions of `max_bytes` total size.
+	pub(super) fn new(api: Arc<Api>, max_count: usize, max_bytes: usize) -> Self {
+		Self {
+			api,
+			inner: RwLock::new(Inner { transactions: Default::default(), total_bytes: 0 }),
+			max_count,
+			max_bytes,
+		}
+	}
+
+	fn try_insert(
+		&self,
+		source: TransactionSource,
+		from_network: bool,
+		tx: ExtrinsicFor<Api>,
+		watched: bool,
+	) -> Result<ExtrinsicHash<Api>, Api::Error> {
+		let (hash, bytes) = self.api.hash_and_length(&tx);
+		let mut inner = self.inner.write();
+
+		if inner.transactions.contains_key(&hash) {
+			return Err(TxPoolError::AlreadyImported(Box::new(hash)).into())
+		}
+
+		if inner.transactions.len() >= self.max_count ||
+			inner.total_bytes.saturating_add(bytes) > self.max_bytes
+		{
+			return Err(TxPoolError::ImmediatelyDropped.into())
+		}
+
+		inner.total_bytes += bytes;
+		inner
+			.transactions
+			.insert(hash, TxInMemPool { tx, bytes, source, from_network, watched });
+		Ok(hash)
+	}
+
+	/// Adds unwatched transactions to the mempool.
+	pub(super) fn extend_unwatched(
+		&self,
+		source: TransactionSource,
+		xts: impl IntoIterator<Item = ExtrinsicFor<Api>>,
+	) -> Vec<Result<ExtrinsicHash<Api>, Api::Error>> {
+		xts.into_iter().map(|xt| self.try_insert(source, false, xt, false)).collect()
+	}
+
+	/// Adds unwatched transactions received from the network to the mempool.
+	pub(super) fn extend_from_network(
+		&self,
+		xts: impl IntoIterator<Item = ExtrinsicFor<Api>>,
+	) -> Vec<Result<ExtrinsicHash<Api>, Api::Error>> {
+		xts.into_iter()
+			.map(|xt| self.try_insert(TransactionSource::External, true, xt, false))
+			.collect()
+	}
+
+	/// Adds a watched transaction to the mempool.
+	pub(super) fn push_watched(
+		&self,
+		source: TransactionSource,
+		xt: ExtrinsicFor<Api>,
+	) -> Result<ExtrinsicHash<Api>, Api::Error> {
+		self.try_insert(source, false, xt, true)
+	}
+
+	/// Removes the transaction from the mempool.
+	pub(super) fn remove(&self, hash: &ExtrinsicHash<Api>) -> Option<TxInMemPool<Api>> {
+		let mut inner = self.inner.write();
+		let removed = inner.transactions.remove(hash);
+		if let Some(ref tx) = removed {
+			inner.total_bytes -= tx.bytes;
+		}
+		removed
+	}
+
+	/// Removes all transactions for which `f` returns `false` and returns them.
+	pub(super) fn retain(
+		&self,
+		mut f: impl FnMut(&ExtrinsicHash<Api>, &TxInMemPool<Api>) -> bool,
+	) -> Vec<(ExtrinsicHash<Api>, TxInMemPool<Api>)> {
+		let mut inner = self.inner.write();
+		let to_remove = inner
+			.transactions
+			.iter()
+			.filter(|(hash, tx)| !f(*hash, *tx))
+			.map(|(hash, _)| *hash)
+			.collect::<Vec<_>>();
+
+		to_remove
+			.into_iter()
+			.filter_map(|hash| {
+				let tx = inner.transactions.remove(&hash)?;
+				inner.total_bytes -= tx.bytes;
+				Some((hash, tx))
+			})
+			.collect()
+	}
+
+	/// Returns `true` if the transaction is in the mempool.
+	pub(super) fn contains(&self, hash: &ExtrinsicHash<Api>) -> bool {
+		self.inner.read().transactions.contains_key(hash)
+	}
+
+	/// Returns all transactions of the mempool with their source and whether they were received
+	/// from the network.
+	pub(super) fn transactions(
+		&self,
+	) -> Vec<(ExtrinsicHash<Api>, TransactionSource, bool, ExtrinsicFor<Api>)> {
+		self.inner
+			.read()
+			.transactions
+			.iter()
+			.map(|(hash, tx)| (*hash, tx.source, tx.from_network, tx.tx.clone()))
+			.collect()
+	}
+
+	/// Returns the hashes of watched transactions.
+	pub(super) fn watched(&self) -> Vec<ExtrinsicHash<Api>> {
+		self.inner
+			.read()
+			.transactions
+			.iter()
+			.filter(|(_, tx)| tx.watched)
+			.map(|(hash, _)| *hash)
+			.collect()
+	}
+
+	/// Returns the number of transactions in the mempool.
+	pub(super) fn len(&self) -> usize {
+		self.inner.read().transactions.len()
+	}
+}
+
+impl<Api: ChainApi> Journaled<Api> for TxMemPool<Api> {
+	fn hash_of(&self, xt: &ExtrinsicFor<Api>) -> ExtrinsicHash<Api> {
+		self.api.hash_and_length(xt).0
+	}
+
+	fn pending(&self) -> Vec<(ExtrinsicHash<Api>, TransactionSource, Vec<u8>)> {
+		self.inner
+			.read()
+			.transactions
+			.iter()
+			.map(|(hash, tx)| (*hash, tx.source, tx.tx.encode()))
+			.collect()
+	}
+}
@@ -0,0 +1,769 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The fork-aware transaction pool.
+
+use std::{collections::HashMap, pin::Pin, sync::Arc};
+
+use async_trait::async_trait;
+use futures::{
+	channel::mpsc::{channel, Sender},
+	prelude::*,
+};
+use parking_lot::Mutex;
+use prometheus_endpoint::Registry as PrometheusRegistry;
+use sc_transaction_pool_api::{
+	error::Error as TxPoolError, ChainEvent, ImportNotificationStream, MaintainedTransactionPool,
+	PoolFuture, PoolStatus, TransactionFor, TransactionPool, TransactionSource,
+	TransactionStatusStreamFor, TxHash,
+};
+use sp_blockchain::{HashAndNumber, TreeRoute};
+use sp_core::traits::SpawnEssentialNamed;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Extrinsic, NumberFor},
+};
+
+use super::{
+	multi_view_listener::MultiViewListener,
+	tx_mem_pool::TxMemPool,
+	view::{TxState, View},
+	view_store::{block_body, ViewStore},
+};
+use crate::{
+	api::FullChainApi,
+	enactment_state::{EnactmentAction, EnactmentState},
+	error,
+	graph::{self, ExtrinsicHash, IsValidator},
+	journal,
+	metrics::MetricsLink as PrometheusMetrics,
+	PolledIterator, ReadyIteratorFor, ReadyPoll, LOG_TARGET,
+};
+
+/// A fork-aware transaction pool for a full node.
+pub type FullForkAwarePool<Block, Client> = ForkAwareTxPool<FullChainApi<Client, Block>, Block>;
+
+/// Transaction pool keeping a separate view for every fork.
+///
+/// See the [module level documentation](super) for details.
+pub struct ForkAwareTxPool<PoolApi, Block>
+where
+	Block: BlockT,
+	PoolApi: graph::ChainApi<Block = Block>,
+{
+	api: Arc<PoolApi>,
+	mempool: Arc<TxMemPool<PoolApi>>,
+	view_store: Arc<ViewStore<PoolApi>>,
+	listener: Arc<MultiViewListener<PoolApi>>,
+	ready_poll: Arc<Mutex<ReadyPoll<ReadyIteratorFor<PoolApi>, Block>>>,
+	import_notification_sinks: Arc<Mutex<Vec<Sender<ExtrinsicHash<PoolApi>>>>>,
+	metrics: PrometheusMetrics,
+	enactment_state: Arc<Mutex<EnactmentState<Block>>>,
+	journal: Option<Arc<journal::Journal<PoolApi>>>,
+}
+
+impl<PoolApi, Block> ForkAwareTxPool<PoolApi, Block>
+where
+	Block: BlockT,
+	PoolApi: graph::ChainApi<Block = Block> + 'static,
+{
+	/// Create new fork-aware transaction pool with provided api, for tests.
+	pub fn new_test(
+		pool_api: Arc<PoolApi>,
+		best_block_hash: Block::Hash,
+		finalized_hash: Block::Hash,
+	) -> Self {
+		Self::new(Default::default(), true.into(), pool_api, None, best_block_hash, finalized_hash)
+	}
+
+	/// Create new fork-aware transaction pool with provided api.
+	///
+	/// The mempool is limited by the sum of the ready and future limits of `options`, which
+	/// also apply to every single view.
+	///
+	/// If the journal is enabled, the journaled transactions are restored into the mempool and
+	/// validated once the views are created. The journal is only written when the pool is
+	/// dropped, use [`Self::new_full`] to also write it periodically.
+	pub fn new(
+		options: graph::Options,
+		is_validator: IsValidator,
+		pool_api: Arc<PoolApi>,
+		prometheus: Option<&PrometheusRegistry>,
+		best_block_hash: Block::Hash,
+		finalized_hash: Block::Hash,
+	) -> Self {
+		let mempool = TxMemPool::new(
+			pool_api.clone(),
+			options.ready.count.saturating_add(options.future.count),
+			options.ready.total_bytes.saturating_add(options.future.total_bytes),
+		);
+
+		let journal =
+			options.journal.clone().map(|options| Arc::new(journal::Journal::new(options)));
+		if let Some(journal) = &journal {
+			let restored = journal
+				.load(&mempool)
+				.into_iter()
+				.flat_map(|(source, xt)| mempool.extend_unwatched(source, std::iter::once(xt)))
+				.filter(Result::is_ok)
+				.count();
+			log::info!(target: LOG_TARGET, "Restored {} transactions from the journal", restored);
+		}
+
+		let listener = Arc::new(MultiViewListener::default());
+
+		Self {
+			api: pool_api.clone(),
+			mempool: Arc::new(mempool),
+			view_store: Arc::new(ViewStore::new(
+				pool_api,
+				options,
+				is_validator,
+				listener.clone(),
+				finalized_hash,
+			)),
+			listener,
+			ready_poll: Default::default(),
+			import_notification_sinks: Default::default(),
+			metrics: PrometheusMetrics::new(prometheus),
+			enactment_state: Arc::new(Mutex::new(EnactmentState::new(
+				best_block_hash,
+				finalized_hash,
+			))),
+			journal,
+		}
+	}
+
+	/// Get access to the underlying api
+	pub fn api(&self) -> &PoolApi {
+		&self.api
+	}
+
+	/// Returns the number of transactions in the mempool.
+	pub fn mempool_len(&self) -> usize {
+		self.mempool.len()
+	}
+
+	/// Returns the number of views currently kept by the pool.
+	pub fn views_count(&self) -> usize {
+		self.view_store.views().len()
+	}
+
+	/// Returns the status of the view at the given block, if there is one.
+	pub fn view_status(&self, at: Block::Hash) -> Option<PoolStatus> {
+		self.view_store.get(&at).map(|view| view.status())
+	}
+
+	/// Submits the transactions to the mempool and all the views.
+	fn submit(
+		&self,
+		at: Block::Hash,
+		source: TransactionSource,
+		from_network: bool,
+		xts: Vec<graph::ExtrinsicFor<PoolApi>>,
+	) -> PoolFuture<Vec<Result<ExtrinsicHash<PoolApi>, PoolApi::Error>>, PoolApi::Error> {
+		let api = self.api.clone();
+		let view_store = self.view_store.clone();
+		let mempool = self.mempool.clone();
+		let sinks = self.import_notification_sinks.clone();
+
+		self.metrics
+			.report(|metrics| metrics.submitted_transactions.inc_by(xts.len() as u64));
+
+		async move {
+			ensure_view(&*api, &view_store, &mempool, at).await?;
+
+			let mempool_results = if from_network {
+				mempool.extend_from_network(xts.iter().cloned())
+			} else {
+				mempool.extend_unwatched(source, xts.iter().cloned())
+			};
+			let to_submit = mempool_results
+				.iter()
+				.zip(xts)
+				.filter_map(|(result, xt)| result.as_ref().ok().map(|hash| (*hash, xt)))
+				.collect::<Vec<_>>();
+			let mut submitted =
+				view_store.submit(source, from_network, to_submit).await.into_iter();
+
+			Ok(mempool_results
+				.into_iter()
+				.map(|result| {
+					let hash = result?;
+					let result = submitted
+						.next()
+						.expect("One result for every transaction added to the mempool; qed");
+					match result {
+						Ok(_) =>
+							if view_store.tx_state(&hash) == Some(TxState::Ready) {
+								notify_imported(&sinks, hash);
+							},
+						Err(_) => {
+							mempool.remove(&hash);
+						},
+					}
+					result
+				})
+				.collect())
+		}
+		.boxed()
+	}
+}
+
+impl<PoolApi, Block> Drop for ForkAwareTxPool<PoolApi, Block>
+where
+	Block: BlockT,
+	PoolApi: graph::ChainApi<Block = Block>,
+{
+	fn drop(&mut self) {
+		if let Some(journal) = &self.journal {
+			journal.flush(&*self.mempool);
+		}
+	}
+}
+
+/// Makes sure there is at least one view, creating one at the given block if needed.
+async fn ensure_view<PoolApi: graph::ChainApi + 'static>(
+	api: &PoolApi,
+	view_store: &ViewStore<PoolApi>,
+	mempool: &TxMemPool<PoolApi>,
+	at: graph::BlockHash<PoolApi>,
+) -> Result<(), PoolApi::Error> {
+	if !view_store.is_empty() {
+		return Ok(())
+	}
+
+	let number = api
+		.block_id_to_number(&BlockId::Hash(at))?
+		.ok_or_else(|| TxPoolError::InvalidBlockId(format!("{:?}", at)))?;
+	let (view, created) = view_store.get_or_create_view(HashAndNumber { hash: at, number }).await;
+	if created {
+		view_store.update_view(&view, mempool).await;
+	}
+	if view_store.best_view().is_none() {
+		view_store.set_best(at);
+	}
+	Ok(())
+}
+
+/// Notifies the import notification sinks about the transaction.
+fn notify_imported<Hash: Copy + std::fmt::Debug>(sinks: &Mutex<Vec<Sender<Hash>>>, hash: Hash) {
+	sinks.lock().retain_mut(|sink| match sink.try_send(hash) {
+		Ok(()) => true,
+		Err(e) =>
+			if e.is_full() {
+				log::warn!(
+					target: LOG_TARGET,
+					"[{:?}] Trying to notify an import but the channel is full",
+					hash,
+				);
+				true
+			} else {
+				false
+			},
+	});
+}
+
+fn ready_iterator<PoolApi: graph::ChainApi>(view: &View<PoolApi>) -> ReadyIteratorFor<PoolApi> {
+	Box::new(view.pool.validated_pool().ready())
+}
+
+impl<PoolApi, Block> TransactionPool for ForkAwareTxPool<PoolApi, Block>
+where
+	Block: BlockT,
+	PoolApi: 'static + graph::ChainApi<Block = Block>,
+{
+	type Block = PoolApi::Block;
+	type Hash = graph::ExtrinsicHash<PoolApi>;
+	type InPoolTransaction = graph::base_pool::Transaction<TxHash<Self>, TransactionFor<Self>>;
+	type Error = PoolApi::Error;
+
+	fn submit_at(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		source: TransactionSource,
+		xts: Vec<TransactionFor<Self>>,
+	) -> PoolFuture<Vec<Result<TxHash<Self>, Self::Error>>, Self::Error> {
+		self.submit(at, source, false, xts)
+	}
+
+	fn submit_one(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		source: TransactionSource,
+		xt: TransactionFor<Self>,
+	) -> PoolFuture<TxHash<Self>, Self::Error> {
+		let results = self.submit_at(at, source, vec![xt]);
+		async move { results.await?.pop().expect("One extrinsic passed; one result returned; qed") }
+			.boxed()
+	}
+
+	fn submit_and_watch(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		source: TransactionSource,
+		xt: TransactionFor<Self>,
+	) -> PoolFuture<Pin<Box<TransactionStatusStreamFor<Self>>>, Self::Error> {
+		let api = self.api.clone();
+		let view_store = self.view_store.clone();
+		let mempool = self.mempool.clone();
+		let listener = self.listener.clone();
+		let sinks = self.import_notification_sinks.clone();
+
+		self.metrics.report(|metrics| metrics.submitted_transactions.inc());
+
+		async move {
+			ensure_view(&*api, &view_store, &mempool, at).await?;
+
+			let hash = mempool.push_watched(source, xt.clone())?;
+			let watcher = listener.create_watcher(hash);
+			let result = view_store
+				.submit(source, false, vec![(hash, xt)])
+				.await
+				.pop()
+				.expect("One extrinsic passed; one result returned; qed");
+
+			if let Err(e) = result {
+				mempool.remove(&hash);
+				listener.remove_watcher(&hash);
+				return Err(e)
+			}
+
+			if let Some(state) = view_store.tx_state(&hash) {
+				listener.state_changed(&hash, state);
+				if state == TxState::Ready {
+					notify_imported(&sinks, hash);
+				}
+			}
+
+			Ok(watcher)
+		}
+		.boxed()
+	}
+
+	fn submit_from_network(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		xt: TransactionFor<Self>,
+	) -> PoolFuture<TxHash<Self>, Self::Error> {
+		let results = self.submit(at, TransactionSource::External, true, vec![xt]);
+		async move { results.await?.pop().expect("One extrinsic passed; one result returned; qed") }
+			.boxed()
+	}
+
+	fn remove_invalid(&self, hashes: &[TxHash<Self>]) -> Vec<Arc<Self::InPoolTransaction>> {
+		let removed = self.view_store.remove_invalid(hashes);
+		for hash in hashes {
+			self.mempool.remove(hash);
+			self.listener.invalid(hash);
+		}
+		self.metrics
+			.report(|metrics| metrics.validations_invalid.inc_by(removed.len() as u64));
+		removed
+	}
+
+	fn status(&self) -> PoolStatus {
+		self.view_store.best_view().map(|view| view.status()).unwrap_or(PoolStatus {
+			ready: 0,
+			ready_bytes: 0,
+			future: 0,
+			future_bytes: 0,
+		})
+	}
+
+	fn import_notification_stream(&self) -> ImportNotificationStream<TxHash<Self>> {
+		const CHANNEL_BUFFER_SIZE: usize = 1024;
+
+		let (sink, stream) = channel(CHANNEL_BUFFER_SIZE);
+		self.import_notification_sinks.lock().push(sink);
+		stream
+	}
+
+	fn hash_of(&self, xt: &TransactionFor<Self>) -> TxHash<Self> {
+		self.api.hash_and_length(xt).0
+	}
+
+	fn on_broadcasted(&self, propagations: HashMap<TxHash<Self>, Vec<String>>) {
+		for (hash, peers) in propagations {
+			self.listener.broadcasted(&hash, peers);
+		}
+	}
+
+	fn ready_transaction(&self, hash: &TxHash<Self>) -> Option<Arc<Self::InPoolTransaction>> {
+		self.view_store
+			.best_view()
+			.and_then(|view| view.pool.validated_pool().ready_by_hash(hash))
+	}
+
+	fn ready_at(&self, at: NumberFor<Self::Block>) -> PolledIterator<PoolApi> {
+		let Some(best_view) = self.view_store.best_view() else {
+			return async { Box::new(std::iter::empty()) as Box<_> }.boxed()
+		};
+
+		if best_view.at.number >= at {
+			log::trace!(target: LOG_TARGET, "Transaction pool already processed block  #{}", at);
+			let iterator = ready_iterator(&best_view);
+			return async move { iterator }.boxed()
+		}
+
+		self.ready_poll
+			.lock()
+			.add(at)
+			.map(|received| {
+				received.unwrap_or_else(|e| {
+					log::warn!(target: LOG_TARGET, "Error receiving pending set: {:?}", e);
+					Box::new(std::iter::empty())
+				})
+			})
+			.boxed()
+	}
+
+	fn ready_at_block(
+		&self,
+		hash: <Self::Block as BlockT>::Hash,
+		number: NumberFor<Self::Block>,
+	) -> PolledIterator<PoolApi> {
+		if let Some(view) = self.view_store.get(&hash) {
+			let iterator = ready_iterator(&view);
+			return async move { iterator }.boxed()
+		}
+
+		let view_store = self.view_store.clone();
+		let mempool = self.mempool.clone();
+		async move {
+			let (view, created) =
+				view_store.get_or_create_view(HashAndNumber { hash, number }).await;
+			if created {
+				view_store.update_view(&view, &mempool).await;
+			}
+			ready_iterator(&view)
+		}
+		.boxed()
+	}
+
+	fn ready(&self) -> ReadyIteratorFor<PoolApi> {
+		match self.view_store.best_view() {
+			Some(view) => ready_iterator(&view),
+			None => Box::new(std::iter::empty()),
+		}
+	}
+
+	fn futures(&self) -> Vec<Self::InPoolTransaction> {
+		self.view_store
+			.best_view()
+			.map(|view| {
+				let pool = view.pool.validated_pool().pool.read();
+				pool.futures().cloned().collect::<Vec<_>>()
+			})
+			.unwrap_or_default()
+	}
+}
+
+impl<Block, Client> FullForkAwarePool<Block, Client>
+where
+	Block: BlockT,
+	Client: sp_api::ProvideRuntimeApi<Block>
+		+ sc_client_api::BlockBackend<Block>
+		+ sc_client_api::blockchain::HeaderBackend<Block>
+		+ sp_runtime::traits::BlockIdTo<Block>
+		+ sc_client_api::ExecutorProvider<Block>
+		+ sc_client_api::UsageProvider<Block>
+		+ sp_blockchain::HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ Send
+		+ Sync
+		+ 'static,
+	Client::Api: sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block>,
+{
+	/// Create new fork-aware transaction pool for a full node with the provided api.
+	pub fn new_full(
+		options: graph::Options,
+		is_validator: IsValidator,
+		prometheus: Option<&PrometheusRegistry>,
+		spawner: impl SpawnEssentialNamed,
+		client: Arc<Client>,
+	) -> Arc<Self> {
+		let pool_api = Arc::new(FullChainApi::new(client.clone(), prometheus, &spawner));
+		let pool = Self::new(
+			options,
+			is_validator,
+			pool_api,
+			prometheus,
+			client.usage_info().chain.best_hash,
+			client.usage_info().chain.finalized_hash,
+		);
+
+		if let Some(journal) = pool.journal.clone() {
+			spawner.spawn_essential(
+				"txpool-journal",
+				Some("transaction-pool"),
+				journal::flush_task(journal, pool.mempool.clone()).boxed(),
+			);
+		}
+
+		Arc::new(pool)
+	}
+}
+
+impl<Block, Client> sc_transaction_pool_api::LocalTransactionPool
+	for ForkAwareTxPool<FullChainApi<Client, Block>, Block>
+where
+	Block: BlockT,
+	Client: sp_api::ProvideRuntimeApi<Block>
+		+ sc_client_api::BlockBackend<Block>
+		+ sc_client_api::blockchain::HeaderBackend<Block>
+		+ sp_runtime::traits::BlockIdTo<Block>
+		+ sp_blockchain::HeaderMetadata<Block, Error = sp_blockchain::Error>,
+	Client: Send + Sync + 'static,
+	Client::Api: sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block>,
+{
+	type Block = Block;
+	type Hash = graph::ExtrinsicHash<FullChainApi<Client, Block>>;
+	type Error = <FullChainApi<Client, Block> as graph::ChainApi>::Error;
+
+	fn submit_local(
+		&self,
+		at: Block::Hash,
+		xt: sc_transaction_pool_api::LocalTransactionFor<Self>,
+	) -> Result<Self::Hash, Self::Error> {
+		use crate::graph::ChainApi;
+		use sp_runtime::{
+			traits::SaturatedConversion, transaction_validity::TransactionValidityError,
+		};
+
+		let validity = self
+			.api
+			.validate_transaction_blocking(at, TransactionSource::Local, xt.clone())?
+			.map_err(|e| {
+				Self::Error::Pool(match e {
+					TransactionValidityError::Invalid(i) => TxPoolError::InvalidTransaction(i),
+					TransactionValidityError::Unknown(u) => TxPoolError::UnknownTransaction(u),
+				})
+			})?;
+
+		let (hash, bytes) = self.api.hash_and_length(&xt);
+		let block_number = self
+			.api
+			.block_id_to_number(&BlockId::hash(at))?
+			.ok_or_else(|| error::Error::BlockIdConversion(format!("{:?}", at)))?;
+
+		self.mempool
+			.extend_unwatched(TransactionSource::Local, std::iter::once(xt.clone()))
+			.pop()
+			.expect("One extrinsic passed; one result returned; qed")?;
+
+		let transaction = graph::base_pool::Transaction {
+			data: xt,
+			bytes,
+			hash,
+			source: TransactionSource::Local,
+			from_network: false,
+			priority: validity.priority,
+			requires: validity.requires,
+			provides: validity.provides,
+			propagate: validity.propagate,
+			valid_till: block_number.saturated_into::<u64>().saturating_add(validity.longevity),
+		};
+
+		// Views are populated from the mempool once created, so there is nothing else to do
+		// if there are none yet.
+		let views = self.view_store.views();
+		if views.is_empty() {
+			return Ok(hash)
+		}
+
+		let mut result = None;
+		for view in views {
+			let view_result = view
+				.pool
+				.validated_pool()
+				.submit(std::iter::once(graph::ValidatedTransaction::Valid(transaction.clone())))
+				.remove(0);
+			if !matches!(result, Some(Ok(_))) {
+				result = Some(view_result);
+			}
+		}
+
+		let result = result.expect("There is at least one view; qed");
+		if result.is_err() {
+			self.mempool.remove(&hash);
+		}
+		result
+	}
+}
+
+impl<PoolApi, Block> ForkAwareTxPool<PoolApi, Block>
+where
+	Block: BlockT,
+	PoolApi: 'static + graph::ChainApi<Block = Block>,
+{
+	/// Handles a new best block.
+	///
+	/// Reports the transactions of the retracted and enacted blocks to the watchers, brings the
+	/// transactions of retracted blocks back to the mempool and makes the view at the new best
+	/// block the best view.
+	async fn handle_enactment(&self, tree_route: TreeRoute<Block>) {
+		log::trace!(target: LOG_TARGET, "handle_enactment tree_route: {tree_route:?}");
+
+		let Some(best) = tree_route.last().cloned() else {
+			log::warn!(
+				target: LOG_TARGET,
+				"Skipping ChainEvent - no last block in tree route {:?}",
+				tree_route,
+			);
+			return
+		};
+
+		let mut resubmit = Vec::new();
+		for retracted in tree_route.retracted() {
+			for xt in block_body(&*self.api, retracted.hash).await {
+				let hash = self.api.hash_and_length(&xt).0;
+				if self.mempool.contains(&hash) {
+					self.listener.retracted(&hash, retracted.hash);
+				} else if xt.is_signed().unwrap_or(true) {
+					log::debug!(
+						target: LOG_TARGET,
+						"[{:?}]: Resubmitting from retracted block {:?}",
+						hash,
+						retracted.hash,
+					);
+					resubmit.push(xt);
+				}
+			}
+		}
+
+		let resubmitted = resubmit.len() as u64;
+		// These transactions are coming from retracted blocks, we should simply consider them
+		// external.
+		self.mempool.extend_unwatched(TransactionSource::External, resubmit);
+		self.metrics
+			.report(|metrics| metrics.block_transactions_resubmitted.inc_by(resubmitted));
+
+		let mut pruned = 0;
+		for enacted in tree_route.enacted() {
+			for (index, xt) in block_body(&*self.api, enacted.hash).await.into_iter().enumerate() {
+				let hash = self.api.hash_and_length(&xt).0;
+				if self.mempool.contains(&hash) {
+					self.listener.in_block(&hash, enacted.hash, index);
+				}
+				pruned += 1;
+			}
+		}
+		self.metrics.report(|metrics| metrics.block_transactions_pruned.inc_by(pruned));
+
+		let (view, _) = self.view_store.get_or_create_view(best.clone()).await;
+		self.view_store.update_view(&view, &self.mempool).await;
+		self.view_store.set_best(best.hash);
+
+		for hash in self.mempool.watched() {
+			if let Some(state) = view.tx_state(&hash) {
+				self.listener.state_changed(&hash, state);
+			}
+		}
+
+		self.ready_poll.lock().trigger(best.number, move || ready_iterator(&view));
+	}
+
+	/// Handles a finalized block.
+	///
+	/// Reports finalized transactions, removes them from the mempool and drops the views that
+	/// are no longer needed. Transactions that are not known to any of the remaining views are
+	/// reported as invalid and removed from the mempool.
+	async fn handle_finalized(&self, hash: Block::Hash, tree_route: &[Block::Hash]) {
+		log::trace!(target: LOG_TARGET, "handle_finalized {hash:?} tree_route: {tree_route:?}");
+
+		for block in tree_route.iter().chain(std::iter::once(&hash)) {
+			for (index, xt) in block_body(&*self.api, *block).await.into_iter().enumerate() {
+				let tx_hash = self.api.hash_and_length(&xt).0;
+				if self.mempool.remove(&tx_hash).is_some() {
+					self.listener.finalized(&tx_hash, *block, index);
+				}
+			}
+		}
+
+		let number = match self.api.block_id_to_number(&BlockId::Hash(hash)) {
+			Ok(Some(number)) => number,
+			Ok(None) => {
+				log::debug!(target: LOG_TARGET, "Could not find number of finalized {:?}.", hash);
+				return
+			},
+			Err(e) => {
+				log::debug!(
+					target: LOG_TARGET,
+					"Error retrieving number of finalized {:?}: {}",
+					hash,
+					e
+				);
+				return
+			},
+		};
+		self.view_store.handle_finalized(HashAndNumber { hash, number });
+
+		if !self.view_store.is_empty() {
+			let invalid = self.mempool.retain(|tx_hash, _| {
+				self.view_store.is_included_anywhere(tx_hash) ||
+					self.view_store.is_imported_anywhere(tx_hash)
+			});
+			for (tx_hash, _) in &invalid {
+				self.listener.invalid(tx_hash);
+			}
+			self.metrics
+				.report(|metrics| metrics.validations_invalid.inc_by(invalid.len() as u64));
+		}
+		self.view_store.retain_included(|tx_hash| self.mempool.contains(tx_hash));
+
+		self.listener.remove_closed();
+	}
+}
+
+#[async_trait]
+impl<PoolApi, Block> MaintainedTransactionPool for ForkAwareTxPool<PoolApi, Block>
+where
+	Block: BlockT,
+	PoolApi: 'static + graph::ChainApi<Block = Block>,
+{
+	async fn maintain(&self, event: ChainEvent<Self::Block>) {
+		let compute_tree_route = |from, to| -> Result<TreeRoute<Block>, String> {
+			self.api.tree_route(from, to).map_err(|e| {
+				format!("Error occurred while computing tree_route from {from:?} to {to:?}: {e}")
+			})
+		};
+		let block_id_to_number =
+			|hash| self.api.block_id_to_number(&BlockId::Hash(hash)).map_err(|e| format!("{}", e));
+
+		let result =
+			self.enactment_state
+				.lock()
+				.update(&event, &compute_tree_route, &block_id_to_number);
+
+		match result {
+			Err(msg) => {
+				log::debug!(target: LOG_TARGET, "{msg}");
+				self.enactment_state.lock().force_update(&event);
+			},
+			Ok(EnactmentAction::Skip) => return,
+			Ok(EnactmentAction::HandleFinalization) => {},
+			Ok(EnactmentAction::HandleEnactment(tree_route)) => {
+				self.handle_enactment(tree_route).await;
+			},
+		};
+
+		if let ChainEvent::Finalized { hash, tree_route } = event {
+			self.handle_finalized(hash, &tree_route).await;
+		}
+	}
+}
@@ -0,0 +1,176 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A view of the transaction pool at a particular block.
+
+use std::{collections::HashSet, sync::Arc};
+
+use parking_lot::RwLock;
+use sc_transaction_pool_api::{PoolStatus, TransactionSource};
+use sp_blockchain::HashAndNumber;
+use sp_runtime::generic::BlockId;
+
+use crate::{
+	graph::{
+		self, BlockHash, ChainApi, ExtrinsicFor, ExtrinsicHash, IsValidator, Options,
+		ValidatedTransaction,
+	},
+	LOG_TARGET,
+};
+
+/// State of a transaction within a view.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum TxState {
+	/// The transaction is in the ready queue.
+	Ready,
+	/// The transaction is in the future queue.
+	Future,
+}
+
+/// Transactions validated against the state of a single block.
+pub(super) struct View<Api: ChainApi> {
+	/// The block this view was built for.
+	pub(super) at: HashAndNumber<Api::Block>,
+	/// The underlying pool.
+	pub(super) pool: graph::Pool<Api>,
+	/// Transactions included in the blocks between the finalized block and the block of the
+	/// view.
+	included: RwLock<HashSet<ExtrinsicHash<Api>>>,
+}
+
+impl<Api: ChainApi> View<Api> {
+	/// Creates an empty view at the given block.
+	pub(super) fn new(
+		api: Arc<Api>,
+		at: HashAndNumber<Api::Block>,
+		options: Options,
+		is_validator: IsValidator,
+	) -> Self {
+		Self {
+			at,
+			pool: graph::Pool::new(options, is_validator, api),
+			included: Default::default(),
+		}
+	}
+
+	/// Creates a view at the given block that contains all transactions of `self`.
+	///
+	/// The transactions are copied without re-validation, the caller is responsible for
+	/// pruning the transactions included in the blocks between `self` and `at`.
+	pub(super) fn new_from_other(
+		&self,
+		api: Arc<Api>,
+		at: HashAndNumber<Api::Block>,
+		options: Options,
+		is_validator: IsValidator,
+	) -> Self {
+		let view = Self::new(api, at, options, is_validator);
+		let transactions = {
+			let pool = self.pool.validated_pool().pool.read();
+			pool.ready()
+				.map(|tx| tx.duplicate())
+				.chain(pool.futures().map(|tx| tx.duplicate()))
+				.collect::<Vec<_>>()
+		};
+		view.pool
+			.validated_pool()
+			.submit(transactions.into_iter().map(ValidatedTransaction::Valid));
+		*view.included.write() = self.included.read().clone();
+		view
+	}
+
+	/// Validates the given transactions at the block of the view and imports the valid ones.
+	///
+	/// Transactions received from the network are always submitted with the `External` source.
+	pub(super) async fn submit_many(
+		&self,
+		source: TransactionSource,
+		from_network: bool,
+		xts: impl IntoIterator<Item = ExtrinsicFor<Api>>,
+	) -> Result<Vec<Result<ExtrinsicHash<Api>, Api::Error>>, Api::Error> {
+		if from_network {
+			self.pool.submit_from_network(self.at.hash, xts).await
+		} else {
+			self.pool.submit_at(self.at.hash, source, xts).await
+		}
+	}
+
+	/// Prunes the transactions included in the given block.
+	///
+	/// `parent` is used to obtain the tags of the included transactions unknown to the view.
+	pub(super) async fn prune_block(
+		&self,
+		block: BlockHash<Api>,
+		parent: BlockHash<Api>,
+		extrinsics: &[ExtrinsicFor<Api>],
+	) {
+		if let Err(e) = self.pool.prune(block, parent, extrinsics).await {
+			log::debug!(
+				target: LOG_TARGET,
+				"[{:?}] Pruning block {:?} in view failed: {}",
+				self.at.hash,
+				block,
+				e,
+			);
+		}
+	}
+
+	/// Removes the transactions that are stale at the block of the view.
+	pub(super) fn clear_stale(&self) {
+		if let Err(e) = self.pool.validated_pool().clear_stale(&BlockId::Hash(self.at.hash)) {
+			log::debug!(target: LOG_TARGET, "[{:?}] Clearing stale failed: {}", self.at.hash, e);
+		}
+	}
+
+	/// Returns the state of the transaction in this view, if the view knows it.
+	pub(super) fn tx_state(&self, hash: &ExtrinsicHash<Api>) -> Option<TxState> {
+		let pool = self.pool.validated_pool().pool.read();
+		if pool.ready_by_hash(hash).is_some() {
+			Some(TxState::Ready)
+		} else if pool.is_imported(hash) {
+			Some(TxState::Future)
+		} else {
+			None
+		}
+	}
+
+	/// Returns `true` if the view contains the transaction.
+	pub(super) fn is_imported(&self, hash: &ExtrinsicHash<Api>) -> bool {
+		self.pool.validated_pool().pool.read().is_imported(hash)
+	}
+
+	/// Records that the transaction is included in a block on the chain of the view.
+	pub(super) fn mark_included(&self, hash: ExtrinsicHash<Api>) {
+		self.included.write().insert(hash);
+	}
+
+	/// Returns `true` if the transaction is included in a block on the chain of the view.
+	pub(super) fn is_included(&self, hash: &ExtrinsicHash<Api>) -> bool {
+		self.included.read().contains(hash)
+	}
+
+	/// Forgets the included transactions for which `f` returns `false`.
+	pub(super) fn retain_included(&self, f: impl Fn(&ExtrinsicHash<Api>) -> bool) {
+		self.included.write().retain(|hash| f(hash));
+	}
+
+	/// Returns the status of the view.
+	pub(super) fn status(&self) -> PoolStatus {
+		self.pool.validated_pool().status()
+	}
+}
@@ -0,0 +1,456 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Collection of the views kept by the fork-aware pool.
+
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+};
+
+use futures::{future, FutureExt};
+use parking_lot::RwLock;
+use sc_transaction_pool_api::{error::Error as TxPoolError, TransactionSource};
+use sp_blockchain::{HashAndNumber, TreeRoute};
+use sp_runtime::traits::Header as HeaderT;
+
+use super::{
+	multi_view_listener::MultiViewListener,
+	tx_mem_pool::TxMemPool,
+	view::{TxState, View},
+};
+use crate::{
+	graph::{
+		BlockHash, ChainApi, ExtrinsicFor, ExtrinsicHash, IsValidator, Options, TransactionFor,
+	},
+	LOG_TARGET,
+};
+
+/// Returns the body of the given block, or an empty body if it is not available.
+pub(super) async fn block_body<Api: ChainApi>(
+	api: &Api,
+	hash: BlockHash<Api>,
+) -> Vec<ExtrinsicFor<Api>> {
+	api.block_body(hash)
+		.await
+		.unwrap_or_else(|e| {
+			log::warn!(target: LOG_TARGET, "Failed to fetch block body of {:?}: {}", hash, e);
+			None
+		})
+		.unwrap_or_default()
+}
+
+/// Views of the fork-aware pool, indexed by the hash of their block.
+///
+/// Only the leaves of the tree of known blocks have a view: inserting a view drops the views of
+/// its ancestors.
+pub(super) struct ViewStore<Api: ChainApi> {
+	api: Arc<Api>,
+	options: Options,
+	is_validator: Arc<IsValidator>,
+	listener: Arc<MultiViewListener<Api>>,
+	views: RwLock<HashMap<BlockHash<Api>, Arc<View<Api>>>>,
+	best: RwLock<Option<BlockHash<Api>>>,
+	finalized: RwLock<BlockHash<Api>>,
+}
+
+impl<Api: ChainApi + 'static> ViewStore<Api> {
+	/// Creates an empty store.
+	pub(super) fn new(
+		api: Arc<Api>,
+		options: Options,
+		is_validator: IsValidator,
+		listener: Arc<MultiViewListener<Api>>,
+		finalized: BlockHash<Api>,
+	) -> Self {
+		Self {
+			api,
+			options,
+			is_validator: Arc::new(is_validator),
+			listener,
+			views: Default::default(),
+			best: Default::default(),
+			finalized: RwLock::new(finalized),
+		}
+	}
+
+	fn view_is_validator(&self) -> IsValidator {
+		let is_validator = self.is_validator.clone();
+		let f: Box<dyn Fn() -> bool + Send + Sync> = Box::new(move || (is_validator.0)());
+		f.into()
+	}
+
+	/// Returns `true` if there are no views.
+	pub(super) fn is_empty(&self) -> bool {
+		self.views.read().is_empty()
+	}
+
+	/// Returns the view at the given block.
+	pub(super) fn get(&self, hash: &BlockHash<Api>) -> Option<Arc<View<Api>>> {
+		self.views.read().get(hash).cloned()
+	}
+
+	/// Returns all views.
+	pub(super) fn views(&self) -> Vec<Arc<View<Api>>> {
+		self.views.read().values().cloned().collect()
+	}
+
+	/// Returns the view at the best block.
+	pub(super) fn best_view(&self) -> Option<Arc<View<Api>>> {
+		let best = (*self.best.read())?;
+		self.get(&best)
+	}
+
+	/// Marks the view at the given block as the best one.
+	pub(super) fn set_best(&self, hash: BlockHash<Api>) {
+		*self.best.write() = Some(hash);
+	}
+
+	/// Returns the state of the transaction in the best view, or in any other view if the best
+	/// view does not know it.
+	pub(super) fn tx_state(&self, hash: &ExtrinsicHash<Api>) -> Option<TxState> {
+		let best = self.best_view();
+		best.as_ref().and_then(|view| view.tx_state(hash)).or_else(|| {
+			self.views
+				.read()
+				.values()
+				.filter(|view| Some(view.at.hash) != best.as_ref().map(|best| best.at.hash))
+				.find_map(|view| view.tx_state(hash))
+		})
+	}
+
+	/// Returns `true` if any view contains the transaction.
+	pub(super) fn is_imported_anywhere(&self, hash: &ExtrinsicHash<Api>) -> bool {
+		self.views.read().values().any(|view| view.is_imported(hash))
+	}
+
+	/// Returns `true` if the transaction is included in a block on the chain of any view.
+	pub(super) fn is_included_anywhere(&self, hash: &ExtrinsicHash<Api>) -> bool {
+		self.views.read().values().any(|view| view.is_included(hash))
+	}
+
+	/// Forgets the included transactions for which `f` returns `false` in all views.
+	pub(super) fn retain_included(&self, f: impl Fn(&ExtrinsicHash<Api>) -> bool) {
+		for view in self.views() {
+			view.retain_included(&f);
+		}
+	}
+
+	/// Returns `true` if `ancestor` is a strict ancestor of `block`.
+	fn is_ancestor(
+		&self,
+		ancestor: &HashAndNumber<Api::Block>,
+		block: &HashAndNumber<Api::Block>,
+	) -> bool {
+		ancestor.number < block.number &&
+			self.api
+				.tree_route(ancestor.hash, block.hash)
+				.map(|tree_route| tree_route.retracted().is_empty())
+				.unwrap_or(false)
+	}
+
+	/// Returns the view at the given block, creating it if needed.
+	///
+	/// A newly created view only contains the transactions inherited from its nearest ancestor
+	/// view, use [`Self::update_view`] to add the remaining transactions of the mempool.
+	///
+	/// The new view replaces the views of its ancestors. If a descendant of the block already
+	/// has a view, the new view is not kept in the store.
+	pub(super) async fn get_or_create_view(
+		&self,
+		at: HashAndNumber<Api::Block>,
+	) -> (Arc<View<Api>>, bool) {
+		if let Some(view) = self.get(&at.hash) {
+			return (view, false)
+		}
+
+		let view = Arc::new(self.create_view(at.clone()).await);
+
+		loop {
+			// the ancestry is looked up in the database, so it's computed without holding the lock.
+			let known = self.views_at();
+			let has_descendant = known.values().any(|other| self.is_ancestor(&at, other));
+			let ancestors = known
+				.iter()
+				.filter(|(_, other)| self.is_ancestor(other, &at))
+				.map(|(hash, _)| *hash)
+				.collect::<HashSet<_>>();
+
+			let mut views = self.views.write();
+			if views.keys().any(|hash| !known.contains_key(hash)) {
+				// the ancestry of the views added meanwhile is unknown.
+				continue
+			}
+
+			if has_descendant {
+				log::debug!(target: LOG_TARGET, "Created transient view at {:?}", at);
+				return (view, true)
+			}
+
+			if let Some(existing) = views.get(&at.hash) {
+				return (existing.clone(), false)
+			}
+
+			let mut best = self.best.write();
+			views.retain(|hash, other| {
+				let keep = !ancestors.contains(hash);
+				if !keep {
+					log::debug!(target: LOG_TARGET, "Dropping view at {:?}, not a leaf", other.at);
+					if *best == Some(*hash) {
+						*best = Some(at.hash);
+					}
+				}
+				keep
+			});
+			views.insert(at.hash, view.clone());
+			log::debug!(target: LOG_TARGET, "Created view at {:?}, views: {}", at, views.len());
+			return (view, true)
+		}
+	}
+
+	/// Returns the blocks of the views, keyed by their hash.
+	fn views_at(&self) -> HashMap<BlockHash<Api>, HashAndNumber<Api::Block>> {
+		self.views.read().iter().map(|(hash, view)| (*hash, view.at.clone())).collect()
+	}
+
+	/// Records the transactions of the block as included on the chain of the view and reports
+	/// them to the watchers.
+	async fn include_block(
+		&self,
+		view: &View<Api>,
+		block: BlockHash<Api>,
+	) -> Vec<ExtrinsicFor<Api>> {
+		let extrinsics = block_body(&*self.api, block).await;
+		for (index, xt) in extrinsics.iter().enumerate() {
+			let hash = self.api.hash_and_length(xt).0;
+			view.mark_included(hash);
+			self.listener.in_block(&hash, block, index);
+		}
+		extrinsics
+	}
+
+	/// Returns the nearest view on the chain of the given block, together with the tree route
+	/// from that view to the block.
+	fn find_ancestor(
+		&self,
+		at: &HashAndNumber<Api::Block>,
+	) -> Option<(Arc<View<Api>>, TreeRoute<Api::Block>)> {
+		self.views
+			.read()
+			.values()
+			.filter(|view| view.at.number < at.number)
+			.filter_map(|view| {
+				let tree_route = self
+					.api
+					.tree_route(view.at.hash, at.hash)
+					.map_err(|e| {
+						log::debug!(
+							target: LOG_TARGET,
+							"Tree route from {:?} to {:?} failed: {}",
+							view.at.hash,
+							at.hash,
+							e,
+						)
+					})
+					.ok()?;
+				tree_route.retracted().is_empty().then(|| (view.clone(), tree_route))
+			})
+			.max_by_key(|(view, _)| view.at.number)
+	}
+
+	async fn create_view(&self, at: HashAndNumber<Api::Block>) -> View<Api> {
+		let Some((ancestor, tree_route)) = self.find_ancestor(&at) else {
+			let view = View::new(
+				self.api.clone(),
+				at.clone(),
+				self.options.clone(),
+				self.view_is_validator(),
+			);
+
+			// The view is validated from scratch, it only needs to know the transactions that
+			// are already included on its chain.
+			let finalized = *self.finalized.read();
+			match self.api.tree_route(finalized, at.hash) {
+				Ok(tree_route) if tree_route.retracted().is_empty() =>
+					for enacted in tree_route.enacted() {
+						self.include_block(&view, enacted.hash).await;
+					},
+				Ok(_) => {},
+				Err(e) => log::debug!(
+					target: LOG_TARGET,
+					"Tree route from {:?} to {:?} failed: {}",
+					finalized,
+					at.hash,
+					e,
+				),
+			}
+			return view
+		};
+
+		let view = ancestor.new_from_other(
+			self.api.clone(),
+			at,
+			self.options.clone(),
+			self.view_is_validator(),
+		);
+
+		for enacted in tree_route.enacted() {
+			let parent = match self.api.block_header(enacted.hash) {
+				Ok(Some(header)) => *header.parent_hash(),
+				Ok(None) => {
+					log::debug!(target: LOG_TARGET, "Could not find header for {:?}.", enacted);
+					continue
+				},
+				Err(e) => {
+					log::debug!(
+						target: LOG_TARGET,
+						"Error retrieving header for {:?}: {}",
+						enacted,
+						e
+					);
+					continue
+				},
+			};
+			let extrinsics = self.include_block(&view, enacted.hash).await;
+			view.prune_block(enacted.hash, parent, &extrinsics).await;
+		}
+		view.clear_stale();
+
+		view
+	}
+
+	/// Submits the transactions of the mempool that are unknown to the view.
+	///
+	/// Transactions included in a block on the chain of the view are skipped.
+	pub(super) async fn update_view(&self, view: &View<Api>, mempool: &TxMemPool<Api>) {
+		let mut by_source = Vec::<((TransactionSource, bool), Vec<ExtrinsicFor<Api>>)>::new();
+		for (hash, source, from_network, xt) in mempool.transactions() {
+			if view.is_imported(&hash) || view.is_included(&hash) {
+				continue
+			}
+			let origin = (source, from_network);
+			match by_source.iter_mut().find(|(o, _)| *o == origin) {
+				Some((_, xts)) => xts.push(xt),
+				None => by_source.push((origin, vec![xt])),
+			}
+		}
+
+		for ((source, from_network), xts) in by_source {
+			if let Err(e) = view.submit_many(source, from_network, xts).await {
+				log::debug!(
+					target: LOG_TARGET,
+					"[{:?}] Updating view from mempool failed: {}",
+					view.at.hash,
+					e
+				);
+			}
+		}
+	}
+
+	/// Submits the transactions to all views.
+	///
+	/// The result of a transaction is `Ok` if it was accepted by at least one view, otherwise it
+	/// is the error reported by the best view (or any view if there is no best view).
+	pub(super) async fn submit(
+		&self,
+		source: TransactionSource,
+		from_network: bool,
+		xts: Vec<(ExtrinsicHash<Api>, ExtrinsicFor<Api>)>,
+	) -> Vec<Result<ExtrinsicHash<Api>, Api::Error>> {
+		let best = *self.best.read();
+		let views = self.views();
+		let view_results = future::join_all(views.iter().map(|view| {
+			view.submit_many(source, from_network, xts.iter().map(|(_, xt)| xt.clone()))
+				.map(move |results| (view.at.hash, results))
+		}))
+		.await;
+
+		let mut results = xts
+			.iter()
+			.map(|(hash, _)| {
+				Err(TxPoolError::InvalidBlockId(format!("no view accepted {:?}", hash)).into())
+			})
+			.collect::<Vec<Result<_, Api::Error>>>();
+		let mut from_best = vec![false; xts.len()];
+
+		for (view_hash, view_result) in view_results {
+			let view_result = match view_result {
+				Ok(view_result) => view_result,
+				Err(e) => {
+					log::debug!(
+						target: LOG_TARGET,
+						"[{:?}] Submitting to view failed: {}",
+						view_hash,
+						e
+					);
+					continue
+				},
+			};
+			let is_best = Some(view_hash) == best;
+			for (idx, result) in view_result.into_iter().enumerate() {
+				match (&results[idx], result) {
+					(Ok(_), _) => {},
+					(Err(_), Ok(hash)) => results[idx] = Ok(hash),
+					(Err(_), Err(e)) if is_best || !from_best[idx] => {
+						results[idx] = Err(e);
+						from_best[idx] = is_best;
+					},
+					(Err(_), Err(_)) => {},
+				}
+			}
+		}
+
+		results
+	}
+
+	/// Removes the given transactions and their dependencies from all views.
+	pub(super) fn remove_invalid(&self, hashes: &[ExtrinsicHash<Api>]) -> Vec<TransactionFor<Api>> {
+		let mut removed = HashMap::new();
+		for view in self.views() {
+			for tx in view.pool.validated_pool().remove_invalid(hashes) {
+				removed.entry(tx.hash).or_insert(tx);
+			}
+		}
+		removed.into_values().collect()
+	}
+
+	/// Drops the views that are below the finalized block or not descending from it.
+	pub(super) fn handle_finalized(&self, finalized: HashAndNumber<Api::Block>) {
+		*self.finalized.write() = finalized.hash;
+		// the ancestry is looked up in the database, so it's computed without holding the lock.
+		// Views added meanwhile are checked on the next finalization.
+		let dropped = self
+			.views_at()
+			.into_iter()
+			.filter(|(hash, at)| *hash != finalized.hash && !self.is_ancestor(&finalized, at))
+			.map(|(hash, _)| hash)
+			.collect::<HashSet<_>>();
+		let mut views = self.views.write();
+		views.retain(|hash, view| {
+			let keep = !dropped.contains(hash);
+			if !keep {
+				log::debug!(target: LOG_TARGET, "Dropping view at {:?}", view.at);
+			}
+			keep
+		});
+		let mut best = self.best.write();
+		if best.map_or(false, |best| !views.contains_key(&best)) {
+			*best = None;
+		}
+	}
+}
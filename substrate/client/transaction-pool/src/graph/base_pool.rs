@@ -20,7 +20,12 @@
 //!
 //! For a more full-featured pool, have a look at the `pool` module.
 
-use std::{cmp::Ordering, collections::HashSet, fmt, hash, sync::Arc};
+use std::{
+	cmp::Ordering,
+	collections::{HashMap, HashSet},
+	fmt, hash,
+	sync::Arc,
+};
 
 use crate::LOG_TARGET;
 use log::{debug, trace, warn};
@@ -33,6 +38,7 @@ use sp_runtime::{
 		TransactionLongevity as Longevity, TransactionPriority as Priority,
 		TransactionSource as Source, TransactionTag as Tag,
 	},
+	Percent,
 };
 
 use super::{
@@ -104,6 +110,11 @@ pub struct Transaction<Hash, Extrinsic> {
 	pub propagate: bool,
 	/// Source of that transaction.
 	pub source: Source,
+	/// Whether the transaction was received from the network.
+	///
+	/// Such transactions can't use the ready queue capacity reserved for transactions submitted
+	/// to this node.
+	pub from_network: bool,
 }
 
 impl<Hash, Extrinsic> AsRef<Extrinsic> for Transaction<Hash, Extrinsic> {
@@ -158,6 +169,7 @@ impl<Hash: Clone, Extrinsic: Clone> Transaction<Hash, Extrinsic> {
 			hash: self.hash.clone(),
 			priority: self.priority,
 			source: self.source,
+			from_network: self.from_network,
 			valid_till: self.valid_till,
 			requires: self.requires.clone(),
 			provides: self.provides.clone(),
@@ -197,6 +209,13 @@ where
 /// Store last pruned tags for given number of invocations.
 const RECENTLY_PRUNED_TAGS: usize = 2;
 
+/// Queue of the pool a transaction was added to or removed from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Queue {
+	Ready,
+	Future,
+}
+
 /// Transaction pool.
 ///
 /// Builds a dependency graph for all transactions in the pool and returns
@@ -218,6 +237,14 @@ pub struct BasePool<Hash: hash::Hash + Eq, Ex> {
 	/// transactions to future in case they were just stuck in verification.
 	recently_pruned: [HashSet<Tag>; RECENTLY_PRUNED_TAGS],
 	recently_pruned_index: usize,
+	/// Limits of transactions per sender, disabled if `None`.
+	sender_limits: Option<SenderLimits>,
+	/// Number of ready and future transactions per sender, tracked if `sender_limits` are set.
+	senders: HashMap<Vec<u8>, (usize, usize)>,
+	/// Capacity of the ready queue reserved for transactions submitted to this node.
+	local_reserved: Limit,
+	/// Number and total size of ready transactions received from the network.
+	network_ready: (usize, usize),
 }
 
 impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> Default for BasePool<Hash, Ex> {
@@ -235,9 +262,35 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			ready: Default::default(),
 			recently_pruned: Default::default(),
 			recently_pruned_index: 0,
+			sender_limits: None,
+			senders: Default::default(),
+			local_reserved: Limit { count: 0, total_bytes: 0 },
+			network_ready: (0, 0),
 		}
 	}
 
+	/// Sets the minimal priority increase required to replace transactions in the ready queue.
+	pub fn with_replacement_bump(mut self, bump: Percent) -> Self {
+		self.ready.set_replacement_bump(bump);
+		self
+	}
+
+	/// Limits the number of ready and future transactions from a single sender.
+	pub fn with_sender_limits(mut self, sender_limits: Option<SenderLimits>) -> Self {
+		self.sender_limits = sender_limits;
+		self
+	}
+
+	/// Reserves part of the ready queue capacity for transactions submitted to this node.
+	///
+	/// Transactions received from the network are kept within the ready limit reduced by
+	/// `local_reserved`, while the transactions submitted locally (including the ones submitted
+	/// via RPC) may use the whole ready limit.
+	pub fn with_local_reserved(mut self, local_reserved: Limit) -> Self {
+		self.local_reserved = local_reserved;
+		self
+	}
+
 	/// Temporary enables future transactions, runs closure and then restores
 	/// `reject_future_transactions` flag back to previous value.
 	///
@@ -273,6 +326,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 
 		let tx = WaitingTransaction::new(tx, self.ready.provided_tags(), &self.recently_pruned);
 		trace!(target: LOG_TARGET, "[{:?}] {:?}", tx.transaction.hash, tx);
+		self.check_sender_limits(&tx)?;
 		debug!(
 			target: LOG_TARGET,
 			"[{:?}] Importing to {}",
@@ -287,6 +341,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			}
 
 			let hash = tx.transaction.hash.clone();
+			self.track_added(Queue::Future, &tx.transaction);
 			self.future.import(tx);
 			return Ok(Imported::Future { hash })
 		}
@@ -294,6 +349,65 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		self.import_to_ready(tx)
 	}
 
+	/// Makes sure that importing the transaction doesn't exceed the per-sender limits.
+	///
+	/// Transactions replacing a ready transaction of the same sender are always allowed, since
+	/// they don't increase the number of transactions the sender has in the pool.
+	fn check_sender_limits(&self, tx: &WaitingTransaction<Hash, Ex>) -> error::Result<()> {
+		let Some(ref limits) = self.sender_limits else { return Ok(()) };
+		let Some(sender) = limits.sender(&*tx.transaction) else { return Ok(()) };
+		let (ready, future) = self.senders.get(sender).copied().unwrap_or_default();
+
+		let (count, limit) = if tx.is_ready() {
+			let provided_tags = self.ready.provided_tags();
+			if tx.transaction.provides.iter().any(|tag| provided_tags.contains_key(tag)) {
+				return Ok(())
+			}
+			(ready, limits.ready)
+		} else {
+			(future, limits.future)
+		};
+
+		if count >= limit {
+			debug!(
+				target: LOG_TARGET,
+				"[{:?}] Sender limit reached ({} transactions)", tx.transaction.hash, count,
+			);
+			return Err(error::Error::SenderLimitReached)
+		}
+
+		Ok(())
+	}
+
+	/// Updates the per-sender and network lane counters after `tx` was added to the `queue`.
+	fn track_added(&mut self, queue: Queue, tx: &Transaction<Hash, Ex>) {
+		self.track(queue, tx, |count, value| *count += value)
+	}
+
+	/// Updates the per-sender and network lane counters after `tx` was removed from the `queue`.
+	fn track_removed(&mut self, queue: Queue, tx: &Transaction<Hash, Ex>) {
+		self.track(queue, tx, |count, value| *count = count.saturating_sub(value))
+	}
+
+	fn track(&mut self, queue: Queue, tx: &Transaction<Hash, Ex>, update: fn(&mut usize, usize)) {
+		if queue == Queue::Ready && tx.from_network {
+			update(&mut self.network_ready.0, 1);
+			update(&mut self.network_ready.1, tx.bytes);
+		}
+
+		let Some(sender) = self.sender_limits.as_ref().and_then(|limits| limits.sender(tx)) else {
+			return
+		};
+		let counts = self.senders.entry(sender.to_vec()).or_default();
+		match queue {
+			Queue::Ready => update(&mut counts.0, 1),
+			Queue::Future => update(&mut counts.1, 1),
+		}
+		if *counts == (0, 0) {
+			self.senders.remove(sender);
+		}
+	}
+
 	/// Imports transaction to ready queue.
 	///
 	/// NOTE the transaction has to have all requirements satisfied.
@@ -311,13 +425,32 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 
 		// take first transaction from the list
 		while let Some(tx) = to_import.pop() {
+			// the first transaction was already checked on import, promoted ones are checked
+			// before they unlock any further transactions
+			if !first && self.check_sender_limits(&tx).is_err() {
+				let current_hash = tx.transaction.hash.clone();
+				if !self.postpone(tx) {
+					failed.push(current_hash);
+				}
+				continue
+			}
+
 			// find transactions in Future that it unlocks
-			to_import.append(&mut self.future.satisfy_tags(&tx.transaction.provides));
+			let mut unlocked = self.future.satisfy_tags(&tx.transaction.provides);
+			for unlocked in &unlocked {
+				self.track_removed(Queue::Future, &unlocked.transaction);
+			}
+			to_import.append(&mut unlocked);
 
 			// import this transaction
 			let current_hash = tx.transaction.hash.clone();
+			let transaction = tx.transaction.clone();
 			match self.ready.import(tx) {
 				Ok(mut replaced) => {
+					self.track_added(Queue::Ready, &transaction);
+					for replaced in &replaced {
+						self.track_removed(Queue::Ready, replaced);
+					}
 					if !first {
 						promoted.push(current_hash);
 					}
@@ -345,7 +478,9 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		if removed.iter().any(|tx| tx.hash == hash) {
 			// We still need to remove all transactions that we promoted
 			// since they depend on each other and will never get to the best iterator.
-			self.ready.remove_subtree(&promoted);
+			for removed in self.ready.remove_subtree(&promoted) {
+				self.track_removed(Queue::Ready, &removed);
+			}
 
 			debug!(target: LOG_TARGET, "[{:?}] Cycle detected, bailing.", hash);
 			return Err(error::Error::CycleDetected)
@@ -354,6 +489,34 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		Ok(Imported::Ready { hash, promoted, failed, removed })
 	}
 
+	/// Moves a ready transaction that exceeds the per-sender limits back to the future queue.
+	///
+	/// The transaction waits for the required tags that are currently provided by the ready
+	/// queue, i.e. it is promoted again once the transactions providing them are pruned. Returns
+	/// `false` and drops the transaction if none of its required tags is provided by the ready
+	/// queue anymore, since it could never be promoted again.
+	fn postpone(&mut self, mut tx: WaitingTransaction<Hash, Ex>) -> bool {
+		let provided_tags = self.ready.provided_tags();
+		tx.missing_tags = tx
+			.transaction
+			.requires
+			.iter()
+			.filter(|tag| provided_tags.contains_key(*tag))
+			.cloned()
+			.collect();
+		if tx.is_ready() {
+			return false
+		}
+
+		debug!(
+			target: LOG_TARGET,
+			"[{:?}] Sender limit reached, kept in future", tx.transaction.hash,
+		);
+		self.track_added(Queue::Future, &tx.transaction);
+		self.future.import(tx);
+		true
+	}
+
 	/// Returns an iterator over ready transactions in the pool.
 	pub fn ready(&self) -> BestIterator<Hash, Ex> {
 		self.ready.get()
@@ -386,6 +549,9 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	/// them. Technically the worst transaction should be evaluated by computing the entire pending
 	/// set. We use a simplified approach to remove transactions with the lowest priority first or
 	/// those that occupy the pool for the longest time in case priority is the same.
+	///
+	/// Transactions received from the network are first evicted until they fit into the ready
+	/// limit reduced by the capacity reserved for transactions submitted to this node.
 	pub fn enforce_limits(
 		&mut self,
 		ready: &Limit,
@@ -393,31 +559,18 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	) -> Vec<Arc<Transaction<Hash, Ex>>> {
 		let mut removed = vec![];
 
-		while ready.is_exceeded(self.ready.len(), self.ready.bytes()) {
-			// find the worst transaction
-			let worst = self.ready.fold::<TransactionRef<Hash, Ex>, _>(|worst, current| {
-				let transaction = &current.transaction;
-				worst
-					.map(|worst| {
-						// Here we don't use `TransactionRef`'s ordering implementation because
-						// while it prefers priority like need here, it also prefers older
-						// transactions for inclusion purposes and limit enforcement needs to prefer
-						// newer transactions instead and drop the older ones.
-						match worst.transaction.priority.cmp(&transaction.transaction.priority) {
-							Ordering::Less => worst,
-							Ordering::Equal =>
-								if worst.insertion_id > transaction.insertion_id {
-									transaction.clone()
-								} else {
-									worst
-								},
-							Ordering::Greater => transaction.clone(),
-						}
-					})
-					.or_else(|| Some(transaction.clone()))
-			});
+		if let Some(network) = self.network_limit(ready) {
+			while network.is_exceeded(self.network_ready.0, self.network_ready.1) {
+				match self.worst_ready(|tx| tx.from_network) {
+					Some(worst) =>
+						removed.append(&mut self.remove_subtree(&[worst.transaction.hash.clone()])),
+					None => break,
+				}
+			}
+		}
 
-			if let Some(worst) = worst {
+		while ready.is_exceeded(self.ready.len(), self.ready.bytes()) {
+			if let Some(worst) = self.worst_ready(|_| true) {
 				removed.append(&mut self.remove_subtree(&[worst.transaction.hash.clone()]))
 			} else {
 				break
@@ -442,6 +595,62 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		removed
 	}
 
+	/// Returns true if the queues exceed the given limits.
+	///
+	/// Apart from the limits themselves, the ready transactions received from the network must
+	/// fit into the ready limit reduced by the reserved capacity.
+	pub fn is_exceeded(&self, ready: &Limit, future: &Limit) -> bool {
+		ready.is_exceeded(self.ready.len(), self.ready.bytes()) ||
+			future.is_exceeded(self.future.len(), self.future.bytes()) ||
+			self.network_limit(ready).map_or(false, |network| {
+				network.is_exceeded(self.network_ready.0, self.network_ready.1)
+			})
+	}
+
+	/// Returns the part of the `ready` limit available to transactions received from the network,
+	/// or `None` if no capacity is reserved.
+	fn network_limit(&self, ready: &Limit) -> Option<Limit> {
+		let reserved = &self.local_reserved;
+		(reserved.count > 0 || reserved.total_bytes > 0).then(|| Limit {
+			count: ready.count.saturating_sub(reserved.count),
+			total_bytes: ready.total_bytes.saturating_sub(reserved.total_bytes),
+		})
+	}
+
+	/// Finds the worst ready transaction matching the filter.
+	///
+	/// The worst transaction is the one with the lowest priority or the most recently inserted one
+	/// in case priority is the same.
+	fn worst_ready(
+		&self,
+		filter: impl Fn(&Transaction<Hash, Ex>) -> bool,
+	) -> Option<TransactionRef<Hash, Ex>> {
+		self.ready.fold::<TransactionRef<Hash, Ex>, _>(|worst, current| {
+			let transaction = &current.transaction;
+			if !filter(&transaction.transaction) {
+				return worst
+			}
+			worst
+				.map(|worst| {
+					// Here we don't use `TransactionRef`'s ordering implementation because
+					// while it prefers priority like need here, it also prefers older
+					// transactions for inclusion purposes and limit enforcement needs to prefer
+					// newer transactions instead and drop the older ones.
+					match worst.transaction.priority.cmp(&transaction.transaction.priority) {
+						Ordering::Less => worst,
+						Ordering::Equal =>
+							if worst.insertion_id > transaction.insertion_id {
+								transaction.clone()
+							} else {
+								worst
+							},
+						Ordering::Greater => transaction.clone(),
+					}
+				})
+				.or_else(|| Some(transaction.clone()))
+		})
+	}
+
 	/// Removes all transactions represented by the hashes and all other transactions
 	/// that depend on them.
 	///
@@ -452,13 +661,23 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	/// and you don't want them to be stored in the pool use `prune_tags` method.
 	pub fn remove_subtree(&mut self, hashes: &[Hash]) -> Vec<Arc<Transaction<Hash, Ex>>> {
 		let mut removed = self.ready.remove_subtree(hashes);
-		removed.extend(self.future.remove(hashes));
+		for tx in &removed {
+			self.track_removed(Queue::Ready, tx);
+		}
+		for tx in self.future.remove(hashes) {
+			self.track_removed(Queue::Future, &tx);
+			removed.push(tx);
+		}
 		removed
 	}
 
 	/// Removes and returns all transactions from the future queue.
 	pub fn clear_future(&mut self) -> Vec<Arc<Transaction<Hash, Ex>>> {
-		self.future.clear()
+		let removed = self.future.clear();
+		for tx in &removed {
+			self.track_removed(Queue::Future, tx);
+		}
+		removed
 	}
 
 	/// Prunes transactions that provide given list of tags.
@@ -482,11 +701,23 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			// store the tags for next submission
 			recently_pruned.insert(tag);
 		}
+		for tx in &to_import {
+			self.track_removed(Queue::Future, &tx.transaction);
+		}
+		for tx in &pruned {
+			self.track_removed(Queue::Ready, tx);
+		}
 
 		let mut promoted = vec![];
 		let mut failed = vec![];
 		for tx in to_import {
 			let hash = tx.transaction.hash.clone();
+			if self.check_sender_limits(&tx).is_err() {
+				if !self.postpone(tx) {
+					failed.push(hash);
+				}
+				continue
+			}
 			match self.import_to_ready(tx) {
 				Ok(res) => promoted.push(res),
 				Err(e) => {
@@ -529,6 +760,49 @@ impl Limit {
 	}
 }
 
+/// Per-sender queue limits.
+///
+/// The pool doesn't know about accounts, so the sender is taken from the nonce tag that FRAME's
+/// `CheckNonce` extension adds to the `provides` tags of the transaction validity. The tag is the
+/// SCALE encoding of `(AccountId, Nonce)`, so it's recognized by its length and the sender is the
+/// encoded account id it starts with. Transactions without such tag (e.g. unsigned ones) or with
+/// nonce tags of several accounts are not subject to the limits.
+///
+/// This is a heuristic: `account_id_len` and `nonce_len` have to match the types of the runtime,
+/// otherwise no transaction is limited. Runtimes providing nonce tags of another layout, or other
+/// tags of the same length, are not supported.
+#[derive(Debug, Clone)]
+pub struct SenderLimits {
+	/// Maximal number of ready transactions of a single sender.
+	pub ready: usize,
+	/// Maximal number of future transactions of a single sender.
+	pub future: usize,
+	/// Length of the encoded `AccountId` of the runtime.
+	pub account_id_len: usize,
+	/// Length of the encoded `Nonce` of the runtime.
+	pub nonce_len: usize,
+}
+
+impl Default for SenderLimits {
+	fn default() -> Self {
+		Self { ready: 64, future: 16, account_id_len: 32, nonce_len: 4 }
+	}
+}
+
+impl SenderLimits {
+	/// Returns the sender of the transaction, if it can be determined.
+	fn sender<'a, Hash, Ex>(&self, tx: &'a Transaction<Hash, Ex>) -> Option<&'a [u8]> {
+		let nonce_tag_len = self.account_id_len + self.nonce_len;
+		let mut senders = tx
+			.provides
+			.iter()
+			.filter(|tag| tag.len() == nonce_tag_len)
+			.map(|tag| &tag[..self.account_id_len]);
+		let sender = senders.next()?;
+		senders.all(|other| other == sender).then_some(sender)
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -549,6 +823,7 @@ mod tests {
 		provides: vec![],
 		propagate: true,
 		source: Source::External,
+		from_network: false,
 	};
 
 	#[test]
@@ -1042,4 +1317,267 @@ source: TransactionSource::External, requires: [03, 02], provides: [04], data: [
 		assert_eq!(pool.reject_future_transactions, true);
 		assert_eq!(pool.future.len(), 1);
 	}
+
+	#[test]
+	fn should_limit_transactions_per_sender() {
+		// given
+		let mut pool = pool().with_sender_limits(Some(SenderLimits {
+			ready: 2,
+			future: 1,
+			account_id_len: 2,
+			nonce_len: 1,
+		}));
+		pool.import(Transaction {
+			data: vec![1u8],
+			hash: 1,
+			provides: vec![vec![1, 1, 1]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		pool.import(Transaction {
+			data: vec![2u8],
+			hash: 2,
+			requires: vec![vec![1, 1, 1]],
+			provides: vec![vec![1, 1, 2]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+
+		// when
+		let err = pool.import(Transaction {
+			data: vec![3u8],
+			hash: 3,
+			requires: vec![vec![1, 1, 2]],
+			provides: vec![vec![1, 1, 3]],
+			..DEFAULT_TX.clone()
+		});
+
+		// then
+		assert!(matches!(err, Err(error::Error::SenderLimitReached)));
+
+		// replacing a transaction of the sender is still possible
+		pool.import(Transaction {
+			data: vec![4u8],
+			hash: 4,
+			priority: 10,
+			requires: vec![vec![1, 1, 1]],
+			provides: vec![vec![1, 1, 2]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		// and so is importing transactions of other senders
+		pool.import(Transaction {
+			data: vec![5u8],
+			hash: 5,
+			provides: vec![vec![2, 2, 1]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		assert_eq!(pool.ready().count(), 3);
+
+		// future transactions are limited separately
+		pool.import(Transaction {
+			data: vec![6u8],
+			hash: 6,
+			requires: vec![vec![1, 1, 8]],
+			provides: vec![vec![1, 1, 9]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		let err = pool.import(Transaction {
+			data: vec![7u8],
+			hash: 7,
+			requires: vec![vec![1, 1, 9]],
+			provides: vec![vec![1, 1, 10]],
+			..DEFAULT_TX.clone()
+		});
+		assert!(matches!(err, Err(error::Error::SenderLimitReached)));
+		assert_eq!(pool.future.len(), 1);
+
+		// the counters follow the transactions leaving the pool
+		assert_eq!(pool.senders.get(&vec![1, 1]), Some(&(2, 1)));
+		pool.remove_subtree(&[1, 5, 6]);
+		assert!(pool.senders.is_empty());
+	}
+
+	#[test]
+	fn should_keep_promoted_transactions_in_future_when_sender_limit_is_reached() {
+		// given
+		let mut pool = pool().with_sender_limits(Some(SenderLimits {
+			ready: 2,
+			future: 2,
+			account_id_len: 2,
+			nonce_len: 1,
+		}));
+		pool.import(Transaction {
+			data: vec![1u8],
+			hash: 1,
+			provides: vec![vec![1, 1, 1]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		pool.import(Transaction {
+			data: vec![3u8],
+			hash: 3,
+			requires: vec![vec![1, 1, 2]],
+			provides: vec![vec![1, 1, 3]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+
+		// when
+		let res = pool
+			.import(Transaction {
+				data: vec![2u8],
+				hash: 2,
+				requires: vec![vec![1, 1, 1]],
+				provides: vec![vec![1, 1, 2]],
+				..DEFAULT_TX.clone()
+			})
+			.unwrap();
+
+		// then
+		assert_eq!(
+			res,
+			Imported::Ready { hash: 2, promoted: vec![], failed: vec![], removed: vec![] }
+		);
+		assert_eq!(pool.ready().count(), 2);
+		assert_eq!(pool.future.len(), 1);
+		assert_eq!(pool.senders.get(&vec![1, 1]), Some(&(2, 1)));
+
+		// the transaction is promoted once the sender is below the limit again
+		let result = pool.prune_tags(vec![vec![1, 1, 1]]);
+		assert!(result.promoted.is_empty());
+		assert_eq!(pool.future.len(), 1);
+		let result = pool.prune_tags(vec![vec![1, 1, 2]]);
+		assert_eq!(
+			result.promoted,
+			vec![Imported::Ready { hash: 3, promoted: vec![], failed: vec![], removed: vec![] }]
+		);
+		assert_eq!(pool.future.len(), 0);
+		assert_eq!(pool.senders.get(&vec![1, 1]), Some(&(1, 0)));
+	}
+
+	#[test]
+	fn should_find_sender_with_configured_tag_layout() {
+		// 20-byte account ids and 8-byte nonces
+		let tag = |nonce: u64| [vec![1u8; 20], nonce.to_le_bytes().to_vec()].concat();
+		let first =
+			Transaction { data: vec![1u8], hash: 1, provides: vec![tag(0)], ..DEFAULT_TX.clone() };
+		let second = Transaction {
+			data: vec![2u8],
+			hash: 2,
+			requires: vec![tag(0)],
+			provides: vec![tag(1)],
+			..DEFAULT_TX.clone()
+		};
+
+		// the default layout doesn't match the tags
+		let mut default_pool = pool().with_sender_limits(Some(SenderLimits {
+			ready: 1,
+			future: 1,
+			..Default::default()
+		}));
+		default_pool.import(first.clone()).unwrap();
+		default_pool.import(second.clone()).unwrap();
+		assert_eq!(default_pool.ready().count(), 2);
+
+		// the configured one does
+		let mut pool = pool().with_sender_limits(Some(SenderLimits {
+			ready: 1,
+			future: 1,
+			account_id_len: 20,
+			nonce_len: 8,
+		}));
+		pool.import(first).unwrap();
+		let err = pool.import(second);
+		assert!(matches!(err, Err(error::Error::SenderLimitReached)));
+		assert_eq!(pool.senders.get(&vec![1u8; 20]), Some(&(1, 0)));
+	}
+
+	#[test]
+	fn should_not_limit_transactions_without_single_sender() {
+		// given
+		let mut pool = pool().with_sender_limits(Some(SenderLimits {
+			ready: 1,
+			future: 1,
+			account_id_len: 2,
+			nonce_len: 1,
+		}));
+		pool.import(Transaction {
+			data: vec![1u8],
+			hash: 1,
+			provides: vec![vec![1, 1, 1]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+
+		// when
+		// the transaction provides nonce tags of two accounts
+		pool.import(Transaction {
+			data: vec![2u8],
+			hash: 2,
+			provides: vec![vec![1, 1, 2], vec![2, 2, 1]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		// and the tag of this one doesn't look like a nonce tag
+		pool.import(Transaction {
+			data: vec![3u8],
+			hash: 3,
+			provides: vec![vec![1, 1, 3, 0]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+
+		// then
+		assert_eq!(pool.ready().count(), 3);
+	}
+
+	#[test]
+	fn should_reserve_ready_capacity_for_submitted_transactions() {
+		// given
+		let mut pool = pool().with_local_reserved(Limit { count: 1, total_bytes: 0 });
+		let limit = Limit { count: 2, total_bytes: 1000 };
+		// submitted via RPC, so the source is external
+		pool.import(Transaction {
+			data: vec![1u8],
+			hash: 1,
+			priority: 1,
+			provides: vec![vec![1]],
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		pool.import(Transaction {
+			data: vec![2u8],
+			hash: 2,
+			priority: 5,
+			provides: vec![vec![2]],
+			from_network: true,
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		pool.import(Transaction {
+			data: vec![3u8],
+			hash: 3,
+			priority: 10,
+			provides: vec![vec![3]],
+			from_network: true,
+			..DEFAULT_TX.clone()
+		})
+		.unwrap();
+		assert!(pool.is_exceeded(&limit, &limit));
+
+		// when
+		let removed = pool.enforce_limits(&limit, &limit);
+
+		// then
+		// the network transaction is removed even though the submitted one has lower priority
+		assert_eq!(removed.into_iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![2]);
+		let mut ready = pool.ready().map(|tx| tx.hash).collect::<Vec<_>>();
+		ready.sort();
+		assert_eq!(ready, vec![1, 3]);
+		assert_eq!(pool.network_ready, (1, 1));
+		assert!(!pool.is_exceeded(&limit, &limit));
+	}
 }
@@ -16,9 +16,13 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+	time::Duration,
+};
 
-use crate::LOG_TARGET;
+use crate::{journal::JournalOptions, pool_type::TransactionPoolType, LOG_TARGET};
 use futures::{channel::mpsc::Receiver, Future};
 use sc_transaction_pool_api::error;
 use sp_blockchain::TreeRoute;
@@ -28,6 +32,7 @@ use sp_runtime::{
 	transaction_validity::{
 		TransactionSource, TransactionTag as Tag, TransactionValidity, TransactionValidityError,
 	},
+	Percent,
 };
 use std::time::Instant;
 
@@ -119,6 +124,19 @@ pub struct Options {
 	pub reject_future_transactions: bool,
 	/// How long the extrinsic is banned for.
 	pub ban_time: Duration,
+	/// On-disk journal of pending transactions, disabled if `None`.
+	pub journal: Option<JournalOptions>,
+	/// Minimal priority increase required for a transaction to replace the ones providing the
+	/// same tags.
+	pub replacement_bump: Percent,
+	/// Limits of ready and future transactions per sender, disabled if `None`.
+	pub sender_limits: Option<base::SenderLimits>,
+	/// Part of the ready queue capacity reserved for transactions submitted to this node, i.e. not
+	/// received from the network.
+	pub local_reserved: base::Limit,
+	/// Type of the pool created by
+	/// [`FullTransactionPoolImpl::new_full`](crate::FullTransactionPoolImpl::new_full).
+	pub pool_type: TransactionPoolType,
 }
 
 impl Default for Options {
@@ -128,6 +146,11 @@ impl Default for Options {
 			future: base::Limit { count: 512, total_bytes: 1 * 1024 * 1024 },
 			reject_future_transactions: false,
 			ban_time: Duration::from_secs(60 * 30),
+			journal: None,
+			replacement_bump: Percent::from_percent(0),
+			sender_limits: None,
+			local_reserved: base::Limit { count: 0, total_bytes: 0 },
+			pool_type: TransactionPoolType::default(),
 		}
 	}
 }
@@ -163,6 +186,24 @@ impl<B: ChainApi> Pool<B> {
 		Ok(self.validated_pool.submit(validated_transactions.into_values()))
 	}
 
+	/// Imports a bunch of unverified extrinsics received from the network to the pool.
+	///
+	/// Unlike the transactions submitted with `submit_at`, these can't use the ready queue
+	/// capacity reserved for transactions submitted to this node.
+	pub async fn submit_from_network(
+		&self,
+		at: <B::Block as BlockT>::Hash,
+		xts: impl IntoIterator<Item = ExtrinsicFor<B>>,
+	) -> Result<Vec<Result<ExtrinsicHash<B>, B::Error>>, B::Error> {
+		let xts = xts.into_iter().map(|xt| (TransactionSource::External, xt));
+		let validated_transactions = self.verify(at, xts, CheckBannedBeforeVerify::Yes).await?;
+		Ok(self.validated_pool.submit(
+			validated_transactions
+				.into_values()
+				.map(ValidatedTransaction::received_from_network),
+		))
+	}
+
 	/// Resubmit the given extrinsics to the pool.
 	///
 	/// This does not check if a transaction is banned, before we verify it again.
@@ -341,6 +382,12 @@ impl<B: ChainApi> Pool<B> {
 		// Try to re-validate pruned transactions since some of them might be still valid.
 		// note that `known_imported_hashes` will be rejected here due to temporary ban.
 		let pruned_hashes = prune_status.pruned.iter().map(|tx| tx.hash).collect::<Vec<_>>();
+		let from_network = prune_status
+			.pruned
+			.iter()
+			.filter(|tx| tx.from_network)
+			.map(|tx| tx.hash)
+			.collect::<HashSet<_>>();
 		let pruned_transactions =
 			prune_status.pruned.into_iter().map(|tx| (tx.source, tx.data.clone()));
 
@@ -354,7 +401,18 @@ impl<B: ChainApi> Pool<B> {
 			&BlockId::Hash(at),
 			known_imported_hashes,
 			pruned_hashes,
-			reverified_transactions.into_values().collect(),
+			reverified_transactions
+				.into_iter()
+				.map(
+					|(hash, tx)| {
+						if from_network.contains(&hash) {
+							tx.received_from_network()
+						} else {
+							tx
+						}
+					},
+				)
+				.collect(),
 		)
 	}
 
@@ -735,6 +793,47 @@ mod tests {
 		assert_eq!(pool.validated_pool().status().future, 0);
 	}
 
+	#[test]
+	fn should_keep_network_transactions_out_of_reserved_capacity() {
+		// given
+		let limit = Limit { count: 2, total_bytes: 1000 };
+		let options = Options {
+			ready: limit.clone(),
+			future: limit.clone(),
+			local_reserved: Limit { count: 1, total_bytes: 0 },
+			..Default::default()
+		};
+
+		let api = Arc::new(TestApi::default());
+		let pool = Pool::new(options, true.into(), api.clone());
+		let at = api.expect_hash_from_number(0);
+
+		// after validation `Transfer` will have priority set to 4 (validate_transaction mock)
+		let xt = uxt(Transfer {
+			from: Alice.into(),
+			to: AccountId::from_h256(H256::from_low_u64_be(2)),
+			amount: 5,
+			nonce: 0,
+		});
+		let submitted = block_on(pool.submit_one(at, SOURCE, xt)).unwrap();
+		let xt = ExtrinsicBuilder::new_include_data(Vec::new()).build();
+		let received = block_on(pool.submit_from_network(at, vec![xt])).unwrap().pop().unwrap();
+
+		// when
+		let xt = ExtrinsicBuilder::new_indexed_call(Vec::new()).build();
+		let result = block_on(pool.submit_from_network(at, vec![xt])).unwrap().pop().unwrap();
+
+		// then
+		// the network transactions don't fit into the unreserved capacity, even though the
+		// transaction submitted via RPC has lower priority
+		assert!(matches!(result, Err(sc_transaction_pool_api::error::Error::ImmediatelyDropped)));
+		let mut ready = pool.validated_pool().ready().map(|tx| tx.hash).collect::<Vec<_>>();
+		ready.sort();
+		let mut expected = vec![submitted, received.unwrap()];
+		expected.sort();
+		assert_eq!(ready, expected);
+	}
+
 	#[test]
 	fn should_reject_transactions_with_no_provides() {
 		// given
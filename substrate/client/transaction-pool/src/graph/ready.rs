@@ -27,7 +27,7 @@ use crate::LOG_TARGET;
 use log::{debug, trace};
 use sc_transaction_pool_api::error;
 use serde::Serialize;
-use sp_runtime::{traits::Member, transaction_validity::TransactionTag as Tag};
+use sp_runtime::{traits::Member, transaction_validity::TransactionTag as Tag, PerThing, Percent};
 
 use super::{
 	base_pool::Transaction,
@@ -118,6 +118,9 @@ pub struct ReadyTransactions<Hash: hash::Hash + Eq, Ex> {
 	/// Best transactions that are ready to be included to the block without any other previous
 	/// transaction.
 	best: BTreeSet<TransactionRef<Hash, Ex>>,
+	/// Minimal priority increase (relative to the replaced transactions) required to replace
+	/// transactions providing the same tags.
+	replacement_bump: Percent,
 }
 
 impl<Hash, Ex> tracked_map::Size for ReadyTx<Hash, Ex> {
@@ -133,11 +136,19 @@ impl<Hash: hash::Hash + Eq, Ex> Default for ReadyTransactions<Hash, Ex> {
 			provided_tags: Default::default(),
 			ready: Default::default(),
 			best: Default::default(),
+			replacement_bump: Default::default(),
 		}
 	}
 }
 
 impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
+	/// Sets the minimal priority increase required to replace a transaction.
+	///
+	/// With zero bump the replacement only needs to have strictly higher priority.
+	pub fn set_replacement_bump(&mut self, bump: Percent) {
+		self.replacement_bump = bump;
+	}
+
 	/// Borrows a map of tags that are provided by transactions in this queue.
 	pub fn provided_tags(&self) -> &HashMap<Tag, Hash> {
 		&self.provided_tags
@@ -233,10 +244,7 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 	}
 
 	/// Fold a list of ready transactions to compute a single value.
-	pub fn fold<R, F: FnMut(Option<R>, &ReadyTx<Hash, Ex>) -> Option<R>>(
-		&mut self,
-		f: F,
-	) -> Option<R> {
+	pub fn fold<R, F: FnMut(Option<R>, &ReadyTx<Hash, Ex>) -> Option<R>>(&self, f: F) -> Option<R> {
 		self.ready.read().values().fold(None, f)
 	}
 
@@ -414,7 +422,8 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 	///
 	/// In case that's true it determines if the priority of transactions that
 	/// we are about to replace is lower than the priority of the replacement transaction.
-	/// We remove/replace old transactions in case they have lower priority.
+	/// We remove/replace old transactions in case they have lower priority and the
+	/// replacement exceeds it by at least the configured replacement bump.
 	///
 	/// In case replacement is successful returns a list of removed transactions
 	/// and a list of hashes that are still in pool and gets unlocked by the new transaction.
@@ -447,7 +456,9 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 			};
 
 			// bail - the transaction has too low priority to replace the old ones
-			if old_priority >= tx.priority {
+			let required_priority =
+				old_priority.saturating_add(self.replacement_bump.mul_ceil(old_priority));
+			if old_priority >= tx.priority || required_priority > tx.priority {
 				return Err(error::Error::TooLowPriority { old: old_priority, new: tx.priority })
 			}
 
@@ -602,6 +613,7 @@ mod tests {
 			provides: vec![vec![3], vec![4]],
 			propagate: true,
 			source: Source::External,
+			from_network: false,
 		}
 	}
 
@@ -641,6 +653,33 @@ mod tests {
 		assert_eq!(ready.get().count(), 1);
 	}
 
+	#[test]
+	fn should_require_replacement_bump() {
+		// given
+		let mut ready = ReadyTransactions::default();
+		ready.set_replacement_bump(Percent::from_percent(10));
+		let mut tx1 = tx(1);
+		tx1.requires.clear();
+		tx1.priority = 100;
+		let mut tx2 = tx(2);
+		tx2.requires.clear();
+		tx2.priority = 105;
+		let mut tx3 = tx(3);
+		tx3.requires.clear();
+		tx3.priority = 110;
+
+		// when
+		import(&mut ready, tx1).unwrap();
+
+		// then
+		assert!(matches!(
+			import(&mut ready, tx2),
+			Err(error::Error::TooLowPriority { old: 100, new: 105 })
+		));
+		assert_eq!(import(&mut ready, tx3).unwrap().len(), 1);
+		assert_eq!(ready.get().next().unwrap().hash, 3);
+	}
+
 	#[test]
 	fn should_replace_multiple_transactions_correctly() {
 		// given
@@ -712,6 +751,7 @@ mod tests {
 			provides: vec![],
 			propagate: true,
 			source: Source::External,
+			from_network: false,
 		};
 
 		// when
@@ -72,6 +72,7 @@ impl<Hash, Ex, Error> ValidatedTransaction<Hash, Ex, Error> {
 			bytes,
 			hash,
 			source,
+			from_network: false,
 			priority: validity.priority,
 			requires: validity.requires,
 			provides: validity.provides,
@@ -79,6 +80,14 @@ impl<Hash, Ex, Error> ValidatedTransaction<Hash, Ex, Error> {
 			valid_till: at.saturated_into::<u64>().saturating_add(validity.longevity),
 		})
 	}
+
+	/// Marks the valid transaction as received from the network.
+	pub fn received_from_network(mut self) -> Self {
+		if let Self::Valid(ref mut tx) = self {
+			tx.from_network = true;
+		}
+		self
+	}
 }
 
 /// A type of validated transaction stored in the pool.
@@ -86,7 +95,7 @@ pub type ValidatedTransactionFor<B> =
 	ValidatedTransaction<ExtrinsicHash<B>, ExtrinsicFor<B>, <B as ChainApi>::Error>;
 
 /// A closure that returns true if the local node is a validator that can author blocks.
-pub struct IsValidator(Box<dyn Fn() -> bool + Send + Sync>);
+pub struct IsValidator(pub(crate) Box<dyn Fn() -> bool + Send + Sync>);
 
 impl From<bool> for IsValidator {
 	fn from(is_validator: bool) -> Self {
@@ -114,7 +123,10 @@ pub struct ValidatedPool<B: ChainApi> {
 impl<B: ChainApi> ValidatedPool<B> {
 	/// Create a new transaction pool.
 	pub fn new(options: Options, is_validator: IsValidator, api: Arc<B>) -> Self {
-		let base_pool = base::BasePool::new(options.reject_future_transactions);
+		let base_pool = base::BasePool::new(options.reject_future_transactions)
+			.with_replacement_bump(options.replacement_bump)
+			.with_sender_limits(options.sender_limits.clone())
+			.with_local_reserved(options.local_reserved.clone());
 		let ban_time = options.ban_time;
 		Self {
 			is_validator,
@@ -227,14 +239,15 @@ impl<B: ChainApi> ValidatedPool<B> {
 	}
 
 	fn enforce_limits(&self) -> HashSet<ExtrinsicHash<B>> {
-		let status = self.pool.read().status();
 		let ready_limit = &self.options.ready;
 		let future_limit = &self.options.future;
+		let (status, is_exceeded) = {
+			let pool = self.pool.read();
+			(pool.status(), pool.is_exceeded(ready_limit, future_limit))
+		};
 
 		log::debug!(target: LOG_TARGET, "Pool Status: {:?}", status);
-		if ready_limit.is_exceeded(status.ready, status.ready_bytes) ||
-			future_limit.is_exceeded(status.future, status.future_bytes)
-		{
+		if is_exceeded {
 			log::debug!(
 				target: LOG_TARGET,
 				"Enforcing limits ({}/{}kB ready, {}/{}kB future",
@@ -0,0 +1,387 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! On-disk journal of pending transactions.
+//!
+//! When enabled through [`Options::journal`](crate::Options::journal), the pending
+//! transactions of the pool are periodically written to a file, and once more when the pool
+//! is dropped. At startup the transactions found in the journal are validated again and
+//! re-imported, so pending transactions survive a node restart.
+//!
+//! The [`BasicPool`](crate::BasicPool) journals its ready and future transactions, the
+//! [`ForkAwareTxPool`](crate::ForkAwareTxPool) journals its mempool.
+
+use std::{
+	collections::HashMap,
+	fs, io,
+	path::PathBuf,
+	sync::Arc,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use codec::{Decode, Encode};
+use parking_lot::Mutex;
+use sp_runtime::transaction_validity::TransactionSource;
+
+use crate::{
+	graph::{BlockHash, ChainApi, ExtrinsicFor, ExtrinsicHash, Pool},
+	LOG_TARGET,
+};
+
+/// Version of the journal file format.
+const JOURNAL_VERSION: u32 = 1;
+
+/// How often the journal is written to disk.
+const JOURNAL_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Configuration of the transaction journal.
+#[derive(Debug, Clone)]
+pub struct JournalOptions {
+	/// Path of the journal file.
+	pub path: PathBuf,
+	/// Transactions that entered the pool longer ago than this are not journaled nor
+	/// restored.
+	pub max_age: Duration,
+	/// Maximum total size of the encoded transactions kept in the journal.
+	///
+	/// Ready transactions are journaled first, in the order they would be included in a block.
+	pub max_bytes: usize,
+}
+
+impl Default for JournalOptions {
+	fn default() -> Self {
+		Self {
+			path: PathBuf::from("txpool_journal"),
+			max_age: Duration::from_secs(3 * 60 * 60),
+			max_bytes: 10 * 1024 * 1024,
+		}
+	}
+}
+
+#[derive(Debug, Encode, Decode, PartialEq)]
+struct JournalEntry {
+	/// Unix time (in seconds) at which the transaction first entered the pool.
+	imported_at: u64,
+	/// Source of the transaction.
+	source: TransactionSource,
+	/// Encoded transaction.
+	data: Vec<u8>,
+}
+
+fn unix_time_secs() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// Transactions that can be written to the journal.
+pub(crate) trait Journaled<Api: ChainApi> {
+	/// Returns the hash of the transaction.
+	fn hash_of(&self, xt: &ExtrinsicFor<Api>) -> ExtrinsicHash<Api>;
+
+	/// Returns the pending transactions with their encoding, in the order they are journaled.
+	fn pending(&self) -> Vec<(ExtrinsicHash<Api>, TransactionSource, Vec<u8>)>;
+}
+
+impl<Api: ChainApi> Journaled<Api> for Pool<Api> {
+	fn hash_of(&self, xt: &ExtrinsicFor<Api>) -> ExtrinsicHash<Api> {
+		Pool::hash_of(self, xt)
+	}
+
+	fn pending(&self) -> Vec<(ExtrinsicHash<Api>, TransactionSource, Vec<u8>)> {
+		let base_pool = self.validated_pool().pool.read();
+		let ready = base_pool.ready();
+		let futures = base_pool.futures();
+		ready
+			.map(|tx| (tx.hash, tx.source, tx.data.encode()))
+			.chain(futures.map(|tx| (tx.hash, tx.source, tx.data.encode())))
+			.collect()
+	}
+}
+
+/// Journal of the transactions of a pool.
+pub(crate) struct Journal<Api: ChainApi> {
+	options: JournalOptions,
+	/// Time at which the currently journaled transactions entered the pool.
+	imported_at: Mutex<HashMap<ExtrinsicHash<Api>, u64>>,
+}
+
+impl<Api: ChainApi> Journal<Api> {
+	/// Creates a new journal.
+	pub(crate) fn new(options: JournalOptions) -> Self {
+		Self { options, imported_at: Default::default() }
+	}
+
+	fn is_expired(&self, imported_at: u64, now: u64) -> bool {
+		now.saturating_sub(imported_at) > self.options.max_age.as_secs()
+	}
+
+	/// Reads the transactions stored in the journal.
+	///
+	/// Transactions that are too old or cannot be decoded are skipped.
+	pub(crate) fn load(
+		&self,
+		pool: &impl Journaled<Api>,
+	) -> Vec<(TransactionSource, ExtrinsicFor<Api>)> {
+		self.load_at(pool, unix_time_secs())
+	}
+
+	fn load_at(
+		&self,
+		pool: &impl Journaled<Api>,
+		now: u64,
+	) -> Vec<(TransactionSource, ExtrinsicFor<Api>)> {
+		let bytes = match fs::read(&self.options.path) {
+			Ok(bytes) => bytes,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => return Vec::new(),
+			Err(e) => {
+				log::warn!(
+					target: LOG_TARGET,
+					"Failed to read transaction journal {}: {}",
+					self.options.path.display(),
+					e,
+				);
+				return Vec::new()
+			},
+		};
+
+		let entries = match <(u32, Vec<JournalEntry>)>::decode(&mut &bytes[..]) {
+			Ok((JOURNAL_VERSION, entries)) => entries,
+			Ok((version, _)) => {
+				log::warn!(
+					target: LOG_TARGET,
+					"Ignoring transaction journal with unsupported version {}",
+					version,
+				);
+				return Vec::new()
+			},
+			Err(e) => {
+				log::warn!(target: LOG_TARGET, "Ignoring corrupted transaction journal: {}", e);
+				return Vec::new()
+			},
+		};
+
+		let mut imported_at = self.imported_at.lock();
+		entries
+			.into_iter()
+			.filter(|entry| !self.is_expired(entry.imported_at, now))
+			.filter_map(|entry| {
+				let xt = ExtrinsicFor::<Api>::decode(&mut &entry.data[..])
+					.map_err(
+						|e| log::debug!(target: LOG_TARGET, "Skipping undecodable journal entry: {}", e),
+					)
+					.ok()?;
+				imported_at.insert(pool.hash_of(&xt), entry.imported_at);
+				Some((entry.source, xt))
+			})
+			.collect()
+	}
+
+	/// Writes the pending transactions of the pool to the journal.
+	pub(crate) fn store(&self, pool: &impl Journaled<Api>) -> io::Result<()> {
+		self.store_at(pool, unix_time_secs())
+	}
+
+	fn store_at(&self, pool: &impl Journaled<Api>, now: u64) -> io::Result<()> {
+		let transactions = pool.pending();
+		let entries = {
+			let mut imported_at = self.imported_at.lock();
+
+			let mut journaled = HashMap::new();
+			let mut total_bytes = 0usize;
+			let mut entries = Vec::new();
+
+			for (hash, source, data) in transactions {
+				let tx_imported_at = imported_at.get(&hash).copied().unwrap_or(now);
+				if self.is_expired(tx_imported_at, now) {
+					continue
+				}
+				total_bytes = total_bytes.saturating_add(data.len());
+				if total_bytes > self.options.max_bytes {
+					break
+				}
+				journaled.insert(hash, tx_imported_at);
+				entries.push(JournalEntry { imported_at: tx_imported_at, source, data });
+			}
+
+			*imported_at = journaled;
+			entries
+		};
+
+		if let Some(parent) = self.options.path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		let tmp_path = self.options.path.with_extension("tmp");
+		fs::write(&tmp_path, (JOURNAL_VERSION, entries).encode())?;
+		fs::rename(&tmp_path, &self.options.path)
+	}
+
+	/// Writes the journal, logging any failure.
+	pub(crate) fn flush(&self, pool: &impl Journaled<Api>) {
+		if let Err(e) = self.store(pool) {
+			log::warn!(
+				target: LOG_TARGET,
+				"Failed to write transaction journal {}: {}",
+				self.options.path.display(),
+				e,
+			);
+		}
+	}
+}
+
+/// Restores the journaled transactions into the pool and keeps the journal up to date.
+///
+/// The restored transactions are validated at the block `at`.
+pub(crate) async fn journal_task<Api: ChainApi>(
+	journal: Arc<Journal<Api>>,
+	pool: Arc<Pool<Api>>,
+	at: BlockHash<Api>,
+) {
+	let restored = journal.load(&*pool);
+	if !restored.is_empty() {
+		let total = restored.len();
+		let mut by_source = Vec::<(TransactionSource, Vec<ExtrinsicFor<Api>>)>::new();
+		for (source, xt) in restored {
+			match by_source.iter_mut().find(|(s, _)| *s == source) {
+				Some((_, xts)) => xts.push(xt),
+				None => by_source.push((source, vec![xt])),
+			}
+		}
+
+		let mut imported = 0;
+		for (source, xts) in by_source {
+			match pool.submit_at(at, source, xts).await {
+				Ok(results) => imported += results.iter().filter(|r| r.is_ok()).count(),
+				Err(e) => log::warn!(
+					target: LOG_TARGET,
+					"Failed to restore journaled transactions at {:?}: {}",
+					at,
+					e,
+				),
+			}
+		}
+		log::info!(
+			target: LOG_TARGET,
+			"Restored {} of {} transactions from the journal",
+			imported,
+			total,
+		);
+	}
+
+	flush_task(journal, pool).await
+}
+
+/// Keeps the journal up to date.
+pub(crate) async fn flush_task<Api: ChainApi, P: Journaled<Api>>(
+	journal: Arc<Journal<Api>>,
+	pool: Arc<P>,
+) {
+	loop {
+		futures_timer::Delay::new(JOURNAL_FLUSH_INTERVAL).await;
+		journal.flush(&*pool);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::{pool, uxt};
+	use futures::executor::block_on;
+	use substrate_test_runtime::{AccountId, Transfer, H256};
+	use substrate_test_runtime_client::AccountKeyring::Alice;
+
+	fn transfer(nonce: u64) -> ExtrinsicFor<crate::tests::TestApi> {
+		uxt(Transfer {
+			from: Alice.into(),
+			to: AccountId::from_h256(H256::from_low_u64_be(2)),
+			amount: 5,
+			nonce,
+		})
+	}
+
+	fn journal(dir: &tempfile::TempDir, max_bytes: usize) -> JournalOptions {
+		JournalOptions {
+			path: dir.path().join("journal"),
+			max_age: Duration::from_secs(60),
+			max_bytes,
+		}
+	}
+
+	#[test]
+	fn should_restore_journaled_transactions() {
+		let dir = tempfile::tempdir().unwrap();
+		let (pool, api) = pool();
+		let at = api.expect_hash_from_number(0);
+		block_on(pool.submit_at(at, TransactionSource::External, vec![transfer(0), transfer(2)]))
+			.unwrap();
+		assert_eq!(pool.validated_pool().status().ready, 1);
+		assert_eq!(pool.validated_pool().status().future, 1);
+
+		Journal::new(journal(&dir, usize::MAX)).store_at(&pool, 100).unwrap();
+
+		let (pool, _) = self::pool();
+		let restored = Journal::new(journal(&dir, usize::MAX)).load_at(&pool, 150);
+		assert_eq!(
+			restored,
+			vec![
+				(TransactionSource::External, transfer(0)),
+				(TransactionSource::External, transfer(2))
+			]
+		);
+	}
+
+	#[test]
+	fn should_skip_expired_transactions() {
+		let dir = tempfile::tempdir().unwrap();
+		let (pool, api) = pool();
+		let at = api.expect_hash_from_number(0);
+		block_on(pool.submit_one(at, TransactionSource::External, transfer(0))).unwrap();
+
+		let journal = Journal::new(journal(&dir, usize::MAX));
+		journal.store_at(&pool, 100).unwrap();
+		// keeps the original import time of the transaction
+		journal.store_at(&pool, 130).unwrap();
+
+		assert_eq!(journal.load_at(&pool, 160).len(), 1);
+		assert!(journal.load_at(&pool, 161).is_empty());
+	}
+
+	#[test]
+	fn should_respect_size_limit() {
+		let dir = tempfile::tempdir().unwrap();
+		let (pool, api) = pool();
+		let at = api.expect_hash_from_number(0);
+		block_on(pool.submit_at(at, TransactionSource::External, vec![transfer(0), transfer(1)]))
+			.unwrap();
+
+		let max_bytes = transfer(0).encode().len();
+		let journal = Journal::new(journal(&dir, max_bytes));
+		journal.store_at(&pool, 100).unwrap();
+
+		assert_eq!(journal.load_at(&pool, 100), vec![(TransactionSource::External, transfer(0))]);
+	}
+
+	#[test]
+	fn should_ignore_missing_or_corrupted_journal() {
+		let dir = tempfile::tempdir().unwrap();
+		let (pool, _) = pool();
+		let journal = Journal::new(journal(&dir, usize::MAX));
+		assert!(journal.load(&pool).is_empty());
+
+		fs::write(dir.path().join("journal"), b"corrupted").unwrap();
+		assert!(journal.load(&pool).is_empty());
+	}
+}
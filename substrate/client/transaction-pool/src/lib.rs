@@ -25,8 +25,11 @@
 mod api;
 mod enactment_state;
 pub mod error;
+pub mod fork_aware_txpool;
 mod graph;
+mod journal;
 mod metrics;
+mod pool_type;
 mod revalidation;
 #[cfg(test)]
 mod tests;
@@ -34,15 +37,19 @@ mod tests;
 pub use crate::api::FullChainApi;
 use async_trait::async_trait;
 use enactment_state::{EnactmentAction, EnactmentState};
+pub use fork_aware_txpool::{ForkAwareTxPool, FullForkAwarePool};
 use futures::{
 	channel::oneshot,
 	future::{self, ready},
 	prelude::*,
 };
 pub use graph::{
-	base_pool::Limit as PoolLimit, ChainApi, Options, Pool, Transaction, ValidatedTransaction,
+	base_pool::{Limit as PoolLimit, SenderLimits as PoolSenderLimits},
+	ChainApi, Options, Pool, Transaction, ValidatedTransaction,
 };
+pub use journal::JournalOptions;
 use parking_lot::Mutex;
+pub use pool_type::{FullTransactionPoolImpl, TransactionPoolImpl, TransactionPoolType};
 use std::{
 	collections::{HashMap, HashSet},
 	pin::Pin,
@@ -93,6 +100,7 @@ where
 	ready_poll: Arc<Mutex<ReadyPoll<ReadyIteratorFor<PoolApi>, Block>>>,
 	metrics: PrometheusMetrics,
 	enactment_state: Arc<Mutex<EnactmentState<Block>>>,
+	journal: Option<Arc<journal::Journal<PoolApi>>>,
 }
 
 struct ReadyPoll<T, Block: BlockT> {
@@ -183,6 +191,7 @@ where
 					best_block_hash,
 					finalized_hash,
 				))),
+				journal: None,
 			},
 			background_task,
 		)
@@ -201,6 +210,8 @@ where
 		best_block_hash: Block::Hash,
 		finalized_hash: Block::Hash,
 	) -> Self {
+		let journal =
+			options.journal.clone().map(|options| Arc::new(journal::Journal::new(options)));
 		let pool = Arc::new(graph::Pool::new(options, is_validator, pool_api.clone()));
 		let (revalidation_queue, background_task) = match revalidation_type {
 			RevalidationType::Light =>
@@ -219,6 +230,14 @@ where
 			spawner.spawn_essential("txpool-background", Some("transaction-pool"), background_task);
 		}
 
+		if let Some(journal) = journal.clone() {
+			spawner.spawn_essential(
+				"txpool-journal",
+				Some("transaction-pool"),
+				journal::journal_task(journal, pool.clone(), best_block_hash).boxed(),
+			);
+		}
+
 		Self {
 			api: pool_api,
 			pool,
@@ -234,6 +253,7 @@ where
 				best_block_hash,
 				finalized_hash,
 			))),
+			journal,
 		}
 	}
 
@@ -248,6 +268,18 @@ where
 	}
 }
 
+impl<PoolApi, Block> Drop for BasicPool<PoolApi, Block>
+where
+	Block: BlockT,
+	PoolApi: graph::ChainApi<Block = Block>,
+{
+	fn drop(&mut self) {
+		if let Some(journal) = &self.journal {
+			journal.flush(&*self.pool);
+		}
+	}
+}
+
 impl<PoolApi, Block> TransactionPool for BasicPool<PoolApi, Block>
 where
 	Block: BlockT,
@@ -265,11 +297,19 @@ where
 		xts: Vec<TransactionFor<Self>>,
 	) -> PoolFuture<Vec<Result<TxHash<Self>, Self::Error>>, Self::Error> {
 		let pool = self.pool.clone();
+		let metrics = self.metrics.clone();
 
 		self.metrics
 			.report(|metrics| metrics.submitted_transactions.inc_by(xts.len() as u64));
 
-		async move { pool.submit_at(at, source, xts).await }.boxed()
+		async move {
+			let results = pool.submit_at(at, source, xts).await?;
+			Ok(results
+				.into_iter()
+				.map(|result| result.map_err(|e| metrics.report_rejection(e)))
+				.collect())
+		}
+		.boxed()
 	}
 
 	fn submit_one(
@@ -279,10 +319,12 @@ where
 		xt: TransactionFor<Self>,
 	) -> PoolFuture<TxHash<Self>, Self::Error> {
 		let pool = self.pool.clone();
+		let metrics = self.metrics.clone();
 
 		self.metrics.report(|metrics| metrics.submitted_transactions.inc());
 
-		async move { pool.submit_one(at, source, xt).await }.boxed()
+		async move { pool.submit_one(at, source, xt).await.map_err(|e| metrics.report_rejection(e)) }
+			.boxed()
 	}
 
 	fn submit_and_watch(
@@ -292,17 +334,42 @@ where
 		xt: TransactionFor<Self>,
 	) -> PoolFuture<Pin<Box<TransactionStatusStreamFor<Self>>>, Self::Error> {
 		let pool = self.pool.clone();
+		let metrics = self.metrics.clone();
 
 		self.metrics.report(|metrics| metrics.submitted_transactions.inc());
 
 		async move {
-			let watcher = pool.submit_and_watch(at, source, xt).await?;
+			let watcher = pool
+				.submit_and_watch(at, source, xt)
+				.await
+				.map_err(|e| metrics.report_rejection(e))?;
 
 			Ok(watcher.into_stream().boxed())
 		}
 		.boxed()
 	}
 
+	fn submit_from_network(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		xt: TransactionFor<Self>,
+	) -> PoolFuture<TxHash<Self>, Self::Error> {
+		let pool = self.pool.clone();
+		let metrics = self.metrics.clone();
+
+		self.metrics.report(|metrics| metrics.submitted_transactions.inc());
+
+		async move {
+			let result = pool
+				.submit_from_network(at, std::iter::once(xt))
+				.await?
+				.pop()
+				.expect("One extrinsic passed; one result returned; qed");
+			result.map_err(|e| metrics.report_rejection(e))
+		}
+		.boxed()
+	}
+
 	fn remove_invalid(&self, hashes: &[TxHash<Self>]) -> Vec<Arc<Self::InPoolTransaction>> {
 		let removed = self.pool.validated_pool().remove_invalid(hashes);
 		self.metrics
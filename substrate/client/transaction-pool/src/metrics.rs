@@ -21,6 +21,7 @@
 use std::sync::Arc;
 
 use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
+use sc_transaction_pool_api::error::{Error as PoolError, IntoPoolError};
 
 #[derive(Clone, Default)]
 pub struct MetricsLink(Arc<Option<Metrics>>);
@@ -41,6 +42,23 @@ impl MetricsLink {
 			do_this(metrics);
 		}
 	}
+
+	/// Reports transactions rejected by the replacement or per-sender rules.
+	///
+	/// Returns the (unchanged) error back to the caller.
+	pub fn report_rejection<E: IntoPoolError + From<PoolError>>(&self, error: E) -> E {
+		match error.into_pool_error() {
+			Ok(error) => {
+				self.report(|metrics| match error {
+					PoolError::TooLowPriority { .. } => metrics.replacements_rejected.inc(),
+					PoolError::SenderLimitReached => metrics.sender_limit_rejected.inc(),
+					_ => {},
+				});
+				error.into()
+			},
+			Err(error) => error,
+		}
+	}
 }
 
 /// Transaction pool Prometheus metrics.
@@ -49,6 +67,8 @@ pub struct Metrics {
 	pub validations_invalid: Counter<U64>,
 	pub block_transactions_pruned: Counter<U64>,
 	pub block_transactions_resubmitted: Counter<U64>,
+	pub replacements_rejected: Counter<U64>,
+	pub sender_limit_rejected: Counter<U64>,
 }
 
 impl Metrics {
@@ -82,6 +102,20 @@ impl Metrics {
 				)?,
 				registry,
 			)?,
+			replacements_rejected: register(
+				Counter::new(
+					"substrate_sub_txpool_replacements_rejected",
+					"Total number of transactions rejected for not bumping the priority of the replaced ones enough",
+				)?,
+				registry,
+			)?,
+			sender_limit_rejected: register(
+				Counter::new(
+					"substrate_sub_txpool_sender_limit_rejected",
+					"Total number of transactions rejected because their sender reached its limit",
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
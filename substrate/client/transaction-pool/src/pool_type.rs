@@ -0,0 +1,240 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Runtime selection of the transaction pool implementation.
+
+use std::{collections::HashMap, pin::Pin, sync::Arc};
+
+use async_trait::async_trait;
+use prometheus_endpoint::Registry as PrometheusRegistry;
+use sc_transaction_pool_api::{
+	ChainEvent, ImportNotificationStream, LocalTransactionFor, LocalTransactionPool,
+	MaintainedTransactionPool, PoolFuture, PoolStatus, TransactionFor, TransactionPool,
+	TransactionSource, TransactionStatusStreamFor, TxHash,
+};
+use sp_core::traits::SpawnEssentialNamed;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+use crate::{
+	graph::{self, IsValidator},
+	BasicPool, ForkAwareTxPool, FullChainApi, FullForkAwarePool, FullPool, PolledIterator,
+	ReadyIteratorFor,
+};
+
+/// Type of the transaction pool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TransactionPoolType {
+	/// A single validated state following the best block, see [`BasicPool`].
+	#[default]
+	SingleState,
+	/// A validated view for every fork, see [`ForkAwareTxPool`].
+	ForkAware,
+}
+
+/// A transaction pool of the type selected by [`Options::pool_type`](crate::Options::pool_type).
+pub enum TransactionPoolImpl<PoolApi, Block>
+where
+	Block: BlockT,
+	PoolApi: graph::ChainApi<Block = Block>,
+{
+	/// The single-state pool.
+	SingleState(Arc<BasicPool<PoolApi, Block>>),
+	/// The fork-aware pool.
+	ForkAware(Arc<ForkAwareTxPool<PoolApi, Block>>),
+}
+
+/// A transaction pool of the configured type for a full node.
+pub type FullTransactionPoolImpl<Block, Client> =
+	TransactionPoolImpl<FullChainApi<Client, Block>, Block>;
+
+/// Calls the same method on the selected pool.
+macro_rules! dispatch {
+	($self:ident, $pool:ident => $call:expr) => {
+		match $self {
+			Self::SingleState($pool) => $call,
+			Self::ForkAware($pool) => $call,
+		}
+	};
+}
+
+impl<PoolApi, Block> TransactionPool for TransactionPoolImpl<PoolApi, Block>
+where
+	Block: BlockT,
+	PoolApi: 'static + graph::ChainApi<Block = Block>,
+{
+	type Block = PoolApi::Block;
+	type Hash = graph::ExtrinsicHash<PoolApi>;
+	type InPoolTransaction = graph::base_pool::Transaction<TxHash<Self>, TransactionFor<Self>>;
+	type Error = PoolApi::Error;
+
+	fn submit_at(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		source: TransactionSource,
+		xts: Vec<TransactionFor<Self>>,
+	) -> PoolFuture<Vec<Result<TxHash<Self>, Self::Error>>, Self::Error> {
+		dispatch!(self, pool => pool.submit_at(at, source, xts))
+	}
+
+	fn submit_one(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		source: TransactionSource,
+		xt: TransactionFor<Self>,
+	) -> PoolFuture<TxHash<Self>, Self::Error> {
+		dispatch!(self, pool => pool.submit_one(at, source, xt))
+	}
+
+	fn submit_and_watch(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		source: TransactionSource,
+		xt: TransactionFor<Self>,
+	) -> PoolFuture<Pin<Box<TransactionStatusStreamFor<Self>>>, Self::Error> {
+		dispatch!(self, pool => pool.submit_and_watch(at, source, xt))
+	}
+
+	fn submit_from_network(
+		&self,
+		at: <Self::Block as BlockT>::Hash,
+		xt: TransactionFor<Self>,
+	) -> PoolFuture<TxHash<Self>, Self::Error> {
+		dispatch!(self, pool => pool.submit_from_network(at, xt))
+	}
+
+	fn ready_at(&self, at: NumberFor<Self::Block>) -> PolledIterator<PoolApi> {
+		dispatch!(self, pool => pool.ready_at(at))
+	}
+
+	fn ready_at_block(
+		&self,
+		hash: <Self::Block as BlockT>::Hash,
+		number: NumberFor<Self::Block>,
+	) -> PolledIterator<PoolApi> {
+		dispatch!(self, pool => pool.ready_at_block(hash, number))
+	}
+
+	fn ready(&self) -> ReadyIteratorFor<PoolApi> {
+		dispatch!(self, pool => pool.ready())
+	}
+
+	fn remove_invalid(&self, hashes: &[TxHash<Self>]) -> Vec<Arc<Self::InPoolTransaction>> {
+		dispatch!(self, pool => pool.remove_invalid(hashes))
+	}
+
+	fn futures(&self) -> Vec<Self::InPoolTransaction> {
+		dispatch!(self, pool => pool.futures())
+	}
+
+	fn status(&self) -> PoolStatus {
+		dispatch!(self, pool => pool.status())
+	}
+
+	fn import_notification_stream(&self) -> ImportNotificationStream<TxHash<Self>> {
+		dispatch!(self, pool => pool.import_notification_stream())
+	}
+
+	fn on_broadcasted(&self, propagations: HashMap<TxHash<Self>, Vec<String>>) {
+		dispatch!(self, pool => pool.on_broadcasted(propagations))
+	}
+
+	fn hash_of(&self, xt: &TransactionFor<Self>) -> TxHash<Self> {
+		dispatch!(self, pool => pool.hash_of(xt))
+	}
+
+	fn ready_transaction(&self, hash: &TxHash<Self>) -> Option<Arc<Self::InPoolTransaction>> {
+		dispatch!(self, pool => pool.ready_transaction(hash))
+	}
+}
+
+#[async_trait]
+impl<PoolApi, Block> MaintainedTransactionPool for TransactionPoolImpl<PoolApi, Block>
+where
+	Block: BlockT,
+	PoolApi: 'static + graph::ChainApi<Block = Block>,
+{
+	async fn maintain(&self, event: ChainEvent<Self::Block>) {
+		dispatch!(self, pool => pool.maintain(event).await)
+	}
+}
+
+impl<Block, Client> FullTransactionPoolImpl<Block, Client>
+where
+	Block: BlockT,
+	Client: sp_api::ProvideRuntimeApi<Block>
+		+ sc_client_api::BlockBackend<Block>
+		+ sc_client_api::blockchain::HeaderBackend<Block>
+		+ sp_runtime::traits::BlockIdTo<Block>
+		+ sc_client_api::ExecutorProvider<Block>
+		+ sc_client_api::UsageProvider<Block>
+		+ sp_blockchain::HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ Send
+		+ Sync
+		+ 'static,
+	Client::Api: sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block>,
+{
+	/// Create new transaction pool of the type selected in `options` for a full node.
+	pub fn new_full(
+		options: graph::Options,
+		is_validator: IsValidator,
+		prometheus: Option<&PrometheusRegistry>,
+		spawner: impl SpawnEssentialNamed,
+		client: Arc<Client>,
+	) -> Arc<Self> {
+		Arc::new(match options.pool_type {
+			TransactionPoolType::SingleState => Self::SingleState(FullPool::new_full(
+				options,
+				is_validator,
+				prometheus,
+				spawner,
+				client,
+			)),
+			TransactionPoolType::ForkAware => Self::ForkAware(FullForkAwarePool::new_full(
+				options,
+				is_validator,
+				prometheus,
+				spawner,
+				client,
+			)),
+		})
+	}
+}
+
+impl<Block, Client> LocalTransactionPool for FullTransactionPoolImpl<Block, Client>
+where
+	Block: BlockT,
+	Client: sp_api::ProvideRuntimeApi<Block>
+		+ sc_client_api::BlockBackend<Block>
+		+ sc_client_api::blockchain::HeaderBackend<Block>
+		+ sp_runtime::traits::BlockIdTo<Block>
+		+ sp_blockchain::HeaderMetadata<Block, Error = sp_blockchain::Error>,
+	Client: Send + Sync + 'static,
+	Client::Api: sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block>,
+{
+	type Block = Block;
+	type Hash = graph::ExtrinsicHash<FullChainApi<Client, Block>>;
+	type Error = <FullChainApi<Client, Block> as graph::ChainApi>::Error;
+
+	fn submit_local(
+		&self,
+		at: Block::Hash,
+		xt: LocalTransactionFor<Self>,
+	) -> Result<Self::Hash, Self::Error> {
+		dispatch!(self, pool => pool.submit_local(at, xt))
+	}
+}
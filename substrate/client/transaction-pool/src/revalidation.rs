@@ -117,16 +117,17 @@ async fn batch_revalidate<Api: ChainApi>(
 				);
 			},
 			Ok(Ok(validity)) => {
+				let validated = ValidatedTransaction::valid_at(
+					block_number.saturated_into::<u64>(),
+					ext_hash,
+					ext.source,
+					ext.data.clone(),
+					api.hash_and_length(&ext.data).1,
+					validity,
+				);
 				revalidated.insert(
 					ext_hash,
-					ValidatedTransaction::valid_at(
-						block_number.saturated_into::<u64>(),
-						ext_hash,
-						ext.source,
-						ext.data.clone(),
-						api.hash_and_length(&ext.data).1,
-						validity,
-					),
+					if ext.from_network { validated.received_from_network() } else { validated },
 				);
 			},
 			Err(validation_err) => {
@@ -0,0 +1,257 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tests for the fork-aware transaction pool.
+
+use codec::Encode;
+use futures::executor::{block_on, block_on_stream};
+use sc_transaction_pool::{ForkAwareTxPool, JournalOptions, Options};
+use sc_transaction_pool_api::{
+	ChainEvent, MaintainedTransactionPool, TransactionPool, TransactionStatus,
+};
+use sp_runtime::transaction_validity::TransactionSource;
+use std::{collections::BTreeSet, sync::Arc};
+use substrate_test_runtime_client::{
+	runtime::{Block, Extrinsic, Hash, Header},
+	AccountKeyring::*,
+};
+use substrate_test_runtime_transaction_pool::{uxt, TestApi};
+
+const SOURCE: TransactionSource = TransactionSource::External;
+
+fn pool() -> (ForkAwareTxPool<TestApi, Block>, Arc<TestApi>, Hash) {
+	let api = Arc::new(TestApi::with_alice_nonce(209));
+	let genesis = api.expect_hash_from_number(0);
+	(ForkAwareTxPool::new_test(api.clone(), genesis, genesis), api, genesis)
+}
+
+fn new_best_block_event(header: &Header) -> ChainEvent<Block> {
+	ChainEvent::NewBestBlock { hash: header.hash(), tree_route: None }
+}
+
+fn finalized_block_event(header: &Header, tree_route: Vec<Hash>) -> ChainEvent<Block> {
+	ChainEvent::Finalized { hash: header.hash(), tree_route: Arc::from(tree_route) }
+}
+
+fn ready_at_block(pool: &ForkAwareTxPool<TestApi, Block>, header: &Header) -> BTreeSet<Vec<u8>> {
+	block_on(pool.ready_at_block(header.hash(), header.number))
+		.map(|tx| tx.data.encode())
+		.collect()
+}
+
+fn encoded(xts: &[Extrinsic]) -> BTreeSet<Vec<u8>> {
+	xts.iter().map(Encode::encode).collect()
+}
+
+#[test]
+fn submission_should_work() {
+	let (pool, _, genesis) = pool();
+
+	block_on(pool.submit_one(genesis, SOURCE, uxt(Alice, 209))).unwrap();
+
+	assert_eq!(pool.status().ready, 1);
+	assert_eq!(pool.mempool_len(), 1);
+	assert_eq!(pool.views_count(), 1);
+}
+
+#[test]
+fn invalid_transaction_should_not_stay_in_mempool() {
+	let (pool, api, genesis) = pool();
+	let xt = uxt(Alice, 209);
+	api.add_invalid(&xt);
+
+	assert!(block_on(pool.submit_one(genesis, SOURCE, xt)).is_err());
+	assert_eq!(pool.mempool_len(), 0);
+}
+
+#[test]
+fn ready_at_block_should_return_ready_set_of_the_fork() {
+	let (pool, api, genesis) = pool();
+	let xt0 = uxt(Alice, 209);
+	let xt1 = uxt(Bob, 0);
+
+	block_on(pool.submit_at(genesis, SOURCE, vec![xt0.clone(), xt1.clone()])).unwrap();
+
+	let a1 = api.push_block_with_parent(genesis, vec![xt0.clone()], true);
+	block_on(pool.maintain(new_best_block_event(&a1)));
+	let b1 = api.push_block_with_parent(genesis, vec![], true);
+
+	assert_eq!(ready_at_block(&pool, &a1), encoded(&[xt1.clone()]));
+	assert_eq!(ready_at_block(&pool, &b1), encoded(&[xt0, xt1]));
+	assert_eq!(pool.views_count(), 2);
+}
+
+#[test]
+fn views_should_be_kept_per_leaf() {
+	let (pool, api, genesis) = pool();
+
+	block_on(pool.submit_one(genesis, SOURCE, uxt(Alice, 209))).unwrap();
+
+	let a1 = api.push_block_with_parent(genesis, vec![], true);
+	block_on(pool.maintain(new_best_block_event(&a1)));
+	let a2 = api.push_block_with_parent(a1.hash(), vec![], true);
+	block_on(pool.maintain(new_best_block_event(&a2)));
+
+	assert_eq!(pool.views_count(), 1);
+	assert!(pool.view_status(a1.hash()).is_none());
+	assert_eq!(pool.status().ready, 1);
+
+	let b1 = api.push_block_with_parent(genesis, vec![], true);
+	assert_eq!(ready_at_block(&pool, &b1), encoded(&[uxt(Alice, 209)]));
+	assert_eq!(pool.views_count(), 2);
+
+	// Building on an ancestor of a leaf does not keep an extra view.
+	assert_eq!(ready_at_block(&pool, &a1), encoded(&[uxt(Alice, 209)]));
+	assert_eq!(pool.views_count(), 2);
+}
+
+#[test]
+fn in_block_should_be_reported_on_non_best_forks() {
+	let (pool, api, genesis) = pool();
+	let xt = uxt(Alice, 209);
+
+	let watcher = block_on(pool.submit_and_watch(genesis, SOURCE, xt.clone())).unwrap();
+
+	let a1 = api.push_block_with_parent(genesis, vec![], true);
+	block_on(pool.maintain(new_best_block_event(&a1)));
+
+	// The fork including the transaction is not the best chain.
+	let b1 = api.push_block_with_parent(genesis, vec![xt.clone()], true);
+	assert!(ready_at_block(&pool, &b1).is_empty());
+
+	let mut stream = block_on_stream(watcher);
+	assert_eq!(stream.next(), Some(TransactionStatus::Ready));
+	assert_eq!(stream.next(), Some(TransactionStatus::InBlock((b1.hash(), 0))));
+	assert_eq!(pool.status().ready, 1);
+}
+
+#[test]
+fn finalization_should_drop_views_of_other_forks() {
+	let (pool, api, genesis) = pool();
+	let xt0 = uxt(Alice, 209);
+	let xt1 = uxt(Bob, 0);
+
+	block_on(pool.submit_at(genesis, SOURCE, vec![xt0.clone(), xt1.clone()])).unwrap();
+
+	let a1 = api.push_block_with_parent(genesis, vec![xt0.clone()], true);
+	block_on(pool.maintain(new_best_block_event(&a1)));
+	let b1 = api.push_block_with_parent(genesis, vec![], true);
+	assert_eq!(ready_at_block(&pool, &b1), encoded(&[xt0, xt1.clone()]));
+
+	block_on(pool.maintain(finalized_block_event(&a1, vec![])));
+
+	assert_eq!(pool.views_count(), 1);
+	assert!(pool.view_status(a1.hash()).is_some());
+	assert_eq!(pool.mempool_len(), 1);
+	assert_eq!(ready_at_block(&pool, &a1), encoded(&[xt1]));
+}
+
+#[test]
+fn fork_switch_should_report_in_block_and_retracted() {
+	let (pool, api, genesis) = pool();
+	let xt = uxt(Alice, 209);
+
+	let watcher = block_on(pool.submit_and_watch(genesis, SOURCE, xt.clone())).unwrap();
+
+	let a1 = api.push_block_with_parent(genesis, vec![xt.clone()], true);
+	block_on(pool.maintain(new_best_block_event(&a1)));
+
+	let b1 = api.push_block_with_parent(genesis, vec![], true);
+	let b2 = api.push_block_with_parent(b1.hash(), vec![xt.clone()], true);
+	block_on(pool.maintain(new_best_block_event(&b2)));
+	block_on(pool.maintain(finalized_block_event(&b2, vec![b1.hash()])));
+
+	assert_eq!(
+		block_on_stream(watcher).collect::<Vec<_>>(),
+		vec![
+			TransactionStatus::Ready,
+			TransactionStatus::InBlock((a1.hash(), 0)),
+			TransactionStatus::Retracted(a1.hash()),
+			TransactionStatus::InBlock((b2.hash(), 0)),
+			TransactionStatus::Finalized((b2.hash(), 0)),
+		]
+	);
+	assert_eq!(pool.mempool_len(), 0);
+}
+
+#[test]
+fn transaction_should_become_ready_again_after_retraction() {
+	let (pool, api, genesis) = pool();
+	let xt = uxt(Alice, 209);
+
+	let watcher = block_on(pool.submit_and_watch(genesis, SOURCE, xt.clone())).unwrap();
+
+	let a1 = api.push_block_with_parent(genesis, vec![xt.clone()], true);
+	block_on(pool.maintain(new_best_block_event(&a1)));
+
+	let b1 = api.push_block_with_parent(genesis, vec![], true);
+	let b2 = api.push_block_with_parent(b1.hash(), vec![], true);
+	block_on(pool.maintain(new_best_block_event(&b2)));
+
+	assert_eq!(pool.status().ready, 1);
+	assert_eq!(ready_at_block(&pool, &b2), encoded(&[xt.clone()]));
+
+	let mut stream = block_on_stream(watcher);
+	assert_eq!(stream.next(), Some(TransactionStatus::Ready));
+	assert_eq!(stream.next(), Some(TransactionStatus::InBlock((a1.hash(), 0))));
+	assert_eq!(stream.next(), Some(TransactionStatus::Retracted(a1.hash())));
+	assert_eq!(stream.next(), Some(TransactionStatus::Ready));
+}
+
+#[test]
+fn transactions_of_unknown_retracted_blocks_should_be_resubmitted() {
+	let (pool, api, genesis) = pool();
+	let xt = uxt(Alice, 209);
+
+	block_on(pool.submit_one(genesis, SOURCE, uxt(Bob, 0))).unwrap();
+
+	let a1 = api.push_block_with_parent(genesis, vec![xt.clone()], true);
+	block_on(pool.maintain(new_best_block_event(&a1)));
+	assert_eq!(pool.mempool_len(), 1);
+
+	let b1 = api.push_block_with_parent(genesis, vec![], true);
+	let b2 = api.push_block_with_parent(b1.hash(), vec![], true);
+	block_on(pool.maintain(new_best_block_event(&b2)));
+
+	assert_eq!(pool.mempool_len(), 2);
+	assert_eq!(ready_at_block(&pool, &b2), encoded(&[xt, uxt(Bob, 0)]));
+}
+
+#[test]
+fn journaled_transactions_should_be_restored() {
+	let dir = tempfile::tempdir().unwrap();
+	let options = Options {
+		journal: Some(JournalOptions { path: dir.path().join("journal"), ..Default::default() }),
+		..Default::default()
+	};
+	let api = Arc::new(TestApi::with_alice_nonce(209));
+	let genesis = api.expect_hash_from_number(0);
+
+	let pool =
+		ForkAwareTxPool::new(options.clone(), true.into(), api.clone(), None, genesis, genesis);
+	block_on(pool.submit_at(genesis, SOURCE, vec![uxt(Alice, 209), uxt(Bob, 0)])).unwrap();
+	// The journal is written when the pool is dropped.
+	drop(pool);
+
+	let pool = ForkAwareTxPool::new(options, true.into(), api.clone(), None, genesis, genesis);
+	assert_eq!(pool.mempool_len(), 2);
+
+	let a1 = api.push_block_with_parent(genesis, vec![], true);
+	block_on(pool.maintain(new_best_block_event(&a1)));
+	assert_eq!(ready_at_block(&pool, &a1), encoded(&[uxt(Alice, 209), uxt(Bob, 0)]));
+}
@@ -53,4 +53,28 @@ impl crate::WeightInfo for () {
 	fn set_new_genesis() -> Weight {
 		DbWeight::get().writes(1)
 	}
+
+	fn report_fork_voting(validator_count: u32, max_nominators_per_validator: u32) -> Weight {
+		// same as an equivocation report, but we check a single vote against the
+		// canonical header instead of two votes against each other.
+		<Self as crate::WeightInfo>::report_equivocation(
+			validator_count,
+			max_nominators_per_validator,
+		)
+		// fetching the current block number and the canonical block hash
+		.saturating_add(DbWeight::get().reads(2))
+	}
+
+	fn report_future_block_voting(
+		validator_count: u32,
+		max_nominators_per_validator: u32,
+	) -> Weight {
+		// same as an equivocation report, but we check a single vote.
+		<Self as crate::WeightInfo>::report_equivocation(
+			validator_count,
+			max_nominators_per_validator,
+		)
+		// fetching the current block number
+		.saturating_add(DbWeight::get().reads(1))
+	}
 }
@@ -36,10 +36,14 @@
 
 use codec::{self as codec, Decode, Encode};
 use frame_support::traits::{Get, KeyOwnerProofSystem};
-use frame_system::pallet_prelude::BlockNumberFor;
+use frame_system::pallet_prelude::{BlockNumberFor, HeaderFor};
 use log::{error, info};
-use sp_consensus_beefy::{EquivocationProof, ValidatorSetId, KEY_TYPE as BEEFY_KEY_TYPE};
+use sp_consensus_beefy::{
+	EquivocationProof, ForkVotingProof, FutureBlockVotingProof, ValidatorSetId,
+	KEY_TYPE as BEEFY_KEY_TYPE,
+};
 use sp_runtime::{
+	traits::Header as _,
 	transaction_validity::{
 		InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
 		TransactionValidityError, ValidTransaction,
@@ -122,14 +126,152 @@ where
 pub struct EquivocationReportSystem<T, R, P, L>(sp_std::marker::PhantomData<(T, R, P, L)>);
 
 /// Equivocation evidence convenience alias.
-pub type EquivocationEvidenceFor<T> = (
-	EquivocationProof<
-		BlockNumberFor<T>,
-		<T as Config>::BeefyId,
-		<<T as Config>::BeefyId as RuntimeAppPublic>::Signature,
-	>,
-	<T as Config>::KeyOwnerProof,
-);
+///
+/// Besides double voting, voting on a block that isn't part of the canonical chain or that
+/// hasn't been produced yet is also considered an equivocation and reported as such.
+pub enum EquivocationEvidenceFor<T: Config> {
+	/// An authority voted twice for different payloads in the same round.
+	Equivocation(
+		EquivocationProof<
+			BlockNumberFor<T>,
+			<T as Config>::BeefyId,
+			<<T as Config>::BeefyId as RuntimeAppPublic>::Signature,
+		>,
+		<T as Config>::KeyOwnerProof,
+	),
+	/// An authority voted for a payload that doesn't match the canonical chain.
+	ForkVoting(
+		ForkVotingProof<
+			HeaderFor<T>,
+			<T as Config>::BeefyId,
+			<<T as Config>::BeefyId as RuntimeAppPublic>::Signature,
+		>,
+		<T as Config>::KeyOwnerProof,
+	),
+	/// An authority voted for a block that hasn't been produced yet.
+	FutureBlockVoting(
+		FutureBlockVotingProof<
+			BlockNumberFor<T>,
+			<T as Config>::BeefyId,
+			<<T as Config>::BeefyId as RuntimeAppPublic>::Signature,
+		>,
+		<T as Config>::KeyOwnerProof,
+	),
+}
+
+impl<T: Config> Clone for EquivocationEvidenceFor<T> {
+	fn clone(&self) -> Self {
+		match self {
+			Self::Equivocation(proof, key_owner_proof) =>
+				Self::Equivocation(proof.clone(), key_owner_proof.clone()),
+			Self::ForkVoting(proof, key_owner_proof) =>
+				Self::ForkVoting(proof.clone(), key_owner_proof.clone()),
+			Self::FutureBlockVoting(proof, key_owner_proof) =>
+				Self::FutureBlockVoting(proof.clone(), key_owner_proof.clone()),
+		}
+	}
+}
+
+impl<T: Config> EquivocationEvidenceFor<T> {
+	/// Returns the authority id of the equivocator.
+	pub fn offender_id(&self) -> &T::BeefyId {
+		match self {
+			Self::Equivocation(proof, _) => proof.offender_id(),
+			Self::ForkVoting(proof, _) => proof.offender_id(),
+			Self::FutureBlockVoting(proof, _) => proof.offender_id(),
+		}
+	}
+
+	/// Returns the round number at which the equivocation occurred.
+	pub fn round_number(&self) -> &BlockNumberFor<T> {
+		match self {
+			Self::Equivocation(proof, _) => proof.round_number(),
+			Self::ForkVoting(proof, _) => proof.round_number(),
+			Self::FutureBlockVoting(proof, _) => proof.round_number(),
+		}
+	}
+
+	/// Returns the set id at which the equivocation occurred.
+	pub fn set_id(&self) -> ValidatorSetId {
+		match self {
+			Self::Equivocation(proof, _) => proof.set_id(),
+			Self::ForkVoting(proof, _) => proof.set_id(),
+			Self::FutureBlockVoting(proof, _) => proof.set_id(),
+		}
+	}
+
+	/// Returns the key ownership proof of the equivocator.
+	pub fn key_owner_proof(&self) -> &T::KeyOwnerProof {
+		match self {
+			Self::Equivocation(_, key_owner_proof) |
+			Self::ForkVoting(_, key_owner_proof) |
+			Self::FutureBlockVoting(_, key_owner_proof) => key_owner_proof,
+		}
+	}
+
+	/// Validates the equivocation proof, returning the error to be emitted if it is invalid.
+	fn check_proof(&self) -> Result<(), Error<T>> {
+		match self {
+			// Check votes are different and signatures are valid.
+			Self::Equivocation(proof, _) =>
+				if !sp_consensus_beefy::check_equivocation_proof(proof) {
+					return Err(Error::<T>::InvalidEquivocationProof)
+				},
+			// Check the header is canonical and the vote payload doesn't match its MMR root.
+			Self::ForkVoting(proof, _) => {
+				let number = *proof.header.number();
+				let is_canonical = number < frame_system::Pallet::<T>::block_number() &&
+					frame_system::Pallet::<T>::block_hash(number) == proof.header.hash();
+				if !is_canonical || !sp_consensus_beefy::check_fork_voting_proof(proof) {
+					return Err(Error::<T>::InvalidForkVotingProof)
+				}
+			},
+			// Check the voted block hasn't been produced yet.
+			Self::FutureBlockVoting(proof, _) => {
+				let is_future = *proof.round_number() >= frame_system::Pallet::<T>::block_number();
+				if !is_future || !sp_consensus_beefy::check_future_block_voting_proof(proof) {
+					return Err(Error::<T>::InvalidFutureBlockVotingProof)
+				}
+			},
+		}
+		Ok(())
+	}
+
+	fn into_call(self) -> Call<T> {
+		match self {
+			Self::Equivocation(proof, key_owner_proof) => Call::report_equivocation_unsigned {
+				equivocation_proof: Box::new(proof),
+				key_owner_proof,
+			},
+			Self::ForkVoting(proof, key_owner_proof) => Call::report_fork_voting_unsigned {
+				fork_voting_proof: Box::new(proof),
+				key_owner_proof,
+			},
+			Self::FutureBlockVoting(proof, key_owner_proof) =>
+				Call::report_future_block_voting_unsigned {
+					future_block_voting_proof: Box::new(proof),
+					key_owner_proof,
+				},
+		}
+	}
+
+	fn from_call(call: &Call<T>) -> Option<Self> {
+		match call {
+			Call::report_equivocation_unsigned { equivocation_proof, key_owner_proof } =>
+				Some(Self::Equivocation(*equivocation_proof.clone(), key_owner_proof.clone())),
+			Call::report_fork_voting_unsigned { fork_voting_proof, key_owner_proof } =>
+				Some(Self::ForkVoting(*fork_voting_proof.clone(), key_owner_proof.clone())),
+			Call::report_future_block_voting_unsigned {
+				future_block_voting_proof,
+				key_owner_proof,
+			} => Some(Self::FutureBlockVoting(
+				*future_block_voting_proof.clone(),
+				key_owner_proof.clone(),
+			)),
+			_ => None,
+		}
+	}
+}
 
 impl<T, R, P, L> OffenceReportSystem<Option<T::AccountId>, EquivocationEvidenceFor<T>>
 	for EquivocationReportSystem<T, R, P, L>
@@ -148,13 +290,8 @@ where
 
 	fn publish_evidence(evidence: EquivocationEvidenceFor<T>) -> Result<(), ()> {
 		use frame_system::offchain::SubmitTransaction;
-		let (equivocation_proof, key_owner_proof) = evidence;
-
-		let call = Call::report_equivocation_unsigned {
-			equivocation_proof: Box::new(equivocation_proof),
-			key_owner_proof,
-		};
 
+		let call = evidence.into_call();
 		let res = SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into());
 		match res {
 			Ok(_) => info!(target: LOG_TARGET, "Submitted equivocation report."),
@@ -166,17 +303,13 @@ where
 	fn check_evidence(
 		evidence: EquivocationEvidenceFor<T>,
 	) -> Result<(), TransactionValidityError> {
-		let (equivocation_proof, key_owner_proof) = evidence;
-
 		// Check the membership proof to extract the offender's id
-		let key = (BEEFY_KEY_TYPE, equivocation_proof.offender_id().clone());
-		let offender = P::check_proof(key, key_owner_proof).ok_or(InvalidTransaction::BadProof)?;
+		let key = (BEEFY_KEY_TYPE, evidence.offender_id().clone());
+		let offender = P::check_proof(key, evidence.key_owner_proof().clone())
+			.ok_or(InvalidTransaction::BadProof)?;
 
 		// Check if the offence has already been reported, and if so then we can discard the report.
-		let time_slot = TimeSlot {
-			set_id: equivocation_proof.set_id(),
-			round: *equivocation_proof.round_number(),
-		};
+		let time_slot = TimeSlot { set_id: evidence.set_id(), round: *evidence.round_number() };
 
 		if R::is_known_offence(&[offender], &time_slot) {
 			Err(InvalidTransaction::Stale.into())
@@ -189,27 +322,25 @@ where
 		reporter: Option<T::AccountId>,
 		evidence: EquivocationEvidenceFor<T>,
 	) -> Result<(), DispatchError> {
-		let (equivocation_proof, key_owner_proof) = evidence;
 		let reporter = reporter.or_else(|| <pallet_authorship::Pallet<T>>::author());
-		let offender = equivocation_proof.offender_id().clone();
+		let offender = evidence.offender_id().clone();
 
 		// We check the equivocation within the context of its set id (and
 		// associated session) and round. We also need to know the validator
 		// set count at the time of the offence since it is required to calculate
 		// the slash amount.
-		let set_id = equivocation_proof.set_id();
-		let round = *equivocation_proof.round_number();
-		let session_index = key_owner_proof.session();
-		let validator_set_count = key_owner_proof.validator_count();
+		let set_id = evidence.set_id();
+		let round = *evidence.round_number();
+		let session_index = evidence.key_owner_proof().session();
+		let validator_set_count = evidence.key_owner_proof().validator_count();
 
 		// Validate the key ownership proof extracting the id of the offender.
-		let offender = P::check_proof((BEEFY_KEY_TYPE, offender), key_owner_proof)
-			.ok_or(Error::<T>::InvalidKeyOwnershipProof)?;
+		let offender =
+			P::check_proof((BEEFY_KEY_TYPE, offender), evidence.key_owner_proof().clone())
+				.ok_or(Error::<T>::InvalidKeyOwnershipProof)?;
 
-		// Validate equivocation proof (check votes are different and signatures are valid).
-		if !sp_consensus_beefy::check_equivocation_proof(&equivocation_proof) {
-			return Err(Error::<T>::InvalidEquivocationProof.into())
-		}
+		// Validate the equivocation proof itself.
+		evidence.check_proof()?;
 
 		// Check that the session id for the membership proof is within the
 		// bounds of the set id reported in the equivocation.
@@ -234,12 +365,12 @@ where
 }
 
 /// Methods for the `ValidateUnsigned` implementation:
-/// It restricts calls to `report_equivocation_unsigned` to local calls (i.e. extrinsics generated
-/// on this node) or that already in a block. This guarantees that only block authors can include
-/// unsigned equivocation reports.
+/// It restricts calls to the unsigned reporting extrinsics to local calls (i.e. extrinsics
+/// generated on this node) or that already in a block. This guarantees that only block authors
+/// can include unsigned equivocation reports.
 impl<T: Config> Pallet<T> {
 	pub fn validate_unsigned(source: TransactionSource, call: &Call<T>) -> TransactionValidity {
-		if let Call::report_equivocation_unsigned { equivocation_proof, key_owner_proof } = call {
+		if let Some(evidence) = EquivocationEvidenceFor::<T>::from_call(call) {
 			// discard equivocation report not coming from the local node
 			match source {
 				TransactionSource::Local | TransactionSource::InBlock => { /* allowed */ },
@@ -252,7 +383,8 @@ impl<T: Config> Pallet<T> {
 				},
 			}
 
-			let evidence = (*equivocation_proof.clone(), key_owner_proof.clone());
+			let provides =
+				(evidence.offender_id().clone(), evidence.set_id(), *evidence.round_number());
 			T::EquivocationReportSystem::check_evidence(evidence)?;
 
 			let longevity =
@@ -262,11 +394,7 @@ impl<T: Config> Pallet<T> {
 				// We assign the maximum priority for any equivocation report.
 				.priority(TransactionPriority::MAX)
 				// Only one equivocation report for the same offender at the same slot.
-				.and_provides((
-					equivocation_proof.offender_id().clone(),
-					equivocation_proof.set_id(),
-					*equivocation_proof.round_number(),
-				))
+				.and_provides(provides)
 				.longevity(longevity)
 				// We don't propagate this. This can never be included on a remote node.
 				.propagate(false)
@@ -277,8 +405,7 @@ impl<T: Config> Pallet<T> {
 	}
 
 	pub fn pre_dispatch(call: &Call<T>) -> Result<(), TransactionValidityError> {
-		if let Call::report_equivocation_unsigned { equivocation_proof, key_owner_proof } = call {
-			let evidence = (*equivocation_proof.clone(), key_owner_proof.clone());
+		if let Some(evidence) = EquivocationEvidenceFor::<T>::from_call(call) {
 			T::EquivocationReportSystem::check_evidence(evidence)
 		} else {
 			Err(InvalidTransaction::Call.into())
@@ -19,17 +19,19 @@ use std::vec;
 
 use codec::Encode;
 use sp_consensus_beefy::{
-	check_equivocation_proof, generate_equivocation_proof, known_payloads::MMR_ROOT_ID,
-	Keyring as BeefyKeyring, Payload, ValidatorSet, KEY_TYPE as BEEFY_KEY_TYPE,
+	check_equivocation_proof, generate_equivocation_proof, generate_fork_voting_proof,
+	generate_future_block_voting_proof, known_payloads::MMR_ROOT_ID, Keyring as BeefyKeyring,
+	MmrRootHash, Payload, ValidatorSet, KEY_TYPE as BEEFY_KEY_TYPE,
 };
 
-use sp_runtime::DigestItem;
+use sp_runtime::{traits::Header as _, DigestItem};
 
 use frame_support::{
 	assert_err, assert_ok,
 	dispatch::{GetDispatchInfo, Pays},
 	traits::{Currency, KeyOwnerProofSystem, OnInitialize},
 };
+use frame_system::pallet_prelude::HeaderFor;
 
 use crate::{mock::*, Call, Config, Error, Weight, WeightInfo};
 
@@ -42,6 +44,18 @@ pub fn beefy_log(log: ConsensusLog<BeefyId>) -> DigestItem {
 	DigestItem::Consensus(BEEFY_ENGINE_ID, log.encode())
 }
 
+fn mmr_root_payload(root: MmrRootHash) -> Payload {
+	Payload::from_single_entry(MMR_ROOT_ID, root.encode())
+}
+
+/// Finalize the current block with an MMR root digest and initialize the next one.
+fn finalize_block_with_mmr_root(root: MmrRootHash) -> HeaderFor<Test> {
+	System::deposit_log(beefy_log(ConsensusLog::MmrRoot(root)));
+	let header = System::finalize();
+	System::initialize(&(header.number() + 1), &header.hash(), &Default::default());
+	header
+}
+
 #[test]
 fn genesis_session_initializes_authorities() {
 	let authorities = mock_authorities(vec![1, 2, 3, 4]);
@@ -792,6 +806,275 @@ fn valid_equivocation_reports_dont_pay_fees() {
 	})
 }
 
+#[test]
+fn report_fork_voting_works() {
+	let authorities = test_authorities();
+
+	new_test_ext_raw_authorities(authorities).execute_with(|| {
+		start_era(1);
+
+		let validator_set = Beefy::validator_set().unwrap();
+		let authorities = validator_set.validators();
+		let set_id = validator_set.id();
+		let validators = Session::validators();
+
+		let equivocation_authority_index = 1;
+		let equivocation_key = &authorities[equivocation_authority_index];
+		let equivocation_keyring = BeefyKeyring::from_public(equivocation_key).unwrap();
+
+		// the canonical block commits to an MMR root, the vote to a different one
+		let header = finalize_block_with_mmr_root(MmrRootHash::repeat_byte(1));
+		let fork_voting_proof = generate_fork_voting_proof(
+			(mmr_root_payload(MmrRootHash::repeat_byte(2)), set_id, &equivocation_keyring),
+			header,
+		);
+
+		let key_owner_proof = Historical::prove((BEEFY_KEY_TYPE, &equivocation_key)).unwrap();
+
+		// report the fork voting and the tx should be dispatched successfully
+		assert_ok!(Beefy::report_fork_voting_unsigned(
+			RuntimeOrigin::none(),
+			Box::new(fork_voting_proof),
+			key_owner_proof,
+		));
+
+		start_era(2);
+
+		// check that the balance of the offender is slashed 100%
+		let equivocation_validator_id = validators[equivocation_authority_index];
+		assert_eq!(Balances::total_balance(&equivocation_validator_id), 10_000_000 - 10_000);
+		assert_eq!(Staking::slashable_balance_of(&equivocation_validator_id), 0);
+
+		// check that the balances of all other validators are left intact.
+		for validator in validators.iter().filter(|v| **v != equivocation_validator_id) {
+			assert_eq!(Balances::total_balance(validator), 10_000_000);
+			assert_eq!(Staking::slashable_balance_of(validator), 10_000);
+		}
+	});
+}
+
+#[test]
+fn report_fork_voting_invalid_proof() {
+	let authorities = test_authorities();
+
+	new_test_ext_raw_authorities(authorities).execute_with(|| {
+		start_era(1);
+
+		let validator_set = Beefy::validator_set().unwrap();
+		let authorities = validator_set.validators();
+		let set_id = validator_set.id();
+
+		let equivocation_key = &authorities[0];
+		let equivocation_keyring = BeefyKeyring::from_public(equivocation_key).unwrap();
+		let key_owner_proof = Historical::prove((BEEFY_KEY_TYPE, &equivocation_key)).unwrap();
+
+		let canonical_root = MmrRootHash::repeat_byte(1);
+		let header = finalize_block_with_mmr_root(canonical_root);
+
+		let assert_invalid_fork_voting_proof = |fork_voting_proof| {
+			assert_err!(
+				Beefy::report_fork_voting_unsigned(
+					RuntimeOrigin::none(),
+					Box::new(fork_voting_proof),
+					key_owner_proof.clone(),
+				),
+				Error::<Test>::InvalidForkVotingProof,
+			);
+		};
+
+		// the vote matches the canonical payload, there is no misbehavior.
+		assert_invalid_fork_voting_proof(generate_fork_voting_proof(
+			(mmr_root_payload(canonical_root), set_id, &equivocation_keyring),
+			header.clone(),
+		));
+
+		// the header is not part of the canonical chain.
+		let mut non_canonical_header = header.clone();
+		non_canonical_header.digest.logs.clear();
+		non_canonical_header
+			.digest
+			.push(beefy_log(ConsensusLog::MmrRoot(MmrRootHash::repeat_byte(2))));
+		assert_invalid_fork_voting_proof(generate_fork_voting_proof(
+			(mmr_root_payload(MmrRootHash::repeat_byte(2)), set_id, &equivocation_keyring),
+			non_canonical_header,
+		));
+
+		// the vote is signed by a different key.
+		let mut fork_voting_proof = generate_fork_voting_proof(
+			(mmr_root_payload(MmrRootHash::repeat_byte(2)), set_id, &equivocation_keyring),
+			header,
+		);
+		fork_voting_proof.vote.signature = BeefyKeyring::Charlie.sign(&[0u8; 32]);
+		assert_invalid_fork_voting_proof(fork_voting_proof);
+	});
+}
+
+#[test]
+fn report_future_block_voting_works() {
+	let authorities = test_authorities();
+
+	new_test_ext_raw_authorities(authorities).execute_with(|| {
+		start_era(1);
+
+		let block_num = System::block_number();
+		let validator_set = Beefy::validator_set().unwrap();
+		let authorities = validator_set.validators();
+		let set_id = validator_set.id();
+		let validators = Session::validators();
+
+		let equivocation_authority_index = 0;
+		let equivocation_key = &authorities[equivocation_authority_index];
+		let equivocation_keyring = BeefyKeyring::from_public(equivocation_key).unwrap();
+
+		let payload = Payload::from_single_entry(MMR_ROOT_ID, vec![42]);
+		let future_block_voting_proof = generate_future_block_voting_proof((
+			block_num + 100,
+			payload,
+			set_id,
+			&equivocation_keyring,
+		));
+
+		let key_owner_proof = Historical::prove((BEEFY_KEY_TYPE, &equivocation_key)).unwrap();
+
+		// report the future block voting and the tx should be dispatched successfully
+		assert_ok!(Beefy::report_future_block_voting_unsigned(
+			RuntimeOrigin::none(),
+			Box::new(future_block_voting_proof),
+			key_owner_proof,
+		));
+
+		start_era(2);
+
+		// check that the balance of the offender is slashed 100%
+		let equivocation_validator_id = validators[equivocation_authority_index];
+		assert_eq!(Balances::total_balance(&equivocation_validator_id), 10_000_000 - 10_000);
+		assert_eq!(Staking::slashable_balance_of(&equivocation_validator_id), 0);
+
+		// check that the balances of all other validators are left intact.
+		for validator in validators.iter().filter(|v| **v != equivocation_validator_id) {
+			assert_eq!(Balances::total_balance(validator), 10_000_000);
+			assert_eq!(Staking::slashable_balance_of(validator), 10_000);
+		}
+	});
+}
+
+#[test]
+fn report_future_block_voting_invalid_proof() {
+	let authorities = test_authorities();
+
+	new_test_ext_raw_authorities(authorities).execute_with(|| {
+		start_era(1);
+
+		let block_num = System::block_number();
+		let validator_set = Beefy::validator_set().unwrap();
+		let authorities = validator_set.validators();
+		let set_id = validator_set.id();
+
+		let equivocation_key = &authorities[0];
+		let equivocation_keyring = BeefyKeyring::from_public(equivocation_key).unwrap();
+		let key_owner_proof = Historical::prove((BEEFY_KEY_TYPE, &equivocation_key)).unwrap();
+
+		let assert_invalid_future_block_voting_proof = |future_block_voting_proof| {
+			assert_err!(
+				Beefy::report_future_block_voting_unsigned(
+					RuntimeOrigin::none(),
+					Box::new(future_block_voting_proof),
+					key_owner_proof.clone(),
+				),
+				Error::<Test>::InvalidFutureBlockVotingProof,
+			);
+		};
+
+		let payload = Payload::from_single_entry(MMR_ROOT_ID, vec![42]);
+
+		// the voted block has already been produced.
+		assert_invalid_future_block_voting_proof(generate_future_block_voting_proof((
+			block_num - 1,
+			payload.clone(),
+			set_id,
+			&equivocation_keyring,
+		)));
+
+		// the vote is signed by a different key.
+		let mut future_block_voting_proof = generate_future_block_voting_proof((
+			block_num + 100,
+			payload,
+			set_id,
+			&equivocation_keyring,
+		));
+		future_block_voting_proof.vote.signature = BeefyKeyring::Charlie.sign(&[0u8; 32]);
+		assert_invalid_future_block_voting_proof(future_block_voting_proof);
+	});
+}
+
+#[test]
+fn report_future_block_voting_validate_unsigned_prevents_duplicates() {
+	use sp_runtime::transaction_validity::{InvalidTransaction, TransactionSource};
+
+	let authorities = test_authorities();
+
+	new_test_ext_raw_authorities(authorities).execute_with(|| {
+		start_era(1);
+
+		let block_num = System::block_number();
+		let validator_set = Beefy::validator_set().unwrap();
+		let authorities = validator_set.validators();
+		let set_id = validator_set.id();
+
+		let equivocation_key = &authorities[0];
+		let equivocation_keyring = BeefyKeyring::from_public(equivocation_key).unwrap();
+
+		let payload = Payload::from_single_entry(MMR_ROOT_ID, vec![42]);
+		let future_block_voting_proof = generate_future_block_voting_proof((
+			block_num + 100,
+			payload,
+			set_id,
+			&equivocation_keyring,
+		));
+		let key_owner_proof = Historical::prove((BEEFY_KEY_TYPE, &equivocation_key)).unwrap();
+
+		let call = Call::report_future_block_voting_unsigned {
+			future_block_voting_proof: Box::new(future_block_voting_proof.clone()),
+			key_owner_proof: key_owner_proof.clone(),
+		};
+
+		// only local/inblock reports are allowed
+		assert_eq!(
+			<Beefy as sp_runtime::traits::ValidateUnsigned>::validate_unsigned(
+				TransactionSource::External,
+				&call,
+			),
+			InvalidTransaction::Call.into(),
+		);
+		assert_ok!(<Beefy as sp_runtime::traits::ValidateUnsigned>::validate_unsigned(
+			TransactionSource::Local,
+			&call,
+		));
+		assert_ok!(<Beefy as sp_runtime::traits::ValidateUnsigned>::pre_dispatch(&call));
+
+		// we submit the report
+		Beefy::report_future_block_voting_unsigned(
+			RuntimeOrigin::none(),
+			Box::new(future_block_voting_proof),
+			key_owner_proof,
+		)
+		.unwrap();
+
+		// the report should now be considered stale and the transaction is invalid
+		assert_err!(
+			<Beefy as sp_runtime::traits::ValidateUnsigned>::validate_unsigned(
+				TransactionSource::Local,
+				&call,
+			),
+			InvalidTransaction::Stale,
+		);
+		assert_err!(
+			<Beefy as sp_runtime::traits::ValidateUnsigned>::pre_dispatch(&call),
+			InvalidTransaction::Stale,
+		);
+	});
+}
+
 #[test]
 fn set_new_genesis_works() {
 	let authorities = test_authorities();
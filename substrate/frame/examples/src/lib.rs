@@ -40,6 +40,12 @@
 //! - [`pallet_example_split`]: A simple example of a FRAME pallet demonstrating the ability to
 //!   split sections across multiple files.
 //!
+//! - [`pallet_example_tasks`]: This pallet demonstrates the use of `#[pallet::tasks]` to declare
+//!   permissionless work that can be executed by anyone once its condition holds.
+//!
+//! - [`pallet_example_view_functions`]: This pallet demonstrates the use of
+//!   `#[pallet::view_functions]` to declare read-only queries exposed through the metadata.
+//!
 //! - [`pallet_example_frame_crate`]: Example pallet showcasing how one can be
 //! built using only the `frame` umbrella crate.
 //!
@@ -0,0 +1,104 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Tasks Example Pallet
+//!
+//! **This pallet serves as an example and is not meant to be used in production.**
+//!
+//! A FRAME pallet demonstrating `#[pallet::tasks]`. Numbers are registered with
+//! [`Call::add_number`] and later folded into [`Total`] by the `add_number_into_total` task.
+//! The task can be executed by anyone through `frame_system::Call::do_task`, and the off-chain
+//! worker of this pallet submits every valid task as an unsigned transaction.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// Re-export pallet items so that they can be accessed from the crate namespace.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+const LOG_TARGET: &str = "runtime::example-tasks";
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::{offchain::SendTransactionTypes, pallet_prelude::*};
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config:
+		frame_system::Config + SendTransactionTypes<frame_system::Call<Self>>
+	{
+	}
+
+	/// Numbers waiting to be added into [`Total`], keyed by an arbitrary index.
+	#[pallet::storage]
+	pub type Numbers<T: Config> = StorageMap<_, Twox64Concat, u32, u32, OptionQuery>;
+
+	/// Sum of all the numbers processed so far.
+	#[pallet::storage]
+	pub type Total<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// There is no number registered at the given index.
+		NotFound,
+		/// A number is already registered at the given index.
+		AlreadyExists,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Register `number` at `index`, to be added into [`Total`] by a task.
+		#[pallet::call_index(0)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn add_number(origin: OriginFor<T>, index: u32, number: u32) -> DispatchResult {
+			ensure_signed(origin)?;
+			ensure!(!Numbers::<T>::contains_key(index), Error::<T>::AlreadyExists);
+			Numbers::<T>::insert(index, number);
+			Ok(())
+		}
+	}
+
+	#[pallet::tasks]
+	impl<T: Config> Pallet<T> {
+		/// Add the number registered at `index` into [`Total`] and remove it.
+		#[pallet::task_index(0)]
+		#[pallet::task_condition(|index: &u32| Numbers::<T>::contains_key(index))]
+		#[pallet::task_list(Numbers::<T>::iter_keys())]
+		#[pallet::task_weight(T::DbWeight::get().reads_writes(2, 2))]
+		pub fn add_number_into_total(index: u32) -> DispatchResult {
+			let number = Numbers::<T>::take(index).ok_or(Error::<T>::NotFound)?;
+			Total::<T>::mutate(|total| *total = total.saturating_add(number.into()));
+			Ok(())
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn offchain_worker(_block_number: BlockNumberFor<T>) {
+			let submitted = frame_system::Pallet::<T>::submit_valid_tasks();
+			log::debug!(target: LOG_TARGET, "Submitted {} task(s)", submitted);
+		}
+	}
+}
@@ -0,0 +1,115 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for `pallet-example-tasks`.
+
+use crate::{mock::*, Numbers, Total};
+use codec::Decode;
+use frame_support::{assert_noop, assert_ok, traits::Task};
+use sp_core::offchain::{testing, OffchainWorkerExt, TransactionPoolExt};
+use sp_runtime::traits::Dispatchable;
+
+#[test]
+fn task_enumerate_works() {
+	new_test_ext().execute_with(|| {
+		Numbers::<Runtime>::insert(0, 1);
+		Numbers::<Runtime>::insert(1, 4);
+		assert_eq!(crate::pallet::Task::<Runtime>::iter().count(), 2);
+		assert_eq!(RuntimeTask::iter().count(), 2);
+	});
+}
+
+#[test]
+fn runtime_task_enumerate_works_via_frame_system_config() {
+	new_test_ext().execute_with(|| {
+		Numbers::<Runtime>::insert(0, 1);
+		assert_eq!(
+			<Runtime as frame_system::Config>::RuntimeTask::iter().collect::<Vec<_>>(),
+			vec![RuntimeTask::TasksExample(crate::pallet::Task::AddNumberIntoTotal { index: 0 })]
+		);
+	});
+}
+
+#[test]
+fn task_index_and_validity_work() {
+	new_test_ext().execute_with(|| {
+		let task = RuntimeTask::TasksExample(crate::pallet::Task::AddNumberIntoTotal { index: 0 });
+		assert_eq!(task.task_index(), 0);
+		assert!(!task.is_valid());
+
+		Numbers::<Runtime>::insert(0, 1);
+		assert!(task.is_valid());
+	});
+}
+
+#[test]
+fn do_task_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TasksExample::add_number(RuntimeOrigin::signed(1), 0, 1));
+		assert_ok!(TasksExample::add_number(RuntimeOrigin::signed(1), 1, 4));
+		assert_noop!(
+			TasksExample::add_number(RuntimeOrigin::signed(1), 1, 5),
+			crate::Error::<Runtime>::AlreadyExists
+		);
+
+		for task in RuntimeTask::iter() {
+			assert_ok!(System::do_task(RuntimeOrigin::signed(1), task.clone()));
+			System::assert_last_event(frame_system::Event::TaskCompleted { task }.into());
+		}
+
+		assert_eq!(Total::<Runtime>::get(), 5);
+		assert_eq!(Numbers::<Runtime>::iter().count(), 0);
+		assert_eq!(RuntimeTask::iter().count(), 0);
+	});
+}
+
+#[test]
+fn do_task_rejects_invalid_task() {
+	new_test_ext().execute_with(|| {
+		let task = RuntimeTask::TasksExample(crate::pallet::Task::AddNumberIntoTotal { index: 7 });
+		assert_noop!(
+			System::do_task(RuntimeOrigin::signed(1), task),
+			frame_system::Error::<Runtime>::InvalidTask
+		);
+	});
+}
+
+#[test]
+fn offchain_worker_submits_valid_tasks() {
+	let (offchain, _offchain_state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+
+	let mut t = new_test_ext();
+	t.register_extension(OffchainWorkerExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+
+	t.execute_with(|| {
+		Numbers::<Runtime>::insert(0, 10);
+		assert_eq!(System::submit_valid_tasks(), 1);
+
+		let tx = pool_state.write().transactions.pop().unwrap();
+		assert!(pool_state.read().transactions.is_empty());
+		let tx = Extrinsic::decode(&mut &*tx).unwrap();
+		assert_eq!(tx.signature, None);
+
+		let task = RuntimeTask::TasksExample(crate::pallet::Task::AddNumberIntoTotal { index: 0 });
+		assert_eq!(tx.call, RuntimeCall::System(frame_system::Call::do_task { task }));
+
+		assert_ok!(tx.call.dispatch(RuntimeOrigin::none()));
+		assert_eq!(Total::<Runtime>::get(), 10);
+	});
+}
@@ -0,0 +1,219 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for `pallet-example-view-functions`.
+
+use crate::{
+	pallet::{self, Pallet},
+	pallet2,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	derive_impl,
+	traits::PalletInfoAccess,
+	view_functions::{ViewFunction, ViewFunctionDispatchError, ViewFunctionId},
+};
+use scale_info::{form::PortableForm, meta_type};
+use sp_io::hashing::twox_128;
+use sp_metadata_ir::{
+	PalletViewFunctionMetadataIR, PalletViewFunctionParamMetadataIR, ViewFunctionMetadata,
+};
+use sp_runtime::BuildStorage;
+
+type Block = frame_system::mocking::MockBlock<Runtime>;
+
+frame_support::construct_runtime!(
+	pub enum Runtime {
+		System: frame_system,
+		ViewFunctionsExample: pallet,
+		ViewFunctionsInstance: pallet2,
+		ViewFunctionsInstance1: pallet2::<Instance1>,
+	}
+);
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig as frame_system::DefaultConfig)]
+impl frame_system::Config for Runtime {
+	type Block = Block;
+}
+
+impl pallet::Config for Runtime {}
+impl pallet2::Config for Runtime {}
+impl pallet2::Config<pallet2::Instance1> for Runtime {}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = RuntimeGenesisConfig::default().build_storage().unwrap();
+	t.into()
+}
+
+fn call_view_function<V: ViewFunction + Encode>(view_function: V) -> V::ReturnType
+where
+	V::ReturnType: Decode,
+{
+	let output = Runtime::execute_view_function(V::id(), view_function.encode()).unwrap();
+	<V::ReturnType>::decode(&mut &output[..]).unwrap()
+}
+
+#[test]
+fn pallet_get_value_query() {
+	new_test_ext().execute_with(|| {
+		let some_value = Some(99);
+		pallet::SomeValue::<Runtime>::set(some_value);
+		assert_eq!(some_value, Pallet::<Runtime>::get_value());
+
+		let query = pallet::GetValue::<Runtime>::new();
+		assert_eq!(call_view_function(query), some_value);
+	});
+}
+
+#[test]
+fn pallet_get_value_with_arg_query() {
+	new_test_ext().execute_with(|| {
+		let some_key = 1u32;
+		let some_value = Some(123);
+		pallet::SomeMap::<Runtime>::set(some_key, some_value);
+		assert_eq!(some_value, Pallet::<Runtime>::get_value_with_arg(some_key));
+
+		let query = pallet::GetValueWithArg::<Runtime>::new(some_key);
+		assert_eq!(call_view_function(query), some_value);
+	});
+}
+
+#[test]
+fn pallet_multiple_instances() {
+	use pallet2::Instance1;
+
+	new_test_ext().execute_with(|| {
+		let instance_value = Some(123);
+		let instance1_value = Some(456);
+
+		pallet2::SomeValue::<Runtime>::set(instance_value);
+		pallet2::SomeValue::<Runtime, Instance1>::set(instance1_value);
+
+		let query = pallet2::GetValue::<Runtime>::new();
+		assert_eq!(call_view_function(query), instance_value);
+
+		let query_instance1 = pallet2::GetValue::<Runtime, Instance1>::new();
+		assert_eq!(call_view_function(query_instance1), instance1_value);
+	});
+}
+
+#[test]
+fn view_function_ids_are_prefixed_by_pallet_name() {
+	let id = pallet::GetValue::<Runtime>::id();
+	assert_eq!(id.prefix, twox_128(ViewFunctionsExample::name().as_bytes()));
+
+	let id_instance1 = pallet2::GetValue::<Runtime, pallet2::Instance1>::id();
+	assert_eq!(id_instance1.prefix, twox_128(ViewFunctionsInstance1::name().as_bytes()));
+	assert_ne!(pallet2::GetValue::<Runtime>::id(), id_instance1);
+	assert_eq!(pallet2::GetValue::<Runtime>::id().suffix, id_instance1.suffix);
+}
+
+#[test]
+fn unknown_view_function_is_not_found() {
+	new_test_ext().execute_with(|| {
+		let id = ViewFunctionId {
+			prefix: twox_128(ViewFunctionsExample::name().as_bytes()),
+			suffix: [0; 16],
+		};
+		assert_eq!(
+			Runtime::execute_view_function(id.clone(), vec![]),
+			Err(ViewFunctionDispatchError::NotFound(id))
+		);
+
+		let id = ViewFunctionId { prefix: [0; 16], suffix: [0; 16] };
+		assert_eq!(
+			Runtime::execute_view_function(id.clone(), vec![]),
+			Err(ViewFunctionDispatchError::NotFound(id))
+		);
+	});
+}
+
+#[test]
+fn invalid_input_is_rejected() {
+	new_test_ext().execute_with(|| {
+		let id = pallet::GetValueWithArg::<Runtime>::id();
+		assert_eq!(
+			Runtime::execute_view_function(id, vec![1]),
+			Err(ViewFunctionDispatchError::Codec)
+		);
+	});
+}
+
+#[test]
+fn metadata_ir_definitions() {
+	let metadata_ir = Runtime::metadata_ir();
+	let pallet = metadata_ir
+		.pallets
+		.iter()
+		.find(|pallet| pallet.name == "ViewFunctionsExample")
+		.expect("pallet is in the metadata");
+
+	assert_eq!(
+		pallet.view_functions,
+		vec![
+			PalletViewFunctionMetadataIR {
+				name: "get_value",
+				id: pallet::GetValue::<Runtime>::id().into(),
+				inputs: vec![],
+				output: meta_type::<Option<u32>>(),
+				docs: vec![" Query value with no input args."],
+			},
+			PalletViewFunctionMetadataIR {
+				name: "get_value_with_arg",
+				id: pallet::GetValueWithArg::<Runtime>::id().into(),
+				inputs: vec![PalletViewFunctionParamMetadataIR {
+					name: "key",
+					ty: meta_type::<u32>(),
+				}],
+				output: meta_type::<Option<u32>>(),
+				docs: vec![" Query value with input args."],
+			},
+		]
+	);
+
+	let system = metadata_ir
+		.pallets
+		.iter()
+		.find(|pallet| pallet.name == "System")
+		.expect("system is in the metadata");
+	assert!(system.view_functions.is_empty());
+}
+
+#[test]
+fn metadata_v15_custom_section() {
+	use frame_support::__private::metadata::RuntimeMetadata;
+
+	let RuntimeMetadata::V15(metadata) = sp_metadata_ir::into_latest(Runtime::metadata_ir()).1
+	else {
+		panic!("latest metadata is V15")
+	};
+	let custom = &metadata.custom.map[sp_metadata_ir::VIEW_FUNCTIONS_CUSTOM_KEY];
+	let pallets =
+		Vec::<sp_metadata_ir::PalletViewFunctionsMetadata>::decode(&mut &custom.value[..]).unwrap();
+
+	let names = pallets.iter().map(|pallet| pallet.pallet.as_str()).collect::<Vec<_>>();
+	assert_eq!(
+		names,
+		vec!["ViewFunctionsExample", "ViewFunctionsInstance", "ViewFunctionsInstance1"]
+	);
+
+	let get_value_with_arg: &ViewFunctionMetadata = &pallets[0].functions[1];
+	assert_eq!(get_value_with_arg.id, <[u8; 32]>::from(pallet::GetValueWithArg::<Runtime>::id()));
+	let ty: &scale_info::Type<PortableForm> =
+		metadata.types.resolve(get_value_with_arg.inputs[0].ty).unwrap();
+	assert_eq!(ty.type_def, scale_info::TypeDef::Primitive(scale_info::TypeDefPrimitive::U32));
+}
@@ -167,6 +167,8 @@ pub type OriginOf<E, C> = <CallOf<E, C> as Dispatchable>::RuntimeOrigin;
 ///   used to call hooks e.g. `on_initialize`.
 /// - `OnRuntimeUpgrade`: Custom logic that should be called after a runtime upgrade. Modules are
 ///   already called by `AllPalletsWithSystem`. It will be called before all modules will be called.
+/// - `MultiStepMigrator`: Drives multi-block migrations. It is stepped once per block right after
+///   `on_initialize` and no extrinsics other than inherents are applied while it is ongoing.
 pub struct Executive<
 	System,
 	Block,
@@ -174,6 +176,7 @@ pub struct Executive<
 	UnsignedValidator,
 	AllPalletsWithSystem,
 	OnRuntimeUpgrade = (),
+	MultiStepMigrator = (),
 >(
 	PhantomData<(
 		System,
@@ -182,6 +185,7 @@ pub struct Executive<
 		UnsignedValidator,
 		AllPalletsWithSystem,
 		OnRuntimeUpgrade,
+		MultiStepMigrator,
 	)>,
 );
 
@@ -200,8 +204,17 @@ impl<
 			+ OnFinalize<BlockNumberFor<System>>
 			+ OffchainWorker<BlockNumberFor<System>>,
 		COnRuntimeUpgrade: OnRuntimeUpgrade,
+		MultiStepMigrator: frame_support::migrations::MultiStepMigrator,
 	> ExecuteBlock<Block>
-	for Executive<System, Block, Context, UnsignedValidator, AllPalletsWithSystem, COnRuntimeUpgrade>
+	for Executive<
+		System,
+		Block,
+		Context,
+		UnsignedValidator,
+		AllPalletsWithSystem,
+		COnRuntimeUpgrade,
+		MultiStepMigrator,
+	>
 where
 	Block::Extrinsic: Checkable<Context> + Codec,
 	CheckedOf<Block::Extrinsic, Context>: Applyable + GetDispatchInfo,
@@ -218,6 +231,7 @@ where
 			UnsignedValidator,
 			AllPalletsWithSystem,
 			COnRuntimeUpgrade,
+			MultiStepMigrator,
 		>::execute_block(block);
 	}
 }
@@ -240,7 +254,17 @@ impl<
 			+ TryState<BlockNumberFor<System>>
 			+ TryDecodeEntireStorage,
 		COnRuntimeUpgrade: OnRuntimeUpgrade,
-	> Executive<System, Block, Context, UnsignedValidator, AllPalletsWithSystem, COnRuntimeUpgrade>
+		MultiStepMigrator: frame_support::migrations::MultiStepMigrator,
+	>
+	Executive<
+		System,
+		Block,
+		Context,
+		UnsignedValidator,
+		AllPalletsWithSystem,
+		COnRuntimeUpgrade,
+		MultiStepMigrator,
+	>
 where
 	Block::Extrinsic: Checkable<Context> + Codec,
 	CheckedOf<Block::Extrinsic, Context>: Applyable + GetDispatchInfo,
@@ -450,7 +474,17 @@ impl<
 			+ OnFinalize<BlockNumberFor<System>>
 			+ OffchainWorker<BlockNumberFor<System>>,
 		COnRuntimeUpgrade: OnRuntimeUpgrade,
-	> Executive<System, Block, Context, UnsignedValidator, AllPalletsWithSystem, COnRuntimeUpgrade>
+		MultiStepMigrator: frame_support::migrations::MultiStepMigrator,
+	>
+	Executive<
+		System,
+		Block,
+		Context,
+		UnsignedValidator,
+		AllPalletsWithSystem,
+		COnRuntimeUpgrade,
+		MultiStepMigrator,
+	>
 where
 	Block::Extrinsic: Checkable<Context> + Codec,
 	CheckedOf<Block::Extrinsic, Context>: Applyable + GetDispatchInfo,
@@ -509,6 +543,7 @@ where
 		weight = weight.saturating_add(<AllPalletsWithSystem as OnInitialize<
 			BlockNumberFor<System>,
 		>>::on_initialize(*block_number));
+		weight = weight.saturating_add(MultiStepMigrator::step());
 		weight = weight.saturating_add(
 			<System::BlockWeights as frame_support::traits::Get<_>>::get().base_block,
 		);
@@ -638,8 +673,27 @@ where
 
 		// Decode parameters and dispatch
 		let dispatch_info = xt.get_dispatch_info();
+
+		// Only inherents may be applied while multi-block migrations are ongoing. The block is
+		// considered full for everything else, so that block authors skip such extrinsics without
+		// dropping them from the pool.
+		if dispatch_info.class != DispatchClass::Mandatory && MultiStepMigrator::ongoing() {
+			return Err(InvalidTransaction::ExhaustsResources.into())
+		}
+
 		let r = Applyable::apply::<UnsignedValidator>(xt, &dispatch_info, encoded_len)?;
 
+		// Report the declared and the actual `ref_time` to the node, to compare them with the time
+		// the dispatch really took.
+		sp_tracing::within_span! {
+			sp_tracing::trace_span!(
+				"dispatch_weight",
+				declared = dispatch_info.weight.ref_time(),
+				actual = frame_support::dispatch::extract_actual_weight(&r, &dispatch_info)
+					.ref_time(),
+			);
+		}
+
 		// Mandatory(inherents) are not allowed to fail.
 		//
 		// The entire block should be discarded if an inherent fails to apply. Otherwise
@@ -1021,6 +1075,33 @@ mod tests {
 		CustomOnRuntimeUpgrade,
 	>;
 
+	parameter_types! {
+		pub static MbmActive: bool = false;
+		pub static MbmSteps: u32 = 0;
+	}
+
+	struct MockedModeMigrator;
+	impl frame_support::migrations::MultiStepMigrator for MockedModeMigrator {
+		fn ongoing() -> bool {
+			MbmActive::get()
+		}
+
+		fn step() -> Weight {
+			MbmSteps::mutate(|s| *s += 1);
+			Weight::from_parts(123, 0)
+		}
+	}
+
+	type MbmExecutive = super::Executive<
+		Runtime,
+		Block<TestXt>,
+		ChainContext<Runtime>,
+		Runtime,
+		AllPalletsWithSystem,
+		CustomOnRuntimeUpgrade,
+		MockedModeMigrator,
+	>;
+
 	fn extra(nonce: u64, fee: Balance) -> SignedExtra {
 		(
 			frame_system::CheckEra::from(Era::Immortal),
@@ -1698,4 +1779,58 @@ mod tests {
 			);
 		})
 	}
+
+	#[test]
+	fn extrinsics_are_suspended_while_mbms_are_ongoing() {
+		let xt1 = TestXt::new(RuntimeCall::Custom(custom::Call::inherent_call {}), None);
+		let xt2 = TestXt::new(call_transfer(33, 0), sign_extra(1, 0, 0));
+		MbmActive::set(true);
+		MbmSteps::set(0);
+
+		new_test_ext(1).execute_with(|| {
+			MbmExecutive::initialize_block(&Header::new(
+				1,
+				H256::default(),
+				H256::default(),
+				[69u8; 32].into(),
+				Digest::default(),
+			));
+			// The migrator is stepped once per block and its weight is accounted for.
+			assert_eq!(MbmSteps::get(), 1);
+			assert!(System::block_weight()
+				.get(DispatchClass::Mandatory)
+				.all_gte(Weight::from_parts(123, 0)));
+
+			// Inherents are still applied, but nothing else.
+			MbmExecutive::apply_extrinsic(xt1).unwrap().unwrap();
+			assert_err!(
+				MbmExecutive::apply_extrinsic(xt2.clone()),
+				TransactionValidityError::Invalid(InvalidTransaction::ExhaustsResources)
+			);
+
+			// Once the migrations are done, transactions are applied again.
+			MbmActive::set(false);
+			MbmExecutive::apply_extrinsic(xt2).unwrap().unwrap();
+		});
+	}
+
+	#[test]
+	#[should_panic(expected = "Transaction would exhaust the block limits")]
+	fn block_with_extrinsics_fails_while_mbms_are_ongoing() {
+		let xt = TestXt::new(call_transfer(33, 0), sign_extra(1, 0, 0));
+		MbmActive::set(true);
+
+		new_test_ext(1).execute_with(|| {
+			MbmExecutive::execute_block(Block::new(
+				Header::new(
+					1,
+					H256::default(),
+					H256::default(),
+					[69u8; 32].into(),
+					Digest::default(),
+				),
+				vec![xt],
+			));
+		});
+	}
 }
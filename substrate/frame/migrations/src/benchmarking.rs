@@ -0,0 +1,119 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::{Pallet as MultiBlockMigrations, *};
+use frame_benchmarking::v2::*;
+use sp_std::vec;
+
+#[benchmarks]
+mod benches {
+	use super::*;
+
+	#[benchmark]
+	fn onboard_new_mbms() {
+		#[block]
+		{
+			Pallet::<T>::onboard_new_mbms();
+		}
+
+		assert_eq!(Cursor::<T>::exists(), T::Migrations::len() > 0);
+	}
+
+	#[benchmark]
+	fn progress_mbms_none() {
+		Cursor::<T>::kill();
+
+		#[block]
+		{
+			MultiBlockMigrations::<T>::step();
+		}
+	}
+
+	/// Approximates the bookkeeping of `exec_migration` by accessing `Historic` the same way.
+	#[benchmark]
+	fn exec_migration() {
+		let cursor = ActiveCursor {
+			index: T::Migrations::len(),
+			inner_cursor: None,
+			started_at: 0u32.into(),
+		};
+		let id: IdentifierOf<T> = vec![1; T::IdentifierMaxLen::get() as usize].try_into().unwrap();
+		let mut meter = WeightMeter::new();
+
+		#[block]
+		{
+			let _ = Historic::<T>::contains_key(&id);
+			Pallet::<T>::exec_migration(cursor, false, &mut meter, None);
+			Historic::<T>::insert(&id, ());
+		}
+	}
+
+	#[benchmark]
+	fn force_set_cursor() -> Result<(), BenchmarkError> {
+		let origin =
+			T::ForceOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let cursor: CursorOf<T> = MigrationCursor::Stuck;
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, Some(cursor.clone()));
+
+		assert_eq!(Cursor::<T>::get(), Some(cursor));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn force_onboard_mbms() -> Result<(), BenchmarkError> {
+		let origin =
+			T::ForceOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		Cursor::<T>::kill();
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin);
+
+		assert_eq!(Cursor::<T>::exists(), T::Migrations::len() > 0);
+		Ok(())
+	}
+
+	#[benchmark]
+	fn clear_historic(n: Linear<0, 256>) -> Result<(), BenchmarkError> {
+		let origin =
+			T::ForceOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+		let ids = (0..n)
+			.map(|i| {
+				let id: IdentifierOf<T> = i.encode().try_into().unwrap();
+				Historic::<T>::insert(&id, ());
+				id
+			})
+			.collect::<Vec<_>>();
+
+		#[extrinsic_call]
+		_(origin as T::RuntimeOrigin, ids);
+
+		assert_eq!(Historic::<T>::iter().count(), 0);
+		Ok(())
+	}
+
+	// Implements a test for each benchmark. Execute with:
+	// `cargo test -p pallet-migrations --features runtime-benchmarks`.
+	impl_benchmark_test_suite!(
+		MultiBlockMigrations,
+		crate::mock::new_test_ext(),
+		crate::mock::Test
+	);
+}
@@ -0,0 +1,672 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Multi-Block Migrations
+//!
+//! Executes multi-block migrations, i.e. storage migrations that are too heavy to be executed in
+//! a single block.
+//!
+//! ## Pallet API
+//!
+//! See the [`pallet`] module for more information about the interfaces this pallet exposes,
+//! including its configuration trait, dispatchables, storage items, events, and errors.
+//!
+//! ## Overview
+//!
+//! Migrations implement [`SteppedMigration`] and are configured as a tuple in
+//! [`Config::Migrations`]. When the runtime is upgraded, they are onboarded in `on_runtime_upgrade`
+//! and then executed one after another at the start of the following blocks, each of them doing
+//! as much work as fits into [`Config::MaxServiceWeight`] per block.
+//!
+//! The pallet does not step migrations from its own hooks. Instead, it implements
+//! [`MultiStepMigrator`] and must be passed to `frame_executive::Executive`, which drives it
+//! right after `on_initialize`:
+//!
+//! ```ignore
+//! pub type Executive = frame_executive::Executive<
+//!   Runtime,
+//!   Block,
+//!   frame_system::ChainContext<Runtime>,
+//!   Runtime,
+//!   AllPalletsWithSystem,
+//!   Migrations,
+//!   MultiBlockMigrations,
+//! >;
+//! ```
+//!
+//! The progress is tracked in the [`Cursor`] storage item. A migration is only ever advanced by
+//! one step per block. Once it returns `None` as cursor, it is marked as done in the [`Historic`]
+//! storage and will never be executed again, even if it stays configured in a later upgrade.
+//!
+//! ### Suspending transactions
+//!
+//! While migrations are ongoing, the chain state is in an intermediate state and user
+//! transactions should not be processed. The `Executive` therefore refuses to apply any
+//! non-inherent extrinsic until all migrations completed, which is sufficient for most runtimes.
+//! Additionally, the pallet implements [`Contains`] for the runtime call so that it can be used as
+//! (part of) the `BaseCallFilter`, e.g. to stop inherents from calling into the state that is
+//! being migrated:
+//!
+//! ```ignore
+//! impl frame_system::Config for Runtime {
+//!   // …
+//!   type BaseCallFilter = InsideBoth<DefaultFilter, MultiBlockMigrations>;
+//!   // …
+//! }
+//! ```
+//!
+//! Only calls in [`Config::WhitelistedCalls`] pass this filter until all migrations completed.
+//! Mandatory inherents like `Timestamp::set` must be whitelisted in that case, otherwise no block
+//! can be built while migrations are ongoing.
+//!
+//! ### Failed migrations
+//!
+//! A migration which returns an error, takes more steps than its
+//! [`SteppedMigration::max_steps`] or cannot make progress within an entire block is considered
+//! failed. What happens next is decided by the [`Config::FailedMigrationHandler`]: the chain
+//! either stays stuck (and transactions stay suspended) until governance intervenes with
+//! [`Pallet::force_set_cursor`], or the remaining migrations are aborted and normal operation
+//! resumes.
+//!
+//! ### Try-runtime
+//!
+//! With the `try-runtime` feature, all onboarded migrations are run to completion as part of
+//! `on_runtime_upgrade`, including their `pre_upgrade` and `post_upgrade` hooks.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+mod benchmarking;
+#[cfg(test)]
+mod mock;
+pub mod mock_helpers;
+#[cfg(test)]
+mod tests;
+pub mod weights;
+
+pub use pallet::*;
+pub use weights::WeightInfo;
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use core::ops::ControlFlow;
+use frame_support::{
+	defensive, defensive_assert,
+	migrations::*,
+	traits::{Contains, Get},
+	weights::{Weight, WeightMeter},
+	BoundedVec,
+};
+use frame_system::{pallet_prelude::BlockNumberFor, Pallet as System};
+use sp_runtime::Saturating;
+use sp_std::vec::Vec;
+
+const LOG_TARGET: &str = "runtime::migrations";
+
+/// Points to the next migration to execute.
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode, scale_info::TypeInfo, MaxEncodedLen)]
+pub enum MigrationCursor<Cursor, BlockNumber> {
+	/// Points to the currently active migration and its inner cursor.
+	Active(ActiveCursor<Cursor, BlockNumber>),
+
+	/// Migration got stuck and cannot proceed. This is bad.
+	Stuck,
+}
+
+impl<Cursor, BlockNumber> MigrationCursor<Cursor, BlockNumber> {
+	/// Try to return self as an [`ActiveCursor`].
+	pub fn as_active(&self) -> Option<&ActiveCursor<Cursor, BlockNumber>> {
+		match self {
+			MigrationCursor::Active(active) => Some(active),
+			MigrationCursor::Stuck => None,
+		}
+	}
+}
+
+impl<Cursor, BlockNumber> From<ActiveCursor<Cursor, BlockNumber>>
+	for MigrationCursor<Cursor, BlockNumber>
+{
+	fn from(active: ActiveCursor<Cursor, BlockNumber>) -> Self {
+		MigrationCursor::Active(active)
+	}
+}
+
+/// Points to the currently active migration and its inner cursor.
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode, scale_info::TypeInfo, MaxEncodedLen)]
+pub struct ActiveCursor<Cursor, BlockNumber> {
+	/// The index of the migration in the MBM tuple.
+	pub index: u32,
+	/// The cursor of the migration that is referenced by `index`.
+	pub inner_cursor: Option<Cursor>,
+	/// The block number that the migration started at.
+	///
+	/// This is used to calculate how many blocks it took.
+	pub started_at: BlockNumber,
+}
+
+impl<Cursor, BlockNumber: Copy> ActiveCursor<Cursor, BlockNumber> {
+	/// Advance the overarching cursor to the next migration.
+	pub(crate) fn goto_next_migration(&mut self, current_block: BlockNumber) {
+		self.index.saturating_inc();
+		self.inner_cursor = None;
+		self.started_at = current_block;
+	}
+}
+
+/// Convenience alias for [`MigrationCursor`].
+pub type CursorOf<T> = MigrationCursor<RawCursorOf<T>, BlockNumberFor<T>>;
+
+/// Convenience alias for the raw inner cursor of a migration.
+pub type RawCursorOf<T> = BoundedVec<u8, <T as Config>::CursorMaxLen>;
+
+/// Convenience alias for the raw id of a migration.
+pub type IdentifierOf<T> = BoundedVec<u8, <T as Config>::IdentifierMaxLen>;
+
+/// The `pre_upgrade` states of the migrations whose `post_upgrade` hook is still pending, by index.
+#[cfg(feature = "try-runtime")]
+type UpgradeStates = sp_std::collections::btree_map::BTreeMap<u32, Vec<u8>>;
+#[cfg(not(feature = "try-runtime"))]
+type UpgradeStates = ();
+
+/// Convenience alias for [`ActiveCursor`].
+pub type ActiveCursorOf<T> = ActiveCursor<RawCursorOf<T>, BlockNumberFor<T>>;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type of the runtime.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// All the multi-block migrations to run.
+		///
+		/// Should only be updated in a runtime-upgrade once all the old migrations have completed.
+		/// (Check that [`Cursor`] is `None`).
+		type Migrations: SteppedMigrations;
+
+		/// The maximal length of an encoded cursor.
+		///
+		/// A good default needs to be selected such that no migration will ever have a cursor
+		/// with MEL above this limit. This is statically checked in `integrity_test`.
+		#[pallet::constant]
+		type CursorMaxLen: Get<u32>;
+
+		/// The maximal length of an encoded identifier.
+		///
+		/// A good default needs to be selected such that no migration will ever have an identifier
+		/// with MEL above this limit. This is statically checked in `integrity_test`.
+		#[pallet::constant]
+		type IdentifierMaxLen: Get<u32>;
+
+		/// Notifications for status updates of a runtime upgrade.
+		///
+		/// Could be used to pause XCM etc.
+		type MigrationStatusHandler: MigrationStatusHandler;
+
+		/// Handler for failed migrations.
+		type FailedMigrationHandler: FailedMigrationHandler;
+
+		/// The maximum weight to spend each block to execute migrations.
+		type MaxServiceWeight: Get<Weight>;
+
+		/// Calls that can still be dispatched while migrations are ongoing.
+		///
+		/// Should contain at least the calls that governance needs to recover a stuck chain.
+		type WhitelistedCalls: Contains<<Self as frame_system::Config>::RuntimeCall>;
+
+		/// Origin that can force the cursor and the set of completed migrations.
+		type ForceOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// Weight information for the calls and functions of this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	/// The currently active migration to run and its cursor.
+	///
+	/// `None` indicates that no migration is running.
+	#[pallet::storage]
+	pub type Cursor<T: Config> = StorageValue<_, CursorOf<T>, OptionQuery>;
+
+	/// Set of all successfully executed migrations.
+	///
+	/// This is used as blacklist, to not re-execute migrations that have not been removed from the
+	/// codebase yet. Governance can regularly clear this out via `clear_historic`.
+	#[pallet::storage]
+	pub type Historic<T: Config> = StorageMap<_, Twox64Concat, IdentifierOf<T>, (), OptionQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A Runtime upgrade started.
+		///
+		/// Its end is indicated by `UpgradeCompleted` or `UpgradeFailed`.
+		UpgradeStarted {
+			/// The number of migrations that this upgrade contains.
+			///
+			/// This can be used to design a progress indicator in combination with counting the
+			/// `MigrationCompleted` and `MigrationSkipped` events.
+			migrations: u32,
+		},
+		/// The current runtime upgrade completed.
+		///
+		/// This implies that all of its migrations completed successfully as well.
+		UpgradeCompleted,
+		/// Runtime upgrade failed.
+		///
+		/// This is very bad and will require governance intervention.
+		UpgradeFailed,
+		/// A migration was skipped since it was already executed in the past.
+		MigrationSkipped {
+			/// The index of the skipped migration within the [`Config::Migrations`] list.
+			index: u32,
+		},
+		/// A migration progressed.
+		MigrationAdvanced {
+			/// The index of the migration within the [`Config::Migrations`] list.
+			index: u32,
+			/// The number of blocks that elapsed since the migration started.
+			took: BlockNumberFor<T>,
+		},
+		/// A Migration completed.
+		MigrationCompleted {
+			/// The index of the migration within the [`Config::Migrations`] list.
+			index: u32,
+			/// The number of blocks that elapsed since the migration started.
+			took: BlockNumberFor<T>,
+		},
+		/// A Migration failed.
+		///
+		/// This implies that the whole upgrade failed and governance intervention is required.
+		MigrationFailed {
+			/// The index of the migration within the [`Config::Migrations`] list.
+			index: u32,
+			/// The number of blocks that elapsed since the migration started.
+			took: BlockNumberFor<T>,
+		},
+		/// The set of historical migrations has been cleared.
+		HistoricCleared,
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The operation cannot complete since some MBMs are ongoing.
+		Ongoing,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let weight = Self::onboard_new_mbms();
+
+			#[cfg(feature = "try-runtime")]
+			Self::try_execute_all();
+
+			weight
+		}
+
+		#[cfg(feature = "std")]
+		fn integrity_test() {
+			// Check that the migrations tuple is legit.
+			frame_support::assert_ok!(T::Migrations::integrity_test());
+
+			// The per-block service weight must be sane.
+			{
+				let want = T::MaxServiceWeight::get();
+				let max = <T as frame_system::Config>::BlockWeights::get().max_block;
+
+				assert!(want.all_lte(max), "Service weight is larger than a block: {want} > {max}");
+			}
+
+			// Cursor MEL
+			{
+				let mel = T::Migrations::cursor_max_encoded_len();
+				let max_mel = T::CursorMaxLen::get() as usize;
+				assert!(
+					mel <= max_mel,
+					"A Cursor is not guaranteed to fit into the storage: {mel} > {max_mel}",
+				);
+			}
+
+			// Identifier MEL
+			{
+				let mel = T::Migrations::identifier_max_encoded_len();
+				let max_mel = T::IdentifierMaxLen::get() as usize;
+				assert!(
+					mel <= max_mel,
+					"An Identifier is not guaranteed to fit into the storage: {mel} > {max_mel}",
+				);
+			}
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Allows root to set a cursor to forcefully start, stop or forward the migration process.
+		///
+		/// Should normally not be needed and is only in place as emergency measure. Note that
+		/// restarting the migration process in this manner will not call the
+		/// [`MigrationStatusHandler::started`] hook or emit an `UpgradeStarted` event.
+		#[pallet::call_index(0)]
+		#[pallet::weight(T::WeightInfo::force_set_cursor())]
+		pub fn force_set_cursor(
+			origin: OriginFor<T>,
+			cursor: Option<CursorOf<T>>,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			Cursor::<T>::set(cursor);
+
+			Ok(())
+		}
+
+		/// Forces the onboarding of the migrations.
+		///
+		/// This process happens automatically on a runtime upgrade. It is in place as an emergency
+		/// measurement. The cursor needs to be `None` for this to succeed.
+		#[pallet::call_index(1)]
+		#[pallet::weight(T::WeightInfo::force_onboard_mbms())]
+		pub fn force_onboard_mbms(origin: OriginFor<T>) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			ensure!(!Cursor::<T>::exists(), Error::<T>::Ongoing);
+			Self::onboard_new_mbms();
+
+			Ok(())
+		}
+
+		/// Clears the `Historic` set.
+		///
+		/// The given migration identifiers are removed, which allows them to be executed again in
+		/// a later upgrade.
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::WeightInfo::clear_historic(selector.len() as u32))]
+		pub fn clear_historic(
+			origin: OriginFor<T>,
+			selector: Vec<IdentifierOf<T>>,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			for id in selector {
+				Historic::<T>::remove(id);
+			}
+			Self::deposit_event(Event::HistoricCleared);
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Onboard all new Multi-Block-Migrations and start the process of executing them.
+	///
+	/// Should only be called once all previous migrations completed.
+	fn onboard_new_mbms() -> Weight {
+		if let Some(cursor) = Cursor::<T>::get() {
+			log::error!(target: LOG_TARGET, "Ongoing migrations interrupted - chain stuck");
+
+			let maybe_index = cursor.as_active().map(|c| c.index);
+			Self::upgrade_failed(maybe_index);
+			return T::WeightInfo::onboard_new_mbms()
+		}
+
+		let migrations = T::Migrations::len();
+		log::debug!(target: LOG_TARGET, "Onboarding {migrations} new MBM migrations");
+
+		if migrations > 0 {
+			// Set the cursor to the first migration:
+			Cursor::<T>::set(Some(
+				ActiveCursor {
+					index: 0,
+					inner_cursor: None,
+					started_at: System::<T>::block_number(),
+				}
+				.into(),
+			));
+			Self::deposit_event(Event::UpgradeStarted { migrations });
+			T::MigrationStatusHandler::started();
+		}
+
+		T::WeightInfo::onboard_new_mbms()
+	}
+
+	/// Tries to make progress on the Multi-Block-Migrations process.
+	///
+	/// The try-runtime hooks of the migrations are only run if `states` is given.
+	fn progress_mbms(n: BlockNumberFor<T>, mut states: Option<&mut UpgradeStates>) -> Weight {
+		let mut meter = WeightMeter::with_limit(T::MaxServiceWeight::get());
+		meter.consume(T::WeightInfo::progress_mbms_none());
+
+		let mut cursor = match Cursor::<T>::get() {
+			None => {
+				log::trace!(target: LOG_TARGET, "[Block {n:?}] Waiting for cursor to become `Some`.");
+				return meter.consumed()
+			},
+			Some(MigrationCursor::Active(cursor)) => {
+				log::debug!(target: LOG_TARGET, "Progressing MBM #{}", cursor.index);
+				cursor
+			},
+			Some(MigrationCursor::Stuck) => {
+				log::error!(target: LOG_TARGET, "Migration stuck. Governance intervention required.");
+				return meter.consumed()
+			},
+		};
+		debug_assert!(Self::ongoing());
+
+		// The limit here is a defensive measure to prevent an infinite loop. It expresses that we
+		// allow no more than 8 MBMs to finish in a single block. This should be harmless, since we
+		// generally expect *Multi*-Block-Migrations to take *multiple* blocks.
+		for i in 0..8 {
+			match Self::exec_migration(cursor, i == 0, &mut meter, states.as_deref_mut()) {
+				None => return meter.consumed(),
+				Some(ControlFlow::Continue(next_cursor)) => {
+					cursor = next_cursor;
+				},
+				Some(ControlFlow::Break(last_cursor)) => {
+					cursor = last_cursor;
+					break
+				},
+			}
+		}
+
+		Cursor::<T>::set(Some(cursor.into()));
+
+		meter.consumed()
+	}
+
+	/// Try to make progress on the current migration.
+	///
+	/// Returns whether processing should continue or break for this block. The return value means:
+	/// - `None`: The migration process is completely finished.
+	/// - `ControlFlow::Break`: Continue in the *next* block with the given cursor.
+	/// - `ControlFlow::Continue`: Continue in the *current* block with the given cursor.
+	///
+	/// If `states` is given, the `pre_upgrade` hook of a migration runs before its first step and
+	/// the `post_upgrade` hook once it completed.
+	fn exec_migration(
+		mut cursor: ActiveCursorOf<T>,
+		is_first: bool,
+		meter: &mut WeightMeter,
+		mut states: Option<&mut UpgradeStates>,
+	) -> Option<ControlFlow<ActiveCursorOf<T>, ActiveCursorOf<T>>> {
+		#[cfg(not(feature = "try-runtime"))]
+		let _ = states.take();
+
+		// The bookkeeping of each branch costs about the same, so we always reserve the maximum.
+		if meter.try_consume(T::WeightInfo::exec_migration()).is_err() {
+			defensive_assert!(!is_first, "There should be enough weight to do this at least once");
+			return Some(ControlFlow::Break(cursor))
+		}
+
+		let Some(id) = T::Migrations::nth_id(cursor.index) else {
+			// No more migrations in the tuple - we are done.
+			defensive_assert!(cursor.index == T::Migrations::len(), "Inconsistent MBMs tuple");
+			Self::deposit_event(Event::UpgradeCompleted);
+			Cursor::<T>::kill();
+			T::MigrationStatusHandler::completed();
+			return None
+		};
+
+		let Ok(bounded_id): Result<IdentifierOf<T>, _> = id.try_into() else {
+			defensive!(
+				"integrity_test ensures that all identifiers fit into IdentifierMaxLen; qed"
+			);
+			Self::upgrade_failed(Some(cursor.index));
+			return None
+		};
+
+		if Historic::<T>::contains_key(&bounded_id) {
+			Self::deposit_event(Event::MigrationSkipped { index: cursor.index });
+			cursor.goto_next_migration(System::<T>::block_number());
+			return Some(ControlFlow::Continue(cursor))
+		}
+
+		#[cfg(feature = "try-runtime")]
+		if let Some(states) = states.as_deref_mut() {
+			if cursor.inner_cursor.is_none() && !states.contains_key(&cursor.index) {
+				if let Some(state) = T::Migrations::nth_pre_upgrade(cursor.index) {
+					let state = state.expect("pre_upgrade of a multi-block migration failed");
+					states.insert(cursor.index, state);
+				}
+			}
+		}
+
+		let max_steps = T::Migrations::nth_max_steps(cursor.index);
+		let next_cursor = T::Migrations::nth_transactional_step(
+			cursor.index,
+			cursor.inner_cursor.clone().map(|c| c.into_inner()),
+			meter,
+		);
+		let Some((max_steps, next_cursor)) = max_steps.zip(next_cursor) else {
+			defensive!("integrity_test ensures that the tuple is valid; qed");
+			Self::upgrade_failed(Some(cursor.index));
+			return None
+		};
+
+		let took = System::<T>::block_number().saturating_sub(cursor.started_at);
+		match next_cursor {
+			Ok(Some(next_cursor)) => {
+				let Ok(bound_next_cursor) = next_cursor.try_into() else {
+					defensive!(
+						"integrity_test ensures that all cursors fit into CursorMaxLen; qed"
+					);
+					Self::upgrade_failed(Some(cursor.index));
+					return None
+				};
+
+				Self::deposit_event(Event::MigrationAdvanced { index: cursor.index, took });
+				cursor.inner_cursor = Some(bound_next_cursor);
+
+				if max_steps.map_or(false, |max| took > max.into()) {
+					Self::deposit_event(Event::MigrationFailed { index: cursor.index, took });
+					Self::upgrade_failed(Some(cursor.index));
+					None
+				} else {
+					// A migration cannot progress more than one step per block, we therefore break.
+					Some(ControlFlow::Break(cursor))
+				}
+			},
+			Ok(None) => {
+				// A migration is done when it returns cursor `None`.
+				Self::deposit_event(Event::MigrationCompleted { index: cursor.index, took });
+				#[cfg(feature = "try-runtime")]
+				if let Some(state) = states.and_then(|states| states.remove(&cursor.index)) {
+					if let Some(result) = T::Migrations::nth_post_upgrade(cursor.index, state) {
+						result.expect("post_upgrade of a multi-block migration failed");
+					}
+				}
+				Historic::<T>::insert(&bounded_id, ());
+				cursor.goto_next_migration(System::<T>::block_number());
+				Some(ControlFlow::Continue(cursor))
+			},
+			Err(SteppedMigrationError::InsufficientWeight { required }) => {
+				if is_first || required.any_gt(meter.limit()) {
+					Self::deposit_event(Event::MigrationFailed { index: cursor.index, took });
+					Self::upgrade_failed(Some(cursor.index));
+					None
+				} else {
+					// Retry and hope that there is more weight in the next block.
+					Some(ControlFlow::Break(cursor))
+				}
+			},
+			Err(SteppedMigrationError::InvalidCursor | SteppedMigrationError::Failed) => {
+				Self::deposit_event(Event::MigrationFailed { index: cursor.index, took });
+				Self::upgrade_failed(Some(cursor.index));
+				None
+			},
+		}
+	}
+
+	/// Fail the current runtime upgrade, caused by `migration`.
+	fn upgrade_failed(migration: Option<u32>) {
+		use FailedMigrationHandling::*;
+		Self::deposit_event(Event::UpgradeFailed);
+
+		match T::FailedMigrationHandler::failed(migration) {
+			KeepStuck => Cursor::<T>::set(Some(MigrationCursor::Stuck)),
+			ForceUnstuck => {
+				Cursor::<T>::kill();
+				T::MigrationStatusHandler::completed();
+			},
+		}
+	}
+
+	/// Execute all onboarded migrations to completion, including their try-runtime hooks.
+	///
+	/// Panics if any of the migrations or hooks fail.
+	#[cfg(feature = "try-runtime")]
+	fn try_execute_all() {
+		let mut states = UpgradeStates::new();
+		let initial_block = System::<T>::block_number();
+
+		while let Some(MigrationCursor::Active(_)) = Cursor::<T>::get() {
+			frame_system::Pallet::<T>::set_block_number(
+				System::<T>::block_number().saturating_add(1u32.into()),
+			);
+			Self::progress_mbms(System::<T>::block_number(), Some(&mut states));
+		}
+		frame_system::Pallet::<T>::set_block_number(initial_block);
+
+		assert!(
+			!matches!(Cursor::<T>::get(), Some(MigrationCursor::Stuck)),
+			"A multi-block migration failed"
+		);
+	}
+}
+
+impl<T: Config> MultiStepMigrator for Pallet<T> {
+	fn ongoing() -> bool {
+		Cursor::<T>::exists()
+	}
+
+	fn step() -> Weight {
+		Self::progress_mbms(System::<T>::block_number(), None)
+	}
+}
+
+impl<T: Config> Contains<<T as frame_system::Config>::RuntimeCall> for Pallet<T> {
+	/// Return whether the given call is allowed to be dispatched.
+	///
+	/// Only whitelisted calls are allowed while migrations are ongoing or stuck.
+	fn contains(call: &<T as frame_system::Config>::RuntimeCall) -> bool {
+		!Self::ongoing() || T::WhitelistedCalls::contains(call)
+	}
+}
@@ -0,0 +1,184 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mocked runtime for testing the migrations pallet.
+
+#![cfg(test)]
+
+use crate::{mock_helpers::*, Event, Historic};
+
+use frame_support::{
+	derive_impl,
+	migrations::*,
+	parameter_types,
+	traits::{ConstU64, Contains, Hooks, InsideBoth, IsSubType, OnRuntimeUpgrade},
+	weights::Weight,
+};
+use frame_system::EventRecord;
+use sp_core::{ConstU32, H256};
+use sp_runtime::{
+	traits::{BlakeTwo256, IdentityLookup},
+	BuildStorage,
+};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test
+	{
+		System: frame_system,
+		Migrations: crate,
+	}
+);
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig as frame_system::DefaultConfig)]
+impl frame_system::Config for Test {
+	type BaseCallFilter = InsideBoth<frame_support::traits::Everything, Migrations>;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Nonce = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Block = Block;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+parameter_types! {
+	pub const MaxServiceWeight: Weight = Weight::MAX.div(10);
+	/// Whether a failed migration should unstuck the chain instead of freezing it.
+	pub storage UnstuckOnFailure: bool = false;
+}
+
+/// Only allows calls into the migrations pallet itself while migrations are ongoing.
+pub struct MigrationsCallsOnly;
+impl Contains<RuntimeCall> for MigrationsCallsOnly {
+	fn contains(call: &RuntimeCall) -> bool {
+		IsSubType::<crate::Call<Test>>::is_sub_type(call).is_some()
+	}
+}
+
+/// Records the status handler notifications and applies [`UnstuckOnFailure`].
+pub struct MockedHandlers;
+impl MigrationStatusHandler for MockedHandlers {
+	fn started() {
+		log::info!("MigrationStatusHandler started");
+		frame_support::storage::unhashed::put(b"started", &true);
+	}
+
+	fn completed() {
+		log::info!("MigrationStatusHandler completed");
+		frame_support::storage::unhashed::put(b"completed", &true);
+	}
+}
+
+impl FailedMigrationHandler for MockedHandlers {
+	fn failed(migration: Option<u32>) -> FailedMigrationHandling {
+		log::error!("FailedMigrationHandler failed at: {migration:?}");
+		if UnstuckOnFailure::get() {
+			FailedMigrationHandling::ForceUnstuck
+		} else {
+			FailedMigrationHandling::KeepStuck
+		}
+	}
+}
+
+impl crate::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Migrations = MockedMigrations;
+	type CursorMaxLen = ConstU32<65_536>;
+	type IdentifierMaxLen = ConstU32<256>;
+	type MigrationStatusHandler = MockedHandlers;
+	type FailedMigrationHandler = MockedHandlers;
+	type MaxServiceWeight = MaxServiceWeight;
+	type WhitelistedCalls = MigrationsCallsOnly;
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type WeightInfo = ();
+}
+
+/// Test externalities with the default genesis.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Run this closure in test externalities.
+pub fn test_closure<R>(f: impl FnOnce() -> R) -> R {
+	new_test_ext().execute_with(f)
+}
+
+/// Whether the [`MigrationStatusHandler`] was notified of the given status.
+pub fn notified(status: &[u8]) -> bool {
+	frame_support::storage::unhashed::get_or_default(status)
+}
+
+/// Progress blocks until `n`, running the `on_initialize` hooks and a migration step in each new
+/// block, like the `Executive` would.
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		if System::block_number() > 1 {
+			Migrations::on_finalize(System::block_number());
+			System::on_finalize(System::block_number());
+		}
+		log::debug!("Block {}", System::block_number() + 1);
+		System::set_block_number(System::block_number() + 1);
+		System::on_initialize(System::block_number());
+		Migrations::on_initialize(System::block_number());
+		Migrations::step();
+	}
+}
+
+/// Simulate a runtime upgrade by calling `on_runtime_upgrade` of all pallets.
+pub fn upgrade() {
+	AllPalletsWithSystem::on_runtime_upgrade();
+}
+
+/// Returns the historic migrations, sorted by their identifier.
+pub fn historic() -> Vec<MockedIdentifier> {
+	let mut historic = Historic::<Test>::iter_keys().collect::<Vec<_>>();
+	historic.sort();
+	historic
+}
+
+/// Asserts that the given events were emitted by the migrations pallet, in order.
+pub fn assert_events(want: Vec<Event<Test>>) {
+	let got: Vec<_> = System::events()
+		.into_iter()
+		.filter_map(|EventRecord { event, .. }| match event {
+			RuntimeEvent::Migrations(e) => Some(e),
+			_ => None,
+		})
+		.collect();
+	assert_eq!(got, want);
+	System::reset_events();
+}
@@ -0,0 +1,300 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(test)]
+
+use crate::{
+	mock::{Test as T, *},
+	mock_helpers::{MockedMigrationKind::*, *},
+	Cursor, Event, MigrationCursor,
+};
+use frame_support::{assert_noop, assert_ok, traits::Contains, weights::Weight};
+use sp_runtime::DispatchError;
+
+#[test]
+fn simple_works() {
+	use Event::*;
+	test_closure(|| {
+		// Add three migrations, each taking one block longer than the previous.
+		MockedMigrations::set(vec![(SucceedAfter, 0), (SucceedAfter, 1), (SucceedAfter, 2)]);
+
+		System::set_block_number(1);
+		upgrade();
+		run_to_block(10);
+
+		// Check that the executed migrations are recorded in `Historic`.
+		assert_eq!(
+			historic(),
+			vec![
+				mocked_id(SucceedAfter, 0),
+				mocked_id(SucceedAfter, 1),
+				mocked_id(SucceedAfter, 2),
+			]
+		);
+
+		// Check that we got all events.
+		assert_events(vec![
+			UpgradeStarted { migrations: 3 },
+			MigrationCompleted { index: 0, took: 1 },
+			MigrationAdvanced { index: 1, took: 0 },
+			MigrationCompleted { index: 1, took: 1 },
+			MigrationAdvanced { index: 2, took: 0 },
+			MigrationAdvanced { index: 2, took: 1 },
+			MigrationCompleted { index: 2, took: 2 },
+			UpgradeCompleted,
+		]);
+		assert!(notified(b"started") && notified(b"completed"));
+		assert!(!Cursor::<T>::exists());
+	});
+}
+
+#[test]
+fn failing_migration_freezes_the_chain() {
+	use Event::*;
+	test_closure(|| {
+		MockedMigrations::set(vec![(FailAfter, 2)]);
+
+		System::set_block_number(1);
+		upgrade();
+		run_to_block(10);
+
+		// Failed migrations are not recorded in `Historic`.
+		assert!(historic().is_empty());
+		assert_events(vec![
+			UpgradeStarted { migrations: 1 },
+			MigrationAdvanced { index: 0, took: 1 },
+			MigrationAdvanced { index: 0, took: 2 },
+			MigrationFailed { index: 0, took: 3 },
+			UpgradeFailed,
+		]);
+
+		// The chain is stuck until governance intervenes.
+		assert_eq!(Cursor::<T>::get(), Some(MigrationCursor::Stuck));
+		assert!(!notified(b"completed"));
+	});
+}
+
+#[test]
+fn failing_migration_can_unstuck_the_chain() {
+	use Event::*;
+	test_closure(|| {
+		UnstuckOnFailure::set(&true);
+		MockedMigrations::set(vec![(FailAfter, 0), (SucceedAfter, 0)]);
+
+		System::set_block_number(1);
+		upgrade();
+		run_to_block(10);
+
+		// The second migration is never executed.
+		assert!(historic().is_empty());
+		assert_events(vec![
+			UpgradeStarted { migrations: 2 },
+			MigrationFailed { index: 0, took: 1 },
+			UpgradeFailed,
+		]);
+		assert!(!Cursor::<T>::exists());
+		assert!(notified(b"completed"));
+	});
+}
+
+#[test]
+fn historic_skipping_works() {
+	use Event::*;
+	test_closure(|| {
+		MockedMigrations::set(vec![(SucceedAfter, 0), (SucceedAfter, 1)]);
+
+		System::set_block_number(1);
+		upgrade();
+		run_to_block(10);
+		assert_eq!(historic(), vec![mocked_id(SucceedAfter, 0), mocked_id(SucceedAfter, 1)]);
+		System::reset_events();
+
+		// A later upgrade that still contains the old migrations skips them.
+		MockedMigrations::set(vec![(SucceedAfter, 0), (SucceedAfter, 1), (SucceedAfter, 2)]);
+		upgrade();
+		run_to_block(20);
+
+		assert_events(vec![
+			UpgradeStarted { migrations: 3 },
+			MigrationSkipped { index: 0 },
+			MigrationSkipped { index: 1 },
+			MigrationAdvanced { index: 2, took: 0 },
+			MigrationAdvanced { index: 2, took: 1 },
+			MigrationCompleted { index: 2, took: 2 },
+			UpgradeCompleted,
+		]);
+		assert_eq!(historic().len(), 3);
+	});
+}
+
+#[test]
+fn timeout_fails_the_upgrade() {
+	use Event::*;
+	test_closure(|| {
+		// `TimeoutAfter` never completes and is cut off after its `max_steps`.
+		MockedMigrations::set(vec![(TimeoutAfter, 3)]);
+
+		System::set_block_number(1);
+		upgrade();
+		run_to_block(10);
+
+		assert_events(vec![
+			UpgradeStarted { migrations: 1 },
+			MigrationAdvanced { index: 0, took: 1 },
+			MigrationAdvanced { index: 0, took: 2 },
+			MigrationAdvanced { index: 0, took: 3 },
+			MigrationAdvanced { index: 0, took: 4 },
+			MigrationFailed { index: 0, took: 4 },
+			UpgradeFailed,
+		]);
+		assert_eq!(Cursor::<T>::get(), Some(MigrationCursor::Stuck));
+	});
+}
+
+#[test]
+fn high_weight_migration_fails_the_upgrade() {
+	use Event::*;
+	test_closure(|| {
+		// A migration that needs more weight than available in a whole block can never progress.
+		MockedMigrations::set(vec![(HighWeightAfter(Weight::MAX), 0)]);
+
+		System::set_block_number(1);
+		upgrade();
+		run_to_block(10);
+
+		assert_events(vec![
+			UpgradeStarted { migrations: 1 },
+			MigrationFailed { index: 0, took: 1 },
+			UpgradeFailed,
+		]);
+		assert_eq!(Cursor::<T>::get(), Some(MigrationCursor::Stuck));
+	});
+}
+
+#[test]
+fn calls_are_filtered_while_ongoing() {
+	test_closure(|| {
+		MockedMigrations::set(vec![(SucceedAfter, 2)]);
+		let remark = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+		let force = RuntimeCall::Migrations(crate::Call::force_set_cursor { cursor: None });
+		type Filter = <T as frame_system::Config>::BaseCallFilter;
+
+		assert!(Filter::contains(&remark));
+
+		System::set_block_number(1);
+		upgrade();
+		assert!(Migrations::ongoing());
+		assert!(!Filter::contains(&remark));
+		assert!(Filter::contains(&force));
+
+		run_to_block(10);
+		assert!(!Migrations::ongoing());
+		assert!(Filter::contains(&remark));
+	});
+}
+
+#[test]
+fn force_set_cursor_works() {
+	test_closure(|| {
+		MockedMigrations::set(vec![(FailAfter, 0)]);
+
+		System::set_block_number(1);
+		upgrade();
+		run_to_block(3);
+		assert_eq!(Cursor::<T>::get(), Some(MigrationCursor::Stuck));
+
+		assert_noop!(
+			Migrations::force_set_cursor(RuntimeOrigin::signed(1), None),
+			DispatchError::BadOrigin
+		);
+		// Governance can unstuck the chain.
+		assert_ok!(Migrations::force_set_cursor(RuntimeOrigin::root(), None));
+		assert!(!Cursor::<T>::exists());
+	});
+}
+
+#[test]
+fn force_onboard_mbms_works() {
+	use Event::*;
+	test_closure(|| {
+		MockedMigrations::set(vec![(SucceedAfter, 0)]);
+
+		System::set_block_number(1);
+		assert_ok!(Migrations::force_onboard_mbms(RuntimeOrigin::root()));
+		assert_noop!(
+			Migrations::force_onboard_mbms(RuntimeOrigin::root()),
+			crate::Error::<T>::Ongoing
+		);
+
+		run_to_block(3);
+		assert_events(vec![
+			UpgradeStarted { migrations: 1 },
+			MigrationCompleted { index: 0, took: 1 },
+			UpgradeCompleted,
+		]);
+	});
+}
+
+#[test]
+fn clear_historic_works() {
+	test_closure(|| {
+		MockedMigrations::set(vec![(SucceedAfter, 0), (SucceedAfter, 1)]);
+
+		System::set_block_number(1);
+		upgrade();
+		run_to_block(10);
+		assert_eq!(historic().len(), 2);
+
+		assert_noop!(
+			Migrations::clear_historic(RuntimeOrigin::signed(1), vec![]),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Migrations::clear_historic(
+			RuntimeOrigin::root(),
+			vec![mocked_id(SucceedAfter, 0)]
+		));
+		assert_eq!(historic(), vec![mocked_id(SucceedAfter, 1)]);
+	});
+}
+
+#[test]
+fn failed_handlers_work() {
+	use frame_support::migrations::{
+		FailedMigrationHandler, FreezeChainOnFailedMigration, ResumeChainOnFailedMigration,
+	};
+
+	use frame_support::migrations::FailedMigrationHandling;
+
+	assert_eq!(FreezeChainOnFailedMigration::failed(Some(0)), FailedMigrationHandling::KeepStuck);
+	assert_eq!(ResumeChainOnFailedMigration::failed(None), FailedMigrationHandling::ForceUnstuck);
+}
+
+#[test]
+#[cfg(feature = "try-runtime")]
+fn try_runtime_checks_every_migration() {
+	test_closure(|| {
+		// Each migration completes in the same step in which the next one starts.
+		MockedMigrations::set(vec![(SucceedAfter, 0), (SucceedAfter, 1), (SucceedAfter, 2)]);
+
+		System::set_block_number(1);
+		upgrade();
+
+		assert_eq!(Cursor::<T>::get(), None);
+		assert_eq!(historic().len(), 3);
+		assert_eq!(MockedMigrations::upgrade_checks(), (vec![0, 1, 2], vec![0, 1, 2]));
+	});
+}
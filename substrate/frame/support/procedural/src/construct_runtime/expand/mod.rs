@@ -26,7 +26,9 @@ mod metadata;
 mod origin;
 mod outer_enums;
 mod slash_reason;
+mod task;
 mod unsigned;
+mod view_function;
 
 pub use call::expand_outer_dispatch;
 pub use config::expand_outer_config;
@@ -38,4 +40,6 @@ pub use metadata::expand_runtime_metadata;
 pub use origin::expand_outer_origin;
 pub use outer_enums::{expand_outer_enum, OuterEnumType};
 pub use slash_reason::expand_outer_slash_reason;
+pub use task::expand_outer_task;
 pub use unsigned::expand_outer_validate_unsigned;
+pub use view_function::expand_outer_view_function;
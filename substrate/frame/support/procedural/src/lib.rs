@@ -884,11 +884,12 @@ pub fn inject_runtime_type(_: TokenStream, tokens: TokenStream) -> TokenStream {
 		item.ident != "RuntimeOrigin" &&
 		item.ident != "RuntimeHoldReason" &&
 		item.ident != "RuntimeFreezeReason" &&
+		item.ident != "RuntimeTask" &&
 		item.ident != "PalletInfo"
 	{
 		return syn::Error::new_spanned(
 			item,
-			"`#[inject_runtime_type]` can only be attached to `RuntimeCall`, `RuntimeEvent`, `RuntimeOrigin` or `PalletInfo`",
+			"`#[inject_runtime_type]` can only be attached to `RuntimeCall`, `RuntimeEvent`, `RuntimeTask`, `RuntimeOrigin` or `PalletInfo`",
 		)
 		.to_compile_error()
 		.into();
@@ -1518,6 +1519,98 @@ pub fn composite_enum(_: TokenStream, _: TokenStream) -> TokenStream {
 	pallet_macro_stub()
 }
 
+/// Allows a pallet to declare a set of functions as *tasks*, i.e. permissionless units of
+/// service work (such as cleanups) that anybody can trigger once they are valid.
+///
+/// The attribute is placed on an `impl<T: Config> Pallet<T>` block. Each function inside of it
+/// is a task and must be annotated with [`#[pallet::task_index]`](`macro@task_index`),
+/// [`#[pallet::task_condition]`](`macro@task_condition`),
+/// [`#[pallet::task_list]`](`macro@task_list`) and
+/// [`#[pallet::task_weight]`](`macro@task_weight`). Task functions take no origin and must return
+/// `DispatchResult`.
+///
+/// ```ignore
+/// #[pallet::tasks]
+/// impl<T: Config> Pallet<T> {
+///     #[pallet::task_index(0)]
+///     #[pallet::task_condition(|i| Numbers::<T>::contains_key(i))]
+///     #[pallet::task_list(Numbers::<T>::iter_keys())]
+///     #[pallet::task_weight(T::WeightInfo::add_number_into_total())]
+///     pub fn add_number_into_total(i: u32) -> DispatchResult {
+///         let v = Numbers::<T>::take(i).ok_or(Error::<T>::NotFound)?;
+///         Total::<T>::mutate(|t| *t += v);
+///         Ok(())
+///     }
+/// }
+/// ```
+///
+/// ## Macro expansion
+///
+/// The macro generates a `Task<T>` enum with one variant per task function, named after the
+/// function in `UpperCamelCase` and holding its arguments, and implements
+/// `frame_support::traits::Task` for it. The pallet then exposes a `Task` part that is aggregated
+/// into the `RuntimeTask` enum by `construct_runtime`.
+#[proc_macro_attribute]
+pub fn tasks(_: TokenStream, _: TokenStream) -> TokenStream {
+	pallet_macro_stub()
+}
+
+/// Defines the index of a task within the `Task` enum of the pallet, analogous to
+/// [`#[pallet::call_index]`](`macro@call_index`). See [`#[pallet::tasks]`](`macro@tasks`).
+#[proc_macro_attribute]
+pub fn task_index(_: TokenStream, _: TokenStream) -> TokenStream {
+	pallet_macro_stub()
+}
+
+/// Defines the condition under which a task is valid, as a closure that receives a reference to
+/// each of the task's arguments. See [`#[pallet::tasks]`](`macro@tasks`).
+#[proc_macro_attribute]
+pub fn task_condition(_: TokenStream, _: TokenStream) -> TokenStream {
+	pallet_macro_stub()
+}
+
+/// Defines an iterator over the arguments of all candidate instances of a task. Tasks with
+/// multiple arguments are yielded as tuples. See [`#[pallet::tasks]`](`macro@tasks`).
+#[proc_macro_attribute]
+pub fn task_list(_: TokenStream, _: TokenStream) -> TokenStream {
+	pallet_macro_stub()
+}
+
+/// Defines the weight of executing a task. See [`#[pallet::tasks]`](`macro@tasks`).
+#[proc_macro_attribute]
+pub fn task_weight(_: TokenStream, _: TokenStream) -> TokenStream {
+	pallet_macro_stub()
+}
+
+/// Allows a pallet to declare read-only *view functions*, queryable by clients without writing a
+/// dedicated runtime API.
+///
+/// The attribute is placed on an `impl<T: Config> Pallet<T>` block. Each function inside of it
+/// must be public, take no `self` and return a value:
+///
+/// ```ignore
+/// #[pallet::view_functions]
+/// impl<T: Config> Pallet<T> {
+///     /// Get the value stored under `key`.
+///     pub fn get_value_with_arg(key: u32) -> Option<u32> {
+///         SomeMap::<T>::get(key)
+///     }
+/// }
+/// ```
+///
+/// ## Macro expansion
+///
+/// For each function the macro generates a struct named after the function in `UpperCamelCase`
+/// and holding its arguments, which implements `frame_support::view_functions::ViewFunction`.
+/// A view function is identified by a `ViewFunctionId`: the hash of the pallet name followed by
+/// the hash of the function signature. The view functions are added to the pallet metadata and
+/// `construct_runtime` dispatches them through `Runtime::execute_view_function`, meant to
+/// implement the `RuntimeViewFunction` runtime API.
+#[proc_macro_attribute]
+pub fn view_functions(_: TokenStream, _: TokenStream) -> TokenStream {
+	pallet_macro_stub()
+}
+
 /// Can be attached to a module. Doing so will declare that module as importable into a pallet
 /// via [`#[import_section]`](`macro@import_section`).
 ///
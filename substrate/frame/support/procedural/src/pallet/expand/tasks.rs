@@ -0,0 +1,168 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Home of the expansion code for the Tasks API
+
+use crate::pallet::Def;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// Generate the `Task` enum of the pallet and implement `frame_support::traits::Task` for it.
+pub fn expand_tasks(def: &mut Def) -> TokenStream {
+	let Some(tasks) = def.tasks.as_ref() else { return TokenStream::new() };
+
+	let span = tasks.attr_span;
+	let frame_support = &def.frame_support;
+	let type_impl_gen = &def.type_impl_generics(span);
+	let type_decl_bounded_gen = &def.type_decl_bounded_generics(span);
+	let type_use_gen = &def.type_use_generics(span);
+	let pallet_ident = &def.pallet_struct.pallet;
+	let where_clause = &tasks.where_clause;
+	let task_ident = syn::Ident::new("Task", span);
+
+	let mut variants = Vec::new();
+	let mut iter_extends = Vec::new();
+	let mut is_valid_arms = Vec::new();
+	let mut run_arms = Vec::new();
+	let mut weight_arms = Vec::new();
+	let mut index_arms = Vec::new();
+
+	for task in &tasks.tasks {
+		let variant = format_ident!(
+			"{}",
+			inflector::cases::pascalcase::to_pascal_case(&task.name.to_string()),
+			span = task.name.span(),
+		);
+		let fn_name = &task.name;
+		let attrs = &task.attrs;
+		let docs = &task.docs;
+		let task_index = task.index;
+		let condition = &task.condition;
+		let list = &task.list;
+		let weight = &task.weight;
+		let arg_names = task.args.iter().map(|(name, _)| name).collect::<Vec<_>>();
+		let arg_types = task.args.iter().map(|(_, ty)| ty).collect::<Vec<_>>();
+
+		// A single argument is yielded as is by the list, multiple arguments as a tuple.
+		let list_pattern = match arg_names.as_slice() {
+			[single] => quote!(#single),
+			args => quote!( ( #( #args ),* ) ),
+		};
+
+		variants.push(quote! {
+			#( #attrs )*
+			#( #[doc = #docs] )*
+			#variant { #( #arg_names: #arg_types ),* },
+		});
+
+		iter_extends.push(quote! {
+			#( #attrs )*
+			all_tasks.extend(
+				(#list).map(|#list_pattern| #task_ident::#variant { #( #arg_names ),* })
+			);
+		});
+
+		is_valid_arms.push(quote! {
+			#( #attrs )*
+			#task_ident::#variant { #( #arg_names ),* } => (#condition)( #( #arg_names ),* ),
+		});
+
+		run_arms.push(quote! {
+			#( #attrs )*
+			#task_ident::#variant { #( #arg_names ),* } =>
+				<#pallet_ident<#type_use_gen>>::#fn_name( #( #arg_names.clone() ),* ),
+		});
+
+		weight_arms.push(quote! {
+			#( #attrs )*
+			#task_ident::#variant { .. } => #weight,
+		});
+
+		index_arms.push(quote! {
+			#( #attrs )*
+			#task_ident::#variant { .. } => #task_index,
+		});
+	}
+
+	quote::quote_spanned!(span =>
+		/// Contains a variant per task defined in `#[pallet::tasks]`.
+		#[derive(
+			#frame_support::RuntimeDebugNoBound,
+			#frame_support::CloneNoBound,
+			#frame_support::EqNoBound,
+			#frame_support::PartialEqNoBound,
+			#frame_support::__private::codec::Encode,
+			#frame_support::__private::codec::Decode,
+			#frame_support::__private::scale_info::TypeInfo,
+		)]
+		#[codec(encode_bound())]
+		#[codec(decode_bound())]
+		#[scale_info(skip_type_params(#type_use_gen))]
+		pub enum #task_ident<#type_decl_bounded_gen> #where_clause {
+			#[doc(hidden)]
+			#[codec(skip)]
+			__Ignore(
+				#frame_support::__private::sp_std::marker::PhantomData<(#type_use_gen,)>,
+				#frame_support::Never,
+			),
+			#( #variants )*
+		}
+
+		impl<#type_impl_gen> #frame_support::traits::Task for #task_ident<#type_use_gen>
+			#where_clause
+		{
+			type Enumeration = #frame_support::traits::tasks::__private::IntoIter<
+				#task_ident<#type_use_gen>
+			>;
+
+			fn iter() -> Self::Enumeration {
+				#[allow(unused_mut)]
+				let mut all_tasks = #frame_support::traits::tasks::__private::Vec::new();
+				#( #iter_extends )*
+				all_tasks.into_iter()
+			}
+
+			fn is_valid(&self) -> bool {
+				match self {
+					#( #is_valid_arms )*
+					#task_ident::__Ignore(_, _) => unreachable!("`__Ignore` can never be constructed"),
+				}
+			}
+
+			fn run(&self) -> Result<(), #frame_support::traits::tasks::__private::DispatchError> {
+				match self {
+					#( #run_arms )*
+					#task_ident::__Ignore(_, _) => unreachable!("`__Ignore` can never be constructed"),
+				}
+			}
+
+			fn weight(&self) -> #frame_support::weights::Weight {
+				match self {
+					#( #weight_arms )*
+					#task_ident::__Ignore(_, _) => unreachable!("`__Ignore` can never be constructed"),
+				}
+			}
+
+			fn task_index(&self) -> u32 {
+				match self {
+					#( #index_arms )*
+					#task_ident::__Ignore(_, _) => unreachable!("`__Ignore` can never be constructed"),
+				}
+			}
+		}
+	)
+}
@@ -0,0 +1,266 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Home of the expansion code for the view functions API
+
+use crate::pallet::{parse::view_functions::ViewFunctionDef, Def};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+
+/// * Generate a struct per view function, implementing
+///   `frame_support::view_functions::ViewFunction`.
+/// * Implement `ViewFunctionIdPrefix` and `DispatchViewFunction` for the pallet.
+/// * Implement `pallet_view_functions_metadata` for the pallet.
+pub fn expand_view_functions(def: &Def) -> TokenStream {
+	let (span, where_clause, view_fns) = match def.view_functions.as_ref() {
+		Some(view_fns) => (
+			view_fns.attr_span,
+			super::merge_where_clauses(&[&def.config.where_clause, &view_fns.where_clause]),
+			view_fns.view_functions.clone(),
+		),
+		None => (Span::call_site(), def.config.where_clause.clone(), Vec::new()),
+	};
+
+	let view_function_structs = view_fns
+		.iter()
+		.map(|view_fn| expand_view_function(def, span, where_clause.as_ref(), view_fn));
+	let prefix_and_dispatch =
+		impl_view_function_prefix_and_dispatch(def, span, where_clause.as_ref(), &view_fns);
+	let metadata = impl_view_functions_metadata(def, span, where_clause.as_ref(), &view_fns);
+
+	quote::quote! {
+		#( #view_function_structs )*
+		#prefix_and_dispatch
+		#metadata
+	}
+}
+
+/// Name of the struct generated for the given view function.
+fn view_function_struct_ident(view_fn: &ViewFunctionDef) -> syn::Ident {
+	format_ident!(
+		"{}",
+		inflector::cases::pascalcase::to_pascal_case(&view_fn.name.to_string()),
+		span = view_fn.name.span(),
+	)
+}
+
+/// Suffix of the view function id: the hash of the function signature.
+fn view_function_id_suffix(view_fn: &ViewFunctionDef) -> [u8; 16] {
+	let mut signature = view_fn.name.to_string();
+	signature.push('(');
+	for (_, ty) in &view_fn.args {
+		signature.push_str(&ty.to_token_stream().to_string());
+		signature.push(',');
+	}
+	signature.push_str(")->");
+	signature.push_str(&view_fn.return_type.to_token_stream().to_string());
+	sp_core_hashing::twox_128(signature.as_bytes())
+}
+
+fn expand_view_function(
+	def: &Def,
+	span: Span,
+	where_clause: Option<&syn::WhereClause>,
+	view_fn: &ViewFunctionDef,
+) -> TokenStream {
+	let frame_support = &def.frame_support;
+	let pallet_ident = &def.pallet_struct.pallet;
+	let type_impl_gen = &def.type_impl_generics(span);
+	let type_decl_bounded_gen = &def.type_decl_bounded_generics(span);
+	let type_use_gen = &def.type_use_generics(span);
+	let capture_docs = if cfg!(feature = "no-metadata-docs") { "never" } else { "always" };
+
+	let view_function_struct_ident = view_function_struct_ident(view_fn);
+	let view_fn_name = &view_fn.name;
+	let (arg_names, arg_types): (Vec<_>, Vec<_>) = view_fn.args.iter().cloned().unzip();
+	let return_type = &view_fn.return_type;
+	let docs = &view_fn.docs;
+	let id_suffix = view_function_id_suffix(view_fn);
+
+	quote::quote! {
+		#( #[doc = #docs] )*
+		#[allow(missing_docs)]
+		#[derive(
+			#frame_support::RuntimeDebugNoBound,
+			#frame_support::CloneNoBound,
+			#frame_support::EqNoBound,
+			#frame_support::PartialEqNoBound,
+			#frame_support::__private::codec::Encode,
+			#frame_support::__private::codec::Decode,
+			#frame_support::__private::scale_info::TypeInfo,
+		)]
+		#[codec(encode_bound())]
+		#[codec(decode_bound())]
+		#[scale_info(skip_type_params(#type_use_gen), capture_docs = #capture_docs)]
+		pub struct #view_function_struct_ident<#type_decl_bounded_gen> #where_clause {
+			#(
+				pub #arg_names: #arg_types,
+			)*
+			#[codec(skip)]
+			_marker: #frame_support::__private::sp_std::marker::PhantomData<(#type_use_gen,)>,
+		}
+
+		impl<#type_impl_gen> #view_function_struct_ident<#type_use_gen> #where_clause {
+			/// Create a new instance of this view function query.
+			pub fn new(#( #arg_names: #arg_types, )*) -> Self {
+				Self {
+					#( #arg_names, )*
+					_marker: ::core::default::Default::default(),
+				}
+			}
+		}
+
+		impl<#type_impl_gen> #frame_support::view_functions::ViewFunctionIdSuffix
+			for #view_function_struct_ident<#type_use_gen>
+			#where_clause
+		{
+			const SUFFIX: [u8; 16] = [ #( #id_suffix ),* ];
+		}
+
+		impl<#type_impl_gen> #frame_support::view_functions::ViewFunction
+			for #view_function_struct_ident<#type_use_gen>
+			#where_clause
+		{
+			type ReturnType = #return_type;
+
+			fn id() -> #frame_support::view_functions::ViewFunctionId {
+				#frame_support::view_functions::ViewFunctionId {
+					prefix: <
+						#pallet_ident<#type_use_gen> as #frame_support::view_functions::ViewFunctionIdPrefix
+					>::prefix(),
+					suffix: <
+						Self as #frame_support::view_functions::ViewFunctionIdSuffix
+					>::SUFFIX,
+				}
+			}
+
+			fn invoke(self) -> Self::ReturnType {
+				let Self { #( #arg_names, )* _marker } = self;
+				#pallet_ident::<#type_use_gen>::#view_fn_name( #( #arg_names, )* )
+			}
+		}
+	}
+}
+
+fn impl_view_function_prefix_and_dispatch(
+	def: &Def,
+	span: Span,
+	where_clause: Option<&syn::WhereClause>,
+	view_fns: &[ViewFunctionDef],
+) -> TokenStream {
+	let frame_support = &def.frame_support;
+	let pallet_ident = &def.pallet_struct.pallet;
+	let type_impl_gen = &def.type_impl_generics(span);
+	let type_use_gen = &def.type_use_generics(span);
+
+	let view_function_struct_idents = view_fns.iter().map(view_function_struct_ident);
+
+	quote::quote! {
+		impl<#type_impl_gen> #frame_support::view_functions::ViewFunctionIdPrefix
+			for #pallet_ident<#type_use_gen>
+			#where_clause
+		{
+			fn prefix() -> [u8; 16] {
+				#frame_support::__private::sp_io::hashing::twox_128(
+					<Self as #frame_support::traits::PalletInfoAccess>::name().as_bytes()
+				)
+			}
+		}
+
+		impl<#type_impl_gen> #frame_support::view_functions::DispatchViewFunction
+			for #pallet_ident<#type_use_gen>
+			#where_clause
+		{
+			#[allow(unused_variables)]
+			fn dispatch_view_function<O: #frame_support::__private::codec::Output>(
+				id: &#frame_support::view_functions::ViewFunctionId,
+				input: &mut &[u8],
+				output: &mut O,
+			) -> Result<(), #frame_support::view_functions::ViewFunctionDispatchError> {
+				#(
+					if id.suffix == <
+						#view_function_struct_idents<#type_use_gen> as
+							#frame_support::view_functions::ViewFunctionIdSuffix
+					>::SUFFIX {
+						return <
+							#view_function_struct_idents<#type_use_gen> as
+								#frame_support::view_functions::ViewFunction
+						>::execute(input, output)
+					}
+				)*
+				Err(#frame_support::view_functions::ViewFunctionDispatchError::NotFound(id.clone()))
+			}
+		}
+	}
+}
+
+fn impl_view_functions_metadata(
+	def: &Def,
+	span: Span,
+	where_clause: Option<&syn::WhereClause>,
+	view_fns: &[ViewFunctionDef],
+) -> TokenStream {
+	let frame_support = &def.frame_support;
+	let pallet_ident = &def.pallet_struct.pallet;
+	let type_impl_gen = &def.type_impl_generics(span);
+	let type_use_gen = &def.type_use_generics(span);
+
+	let view_functions = view_fns.iter().map(|view_fn| {
+		let view_function_struct_ident = view_function_struct_ident(view_fn);
+		let name = view_fn.name.to_string();
+		let return_type = &view_fn.return_type;
+
+		let inputs = view_fn.args.iter().map(|(name, ty)| {
+			let name = name.to_string();
+			quote::quote! {
+				#frame_support::__private::metadata_ir::PalletViewFunctionParamMetadataIR {
+					name: #name,
+					ty: #frame_support::__private::scale_info::meta_type::<#ty>(),
+				}
+			}
+		});
+
+		let no_docs = vec![];
+		let docs = if cfg!(feature = "no-metadata-docs") { &no_docs } else { &view_fn.docs };
+
+		quote::quote! {
+			#frame_support::__private::metadata_ir::PalletViewFunctionMetadataIR {
+				name: #name,
+				id: <
+					#view_function_struct_ident<#type_use_gen> as
+						#frame_support::view_functions::ViewFunction
+				>::id().into(),
+				inputs: #frame_support::__private::sp_std::vec![ #( #inputs ),* ],
+				output: #frame_support::__private::scale_info::meta_type::<#return_type>(),
+				docs: #frame_support::__private::sp_std::vec![ #( #docs ),* ],
+			}
+		}
+	});
+
+	quote::quote! {
+		impl<#type_impl_gen> #pallet_ident<#type_use_gen> #where_clause {
+			#[doc(hidden)]
+			pub fn pallet_view_functions_metadata()
+				-> #frame_support::__private::sp_std::vec::Vec<
+					#frame_support::__private::metadata_ir::PalletViewFunctionMetadataIR
+				>
+			{
+				#frame_support::__private::sp_std::vec![ #( #view_functions ),* ]
+			}
+		}
+	}
+}
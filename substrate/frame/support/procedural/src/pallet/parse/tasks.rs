@@ -0,0 +1,262 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Home of the parsing code for the Tasks API
+
+use super::helper;
+use quote::ToTokens;
+use std::collections::HashMap;
+use syn::spanned::Spanned;
+
+/// List of additional token to be used for parsing.
+mod keyword {
+	syn::custom_keyword!(pallet);
+	syn::custom_keyword!(task_index);
+	syn::custom_keyword!(task_condition);
+	syn::custom_keyword!(task_list);
+	syn::custom_keyword!(task_weight);
+	syn::custom_keyword!(DispatchResult);
+}
+
+/// Definition of the tasks of a pallet, typically `#[pallet::tasks] impl<T: Config> Pallet<T>`.
+pub struct TasksDef {
+	/// The index of the tasks impl item in the pallet module.
+	pub index: usize,
+	/// A set of usage of instance, must be check for consistency with trait.
+	pub instances: Vec<helper::InstanceUsage>,
+	/// The where_clause used.
+	pub where_clause: Option<syn::WhereClause>,
+	/// The span of the pallet::tasks attribute.
+	pub attr_span: proc_macro2::Span,
+	/// Information on each task function.
+	pub tasks: Vec<TaskDef>,
+}
+
+/// Definition of a single task function.
+#[derive(Clone)]
+pub struct TaskDef {
+	/// The name of the task function.
+	pub name: syn::Ident,
+	/// The arguments of the task function: `(name, type)`.
+	pub args: Vec<(syn::Ident, Box<syn::Type>)>,
+	/// The index of the task, given by `#[pallet::task_index($idx)]`.
+	pub index: u32,
+	/// The closure given by `#[pallet::task_condition($closure)]`.
+	pub condition: syn::ExprClosure,
+	/// The iterator expression given by `#[pallet::task_list($expr)]`.
+	pub list: syn::Expr,
+	/// The weight expression given by `#[pallet::task_weight($expr)]`.
+	pub weight: syn::Expr,
+	/// Docs of the task function.
+	pub docs: Vec<syn::Expr>,
+	/// Attributes annotated at the top of the task function.
+	pub attrs: Vec<syn::Attribute>,
+}
+
+/// Attributes for functions in the tasks impl block.
+pub enum TaskAttr {
+	/// Parse for `#[pallet::task_index(expr)]`
+	Index(proc_macro2::Span, u32),
+	/// Parse for `#[pallet::task_condition(closure)]`
+	Condition(proc_macro2::Span, syn::ExprClosure),
+	/// Parse for `#[pallet::task_list(expr)]`
+	List(proc_macro2::Span, syn::Expr),
+	/// Parse for `#[pallet::task_weight(expr)]`
+	Weight(proc_macro2::Span, syn::Expr),
+}
+
+impl TaskAttr {
+	fn span(&self) -> proc_macro2::Span {
+		match self {
+			Self::Index(span, _) |
+			Self::Condition(span, _) |
+			Self::List(span, _) |
+			Self::Weight(span, _) => *span,
+		}
+	}
+}
+
+impl syn::parse::Parse for TaskAttr {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		input.parse::<syn::Token![#]>()?;
+		let content;
+		syn::bracketed!(content in input);
+		content.parse::<keyword::pallet>()?;
+		content.parse::<syn::Token![::]>()?;
+
+		let lookahead = content.lookahead1();
+		if lookahead.peek(keyword::task_index) {
+			let span = content.parse::<keyword::task_index>()?.span();
+			let index_content;
+			syn::parenthesized!(index_content in content);
+			let index = index_content.parse::<syn::LitInt>()?;
+			if !index.suffix().is_empty() {
+				let msg = "Number literal must not have a suffix";
+				return Err(syn::Error::new(index.span(), msg))
+			}
+			Ok(TaskAttr::Index(span, index.base10_parse()?))
+		} else if lookahead.peek(keyword::task_condition) {
+			let span = content.parse::<keyword::task_condition>()?.span();
+			let closure_content;
+			syn::parenthesized!(closure_content in content);
+			Ok(TaskAttr::Condition(
+				span,
+				closure_content.parse::<syn::ExprClosure>().map_err(|e| {
+					let msg = "Invalid pallet::task_condition attribute: expected a closure";
+					let mut err = syn::Error::new(closure_content.span(), msg);
+					err.combine(e);
+					err
+				})?,
+			))
+		} else if lookahead.peek(keyword::task_list) {
+			let span = content.parse::<keyword::task_list>()?.span();
+			let list_content;
+			syn::parenthesized!(list_content in content);
+			Ok(TaskAttr::List(span, list_content.parse::<syn::Expr>()?))
+		} else if lookahead.peek(keyword::task_weight) {
+			let span = content.parse::<keyword::task_weight>()?.span();
+			let weight_content;
+			syn::parenthesized!(weight_content in content);
+			Ok(TaskAttr::Weight(span, weight_content.parse::<syn::Expr>()?))
+		} else {
+			Err(lookahead.error())
+		}
+	}
+}
+
+impl TasksDef {
+	pub fn try_from(
+		attr_span: proc_macro2::Span,
+		index: usize,
+		item: &mut syn::Item,
+	) -> syn::Result<Self> {
+		let item_impl = if let syn::Item::Impl(item) = item {
+			item
+		} else {
+			return Err(syn::Error::new(item.span(), "Invalid pallet::tasks, expected item impl"))
+		};
+
+		let instances = vec![
+			helper::check_impl_gen(&item_impl.generics, item_impl.impl_token.span())?,
+			helper::check_pallet_struct_usage(&item_impl.self_ty)?,
+		];
+
+		if let Some((_, _, for_)) = item_impl.trait_ {
+			let msg = "Invalid pallet::tasks, expected no trait ident as in \
+				`impl<..> Pallet<..> { .. }`";
+			return Err(syn::Error::new(for_.span(), msg))
+		}
+
+		let mut tasks = vec![];
+		let mut indices = HashMap::new();
+		for item in &mut item_impl.items {
+			let syn::ImplItem::Fn(method) = item else {
+				let msg = "Invalid pallet::tasks, only functions are allowed";
+				return Err(syn::Error::new(item.span(), msg))
+			};
+
+			if let Some(syn::FnArg::Receiver(receiver)) = method.sig.inputs.first() {
+				let msg = "Invalid pallet::tasks, task functions must not take `self`";
+				return Err(syn::Error::new(receiver.span(), msg))
+			}
+
+			let returns_dispatch_result = match &method.sig.output {
+				syn::ReturnType::Type(_, type_) =>
+					syn::parse2::<keyword::DispatchResult>(type_.to_token_stream()).is_ok(),
+				syn::ReturnType::Default => false,
+			};
+			if !returns_dispatch_result {
+				let msg = "Invalid pallet::tasks, require return type DispatchResult";
+				return Err(syn::Error::new(method.sig.span(), msg))
+			}
+
+			let (mut task_index, mut condition, mut list, mut weight) = (None, None, None, None);
+			for attr in helper::take_item_pallet_attrs::<TaskAttr>(&mut method.attrs)? {
+				let span = attr.span();
+				let duplicate = match attr {
+					TaskAttr::Index(_, i) => task_index.replace(i).is_some(),
+					TaskAttr::Condition(_, c) => condition.replace(c).is_some(),
+					TaskAttr::List(_, l) => list.replace(l).is_some(),
+					TaskAttr::Weight(_, w) => weight.replace(w).is_some(),
+				};
+				if duplicate {
+					let msg = "Invalid pallet::tasks, duplicated task attribute";
+					return Err(syn::Error::new(span, msg))
+				}
+			}
+
+			let missing = |attr: &str| {
+				let msg = format!(
+					"Invalid pallet::tasks, missing `#[pallet::{}(..)]` attribute on task function",
+					attr
+				);
+				syn::Error::new(method.sig.span(), msg)
+			};
+			let task_index = task_index.ok_or_else(|| missing("task_index"))?;
+			let condition = condition.ok_or_else(|| missing("task_condition"))?;
+			let list = list.ok_or_else(|| missing("task_list"))?;
+			let weight = weight.ok_or_else(|| missing("task_weight"))?;
+
+			if let Some(used_fn) = indices.insert(task_index, method.sig.ident.clone()) {
+				let msg = format!(
+					"Task indices are conflicting: Both functions {} and {} are at index {}",
+					used_fn, method.sig.ident, task_index,
+				);
+				let mut err = syn::Error::new(used_fn.span(), &msg);
+				err.combine(syn::Error::new(method.sig.ident.span(), msg));
+				return Err(err)
+			}
+
+			let mut args = vec![];
+			for arg in method.sig.inputs.iter() {
+				let syn::FnArg::Typed(arg) = arg else {
+					unreachable!("Receivers are rejected above; qed");
+				};
+				let syn::Pat::Ident(pat) = &*arg.pat else {
+					let msg = "Invalid pallet::tasks, argument must be ident";
+					return Err(syn::Error::new(arg.pat.span(), msg))
+				};
+				args.push((pat.ident.clone(), arg.ty.clone()));
+			}
+
+			if condition.inputs.len() != args.len() {
+				let msg = "Invalid pallet::tasks, task_condition closure must have the same \
+					number of arguments as the task function";
+				return Err(syn::Error::new(condition.span(), msg))
+			}
+
+			tasks.push(TaskDef {
+				name: method.sig.ident.clone(),
+				args,
+				index: task_index,
+				condition,
+				list,
+				weight,
+				docs: frame_support_procedural_tools::get_doc_literals(&method.attrs),
+				attrs: helper::get_item_cfg_attrs(&method.attrs),
+			});
+		}
+
+		Ok(Self {
+			index,
+			instances,
+			where_clause: item_impl.generics.where_clause.clone(),
+			attr_span,
+			tasks,
+		})
+	}
+}
@@ -0,0 +1,134 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Home of the parsing code for the view functions API
+
+use super::helper;
+use syn::spanned::Spanned;
+
+/// Definition of the view functions of a pallet, typically
+/// `#[pallet::view_functions] impl<T: Config> Pallet<T>`.
+pub struct ViewFunctionsImplDef {
+	/// The index of the view functions impl item in the pallet module.
+	pub index: usize,
+	/// A set of usage of instance, must be check for consistency with trait.
+	pub instances: Vec<helper::InstanceUsage>,
+	/// The where_clause used.
+	pub where_clause: Option<syn::WhereClause>,
+	/// The span of the pallet::view_functions attribute.
+	pub attr_span: proc_macro2::Span,
+	/// Information on each view function.
+	pub view_functions: Vec<ViewFunctionDef>,
+}
+
+/// Definition of a single view function.
+#[derive(Clone)]
+pub struct ViewFunctionDef {
+	/// The name of the view function.
+	pub name: syn::Ident,
+	/// The arguments of the view function: `(name, type)`.
+	pub args: Vec<(syn::Ident, Box<syn::Type>)>,
+	/// The return type of the view function.
+	pub return_type: syn::Type,
+	/// Docs of the view function.
+	pub docs: Vec<syn::Expr>,
+}
+
+impl ViewFunctionsImplDef {
+	pub fn try_from(
+		attr_span: proc_macro2::Span,
+		index: usize,
+		item: &mut syn::Item,
+	) -> syn::Result<Self> {
+		let item_impl = if let syn::Item::Impl(item) = item {
+			item
+		} else {
+			let msg = "Invalid pallet::view_functions, expected item impl";
+			return Err(syn::Error::new(item.span(), msg))
+		};
+
+		let instances = vec![
+			helper::check_impl_gen(&item_impl.generics, item_impl.impl_token.span())?,
+			helper::check_pallet_struct_usage(&item_impl.self_ty)?,
+		];
+
+		if let Some((_, _, for_)) = item_impl.trait_ {
+			let msg = "Invalid pallet::view_functions, expected no trait ident as in \
+				`impl<..> Pallet<..> { .. }`";
+			return Err(syn::Error::new(for_.span(), msg))
+		}
+
+		let mut view_functions = vec![];
+		for item in &item_impl.items {
+			let syn::ImplItem::Fn(method) = item else {
+				let msg = "Invalid pallet::view_functions, only functions are allowed";
+				return Err(syn::Error::new(item.span(), msg))
+			};
+
+			if !matches!(method.vis, syn::Visibility::Public(_)) {
+				let msg = "Invalid pallet::view_functions, view function must be public: \
+					`pub fn`";
+				return Err(syn::Error::new(method.sig.span(), msg))
+			}
+
+			if let Some(syn::FnArg::Receiver(receiver)) = method.sig.inputs.first() {
+				let msg = "Invalid pallet::view_functions, view functions must not take `self`";
+				return Err(syn::Error::new(receiver.span(), msg))
+			}
+
+			if !method.sig.generics.params.is_empty() {
+				let msg = "Invalid pallet::view_functions, view functions must not be generic";
+				return Err(syn::Error::new(method.sig.generics.span(), msg))
+			}
+
+			let return_type = match &method.sig.output {
+				syn::ReturnType::Type(_, type_) => (**type_).clone(),
+				syn::ReturnType::Default => {
+					let msg = "Invalid pallet::view_functions, view functions must return a value";
+					return Err(syn::Error::new(method.sig.span(), msg))
+				},
+			};
+
+			let mut args = vec![];
+			for arg in method.sig.inputs.iter() {
+				let syn::FnArg::Typed(arg) = arg else {
+					unreachable!("Receivers are rejected above; qed");
+				};
+				let syn::Pat::Ident(pat) = &*arg.pat else {
+					let msg = "Invalid pallet::view_functions, argument must be ident";
+					return Err(syn::Error::new(arg.pat.span(), msg))
+				};
+				args.push((pat.ident.clone(), arg.ty.clone()));
+			}
+
+			view_functions.push(ViewFunctionDef {
+				name: method.sig.ident.clone(),
+				args,
+				return_type,
+				docs: frame_support_procedural_tools::get_doc_literals(&method.attrs),
+			});
+		}
+
+		Ok(Self {
+			index,
+			instances,
+			where_clause: item_impl.generics.where_clause.clone(),
+			attr_span,
+			view_functions,
+		})
+	}
+}
@@ -75,6 +75,7 @@ pub mod storage;
 #[cfg(test)]
 mod tests;
 pub mod traits;
+pub mod view_functions;
 pub mod weights;
 #[doc(hidden)]
 pub mod unsigned {
@@ -946,6 +947,8 @@ pub mod pallet_prelude {
 /// * [`pallet::validate_unsigned`](#validate-unsigned-palletvalidate_unsigned-optional)
 /// * [`pallet::origin`](#origin-palletorigin-optional)
 /// * [`pallet::composite_enum`](#composite-enum-palletcomposite_enum-optional)
+/// * [`pallet::tasks`](#tasks-pallettasks-optional)
+/// * [`pallet::view_functions`](#view-functions-palletview_functions-optional)
 ///
 /// Note that at compile-time, the `#[pallet]` macro will analyze and expand all of these
 /// attributes, ultimately removing their AST nodes before they can be parsed as real
@@ -1681,6 +1684,57 @@ pub mod pallet_prelude {
 /// The inverse is also true: if there are any #[derive] attributes present for the enum, then
 /// the attribute will not automatically derive any of the traits described above.
 ///
+/// # Tasks `#[pallet::tasks]` (optional)
+///
+/// The `#[pallet::tasks]` attribute allows you to declare permissionless units of service
+/// work, e.g. the cleanup of stale storage, that anybody can execute once they are valid:
+///
+/// ```ignore
+/// #[pallet::tasks]
+/// impl<T: Config> Pallet<T> {
+///     #[pallet::task_index(0)]
+///     #[pallet::task_condition(|i| Numbers::<T>::contains_key(i))]
+///     #[pallet::task_list(Numbers::<T>::iter_keys())]
+///     #[pallet::task_weight(T::WeightInfo::add_number_into_total())]
+///     pub fn add_number_into_total(i: u32) -> DispatchResult {
+///         ..
+///     }
+/// }
+/// ```
+///
+/// Each function in the block must define all four `task_*` attributes and return
+/// `DispatchResult`. The macro generates a `Task<T>` enum with a variant per function which
+/// implements [`Task`](`traits::Task`). `construct_runtime` aggregates these enums into a
+/// `RuntimeTask` enum, and valid tasks can be executed through `frame_system::Call::do_task`.
+/// Off-chain workers can use `frame_system::Pallet::submit_valid_tasks` to discover and submit
+/// them.
+///
+/// Also see [`pallet::tasks`](`frame_support::pallet_macros::tasks`)
+///
+/// # View functions `#[pallet::view_functions]` (optional)
+///
+/// The `#[pallet::view_functions]` attribute allows you to declare read-only queries, callable
+/// by clients without a dedicated runtime API:
+///
+/// ```ignore
+/// #[pallet::view_functions]
+/// impl<T: Config> Pallet<T> {
+///     /// Get the value stored under `key`.
+///     pub fn get_value_with_arg(key: u32) -> Option<u32> {
+///         SomeMap::<T>::get(key)
+///     }
+/// }
+/// ```
+///
+/// Each function must be public, take no `self` and return a value. The macro generates a
+/// struct per function implementing [`ViewFunction`](`view_functions::ViewFunction`) and adds
+/// the functions to the pallet metadata. `construct_runtime` generates
+/// `Runtime::execute_view_function`, which dispatches a query to the right pallet and function
+/// by its [`ViewFunctionId`](`view_functions::ViewFunctionId`) and is meant to implement the
+/// [`RuntimeViewFunction`](`view_functions::runtime_api::RuntimeViewFunction`) runtime API.
+///
+/// Also see [`pallet::view_functions`](`frame_support::pallet_macros::view_functions`)
+///
 /// # General notes on instantiable pallets
 ///
 /// An instantiable pallet is one where Config is generic, i.e. `Config<I>`. This allows
@@ -2231,8 +2285,8 @@ pub mod pallet_macros {
 		composite_enum, config, disable_frame_system_supertrait_check, error, event,
 		extra_constants, feeless_if, generate_deposit, generate_store, getter, hooks,
 		import_section, inherent, no_default, no_default_bounds, origin, pallet_section,
-		storage_prefix, storage_version, type_value, unbounded, validate_unsigned, weight,
-		whitelist_storage,
+		storage_prefix, storage_version, task_condition, task_index, task_list, task_weight, tasks,
+		type_value, unbounded, validate_unsigned, view_functions, weight, whitelist_storage,
 	};
 
 	/// Allows a pallet to declare a set of functions as a *dispatchable extrinsic*. In
@@ -0,0 +1,143 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Traits and types used by the `#[pallet::view_functions]` attribute.
+//!
+//! A view function is a read-only query declared by a pallet. Each view function is identified
+//! by a [`ViewFunctionId`]: the `prefix` is the hash of the pallet name and the `suffix` is the
+//! hash of the function signature. The runtime dispatches queries through
+//! [`DispatchViewFunction`], exposed to clients by the [`runtime_api::RuntimeViewFunction`]
+//! runtime API.
+
+use codec::{Decode, DecodeAll, Encode, Output};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
+/// The unique identifier of a view function.
+#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, PartialEq, Eq)]
+pub struct ViewFunctionId {
+	/// The part of the id for dispatching view functions from the top level of the runtime.
+	///
+	/// Specifies which view function grouping this view function belongs to. This could be a
+	/// group of view functions associated with a pallet, or a pallet agnostic group of view
+	/// functions.
+	pub prefix: [u8; 16],
+	/// The part of the id for dispatching to a view function within a group.
+	pub suffix: [u8; 16],
+}
+
+impl From<ViewFunctionId> for [u8; 32] {
+	fn from(value: ViewFunctionId) -> Self {
+		let mut output = [0u8; 32];
+		output[..16].copy_from_slice(&value.prefix);
+		output[16..].copy_from_slice(&value.suffix);
+		output
+	}
+}
+
+/// Error type for view function dispatching.
+#[derive(Encode, Decode, RuntimeDebug, TypeInfo, PartialEq, Eq)]
+pub enum ViewFunctionDispatchError {
+	/// View functions are not implemented for this runtime.
+	NotImplemented,
+	/// A view function with the given `ViewFunctionId` was not found.
+	NotFound(ViewFunctionId),
+	/// Failed to decode the view function input.
+	Codec,
+}
+
+impl From<codec::Error> for ViewFunctionDispatchError {
+	fn from(_: codec::Error) -> Self {
+		ViewFunctionDispatchError::Codec
+	}
+}
+
+/// Implemented by both pallets and the runtime. The runtime is dispatching by prefix using the
+/// pallet implementation of `ViewFunctionIdPrefix` then the pallet is dispatching by suffix using
+/// the methods implementation of `ViewFunctionIdSuffix`.
+pub trait DispatchViewFunction {
+	/// Dispatch the view function with the given `id`, decoding its arguments from `input` and
+	/// encoding its result into `output`.
+	fn dispatch_view_function<O: Output>(
+		id: &ViewFunctionId,
+		input: &mut &[u8],
+		output: &mut O,
+	) -> Result<(), ViewFunctionDispatchError>;
+}
+
+impl DispatchViewFunction for () {
+	fn dispatch_view_function<O: Output>(
+		_id: &ViewFunctionId,
+		_input: &mut &[u8],
+		_output: &mut O,
+	) -> Result<(), ViewFunctionDispatchError> {
+		Err(ViewFunctionDispatchError::NotImplemented)
+	}
+}
+
+/// Automatically implemented for each pallet by the macro [`pallet`](crate::pallet).
+pub trait ViewFunctionIdPrefix {
+	/// The prefix of the ids of the view functions of this pallet.
+	fn prefix() -> [u8; 16];
+}
+
+/// Automatically implemented for each view function by the macro [`pallet`](crate::pallet).
+pub trait ViewFunctionIdSuffix {
+	/// The suffix of the id of this view function.
+	const SUFFIX: [u8; 16];
+}
+
+/// Automatically implemented for each view function by the macro [`pallet`](crate::pallet).
+///
+/// The implementing type holds the arguments of the view function.
+pub trait ViewFunction: DecodeAll {
+	/// The type returned by the view function.
+	type ReturnType: Encode;
+
+	/// The id of the view function.
+	fn id() -> ViewFunctionId;
+
+	/// Execute the view function with the arguments held by `self`.
+	fn invoke(self) -> Self::ReturnType;
+
+	/// Decode the view function from `input`, invoke it and encode the result into `output`.
+	fn execute<O: Output>(
+		input: &mut &[u8],
+		output: &mut O,
+	) -> Result<(), ViewFunctionDispatchError> {
+		let view_function = Self::decode_all(input)?;
+		let result = view_function.invoke();
+		Encode::encode_to(&result, output);
+		Ok(())
+	}
+}
+
+/// Runtime API for executing view functions.
+pub mod runtime_api {
+	use super::*;
+
+	sp_api::decl_runtime_apis! {
+		/// Runtime API for executing view functions.
+		pub trait RuntimeViewFunction {
+			/// Execute a view function query.
+			fn execute_view_function(
+				query_id: ViewFunctionId,
+				input: sp_std::vec::Vec<u8>,
+			) -> Result<sp_std::vec::Vec<u8>, ViewFunctionDispatchError>;
+		}
+	}
+}
@@ -96,7 +96,8 @@ use frame_support::{
 	storage::{self, StorageStreamIter},
 	traits::{
 		ConstU32, Contains, EnsureOrigin, EnsureOriginWithArg, Get, HandleLifetime,
-		OnKilledAccount, OnNewAccount, OriginTrait, PalletInfo, SortedMembers, StoredMap, TypedGet,
+		OnKilledAccount, OnNewAccount, OriginTrait, PalletInfo, SortedMembers, StoredMap, Task,
+		TypedGet,
 	},
 	Parameter,
 };
@@ -240,6 +241,8 @@ pub mod pallet {
 			#[inject_runtime_type]
 			type RuntimeCall = ();
 			#[inject_runtime_type]
+			type RuntimeTask = ();
+			#[inject_runtime_type]
 			type PalletInfo = ();
 			type BaseCallFilter = frame_support::traits::Everything;
 			type BlockHashCount = frame_support::traits::ConstU64<10>;
@@ -321,6 +324,10 @@ pub mod pallet {
 			#[inject_runtime_type]
 			type RuntimeCall = ();
 
+			/// The aggregated task type, injected by `construct_runtime!`.
+			#[inject_runtime_type]
+			type RuntimeTask = ();
+
 			/// Converts a module to the index of the module, injected by `construct_runtime!`.
 			#[inject_runtime_type]
 			type PalletInfo = ();
@@ -400,6 +407,10 @@ pub mod pallet {
 			+ Debug
 			+ From<Call<Self>>;
 
+		/// The aggregated `RuntimeTask` type.
+		#[pallet::no_default_bounds]
+		type RuntimeTask: Task;
+
 		/// This stores the number of previous transactions associated with a sender account.
 		type Nonce: Parameter
 			+ Member
@@ -628,6 +639,39 @@ pub mod pallet {
 			Self::deposit_event(Event::Remarked { sender: who, hash });
 			Ok(().into())
 		}
+
+		/// Execute a task that is currently valid.
+		///
+		/// Can be dispatched by any signed origin, or submitted as an unsigned transaction, for
+		/// example by an off-chain worker through [`Pallet::submit_valid_tasks`].
+		#[pallet::call_index(8)]
+		#[pallet::weight(task.weight())]
+		pub fn do_task(origin: OriginFor<T>, task: T::RuntimeTask) -> DispatchResultWithPostInfo {
+			ensure_signed(origin.clone()).map(|_| ()).or_else(|_| ensure_none(origin))?;
+
+			ensure!(task.is_valid(), Error::<T>::InvalidTask);
+			task.run()?;
+			Self::deposit_event(Event::TaskCompleted { task });
+
+			Ok(().into())
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			let Call::do_task { task } = call else { return InvalidTransaction::Call.into() };
+			if !task.is_valid() {
+				return InvalidTransaction::Call.into()
+			}
+
+			ValidTransaction::with_tag_prefix("SystemTask")
+				.and_provides(task.encode())
+				.propagate(true)
+				.build()
+		}
 	}
 
 	/// Event for the System pallet.
@@ -645,6 +689,8 @@ pub mod pallet {
 		KilledAccount { account: T::AccountId },
 		/// On on-chain remark happened.
 		Remarked { sender: T::AccountId, hash: T::Hash },
+		/// A task was executed successfully.
+		TaskCompleted { task: T::RuntimeTask },
 	}
 
 	/// Error for the System pallet
@@ -666,6 +712,8 @@ pub mod pallet {
 		NonZeroRefCount,
 		/// The origin filter prevent the call to be dispatched.
 		CallFiltered,
+		/// The task is not valid and cannot be executed.
+		InvalidTask,
 	}
 
 	/// Exposed trait-generic origin type.
@@ -1922,6 +1970,29 @@ pub fn split_inner<T, R, S>(
 	}
 }
 
+impl<T: Config> Pallet<T> {
+	/// Submit every currently valid task of the runtime as an unsigned [`Call::do_task`].
+	///
+	/// Meant to be called from the off-chain worker of a pallet that declares tasks. Returns the
+	/// number of tasks that were successfully handed to the transaction pool.
+	pub fn submit_valid_tasks() -> u32
+	where
+		T: offchain::SendTransactionTypes<Call<T>>,
+	{
+		let mut submitted = 0;
+		for task in T::RuntimeTask::iter().filter(|task| task.is_valid()) {
+			let call = Call::<T>::do_task { task };
+			match offchain::SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(
+				call.into(),
+			) {
+				Ok(()) => submitted += 1,
+				Err(()) => log::debug!(target: LOG_TARGET, "Failed to submit task transaction"),
+			}
+		}
+		submitted
+	}
+}
+
 pub struct ChainContext<T>(PhantomData<T>);
 impl<T> Default for ChainContext<T> {
 	fn default() -> Self {
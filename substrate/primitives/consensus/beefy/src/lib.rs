@@ -47,7 +47,7 @@ use codec::{Codec, Decode, Encode};
 use scale_info::TypeInfo;
 use sp_application_crypto::RuntimeAppPublic;
 use sp_core::H256;
-use sp_runtime::traits::{Hash, Keccak256, NumberFor};
+use sp_runtime::traits::{Block as BlockT, Hash, Header as HeaderT, Keccak256, NumberFor};
 use sp_std::prelude::*;
 
 /// Key type for BEEFY module.
@@ -301,6 +301,59 @@ impl<Number, Id, Signature> EquivocationProof<Number, Id, Signature> {
 	}
 }
 
+/// Proof of an authority voting on a block that is not part of the canonical chain.
+///
+/// The vote targets a block number of the canonical chain, but commits to a payload (MMR root)
+/// that differs from the one found in the canonical header at that height. The canonicity of
+/// `header` is not part of the proof itself and must be checked by the verifier.
+#[derive(Clone, Debug, Decode, Encode, PartialEq, TypeInfo)]
+pub struct ForkVotingProof<Header: HeaderT, Id, Signature> {
+	/// The vote on the non-canonical payload.
+	pub vote: VoteMessage<Header::Number, Id, Signature>,
+	/// The canonical header at the block number targeted by the vote.
+	pub header: Header,
+}
+
+impl<Header: HeaderT, Id, Signature> ForkVotingProof<Header, Id, Signature> {
+	/// Returns the authority id of the misbehaving voter.
+	pub fn offender_id(&self) -> &Id {
+		&self.vote.id
+	}
+	/// Returns the round number at which the misbehavior occurred.
+	pub fn round_number(&self) -> &Header::Number {
+		&self.vote.commitment.block_number
+	}
+	/// Returns the set id at which the misbehavior occurred.
+	pub fn set_id(&self) -> ValidatorSetId {
+		self.vote.commitment.validator_set_id
+	}
+}
+
+/// Proof of an authority voting on a block that has not been produced yet.
+///
+/// Whether the targeted block is in the future is not part of the proof itself and must be
+/// checked by the verifier against its view of the chain.
+#[derive(Clone, Debug, Decode, Encode, PartialEq, TypeInfo)]
+pub struct FutureBlockVotingProof<Number, Id, Signature> {
+	/// The vote on the future block.
+	pub vote: VoteMessage<Number, Id, Signature>,
+}
+
+impl<Number, Id, Signature> FutureBlockVotingProof<Number, Id, Signature> {
+	/// Returns the authority id of the misbehaving voter.
+	pub fn offender_id(&self) -> &Id {
+		&self.vote.id
+	}
+	/// Returns the round number at which the misbehavior occurred.
+	pub fn round_number(&self) -> &Number {
+		&self.vote.commitment.block_number
+	}
+	/// Returns the set id at which the misbehavior occurred.
+	pub fn set_id(&self) -> ValidatorSetId {
+		self.vote.commitment.validator_set_id
+	}
+}
+
 /// Check a commitment signature by encoding the commitment and
 /// verifying the provided signature using the expected authority id.
 pub fn check_commitment_signature<Number, Id, MsgHash>(
@@ -352,6 +405,50 @@ where
 	return valid_first && valid_second
 }
 
+/// Verifies the fork voting proof by making sure that the vote targets the block number of
+/// `header`, that it commits to an MMR root different from the one in the `header` digest and
+/// that its signature is valid.
+///
+/// Note: this doesn't check that `header` is part of the canonical chain.
+pub fn check_fork_voting_proof<Header, Id, MsgHash>(
+	report: &ForkVotingProof<Header, Id, <Id as RuntimeAppPublic>::Signature>,
+) -> bool
+where
+	Header: HeaderT,
+	Id: BeefyAuthorityId<MsgHash> + PartialEq,
+	MsgHash: Hash,
+{
+	let vote = &report.vote;
+	if vote.commitment.block_number != *report.header.number() {
+		return false
+	}
+
+	// both the vote and the header must commit to an MMR root, and these must differ
+	let voted_root =
+		vote.commitment.payload.get_decoded::<MmrRootHash>(&known_payloads::MMR_ROOT_ID);
+	match (voted_root, mmr::find_mmr_root_digest_in_header(&report.header)) {
+		(Some(voted_root), Some(canonical_root)) if voted_root != canonical_root => {},
+		_ => return false,
+	}
+
+	check_commitment_signature(&vote.commitment, &vote.id, &vote.signature)
+}
+
+/// Verifies the future block voting proof by making sure that the vote signature is valid.
+///
+/// Note: this doesn't check that the voted block is in the future.
+pub fn check_future_block_voting_proof<Number, Id, MsgHash>(
+	report: &FutureBlockVotingProof<Number, Id, <Id as RuntimeAppPublic>::Signature>,
+) -> bool
+where
+	Id: BeefyAuthorityId<MsgHash> + PartialEq,
+	Number: Clone + Encode + PartialEq,
+	MsgHash: Hash,
+{
+	let vote = &report.vote;
+	check_commitment_signature(&vote.commitment, &vote.id, &vote.signature)
+}
+
 /// New BEEFY validator set notification hook.
 pub trait OnNewValidatorSet<AuthorityId> {
 	/// Function called by the pallet when BEEFY validator set changes.
@@ -390,7 +487,7 @@ impl OpaqueKeyOwnershipProof {
 
 sp_api::decl_runtime_apis! {
 	/// API necessary for BEEFY voters.
-	#[api_version(3)]
+	#[api_version(4)]
 	pub trait BeefyApi<AuthorityId> where
 		AuthorityId : Codec + RuntimeAppPublic,
 	{
@@ -414,6 +511,34 @@ sp_api::decl_runtime_apis! {
 			key_owner_proof: OpaqueKeyOwnershipProof,
 		) -> Option<()>;
 
+		/// Submits an unsigned extrinsic to report a vote on a block that is not
+		/// part of the canonical chain. The caller must provide the fork voting
+		/// proof and a key ownership proof (should be obtained using
+		/// `generate_key_ownership_proof`). The extrinsic will be unsigned and
+		/// should only be accepted for local authorship (not to be broadcast to
+		/// the network). This method returns `None` when creation of the
+		/// extrinsic fails. Only useful in an offchain context.
+		#[api_version(4)]
+		fn submit_report_fork_voting_unsigned_extrinsic(
+			fork_voting_proof:
+				ForkVotingProof<<Block as BlockT>::Header, AuthorityId, <AuthorityId as RuntimeAppPublic>::Signature>,
+			key_owner_proof: OpaqueKeyOwnershipProof,
+		) -> Option<()>;
+
+		/// Submits an unsigned extrinsic to report a vote on a block that has
+		/// not been produced yet. The caller must provide the future block
+		/// voting proof and a key ownership proof (should be obtained using
+		/// `generate_key_ownership_proof`). The extrinsic will be unsigned and
+		/// should only be accepted for local authorship (not to be broadcast to
+		/// the network). This method returns `None` when creation of the
+		/// extrinsic fails. Only useful in an offchain context.
+		#[api_version(4)]
+		fn submit_report_future_block_voting_unsigned_extrinsic(
+			future_block_voting_proof:
+				FutureBlockVotingProof<NumberFor<Block>, AuthorityId, <AuthorityId as RuntimeAppPublic>::Signature>,
+			key_owner_proof: OpaqueKeyOwnershipProof,
+		) -> Option<()>;
+
 		/// Generates a proof of key ownership for the given authority in the
 		/// given set. An example usage of this module is coupled with the
 		/// session historical module to prove that a given authority key is
@@ -453,6 +578,78 @@ mod tests {
 		assert_eq!(validators.validators(), &vec![alice.public()]);
 	}
 
+	#[test]
+	fn fork_and_future_block_voting_proofs_are_checked() {
+		use sp_runtime::{testing::Header, Digest, DigestItem};
+
+		let mmr_root_payload = |byte: u8| {
+			Payload::from_single_entry(
+				known_payloads::MMR_ROOT_ID,
+				MmrRootHash::repeat_byte(byte).encode(),
+			)
+		};
+		let header_with_root = |number: u64, byte: u8| {
+			let log =
+				ConsensusLog::<ecdsa_crypto::AuthorityId>::MmrRoot(MmrRootHash::repeat_byte(byte));
+			let digest =
+				Digest { logs: vec![DigestItem::Consensus(BEEFY_ENGINE_ID, log.encode())] };
+			Header::new(number, Default::default(), Default::default(), Default::default(), digest)
+		};
+		let check_fork = |proof: &ForkVotingProof<Header, ecdsa_crypto::Public, _>| {
+			check_fork_voting_proof::<_, _, Keccak256>(proof)
+		};
+
+		// vote on a payload different from the canonical one is a valid proof
+		let proof = generate_fork_voting_proof(
+			(mmr_root_payload(1), 0, &Keyring::Alice),
+			header_with_root(42, 2),
+		);
+		assert!(check_fork(&proof));
+
+		// vote on the canonical payload is not
+		let proof = generate_fork_voting_proof(
+			(mmr_root_payload(2), 0, &Keyring::Alice),
+			header_with_root(42, 2),
+		);
+		assert!(!check_fork(&proof));
+
+		// vote on a different block number than the header is not
+		let mut proof = generate_fork_voting_proof(
+			(mmr_root_payload(1), 0, &Keyring::Alice),
+			header_with_root(42, 2),
+		);
+		proof.header = header_with_root(43, 2);
+		assert!(!check_fork(&proof));
+
+		// header without MMR root digest is not
+		let proof = generate_fork_voting_proof(
+			(mmr_root_payload(1), 0, &Keyring::Alice),
+			Header::new(
+				42,
+				Default::default(),
+				Default::default(),
+				Default::default(),
+				Default::default(),
+			),
+		);
+		assert!(!check_fork(&proof));
+
+		// bad signature is not
+		let mut proof = generate_fork_voting_proof(
+			(mmr_root_payload(1), 0, &Keyring::Alice),
+			header_with_root(42, 2),
+		);
+		proof.vote.id = Keyring::Bob.public();
+		assert!(!check_fork(&proof));
+
+		// future block voting proofs only check the signature
+		let mut proof =
+			generate_future_block_voting_proof((100, mmr_root_payload(1), 0, &Keyring::Alice));
+		assert!(check_future_block_voting_proof::<_, _, Keccak256>(&proof));
+		proof.vote.commitment.block_number = 101;
+		assert!(!check_future_block_voting_proof::<_, _, Keccak256>(&proof));
+	}
+
 	#[test]
 	fn ecdsa_beefy_verify_works() {
 		let msg = &b"test-message"[..];
@@ -135,6 +135,13 @@ pub type BeefyNextAuthoritySet<MerkleRoot> = BeefyAuthoritySet<MerkleRoot>;
 
 /// Extract the MMR root hash from a digest in the given header, if it exists.
 pub fn find_mmr_root_digest<B: Block>(header: &B::Header) -> Option<MmrRootHash> {
+	find_mmr_root_digest_in_header(header)
+}
+
+/// Extract the MMR root hash from a digest in the given header, if it exists.
+///
+/// Same as [`find_mmr_root_digest`], but generic over the header type only.
+pub fn find_mmr_root_digest_in_header<H: Header>(header: &H) -> Option<MmrRootHash> {
 	let id = OpaqueDigestItemId::Consensus(&BEEFY_ENGINE_ID);
 
 	let filter = |log: ConsensusLog<AuthorityId>| match log {
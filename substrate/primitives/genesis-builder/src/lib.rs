@@ -27,12 +27,24 @@
 //! - deserialize the `GenesisConfig` from given json blob and put `GenesisConfig` into the state
 //!   storage. Allows to build customized configuration.
 //!
+//! - list the named genesis config presets (e.g. `development`, `local_testnet`) built into the
+//!   runtime and return the JSON patch for the given preset.
+//!
 //! Providing externalities with empty storage and putting `GenesisConfig` into storage allows to
 //! catch and build the raw storage of `GenesisConfig` which is the foundation for genesis block.
 
 /// The result type alias, used in build methods. `Err` contains formatted error message.
 pub type Result = core::result::Result<(), sp_runtime::RuntimeString>;
 
+/// The type representing the identifier of a genesis config preset.
+pub type PresetId = sp_runtime::RuntimeString;
+
+/// The default identifier of the preset used for development chains.
+pub const DEV_RUNTIME_PRESET: &str = "development";
+
+/// The default identifier of the preset used for local testnets.
+pub const LOCAL_TESTNET_RUNTIME_PRESET: &str = "local_testnet";
+
 sp_api::decl_runtime_apis! {
 	/// API to interact with GenesisConfig for the runtime
 	pub trait GenesisBuilder {
@@ -50,5 +62,20 @@ sp_api::decl_runtime_apis! {
 		///
 		/// Please note that provided json blob must contain all `GenesisConfig` fields, no defaults will be used.
 		fn build_config(json: sp_std::vec::Vec<u8>) -> Result;
+
+		/// Returns a JSON blob representation of the built-in `GenesisConfig` identified by `id`.
+		///
+		/// If `id` is `None` the function returns the JSON blob of the default `GenesisConfig`
+		/// (the same as [`Self::create_default_config`]). Otherwise it returns the JSON patch of the
+		/// named preset, or `None` if the runtime has no such preset. The patch is meant to be
+		/// applied on top of the default `GenesisConfig`.
+		#[api_version(2)]
+		fn get_preset(id: Option<PresetId>) -> Option<sp_std::vec::Vec<u8>>;
+
+		/// Returns the identifiers of all the built-in `GenesisConfig` presets.
+		///
+		/// The returned identifiers can be passed to [`Self::get_preset`].
+		#[api_version(2)]
+		fn preset_names() -> sp_std::vec::Vec<PresetId>;
 	}
 }
@@ -30,6 +30,11 @@ pub use types::*;
 
 mod v14;
 mod v15;
+mod view_functions;
+pub use view_functions::{
+	PalletViewFunctionsMetadata, ViewFunctionMetadata, ViewFunctionParamMetadata,
+	VIEW_FUNCTIONS_CUSTOM_KEY,
+};
 
 /// Metadata V14.
 const V14: u32 = 14;
@@ -115,4 +120,61 @@ mod test {
 
 		assert!(matches!(metadata.1, RuntimeMetadata::V15(_)));
 	}
+
+	#[test]
+	fn view_functions_in_custom_metadata_v15() {
+		use codec::Decode;
+
+		let mut ir = ir_metadata();
+		ir.pallets.push(PalletMetadataIR {
+			name: "Example",
+			storage: None,
+			calls: None,
+			event: None,
+			constants: vec![],
+			error: None,
+			view_functions: vec![PalletViewFunctionMetadataIR {
+				name: "get_value",
+				id: [1; 32],
+				inputs: vec![PalletViewFunctionParamMetadataIR {
+					name: "key",
+					ty: meta_type::<u32>(),
+				}],
+				output: meta_type::<Option<u64>>(),
+				docs: vec!["Get a value."],
+			}],
+			index: 0,
+			docs: vec![],
+		});
+
+		let metadata = into_version(ir, V15).expect("Should return prefixed metadata");
+		let RuntimeMetadata::V15(metadata) = metadata.1 else { panic!("Expected V15 metadata") };
+
+		let custom = metadata
+			.custom
+			.map
+			.get(VIEW_FUNCTIONS_CUSTOM_KEY)
+			.expect("View functions are in the custom metadata");
+		let pallets = Vec::<PalletViewFunctionsMetadata>::decode(&mut &custom.value[..]).unwrap();
+
+		assert_eq!(pallets.len(), 1);
+		assert_eq!(pallets[0].pallet, "Example");
+		let function = &pallets[0].functions[0];
+		assert_eq!(function.name, "get_value");
+		assert_eq!(function.id, [1; 32]);
+		assert_eq!(function.inputs[0].name, "key");
+		assert_eq!(function.docs, vec!["Get a value.".to_string()]);
+
+		let input_ty = metadata.types.resolve(function.inputs[0].ty).unwrap();
+		assert!(matches!(input_ty.type_def, scale_info::TypeDef::Primitive(_)));
+		assert!(metadata.types.resolve(function.output).is_some());
+	}
+
+	#[test]
+	fn no_view_functions_leaves_custom_metadata_empty() {
+		let metadata = into_version(ir_metadata(), V15).expect("Should return prefixed metadata");
+		let RuntimeMetadata::V15(metadata) = metadata.1 else { panic!("Expected V15 metadata") };
+
+		assert!(metadata.custom.map.is_empty());
+	}
 }
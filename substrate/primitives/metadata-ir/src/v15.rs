@@ -25,23 +25,38 @@ use super::types::{
 };
 
 use frame_metadata::v15::{
-	CustomMetadata, ExtrinsicMetadata, OuterEnums, PalletMetadata, RuntimeApiMetadata,
-	RuntimeApiMethodMetadata, RuntimeApiMethodParamMetadata, RuntimeMetadataV15,
-	SignedExtensionMetadata,
+	ExtrinsicMetadata, OuterEnums, PalletMetadata, RuntimeApiMetadata, RuntimeApiMethodMetadata,
+	RuntimeApiMethodParamMetadata, RuntimeMetadataV15, SignedExtensionMetadata,
 };
+use scale_info::{IntoPortable, Registry};
 
 impl From<MetadataIR> for RuntimeMetadataV15 {
 	fn from(ir: MetadataIR) -> Self {
-		RuntimeMetadataV15::new(
-			ir.pallets.into_iter().map(Into::into).collect(),
-			ir.extrinsic.into(),
-			ir.ty,
-			ir.apis.into_iter().map(Into::into).collect(),
-			ir.outer_enums.into(),
-			// Substrate does not collect yet the custom metadata fields.
-			// This allows us to extend the V15 easily.
-			CustomMetadata { map: Default::default() },
-		)
+		// Types are registered in the same order as `RuntimeMetadataV15::new`, the view functions
+		// are registered last since they are carried by the custom section of the metadata.
+		let view_functions = ir
+			.pallets
+			.iter()
+			.map(|pallet| (pallet.name, pallet.view_functions.clone()))
+			.collect();
+
+		let mut registry = Registry::new();
+		let pallets = registry.map_into_portable(ir.pallets.into_iter().map(PalletMetadata::from));
+		let extrinsic = ExtrinsicMetadata::from(ir.extrinsic).into_portable(&mut registry);
+		let ty = registry.register_type(&ir.ty);
+		let apis = registry.map_into_portable(ir.apis.into_iter().map(RuntimeApiMetadata::from));
+		let outer_enums = OuterEnums::from(ir.outer_enums).into_portable(&mut registry);
+		let custom = crate::view_functions::into_custom_metadata(view_functions, &mut registry);
+
+		RuntimeMetadataV15 {
+			types: registry.into(),
+			pallets,
+			extrinsic,
+			ty,
+			apis,
+			outer_enums,
+			custom,
+		}
 	}
 }
 
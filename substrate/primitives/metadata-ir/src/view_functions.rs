@@ -0,0 +1,120 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! View functions section of the metadata.
+//!
+//! Metadata V15 has no dedicated field for pallet view functions. They are exposed through the
+//! custom section of the metadata, under the [`VIEW_FUNCTIONS_CUSTOM_KEY`] key, as a SCALE
+//! encoded `Vec<PalletViewFunctionsMetadata>`. The types referenced by the view functions are
+//! registered in the type registry of the metadata.
+
+use crate::PalletViewFunctionMetadataIR;
+
+use codec::{Decode, Encode};
+use frame_metadata::v15::{CustomMetadata, CustomValueMetadata};
+use scale_info::{
+	form::PortableForm,
+	meta_type,
+	prelude::{collections::BTreeMap, string::String, vec::Vec},
+	IntoPortable, Registry, TypeInfo,
+};
+
+/// The key of the view functions in the custom section of the metadata.
+pub const VIEW_FUNCTIONS_CUSTOM_KEY: &str = "view_functions";
+
+/// The view functions of a pallet.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, Debug, TypeInfo)]
+pub struct PalletViewFunctionsMetadata {
+	/// Pallet name.
+	pub pallet: String,
+	/// View functions of the pallet.
+	pub functions: Vec<ViewFunctionMetadata>,
+}
+
+/// Metadata of a view function.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, Debug, TypeInfo)]
+pub struct ViewFunctionMetadata {
+	/// Function name.
+	pub name: String,
+	/// Function id, used to query the function through the runtime API.
+	pub id: [u8; 32],
+	/// Function parameters.
+	pub inputs: Vec<ViewFunctionParamMetadata>,
+	/// Id of the output type in the type registry of the metadata.
+	#[codec(compact)]
+	pub output: u32,
+	/// Function documentation.
+	pub docs: Vec<String>,
+}
+
+/// Metadata of a view function parameter.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, Debug, TypeInfo)]
+pub struct ViewFunctionParamMetadata {
+	/// Parameter name.
+	pub name: String,
+	/// Id of the parameter type in the type registry of the metadata.
+	#[codec(compact)]
+	pub ty: u32,
+}
+
+impl ViewFunctionMetadata {
+	fn new(ir: PalletViewFunctionMetadataIR, registry: &mut Registry) -> Self {
+		let ir = ir.into_portable(registry);
+		ViewFunctionMetadata {
+			name: ir.name,
+			id: ir.id,
+			inputs: ir
+				.inputs
+				.into_iter()
+				.map(|input| ViewFunctionParamMetadata { name: input.name, ty: input.ty.id })
+				.collect(),
+			output: ir.output.id,
+			docs: ir.docs,
+		}
+	}
+}
+
+/// Build the custom section of the metadata holding the view functions of the given pallets.
+///
+/// The section is left empty when no pallet declares view functions.
+pub(crate) fn into_custom_metadata(
+	pallets: Vec<(&'static str, Vec<PalletViewFunctionMetadataIR>)>,
+	registry: &mut Registry,
+) -> CustomMetadata<PortableForm> {
+	let pallets = pallets
+		.into_iter()
+		.filter(|(_, functions)| !functions.is_empty())
+		.map(|(pallet, functions)| PalletViewFunctionsMetadata {
+			pallet: pallet.into(),
+			functions: functions
+				.into_iter()
+				.map(|function| ViewFunctionMetadata::new(function, registry))
+				.collect(),
+		})
+		.collect::<Vec<_>>();
+
+	let mut map = BTreeMap::new();
+	if !pallets.is_empty() {
+		let ty = registry.register_type(&meta_type::<Vec<PalletViewFunctionsMetadata>>());
+		map.insert(
+			VIEW_FUNCTIONS_CUSTOM_KEY.into(),
+			CustomValueMetadata { ty, value: pallets.encode() },
+		);
+	}
+
+	CustomMetadata { map }
+}
@@ -300,6 +300,19 @@ impl WasmValuesSet {
 	pub fn empty() -> Self {
 		WasmValuesSet(Vec::with_capacity(0))
 	}
+
+	/// Whether no values were provided
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	/// The value provided for the field with the given name, if any
+	pub fn get(&self, name: &str) -> Option<&WasmValue> {
+		self.0
+			.iter()
+			.find(|(field, _)| field.0 == name.as_bytes())
+			.and_then(|(_, value)| value.as_ref())
+	}
 }
 
 impl tracing_core::field::Visit for WasmValuesSet {
@@ -637,14 +650,34 @@ mod std_features {
 			let params = a.fields;
 			let metadata: &tracing_core::metadata::Metadata<'static> = (&a.metadata).into();
 
-			tracing::span::Span::child_of(
+			let values = (!params.is_empty()).then(|| params.clone());
+			let previous = NEW_SPAN_VALUES.with(|current| current.replace(values));
+			let span = tracing::span::Span::child_of(
 				a.parent_id.map(tracing_core::span::Id::from_u64),
 				metadata,
 				&tracing::valueset! { metadata.fields(), target, name, file, line, module_path, ?params },
-			)
+			);
+			NEW_SPAN_VALUES.with(|current| *current.borrow_mut() = previous);
+			span
 		}
 	}
 
+	thread_local! {
+		/// Values of the wasm-originated span that is being created on this thread.
+		static NEW_SPAN_VALUES: std::cell::RefCell<Option<crate::WasmValuesSet>> =
+			std::cell::RefCell::new(None);
+	}
+
+	/// Returns the values of the wasm-originated span that is being created on this thread.
+	///
+	/// The values are only available while the subscriber creates a span converted from
+	/// [`crate::WasmEntryAttributes`], e.g. in `Layer::new_span`. This allows layers to read them
+	/// as a typed [`crate::WasmValuesSet`] instead of through the debug representation of the
+	/// `params` field, whatever subscriber they are used with.
+	pub fn new_wasm_span_values() -> Option<crate::WasmValuesSet> {
+		NEW_SPAN_VALUES.with(|current| current.borrow().clone())
+	}
+
 	impl crate::WasmEntryAttributes {
 		/// convert the given Attributes to an event and emit it using `tracing_core`.
 		pub fn emit(self: crate::WasmEntryAttributes) {
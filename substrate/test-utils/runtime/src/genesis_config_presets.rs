@@ -0,0 +1,72 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Genesis config presets of the substrate-test-runtime.
+
+use super::{currency, TEST_RUNTIME_BABE_EPOCH_CONFIGURATION};
+use serde_json::{json, Value};
+use sp_genesis_builder::{PresetId, DEV_RUNTIME_PRESET};
+use sp_std::{vec, vec::Vec};
+
+/// SS58 address of the well-known `Alice` sr25519 account.
+const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+/// SS58 address of the well-known `Bob` sr25519 account.
+const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+/// SS58 address of the well-known `Charlie` sr25519 account.
+const CHARLIE: &str = "5FLSigC9HGRKVhB9FiEo4Y3koPsNmBmLJbpXg2mp1hXcS59Y";
+
+/// The identifier of the single-authority staging preset.
+pub const STAGING_RUNTIME_PRESET: &str = "staging";
+
+fn genesis_patch(authorities: &[&str], endowed_accounts: &[&str]) -> Value {
+	json!({
+		"babe": {
+			"authorities": authorities.iter().map(|a| json!([a, 1])).collect::<Vec<_>>(),
+			"epochConfig": TEST_RUNTIME_BABE_EPOCH_CONFIGURATION,
+		},
+		"substrateTest": {
+			"authorities": authorities,
+		},
+		"balances": {
+			"balances": endowed_accounts
+				.iter()
+				.map(|a| json!([a, 1000 * currency::DOLLARS]))
+				.collect::<Vec<_>>(),
+		},
+	})
+}
+
+/// Returns the JSON patch of the preset with the given `id`.
+pub fn get_preset(id: &PresetId) -> Option<Vec<u8>> {
+	let patch = match id.as_ref() {
+		id if id == DEV_RUNTIME_PRESET.as_bytes() =>
+			genesis_patch(&[ALICE, BOB, CHARLIE], &[ALICE, BOB, CHARLIE]),
+		id if id == STAGING_RUNTIME_PRESET.as_bytes() => genesis_patch(&[ALICE], &[ALICE]),
+		_ => return None,
+	};
+
+	Some(
+		serde_json::to_string(&patch)
+			.expect("serialization to json is expected to work. qed.")
+			.into_bytes(),
+	)
+}
+
+/// Returns the identifiers of all presets.
+pub fn preset_names() -> Vec<PresetId> {
+	vec![PresetId::from(DEV_RUNTIME_PRESET), PresetId::from(STAGING_RUNTIME_PRESET)]
+}
@@ -21,6 +21,7 @@
 
 #[cfg(feature = "std")]
 pub mod extrinsic;
+pub mod genesis_config_presets;
 #[cfg(feature = "std")]
 pub mod genesismap;
 pub mod substrate_test_pallet;
@@ -29,7 +30,7 @@ use codec::{Decode, Encode};
 use frame_support::{
 	construct_runtime,
 	dispatch::DispatchClass,
-	genesis_builder_helper::{build_config, create_default_config},
+	genesis_builder_helper::{build_config, create_default_config, get_preset},
 	parameter_types,
 	traits::{ConstU32, ConstU64},
 	weights::{
@@ -348,6 +349,7 @@ impl frame_system::pallet::Config for Runtime {
 	type BlockLength = ();
 	type RuntimeOrigin = RuntimeOrigin;
 	type RuntimeCall = RuntimeCall;
+	type RuntimeTask = RuntimeTask;
 	type Nonce = Nonce;
 	type Hash = H256;
 	type Hashing = Hashing;
@@ -469,6 +471,19 @@ pub const TEST_RUNTIME_BABE_EPOCH_CONFIGURATION: BabeEpochConfiguration = BabeEp
 	allowed_slots: AllowedSlots::PrimaryAndSecondaryPlainSlots,
 };
 
+/// Epoch reported by the Sassafras runtime API, which has no on-chain state behind it.
+fn test_sassafras_epoch(index: u64) -> sp_consensus_sassafras::Epoch {
+	let length = EpochDuration::get();
+	sp_consensus_sassafras::Epoch {
+		index,
+		start: (index * length).into(),
+		length: length as u32,
+		randomness: Default::default(),
+		authorities: Vec::new(),
+		config: Default::default(),
+	}
+}
+
 impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
 		fn version() -> RuntimeVersion {
@@ -670,6 +685,52 @@ impl_runtime_apis! {
 		}
 	}
 
+	// Sassafras is not part of the runtime, the API only allows to drive the client side of
+	// the protocol in tests: no tickets are ever assigned and the epochs are provided by the
+	// client configuration.
+	impl sp_consensus_sassafras::SassafrasApi<Block> for Runtime {
+		fn ring_context() -> Option<sp_consensus_sassafras::vrf::RingContext> {
+			None
+		}
+
+		fn submit_tickets_unsigned_extrinsic(
+			_tickets: Vec<sp_consensus_sassafras::TicketEnvelope>,
+		) -> bool {
+			false
+		}
+
+		fn slot_ticket_id(_slot: sp_consensus_sassafras::Slot) -> Option<sp_consensus_sassafras::TicketId> {
+			None
+		}
+
+		fn slot_ticket(
+			_slot: sp_consensus_sassafras::Slot,
+		) -> Option<(sp_consensus_sassafras::TicketId, sp_consensus_sassafras::TicketBody)> {
+			None
+		}
+
+		fn current_epoch() -> sp_consensus_sassafras::Epoch {
+			test_sassafras_epoch(0)
+		}
+
+		fn next_epoch() -> sp_consensus_sassafras::Epoch {
+			test_sassafras_epoch(1)
+		}
+
+		fn generate_key_ownership_proof(
+			_authority_id: sp_consensus_sassafras::AuthorityId,
+		) -> Option<sp_consensus_sassafras::OpaqueKeyOwnershipProof> {
+			None
+		}
+
+		fn submit_report_equivocation_unsigned_extrinsic(
+			_equivocation_proof: sp_consensus_sassafras::EquivocationProof<<Block as BlockT>::Header>,
+			_key_owner_proof: sp_consensus_sassafras::OpaqueKeyOwnershipProof,
+		) -> bool {
+			false
+		}
+	}
+
 	impl sp_offchain::OffchainWorkerApi<Block> for Runtime {
 		fn offchain_worker(header: &<Block as BlockT>::Header) {
 			let ext = Extrinsic::new_unsigned(
@@ -721,6 +782,7 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[api_version(2)]
 	impl sp_genesis_builder::GenesisBuilder<Block> for Runtime {
 		fn create_default_config() -> Vec<u8> {
 			create_default_config::<RuntimeGenesisConfig>()
@@ -729,6 +791,14 @@ impl_runtime_apis! {
 		fn build_config(config: Vec<u8>) -> sp_genesis_builder::Result {
 			build_config::<RuntimeGenesisConfig>(config)
 		}
+
+		fn get_preset(id: Option<sp_genesis_builder::PresetId>) -> Option<Vec<u8>> {
+			get_preset::<RuntimeGenesisConfig>(&id, genesis_config_presets::get_preset)
+		}
+
+		fn preset_names() -> Vec<sp_genesis_builder::PresetId> {
+			genesis_config_presets::preset_names()
+		}
 	}
 }
 